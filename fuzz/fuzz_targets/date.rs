@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taskwarrior3lib::date::relative::parse_duration;
+use taskwarrior3lib::date::{DateParser, DateParsing};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let parser = DateParser::new();
+    let _ = parser.parse_date(input);
+    let _ = parser.calculate_relative_date(chrono::Utc::now(), input);
+    let _ = parse_duration(input);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taskwarrior3lib::query::filters::parse_project_from_filter;
+use taskwarrior3lib::query::natural;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let parsed = natural::parse(input);
+    let _ = parsed.query.to_filter_string();
+    let _ = parse_project_from_filter(input);
+});
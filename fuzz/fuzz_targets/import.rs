@@ -0,0 +1,30 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taskwarrior3lib::config::ParseMode;
+use taskwarrior3lib::io::import::{import_tasks_from_string, ImportConfig, ImportFormat};
+use taskwarrior3lib::io::taskwarrior2;
+use taskwarrior3lib::task::UdaTypes;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+    for format in [
+        ImportFormat::Auto,
+        ImportFormat::Json,
+        ImportFormat::Csv,
+        ImportFormat::TaskwarriorLegacy,
+        ImportFormat::Taskwarrior2Data,
+    ] {
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let config = ImportConfig {
+                format: format.clone(),
+                mode,
+                ..Default::default()
+            };
+            let _ = import_tasks_from_string(content, Some(config));
+        }
+    }
+    let _ = taskwarrior2::parse_undo_data(content, &UdaTypes::new());
+});
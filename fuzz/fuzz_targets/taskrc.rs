@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taskwarrior3lib::config::{Configuration, ParseMode};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = Configuration::from_rc_str(content, ParseMode::Strict);
+    if let Ok(config) = Configuration::from_rc_str(content, ParseMode::Lenient) {
+        let _ = config.validate();
+        let _ = config.environment();
+    }
+});
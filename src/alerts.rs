@@ -0,0 +1,321 @@
+//! Due-date alerts
+//!
+//! [`scan`] reports what changed since the previous scan: tasks that became
+//! overdue, tasks that entered the "due soon" window, and waiting tasks whose
+//! wait date passed. The time of the previous scan is kept in a small cursor
+//! file so each alert is raised once, even across restarts. It is meant to be
+//! called from a timer or the daemon, with the alerts handed to notification
+//! code:
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::alerts::{self, AlertPolicy};
+//! use taskwarrior3lib::storage::FileStorageBackend;
+//! use taskwarrior3lib::Configuration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = Configuration::default();
+//! let storage = FileStorageBackend::with_path(config.data_dir.clone());
+//! let policy = AlertPolicy::from_config(&config)?;
+//! for alert in alerts::scan(&storage, &policy)? {
+//!     println!("{}", alert.message());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Config keys: `alerts.due.window` (default `1h`) and `alerts.cursor`
+//! (default `<data dir>/alerts.cursor.json`).
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::clock;
+use crate::config::Configuration;
+use crate::date::relative::parse_duration;
+use crate::error::{ConfigError, TaskError};
+use crate::storage::StorageBackend;
+use crate::task::{Task, TaskStatus};
+
+/// Which alerts to raise and where to keep the scan cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertPolicy {
+    /// How far ahead a due date counts as "soon"; None disables due-soon alerts
+    pub due_window: Option<Duration>,
+    pub overdue: bool,
+    pub actionable: bool,
+    /// File holding the time of the last scan; None keeps no state, so every
+    /// scan behaves like the first
+    pub cursor_path: Option<PathBuf>,
+}
+
+impl Default for AlertPolicy {
+    fn default() -> Self {
+        Self {
+            due_window: Some(Duration::hours(1)),
+            overdue: true,
+            actionable: true,
+            cursor_path: None,
+        }
+    }
+}
+
+impl AlertPolicy {
+    /// Read `alerts.due.window` and `alerts.cursor`; the cursor defaults to
+    /// `alerts.cursor.json` in the data directory
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let due_window = match config.get("alerts.due.window") {
+            Some(raw) if raw.trim().eq_ignore_ascii_case("off") => None,
+            Some(raw) => Some(parse_duration(raw).map_err(|_| ConfigError::InvalidValue {
+                key: "alerts.due.window".to_string(),
+                value: raw.to_string(),
+                expected: "a duration such as '30min' or '1h', or 'off'".to_string(),
+            })?),
+            None => Self::default().due_window,
+        };
+        let cursor_path = config
+            .get("alerts.cursor")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| config.data_dir.join("alerts.cursor.json"));
+
+        Ok(Self {
+            due_window,
+            cursor_path: Some(cursor_path),
+            ..Self::default()
+        })
+    }
+}
+
+/// Why an alert was raised
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertKind {
+    /// The due date passed
+    Overdue,
+    /// The due date is within the policy's window
+    DueSoon,
+    /// The wait date passed and the task is actionable again
+    Actionable,
+}
+
+/// A task that needs attention
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub task: Task,
+    /// The due or wait time that triggered the alert
+    pub at: DateTime<Utc>,
+}
+
+impl Alert {
+    /// Short human-readable description
+    pub fn message(&self) -> String {
+        match self.kind {
+            AlertKind::Overdue => format!("Overdue: {}", self.task.description),
+            AlertKind::DueSoon => format!("Due soon: {}", self.task.description),
+            AlertKind::Actionable => format!("Ready: {}", self.task.description),
+        }
+    }
+}
+
+/// Scan state persisted between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertCursor {
+    /// When the last scan ran; None before the first scan
+    pub last_scan: Option<DateTime<Utc>>,
+}
+
+impl AlertCursor {
+    /// Load the cursor, treating a missing file as a first scan
+    pub fn load(path: &std::path::Path) -> Result<Self, TaskError> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the cursor
+    pub fn save(&self, path: &std::path::Path) -> Result<(), TaskError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Scan storage for new alerts, advancing the persisted cursor
+pub fn scan(storage: &dyn StorageBackend, policy: &AlertPolicy) -> Result<Vec<Alert>, TaskError> {
+    scan_at(storage, policy, clock::now())
+}
+
+/// [`scan`] relative to `now`
+pub fn scan_at(
+    storage: &dyn StorageBackend,
+    policy: &AlertPolicy,
+    now: DateTime<Utc>,
+) -> Result<Vec<Alert>, TaskError> {
+    let mut cursor = match &policy.cursor_path {
+        Some(path) => AlertCursor::load(path)?,
+        None => AlertCursor::default(),
+    };
+    let tasks = storage.load_all_tasks()?;
+    let alerts = scan_tasks(&tasks, policy, &mut cursor, now);
+    if let Some(path) = &policy.cursor_path {
+        cursor.save(path)?;
+    }
+    Ok(alerts)
+}
+
+/// Compute alerts for `tasks` since `cursor.last_scan` and move the cursor
+/// to `now`. On the first scan every overdue, due-soon and actionable task
+/// is reported. Alerts are ordered by trigger time.
+pub fn scan_tasks(
+    tasks: &[Task],
+    policy: &AlertPolicy,
+    cursor: &mut AlertCursor,
+    now: DateTime<Utc>,
+) -> Vec<Alert> {
+    let since = cursor.last_scan;
+    // Whether `at` passed between the previous scan and now
+    let crossed = |at: DateTime<Utc>| since.is_none_or(|s| at > s) && at <= now;
+    let mut alerts = Vec::new();
+
+    for task in tasks {
+        let open = matches!(task.status, TaskStatus::Pending | TaskStatus::Waiting);
+        if !open {
+            continue;
+        }
+
+        if let Some(wait) = task.wait {
+            if policy.actionable && crossed(wait) {
+                alerts.push(Alert {
+                    kind: AlertKind::Actionable,
+                    task: task.clone(),
+                    at: wait,
+                });
+            }
+        }
+
+        let Some(due) = task.due else {
+            continue;
+        };
+        if due <= now {
+            if policy.overdue && crossed(due) {
+                alerts.push(Alert {
+                    kind: AlertKind::Overdue,
+                    task: task.clone(),
+                    at: due,
+                });
+            }
+        } else if let Some(window) = policy.due_window {
+            // Entered the window since the last scan; a window reaching
+            // past the earliest representable time opened long ago
+            let opened = due.checked_sub_signed(window).unwrap_or(DateTime::<Utc>::MIN_UTC);
+            if crossed(opened) {
+                alerts.push(Alert {
+                    kind: AlertKind::DueSoon,
+                    task: task.clone(),
+                    at: due,
+                });
+            }
+        }
+    }
+
+    cursor.last_scan = Some(now);
+    alerts.sort_by_key(|a| a.at);
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorageBackend;
+    use tempfile::TempDir;
+
+    fn task(description: &str) -> Task {
+        Task::new(description.to_string())
+    }
+
+    #[test]
+    fn test_first_scan_reports_current_state() {
+        let now = Utc::now();
+        let mut overdue = task("overdue");
+        overdue.due = Some(now - Duration::days(3));
+        let mut soon = task("soon");
+        soon.due = Some(now + Duration::minutes(30));
+        let mut later = task("later");
+        later.due = Some(now + Duration::days(3));
+        let mut done = task("done");
+        done.status = TaskStatus::Completed;
+        done.due = Some(now - Duration::days(1));
+
+        let mut cursor = AlertCursor::default();
+        let alerts = scan_tasks(&[overdue, soon, later, done], &AlertPolicy::default(), &mut cursor, now);
+        let kinds: Vec<_> = alerts.iter().map(|a| (a.kind, a.task.description.as_str())).collect();
+        assert_eq!(kinds, vec![(AlertKind::Overdue, "overdue"), (AlertKind::DueSoon, "soon")]);
+        assert_eq!(cursor.last_scan, Some(now));
+    }
+
+    #[test]
+    fn test_later_scans_report_only_transitions() {
+        let start = Utc::now();
+        let mut due = task("report");
+        due.due = Some(start + Duration::hours(2));
+        let mut waiting = task("waiting");
+        waiting.status = TaskStatus::Waiting;
+        waiting.wait = Some(start + Duration::minutes(10));
+        let tasks = [due, waiting];
+        let policy = AlertPolicy::default();
+
+        let mut cursor = AlertCursor::default();
+        assert!(scan_tasks(&tasks, &policy, &mut cursor, start).is_empty());
+
+        let alerts = scan_tasks(&tasks, &policy, &mut cursor, start + Duration::minutes(70));
+        let kinds: Vec<_> = alerts.iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, vec![AlertKind::Actionable, AlertKind::DueSoon]);
+
+        assert!(scan_tasks(&tasks, &policy, &mut cursor, start + Duration::minutes(80)).is_empty());
+
+        let alerts = scan_tasks(&tasks, &policy, &mut cursor, start + Duration::hours(3));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::Overdue);
+    }
+
+    #[test]
+    fn test_scan_persists_cursor() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+        storage.initialize().unwrap();
+        let mut overdue = task("overdue");
+        overdue.due = Some(Utc::now() - Duration::hours(1));
+        storage.save_task(&overdue).unwrap();
+
+        let config = Configuration {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let policy = AlertPolicy::from_config(&config).unwrap();
+
+        assert_eq!(scan(&storage, &policy).unwrap().len(), 1);
+        assert!(scan(&storage, &policy).unwrap().is_empty());
+        assert!(temp_dir.path().join("alerts.cursor.json").exists());
+    }
+
+    #[test]
+    fn test_huge_due_window() {
+        let mut config = Configuration::default();
+        config.set("alerts.due.window", "999999999999999d");
+        assert!(AlertPolicy::from_config(&config).is_err());
+
+        // In range for a duration, but not when subtracted from a date
+        config.set("alerts.due.window", "99999999999d");
+        let policy = AlertPolicy::from_config(&config).unwrap();
+        let now = Utc::now();
+        let mut later = task("later");
+        later.due = Some(now + Duration::days(300));
+        let alerts = scan_tasks(&[later], &policy, &mut AlertCursor::default(), now);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, AlertKind::DueSoon);
+    }
+}
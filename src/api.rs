@@ -0,0 +1,51 @@
+//! The stable public API
+//!
+//! Everything in this crate is `pub`, but not everything is meant to be
+//! depended on: the TaskChampion operation mapping, the replica actor and
+//! similar plumbing change whenever the storage layer is reworked. This
+//! module names the part that follows semver. Items re-exported here only
+//! change incompatibly with a major version bump; anything reached through
+//! another path (and everything marked `#[doc(hidden)]`) may change in any
+//! release.
+//!
+//! For crossing process or language boundaries, [`dto`] holds flat serde
+//! shapes whose JSON form is stable as well, independent of [`Task`]'s
+//! Taskwarrior-compatible serialization.
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::api::{
+//!     Configuration, DefaultHookSystem, DefaultTaskManager, FileStorageBackend, TaskManager,
+//! };
+//! use taskwarrior3lib::api::dto::TaskDto;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let storage = Box::new(FileStorageBackend::new());
+//! let mut manager =
+//!     DefaultTaskManager::new(Configuration::default(), storage, Box::new(DefaultHookSystem::new()))?;
+//! let task = manager.add_task("Buy milk".to_string())?;
+//! println!("{}", serde_json::to_string(&TaskDto::from(&task))?);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod dto;
+
+pub use crate::config::{Configuration, ConfigurationBuilder, ConfigurationProvider};
+pub use crate::date::{DateParser, DateSynonym};
+pub use crate::error::{
+    ConfigError, DateError, ErrorContext, QueryError, StorageError, SyncError, TaskError,
+    ValidationError,
+};
+pub use crate::hooks::{DefaultHookSystem, HookSystem};
+pub use crate::query::{
+    DateFilter, PriorityFilter, ProjectFilter, SortCriteria, SortField, TagFilter, TaskQuery,
+    TaskQueryBuilder, TaskQueryBuilderImpl,
+};
+pub use crate::storage::{FileStorageBackend, StorageBackend, TaskChampionStorageBackend};
+pub use crate::task::manager::{
+    AddOptions, DefaultTaskManager, SyncResult, TaskUpdate, ValidationReport,
+};
+pub use crate::task::model::UdaValue;
+pub use crate::task::{
+    Annotation, NewTask, Priority, Task, TaskManager, TaskManagerBuilder, TaskStatus,
+};
@@ -0,0 +1,274 @@
+//! Serde data transfer objects
+//!
+//! Flat, stable shapes for crossing process or language boundaries. They
+//! deliberately avoid the Taskwarrior on-disk format that [`Task`]'s own
+//! serde implementation produces.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+use crate::query::{ProjectFilter, TagFilter, TaskQuery};
+use crate::task::manager::{SyncResult, TaskUpdate};
+use crate::task::{Task, TaskStatus};
+
+/// A task as seen by clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskDto {
+    pub uuid: Uuid,
+    pub description: String,
+    pub status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<DateTime<Utc>>,
+    pub entry: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+    /// Version to send back as `if_match` on `modify`
+    #[serde(default)]
+    pub etag: String,
+}
+
+impl From<&Task> for TaskDto {
+    fn from(task: &Task) -> Self {
+        Self {
+            uuid: task.id,
+            description: task.description.clone(),
+            status: task.status,
+            project: task.project.clone(),
+            priority: task.priority_code().map(str::to_string),
+            tags: task.tags.iter().cloned().collect(),
+            due: task.due,
+            entry: task.entry,
+            modified: task.modified,
+            end: task.end,
+            annotations: task.annotations.iter().map(|a| a.description.clone()).collect(),
+            etag: task.etag(),
+        }
+    }
+}
+
+/// Parameters for `add`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AddTaskParams {
+    pub description: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub due: Option<DateTime<Utc>>,
+}
+
+impl AddTaskParams {
+    /// Fields beyond the description, as an update applied after creation
+    pub fn to_update(&self) -> TaskUpdate {
+        let mut update = TaskUpdate::new();
+        update.project = self.project.clone();
+        update.due = self.due;
+        if let Some(priority) = &self.priority {
+            update = update.priority_code(priority.clone());
+        }
+        for tag in &self.tags {
+            update = update.add_tag(tag.clone());
+        }
+        update
+    }
+}
+
+/// Parameters for `modify`; absent fields are left unchanged
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModifyTaskParams {
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    /// Reject the change if the task's etag is no longer this
+    #[serde(default)]
+    pub if_match: Option<String>,
+}
+
+impl ModifyTaskParams {
+    /// Build the update for `current`, resolving tag additions and removals
+    /// against its existing tags
+    pub fn to_update(&self, current: &Task) -> TaskUpdate {
+        let mut update = TaskUpdate::new();
+        update.expected_etag = self.if_match.clone();
+        update.description = self.description.clone();
+        update.project = self.project.clone();
+        update.due = self.due;
+        if let Some(priority) = &self.priority {
+            update = update.priority_code(priority.clone());
+        }
+        if !self.add_tags.is_empty() || !self.remove_tags.is_empty() {
+            let mut tags = current.tags.clone();
+            tags.extend(self.add_tags.iter().cloned());
+            for tag in &self.remove_tags {
+                tags.remove(tag);
+            }
+            update.tags = Some(tags);
+        }
+        update
+    }
+}
+
+/// Parameters for `query`; all filters are optional
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryParams {
+    #[serde(default)]
+    pub status: Option<TaskStatus>,
+    /// Project, including subprojects
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Tasks must have all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl From<&QueryParams> for TaskQuery {
+    fn from(params: &QueryParams) -> Self {
+        TaskQuery {
+            status: params.status,
+            project_filter: params.project.clone().map(ProjectFilter::Hierarchy),
+            tag_filter: (!params.tags.is_empty()).then(|| TagFilter {
+                any_of: params.tags.iter().cloned().collect(),
+                ..Default::default()
+            }),
+            limit: params.limit,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parameters naming a single task
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UuidParams {
+    pub uuid: Uuid,
+}
+
+/// Result of `sync`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncResultDto {
+    pub tasks_pulled: usize,
+    pub tasks_pushed: usize,
+    pub conflicts_resolved: usize,
+    pub tasks_purged: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purge_error: Option<String>,
+}
+
+impl From<&SyncResult> for SyncResultDto {
+    fn from(result: &SyncResult) -> Self {
+        Self {
+            tasks_pulled: result.tasks_pulled,
+            tasks_pushed: result.tasks_pushed,
+            conflicts_resolved: result.conflicts_resolved,
+            tasks_purged: result.tasks_purged,
+            purge_error: result.purge_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_dto_roundtrip() {
+        let mut task = Task::new("Buy milk".to_string());
+        task.add_tag("errand".to_string());
+        task.set_priority_code(Some("H"));
+
+        let dto = TaskDto::from(&task);
+        let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["status"], "pending");
+        assert_eq!(json["priority"], "H");
+        assert_eq!(json["tags"][0], "errand");
+        assert!(json.get("project").is_none());
+        assert_eq!(serde_json::from_value::<TaskDto>(json).unwrap(), dto);
+    }
+
+    #[test]
+    fn test_task_dto_shape_is_stable() {
+        // Clients depend on these keys; changing them is a breaking change
+        let mut task = Task::new("Buy milk".to_string());
+        task.project = Some("Home".to_string());
+        task.add_tag("errand".to_string());
+        task.set_priority_code(Some("H"));
+        task.due = Some(task.entry);
+        task.modified = Some(task.entry);
+        task.end = Some(task.entry);
+        task.add_annotation(crate::task::Annotation::new("2%".to_string()));
+
+        let json = serde_json::to_value(TaskDto::from(&task)).unwrap();
+        let keys: BTreeSet<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+        let expected: BTreeSet<&str> = [
+            "uuid",
+            "description",
+            "status",
+            "project",
+            "priority",
+            "tags",
+            "due",
+            "entry",
+            "modified",
+            "end",
+            "annotations",
+            "etag",
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_modify_resolves_tags() {
+        let mut task = Task::new("t".to_string());
+        task.add_tag("old".to_string());
+        let params: ModifyTaskParams = serde_json::from_str(&format!(
+            r#"{{"uuid":"{}","add_tags":["new"],"remove_tags":["old"]}}"#,
+            task.id
+        ))
+        .unwrap();
+        let update = params.to_update(&task);
+        assert_eq!(update.tags.unwrap().into_iter().collect::<Vec<_>>(), vec!["new"]);
+        assert!(update.description.is_none());
+        assert!(update.expected_etag.is_none());
+    }
+
+    #[test]
+    fn test_modify_carries_etag() {
+        let task = Task::new("t".to_string());
+        let dto = TaskDto::from(&task);
+        let params = ModifyTaskParams {
+            uuid: task.id,
+            description: Some("u".to_string()),
+            if_match: Some(dto.etag.clone()),
+            ..Default::default()
+        };
+        assert_eq!(params.to_update(&task).expected_etag, Some(task.etag()));
+    }
+}
@@ -0,0 +1,388 @@
+//! proptest strategies for the core types
+//!
+//! [`Arbitrary`] implementations for [`Task`], [`TaskUpdate`] and
+//! [`TaskQuery`], so property tests can use `any::<Task>()` and friends.
+//! Generated values stay inside what the library itself produces: dates
+//! are whole seconds between 2000 and 2040, UDA names start with `x_` so
+//! they never shadow a built-in attribute, and queries carry no closure
+//! predicates.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeZone, Utc};
+use proptest::collection::{hash_map, hash_set, vec};
+use proptest::option;
+use proptest::prelude::*;
+use uuid::Uuid;
+
+use crate::query::geo::Proximity;
+use crate::query::{
+    DateFilter, FilterMode, OwnerFilter, PriorityFilter, ProjectFilter, SortCriteria, SortField,
+    TagFilter, TaskQuery,
+};
+use crate::task::model::UdaValue;
+use crate::task::manager::TaskUpdate;
+use crate::task::recurrence::RecurrencePattern;
+use crate::task::{Annotation, Priority, Task, TaskStatus};
+
+/// Seconds since the epoch of 2000-01-01 and 2040-01-01
+const DATE_RANGE: std::ops::Range<i64> = 946_684_800..2_208_988_800;
+
+/// A date between 2000 and 2040, in whole seconds
+pub fn date() -> impl Strategy<Value = DateTime<Utc>> {
+    DATE_RANGE.prop_map(|secs| Utc.timestamp_opt(secs, 0).unwrap())
+}
+
+/// A short lowercase word, usable as tag, project part or owner
+pub fn word() -> impl Strategy<Value = String> {
+    "[a-z][a-z0-9]{0,7}"
+}
+
+/// Free text with spaces and punctuation
+pub fn text() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9][A-Za-z0-9 ,.!?'-]{0,30}"
+}
+
+/// A project name with up to three levels, e.g. `home.garden`
+pub fn project() -> impl Strategy<Value = String> {
+    vec(word(), 1..=3).prop_map(|parts| parts.join("."))
+}
+
+/// A UDA name that does not collide with built-in attributes
+pub fn uda_name() -> impl Strategy<Value = String> {
+    "x_[a-z]{1,6}"
+}
+
+pub fn status() -> impl Strategy<Value = TaskStatus> {
+    prop_oneof![
+        Just(TaskStatus::Pending),
+        Just(TaskStatus::Completed),
+        Just(TaskStatus::Deleted),
+        Just(TaskStatus::Waiting),
+        Just(TaskStatus::Recurring),
+    ]
+}
+
+pub fn priority() -> impl Strategy<Value = Priority> {
+    prop_oneof![
+        Just(Priority::High),
+        Just(Priority::Medium),
+        Just(Priority::Low)
+    ]
+}
+
+pub fn uda_value() -> impl Strategy<Value = UdaValue> {
+    prop_oneof![
+        "[a-z][a-z ]{0,11}".prop_map(UdaValue::String),
+        (-100_000i32..100_000).prop_map(|n| UdaValue::Number(f64::from(n) / 4.0)),
+        date().prop_map(UdaValue::Date),
+    ]
+}
+
+fn uuid() -> impl Strategy<Value = Uuid> {
+    any::<u128>().prop_map(Uuid::from_u128)
+}
+
+fn annotation() -> impl Strategy<Value = Annotation> {
+    (text(), date()).prop_map(|(description, entry)| Annotation::with_timestamp(description, entry))
+}
+
+fn tags() -> impl Strategy<Value = HashSet<String>> {
+    hash_set(word(), 0..4)
+}
+
+impl Arbitrary for Task {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Task>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let identity = (uuid(), text(), status(), date(), option::of(date()));
+        let dates = (
+            option::of(date()),
+            option::of(date()),
+            option::of(date()),
+            option::of(date()),
+            option::of(date()),
+        );
+        let attributes = (
+            option::of(priority()),
+            option::of(project()),
+            tags(),
+            vec(annotation(), 0..3),
+            hash_set(uuid(), 0..3),
+            hash_map(uda_name(), uda_value(), 0..3),
+        );
+        let recurrence = (
+            option::of(prop_oneof![Just("daily"), Just("weekly"), Just("monthly")]),
+            option::of(uuid()),
+            option::of(word()),
+            -100i32..100,
+        );
+        (identity, dates, attributes, recurrence)
+            .prop_map(
+                |(
+                    (id, description, status, entry, modified),
+                    (due, scheduled, wait, end, start),
+                    (priority, project, tags, annotations, depends, udas),
+                    (recur, parent, owner, urgency),
+                )| {
+                    let mut task = Task::new(description);
+                    task.id = id;
+                    task.status = status;
+                    task.entry = entry;
+                    task.modified = modified;
+                    task.due = due;
+                    task.scheduled = scheduled;
+                    task.wait = wait;
+                    task.end = end;
+                    task.start = start;
+                    task.active = start.is_some();
+                    task.priority = priority;
+                    task.project = project;
+                    task.tags = tags;
+                    task.annotations = annotations;
+                    task.depends = depends;
+                    task.udas = udas;
+                    task.recur = recur.map(|r| RecurrencePattern::new(r.to_string()));
+                    task.parent = parent;
+                    task.owner = owner;
+                    task.urgency = f64::from(urgency) / 10.0;
+                    task
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for TaskUpdate {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<TaskUpdate>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(text()),
+            option::of(status()),
+            option::of(project()),
+            option::of(priority()),
+            option::of(date()),
+            option::of(tags()),
+            option::of(vec(annotation(), 0..3)),
+            option::of(hash_map(uda_name(), "[a-z]{1,8}", 0..3)),
+            option::of(hash_map(uda_name(), uda_value(), 0..3)),
+            option::of(hash_set(uda_name(), 0..2)),
+        )
+            .prop_map(
+                |(
+                    description,
+                    status,
+                    project,
+                    priority,
+                    due,
+                    tags,
+                    annotations,
+                    uda,
+                    uda_values,
+                    remove_udas,
+                )| TaskUpdate {
+                    description,
+                    status,
+                    project,
+                    priority,
+                    due,
+                    wait: None,
+                    scheduled: None,
+                    tags,
+                    annotations,
+                    uda,
+                    uda_values,
+                    remove_udas,
+                    depends: None,
+                    expected_etag: None,
+                },
+            )
+            .boxed()
+    }
+}
+
+fn project_filter() -> impl Strategy<Value = ProjectFilter> {
+    prop_oneof![
+        project().prop_map(ProjectFilter::Exact),
+        project().prop_map(ProjectFilter::Equals),
+        project().prop_map(ProjectFilter::Hierarchy),
+        vec(project(), 1..3).prop_map(ProjectFilter::Multiple),
+        Just(ProjectFilter::None),
+    ]
+}
+
+fn tag_filter() -> impl Strategy<Value = TagFilter> {
+    (tags(), tags(), tags(), option::of(0usize..4), any::<bool>()).prop_map(
+        |(any_of, all_of, none_of, min_count, untagged)| TagFilter {
+            any_of,
+            all_of,
+            none_of,
+            min_count,
+            untagged,
+        },
+    )
+}
+
+fn date_filter() -> impl Strategy<Value = DateFilter> {
+    prop_oneof![
+        3 => date_condition(),
+        1 => vec(date_condition(), 2..4).prop_map(DateFilter::All),
+    ]
+}
+
+fn date_condition() -> impl Strategy<Value = DateFilter> {
+    prop_oneof![
+        date().prop_map(DateFilter::DueBefore),
+        date().prop_map(DateFilter::DueAfter),
+        (date(), date()).prop_map(|(a, b)| DateFilter::DueBetween(a.min(b), a.max(b))),
+        date().prop_map(DateFilter::ScheduledBefore),
+        date().prop_map(DateFilter::ScheduledAfter),
+        date().prop_map(DateFilter::ModifiedBefore),
+        date().prop_map(DateFilter::ModifiedAfter),
+        date().prop_map(DateFilter::EntryBefore),
+        date().prop_map(DateFilter::EntryAfter),
+        date().prop_map(DateFilter::EndBefore),
+        date().prop_map(DateFilter::EndAfter),
+        (date(), date()).prop_map(|(a, b)| DateFilter::EndBetween(a.min(b), a.max(b))),
+    ]
+}
+
+fn sort_field() -> impl Strategy<Value = SortField> {
+    prop_oneof![
+        Just(SortField::Id),
+        Just(SortField::Description),
+        Just(SortField::Status),
+        Just(SortField::Entry),
+        Just(SortField::Modified),
+        Just(SortField::Due),
+        Just(SortField::Scheduled),
+        Just(SortField::Wait),
+        Just(SortField::Start),
+        Just(SortField::End),
+        Just(SortField::Priority),
+        Just(SortField::Project),
+        Just(SortField::Owner),
+        Just(SortField::Urgency),
+        uda_name().prop_map(SortField::Uda),
+    ]
+}
+
+impl Arbitrary for TaskQuery {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<TaskQuery>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let filters = (
+            option::of(status()),
+            option::of(project_filter()),
+            option::of(tag_filter()),
+            option::of(date_filter()),
+            option::of(prop_oneof![
+                word().prop_map(OwnerFilter::Is),
+                word().prop_map(OwnerFilter::IsNot),
+                Just(OwnerFilter::Unowned),
+            ]),
+            option::of(prop_oneof![
+                "[HML]".prop_map(PriorityFilter::Is),
+                Just(PriorityFilter::Unset),
+            ]),
+            // Quarter degrees and kilometres serialize exactly
+            option::of((-360i32..=360, -720i32..=720, 0i32..400).prop_map(
+                |(lat, lon, radius)| {
+                    Proximity::new(f64::from(lat) / 4.0, f64::from(lon) / 4.0, f64::from(radius) / 4.0)
+                        .unwrap()
+                },
+            )),
+        );
+        let paging = (
+            option::of(
+                (sort_field(), any::<bool>())
+                    .prop_map(|(field, ascending)| SortCriteria { field, ascending }),
+            ),
+            option::of(0usize..100),
+            option::of(0usize..100),
+            option::of(prop_oneof![
+                Just(FilterMode::CombineWithContext),
+                Just(FilterMode::IgnoreContext),
+            ]),
+        );
+        (filters, paging)
+            .prop_map(
+                |(
+                    (
+                        status,
+                        project_filter,
+                        tag_filter,
+                        date_filter,
+                        owner_filter,
+                        priority_filter,
+                        proximity,
+                    ),
+                    (sort, limit, offset, filter_mode),
+                )| TaskQuery {
+                    status,
+                    project_filter,
+                    tag_filter,
+                    date_filter,
+                    sort,
+                    limit,
+                    offset,
+                    filter_mode,
+                    custom_filters: Vec::new(),
+                    owner_filter,
+                    priority_filter,
+                    priority_scheme: None,
+                    collation: None,
+                    proximity,
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::operation_batch::{build_save_batch, Operation};
+    use crate::storage::serialization::{deserialize_task, serialize_task};
+
+    proptest! {
+        #[test]
+        fn task_json_round_trip(task in any::<Task>()) {
+            let json = serialize_task(&task).unwrap();
+            prop_assert_eq!(deserialize_task(&json).unwrap(), task);
+        }
+
+        #[test]
+        fn query_json_round_trip(query in any::<TaskQuery>()) {
+            let json = serde_json::to_string(&query).unwrap();
+            prop_assert_eq!(serde_json::from_str::<TaskQuery>(&json).unwrap(), query);
+        }
+
+        #[test]
+        fn update_is_idempotent(task in any::<Task>(), update in any::<TaskUpdate>()) {
+            let mut once = task;
+            update.apply_to(&mut once);
+            let mut twice = once.clone();
+            update.apply_to(&mut twice);
+            // Only the modification time moves on
+            twice.modified = once.modified;
+            prop_assert_eq!(twice, once);
+        }
+
+        #[test]
+        fn save_batches_target_the_task(old in any::<Task>(), new in any::<Task>()) {
+            prop_assert_eq!(build_save_batch(Some(&old), &old), vec![Operation::UndoPoint]);
+
+            let batch = build_save_batch(None, &new);
+            let json = serde_json::to_string(&batch).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Vec<Operation>>(&json).unwrap(), batch.clone());
+            let creates_task = matches!(&batch[1], Operation::Create { uuid, .. } if *uuid == new.id);
+            prop_assert!(creates_task);
+            prop_assert_eq!(batch.len(), 2 + new.udas.len());
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Backup archive format
+//!
+//! An archive is the magic bytes [`MAGIC`], the length of a JSON
+//! [`Manifest`] as a big-endian `u32`, the manifest, and then the contents
+//! of every file it lists, in order. Each file carries its SHA-256, so a
+//! damaged archive is noticed before anything is restored from it.
+
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::backup::backup_error;
+use crate::error::TaskError;
+use crate::storage::inspect::TASKCHAMPION_DB;
+use crate::storage::DataFormat;
+
+/// First bytes of every unencrypted archive
+pub const MAGIC: &[u8; 8] = b"TWBKARC1";
+
+/// Archive format version written by [`pack`]
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// What an archive contains
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    pub files: Vec<ManifestEntry>,
+}
+
+/// One file of an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name within the data directory
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the contents
+    pub sha256: String,
+}
+
+/// Archive the task data in `data_dir`: every file belonging to a storage
+/// format, with the TaskChampion database copied consistently even while
+/// it is in use. Backups and unrelated files are left out.
+pub fn pack(data_dir: &Path, created: DateTime<Utc>) -> Result<(Vec<u8>, Manifest), TaskError> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(data_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // The snapshot of the database already contains its WAL
+        let wal = name.starts_with(TASKCHAMPION_DB) && name != TASKCHAMPION_DB;
+        if entry.file_type()?.is_file() && DataFormat::of_file(&name).is_some() && !wal {
+            names.push(name);
+        }
+    }
+    names.sort();
+
+    let mut files = Vec::new();
+    let mut contents = Vec::new();
+    for name in names {
+        let path = data_dir.join(&name);
+        let data = if name == TASKCHAMPION_DB {
+            sqlite_snapshot(&path)?
+        } else {
+            fs::read(&path)?
+        };
+        files.push(ManifestEntry {
+            path: name,
+            size: data.len() as u64,
+            sha256: hex::encode(Sha256::digest(&data)),
+        });
+        contents.push(data);
+    }
+    let manifest = Manifest {
+        version: ARCHIVE_VERSION,
+        created,
+        files,
+    };
+
+    let header = serde_json::to_vec(&manifest)?;
+    let mut archive = MAGIC.to_vec();
+    archive.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    archive.extend_from_slice(&header);
+    for data in contents {
+        archive.extend_from_slice(&data);
+    }
+    Ok((archive, manifest))
+}
+
+/// Check `archive` and return its manifest with the contents of each file
+pub fn read(archive: &[u8]) -> Result<(Manifest, Vec<&[u8]>), TaskError> {
+    let rest = archive
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| backup_error("not a backup archive (or encrypted)"))?;
+    let (length, rest) = rest
+        .split_first_chunk::<4>()
+        .ok_or_else(|| backup_error("archive is truncated"))?;
+    let length = u32::from_be_bytes(*length) as usize;
+    if rest.len() < length {
+        return Err(backup_error("archive is truncated"));
+    }
+    let (header, mut rest) = rest.split_at(length);
+    let manifest: Manifest = serde_json::from_slice(header)?;
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(backup_error(format!(
+            "archive version {} is newer than this library supports",
+            manifest.version
+        )));
+    }
+
+    let mut contents = Vec::new();
+    for entry in &manifest.files {
+        let safe = !entry.path.is_empty()
+            && !entry.path.contains(['/', '\\'])
+            && entry.path != ".."
+            && entry.path != ".";
+        if !safe {
+            return Err(backup_error(format!("invalid file name {:?}", entry.path)));
+        }
+        let size = usize::try_from(entry.size).unwrap_or(usize::MAX);
+        if rest.len() < size {
+            return Err(backup_error(format!("{} is truncated", entry.path)));
+        }
+        let (data, tail) = rest.split_at(size);
+        if hex::encode(Sha256::digest(data)) != entry.sha256 {
+            return Err(backup_error(format!("{} fails its checksum", entry.path)));
+        }
+        contents.push(data);
+        rest = tail;
+    }
+    Ok((manifest, contents))
+}
+
+/// Check `archive` and write its files into `dest`, which must be empty or
+/// missing
+pub fn unpack(archive: &[u8], dest: &Path) -> Result<Manifest, TaskError> {
+    let (manifest, contents) = read(archive)?;
+    if dest.exists() && fs::read_dir(dest)?.next().is_some() {
+        return Err(backup_error(format!(
+            "{} is not empty; restore into a new directory",
+            dest.display()
+        )));
+    }
+    fs::create_dir_all(dest)?;
+    for (entry, data) in manifest.files.iter().zip(contents) {
+        fs::write(dest.join(&entry.path), data)?;
+    }
+    Ok(manifest)
+}
+
+// Copy a SQLite database with `VACUUM INTO`, which sees one consistent
+// state even if another connection is writing
+fn sqlite_snapshot(path: &Path) -> Result<Vec<u8>, TaskError> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let snapshot = std::env::temp_dir().join(format!(
+        "taskwarrior-backup-{}.sqlite3",
+        uuid::Uuid::new_v4()
+    ));
+    let copied = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| conn.execute("VACUUM INTO ?1", [snapshot.to_string_lossy().into_owned()]))
+        .map_err(|e| backup_error(format!("cannot copy {}: {e}", path.display())));
+    let data = copied.and_then(|_| Ok(fs::read(&snapshot)?));
+    let _ = fs::remove_file(&snapshot);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pack_and_unpack() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("tasks.json"), "[]").unwrap();
+        fs::write(dir.path().join("pending.data"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not task data").unwrap();
+        fs::create_dir(dir.path().join("backups")).unwrap();
+
+        let (archive, manifest) = pack(dir.path(), Utc::now()).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(names, ["pending.data", "tasks.json"]);
+
+        let dest = dir.path().join("restored");
+        assert_eq!(unpack(&archive, &dest).unwrap(), manifest);
+        assert_eq!(fs::read_to_string(dest.join("tasks.json")).unwrap(), "[]");
+        assert!(unpack(&archive, &dest).is_err(), "destination not empty");
+
+        let mut damaged = archive.clone();
+        *damaged.last_mut().unwrap() ^= 1;
+        assert!(read(&damaged).is_err());
+        assert!(read(&archive[..archive.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_sqlite_snapshot() {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join(TASKCHAMPION_DB);
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL; CREATE TABLE t (x); INSERT INTO t VALUES (42);",
+        )
+        .unwrap();
+
+        let (archive, manifest) = pack(dir.path(), Utc::now()).unwrap();
+        assert_eq!(manifest.files.len(), 1, "WAL files are not archived");
+        let dest = dir.path().join("restored");
+        unpack(&archive, &dest).unwrap();
+        let copy = rusqlite::Connection::open(dest.join(TASKCHAMPION_DB)).unwrap();
+        let x: i64 = copy
+            .query_row("SELECT x FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(x, 42);
+    }
+}
@@ -0,0 +1,183 @@
+//! Passphrase encryption of backup archives (`backup-encryption` feature)
+//!
+//! - PBKDF2-HMAC-SHA256 (RFC 8018) turns the passphrase and a random salt
+//!   into a 256-bit key, and
+//! - ChaCha20-Poly1305 (RFC 8439) encrypts the archive under a random
+//!   nonce, authenticating the header as associated data, so a wrong
+//!   passphrase or a damaged archive is caught before anything is
+//!   decrypted.
+//!
+//! ```text
+//! "TWBKENC2" | iterations: u32 BE | salt: 16 | nonce: 12 | ciphertext | tag: 16
+//! ```
+//!
+//! Both primitives come from the RustCrypto crates. Without the feature
+//! [`is_encrypted`] still recognises encrypted archives, but sealing and
+//! opening them fail.
+
+use crate::backup::backup_error;
+use crate::error::TaskError;
+
+/// First bytes of every encrypted archive
+pub const MAGIC: &[u8; 8] = b"TWBKENC2";
+
+/// PBKDF2 iterations for new archives; stored in each archive, so it can
+/// be raised without breaking old ones. Unit tests use fewer to stay fast
+/// in debug builds.
+pub const KDF_ITERATIONS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+
+/// Whether `data` was produced by [`seal`]
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+#[cfg(feature = "backup-encryption")]
+pub use sealing::{open, seal};
+
+/// Encrypt `plain` with `passphrase`; needs the `backup-encryption` feature
+#[cfg(not(feature = "backup-encryption"))]
+pub fn seal(_plain: &[u8], _passphrase: &str) -> Result<Vec<u8>, TaskError> {
+    Err(disabled())
+}
+
+/// Check and decrypt an archive; needs the `backup-encryption` feature
+#[cfg(not(feature = "backup-encryption"))]
+pub fn open(_sealed: &[u8], _passphrase: &str) -> Result<Vec<u8>, TaskError> {
+    Err(disabled())
+}
+
+#[cfg(not(feature = "backup-encryption"))]
+fn disabled() -> TaskError {
+    backup_error("encrypted backups need the `backup-encryption` feature")
+}
+
+#[cfg(feature = "backup-encryption")]
+mod sealing {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+    use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+    use super::{backup_error, TaskError, KDF_ITERATIONS, MAGIC};
+
+    // Refuse archives claiming more, which would take hours to open
+    const MAX_KDF_ITERATIONS: u32 = 10_000_000;
+
+    pub(super) const SALT_LEN: usize = 16;
+    pub(super) const NONCE_LEN: usize = 12;
+    pub(super) const TAG_LEN: usize = 16;
+    pub(super) const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+
+    /// Encrypt `plain` with `passphrase`
+    pub fn seal(plain: &[u8], passphrase: &str) -> Result<Vec<u8>, TaskError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        seal_with(plain, passphrase, KDF_ITERATIONS, salt, nonce.into())
+    }
+
+    pub(super) fn seal_with(
+        plain: &[u8],
+        passphrase: &str,
+        iterations: u32,
+        salt: [u8; SALT_LEN],
+        nonce: [u8; NONCE_LEN],
+    ) -> Result<Vec<u8>, TaskError> {
+        let mut sealed = MAGIC.to_vec();
+        sealed.extend_from_slice(&iterations.to_be_bytes());
+        sealed.extend_from_slice(&salt);
+        sealed.extend_from_slice(&nonce);
+        let ciphertext = cipher(passphrase, &salt, iterations)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plain,
+                    aad: &sealed,
+                },
+            )
+            .map_err(|_| backup_error("encrypting the archive failed"))?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Check and decrypt an archive sealed with `passphrase`
+    pub fn open(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, TaskError> {
+        if !super::is_encrypted(sealed) {
+            return Err(backup_error("archive is not encrypted"));
+        }
+        if sealed.len() < HEADER_LEN + TAG_LEN {
+            return Err(backup_error("encrypted archive is truncated"));
+        }
+        let (header, ciphertext) = sealed.split_at(HEADER_LEN);
+        let iterations = u32::from_be_bytes(header[8..12].try_into().unwrap());
+        if iterations == 0 || iterations > MAX_KDF_ITERATIONS {
+            return Err(backup_error(format!(
+                "encrypted archive claims {iterations} key derivation iterations"
+            )));
+        }
+        let salt = &header[12..12 + SALT_LEN];
+        let nonce = &header[12 + SALT_LEN..];
+        cipher(passphrase, salt, iterations)
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: header,
+                },
+            )
+            .map_err(|_| {
+                backup_error("wrong passphrase or damaged archive (authentication failed)")
+            })
+    }
+
+    fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+        let key = derive_key(passphrase.as_bytes(), salt, iterations);
+        ChaCha20Poly1305::new(Key::from_slice(&key))
+    }
+
+    pub(super) fn derive_key(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, &mut key);
+        key
+    }
+}
+
+#[cfg(all(test, feature = "backup-encryption"))]
+mod tests {
+    use super::sealing::*;
+    use super::*;
+
+    #[test]
+    fn test_pbkdf2_known_vectors() {
+        assert_eq!(
+            hex::encode(derive_key(b"password", b"salt", 1)),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        assert_eq!(
+            hex::encode(derive_key(b"password", b"salt", 4096)),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let plain = b"a backup archive that spans more than one cipher block";
+        let sealed = seal_with(plain, "hunter2", 10, [1; 16], [2; 12]).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(sealed.len(), HEADER_LEN + plain.len() + TAG_LEN);
+        assert!(!sealed.windows(6).any(|w| w == b"backup"));
+        assert_eq!(open(&sealed, "hunter2").unwrap(), plain);
+        assert!(open(&sealed, "hunter3").is_err());
+
+        let mut tampered = sealed.clone();
+        tampered[HEADER_LEN] ^= 1;
+        assert!(open(&tampered, "hunter2").is_err());
+        let mut header = sealed.clone();
+        header[11] ^= 1;
+        assert!(open(&header, "hunter2").is_err());
+        assert!(open(&sealed[..HEADER_LEN], "hunter2").is_err());
+
+        let fresh = seal(plain, "hunter2").unwrap();
+        assert_ne!(fresh[12..HEADER_LEN], seal(plain, "hunter2").unwrap()[12..HEADER_LEN]);
+        assert_eq!(open(&fresh, "hunter2").unwrap(), plain);
+    }
+}
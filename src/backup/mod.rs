@@ -0,0 +1,523 @@
+//! Off-site backups of the task data
+//!
+//! The file backend keeps copies of `tasks.json` next to it, which does not
+//! help when the disk dies or a corrupted database has already been synced
+//! everywhere. A [`BackupRunner`] packs the data directory into a
+//! checksummed archive ([`archive`]), optionally encrypts it with a
+//! passphrase ([`crypto`]), uploads it to a [`BackupTarget`] and prunes old
+//! backups. [`BackupRunner::verify_restore`] downloads the latest backup,
+//! restores it into a scratch directory and counts its tasks, so a backup
+//! that cannot be restored is found before it is needed.
+//!
+//! Targets:
+//!
+//! | Target            | `backup.target`            | Feature         |
+//! |-------------------|----------------------------|-----------------|
+//! | [`LocalDirTarget`] | `/mnt/usb/tasks`          | always          |
+//! | `WebDavTarget`    | `webdav:https://nas/dav/tasks/` | `backup-webdav` |
+//! | `S3Target`        | `s3://bucket/prefix`       | `backup-s3`     |
+//!
+//! ```text
+//! backup.target=s3://backups/laptop
+//! backup.s3.endpoint=http://minio.local:9000   # default: AWS in backup.s3.region
+//! backup.s3.region=eu-west-1                   # default: us-east-1
+//! backup.s3.access.key=…
+//! backup.s3.secret.key=…
+//! backup.webdav.user=…                         # Basic auth, https:// only
+//! backup.webdav.password=…
+//! backup.keep=14                               # backups to keep (default 7)
+//! backup.passphrase=…                          # encrypt archives
+//! ```
+//!
+//! Remote targets speak `https://` through rustls, checking servers
+//! against the Mozilla root certificates, and plain `http://` for stores
+//! on a trusted network; WebDAV passwords are only ever sent over
+//! `https://`. Encryption needs the `backup-encryption` feature. The
+//! daemon runs backups on a schedule with
+//! [`ServiceConfig::backup_every`](crate::daemon::ServiceConfig::backup_every).
+
+pub mod archive;
+pub mod crypto;
+#[cfg(feature = "backup-s3")]
+pub mod s3;
+#[cfg(feature = "backup-webdav")]
+pub mod webdav;
+
+pub use archive::{Manifest, ManifestEntry};
+#[cfg(feature = "backup-s3")]
+pub use s3::S3Target;
+#[cfg(feature = "backup-webdav")]
+pub use webdav::WebDavTarget;
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::config::Configuration;
+use crate::error::{ConfigError, StorageError, TaskError};
+use crate::storage::inspect::{inspect, FormatReport};
+
+/// How long a remote target may stay silent before a request fails
+#[cfg(any(feature = "backup-webdav", feature = "backup-s3"))]
+const REMOTE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Backups kept when `backup.keep` is not set
+pub const DEFAULT_KEEP: usize = 7;
+
+/// File name of every backup: `taskwarrior-<UTC time>.twbk`
+pub const BACKUP_PREFIX: &str = "taskwarrior-";
+pub const BACKUP_EXTENSION: &str = ".twbk";
+
+/// Somewhere backups can be stored
+pub trait BackupTarget: fmt::Debug {
+    /// Where backups go, for messages
+    fn describe(&self) -> String;
+
+    /// Store `data` as `name`, replacing any backup of that name
+    fn put(&mut self, name: &str, data: &[u8]) -> Result<(), TaskError>;
+
+    fn get(&self, name: &str) -> Result<Vec<u8>, TaskError>;
+
+    /// Names of everything stored, in any order
+    fn list(&self) -> Result<Vec<String>, TaskError>;
+
+    fn remove(&mut self, name: &str) -> Result<(), TaskError>;
+}
+
+/// Backups as files in a local directory, e.g. a mounted USB disk or
+/// network share
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalDirTarget {
+    dir: PathBuf,
+}
+
+impl LocalDirTarget {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl BackupTarget for LocalDirTarget {
+    fn describe(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn put(&mut self, name: &str, data: &[u8]) -> Result<(), TaskError> {
+        fs::create_dir_all(&self.dir)?;
+        // Write under a temporary name so a crash never leaves half a backup
+        let partial = self.dir.join(format!(".{name}.partial"));
+        fs::write(&partial, data)?;
+        fs::rename(&partial, self.dir.join(name))?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>, TaskError> {
+        Ok(fs::read(self.dir.join(name))?)
+    }
+
+    fn list(&self) -> Result<Vec<String>, TaskError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        Ok(names)
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), TaskError> {
+        Ok(fs::remove_file(self.dir.join(name))?)
+    }
+}
+
+/// A backup made by [`BackupRunner::run`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupRecord {
+    pub name: String,
+    pub created: DateTime<Utc>,
+    /// Bytes uploaded
+    pub size: u64,
+    pub files: Vec<ManifestEntry>,
+    pub encrypted: bool,
+    /// Old backups removed to stay within `backup.keep`
+    pub pruned: Vec<String>,
+}
+
+/// The outcome of restoring a backup into a scratch directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreCheck {
+    pub name: String,
+    pub manifest: Manifest,
+    /// Tasks found in the restored data, per storage format
+    pub formats: Vec<FormatReport>,
+}
+
+impl RestoreCheck {
+    /// Whether the restored data holds task storage that could be read
+    pub fn is_ok(&self) -> bool {
+        !self.formats.is_empty() && self.formats.iter().all(|f| f.error.is_none())
+    }
+
+    /// Tasks restored, over all formats
+    pub fn task_count(&self) -> usize {
+        self.formats.iter().map(|f| f.tasks.total()).sum()
+    }
+}
+
+/// Makes, prunes, checks and restores backups of one data directory
+#[derive(Debug)]
+pub struct BackupRunner {
+    data_dir: PathBuf,
+    target: Box<dyn BackupTarget>,
+    keep: usize,
+    passphrase: Option<String>,
+}
+
+impl BackupRunner {
+    /// Back up `data_dir` to `target`, keeping [`DEFAULT_KEEP`] backups
+    pub fn new<P: Into<PathBuf>>(data_dir: P, target: Box<dyn BackupTarget>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            target,
+            keep: DEFAULT_KEEP,
+            passphrase: None,
+        }
+    }
+
+    /// The runner configured by `backup.*`, or `None` without
+    /// `backup.target`
+    pub fn from_config(config: &Configuration) -> Result<Option<Self>, TaskError> {
+        let Some(target) = config.get("backup.target") else {
+            return Ok(None);
+        };
+        let mut runner = Self::new(config.data_dir.clone(), open_target(target, config)?);
+        if let Some(raw) = config.get("backup.keep") {
+            let keep = raw.trim().parse().ok().filter(|k| *k > 0).ok_or_else(|| {
+                ConfigError::InvalidValue {
+                    key: "backup.keep".to_string(),
+                    value: raw.to_string(),
+                    expected: "a positive number of backups".to_string(),
+                }
+            })?;
+            runner = runner.keep(keep);
+        }
+        if let Some(passphrase) = config.get("backup.passphrase") {
+            if cfg!(not(feature = "backup-encryption")) {
+                return Err(ConfigError::InvalidValue {
+                    key: "backup.passphrase".to_string(),
+                    value: "…".to_string(),
+                    expected: "no passphrase (encryption needs the `backup-encryption` feature)"
+                        .to_string(),
+                }
+                .into());
+            }
+            runner = runner.passphrase(passphrase.clone());
+        }
+        Ok(Some(runner))
+    }
+
+    /// Keep the newest `keep` backups (at least one)
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    /// Encrypt new backups with `passphrase`, which is also needed to
+    /// restore them
+    pub fn passphrase<S: Into<String>>(mut self, passphrase: S) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+
+    pub fn target(&self) -> &dyn BackupTarget {
+        self.target.as_ref()
+    }
+
+    /// Back up now, then prune
+    pub fn run(&mut self) -> Result<BackupRecord, TaskError> {
+        let created = clock::now();
+        let (archive, manifest) = archive::pack(&self.data_dir, created)?;
+        let data = match &self.passphrase {
+            Some(passphrase) => crypto::seal(&archive, passphrase)?,
+            None => archive,
+        };
+        let name = format!(
+            "{BACKUP_PREFIX}{}{BACKUP_EXTENSION}",
+            created.format("%Y%m%dT%H%M%SZ")
+        );
+        self.target.put(&name, &data)?;
+        let pruned = self.prune()?;
+        Ok(BackupRecord {
+            name,
+            created,
+            size: data.len() as u64,
+            files: manifest.files,
+            encrypted: self.passphrase.is_some(),
+            pruned,
+        })
+    }
+
+    /// Names of the backups on the target, oldest first
+    pub fn backups(&self) -> Result<Vec<String>, TaskError> {
+        let mut names: Vec<String> = self
+            .target
+            .list()?
+            .into_iter()
+            .filter(|n| n.starts_with(BACKUP_PREFIX) && n.ends_with(BACKUP_EXTENSION))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Remove all but the newest `keep` backups, returning their names
+    pub fn prune(&mut self) -> Result<Vec<String>, TaskError> {
+        let backups = self.backups()?;
+        let excess = backups.len().saturating_sub(self.keep);
+        let pruned = backups[..excess].to_vec();
+        for name in &pruned {
+            self.target.remove(name)?;
+        }
+        Ok(pruned)
+    }
+
+    /// Restore the newest backup into a scratch directory and read it back,
+    /// without touching the data directory
+    pub fn verify_restore(&self) -> Result<RestoreCheck, TaskError> {
+        let latest = self
+            .backups()?
+            .pop()
+            .ok_or_else(|| backup_error(format!("no backups in {}", self.target.describe())))?;
+        self.verify_backup(&latest)
+    }
+
+    /// [`verify_restore`](Self::verify_restore) for the backup `name`
+    pub fn verify_backup(&self, name: &str) -> Result<RestoreCheck, TaskError> {
+        let scratch =
+            std::env::temp_dir().join(format!("taskwarrior-restore-{}", uuid::Uuid::new_v4()));
+        let check = self.restore_to(name, &scratch).and_then(|manifest| {
+            Ok(RestoreCheck {
+                name: name.to_string(),
+                manifest,
+                formats: inspect(&scratch)?.formats,
+            })
+        });
+        let _ = fs::remove_dir_all(&scratch);
+        check
+    }
+
+    /// Restore the backup `name` into `dest`, which must be empty or
+    /// missing; point `data.location` there to use it
+    pub fn restore_to(&self, name: &str, dest: &Path) -> Result<Manifest, TaskError> {
+        let data = self.target.get(name)?;
+        let archive = if crypto::is_encrypted(&data) {
+            let passphrase = self.passphrase.as_deref().ok_or_else(|| {
+                backup_error(format!("{name} is encrypted; set backup.passphrase"))
+            })?;
+            crypto::open(&data, passphrase)?
+        } else {
+            data
+        };
+        archive::unpack(&archive, dest)
+    }
+}
+
+/// The target described by a `backup.target` value
+fn open_target(spec: &str, config: &Configuration) -> Result<Box<dyn BackupTarget>, TaskError> {
+    let unsupported = |feature: &str| ConfigError::InvalidValue {
+        key: "backup.target".to_string(),
+        value: spec.to_string(),
+        expected: format!("a target enabled at build time (needs the `{feature}` feature)"),
+    };
+    if let Some(url) = spec.strip_prefix("webdav:") {
+        #[cfg(feature = "backup-webdav")]
+        {
+            let mut target = WebDavTarget::new(url)?;
+            if let Some(user) = config.get("backup.webdav.user") {
+                let password = config.get_or("backup.webdav.password", "");
+                target = target.basic_auth(user.clone(), password)?;
+            }
+            return Ok(Box::new(target));
+        }
+        #[cfg(not(feature = "backup-webdav"))]
+        {
+            let _ = url;
+            return Err(unsupported("backup-webdav").into());
+        }
+    }
+    if let Some(location) = spec.strip_prefix("s3://") {
+        #[cfg(feature = "backup-s3")]
+        {
+            let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+            let region = config.get_or("backup.s3.region", s3::DEFAULT_REGION);
+            let endpoint = config.get_or(
+                "backup.s3.endpoint",
+                &format!("https://s3.{region}.amazonaws.com"),
+            );
+            let key = |name: &str| {
+                config
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| ConfigError::MissingRequired {
+                        key: name.to_string(),
+                    })
+            };
+            let target = S3Target::new(
+                &endpoint,
+                bucket,
+                prefix,
+                &key("backup.s3.access.key")?,
+                &key("backup.s3.secret.key")?,
+            )?
+            .region(region);
+            return Ok(Box::new(target));
+        }
+        #[cfg(not(feature = "backup-s3"))]
+        {
+            let _ = location;
+            return Err(unsupported("backup-s3").into());
+        }
+    }
+    let _ = (config, unsupported);
+    let dir = spec.strip_prefix("dir:").unwrap_or(spec);
+    Ok(Box::new(LocalDirTarget::new(dir)))
+}
+
+pub(crate) fn backup_error<S: Into<String>>(message: S) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Backup {
+            message: message.into(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SequentialIds};
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::storage::{DataFormat, FileStorageBackend};
+    use crate::task::manager::DefaultTaskManager;
+    use crate::task::TaskManager;
+    use chrono::{Duration, TimeZone};
+    use tempfile::TempDir;
+
+    fn data_dir_with_tasks(dir: &Path) {
+        let config = ConfigurationBuilder::new().data_dir(dir).build().unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(dir));
+        let mut manager =
+            DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new())).unwrap();
+        manager.add_task("Water plants".to_string()).unwrap();
+        manager.add_task("Pay rent".to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_run_prune_and_verify() {
+        let dir = TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        data_dir_with_tasks(&data);
+        let time = FixedClock::new(Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap());
+        let _guard = clock::deterministic(time.clone(), SequentialIds::new());
+
+        let target = LocalDirTarget::new(dir.path().join("backups"));
+        let mut runner = BackupRunner::new(&data, Box::new(target)).keep(2);
+        let mut names = Vec::new();
+        for _ in 0..3 {
+            let record = runner.run().unwrap();
+            assert!(!record.encrypted);
+            assert!(record.files.iter().any(|f| f.path == "tasks.json"));
+            names.push(record.name);
+            time.advance(Duration::days(1));
+        }
+        assert_eq!(names[0], "taskwarrior-20250301T120000Z.twbk");
+        assert_eq!(runner.backups().unwrap(), names[1..]);
+
+        let check = runner.verify_restore().unwrap();
+        assert!(check.is_ok());
+        assert_eq!(check.name, names[2]);
+        assert_eq!(check.formats[0].format, DataFormat::FileBackend);
+        assert_eq!(check.task_count(), 2);
+    }
+
+    #[cfg(feature = "backup-encryption")]
+    #[test]
+    fn test_encrypted_backup_needs_passphrase() {
+        let dir = TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        data_dir_with_tasks(&data);
+        let backups = dir.path().join("backups");
+
+        let mut runner =
+            BackupRunner::new(&data, Box::new(LocalDirTarget::new(&backups))).passphrase("s3cret");
+        let record = runner.run().unwrap();
+        assert!(record.encrypted);
+        let stored = fs::read(backups.join(&record.name)).unwrap();
+        assert!(crypto::is_encrypted(&stored));
+        assert!(!stored.windows(12).any(|w| w == b"Water plants"));
+        assert_eq!(runner.verify_restore().unwrap().task_count(), 2);
+
+        let without = BackupRunner::new(&data, Box::new(LocalDirTarget::new(&backups)));
+        assert!(without.verify_restore().is_err());
+        let wrong =
+            BackupRunner::new(&data, Box::new(LocalDirTarget::new(&backups))).passphrase("guess");
+        assert!(wrong.verify_restore().is_err());
+    }
+
+    #[cfg(not(feature = "backup-encryption"))]
+    #[test]
+    fn test_passphrase_needs_encryption_feature() {
+        let dir = TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        data_dir_with_tasks(&data);
+        let backups = dir.path().join("backups");
+
+        let mut runner =
+            BackupRunner::new(&data, Box::new(LocalDirTarget::new(&backups))).passphrase("s3cret");
+        assert!(runner.run().is_err());
+        assert!(!backups.exists());
+
+        let mut config = ConfigurationBuilder::new().data_dir(&data).build().unwrap();
+        config.set("backup.target", backups.to_string_lossy());
+        config.set("backup.passphrase", "s3cret");
+        assert!(BackupRunner::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let dir = TempDir::new().unwrap();
+        let mut config = ConfigurationBuilder::new()
+            .data_dir(dir.path())
+            .build()
+            .unwrap();
+        assert!(BackupRunner::from_config(&config).unwrap().is_none());
+
+        config.set(
+            "backup.target",
+            dir.path().join("backups").to_string_lossy(),
+        );
+        config.set("backup.keep", "3");
+        let runner = BackupRunner::from_config(&config).unwrap().unwrap();
+        assert_eq!(runner.keep, 3);
+        assert_eq!(
+            runner.target().describe(),
+            dir.path().join("backups").display().to_string()
+        );
+
+        config.set("backup.keep", "0");
+        assert!(BackupRunner::from_config(&config).is_err());
+        #[cfg(not(feature = "backup-s3"))]
+        {
+            config.set("backup.keep", "3");
+            config.set("backup.target", "s3://bucket/laptop");
+            assert!(BackupRunner::from_config(&config).is_err());
+        }
+    }
+}
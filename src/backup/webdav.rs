@@ -0,0 +1,214 @@
+//! WebDAV backup target (`backup-webdav` feature)
+//!
+//! Backups are stored as files in one collection, which must exist:
+//! `PUT` uploads, `GET` downloads, `DELETE` prunes and `PROPFIND` with
+//! `Depth: 1` lists. Nextcloud, ownCloud, Apache `mod_dav` and most NAS
+//! boxes work this way.
+
+use regex::Regex;
+
+use crate::backup::{backup_error, BackupTarget, REMOTE_TIMEOUT};
+use crate::error::TaskError;
+use crate::http::{self, Url};
+
+/// A WebDAV collection holding backups
+#[derive(Clone)]
+pub struct WebDavTarget {
+    /// Collection URL, ending in `/`
+    url: String,
+    credentials: Option<(String, String)>,
+}
+
+impl std::fmt::Debug for WebDavTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebDavTarget")
+            .field("url", &self.url)
+            .field("user", &self.credentials.as_ref().map(|(user, _)| user))
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebDavTarget {
+    /// Store backups in the collection at `url` (`http://` or `https://`)
+    pub fn new<S: Into<String>>(url: S) -> Result<Self, TaskError> {
+        let mut url = url.into();
+        Url::parse(&url).map_err(backup_error)?;
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        Ok(Self {
+            url,
+            credentials: None,
+        })
+    }
+
+    /// Authenticate with HTTP Basic auth, which sends the password with
+    /// every request and so is refused unless the URL is `https://`
+    pub fn basic_auth<U: Into<String>, P: Into<String>>(
+        mut self,
+        user: U,
+        password: P,
+    ) -> Result<Self, TaskError> {
+        if !Url::parse(&self.url).map_err(backup_error)?.tls {
+            return Err(backup_error(format!(
+                "refusing to send a WebDAV password over plain HTTP; use https:// for {}",
+                self.url
+            )));
+        }
+        self.credentials = Some((user.into(), password.into()));
+        Ok(self)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        name: &str,
+        extra: &[(&str, String)],
+        body: &[u8],
+    ) -> Result<http::Response, TaskError> {
+        let url = Url::parse(&format!("{}{}", self.url, http::encode(name, false)))
+            .map_err(backup_error)?;
+        let mut headers = extra.to_vec();
+        if let Some((user, password)) = &self.credentials {
+            let token = base64(format!("{user}:{password}").as_bytes());
+            headers.push(("Authorization", format!("Basic {token}")));
+        }
+        http::request(method, &url, &headers, body, REMOTE_TIMEOUT).map_err(backup_error)
+    }
+}
+
+impl BackupTarget for WebDavTarget {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn put(&mut self, name: &str, data: &[u8]) -> Result<(), TaskError> {
+        let headers = [("Content-Type", "application/octet-stream".to_string())];
+        self.request("PUT", name, &headers, data)?
+            .success(&format!("uploading {name}"))
+            .map_err(backup_error)?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Result<Vec<u8>, TaskError> {
+        Ok(self
+            .request("GET", name, &[], &[])?
+            .success(&format!("downloading {name}"))
+            .map_err(backup_error)?
+            .body)
+    }
+
+    fn list(&self) -> Result<Vec<String>, TaskError> {
+        let body = br#"<?xml version="1.0"?><propfind xmlns="DAV:"><prop><resourcetype/></prop></propfind>"#;
+        let headers = [
+            ("Depth", "1".to_string()),
+            ("Content-Type", "application/xml".to_string()),
+        ];
+        let response = self
+            .request("PROPFIND", "", &headers, body)?
+            .success("listing backups")
+            .map_err(backup_error)?;
+        Ok(hrefs(&String::from_utf8_lossy(&response.body)))
+    }
+
+    fn remove(&mut self, name: &str) -> Result<(), TaskError> {
+        self.request("DELETE", name, &[], &[])?
+            .success(&format!("deleting {name}"))
+            .map_err(backup_error)?;
+        Ok(())
+    }
+}
+
+// Last path segment of every `href` in a multistatus response, skipping
+// the collection itself
+fn hrefs(xml: &str) -> Vec<String> {
+    let href = Regex::new(r"<(?:[A-Za-z0-9]+:)?href>([^<]*)</(?:[A-Za-z0-9]+:)?href>").unwrap();
+    href.captures_iter(xml)
+        .filter_map(|c| {
+            let path = c[1].trim();
+            let name = path.rsplit('/').next()?;
+            (!name.is_empty()).then(|| decode(name))
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            base64(b"Aladdin:open sesame"),
+            "QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"abc"), "YWJj");
+    }
+
+    #[test]
+    fn test_password_needs_https() {
+        let plain = WebDavTarget::new("http://nas.local/dav/tasks").unwrap();
+        assert!(plain.basic_auth("ann", "s3cret").is_err());
+        let secure = WebDavTarget::new("https://nas.local/dav/tasks").unwrap();
+        let secure = secure.basic_auth("ann", "s3cret").unwrap();
+        assert_eq!(secure.describe(), "https://nas.local/dav/tasks/");
+        assert!(!format!("{secure:?}").contains("s3cret"));
+    }
+
+    #[test]
+    fn test_hrefs_from_multistatus() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response><d:href>/dav/tasks/</d:href></d:response>
+  <d:response><d:href>/dav/tasks/taskwarrior-20250101T000000Z.twbk</d:href></d:response>
+  <D:response><D:href>/dav/tasks/my%20notes.txt</D:href></D:response>
+</d:multistatus>"#;
+        assert_eq!(
+            hrefs(xml),
+            ["taskwarrior-20250101T000000Z.twbk", "my notes.txt"]
+        );
+    }
+}
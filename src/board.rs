@@ -0,0 +1,431 @@
+//! Kanban boards over tasks
+//!
+//! A [`Board`] is an ordered list of columns, each selecting tasks by
+//! status, tag or UDA value (`status:completed`, `+next`, `kanban:doing`).
+//! [`Board::layout`] places every task in the first column it matches;
+//! [`Board::move_task`] rewrites a task's tags, UDAs or status through a
+//! [`TaskManager`] so that it lands in another column, refusing moves into
+//! a column that has reached its WIP limit.
+//!
+//! Boards can be defined in config:
+//!
+//! ```text
+//! board.dev.columns=todo,doing,done
+//! board.dev.column.todo.filter=kanban:todo
+//! board.dev.column.doing.filter=kanban:doing
+//! board.dev.column.doing.limit=3
+//! board.dev.column.done.filter=status:completed
+//! ```
+
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::error::{ConfigError, TaskError};
+use crate::query::TaskQuery;
+use crate::task::manager::TaskUpdate;
+use crate::task::model::UdaValue;
+use crate::task::{Task, TaskManager, TaskStatus};
+
+/// What puts a task in a column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnRule {
+    /// `status:<status>`
+    Status(TaskStatus),
+    /// `+<tag>`
+    Tag(String),
+    /// `<uda>:<value>`
+    Uda { name: String, value: String },
+}
+
+impl ColumnRule {
+    /// Parse `status:<status>`, `+<tag>` or `<uda>:<value>`
+    pub fn parse(rule: &str) -> Option<Self> {
+        let rule = rule.trim();
+        if let Some(tag) = rule.strip_prefix('+') {
+            return (!tag.is_empty()).then(|| Self::Tag(tag.to_string()));
+        }
+        let (name, value) = rule.split_once(':')?;
+        if name.is_empty() || value.is_empty() {
+            return None;
+        }
+        if name == "status" {
+            let status = match value {
+                "pending" => TaskStatus::Pending,
+                "completed" => TaskStatus::Completed,
+                "deleted" => TaskStatus::Deleted,
+                "waiting" => TaskStatus::Waiting,
+                "recurring" => TaskStatus::Recurring,
+                _ => return None,
+            };
+            return Some(Self::Status(status));
+        }
+        Some(Self::Uda {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Whether `task` satisfies the rule
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            Self::Status(status) => task.status == *status,
+            Self::Tag(tag) => task.has_tag(tag),
+            Self::Uda { name, value } => {
+                matches!(task.udas.get(name), Some(UdaValue::String(v)) if v == value)
+            }
+        }
+    }
+}
+
+/// One column of a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardColumn {
+    pub name: String,
+    pub rule: ColumnRule,
+    /// Most tasks the column may hold
+    pub wip_limit: Option<usize>,
+}
+
+impl BoardColumn {
+    pub fn new<S: Into<String>>(name: S, rule: ColumnRule) -> Self {
+        Self {
+            name: name.into(),
+            rule,
+            wip_limit: None,
+        }
+    }
+
+    /// Limit the column to `limit` tasks
+    pub fn with_wip_limit(mut self, limit: usize) -> Self {
+        self.wip_limit = Some(limit);
+        self
+    }
+}
+
+/// Tasks placed on a board
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardLayout<'a> {
+    /// Tasks per column, in board order
+    pub columns: Vec<(&'a BoardColumn, Vec<&'a Task>)>,
+    /// Tasks matching no column
+    pub unassigned: Vec<&'a Task>,
+}
+
+impl BoardLayout<'_> {
+    /// Columns holding more tasks than their WIP limit allows, with their
+    /// task counts
+    pub fn wip_violations(&self) -> Vec<(&BoardColumn, usize)> {
+        self.columns
+            .iter()
+            .filter(|(column, tasks)| column.wip_limit.is_some_and(|limit| tasks.len() > limit))
+            .map(|(column, tasks)| (*column, tasks.len()))
+            .collect()
+    }
+}
+
+/// An ordered set of columns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Board {
+    pub columns: Vec<BoardColumn>,
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column after the existing ones
+    pub fn column(mut self, column: BoardColumn) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// One column per value of `uda`, named after the value
+    pub fn by_uda(uda: &str, values: &[&str]) -> Self {
+        let columns = values
+            .iter()
+            .map(|value| {
+                BoardColumn::new(
+                    *value,
+                    ColumnRule::Uda {
+                        name: uda.to_string(),
+                        value: value.to_string(),
+                    },
+                )
+            })
+            .collect();
+        Self { columns }
+    }
+
+    /// The board defined by `board.<name>.*` settings, if any. Columns
+    /// without a `filter` select the `kanban` UDA value of their name.
+    pub fn from_config(config: &Configuration, name: &str) -> Result<Option<Self>, ConfigError> {
+        let Some(columns) = config.get(&format!("board.{name}.columns")) else {
+            return Ok(None);
+        };
+        let mut board = Self::new();
+        for column in columns.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            let filter_key = format!("board.{name}.column.{column}.filter");
+            let rule = match config.get(&filter_key) {
+                Some(filter) => {
+                    ColumnRule::parse(filter).ok_or_else(|| ConfigError::InvalidValue {
+                        key: filter_key.clone(),
+                        value: filter.clone(),
+                        expected: "'status:<status>', '+<tag>' or '<uda>:<value>'".to_string(),
+                    })?
+                }
+                None => ColumnRule::Uda {
+                    name: "kanban".to_string(),
+                    value: column.to_string(),
+                },
+            };
+            let mut column = BoardColumn::new(column, rule);
+            let limit_key = format!("board.{name}.column.{}.limit", column.name);
+            if let Some(limit) = config.get(&limit_key) {
+                let limit = limit
+                    .trim()
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue {
+                        key: limit_key,
+                        value: limit.clone(),
+                        expected: "a number of tasks".to_string(),
+                    })?;
+                column.wip_limit = Some(limit);
+            }
+            board.columns.push(column);
+        }
+        Ok(Some(board))
+    }
+
+    /// The column called `name`
+    pub fn get(&self, name: &str) -> Option<&BoardColumn> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// The first column `task` matches
+    pub fn column_of(&self, task: &Task) -> Option<&BoardColumn> {
+        self.columns.iter().find(|c| c.rule.matches(task))
+    }
+
+    /// Place each of `tasks` in the first column it matches, keeping the
+    /// order of `tasks` within a column
+    pub fn layout<'a>(&'a self, tasks: &'a [Task]) -> BoardLayout<'a> {
+        let mut columns: Vec<(&BoardColumn, Vec<&Task>)> =
+            self.columns.iter().map(|c| (c, Vec::new())).collect();
+        let mut unassigned = Vec::new();
+        for task in tasks {
+            match self.columns.iter().position(|c| c.rule.matches(task)) {
+                Some(i) => columns[i].1.push(task),
+                None => unassigned.push(task),
+            }
+        }
+        BoardLayout {
+            columns,
+            unassigned,
+        }
+    }
+
+    /// The update that moves `task` into the column called `to`: the
+    /// column's rule is applied, and tags, UDA values or statuses of other
+    /// columns the task matches are undone
+    pub fn move_update(&self, task: &Task, to: &str) -> Result<TaskUpdate, TaskError> {
+        let target = self.get(to).ok_or_else(|| TaskError::InvalidData {
+            message: format!("No board column named '{to}'"),
+        })?;
+
+        let target_uda = match &target.rule {
+            ColumnRule::Uda { name, .. } => Some(name.as_str()),
+            _ => None,
+        };
+        let target_is_status = matches!(target.rule, ColumnRule::Status(_));
+
+        let mut update = TaskUpdate::new();
+        let mut tags = task.tags.clone();
+        for column in self.columns.iter().filter(|c| c.name != target.name) {
+            let matched = column.rule.matches(task);
+            match &column.rule {
+                ColumnRule::Tag(tag) => {
+                    tags.remove(tag);
+                }
+                // Setting the target's value replaces the UDA anyway
+                ColumnRule::Uda { name, .. } if matched && target_uda != Some(name.as_str()) => {
+                    update = update.unset_uda(name.as_str());
+                }
+                ColumnRule::Status(_) if matched && !target_is_status => {
+                    update = update.status(TaskStatus::Pending);
+                }
+                _ => {}
+            }
+        }
+        match &target.rule {
+            ColumnRule::Status(status) => update = update.status(*status),
+            ColumnRule::Tag(tag) => {
+                tags.insert(tag.clone());
+            }
+            ColumnRule::Uda { name, value } => {
+                update = update.set_uda(name.as_str(), value.as_str())
+            }
+        }
+        if tags != task.tags {
+            update.tags = Some(tags);
+        }
+        Ok(update)
+    }
+
+    /// Move the task with `id` into the column called `to` through
+    /// `manager`. Fails if the column is already at its WIP limit.
+    pub fn move_task<M: TaskManager + ?Sized>(
+        &self,
+        manager: &mut M,
+        id: Uuid,
+        to: &str,
+    ) -> Result<Task, TaskError> {
+        let task = manager.get_task(id)?.ok_or(TaskError::NotFound { id })?;
+        let update = self.move_update(&task, to)?;
+        if let Some(column) = self.get(to).filter(|c| c.wip_limit.is_some()) {
+            if self.column_of(&task).is_some_and(|c| c.name == column.name) {
+                return Ok(task);
+            }
+            let tasks = manager.query_tasks(&TaskQuery::default())?;
+            let count = tasks
+                .iter()
+                .filter(|t| self.column_of(t).is_some_and(|c| c.name == column.name))
+                .count();
+            let limit = column.wip_limit.unwrap_or(usize::MAX);
+            if count >= limit {
+                return Err(TaskError::InvalidState {
+                    message: format!("Column '{}' is at its WIP limit of {limit}", column.name),
+                });
+            }
+        }
+        manager.update_task(id, update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kanban(description: &str, stage: &str) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.udas
+            .insert("kanban".to_string(), UdaValue::String(stage.to_string()));
+        task
+    }
+
+    #[test]
+    fn test_layout_and_wip_limits() {
+        let mut config = Configuration::default();
+        config.set("board.dev.columns", "todo, doing, done");
+        config.set("board.dev.column.doing.limit", "1");
+        config.set("board.dev.column.done.filter", "status:completed");
+        let board = Board::from_config(&config, "dev").unwrap().unwrap();
+        assert_eq!(board.columns.len(), 3);
+        assert_eq!(
+            board.columns[0].rule,
+            ColumnRule::Uda {
+                name: "kanban".to_string(),
+                value: "todo".to_string()
+            }
+        );
+        assert!(Board::from_config(&config, "other").unwrap().is_none());
+
+        let mut finished = kanban("Finished", "doing");
+        finished.complete();
+        let tasks = vec![
+            kanban("Spec", "todo"),
+            kanban("Build", "doing"),
+            kanban("Test", "doing"),
+            finished,
+            Task::new("Loose".to_string()),
+        ];
+        let layout = board.layout(&tasks);
+        let counts: Vec<usize> = layout.columns.iter().map(|(_, t)| t.len()).collect();
+        // The completed task still has kanban:doing but doing comes first
+        assert_eq!(counts, vec![1, 3, 0]);
+        assert_eq!(layout.unassigned[0].description, "Loose");
+        let violations = layout.wip_violations();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            (violations[0].0.name.as_str(), violations[0].1),
+            ("doing", 3)
+        );
+
+        config.set("board.dev.column.doing.limit", "many");
+        assert!(Board::from_config(&config, "dev").is_err());
+    }
+
+    #[test]
+    fn test_move_updates() {
+        let board = Board::new()
+            .column(BoardColumn::new(
+                "next",
+                ColumnRule::Tag("next".to_string()),
+            ))
+            .column(BoardColumn::new(
+                "active",
+                ColumnRule::Tag("active".to_string()),
+            ))
+            .column(BoardColumn::new(
+                "done",
+                ColumnRule::Status(TaskStatus::Completed),
+            ));
+        let mut task = Task::new("Write docs".to_string());
+        task.tags.extend(["next".to_string(), "docs".to_string()]);
+
+        let update = board.move_update(&task, "active").unwrap();
+        let tags = update.tags.unwrap();
+        assert!(tags.contains("active") && tags.contains("docs") && !tags.contains("next"));
+        assert_eq!(update.status, None);
+
+        let update = board.move_update(&task, "done").unwrap();
+        assert_eq!(update.status, Some(TaskStatus::Completed));
+        task.complete();
+        let update = board.move_update(&task, "next").unwrap();
+        assert_eq!(update.status, Some(TaskStatus::Pending));
+        assert!(update.tags.is_none());
+
+        assert!(board.move_update(&task, "missing").is_err());
+        assert_eq!(ColumnRule::parse("status:bogus"), None);
+        assert_eq!(ColumnRule::parse("+"), None);
+    }
+
+    #[test]
+    fn test_move_task_through_manager() {
+        use crate::config::ConfigurationBuilder;
+        use crate::hooks::DefaultHookSystem;
+        use crate::storage::FileStorageBackend;
+        use crate::task::manager::DefaultTaskManager;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = ConfigurationBuilder::new()
+            .data_dir(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(temp_dir.path()));
+        let mut manager =
+            DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new())).unwrap();
+        let board = Board::by_uda("kanban", &["todo", "doing"]);
+        let board = Board {
+            columns: vec![
+                board.columns[0].clone(),
+                board.columns[1].clone().with_wip_limit(1),
+            ],
+        };
+
+        let first = manager.add_task("First".to_string()).unwrap();
+        let second = manager.add_task("Second".to_string()).unwrap();
+        let moved = board.move_task(&mut manager, first.id, "doing").unwrap();
+        assert_eq!(moved.udas["kanban"], UdaValue::String("doing".to_string()));
+        // Moving within the same column is fine, a second task is not
+        board.move_task(&mut manager, first.id, "doing").unwrap();
+        assert!(matches!(
+            board.move_task(&mut manager, second.id, "doing"),
+            Err(TaskError::InvalidState { .. })
+        ));
+        board.move_task(&mut manager, first.id, "todo").unwrap();
+        board.move_task(&mut manager, second.id, "doing").unwrap();
+        let stored = manager.get_task(first.id).unwrap().unwrap();
+        assert_eq!(board.column_of(&stored).unwrap().name, "todo");
+    }
+}
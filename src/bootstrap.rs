@@ -0,0 +1,321 @@
+//! First-run setup
+//!
+//! A new application has to do the same few things before it can use a task
+//! manager on a machine that has never run Taskwarrior: create the data
+//! directory, write a taskrc, create the storage and perhaps add a few
+//! tasks so the first screen is not empty. [`initialize`] does all of that
+//! in one call and is safe to run on every start: files that already exist
+//! are left alone and examples are only added to an empty task list.
+//!
+//! The starter taskrc keeps contexts and UDAs in files of their own, which
+//! are easier for an application to rewrite than the main taskrc:
+//!
+//! ```text
+//! # taskrc
+//! data.location=/home/me/.local/share/task
+//! include contexts.rc
+//! include udas.rc
+//! ```
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::bootstrap::{initialize, ProfileOptions};
+//! use taskwarrior3lib::TaskManager;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut setup = initialize(&ProfileOptions::discover()?.seed_examples(true))?;
+//! for path in &setup.created {
+//!     println!("created {}", path.display());
+//! }
+//! let tasks = setup.manager.query_tasks(&Default::default())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Duration;
+
+use crate::clock;
+use crate::config::discovery::discover_all_paths;
+use crate::config::ConfigurationBuilder;
+use crate::error::{ConfigError, TaskError};
+use crate::hooks::DefaultHookSystem;
+use crate::query::{FilterMode, TaskQuery};
+use crate::storage::{FileStorageBackend, StorageBackend, TaskChampionStorageBackend};
+use crate::task::manager::{DefaultTaskManager, TaskUpdate};
+use crate::task::{Task, TaskManager, UdaTypes};
+
+/// Included by the starter taskrc for `context.*` settings
+pub const CONTEXTS_RC: &str = "contexts.rc";
+/// Included by the starter taskrc for `uda.*` settings
+pub const UDAS_RC: &str = "udas.rc";
+/// Tag carried by every example task, so they are easy to remove
+pub const EXAMPLE_TAG: &str = "example";
+
+/// Storage to create in the data directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    /// `taskchampion.sqlite3`, shared with Taskwarrior 3; needs the
+    /// `taskchampion` feature
+    #[default]
+    TaskChampion,
+    /// This library's `tasks.json` file backend
+    File,
+}
+
+/// Where and how to set up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOptions {
+    pub data_dir: PathBuf,
+    pub taskrc: PathBuf,
+    pub backend: BackendKind,
+    /// Add a few example tasks if there are no tasks yet
+    pub seed_examples: bool,
+}
+
+impl ProfileOptions {
+    /// Set up in `data_dir` with the taskrc at `taskrc`
+    pub fn new<D: Into<PathBuf>, R: Into<PathBuf>>(data_dir: D, taskrc: R) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            taskrc: taskrc.into(),
+            backend: BackendKind::default(),
+            seed_examples: false,
+        }
+    }
+
+    /// Set up in the platform's standard locations (see
+    /// [`discovery`](crate::config::discovery))
+    pub fn discover() -> Result<Self, ConfigError> {
+        let paths = discover_all_paths()?;
+        Ok(Self::new(paths.data_dir, paths.taskrc))
+    }
+
+    pub fn backend(mut self, backend: BackendKind) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn seed_examples(mut self, seed: bool) -> Self {
+        self.seed_examples = seed;
+        self
+    }
+}
+
+/// The result of [`initialize`]
+#[derive(Debug)]
+pub struct Bootstrapped {
+    /// Manager over the new (or existing) data
+    pub manager: DefaultTaskManager,
+    /// Files and directories this run created; empty when everything
+    /// already existed
+    pub created: Vec<PathBuf>,
+    /// Example tasks added
+    pub seeded: Vec<Task>,
+}
+
+/// Create whatever is missing of the setup described by `options` and
+/// open a task manager over it
+pub fn initialize(options: &ProfileOptions) -> Result<Bootstrapped, TaskError> {
+    let mut created = Vec::new();
+
+    if !options.data_dir.is_dir() {
+        create_dir(&options.data_dir)?;
+        created.push(options.data_dir.clone());
+    }
+
+    let rc_dir = options
+        .taskrc
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    if !rc_dir.as_os_str().is_empty() && !rc_dir.is_dir() {
+        create_dir(&rc_dir)?;
+        created.push(rc_dir.clone());
+    }
+    let starter_files = [
+        (options.taskrc.clone(), starter_taskrc(&options.data_dir)),
+        (
+            rc_dir.join(CONTEXTS_RC),
+            "# Contexts, e.g. context.work.read=+work\n".to_string(),
+        ),
+        (
+            rc_dir.join(UDAS_RC),
+            "# User defined attributes, e.g. uda.estimate.type=numeric\n".to_string(),
+        ),
+    ];
+    for (path, content) in starter_files {
+        if !path.exists() {
+            fs::write(&path, content).map_err(|source| ConfigError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            created.push(path);
+        }
+    }
+
+    let config = ConfigurationBuilder::new()
+        .config_file(&options.taskrc)
+        .data_dir(&options.data_dir)
+        .build()?;
+
+    let storage: Box<dyn StorageBackend> = match options.backend {
+        BackendKind::File => Box::new(FileStorageBackend::with_path(&options.data_dir)),
+        BackendKind::TaskChampion => {
+            let db_path = options.data_dir.join("taskchampion.sqlite3");
+            let existed = db_path.exists();
+            let storage = open_taskchampion(&options.data_dir, &db_path)?;
+            if !existed {
+                created.push(db_path);
+            }
+            Box::new(storage.with_uda_types(UdaTypes::from_config(&config)))
+        }
+    };
+    let mut manager = DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new()))?;
+
+    let mut seeded = Vec::new();
+    let everything = TaskQuery {
+        filter_mode: Some(FilterMode::IgnoreContext),
+        ..Default::default()
+    };
+    if options.seed_examples && manager.count_tasks(&everything)? == 0 {
+        for (description, update) in example_tasks() {
+            let task = manager.add_task(description.to_string())?;
+            seeded.push(manager.update_task(task.id, update)?);
+        }
+    }
+
+    Ok(Bootstrapped {
+        manager,
+        created,
+        seeded,
+    })
+}
+
+fn create_dir(path: &Path) -> Result<(), ConfigError> {
+    fs::create_dir_all(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn starter_taskrc(data_dir: &Path) -> String {
+    format!(
+        "# Taskwarrior configuration\n\
+         # See https://taskwarrior.org/docs/configuration/ for all settings\n\
+         \n\
+         data.location={}\n\
+         \n\
+         include {CONTEXTS_RC}\n\
+         include {UDAS_RC}\n",
+        data_dir.display()
+    )
+}
+
+/// Open the replica in `data_dir`, creating its database if missing
+fn open_taskchampion(
+    data_dir: &Path,
+    db_path: &Path,
+) -> Result<TaskChampionStorageBackend, TaskError> {
+    #[cfg(feature = "taskchampion")]
+    {
+        let mut storage = TaskChampionStorageBackend::new(db_path);
+        storage.set_replica(
+            crate::storage::replica_taskchampion::open_taskchampion_replica(data_dir)?,
+        );
+        Ok(storage)
+    }
+    #[cfg(not(feature = "taskchampion"))]
+    {
+        let _ = (data_dir, db_path);
+        Err(TaskError::Storage {
+            source: crate::error::StorageError::Database {
+                message: "Creating a TaskChampion database requires the `taskchampion` feature"
+                    .to_string(),
+            },
+        })
+    }
+}
+
+fn example_tasks() -> Vec<(&'static str, TaskUpdate)> {
+    let tomorrow = clock::now() + Duration::days(1);
+    vec![
+        (
+            "Read the Taskwarrior tutorial",
+            TaskUpdate::new().add_tag(EXAMPLE_TAG),
+        ),
+        (
+            "Plan the week",
+            TaskUpdate::new()
+                .project("Personal")
+                .due(tomorrow)
+                .add_tag(EXAMPLE_TAG),
+        ),
+        (
+            "Delete the example tasks",
+            TaskUpdate::new().add_tag(EXAMPLE_TAG).add_tag("next"),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_initialize_file_backend() {
+        let dir = TempDir::new().unwrap();
+        let options = ProfileOptions::new(dir.path().join("data"), dir.path().join("rc/taskrc"))
+            .backend(BackendKind::File)
+            .seed_examples(true);
+
+        let mut setup = initialize(&options).unwrap();
+        assert_eq!(setup.seeded.len(), 3);
+        assert!(setup.seeded.iter().all(|t| t.tags.contains(EXAMPLE_TAG)));
+        let all = TaskQuery::default();
+        assert_eq!(setup.manager.count_tasks(&all).unwrap(), 3);
+        for name in ["data", "rc", "rc/taskrc", "rc/contexts.rc", "rc/udas.rc"] {
+            assert!(setup.created.contains(&dir.path().join(name)), "{name}");
+        }
+        let taskrc = fs::read_to_string(dir.path().join("rc/taskrc")).unwrap();
+        assert!(taskrc.contains("include contexts.rc"));
+
+        // A second run changes nothing
+        drop(setup);
+        let setup = initialize(&options).unwrap();
+        assert!(setup.created.is_empty());
+        assert!(setup.seeded.is_empty());
+    }
+
+    #[cfg(feature = "taskchampion")]
+    #[test]
+    fn test_initialize_taskchampion() {
+        let dir = TempDir::new().unwrap();
+        let options = ProfileOptions::new(dir.path().join("data"), dir.path().join("taskrc"))
+            .seed_examples(true);
+
+        let mut setup = initialize(&options).unwrap();
+        let db = dir.path().join("data/taskchampion.sqlite3");
+        assert!(db.exists() && setup.created.contains(&db));
+        let all = TaskQuery::default();
+        assert_eq!(setup.manager.count_tasks(&all).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_initialize_keeps_existing_taskrc() {
+        let dir = TempDir::new().unwrap();
+        let taskrc = dir.path().join("taskrc");
+        fs::write(&taskrc, "default.project=Home\n").unwrap();
+        let options = ProfileOptions::new(dir.path(), &taskrc).backend(BackendKind::File);
+
+        let setup = initialize(&options).unwrap();
+        assert!(!setup.created.contains(&taskrc));
+        assert_eq!(
+            fs::read_to_string(&taskrc).unwrap(),
+            "default.project=Home\n"
+        );
+        assert!(setup.seeded.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Sources of time and task IDs
+//!
+//! Everything in the library that stamps a time or mints a UUID (new tasks
+//! and annotations, `modified` updates, `now`-relative dates, backup file
+//! names, hooks) asks [`now`] and [`new_id`] instead of calling
+//! `Utc::now()` and `Uuid::new_v4()` directly. Normally these are the
+//! system clock and random v4 UUIDs; [`deterministic`] swaps in other
+//! sources for the current thread, so tests can freeze time and get the
+//! same IDs on every run:
+//!
+//! ```rust
+//! use chrono::{Duration, TimeZone, Utc};
+//! use taskwarrior3lib::clock::{self, FixedClock, SequentialIds};
+//! use taskwarrior3lib::Task;
+//!
+//! let start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+//! let time = FixedClock::new(start);
+//! let _guard = clock::deterministic(time.clone(), SequentialIds::new());
+//!
+//! let task = Task::new("Write report".to_string());
+//! assert_eq!(task.entry, start);
+//! assert_eq!(task.id.as_u128(), 1);
+//!
+//! time.advance(Duration::hours(1));
+//! assert_eq!(clock::now(), start + Duration::hours(1));
+//! ```
+//!
+//! The override is per thread and lasts until the guard is dropped, so
+//! tests running in parallel do not see each other's clocks.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// A source of the current time
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A source of new task and annotation IDs
+pub trait IdGenerator: fmt::Debug + Send + Sync {
+    fn next_id(&self) -> Uuid;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Random version 4 UUIDs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    time: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FixedClock {
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self {
+            time: Arc::new(Mutex::new(time)),
+        }
+    }
+
+    /// Jump to `time`
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.time.lock().unwrap() = time;
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.time.lock().unwrap()
+    }
+}
+
+/// IDs counting up from 1: `00000000-0000-0000-0000-000000000001`, ...
+/// Clones share the same counter.
+#[derive(Debug, Clone)]
+pub struct SequentialIds {
+    next: Arc<AtomicU64>,
+}
+
+impl Default for SequentialIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SequentialIds {
+    pub fn new() -> Self {
+        Self::starting_at(1)
+    }
+
+    /// Count up from `first`
+    pub fn starting_at(first: u64) -> Self {
+        Self {
+            next: Arc::new(AtomicU64::new(first)),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::from_u128(u128::from(self.next.fetch_add(1, Ordering::Relaxed)))
+    }
+}
+
+type Sources = (Arc<dyn Clock>, Arc<dyn IdGenerator>);
+
+thread_local! {
+    static OVERRIDE: RefCell<Option<Sources>> = const { RefCell::new(None) };
+}
+
+/// The current time, from the clock installed by [`deterministic`] or the
+/// system clock
+pub fn now() -> DateTime<Utc> {
+    OVERRIDE.with(|sources| match &*sources.borrow() {
+        Some((clock, _)) => clock.now(),
+        None => Utc::now(),
+    })
+}
+
+/// A new ID, from the generator installed by [`deterministic`] or a random
+/// v4 UUID
+pub fn new_id() -> Uuid {
+    OVERRIDE.with(|sources| match &*sources.borrow() {
+        Some((_, ids)) => ids.next_id(),
+        None => Uuid::new_v4(),
+    })
+}
+
+/// Use `clock` and `ids` on this thread until the returned guard is
+/// dropped. Guards nest; dropping one restores whatever was active before.
+#[must_use = "the sources are removed again when the guard is dropped"]
+pub fn deterministic<C, I>(clock: C, ids: I) -> DeterministicGuard
+where
+    C: Clock + 'static,
+    I: IdGenerator + 'static,
+{
+    let sources: Sources = (Arc::new(clock), Arc::new(ids));
+    let previous = OVERRIDE.with(|current| current.borrow_mut().replace(sources));
+    DeterministicGuard { previous }
+}
+
+/// Restores the previous time and ID sources when dropped
+#[derive(Debug)]
+pub struct DeterministicGuard {
+    previous: Option<Sources>,
+}
+
+impl Drop for DeterministicGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        OVERRIDE.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_guards_nest_and_restore() {
+        let outer = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let inner = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        {
+            let _outer = deterministic(FixedClock::new(outer), SequentialIds::new());
+            {
+                let _inner =
+                    deterministic(FixedClock::new(inner), SequentialIds::starting_at(100));
+                assert_eq!(now(), inner);
+                assert_eq!(new_id().as_u128(), 100);
+            }
+            assert_eq!(now(), outer);
+            assert_eq!(new_id().as_u128(), 1);
+            assert_eq!(new_id().as_u128(), 2);
+
+            // Other threads keep the real clock
+            let elsewhere = std::thread::spawn(now).join().unwrap();
+            assert!(elsewhere > outer && elsewhere < inner);
+        }
+        assert_ne!(new_id(), new_id());
+    }
+}
@@ -0,0 +1,323 @@
+//! Portable bundles of the non-task environment
+//!
+//! A [`ConfigBundle`] holds the settings that make up someone's setup
+//! rather than their data: contexts, UDAs, reports, urgency coefficients
+//! and aliases. It serializes to TOML or JSON, so the setup can be moved
+//! to another machine without copying rc files around, and is merged into
+//! the configuration there with every conflicting key reported.
+//!
+//! Only values someone set are exported; built-in defaults are left out,
+//! as is the active `context`, which belongs to the machine.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::context::write_rc_settings;
+use crate::config::{ConfigLayer, Configuration};
+use crate::error::ConfigError;
+
+/// Bundle format version written by [`ConfigBundle::from_config`]
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Key prefixes a bundle carries
+pub const BUNDLE_PREFIXES: [&str; 5] = ["context.", "uda.", "report.", "urgency.", "alias."];
+
+/// Settings exported from one configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub version: u32,
+    /// `key = value` in rc syntax, sorted by key
+    pub settings: BTreeMap<String, String>,
+}
+
+/// What to do with a key set to a different value on both sides
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Keep the existing value
+    #[default]
+    KeepExisting,
+    /// Take the value from the bundle
+    Overwrite,
+}
+
+/// A key whose bundled value differs from the configured one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleConflict {
+    pub key: String,
+    pub existing: String,
+    pub incoming: String,
+}
+
+/// Outcome of [`ConfigBundle::merge_into`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleImport {
+    /// Keys that were not set before (or only had a built-in default)
+    pub added: Vec<String>,
+    /// Keys already set to the bundled value
+    pub unchanged: Vec<String>,
+    /// Keys set to a different value; overwritten or kept depending on the
+    /// [`ConflictStrategy`]
+    pub conflicts: Vec<BundleConflict>,
+    /// Keys outside [`BUNDLE_PREFIXES`], which are never imported
+    pub rejected: Vec<String>,
+    /// Whether conflicting keys were overwritten
+    pub overwritten: bool,
+}
+
+impl BundleImport {
+    /// Keys whose value the import changed
+    pub fn changed(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.added.iter().map(String::as_str).collect();
+        if self.overwritten {
+            keys.extend(self.conflicts.iter().map(|c| c.key.as_str()));
+        }
+        keys
+    }
+}
+
+impl ConfigBundle {
+    /// Collect the bundled settings of `config` that do not come from the
+    /// built-in defaults
+    pub fn from_config(config: &Configuration) -> Self {
+        let settings = config
+            .settings
+            .iter()
+            .filter(|(key, _)| is_bundled(key))
+            .filter(|(key, _)| {
+                config
+                    .source(key)
+                    .is_none_or(|s| s.layer() != ConfigLayer::Default)
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Self {
+            version: BUNDLE_VERSION,
+            settings,
+        }
+    }
+
+    /// Serialize as TOML
+    pub fn to_toml(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(self).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, ConfigError> {
+        serde_json::to_string_pretty(self).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Parse a bundle written by [`Self::to_toml`]
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let bundle: Self = toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        bundle.check_version()
+    }
+
+    /// Parse a bundle written by [`Self::to_json`]
+    pub fn from_json(content: &str) -> Result<Self, ConfigError> {
+        let bundle: Self = serde_json::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        bundle.check_version()
+    }
+
+    /// Read a bundle file, as JSON if it ends in `.json` and as TOML
+    /// otherwise
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json(&content)
+        } else {
+            Self::from_toml(&content)
+        }
+    }
+
+    fn check_version(self) -> Result<Self, ConfigError> {
+        if self.version == 0 || self.version > BUNDLE_VERSION {
+            return Err(invalid(format!("unsupported version {}", self.version)));
+        }
+        Ok(self)
+    }
+
+    /// Compare the bundle with `config` and apply it as overrides: new keys
+    /// are always set, conflicting ones only with
+    /// [`ConflictStrategy::Overwrite`]. Nothing is written to disk; see
+    /// [`Self::import`].
+    pub fn merge_into(
+        &self,
+        config: &mut Configuration,
+        strategy: ConflictStrategy,
+    ) -> BundleImport {
+        let mut report = BundleImport {
+            overwritten: strategy == ConflictStrategy::Overwrite,
+            ..Default::default()
+        };
+        for (key, incoming) in &self.settings {
+            if !is_bundled(key) {
+                report.rejected.push(key.clone());
+                continue;
+            }
+            let is_default = config
+                .source(key)
+                .is_some_and(|s| s.layer() == ConfigLayer::Default);
+            match config.get(key) {
+                Some(existing) if existing == incoming => {
+                    report.unchanged.push(key.clone());
+                    continue;
+                }
+                Some(existing) if !is_default => {
+                    report.conflicts.push(BundleConflict {
+                        key: key.clone(),
+                        existing: existing.clone(),
+                        incoming: incoming.clone(),
+                    });
+                    if strategy == ConflictStrategy::KeepExisting {
+                        continue;
+                    }
+                }
+                _ => report.added.push(key.clone()),
+            }
+            config.set(key.clone(), incoming.clone());
+        }
+        report
+    }
+
+    /// [`Self::merge_into`], then persist the changed keys to the
+    /// configuration's rc file
+    pub fn import(
+        &self,
+        config: &mut Configuration,
+        strategy: ConflictStrategy,
+    ) -> Result<BundleImport, ConfigError> {
+        let report = self.merge_into(config, strategy);
+        let updates: Vec<(&str, Option<&str>)> = report
+            .changed()
+            .into_iter()
+            .map(|key| (key, self.settings.get(key).map(String::as_str)))
+            .collect();
+        if !updates.is_empty() {
+            write_rc_settings(&config.config_file, &updates)?;
+        }
+        Ok(report)
+    }
+}
+
+/// Whether `key` belongs in a bundle
+pub fn is_bundled(key: &str) -> bool {
+    BUNDLE_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+fn invalid(message: String) -> ConfigError {
+    ConfigError::InvalidBundle { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured() -> Configuration {
+        let mut config = Configuration::default();
+        config.set("context.work", "+work");
+        config.set("context", "work");
+        config.set("uda.estimate.type", "duration");
+        config.set("report.mine.columns", "id,description");
+        config.set("urgency.user.tag.next.coefficient", "20");
+        config.set("alias.rm", "delete");
+        config.set("data.location", "/home/me/.task");
+        config
+    }
+
+    #[test]
+    fn test_export_leaves_out_defaults_and_machine_settings() {
+        let bundle = ConfigBundle::from_config(&configured());
+        let keys: Vec<&str> = bundle.settings.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "alias.rm",
+                "context.work",
+                "report.mine.columns",
+                "uda.estimate.type",
+                "urgency.user.tag.next.coefficient",
+            ]
+        );
+
+        assert_eq!(
+            ConfigBundle::from_toml(&bundle.to_toml().unwrap()).unwrap(),
+            bundle
+        );
+        assert_eq!(
+            ConfigBundle::from_json(&bundle.to_json().unwrap()).unwrap(),
+            bundle
+        );
+        assert!(matches!(
+            ConfigBundle::from_json(r#"{"version": 9, "settings": {}}"#),
+            Err(ConfigError::InvalidBundle { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let mut bundle = ConfigBundle::from_config(&configured());
+        bundle
+            .settings
+            .insert("urgency.due.coefficient".to_string(), "15".to_string());
+        bundle
+            .settings
+            .insert("data.location".to_string(), "/elsewhere".to_string());
+
+        let mut target = Configuration::default();
+        target.set("alias.rm", "remove");
+        target.set("context.work", "+work");
+        let mut kept = target.clone();
+
+        let report = bundle.merge_into(&mut kept, ConflictStrategy::KeepExisting);
+        assert_eq!(report.unchanged, vec!["context.work"]);
+        assert_eq!(
+            report.conflicts,
+            vec![BundleConflict {
+                key: "alias.rm".to_string(),
+                existing: "remove".to_string(),
+                incoming: "delete".to_string(),
+            }]
+        );
+        assert_eq!(report.rejected, vec!["data.location"]);
+        // A built-in default is replaced without counting as a conflict
+        assert!(report
+            .added
+            .contains(&"urgency.due.coefficient".to_string()));
+        assert_eq!(kept.get("alias.rm").unwrap(), "remove");
+        assert_eq!(kept.get("urgency.due.coefficient").unwrap(), "15");
+        assert!(!report.changed().contains(&"alias.rm"));
+
+        let report = bundle.merge_into(&mut target, ConflictStrategy::Overwrite);
+        assert_eq!(target.get("alias.rm").unwrap(), "delete");
+        assert!(report.changed().contains(&"alias.rm"));
+    }
+
+    #[test]
+    fn test_import_persists_changed_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let rc = dir.path().join("taskrc");
+        std::fs::write(&rc, "# mine\nalias.rm=remove\nverbose=off\n").unwrap();
+        let mut config = Configuration::from_file(&rc).unwrap();
+
+        let bundle = ConfigBundle::from_config(&configured());
+        let report = bundle
+            .import(&mut config, ConflictStrategy::Overwrite)
+            .unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+
+        let reloaded = Configuration::from_file(&rc).unwrap();
+        for (key, value) in &bundle.settings {
+            assert_eq!(reloaded.get(key), Some(value));
+        }
+        assert_eq!(reloaded.get("verbose").unwrap(), "off");
+        assert!(std::fs::read_to_string(&rc)
+            .unwrap()
+            .starts_with("# mine\n"));
+    }
+}
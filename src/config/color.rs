@@ -0,0 +1,112 @@
+//! Color rules
+//!
+//! Taskwarrior colors rows with `color.<rule>` settings such as
+//! `color.active`, `color.tag.next` or `color.project.home`. When several
+//! rules match a task, `rule.precedence.color` decides which wins; entries
+//! ending in `.` (`tag.`, `project.`) stand for every rule with that prefix.
+//! A `tag.<name>.color` setting counts as a `color.tag.<name>` rule unless
+//! that rule is set explicitly.
+
+use crate::config::Configuration;
+
+/// One `color.<rule>` setting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorRule {
+    /// Rule name without the `color.` prefix, e.g. `tag.next`
+    pub name: String,
+    /// Color specification, e.g. `bold red on white`
+    pub color: String,
+}
+
+/// Color rules in precedence order
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ColorRuleSet {
+    /// The `color` setting; frontends should not color output when false
+    pub enabled: bool,
+    rules: Vec<ColorRule>,
+}
+
+impl ColorRuleSet {
+    /// Load `color.*` and `tag.<name>.color` settings, ordered by
+    /// `rule.precedence.color`. Rules with an empty color are dropped.
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut rules: Vec<ColorRule> = config
+            .settings
+            .iter()
+            .filter_map(|(key, color)| {
+                let name = key.strip_prefix("color.")?;
+                (!color.trim().is_empty()).then(|| ColorRule {
+                    name: name.to_string(),
+                    color: color.clone(),
+                })
+            })
+            .collect();
+        for (key, color) in &config.settings {
+            let Some(tag) = key.strip_prefix("tag.").and_then(|k| k.strip_suffix(".color")) else {
+                continue;
+            };
+            let name = format!("tag.{tag}");
+            if !color.trim().is_empty() && !rules.iter().any(|r| r.name == name) {
+                rules.push(ColorRule { name, color: color.clone() });
+            }
+        }
+
+        let precedence: Vec<&str> = config
+            .get("rule.precedence.color")
+            .map(|p| p.split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        let rank = |name: &str| {
+            precedence
+                .iter()
+                .position(|entry| match entry.strip_suffix('.') {
+                    Some(prefix) => name.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.')),
+                    None => *entry == name,
+                })
+                .unwrap_or(precedence.len())
+        };
+        rules.sort_by(|a, b| rank(&a.name).cmp(&rank(&b.name)).then_with(|| a.name.cmp(&b.name)));
+
+        Self {
+            enabled: config.get_bool("color").unwrap_or(true),
+            rules,
+        }
+    }
+
+    /// Color for a rule name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.rules.iter().find(|r| r.name == name).map(|r| r.color.as_str())
+    }
+
+    /// The first of `matching` rule names in precedence order that has a color
+    pub fn resolve<'a>(&'a self, matching: &[&str]) -> Option<&'a ColorRule> {
+        self.rules.iter().find(|r| matching.contains(&r.name.as_str()))
+    }
+
+    /// Rules in precedence order
+    pub fn iter(&self) -> impl Iterator<Item = &ColorRule> {
+        self.rules.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_follow_precedence() {
+        let mut config = Configuration::default();
+        config.set("color.due", "red");
+        config.set("color.active", "black on green");
+        config.set("color.tag.next", "bold");
+        config.set("tag.work.color", "blue");
+        config.set("tag.next.color", "ignored");
+        config.set("color.recurring", "");
+
+        let colors = ColorRuleSet::from_config(&config);
+        let names: Vec<&str> = colors.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["active", "tag.next", "tag.work", "due"]);
+        assert_eq!(colors.get("tag.next"), Some("bold"));
+        assert_eq!(colors.resolve(&["due", "tag.work"]).unwrap().color, "blue");
+        assert!(colors.enabled);
+    }
+}
@@ -172,7 +172,7 @@ pub fn set(config: &mut crate::config::Configuration, name: &str) -> Result<(),
 /// Clear the active context (unsets rc.context). Persists to taskrc.
 pub fn clear(config: &mut crate::config::Configuration) -> Result<(), ConfigError> {
     // Update in-memory settings
-    config.settings.remove("context");
+    config.unset("context");
 
     // Persist to file
     write_context_setting(&config.config_file, None)
@@ -180,6 +180,12 @@ pub fn clear(config: &mut crate::config::Configuration) -> Result<(), ConfigErro
 
 /// Helper to write or remove the `context` key in a Taskwarrior .taskrc file
 fn write_context_setting(path: &Path, value: Option<&str>) -> Result<(), ConfigError> {
+    write_rc_settings(path, &[("context", value)])
+}
+
+/// Write (`Some`) or remove (`None`) keys in a Taskwarrior .taskrc file,
+/// keeping comments and every other line
+pub(crate) fn write_rc_settings(path: &Path, updates: &[(&str, Option<&str>)]) -> Result<(), ConfigError> {
     // Read existing content if present
     let mut lines: Vec<String> = if path.exists() {
         let content = fs::read_to_string(path).map_err(|e| ConfigError::Io {
@@ -191,7 +197,7 @@ fn write_context_setting(path: &Path, value: Option<&str>) -> Result<(), ConfigE
         Vec::new()
     };
 
-    // Remove any existing context=... lines (preserve comments and others)
+    // Remove existing lines for the updated keys (preserve comments and others)
     lines.retain(|line| {
         let trimmed = line.trim();
         if trimmed.is_empty() || trimmed.starts_with('#') {
@@ -199,16 +205,18 @@ fn write_context_setting(path: &Path, value: Option<&str>) -> Result<(), ConfigE
         }
         if let Some((k, _v)) = trimmed.split_once('=') {
             let key = k.trim();
-            key != "context"
+            !updates.iter().any(|(updated, _)| *updated == key)
         } else {
             // Keep non key=value lines as-is
             true
         }
     });
 
-    // Append new context line if setting a value
-        if let Some(name) = value {
-        lines.push(format!("context={name}"));
+    // Append new lines for the keys being set
+    for (key, value) in updates {
+        if let Some(value) = value {
+            lines.push(format!("{key}={value}"));
+        }
     }
 
     // Ensure parent dir exists
@@ -306,8 +314,10 @@ mod tests {
         settings.insert("context.home".to_string(), "project:Home".to_string());
         settings.insert("context.work".to_string(), "project:Work".to_string());
 
-        let mut cfg = crate::config::Configuration::default();
-        cfg.settings = settings;
+        let cfg = crate::config::Configuration {
+            settings,
+            ..Default::default()
+        };
 
         let contexts = list(&cfg).unwrap();
         assert_eq!(contexts.len(), 2);
@@ -347,8 +357,10 @@ mod tests {
         let mut settings = HashMap::new();
         settings.insert("context.home".to_string(), "project:Home".to_string());
 
-        let mut cfg = crate::config::Configuration::default();
-        cfg.settings = settings;
+        let mut cfg = crate::config::Configuration {
+            settings,
+            ..Default::default()
+        };
 
         let err = set(&mut cfg, "work").unwrap_err();
         match err {
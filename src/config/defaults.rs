@@ -0,0 +1,175 @@
+//! Built-in default settings
+//!
+//! The settings stock Taskwarrior uses when the rc leaves them unset. They
+//! form the lowest [`ConfigLayer`](super::ConfigLayer), so every
+//! [`Configuration`](super::Configuration) answers `get("urgency.due.coefficient")`
+//! or `get("report.next.sort")` even with an empty rc.
+//!
+//! Report columns, labels, filters and sorts use Taskwarrior's own syntax;
+//! date formats use Taskwarrior's letters (`Y-M-D`), not chrono's.
+
+/// Default settings as `(key, value)` pairs
+pub const DEFAULTS: &[(&str, &str)] = &[
+    // General
+    ("default.command", "next"),
+    ("confirmation", "1"),
+    ("bulk", "3"),
+    ("nag", "You have more urgent tasks."),
+    ("hooks", "1"),
+    ("gc", "1"),
+    ("color", "1"),
+    ("search.case.sensitive", "1"),
+    ("regex", "1"),
+    ("json.array", "1"),
+    ("abbreviation.minimum", "2"),
+    ("recurrence", "1"),
+    ("recurrence.limit", "1"),
+    ("limit", "25"),
+    ("defaultwidth", "80"),
+    ("defaultheight", "24"),
+    ("row.padding", "0"),
+    ("column.padding", "1"),
+    ("indent.annotation", "2"),
+    ("indent.report", "0"),
+    ("list.all.projects", "0"),
+    ("list.all.tags", "0"),
+    ("complete.all.tags", "0"),
+    // Color
+    (
+        "rule.precedence.color",
+        "deleted,completed,active,keyword.,tag.,project.,overdue,scheduled,due.today,due,blocked,blocking,recurring,tagged,uda.",
+    ),
+    // Dates
+    ("dateformat", "Y-M-D"),
+    ("dateformat.holiday", "YMD"),
+    ("dateformat.edit", "Y-M-D H:N:S"),
+    ("dateformat.info", "Y-M-D H:N:S"),
+    ("weekstart", "sunday"),
+    ("displayweeknumber", "1"),
+    ("due", "7"),
+    // Priority
+    ("uda.priority.type", "string"),
+    ("uda.priority.label", "Priority"),
+    ("uda.priority.values", "H,M,L,"),
+    // Urgency
+    ("urgency.user.tag.next.coefficient", "15.0"),
+    ("urgency.due.coefficient", "12.0"),
+    ("urgency.blocking.coefficient", "8.0"),
+    ("urgency.uda.priority.H.coefficient", "6.0"),
+    ("urgency.uda.priority.M.coefficient", "3.9"),
+    ("urgency.uda.priority.L.coefficient", "1.8"),
+    ("urgency.scheduled.coefficient", "5.0"),
+    ("urgency.active.coefficient", "4.0"),
+    ("urgency.age.coefficient", "2.0"),
+    ("urgency.annotations.coefficient", "1.0"),
+    ("urgency.tags.coefficient", "1.0"),
+    ("urgency.project.coefficient", "1.0"),
+    ("urgency.waiting.coefficient", "-3.0"),
+    ("urgency.blocked.coefficient", "-5.0"),
+    ("urgency.age.max", "365"),
+    ("urgency.inherit", "0"),
+    // Reports
+    ("report.next.description", "Most urgent tasks"),
+    (
+        "report.next.columns",
+        "id,start.age,entry.age,depends,priority,project,tags,recur,scheduled.countdown,due.relative,until.remaining,description,urgency",
+    ),
+    (
+        "report.next.labels",
+        "ID,Active,Age,Deps,P,Project,Tag,Recur,S,Due,Until,Description,Urg",
+    ),
+    ("report.next.filter", "status:pending -WAITING limit:page"),
+    ("report.next.sort", "urgency-"),
+    ("report.list.description", "Most details of tasks"),
+    (
+        "report.list.columns",
+        "id,start.age,entry.age,depends.indicator,priority,project,tags,recur.indicator,scheduled.countdown,due,until.remaining,description.count,urgency",
+    ),
+    (
+        "report.list.labels",
+        "ID,Active,Age,D,P,Project,Tags,R,Sch,Due,Until,Description,Urg",
+    ),
+    ("report.list.filter", "status:pending -WAITING"),
+    ("report.list.sort", "start-,due+,project+,urgency-"),
+    ("report.ls.description", "Few details of tasks"),
+    (
+        "report.ls.columns",
+        "id,start.active,depends.indicator,project,tags,recur.indicator,wait.remaining,scheduled.countdown,due.countdown,until.countdown,description.count",
+    ),
+    (
+        "report.ls.labels",
+        "ID,A,D,Project,Tags,R,Wait,S,Due,Until,Description",
+    ),
+    ("report.ls.filter", "status:pending -WAITING"),
+    ("report.ls.sort", "start-,description+"),
+    ("report.minimal.description", "Minimal details of tasks"),
+    ("report.minimal.columns", "id,project,tags.count,description.count"),
+    ("report.minimal.labels", "ID,Project,Tags,Description"),
+    ("report.minimal.filter", "status:pending -WAITING"),
+    ("report.minimal.sort", "project+/,description+"),
+    ("report.active.description", "Active tasks"),
+    (
+        "report.active.columns",
+        "id,start,start.age,entry.age,depends.indicator,priority,project,tags,recur,wait,scheduled.remaining,due,until,description",
+    ),
+    (
+        "report.active.labels",
+        "ID,Started,Active,Age,D,P,Project,Tags,Recur,W,Sch,Due,Until,Description",
+    ),
+    ("report.active.filter", "status:pending -WAITING +ACTIVE"),
+    ("report.active.sort", "project+,start+"),
+    ("report.overdue.description", "Overdue tasks"),
+    (
+        "report.overdue.columns",
+        "id,start.age,entry.age,depends,priority,project,tags,recur.indicator,scheduled.countdown,due,until,description,urgency",
+    ),
+    (
+        "report.overdue.labels",
+        "ID,Active,Age,Deps,P,Project,Tag,R,S,Due,Until,Description,Urg",
+    ),
+    ("report.overdue.filter", "status:pending -WAITING +OVERDUE"),
+    ("report.overdue.sort", "urgency-,due+"),
+    ("report.waiting.description", "Waiting (hidden) tasks"),
+    (
+        "report.waiting.columns",
+        "id,start.active,entry.age,depends.indicator,priority,project,tags,recur.indicator,wait,wait.remaining,scheduled,due,until,description",
+    ),
+    (
+        "report.waiting.labels",
+        "ID,A,Age,D,P,Project,Tags,R,Wait,Remaining,Sched,Due,Until,Description",
+    ),
+    ("report.waiting.filter", "+WAITING"),
+    ("report.waiting.sort", "due+,wait+,entry+"),
+    ("report.completed.description", "Completed tasks"),
+    (
+        "report.completed.columns",
+        "id,uuid.short,entry,end,entry.age,depends,priority,project,tags,recur.indicator,due,description",
+    ),
+    (
+        "report.completed.labels",
+        "ID,UUID,Created,Completed,Age,Deps,P,Project,Tags,R,Due,Description",
+    ),
+    ("report.completed.filter", "status:completed"),
+    ("report.completed.sort", "end+"),
+];
+
+/// Built-in default for `key`
+pub fn default_value(key: &str) -> Option<&'static str> {
+    DEFAULTS.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_has_unique_keys() {
+        let mut seen = HashSet::new();
+        for (key, _) in DEFAULTS {
+            assert!(seen.insert(key), "duplicate default for {key}");
+        }
+        assert_eq!(default_value("urgency.due.coefficient"), Some("12.0"));
+        assert_eq!(default_value("no.such.key"), None);
+    }
+}
@@ -1,141 +1,346 @@
-//! XDG directory discovery
+//! Directory discovery
 //!
-//! This module handles discovery of configuration and data directories
-//! following the XDG Base Directory specification.
+//! This module finds the Taskwarrior data directory, config directory and
+//! taskrc. Environment variables win, then the XDG variables (honoured on
+//! every platform), then the platform's own locations:
+//!
+//! | Platform | Data                                   | Config                          |
+//! |----------|----------------------------------------|---------------------------------|
+//! | Linux    | `~/.local/share/taskwarrior`           | `~/.config/task(warrior)`       |
+//! | macOS    | `~/Library/Application Support/taskwarrior` | `~/.config/task(warrior)`, then `~/Library/Application Support/task(warrior)` |
+//! | Windows  | `%LOCALAPPDATA%\taskwarrior`          | `%APPDATA%\task(warrior)`        |
+//!
+//! Where several candidates exist the first one present on disk is used,
+//! falling back to the legacy `~/.task` and `~/.taskrc`; with none present
+//! the platform default is returned so it can be created.
+//! [`discover_all_paths`] reports where each path came from, for
+//! diagnostics.
 
 use crate::error::ConfigError;
+use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Environment variables that influence discovery
+const VARIABLES: [&str; 6] = [
+    "TASKDATA",
+    "TASKRC",
+    "XDG_DATA_HOME",
+    "XDG_CONFIG_HOME",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
+/// Operating-system conventions for default locations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Unix,
+    MacOs,
+    Windows,
+}
 
-/// Discover the default Taskwarrior data directory
-pub fn discover_data_dir() -> Result<PathBuf, ConfigError> {
-    // Priority order:
-    // 1. TASKDATA environment variable
-    // 2. XDG_DATA_HOME/taskwarrior
-    // 3. ~/.local/share/taskwarrior (fallback)
-
-    if let Ok(taskdata) = env::var("TASKDATA") {
-        let path = PathBuf::from(taskdata);
-        if path.is_absolute() {
-            return Ok(path);
+impl Platform {
+    /// The platform this library was built for
+    pub fn current() -> Self {
+        if cfg!(windows) {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
         } else {
-            return Err(ConfigError::InvalidPath {
-                path,
-                message: "TASKDATA must be an absolute path".to_string(),
-            });
+            Platform::Unix
         }
     }
+}
+
+/// Where a discovered path came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSource {
+    /// Set by, or derived from, this environment variable
+    Environment(String),
+    /// The platform's conventional location under the home directory
+    Platform,
+    /// Taskwarrior 2's `~/.task` or `~/.taskrc`
+    Legacy,
+}
 
-    // Try XDG_DATA_HOME
-    if let Ok(xdg_data) = env::var("XDG_DATA_HOME") {
-        let xdg_path = PathBuf::from(&xdg_data);
-        if xdg_path.is_absolute() {
-            let path = xdg_path.join("taskwarrior");
-            return Ok(path);
+impl fmt::Display for PathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSource::Environment(variable) => write!(f, "${variable}"),
+            PathSource::Platform => write!(f, "platform default"),
+            PathSource::Legacy => write!(f, "legacy location"),
         }
     }
+}
 
-    // Fall back to default XDG location
-    if let Some(home_dir) = dirs::home_dir() {
-        Ok(home_dir.join(".local").join("share").join("taskwarrior"))
-    } else {
-        Err(ConfigError::Environment {
-            message: "Could not determine home directory for XDG data path".to_string(),
-        })
+/// A path and where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredPath {
+    pub path: PathBuf,
+    pub source: PathSource,
+}
+
+impl DiscoveredPath {
+    fn new(path: PathBuf, source: PathSource) -> Self {
+        Self { path, source }
     }
 }
 
-/// Discover the default Taskwarrior config directory
-pub fn discover_config_dir() -> Result<PathBuf, ConfigError> {
-    // Priority order:
-    // 1. XDG_CONFIG_HOME/taskwarrior
-    // 2. ~/.config/task (preferred) or ~/.config/taskwarrior (fallback)
-
-    if let Ok(xdg_config) = env::var("XDG_CONFIG_HOME") {
-        let xdg_path = PathBuf::from(&xdg_config);
-        if xdg_path.is_absolute() {
-            // Prefer 'task' if present, else 'taskwarrior', else return 'task' as default
-            let task_dir = xdg_path.join("task");
-            let taskwarrior_dir = xdg_path.join("taskwarrior");
-            if task_dir.exists() {
-                return Ok(task_dir);
-            }
-            if taskwarrior_dir.exists() {
-                return Ok(taskwarrior_dir);
-            }
-            // Default to taskwarrior for backwards compatibility in environments
-            // where neither directory exists.
-            return Ok(taskwarrior_dir);
+/// The inputs of discovery: platform, home directory, environment and
+/// which paths exist
+#[derive(Debug, Clone)]
+struct Lookup {
+    platform: Platform,
+    home: Option<PathBuf>,
+    vars: HashMap<String, String>,
+    /// Paths considered present; `None` checks the filesystem
+    existing: Option<Vec<PathBuf>>,
+}
+
+impl Lookup {
+    fn current() -> Self {
+        Self {
+            platform: Platform::current(),
+            home: dirs::home_dir(),
+            vars: VARIABLES
+                .iter()
+                .filter_map(|name| Some((name.to_string(), env::var(name).ok()?)))
+                .collect(),
+            existing: None,
         }
     }
 
-    if let Some(home_dir) = dirs::home_dir() {
-        let task_dir = home_dir.join(".config").join("task");
-        let taskwarrior_dir = home_dir.join(".config").join("taskwarrior");
-        if task_dir.exists() {
-            return Ok(task_dir);
+    fn exists(&self, path: &Path) -> bool {
+        match &self.existing {
+            Some(existing) => existing.iter().any(|p| p == path),
+            None => path.exists(),
         }
-        if taskwarrior_dir.exists() {
-            return Ok(taskwarrior_dir);
+    }
+
+    /// `name` as an absolute path, ignoring unset, empty or relative values
+    fn absolute_var(&self, name: &str) -> Option<DiscoveredPath> {
+        let path = PathBuf::from(self.vars.get(name).filter(|v| !v.is_empty())?);
+        path.is_absolute()
+            .then(|| DiscoveredPath::new(path, PathSource::Environment(name.to_string())))
+    }
+
+    /// `name` as an absolute path; set to anything else is an error
+    fn required_absolute_var(&self, name: &str) -> Result<Option<DiscoveredPath>, ConfigError> {
+        match self.vars.get(name) {
+            None => Ok(None),
+            Some(value) => {
+                let path = PathBuf::from(value);
+                if path.is_absolute() {
+                    Ok(Some(DiscoveredPath::new(
+                        path,
+                        PathSource::Environment(name.to_string()),
+                    )))
+                } else {
+                    Err(ConfigError::InvalidPath {
+                        path,
+                        message: format!("{name} must be an absolute path"),
+                    })
+                }
+            }
         }
-        // Default to ~/.config/taskwarrior for backwards compatibility
-        Ok(taskwarrior_dir)
-    } else {
-        Err(ConfigError::Environment {
-            message: "Could not determine home directory for XDG config path".to_string(),
+    }
+
+    fn home(&self, what: &str) -> Result<&Path, ConfigError> {
+        self.home
+            .as_deref()
+            .ok_or_else(|| ConfigError::Environment {
+                message: format!("Could not determine home directory for {what}"),
+            })
+    }
+
+    /// A Windows known folder: the variable if set, else its usual place
+    /// under the home directory
+    fn known_folder(&self, variable: &str, fallback: &[&str]) -> Option<DiscoveredPath> {
+        self.absolute_var(variable).or_else(|| {
+            let home = self.home.as_ref()?;
+            let path = fallback
+                .iter()
+                .fold(home.clone(), |path, part| path.join(part));
+            Some(DiscoveredPath::new(path, PathSource::Platform))
         })
     }
-}
 
-/// Discover the default .taskrc file location
-pub fn discover_taskrc() -> Result<PathBuf, ConfigError> {
-    // Priority order:
-    // 1. TASKRC environment variable
-    // 2. XDG_CONFIG_HOME/taskwarrior/taskrc
-    // 3. ~/.config/taskwarrior/taskrc
-    // 4. ~/.taskrc (legacy fallback)
-
-    if let Ok(taskrc) = env::var("TASKRC") {
-        let path = PathBuf::from(taskrc);
-        if path.is_absolute() {
-            return Ok(path);
-        } else {
-            return Err(ConfigError::InvalidPath {
-                path,
-                message: "TASKRC must be an absolute path".to_string(),
-            });
+    /// The first of `candidates` that exists, else `candidates[default]`
+    fn first_existing(&self, candidates: Vec<DiscoveredPath>, default: usize) -> DiscoveredPath {
+        let found = candidates.iter().position(|c| self.exists(&c.path));
+        let index = found.unwrap_or(default);
+        candidates
+            .into_iter()
+            .nth(index)
+            .expect("default candidate")
+    }
+
+    fn data_dir(&self) -> Result<DiscoveredPath, ConfigError> {
+        // Priority order:
+        // 1. TASKDATA environment variable
+        // 2. XDG_DATA_HOME/taskwarrior
+        // 3. The platform location, an existing ~/.task, or else the
+        //    platform location
+        if let Some(taskdata) = self.required_absolute_var("TASKDATA")? {
+            return Ok(taskdata);
+        }
+        if let Some(xdg) = self.absolute_var("XDG_DATA_HOME") {
+            return Ok(DiscoveredPath::new(
+                xdg.path.join("taskwarrior"),
+                xdg.source,
+            ));
         }
+
+        let home = self.home("XDG data path")?;
+        let xdg_default = DiscoveredPath::new(
+            home.join(".local").join("share").join("taskwarrior"),
+            PathSource::Platform,
+        );
+        let mut candidates = match self.platform {
+            Platform::Unix => vec![xdg_default],
+            Platform::MacOs => vec![
+                DiscoveredPath::new(
+                    home.join("Library")
+                        .join("Application Support")
+                        .join("taskwarrior"),
+                    PathSource::Platform,
+                ),
+                xdg_default,
+            ],
+            Platform::Windows => {
+                let local = self
+                    .known_folder("LOCALAPPDATA", &["AppData", "Local"])
+                    .ok_or_else(|| ConfigError::Environment {
+                        message: "Could not determine %LOCALAPPDATA%".to_string(),
+                    })?;
+                vec![DiscoveredPath::new(
+                    local.path.join("taskwarrior"),
+                    local.source,
+                )]
+            }
+        };
+        candidates.push(DiscoveredPath::new(home.join(".task"), PathSource::Legacy));
+        Ok(self.first_existing(candidates, 0))
     }
 
-    // Try XDG config directory first
-    let config_dir = discover_config_dir()?;
-    let xdg_taskrc = config_dir.join("taskrc");
-    if xdg_taskrc.exists() {
-        return Ok(xdg_taskrc);
+    fn config_dir(&self) -> Result<DiscoveredPath, ConfigError> {
+        // Priority order:
+        // 1. XDG_CONFIG_HOME/task or XDG_CONFIG_HOME/taskwarrior
+        // 2. The platform's task or taskwarrior directory that exists,
+        //    else its taskwarrior directory
+        let in_dir = |base: &DiscoveredPath| {
+            vec![
+                DiscoveredPath::new(base.path.join("task"), base.source.clone()),
+                DiscoveredPath::new(base.path.join("taskwarrior"), base.source.clone()),
+            ]
+        };
+        if let Some(xdg) = self.absolute_var("XDG_CONFIG_HOME") {
+            // Default to taskwarrior for backwards compatibility in
+            // environments where neither directory exists
+            return Ok(self.first_existing(in_dir(&xdg), 1));
+        }
+
+        let home = self.home("XDG config path")?;
+        let dot_config = DiscoveredPath::new(home.join(".config"), PathSource::Platform);
+        let (candidates, default) = match self.platform {
+            Platform::Unix => (in_dir(&dot_config), 1),
+            Platform::MacOs => {
+                let support = DiscoveredPath::new(
+                    home.join("Library").join("Application Support"),
+                    PathSource::Platform,
+                );
+                let mut candidates = in_dir(&dot_config);
+                candidates.extend(in_dir(&support));
+                (candidates, 3)
+            }
+            Platform::Windows => {
+                let roaming = self
+                    .known_folder("APPDATA", &["AppData", "Roaming"])
+                    .ok_or_else(|| ConfigError::Environment {
+                        message: "Could not determine %APPDATA%".to_string(),
+                    })?;
+                (in_dir(&roaming), 1)
+            }
+        };
+        Ok(self.first_existing(candidates, default))
     }
 
-    // Fall back to legacy location
-    if let Some(home_dir) = dirs::home_dir() {
-        let legacy_taskrc = home_dir.join(".taskrc");
-        if legacy_taskrc.exists() {
-            return Ok(legacy_taskrc);
+    fn taskrc(&self) -> Result<DiscoveredPath, ConfigError> {
+        // Priority order:
+        // 1. TASKRC environment variable
+        // 2. taskrc in the config directory
+        // 3. ~/.taskrc (legacy fallback)
+        // 4. taskrc in the config directory, to be created
+        if let Some(taskrc) = self.required_absolute_var("TASKRC")? {
+            return Ok(taskrc);
         }
-        // Return XDG path even if it doesn't exist (will be created if needed)
-        Ok(xdg_taskrc)
-    } else {
-        Err(ConfigError::Environment {
-            message: "Could not determine home directory for taskrc location".to_string(),
+        let config_dir = self.config_dir()?;
+        let home = self.home("taskrc location")?;
+        let candidates = vec![
+            DiscoveredPath::new(config_dir.path.join("taskrc"), config_dir.source),
+            DiscoveredPath::new(home.join(".taskrc"), PathSource::Legacy),
+        ];
+        Ok(self.first_existing(candidates, 0))
+    }
+
+    fn all_paths(&self) -> Result<TaskwarriorPaths, ConfigError> {
+        let data_dir = self.data_dir()?;
+        let config_dir = self.config_dir()?;
+        let taskrc = self.taskrc()?;
+        Ok(TaskwarriorPaths {
+            data_dir: data_dir.path,
+            config_dir: config_dir.path,
+            taskrc: taskrc.path,
+            sources: PathSources {
+                data_dir: data_dir.source,
+                config_dir: config_dir.source,
+                taskrc: taskrc.source,
+            },
         })
     }
 }
 
-/// Get all XDG-compliant paths for Taskwarrior
+/// Discover the default Taskwarrior data directory
+pub fn discover_data_dir() -> Result<PathBuf, ConfigError> {
+    Lookup::current().data_dir().map(|found| found.path)
+}
+
+/// Discover the default Taskwarrior config directory
+pub fn discover_config_dir() -> Result<PathBuf, ConfigError> {
+    Lookup::current().config_dir().map(|found| found.path)
+}
+
+/// Discover the default .taskrc file location
+pub fn discover_taskrc() -> Result<PathBuf, ConfigError> {
+    Lookup::current().taskrc().map(|found| found.path)
+}
+
+/// Discover a system-wide taskrc, read before the user's
+pub fn discover_system_taskrc() -> Option<PathBuf> {
+    // Priority order:
+    // 1. XDG_CONFIG_DIRS entries, each as <dir>/task/taskrc
+    // 2. /etc/taskrc
+
+    let xdg_dirs = env::var("XDG_CONFIG_DIRS").unwrap_or_default();
+    env::split_paths(&xdg_dirs)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join("task").join("taskrc"))
+        .chain(std::iter::once(PathBuf::from("/etc/taskrc")))
+        .find(|path| path.is_file())
+}
+
+/// Expand a leading `~/` in a path from the configuration
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Get all paths for Taskwarrior, with where each came from
 pub fn discover_all_paths() -> Result<TaskwarriorPaths, ConfigError> {
-    Ok(TaskwarriorPaths {
-        data_dir: discover_data_dir()?,
-        config_dir: discover_config_dir()?,
-        taskrc: discover_taskrc()?,
-    })
+    Lookup::current().all_paths()
 }
 
 /// Structure containing all discovered paths
@@ -144,6 +349,16 @@ pub struct TaskwarriorPaths {
     pub data_dir: PathBuf,
     pub config_dir: PathBuf,
     pub taskrc: PathBuf,
+    /// Where each path came from
+    pub sources: PathSources,
+}
+
+/// Sources of the paths in [`TaskwarriorPaths`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSources {
+    pub data_dir: PathSource,
+    pub config_dir: PathSource,
+    pub taskrc: PathSource,
 }
 
 impl TaskwarriorPaths {
@@ -157,6 +372,15 @@ impl TaskwarriorPaths {
         self.taskrc.parent().map(|p| p.to_path_buf())
     }
 
+    /// Each path with its name and source, e.g. for a diagnostics listing
+    pub fn entries(&self) -> [(&'static str, &Path, &PathSource); 3] {
+        [
+            ("data_dir", &self.data_dir, &self.sources.data_dir),
+            ("config_dir", &self.config_dir, &self.sources.config_dir),
+            ("taskrc", &self.taskrc, &self.sources.taskrc),
+        ]
+    }
+
     /// Validate that all paths are absolute
     pub fn validate(&self) -> Result<(), ConfigError> {
         let paths = [
@@ -257,6 +481,100 @@ mod tests {
         assert!(paths.validate().is_ok());
     }
 
+    fn lookup(platform: Platform, vars: &[(&str, &str)], existing: &[&str]) -> Lookup {
+        Lookup {
+            platform,
+            home: Some(PathBuf::from("/home/sam")),
+            vars: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            existing: Some(existing.iter().map(PathBuf::from).collect()),
+        }
+    }
+
+    #[test]
+    fn test_platform_defaults() {
+        let unix = lookup(Platform::Unix, &[], &[]).all_paths().unwrap();
+        assert_eq!(
+            unix.data_dir,
+            PathBuf::from("/home/sam/.local/share/taskwarrior")
+        );
+        assert_eq!(
+            unix.taskrc,
+            PathBuf::from("/home/sam/.config/taskwarrior/taskrc")
+        );
+        assert_eq!(unix.sources.data_dir, PathSource::Platform);
+
+        let mac = lookup(Platform::MacOs, &[], &[]).all_paths().unwrap();
+        assert_eq!(
+            mac.data_dir,
+            PathBuf::from("/home/sam/Library/Application Support/taskwarrior")
+        );
+        assert_eq!(
+            mac.config_dir,
+            PathBuf::from("/home/sam/Library/Application Support/taskwarrior")
+        );
+
+        let windows = lookup(Platform::Windows, &[("APPDATA", "/users/sam/roaming")], &[])
+            .all_paths()
+            .unwrap();
+        assert_eq!(
+            windows.config_dir,
+            PathBuf::from("/users/sam/roaming/taskwarrior")
+        );
+        assert_eq!(
+            windows.sources.config_dir,
+            PathSource::Environment("APPDATA".to_string())
+        );
+        assert_eq!(
+            windows.data_dir,
+            PathBuf::from("/home/sam/AppData/Local/taskwarrior")
+        );
+    }
+
+    #[test]
+    fn test_xdg_and_legacy_precedence() {
+        // XDG variables win on every platform
+        let xdg = lookup(Platform::MacOs, &[("XDG_DATA_HOME", "/data")], &[])
+            .data_dir()
+            .unwrap();
+        assert_eq!(xdg.path, PathBuf::from("/data/taskwarrior"));
+        assert_eq!(
+            xdg.source,
+            PathSource::Environment("XDG_DATA_HOME".to_string())
+        );
+
+        // Legacy locations are used only when present and nothing newer is
+        let legacy = lookup(
+            Platform::Unix,
+            &[],
+            &["/home/sam/.task", "/home/sam/.taskrc"],
+        )
+        .all_paths()
+        .unwrap();
+        assert_eq!(legacy.data_dir, PathBuf::from("/home/sam/.task"));
+        assert_eq!(legacy.taskrc, PathBuf::from("/home/sam/.taskrc"));
+        assert_eq!(legacy.sources.taskrc, PathSource::Legacy);
+
+        let both = lookup(
+            Platform::Unix,
+            &[],
+            &["/home/sam/.task", "/home/sam/.local/share/taskwarrior"],
+        );
+        assert_eq!(both.data_dir().unwrap().source, PathSource::Platform);
+
+        // A mac user's existing ~/.config/task keeps working
+        let mac = lookup(Platform::MacOs, &[], &["/home/sam/.config/task"]);
+        assert_eq!(
+            mac.config_dir().unwrap().path,
+            PathBuf::from("/home/sam/.config/task")
+        );
+
+        let bad = lookup(Platform::Unix, &[("TASKRC", "taskrc")], &[]);
+        assert!(matches!(bad.taskrc(), Err(ConfigError::InvalidPath { .. })));
+    }
+
     #[test]
     fn test_required_dirs() {
         let _guard = ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
@@ -0,0 +1,117 @@
+//! Everything a frontend derives from configuration, loaded at once
+//!
+//! Reports, UDAs, tags, colors, contexts, aliases and urgency coefficients
+//! are all spelled out in the rc file. Loading them separately lets each
+//! subsystem see a different snapshot of the settings; a
+//! [`ConfiguredEnvironment`] builds them all from the same
+//! [`Configuration`], so they always agree.
+
+use std::collections::BTreeMap;
+
+use crate::config::color::ColorRuleSet;
+use crate::config::context::{self, UserContext};
+use crate::config::{Configuration, PriorityScheme};
+use crate::error::ConfigError;
+use crate::reports::ReportManager;
+use crate::task::tags::TagRegistry;
+use crate::task::uda::UdaSchema;
+
+/// Subsystems configured from one [`Configuration`]
+#[derive(Debug)]
+pub struct ConfiguredEnvironment {
+    /// Built-in reports plus every `report.<name>.*` report
+    pub reports: ReportManager,
+    pub udas: UdaSchema,
+    pub tags: TagRegistry,
+    pub colors: ColorRuleSet,
+    pub contexts: Vec<UserContext>,
+    /// `alias.<name>` expansions
+    pub aliases: BTreeMap<String, String>,
+    /// `urgency.<term>.coefficient` values by term, e.g. `due` or
+    /// `user.tag.next`
+    pub urgency_coefficients: BTreeMap<String, f64>,
+    pub priority: PriorityScheme,
+}
+
+impl ConfiguredEnvironment {
+    /// Build every subsystem from `config`. Fails on malformed context
+    /// definitions or non-numeric urgency coefficients.
+    pub fn load(config: &Configuration) -> Result<Self, ConfigError> {
+        let mut aliases = BTreeMap::new();
+        let mut urgency_coefficients = BTreeMap::new();
+        for (key, value) in &config.settings {
+            if let Some(name) = key.strip_prefix("alias.") {
+                aliases.insert(name.to_string(), value.clone());
+            } else if let Some(term) = key.strip_prefix("urgency.").and_then(|k| k.strip_suffix(".coefficient")) {
+                let coefficient = value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+                    key: key.clone(),
+                    value: value.clone(),
+                    expected: "number".to_string(),
+                })?;
+                urgency_coefficients.insert(term.to_string(), coefficient);
+            }
+        }
+
+        Ok(Self {
+            reports: ReportManager::from_config(config),
+            udas: UdaSchema::from_config(config),
+            tags: TagRegistry::from_config(config),
+            colors: ColorRuleSet::from_config(config),
+            contexts: context::discover_contexts(&config.settings)?,
+            aliases,
+            urgency_coefficients,
+            priority: config.priority_scheme(),
+        })
+    }
+
+    /// The active context, if any
+    pub fn active_context(&self) -> Option<&UserContext> {
+        self.contexts.iter().find(|c| c.active)
+    }
+
+    /// Expansion of `word` if it is an alias, otherwise `word` itself
+    pub fn expand_alias<'a>(&'a self, word: &'a str) -> &'a str {
+        self.aliases.get(word).map(String::as_str).unwrap_or(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_from_one_configuration() {
+        let mut config = Configuration::default();
+        config.set("report.mine.columns", "id,description,due");
+        config.set("report.mine.filter", "status:pending +me limit:5");
+        config.set("report.mine.sort", "due+");
+        config.set("uda.estimate.type", "numeric");
+        config.set("uda.estimate.label", "Est");
+        config.set("tag.work.color", "blue");
+        config.set("context.work", "+work");
+        config.set("context", "work");
+        config.set("alias.rm", "delete");
+        config.set("urgency.user.tag.work.coefficient", "4");
+
+        let env = ConfiguredEnvironment::load(&config).unwrap();
+        let mine = env.reports.get_custom_report("mine").unwrap();
+        assert_eq!(mine.columns, vec!["id", "description", "due"]);
+        assert_eq!(mine.filter.as_deref(), Some("status:pending +me"));
+        assert_eq!(mine.limit, Some(5));
+        assert_eq!(mine.sort.as_deref(), Some("due+"));
+
+        let estimate = env.udas.get("estimate").unwrap();
+        assert_eq!(estimate.label.as_deref(), Some("Est"));
+        assert_eq!(env.udas.types().get("estimate"), Some(crate::task::UdaType::Numeric));
+        assert_eq!(env.tags.get("work").unwrap().color.as_deref(), Some("blue"));
+        assert_eq!(env.colors.get("tag.work"), Some("blue"));
+        assert_eq!(env.active_context().unwrap().read_filter, "+work");
+        assert_eq!(env.expand_alias("rm"), "delete");
+        assert_eq!(env.expand_alias("done"), "done");
+        assert_eq!(env.urgency_coefficients["user.tag.work"], 4.0);
+        assert_eq!(env.urgency_coefficients["due"], 12.0);
+
+        config.set("urgency.due.coefficient", "high");
+        assert!(ConfiguredEnvironment::load(&config).is_err());
+    }
+}
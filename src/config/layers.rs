@@ -0,0 +1,195 @@
+//! Layered configuration with provenance
+//!
+//! Settings are kept per layer and resolved by Taskwarrior's precedence,
+//! lowest first:
+//!
+//! 1. built-in defaults
+//! 2. the system rc (`/etc/taskrc` or `$XDG_CONFIG_DIRS/task/taskrc`)
+//! 3. the user rc (`~/.taskrc`, `$TASKRC`, ...)
+//! 4. files pulled in with `include`
+//! 5. environment variables (`TASKDATA`)
+//! 6. programmatic overrides ([`Configuration::set`](crate::config::Configuration::set))
+//!
+//! A higher layer always wins, whatever order files were read in. Within a
+//! layer the last assignment wins. Every value remembers where it came from,
+//! so `where did this value come from?` has an answer.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Configuration layers, ordered from lowest to highest precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigLayer {
+    Default,
+    SystemRc,
+    UserRc,
+    Include,
+    Environment,
+    Override,
+}
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// A line in an rc file
+    File {
+        layer: ConfigLayer,
+        path: PathBuf,
+        line: usize,
+    },
+    /// An environment variable
+    Environment { variable: String },
+    /// Set programmatically
+    Override,
+}
+
+impl ConfigSource {
+    /// Layer this source belongs to
+    pub fn layer(&self) -> ConfigLayer {
+        match self {
+            ConfigSource::Default => ConfigLayer::Default,
+            ConfigSource::File { layer, .. } => *layer,
+            ConfigSource::Environment { .. } => ConfigLayer::Environment,
+            ConfigSource::Override => ConfigLayer::Override,
+        }
+    }
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "built-in default"),
+            ConfigSource::File { layer, path, line } => {
+                let kind = match layer {
+                    ConfigLayer::SystemRc => "system rc",
+                    ConfigLayer::Include => "include",
+                    _ => "rc",
+                };
+                write!(f, "{kind} {}:{line}", path.display())
+            }
+            ConfigSource::Environment { variable } => write!(f, "environment variable {variable}"),
+            ConfigSource::Override => write!(f, "override"),
+        }
+    }
+}
+
+/// A value together with its origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+/// Settings stored per layer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayeredSettings {
+    layers: BTreeMap<ConfigLayer, HashMap<String, ConfigEntry>>,
+}
+
+impl LayeredSettings {
+    /// Create empty settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `key = value` in the layer of `source`, replacing any earlier
+    /// value in that layer
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>, source: ConfigSource) {
+        self.layers.entry(source.layer()).or_default().insert(
+            key.into(),
+            ConfigEntry {
+                value: value.into(),
+                source,
+            },
+        );
+    }
+
+    /// Remove `key` from every layer
+    pub fn remove(&mut self, key: &str) {
+        for layer in self.layers.values_mut() {
+            layer.remove(key);
+        }
+    }
+
+    /// Remove `key` from `lowest` and every layer above it
+    pub fn remove_from(&mut self, key: &str, lowest: ConfigLayer) {
+        for layer in self.layers.range_mut(lowest..).map(|(_, layer)| layer) {
+            layer.remove(key);
+        }
+    }
+
+    /// The winning entry for `key`
+    pub fn get(&self, key: &str) -> Option<&ConfigEntry> {
+        self.layers.values().rev().find_map(|layer| layer.get(key))
+    }
+
+    /// Every entry for `key`, lowest precedence first; the last one wins
+    pub fn history(&self, key: &str) -> Vec<&ConfigEntry> {
+        self.layers.values().filter_map(|layer| layer.get(key)).collect()
+    }
+
+    /// All keys with their winning values
+    pub fn resolve(&self) -> HashMap<String, String> {
+        let mut resolved = HashMap::new();
+        for layer in self.layers.values() {
+            for (key, entry) in layer {
+                resolved.insert(key.clone(), entry.value.clone());
+            }
+        }
+        resolved
+    }
+
+    /// Entries set in one layer
+    pub fn layer(&self, layer: ConfigLayer) -> impl Iterator<Item = (&String, &ConfigEntry)> {
+        self.layers.get(&layer).into_iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rc(layer: ConfigLayer, line: usize) -> ConfigSource {
+        ConfigSource::File {
+            layer,
+            path: PathBuf::from("/home/me/.taskrc"),
+            line,
+        }
+    }
+
+    #[test]
+    fn test_higher_layer_wins_regardless_of_order() {
+        let mut settings = LayeredSettings::new();
+        settings.insert("color", "on", ConfigSource::Override);
+        settings.insert("color", "off", rc(ConfigLayer::UserRc, 3));
+        settings.insert("color", "auto", ConfigSource::Default);
+
+        let entry = settings.get("color").unwrap();
+        assert_eq!(entry.value, "on");
+        assert_eq!(entry.source, ConfigSource::Override);
+
+        let history: Vec<_> = settings.history("color").iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(history, vec!["auto", "off", "on"]);
+        assert_eq!(settings.resolve()["color"], "on");
+
+        settings.remove_from("color", ConfigLayer::SystemRc);
+        assert_eq!(settings.get("color").unwrap().value, "auto");
+        settings.remove("color");
+        assert!(settings.get("color").is_none());
+    }
+
+    #[test]
+    fn test_source_display() {
+        assert_eq!(rc(ConfigLayer::Include, 7).to_string(), "include /home/me/.taskrc:7");
+        assert_eq!(
+            ConfigSource::Environment {
+                variable: "TASKDATA".to_string()
+            }
+            .to_string(),
+            "environment variable TASKDATA"
+        );
+    }
+}
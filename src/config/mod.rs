@@ -2,12 +2,25 @@
 //!
 //! This module provides configuration loading, validation, and management
 //! following XDG Base Directory specification and Taskwarrior conventions.
+//! Values are kept in [`layers`] so that precedence is explicit and every
+//! value can report where it came from.
 
 pub mod discovery;
+pub mod bundle;
+pub mod color;
 pub mod context;
+pub mod defaults;
+pub mod environment;
+pub mod layers;
+pub mod priority;
 
 use crate::error::{ConfigError, TaskError};
-use discovery::discover_all_paths;
+use discovery::{discover_all_paths, discover_system_taskrc};
+pub use bundle::{BundleConflict, BundleImport, ConfigBundle, ConflictStrategy};
+pub use color::{ColorRule, ColorRuleSet};
+pub use environment::ConfiguredEnvironment;
+pub use layers::{ConfigEntry, ConfigLayer, ConfigSource, LayeredSettings};
+pub use priority::PriorityScheme;
 use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -21,37 +34,78 @@ pub struct Configuration {
     pub data_dir: PathBuf,
     /// Configuration file path  
     pub config_file: PathBuf,
-    /// All configuration key-value pairs
+    /// Effective configuration key-value pairs, resolved from all layers
     pub settings: HashMap<String, String>,
     /// Whether to create missing directories
     pub create_dirs: bool,
+    /// Values per layer, with their sources; use [`Configuration::insert`]
+    /// to change them so `settings` stays in step
+    #[serde(skip)]
+    pub layers: LayeredSettings,
+    /// Problems skipped while loading under [`IncludePolicy::Warn`], for
+    /// the caller to show however it shows messages
+    #[serde(skip)]
+    pub warnings: Vec<String>,
 }
 
 impl Default for Configuration {
+    /// Configuration holding only the built-in defaults
     fn default() -> Self {
-        Self {
+        let mut config = Self {
             data_dir: PathBuf::from(".taskwarrior"),
             config_file: PathBuf::from(".taskrc"),
             settings: HashMap::new(),
             create_dirs: true,
+            layers: LayeredSettings::new(),
+            warnings: Vec::new(),
+        };
+        for (key, value) in defaults::DEFAULTS {
+            config.insert(*key, *value, ConfigSource::Default);
         }
+        config
     }
 }
 
 impl Configuration {
     /// Create configuration from XDG paths
+    ///
+    /// Reads the system rc, then the user rc and its includes, then the
+    /// `TASKDATA` environment variable.
     pub fn from_xdg() -> Result<Self, ConfigError> {
+        Self::from_xdg_with(IncludePolicy::default())
+    }
+
+    /// [`from_xdg`](Self::from_xdg) with a policy for broken includes
+    pub fn from_xdg_with(policy: IncludePolicy) -> Result<Self, ConfigError> {
+        Self::from_xdg_inner(policy, ParseMode::Strict)
+    }
+
+    fn from_xdg_inner(policy: IncludePolicy, mode: ParseMode) -> Result<Self, ConfigError> {
         let paths = discover_all_paths()?;
         let mut config = Self {
             data_dir: paths.data_dir,
             config_file: paths.taskrc.clone(),
-            settings: HashMap::new(),
-            create_dirs: true,
+            ..Default::default()
         };
 
+        if let Some(system_rc) = discover_system_taskrc() {
+            config.load_layer(&system_rc, ConfigLayer::SystemRc, policy, mode)?;
+        }
+
         // Load settings from .taskrc if it exists
         if config.config_file.exists() {
-            config.load_from_file(&paths.taskrc)?;
+            config.load_layer(&paths.taskrc, ConfigLayer::UserRc, policy, mode)?;
+        }
+
+        // discover_data_dir has already validated TASKDATA
+        if let Ok(taskdata) = std::env::var("TASKDATA") {
+            config.insert(
+                "data.location",
+                taskdata,
+                ConfigSource::Environment {
+                    variable: "TASKDATA".to_string(),
+                },
+            );
         }
 
         Ok(config)
@@ -59,35 +113,92 @@ impl Configuration {
 
     /// Load configuration from a specific file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
-        let path = path.as_ref();
+        Self::from_file_with(path, IncludePolicy::default())
+    }
+
+    /// [`from_file`](Self::from_file) with a policy for broken includes
+    pub fn from_file_with<P: AsRef<Path>>(path: P, policy: IncludePolicy) -> Result<Self, ConfigError> {
+        Self::from_file_inner(path.as_ref(), policy, ParseMode::Strict)
+    }
+
+    fn from_file_inner(path: &Path, policy: IncludePolicy, mode: ParseMode) -> Result<Self, ConfigError> {
         let mut config: Configuration = Configuration {
             config_file: path.to_path_buf(),
             ..Default::default()
         };
-        config.load_from_file(path)?;
+        config.load_layer(path, ConfigLayer::UserRc, policy, mode)?;
         Ok(config)
     }
 
-    /// Load settings from .taskrc file
-    fn load_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigError> {
-        // Use a visited set to avoid recursive include loops
-        let mut visited: HashSet<PathBuf> = HashSet::new();
-        let start = path.as_ref().to_path_buf();
-        self.load_from_file_inner(&start, &mut visited)
+    /// Parse rc settings held in memory, e.g. received from another process.
+    /// There is no file to resolve `include` lines against, so they count
+    /// as malformed: an error in strict mode, skipped in lenient mode.
+    pub fn from_rc_str(content: &str, mode: ParseMode) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for (line_num, line) in content.lines().enumerate() {
+            match parse_rc_line(line) {
+                RcLine::Blank => {}
+                RcLine::Setting(key, value) => {
+                    let source = ConfigSource::File {
+                        layer: ConfigLayer::UserRc,
+                        path: PathBuf::new(),
+                        line: line_num + 1,
+                    };
+                    config.insert(key, value, source);
+                }
+                RcLine::Include(_) | RcLine::Malformed if mode == ParseMode::Lenient => {}
+                RcLine::Include(_) | RcLine::Malformed => {
+                    return Err(ConfigError::ParseError {
+                        path: PathBuf::new(),
+                        line: line_num + 1,
+                        content: line.trim().to_string(),
+                        include_chain: Vec::new(),
+                    });
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Load an rc file into `layer`; its includes go to the include layer
+    fn load_layer(
+        &mut self,
+        path: &Path,
+        layer: ConfigLayer,
+        policy: IncludePolicy,
+        mode: ParseMode,
+    ) -> Result<(), ConfigError> {
+        let mut state = IncludeState {
+            policy,
+            mode,
+            visited: HashSet::new(),
+            chain: Vec::new(),
+        };
+        self.load_from_file_inner(path, layer, &mut state)
     }
 
     // Internal helper that tracks visited files and supports include/import
     fn load_from_file_inner(
         &mut self,
         path: &Path,
-        visited: &mut HashSet<PathBuf>,
+        layer: ConfigLayer,
+        state: &mut IncludeState,
     ) -> Result<(), ConfigError> {
-        // Prevent include cycles
         let canon = path.to_path_buf();
-        if visited.contains(&canon) {
+        // A file including one of its own includers would loop forever
+        if state.chain.contains(&canon) {
+            return state.policy.apply(
+                ConfigError::IncludeCycle {
+                    path: canon,
+                    include_chain: state.chain.clone(),
+                },
+                &mut self.warnings,
+            );
+        }
+        // Already read through another include; reading it again changes nothing
+        if !state.visited.insert(canon.clone()) {
             return Ok(());
         }
-        visited.insert(canon.clone());
 
         let content = fs::read_to_string(path).map_err(|e| ConfigError::Io {
             path: path.to_path_buf(),
@@ -95,95 +206,107 @@ impl Configuration {
         })?;
 
         let parent = path.parent().map(|p| p.to_path_buf()).unwrap_or_else(|| PathBuf::from("."));
+        state.chain.push(canon);
 
         for (line_num, line) in content.lines().enumerate() {
-            let line = line.trim();
-
-            // Skip comments and empty lines
-            if line.is_empty() || line.starts_with('#') {
-                continue;
-            }
-
-            // Support include/import directives in two forms:
-            //   include /absolute/or/relative/path
-            //   include=/path
-            // Also accept `import` as an alias
-                if line.starts_with("include ") || line.starts_with("import ") {
-                if let Some((_kw, rest)) = line.split_once(' ') {
-                    let mut inc = rest.trim().to_string();
-                    if (inc.starts_with('"') && inc.ends_with('"')) || (inc.starts_with('\'') && inc.ends_with('\'')) {
-                        inc = inc[1..inc.len()-1].to_string();
-                    }
-                    let inc_path = PathBuf::from(inc);
-                    let resolved = if inc_path.is_relative() { parent.join(inc_path) } else { inc_path };
-                    // If an included file is missing, warn and continue instead of failing.
-                    if !resolved.exists() {
-                        eprintln!("Configuration: include/import not found, skipping: {}", resolved.display());
-                        continue;
-                    }
-                    if let Err(e) = self.load_from_file_inner(&resolved, visited) {
-                        eprintln!("Configuration: failed to load included file {}: {}", resolved.display(), e);
-                        continue;
-                    }
-                    continue;
+            match parse_rc_line(line) {
+                RcLine::Blank => {}
+                RcLine::Include(target) => self.include(&parent, &target, line_num + 1, state)?,
+                RcLine::Setting(key, value) => {
+                    let source = ConfigSource::File {
+                        layer,
+                        path: path.to_path_buf(),
+                        line: line_num + 1,
+                    };
+                    self.insert(key, value, source);
                 }
-            }
-
-            // Parse key=value pairs (also accept `key value`? For now use key=value)
-            if let Some((raw_key, raw_value)) = line.split_once('=') {
-                let mut key = raw_key.trim().to_string();
-                // Normalize common Taskwarrior rc. prefix: accept keys like `rc.context.home`
-                if key.starts_with("rc.") {
-                    key = key.trim_start_matches("rc.").to_string();
+                RcLine::Malformed if state.mode == ParseMode::Lenient => {}
+                RcLine::Malformed => {
+                    state.chain.pop();
+                    return Err(ConfigError::ParseError {
+                        path: path.to_path_buf(),
+                        line: line_num + 1,
+                        content: line.trim().to_string(),
+                        include_chain: state.chain.clone(),
+                    });
                 }
-
-                // Unquote values if they are wrapped in single or double quotes
-                let mut value = raw_value.trim().to_string();
-                if (value.starts_with('"') && value.ends_with('"')) || (value.starts_with('\'') && value.ends_with('\'')) {
-                    // strip outer quotes
-                    value = value[1..value.len()-1].to_string();
-                }
-
-                // Handle include/import written as key=value
-                if key == "include" || key == "import" {
-                    let inc_path = PathBuf::from(value);
-                    let resolved = if inc_path.is_relative() { parent.join(inc_path) } else { inc_path };
-                    if !resolved.exists() {
-                        eprintln!("Configuration: include/import not found (key form), skipping: {}", resolved.display());
-                        continue;
-                    }
-                    if let Err(e) = self.load_from_file_inner(&resolved, visited) {
-                        eprintln!("Configuration: failed to load included file {}: {}", resolved.display(), e);
-                        continue;
-                    }
-                    continue;
-                }
-
-                // Handle special keys
-                match key.as_str() {
-                    "data.location" => {
-                        self.data_dir = PathBuf::from(value);
-                    }
-                    _ => {
-                        self.settings.insert(key, value);
-                    }
-                }
-            } else {
-                return Err(ConfigError::ParseError {
-                    line: line_num + 1,
-                    content: line.to_string(),
-                });
             }
         }
 
+        state.chain.pop();
         Ok(())
     }
 
+    // Load an included file into the include layer
+    fn include(
+        &mut self,
+        parent: &Path,
+        target: &str,
+        line: usize,
+        state: &mut IncludeState,
+    ) -> Result<(), ConfigError> {
+        let inc_path = PathBuf::from(target);
+        let resolved = if inc_path.is_relative() { parent.join(inc_path) } else { inc_path };
+        if !resolved.exists() {
+            return state.policy.apply(
+                ConfigError::MissingInclude {
+                    path: resolved,
+                    included_from: state.chain.last().cloned().unwrap_or_default(),
+                    line,
+                },
+                &mut self.warnings,
+            );
+        }
+        self.load_from_file_inner(&resolved, ConfigLayer::Include, state)
+    }
+
     /// Get a configuration value
     pub fn get(&self, key: &str) -> Option<&String> {
         self.settings.get(key)
     }
 
+    /// Where the effective value of `key` came from
+    ///
+    /// None when the key is unset, or was written to `settings` directly.
+    pub fn source(&self, key: &str) -> Option<&ConfigSource> {
+        self.layers.get(key).map(|entry| &entry.source)
+    }
+
+    /// Every value recorded for `key`, lowest precedence first; the last one
+    /// is the effective value and the others are shadowed
+    pub fn explain(&self, key: &str) -> Vec<&ConfigEntry> {
+        self.layers.history(key)
+    }
+
+    /// Record a value in the layer of `source` and refresh the effective value
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V, source: ConfigSource) {
+        let key = key.into();
+        self.layers.insert(key.clone(), value, source);
+        self.refresh(&key);
+    }
+
+    /// Remove a key from every layer except the built-in defaults, so it
+    /// falls back to its default if it has one
+    pub fn unset(&mut self, key: &str) {
+        self.layers.remove_from(key, ConfigLayer::SystemRc);
+        self.refresh(key);
+    }
+
+    // Recompute the effective value of `key` from the layers
+    fn refresh(&mut self, key: &str) {
+        match self.layers.get(key) {
+            Some(entry) => {
+                if key == "data.location" {
+                    self.data_dir = PathBuf::from(&entry.value);
+                }
+                self.settings.insert(key.to_string(), entry.value.clone());
+            }
+            None => {
+                self.settings.remove(key);
+            }
+        }
+    }
+
     /// Discover contexts from current settings
     pub fn discover_contexts(&self) -> Result<Vec<context::UserContext>, ConfigError> {
         context::discover_contexts(&self.settings)
@@ -197,9 +320,41 @@ impl Configuration {
             .unwrap_or_else(|| default.to_string())
     }
 
-    /// Set a configuration value
+    /// Get a boolean configuration value (true/false, on/off, yes/no, 1/0)
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.settings.get(key)?.to_lowercase().as_str() {
+            "true" | "on" | "yes" | "y" | "1" => Some(true),
+            "false" | "off" | "no" | "n" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Owner to stamp on newly created tasks.
+    ///
+    /// Returns `user.name` when `owner.stamp` is enabled, otherwise None.
+    pub fn task_owner(&self) -> Option<String> {
+        if self.get_bool("owner.stamp").unwrap_or(false) {
+            self.get("user.name").cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Priority scheme from `uda.priority.values`, defaulting to `H,M,L,`
+    pub fn priority_scheme(&self) -> PriorityScheme {
+        PriorityScheme::from_config(self)
+    }
+
+    /// Reports, UDAs, tags, colors, contexts, aliases and urgency
+    /// coefficients, all built from this configuration
+    pub fn environment(&self) -> Result<ConfiguredEnvironment, ConfigError> {
+        ConfiguredEnvironment::load(self)
+    }
+
+    /// Set a configuration value as a programmatic override, which takes
+    /// precedence over every file and the environment
     pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
-        self.settings.insert(key.into(), value.into());
+        self.insert(key, value, ConfigSource::Override);
     }
 
     /// Get the task data file path
@@ -258,6 +413,98 @@ impl Configuration {
     }
 }
 
+/// What to do when an `include` names a missing file or would loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IncludePolicy {
+    /// Skip the include and record the error in
+    /// [`Configuration::warnings`]
+    #[default]
+    Warn,
+    /// Fail loading with the error
+    Error,
+}
+
+impl IncludePolicy {
+    fn apply(self, error: ConfigError, warnings: &mut Vec<String>) -> Result<(), ConfigError> {
+        match self {
+            IncludePolicy::Warn => {
+                warnings.push(format!("{error}, skipping"));
+                Ok(())
+            }
+            IncludePolicy::Error => Err(error),
+        }
+    }
+}
+
+/// How parsers treat malformed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Reject the whole input at the first malformed entry
+    #[default]
+    Strict,
+    /// Skip malformed entries and keep the rest
+    Lenient,
+}
+
+// State carried through one rc file and everything it includes
+struct IncludeState {
+    policy: IncludePolicy,
+    mode: ParseMode,
+    visited: HashSet<PathBuf>,
+    /// Files currently being read, outermost first
+    chain: Vec<PathBuf>,
+}
+
+// One rc file line, classified
+enum RcLine {
+    /// Empty or a comment
+    Blank,
+    Include(String),
+    Setting(String, String),
+    Malformed,
+}
+
+fn parse_rc_line(line: &str) -> RcLine {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return RcLine::Blank;
+    }
+
+    // Support include/import directives in two forms:
+    //   include /absolute/or/relative/path
+    //   include=/path
+    // Also accept `import` as an alias
+    if line.starts_with("include ") || line.starts_with("import ") {
+        if let Some((_kw, rest)) = line.split_once(' ') {
+            return RcLine::Include(unquote(rest.trim()));
+        }
+    }
+
+    let Some((raw_key, raw_value)) = line.split_once('=') else {
+        return RcLine::Malformed;
+    };
+    // Normalize common Taskwarrior rc. prefix: accept keys like `rc.context.home`
+    let key = raw_key.trim().trim_start_matches("rc.").to_string();
+    // Unquote values if they are wrapped in single or double quotes
+    let value = unquote(raw_value.trim());
+    if key == "include" || key == "import" {
+        RcLine::Include(value)
+    } else {
+        RcLine::Setting(key, value)
+    }
+}
+
+// Strip one pair of matching single or double quotes
+fn unquote(s: &str) -> String {
+    let quoted = s.len() >= 2
+        && ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')));
+    if quoted {
+        s[1..s.len() - 1].to_string()
+    } else {
+        s.to_string()
+    }
+}
+
 /// Configuration builder for programmatic setup
 #[derive(Debug, Default)]
 pub struct ConfigurationBuilder {
@@ -265,6 +512,8 @@ pub struct ConfigurationBuilder {
     config_file: Option<PathBuf>,
     overrides: HashMap<String, String>,
     create_dirs: bool,
+    include_policy: IncludePolicy,
+    parse_mode: ParseMode,
 }
 
 impl ConfigurationBuilder {
@@ -300,16 +549,31 @@ impl ConfigurationBuilder {
         self
     }
 
+    /// Set what to do about missing or looping includes
+    pub fn include_policy(mut self, policy: IncludePolicy) -> Self {
+        self.include_policy = policy;
+        self
+    }
+
+    /// Set whether malformed rc lines fail the build (the default) or
+    /// are skipped
+    pub fn parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
     /// Build the configuration
     pub fn build(self) -> Result<Configuration, ConfigError> {
         let mut config = if let Some(config_file) = self.config_file {
-            Configuration::from_file(config_file)?
+            Configuration::from_file_inner(&config_file, self.include_policy, self.parse_mode)?
         } else {
-            Configuration::from_xdg()?
+            Configuration::from_xdg_inner(self.include_policy, self.parse_mode)?
         };
 
         // Apply overrides
         if let Some(data_dir) = self.data_dir {
+            config.set("data.location", data_dir.to_string_lossy());
+            // Keep the exact path even if it is not valid UTF-8
             config.data_dir = data_dir;
         }
 
@@ -367,6 +631,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_task_owner_requires_stamp() {
+        let mut config = Configuration::default();
+        config.set("user.name", "alice");
+        assert_eq!(config.task_owner(), None);
+
+        config.set("owner.stamp", "on");
+        assert_eq!(config.get_bool("owner.stamp"), Some(true));
+        assert_eq!(config.task_owner(), Some("alice".to_string()));
+    }
+
     #[test]
     fn test_taskrc_parsing() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
@@ -383,6 +658,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_rc_str_parse_modes() {
+        let content = "verbose=on\nthis is not a setting\ninclude extra.rc\nrc.color=off\n";
+        let err = Configuration::from_rc_str(content, ParseMode::Strict).unwrap_err();
+        assert!(matches!(err, ConfigError::ParseError { line: 2, .. }));
+
+        let config = Configuration::from_rc_str(content, ParseMode::Lenient).unwrap();
+        assert_eq!(config.get("verbose"), Some(&"on".to_string()));
+        assert_eq!(config.get("color"), Some(&"off".to_string()));
+    }
+
     #[test]
     fn test_taskrc_includes_other_file() -> Result<(), Box<dyn std::error::Error>> {
         use tempfile::NamedTempFile;
@@ -404,4 +690,114 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_builtin_defaults() {
+        let mut config = Configuration::default();
+        assert_eq!(config.get("urgency.due.coefficient"), Some(&"12.0".to_string()));
+        assert_eq!(config.source("report.next.sort"), Some(&ConfigSource::Default));
+        assert!(config.validate().is_ok());
+
+        config.set("urgency.due.coefficient", "20.0");
+        assert_eq!(config.get("urgency.due.coefficient"), Some(&"20.0".to_string()));
+        config.unset("urgency.due.coefficient");
+        assert_eq!(config.get("urgency.due.coefficient"), Some(&"12.0".to_string()));
+    }
+
+    #[test]
+    fn test_layer_precedence_and_provenance() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let taskrc_path = temp_dir.path().join(".taskrc");
+        let include_path = temp_dir.path().join("extra.rc");
+        fs::write(&include_path, "color=off\nverbose=nothing\n")?;
+        fs::write(&taskrc_path, "include extra.rc\ncolor=on\nverbose=on\ndata.location=/tmp/rc\n")?;
+
+        let mut config = Configuration::from_file(&taskrc_path)?;
+        // The include wins over the user rc even though it was read first
+        assert_eq!(config.get("color"), Some(&"off".to_string()));
+        assert_eq!(
+            config.source("color"),
+            Some(&ConfigSource::File {
+                layer: ConfigLayer::Include,
+                path: include_path.clone(),
+                line: 1,
+            })
+        );
+        let history: Vec<_> = config.explain("color").iter().map(|e| e.value.as_str()).collect();
+        assert_eq!(history, vec!["1", "on", "off"]);
+        assert_eq!(config.data_dir, PathBuf::from("/tmp/rc"));
+
+        config.set("verbose", "off");
+        config.set("data.location", "/tmp/override");
+        assert_eq!(config.get("verbose"), Some(&"off".to_string()));
+        assert_eq!(config.source("verbose"), Some(&ConfigSource::Override));
+        assert_eq!(config.explain("verbose").len(), 3);
+        assert_eq!(config.data_dir, PathBuf::from("/tmp/override"));
+
+        config.unset("verbose");
+        assert_eq!(config.get("verbose"), None);
+        assert!(config.explain("verbose").is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_reports_include_chain() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let taskrc_path = temp_dir.path().join(".taskrc");
+        let include_path = temp_dir.path().join("broken.rc");
+        fs::write(&include_path, "color=on\nthis is not a setting\n")?;
+        fs::write(&taskrc_path, "verbose=on\ninclude broken.rc\n")?;
+
+        match Configuration::from_file(&taskrc_path) {
+            Err(ConfigError::ParseError {
+                path,
+                line,
+                content,
+                include_chain,
+            }) => {
+                assert_eq!(path, include_path);
+                assert_eq!(line, 2);
+                assert_eq!(content, "this is not a setting");
+                assert_eq!(include_chain, vec![taskrc_path.clone()]);
+            }
+            other => panic!("expected parse error, got {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_policy() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let taskrc_path = temp_dir.path().join(".taskrc");
+        let loop_path = temp_dir.path().join("loop.rc");
+        fs::write(&taskrc_path, "include loop.rc\ninclude missing.rc\n")?;
+        fs::write(&loop_path, "color=off\ninclude .taskrc\n")?;
+
+        // Warnings skip both the cycle and the missing file
+        let config = Configuration::from_file(&taskrc_path)?;
+        assert_eq!(config.get("color"), Some(&"off".to_string()));
+        assert_eq!(config.warnings.len(), 2);
+        assert!(config.warnings[0].starts_with("Include cycle"));
+        assert!(config.warnings[1].starts_with("Included file not found"));
+
+        match Configuration::from_file_with(&taskrc_path, IncludePolicy::Error) {
+            Err(ConfigError::IncludeCycle { path, include_chain }) => {
+                assert_eq!(path, taskrc_path);
+                assert_eq!(include_chain, vec![taskrc_path.clone(), loop_path.clone()]);
+            }
+            other => panic!("expected include cycle, got {other:?}"),
+        }
+
+        fs::write(&loop_path, "color=off\n")?;
+        match Configuration::from_file_with(&taskrc_path, IncludePolicy::Error) {
+            Err(ConfigError::MissingInclude { path, included_from, line }) => {
+                assert_eq!(path, temp_dir.path().join("missing.rc"));
+                assert_eq!(included_from, taskrc_path);
+                assert_eq!(line, 2);
+            }
+            other => panic!("expected missing include, got {other:?}"),
+        }
+        Ok(())
+    }
 }
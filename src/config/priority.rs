@@ -0,0 +1,153 @@
+//! Configurable priority scheme
+//!
+//! Taskwarrior lets users override the allowed priority values through
+//! `uda.priority.values` (default `H,M,L,`), listed from highest to lowest.
+//! An empty entry marks where tasks without a priority rank. Each value can
+//! carry its own urgency coefficient via
+//! `urgency.uda.priority.<value>.coefficient`.
+//!
+//! The typed [`Priority`](crate::task::Priority) enum still covers the default
+//! `H`/`M`/`L` values; custom values are carried as the `priority` UDA and
+//! read back through [`Task::priority_code`](crate::task::Task::priority_code).
+
+use crate::config::Configuration;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Ordered set of allowed priority values with urgency coefficients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorityScheme {
+    /// Values from highest to lowest; an empty string marks "no priority"
+    values: Vec<String>,
+    coefficients: HashMap<String, f64>,
+}
+
+impl Default for PriorityScheme {
+    fn default() -> Self {
+        Self::new(["H", "M", "L", ""])
+    }
+}
+
+impl PriorityScheme {
+    /// Build a scheme from values listed highest first, using Taskwarrior's
+    /// default coefficients for `H`, `M` and `L` and zero for anything else
+    pub fn new<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let values: Vec<String> = values.into_iter().map(|v| v.into().trim().to_string()).collect();
+        let coefficients = values
+            .iter()
+            .filter(|v| !v.is_empty())
+            .map(|v| (v.clone(), default_coefficient(v)))
+            .collect();
+        Self { values, coefficients }
+    }
+
+    /// Load the scheme from `uda.priority.values` and
+    /// `urgency.uda.priority.<value>.coefficient`
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut scheme = match config.get("uda.priority.values") {
+            Some(raw) => Self::new(raw.split(',')),
+            None => Self::default(),
+        };
+        for value in scheme.values.clone() {
+            if value.is_empty() {
+                continue;
+            }
+            let key = format!("urgency.uda.priority.{value}.coefficient");
+            if let Some(coefficient) = config.get(&key).and_then(|c| c.trim().parse().ok()) {
+                scheme.coefficients.insert(value, coefficient);
+            }
+        }
+        scheme
+    }
+
+    /// Set the urgency coefficient for a value
+    pub fn with_coefficient<S: Into<String>>(mut self, value: S, coefficient: f64) -> Self {
+        self.coefficients.insert(value.into(), coefficient);
+        self
+    }
+
+    /// Allowed values, highest first, excluding the "no priority" marker
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().map(String::as_str).filter(|v| !v.is_empty())
+    }
+
+    /// Whether tasks may have no priority
+    pub fn allows_none(&self) -> bool {
+        self.values.iter().any(|v| v.is_empty())
+    }
+
+    /// Whether `code` is an allowed priority value
+    pub fn is_valid(&self, code: &str) -> bool {
+        self.values().any(|v| v == code)
+    }
+
+    /// Position of a value in the scheme, 0 being the highest.
+    ///
+    /// A missing priority ranks at the empty entry, or after all values if
+    /// the scheme has none; unknown values rank after everything.
+    pub fn rank(&self, code: Option<&str>) -> usize {
+        let code = code.unwrap_or("");
+        self.values
+            .iter()
+            .position(|v| v == code)
+            .unwrap_or(if code.is_empty() {
+                self.values.len()
+            } else {
+                self.values.len() + 1
+            })
+    }
+
+    /// Order two priority codes, highest priority first
+    pub fn compare(&self, a: Option<&str>, b: Option<&str>) -> Ordering {
+        self.rank(a).cmp(&self.rank(b))
+    }
+
+    /// Urgency coefficient for a value (0.0 when unknown)
+    pub fn coefficient(&self, code: &str) -> f64 {
+        self.coefficients.get(code).copied().unwrap_or(0.0)
+    }
+}
+
+fn default_coefficient(value: &str) -> f64 {
+    match value {
+        "H" => 6.0,
+        "M" => 3.9,
+        "L" => 1.8,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_scheme() {
+        let scheme = PriorityScheme::default();
+        assert_eq!(scheme.values().collect::<Vec<_>>(), vec!["H", "M", "L"]);
+        assert!(scheme.allows_none());
+        assert_eq!(scheme.coefficient("H"), 6.0);
+        assert_eq!(scheme.compare(Some("H"), Some("L")), Ordering::Less);
+        assert_eq!(scheme.compare(None, Some("L")), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_scheme_from_config() {
+        let mut config = Configuration::default();
+        config.set("uda.priority.values", "C,H,M,L,");
+        config.set("urgency.uda.priority.C.coefficient", "9.5");
+
+        let scheme = PriorityScheme::from_config(&config);
+        assert!(scheme.is_valid("C"));
+        assert!(!scheme.is_valid("X"));
+        assert_eq!(scheme.rank(Some("C")), 0);
+        assert_eq!(scheme.coefficient("C"), 9.5);
+        assert_eq!(scheme.coefficient("M"), 3.9);
+        assert_eq!(scheme.compare(Some("X"), None), Ordering::Greater);
+    }
+}
@@ -0,0 +1,35 @@
+//! The I/O-free core of the library
+//!
+//! Everything re-exported here works on values alone: the task model,
+//! query evaluation, urgency scoring, dependency analysis and date math.
+//! None of it touches the filesystem, spawns processes or reads the
+//! environment, and the current time comes from [`clock`], so the same
+//! code runs in WASM, embedded hosts and deterministic tests.
+//!
+//! This is a module rather than a separate crate; code that sticks to
+//! these paths keeps working if it is split out later.
+//!
+//! ```rust
+//! use chrono::{Duration, TimeZone, Utc};
+//! use taskwarrior3lib::core::{Task, UrgencyCoefficients};
+//!
+//! let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+//! let mut task = Task::new("Pay rent".to_string());
+//! task.entry = now;
+//! task.due = Some(now - Duration::days(7));
+//! assert_eq!(UrgencyCoefficients::new().score(&task, now), 12.0);
+//! ```
+
+pub use crate::clock;
+pub use crate::date::relative::{
+    add_duration, format_iso_duration, parse_duration, parse_iso_duration, subtract_duration,
+};
+pub use crate::date::{DateParser, DateSynonym};
+pub use crate::query::collation::Collation;
+pub use crate::query::{
+    sort_tasks, DateFilter, OwnerFilter, PriorityFilter, ProjectFilter, SortCriteria, SortField,
+    TagFilter, TaskQuery,
+};
+pub use crate::task::model::UdaValue;
+pub use crate::task::urgency::{due_factor, AgingCurve, PriorityAging, UrgencyCoefficients};
+pub use crate::task::{Annotation, DependencyGraph, Priority, RecurrencePattern, Task, TaskStatus};
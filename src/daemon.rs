@@ -0,0 +1,573 @@
+//! Long-running task service
+//!
+//! [`TaskService`] owns a [`DefaultTaskManager`] on a dedicated thread and
+//! serves requests sent through cloneable [`ServiceClient`] handles. Between
+//! requests it runs scheduled maintenance (promoting waiting tasks whose
+//! wait date has passed, deadline escalation, then the retention purge)
+//! and, when their intervals are set, automatic sync and backups to the
+//! target in `backup.target` (see [`backup`](crate::backup)). Every change is
+//! broadcast as a [`ServiceEvent`] to subscribers, so a TUI or tray icon can
+//! refresh without polling.
+//!
+//! The storage backend, hooks and sync manager are not `Send`, so the
+//! manager is built on the service thread by the factory passed to
+//! [`TaskService::start`].
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::daemon::{ServiceConfig, TaskService};
+//! use taskwarrior3lib::task::manager::TaskManagerBuilder;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let service = TaskService::start(ServiceConfig::default(), || TaskManagerBuilder::new().build())?;
+//! let events = service.subscribe();
+//! let client = service.client();
+//!
+//! client.add_task("Water the plants".to_string())?;
+//! println!("{:?}", events.recv()?);
+//! service.shutdown()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::backup::{BackupRecord, BackupRunner};
+use crate::config::ConfigurationProvider;
+use crate::error::{ConfigError, TaskError};
+use crate::query::TaskQuery;
+use crate::task::manager::{DefaultTaskManager, SyncResult, TaskManager, TaskUpdate};
+use crate::task::escalation::EscalationReport;
+use crate::task::metrics::Metrics;
+use crate::task::retention::PurgeReport;
+use crate::task::Task;
+
+/// Scheduling options for [`TaskService`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceConfig {
+    /// How often to run maintenance; None disables it
+    pub maintenance_interval: Option<Duration>,
+    /// How often to sync; None disables automatic sync
+    pub sync_interval: Option<Duration>,
+    /// How often to back up to `backup.target`; None disables automatic
+    /// backups
+    pub backup_interval: Option<Duration>,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        Self {
+            maintenance_interval: Some(Duration::from_secs(60 * 60)),
+            sync_interval: None,
+            backup_interval: None,
+        }
+    }
+}
+
+impl ServiceConfig {
+    /// Set the maintenance interval
+    pub fn maintenance_every(mut self, interval: Duration) -> Self {
+        self.maintenance_interval = Some(interval);
+        self
+    }
+
+    /// Set the automatic sync interval
+    pub fn sync_every(mut self, interval: Duration) -> Self {
+        self.sync_interval = Some(interval);
+        self
+    }
+
+    /// Set the automatic backup interval
+    pub fn backup_every(mut self, interval: Duration) -> Self {
+        self.backup_interval = Some(interval);
+        self
+    }
+}
+
+/// Notification broadcast to subscribers
+#[derive(Debug, Clone)]
+pub enum ServiceEvent {
+    TaskAdded(Task),
+    TaskUpdated(Task),
+    TaskCompleted(Task),
+    TaskDeleted(Task),
+    /// A sync finished, either requested or automatic
+    Synced(SyncResult),
+    /// A sync failed; carries the error message
+    SyncFailed(String),
+    /// Maintenance escalated the listed tasks; each also comes as
+    /// [`TaskUpdated`](ServiceEvent::TaskUpdated)
+    Escalated(EscalationReport),
+    /// Maintenance ran and purged the listed tasks; a purge that stopped
+    /// early is followed by [`MaintenanceFailed`](ServiceEvent::MaintenanceFailed)
+    MaintenanceCompleted(PurgeReport),
+    /// Maintenance failed; carries the error message
+    MaintenanceFailed(String),
+    /// A backup was made, either requested or automatic
+    BackedUp(BackupRecord),
+    /// A backup failed; carries the error message
+    BackupFailed(String),
+    /// The service thread is exiting
+    Stopped,
+}
+
+type Reply<T> = Sender<Result<T, TaskError>>;
+
+/// Request processed on the service thread
+enum Request {
+    Add(String, Reply<Task>),
+    Get(Uuid, Reply<Option<Task>>),
+    Update(Uuid, TaskUpdate, Reply<Task>),
+    Complete(Uuid, Reply<Task>),
+    Delete(Uuid, Reply<Task>),
+    Query(TaskQuery, Reply<Vec<Task>>),
+    Sync(Reply<SyncResult>),
+    Maintenance(Reply<PurgeReport>),
+    Backup(Reply<BackupRecord>),
+    Metrics(Reply<Metrics>),
+    Ping(Reply<()>),
+    Shutdown,
+}
+
+/// Cloneable handle for sending requests to a running [`TaskService`]
+#[derive(Debug, Clone)]
+pub struct ServiceClient {
+    sender: Sender<Request>,
+}
+
+impl ServiceClient {
+    fn call<T>(&self, make: impl FnOnce(Reply<T>) -> Request) -> Result<T, TaskError> {
+        let (tx, rx) = mpsc::channel();
+        self.sender
+            .send(make(tx))
+            .map_err(|_| TaskError::ServiceStopped)?;
+        rx.recv().map_err(|_| TaskError::ServiceStopped)?
+    }
+
+    /// Add a task
+    pub fn add_task(&self, description: String) -> Result<Task, TaskError> {
+        self.call(|r| Request::Add(description, r))
+    }
+
+    /// Look up a task
+    pub fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        self.call(|r| Request::Get(id, r))
+    }
+
+    /// Apply an update to a task
+    pub fn update_task(&self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+        self.call(|r| Request::Update(id, updates, r))
+    }
+
+    /// Mark a task completed
+    pub fn complete_task(&self, id: Uuid) -> Result<Task, TaskError> {
+        self.call(|r| Request::Complete(id, r))
+    }
+
+    /// Delete a task
+    pub fn delete_task(&self, id: Uuid) -> Result<Task, TaskError> {
+        self.call(|r| Request::Delete(id, r))
+    }
+
+    /// Run a query
+    pub fn query_tasks(&self, query: TaskQuery) -> Result<Vec<Task>, TaskError> {
+        self.call(|r| Request::Query(query, r))
+    }
+
+    /// Sync now
+    pub fn sync(&self) -> Result<SyncResult, TaskError> {
+        self.call(Request::Sync)
+    }
+
+    /// Run maintenance now
+    pub fn run_maintenance(&self) -> Result<PurgeReport, TaskError> {
+        self.call(Request::Maintenance)
+    }
+
+    /// Back up now with the `backup.*` settings
+    pub fn backup(&self) -> Result<BackupRecord, TaskError> {
+        self.call(Request::Backup)
+    }
+
+    /// Snapshot of the manager's operation metrics
+    pub fn metrics(&self) -> Result<Metrics, TaskError> {
+        self.call(Request::Metrics)
+    }
+
+    /// Whether the service is still answering requests
+    pub fn is_running(&self) -> bool {
+        self.call(Request::Ping).is_ok()
+    }
+}
+
+type Subscribers = Arc<Mutex<Vec<Sender<ServiceEvent>>>>;
+
+/// Service owning a task manager on a background thread
+///
+/// Dropping the service stops the thread; use [`TaskService::shutdown`] to
+/// wait for it and observe errors.
+#[derive(Debug)]
+pub struct TaskService {
+    client: ServiceClient,
+    subscribers: Subscribers,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TaskService {
+    /// Start the service. `factory` runs on the service thread to build the
+    /// manager; its error is returned here if it fails.
+    pub fn start<F>(config: ServiceConfig, factory: F) -> Result<Self, TaskError>
+    where
+        F: FnOnce() -> Result<DefaultTaskManager, TaskError> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let (startup_tx, startup_rx) = mpsc::channel();
+        let subscribers: Subscribers = Arc::default();
+        let thread_subscribers = Arc::clone(&subscribers);
+
+        let handle = std::thread::Builder::new()
+            .name("taskwarrior-service".to_string())
+            .spawn(move || {
+                let manager = match factory() {
+                    Ok(manager) => {
+                        let _ = startup_tx.send(Ok(()));
+                        manager
+                    }
+                    Err(e) => {
+                        let _ = startup_tx.send(Err(e));
+                        return;
+                    }
+                };
+                Worker {
+                    manager,
+                    config,
+                    subscribers: thread_subscribers,
+                }
+                .run(receiver);
+            })?;
+
+        match startup_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                client: ServiceClient { sender },
+                subscribers,
+                handle: Some(handle),
+            }),
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = handle.join();
+                Err(TaskError::ServiceStopped)
+            }
+        }
+    }
+
+    /// A handle for sending requests
+    pub fn client(&self) -> ServiceClient {
+        self.client.clone()
+    }
+
+    /// Receive all events emitted from now on
+    pub fn subscribe(&self) -> Receiver<ServiceEvent> {
+        let (tx, rx) = mpsc::channel();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    /// Stop the service and wait for the thread to exit
+    pub fn shutdown(mut self) -> Result<(), TaskError> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> Result<(), TaskError> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+        let _ = self.client.sender.send(Request::Shutdown);
+        handle.join().map_err(|_| TaskError::InvalidState {
+            message: "task service thread panicked".to_string(),
+        })
+    }
+}
+
+impl Drop for TaskService {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// State owned by the service thread
+struct Worker {
+    manager: DefaultTaskManager,
+    config: ServiceConfig,
+    subscribers: Subscribers,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<Request>) {
+        let now = Instant::now();
+        let mut next_maintenance = self.config.maintenance_interval.map(|i| now + i);
+        let mut next_sync = self.config.sync_interval.map(|i| now + i);
+        let mut next_backup = self.config.backup_interval.map(|i| now + i);
+
+        loop {
+            let deadline = [next_maintenance, next_sync, next_backup]
+                .into_iter()
+                .flatten()
+                .min();
+            let received = match deadline {
+                Some(deadline) => {
+                    receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(Request::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+                Ok(request) => self.handle(request),
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            let now = Instant::now();
+            if let (Some(due), Some(interval)) = (next_maintenance, self.config.maintenance_interval) {
+                if due <= now {
+                    let _ = self.maintenance();
+                    next_maintenance = Some(now + interval);
+                }
+            }
+            if let (Some(due), Some(interval)) = (next_sync, self.config.sync_interval) {
+                if due <= now {
+                    let _ = self.sync();
+                    next_sync = Some(now + interval);
+                }
+            }
+            if let (Some(due), Some(interval)) = (next_backup, self.config.backup_interval) {
+                if due <= now {
+                    let _ = self.backup();
+                    next_backup = Some(now + interval);
+                }
+            }
+        }
+
+        self.emit(ServiceEvent::Stopped);
+    }
+
+    fn handle(&mut self, request: Request) {
+        match request {
+            Request::Add(description, reply) => {
+                let result = self.manager.add_task(description);
+                self.reply(reply, result, ServiceEvent::TaskAdded);
+            }
+            Request::Get(id, reply) => {
+                let _ = reply.send(self.manager.get_task(id));
+            }
+            Request::Update(id, updates, reply) => {
+                let result = self.manager.update_task(id, updates);
+                self.reply(reply, result, ServiceEvent::TaskUpdated);
+            }
+            Request::Complete(id, reply) => {
+                let result = self.manager.complete_task(id);
+                self.reply(reply, result, ServiceEvent::TaskCompleted);
+            }
+            Request::Delete(id, reply) => {
+                let result = self.manager.delete_task(id);
+                self.reply(reply, result, ServiceEvent::TaskDeleted);
+            }
+            Request::Query(query, reply) => {
+                let _ = reply.send(self.manager.query_tasks(&query));
+            }
+            Request::Sync(reply) => {
+                let _ = reply.send(self.sync());
+            }
+            Request::Maintenance(reply) => {
+                let _ = reply.send(self.maintenance());
+            }
+            Request::Backup(reply) => {
+                let _ = reply.send(self.backup());
+            }
+            Request::Metrics(reply) => {
+                let _ = reply.send(Ok(self.manager.metrics()));
+            }
+            Request::Ping(reply) => {
+                let _ = reply.send(Ok(()));
+            }
+            Request::Shutdown => {}
+        }
+    }
+
+    /// Emit the event for a successful change, then send the result back
+    fn reply(&self, reply: Reply<Task>, result: Result<Task, TaskError>, event: fn(Task) -> ServiceEvent) {
+        if let Ok(task) = &result {
+            self.emit(event(task.clone()));
+        }
+        let _ = reply.send(result);
+    }
+
+    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+        let result = self.manager.sync();
+        match &result {
+            Ok(summary) => self.emit(ServiceEvent::Synced(summary.clone())),
+            Err(e) => self.emit(ServiceEvent::SyncFailed(e.to_string())),
+        }
+        result
+    }
+
+    fn maintenance(&mut self) -> Result<PurgeReport, TaskError> {
+        let result = self.manager.promote_waiting().and_then(|promoted| {
+            for task in promoted {
+                self.emit(ServiceEvent::TaskUpdated(task));
+            }
+            let escalated = self.manager.escalate(false)?;
+            if !escalated.is_empty() {
+                for escalation in &escalated.escalations {
+                    self.emit(ServiceEvent::TaskUpdated(escalation.task.clone()));
+                }
+                self.emit(ServiceEvent::Escalated(escalated));
+            }
+            self.manager.purge_expired(false)
+        });
+        match &result {
+            Ok(report) => {
+                self.emit(ServiceEvent::MaintenanceCompleted(report.clone()));
+                if let Some(error) = &report.error {
+                    self.emit(ServiceEvent::MaintenanceFailed(error.clone()));
+                }
+            }
+            Err(e) => self.emit(ServiceEvent::MaintenanceFailed(e.to_string())),
+        }
+        result
+    }
+
+    fn backup(&mut self) -> Result<BackupRecord, TaskError> {
+        let result = BackupRunner::from_config(self.manager.config()).and_then(|runner| {
+            let mut runner = runner.ok_or_else(|| ConfigError::MissingRequired {
+                key: "backup.target".to_string(),
+            })?;
+            runner.run()
+        });
+        match &result {
+            Ok(record) => self.emit(ServiceEvent::BackedUp(record.clone())),
+            Err(e) => self.emit(ServiceEvent::BackupFailed(e.to_string())),
+        }
+        result
+    }
+
+    /// Send an event to every subscriber, dropping those that hung up
+    fn emit(&self, event: ServiceEvent) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|s| s.send(event.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::storage::FileStorageBackend;
+    use crate::task::TaskStatus;
+    use tempfile::TempDir;
+
+    fn start(temp_dir: &TempDir, config: ServiceConfig) -> TaskService {
+        let path = temp_dir.path().to_path_buf();
+        TaskService::start(config, move || {
+            let config = ConfigurationBuilder::new().data_dir(path.clone()).build()?;
+            let storage = Box::new(FileStorageBackend::with_path(path));
+            DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new()))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_requests_and_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = start(&temp_dir, ServiceConfig { maintenance_interval: None, sync_interval: None, backup_interval: None });
+        let events = service.subscribe();
+        let client = service.client();
+
+        let task = client.add_task("Service task".to_string()).unwrap();
+        client.complete_task(task.id).unwrap();
+        let done = client
+            .query_tasks(TaskQuery {
+                status: Some(TaskStatus::Completed),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(done.len(), 1);
+        assert!(matches!(client.sync(), Err(TaskError::SyncNotConfigured)));
+        assert_eq!(client.metrics().unwrap().operation("add").unwrap().count, 1);
+
+        assert!(matches!(events.recv().unwrap(), ServiceEvent::TaskAdded(t) if t.id == task.id));
+        assert!(matches!(events.recv().unwrap(), ServiceEvent::TaskCompleted(_)));
+        assert!(matches!(events.recv().unwrap(), ServiceEvent::SyncFailed(_)));
+
+        service.shutdown().unwrap();
+        assert!(matches!(events.recv().unwrap(), ServiceEvent::Stopped));
+        assert!(!client.is_running());
+        assert!(matches!(client.get_task(task.id), Err(TaskError::ServiceStopped)));
+    }
+
+    #[test]
+    fn test_scheduled_maintenance() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = start(
+            &temp_dir,
+            ServiceConfig::default().maintenance_every(Duration::from_millis(10)),
+        );
+        let events = service.subscribe();
+        let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(event, ServiceEvent::MaintenanceCompleted(report) if report.is_empty()));
+    }
+
+    #[test]
+    fn test_scheduled_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = temp_dir.path().join("data");
+        let backups = temp_dir.path().join("backups");
+        let target = backups.to_string_lossy().into_owned();
+        let service = TaskService::start(
+            ServiceConfig {
+                maintenance_interval: None,
+                sync_interval: None,
+                backup_interval: Some(Duration::from_millis(10)),
+            },
+            move || {
+                let config = ConfigurationBuilder::new()
+                    .data_dir(data.clone())
+                    .set("backup.target", target)
+                    .build()?;
+                let storage = Box::new(FileStorageBackend::with_path(data));
+                DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new()))
+            },
+        )
+        .unwrap();
+        let events = service.subscribe();
+        let event = events.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(&event, ServiceEvent::BackedUp(record) if backups.join(&record.name).exists()));
+        service.shutdown().unwrap();
+
+        let unconfigured = start(&temp_dir, ServiceConfig::default());
+        assert!(matches!(
+            unconfigured.client().backup(),
+            Err(TaskError::Configuration { source: ConfigError::MissingRequired { .. } })
+        ));
+    }
+
+    #[test]
+    fn test_factory_error_is_returned() {
+        let result = TaskService::start(ServiceConfig::default(), || {
+            Err(TaskError::InvalidState {
+                message: "no storage".to_string(),
+            })
+        });
+        assert!(matches!(result, Err(TaskError::InvalidState { .. })));
+    }
+}
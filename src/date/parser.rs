@@ -3,9 +3,10 @@
 //! This module provides comprehensive date parsing functionality including
 //! ISO-8601 formats, named synonyms, and relative date calculations.
 
+use crate::clock;
 use crate::date::DateParsing;
 use crate::error::DateError;
-use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc, Weekday};
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
 use chrono_tz::Tz;
 
 /// Main date parser implementation
@@ -71,7 +72,7 @@ impl DateParsing for DateParser {
 
         // Try parsing as relative date
         if input.contains("+") || input.contains("-") {
-            return self.calculate_relative_date(Utc::now(), input);
+            return self.calculate_relative_date(clock::now(), input);
         }
 
         Err(DateError::InvalidFormat {
@@ -81,14 +82,14 @@ impl DateParsing for DateParser {
 
     fn parse_synonym(&self, synonym: &str) -> Result<DateTime<Utc>, DateError> {
         let synonym_lower = synonym.to_lowercase();
-        let now = Utc::now();
+        let now = clock::now();
 
         let date = match synonym_lower.as_str() {
             "now" => now,
             "today" => {
                 let date = now.date_naive();
                 self.timezone
-                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
                     .single()
                     .ok_or_else(|| DateError::Timezone {
                         message: "Ambiguous local date".to_string(),
@@ -98,7 +99,7 @@ impl DateParsing for DateParser {
             "yesterday" => {
                 let date = (now - chrono::Duration::days(1)).date_naive();
                 self.timezone
-                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
                     .single()
                     .ok_or_else(|| DateError::Timezone {
                         message: "Ambiguous local date".to_string(),
@@ -108,7 +109,7 @@ impl DateParsing for DateParser {
             "tomorrow" => {
                 let date = (now + chrono::Duration::days(1)).date_naive();
                 self.timezone
-                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
                     .single()
                     .ok_or_else(|| DateError::Timezone {
                         message: "Ambiguous local date".to_string(),
@@ -158,7 +159,7 @@ impl DateParsing for DateParser {
 
         // Parse expressions like "+1week", "-3days", "now+2months"
         let (base_date, offset_str) = if let Some(stripped) = expression.strip_prefix("now") {
-            (Utc::now(), stripped)
+            (clock::now(), stripped)
         } else {
             (base, expression)
         };
@@ -181,25 +182,30 @@ impl DateParsing for DateParser {
             expression: expression.to_string(),
         })?;
 
+        // Out-of-range offsets are errors, never overflows
+        let invalid = || DateError::InvalidRelative {
+            expression: expression.to_string(),
+        };
         let signed_number = sign * number;
 
         let result = match unit {
-            "d" | "day" | "days" => base_date + chrono::Duration::days(signed_number),
-            "w" | "week" | "weeks" => base_date + chrono::Duration::weeks(signed_number),
+            "d" | "day" | "days" => chrono::Duration::try_days(signed_number)
+                .and_then(|offset| base_date.checked_add_signed(offset))
+                .ok_or_else(invalid)?,
+            "w" | "week" | "weeks" => chrono::Duration::try_weeks(signed_number)
+                .and_then(|offset| base_date.checked_add_signed(offset))
+                .ok_or_else(invalid)?,
             "m" | "month" | "months" => {
-                // Add months using date arithmetic
-                let mut date = base_date.date_naive();
-                if signed_number > 0 {
-                    for _ in 0..signed_number {
-                        date = self.add_month(date);
-                    }
+                // Month arithmetic clamps to the last day of shorter months
+                let months = Months::new(u32::try_from(number).map_err(|_| invalid())?);
+                let date = if sign > 0 {
+                    base_date.date_naive().checked_add_months(months)
                 } else {
-                    for _ in 0..(-signed_number) {
-                        date = self.subtract_month(date);
-                    }
+                    base_date.date_naive().checked_sub_months(months)
                 }
+                .ok_or_else(invalid)?;
                 self.timezone
-                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
                     .single()
                     .ok_or_else(|| DateError::Timezone {
                         message: "Invalid date after month calculation".to_string(),
@@ -207,26 +213,21 @@ impl DateParsing for DateParser {
                     .with_timezone(&Utc)
             }
             "y" | "year" | "years" => {
-                let mut date = base_date.date_naive();
-                let new_year = date.year() + signed_number as i32;
-                date = date
-                    .with_year(new_year)
-                    .ok_or_else(|| DateError::InvalidRelative {
-                        expression: expression.to_string(),
-                    })?;
+                let date = base_date.date_naive();
+                let new_year = i32::try_from(signed_number)
+                    .ok()
+                    .and_then(|years| date.year().checked_add(years))
+                    .ok_or_else(invalid)?;
+                let date = date.with_year(new_year).ok_or_else(invalid)?;
                 self.timezone
-                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
                     .single()
                     .ok_or_else(|| DateError::Timezone {
                         message: "Invalid date after year calculation".to_string(),
                     })?
                     .with_timezone(&Utc)
             }
-            _ => {
-                return Err(DateError::InvalidRelative {
-                    expression: expression.to_string(),
-                })
-            }
+            _ => return Err(invalid()),
         };
 
         Ok(result)
@@ -306,7 +307,7 @@ impl DateParser {
             }
         };
 
-        let today = Utc::now().date_naive();
+        let today = clock::now().date_naive();
         let current_weekday = today.weekday();
         let target_days = target_weekday.num_days_from_monday() as i32;
         let current_days = current_weekday.num_days_from_monday() as i32;
@@ -320,7 +321,7 @@ impl DateParser {
 
         Ok(self
             .timezone
-            .from_local_datetime(&target_date.and_hms_opt(0, 0, 0).unwrap())
+            .from_local_datetime(&target_date.and_time(NaiveTime::MIN))
             .single()
             .ok_or_else(|| DateError::Timezone {
                 message: "Ambiguous weekday calculation".to_string(),
@@ -337,7 +338,7 @@ impl DateParser {
 
         Ok(self
             .timezone
-            .from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap())
+            .from_local_datetime(&first_day.and_time(NaiveTime::MIN))
             .single()
             .ok_or_else(|| DateError::Timezone {
                 message: "Ambiguous start of month".to_string(),
@@ -376,7 +377,7 @@ impl DateParser {
 
         Ok(self
             .timezone
-            .from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap())
+            .from_local_datetime(&first_day.and_time(NaiveTime::MIN))
             .single()
             .ok_or_else(|| DateError::Timezone {
                 message: "Ambiguous start of year".to_string(),
@@ -426,7 +427,7 @@ impl DateParser {
 
         Ok(self
             .timezone
-            .from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap())
+            .from_local_datetime(&first_day.and_time(NaiveTime::MIN))
             .single()
             .ok_or_else(|| DateError::Timezone {
                 message: "Ambiguous quarter start".to_string(),
@@ -452,52 +453,6 @@ impl DateParser {
 
         Ok((&input[..split_pos], &input[split_pos..]))
     }
-
-    fn add_month(&self, date: NaiveDate) -> NaiveDate {
-        if date.month() == 12 {
-            NaiveDate::from_ymd_opt(date.year() + 1, 1, date.day())
-                .unwrap_or_else(|| NaiveDate::from_ymd_opt(date.year() + 1, 1, 28).unwrap())
-        } else {
-            NaiveDate::from_ymd_opt(date.year(), date.month() + 1, date.day()).unwrap_or_else(
-                || {
-                    // Handle month-end edge cases
-                    let mut day = date.day();
-                    while day > 28 {
-                        if let Some(new_date) =
-                            NaiveDate::from_ymd_opt(date.year(), date.month() + 1, day)
-                        {
-                            return new_date;
-                        }
-                        day -= 1;
-                    }
-                    NaiveDate::from_ymd_opt(date.year(), date.month() + 1, day).unwrap()
-                },
-            )
-        }
-    }
-
-    fn subtract_month(&self, date: NaiveDate) -> NaiveDate {
-        if date.month() == 1 {
-            NaiveDate::from_ymd_opt(date.year() - 1, 12, date.day())
-                .unwrap_or_else(|| NaiveDate::from_ymd_opt(date.year() - 1, 12, 31).unwrap())
-        } else {
-            NaiveDate::from_ymd_opt(date.year(), date.month() - 1, date.day()).unwrap_or_else(
-                || {
-                    // Handle month-end edge cases
-                    let mut day = date.day();
-                    while day > 28 {
-                        if let Some(new_date) =
-                            NaiveDate::from_ymd_opt(date.year(), date.month() - 1, day)
-                        {
-                            return new_date;
-                        }
-                        day -= 1;
-                    }
-                    NaiveDate::from_ymd_opt(date.year(), date.month() - 1, day).unwrap()
-                },
-            )
-        }
-    }
 }
 
 #[cfg(test)]
@@ -523,6 +478,28 @@ mod tests {
         let _monday = parser.parse_synonym("monday").unwrap();
     }
 
+    #[test]
+    fn test_synonyms_follow_clock() {
+        use crate::clock::{FixedClock, RandomIds};
+
+        let now = Utc.with_ymd_and_hms(2025, 3, 12, 15, 30, 0).unwrap(); // a Wednesday
+        let _guard = clock::deterministic(FixedClock::new(now), RandomIds);
+        let parser = DateParser::new();
+        assert_eq!(parser.parse_date("now").unwrap(), now);
+        assert_eq!(
+            parser.parse_date("tomorrow").unwrap(),
+            Utc.with_ymd_and_hms(2025, 3, 13, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parser.parse_date("friday").unwrap(),
+            Utc.with_ymd_and_hms(2025, 3, 14, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parser.parse_date("now+2d").unwrap(),
+            now + chrono::Duration::days(2)
+        );
+    }
+
     #[test]
     fn test_relative_dates() {
         let parser = DateParser::new();
@@ -535,6 +512,23 @@ mod tests {
         assert!(past < base);
     }
 
+    #[test]
+    fn test_relative_dates_out_of_range_are_errors() {
+        let parser = DateParser::new();
+        let base = Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap();
+
+        for expression in ["+9999999999999d", "-99999999999w", "2147483648y", "9999991months", "now-99999999999m"] {
+            assert!(
+                parser.calculate_relative_date(base, expression).is_err(),
+                "{expression} should be rejected"
+            );
+        }
+
+        // Month steps clamp to the end of shorter months
+        let next_month = parser.calculate_relative_date(base, "+1m").unwrap();
+        assert_eq!(next_month.date_naive(), NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+    }
+
     #[test]
     fn test_supported_synonyms() {
         let parser = DateParser::new();
@@ -18,13 +18,30 @@ pub fn subtract_duration(
     Ok(base - duration)
 }
 
-/// Parse duration string (e.g., "1week", "3days")
+/// Parse duration string (e.g., "1week", "3days", "2h", "30min")
 pub fn parse_duration(duration_str: &str) -> Result<Duration, DateError> {
     // This is a simplified implementation
     // Full implementation would be in the date parser
     let duration_str = duration_str.trim();
+    let invalid = || DateError::InvalidRelative {
+        expression: duration_str.to_string(),
+    };
 
-    if duration_str.ends_with("day")
+    let minutes = ["minutes", "minute", "mins", "min"]
+        .iter()
+        .find_map(|unit| duration_str.strip_suffix(unit));
+    let hours = ["hours", "hour", "h"]
+        .iter()
+        .find_map(|unit| duration_str.strip_suffix(unit));
+
+    // Out-of-range counts are rejected rather than overflowing
+    if let Some(num_str) = minutes {
+        let num: i64 = num_str.trim().parse().map_err(|_| invalid())?;
+        Duration::try_minutes(num).ok_or_else(invalid)
+    } else if let Some(num_str) = hours {
+        let num: i64 = num_str.trim().parse().map_err(|_| invalid())?;
+        Duration::try_hours(num).ok_or_else(invalid)
+    } else if duration_str.ends_with("day")
         || duration_str.ends_with("days")
         || duration_str.ends_with("d")
     {
@@ -32,10 +49,8 @@ pub fn parse_duration(duration_str: &str) -> Result<Duration, DateError> {
             .trim_end_matches("day")
             .trim_end_matches("days")
             .trim_end_matches("d");
-        let num: i64 = num_str.parse().map_err(|_| DateError::InvalidRelative {
-            expression: duration_str.to_string(),
-        })?;
-        Ok(Duration::days(num))
+        let num: i64 = num_str.parse().map_err(|_| invalid())?;
+        Duration::try_days(num).ok_or_else(invalid)
     } else if duration_str.ends_with("week")
         || duration_str.ends_with("weeks")
         || duration_str.ends_with("w")
@@ -44,17 +59,92 @@ pub fn parse_duration(duration_str: &str) -> Result<Duration, DateError> {
             .trim_end_matches("week")
             .trim_end_matches("weeks")
             .trim_end_matches("w");
-        let num: i64 = num_str.parse().map_err(|_| DateError::InvalidRelative {
-            expression: duration_str.to_string(),
-        })?;
-        Ok(Duration::weeks(num))
+        let num: i64 = num_str.parse().map_err(|_| invalid())?;
+        Duration::try_weeks(num).ok_or_else(invalid)
     } else {
-        Err(DateError::InvalidRelative {
-            expression: duration_str.to_string(),
-        })
+        Err(invalid())
     }
 }
 
+/// Parse an ISO 8601 duration as Taskwarrior stores duration UDAs, e.g.
+/// "PT2H30M", "P1D" or "P2W". A year counts as 365 days and a month as 30.
+pub fn parse_iso_duration(duration_str: &str) -> Result<Duration, DateError> {
+    let invalid = || DateError::InvalidRelative {
+        expression: duration_str.to_string(),
+    };
+    let trimmed = duration_str.trim();
+    let (negative, rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let rest = rest.strip_prefix(['P', 'p']).ok_or_else(invalid)?;
+
+    let mut total = Duration::zero();
+    let mut in_time = false;
+    let mut number = String::new();
+    let mut components = 0;
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            'T' if !in_time && number.is_empty() => in_time = true,
+            c if c.is_ascii_digit() => number.push(c),
+            unit => {
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                let component = match (in_time, unit) {
+                    (false, 'Y') => n.checked_mul(365).and_then(Duration::try_days),
+                    (false, 'M') => n.checked_mul(30).and_then(Duration::try_days),
+                    (false, 'W') => Duration::try_weeks(n),
+                    (false, 'D') => Duration::try_days(n),
+                    (true, 'H') => Duration::try_hours(n),
+                    (true, 'M') => Duration::try_minutes(n),
+                    (true, 'S') => Duration::try_seconds(n),
+                    _ => return Err(invalid()),
+                };
+                // Out-of-range components are rejected rather than overflowing
+                total = component
+                    .and_then(|component| total.checked_add(&component))
+                    .ok_or_else(invalid)?;
+                components += 1;
+            }
+        }
+    }
+    if components == 0 || !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(if negative { -total } else { total })
+}
+
+/// Format a duration in the ISO 8601 form Taskwarrior stores, e.g.
+/// "P1DT2H30M"
+pub fn format_iso_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.unsigned_abs();
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60,
+    );
+
+    let mut out = format!("{sign}P");
+    if days > 0 {
+        out.push_str(&format!("{days}D"));
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || days == 0 {
+        out.push('T');
+        for (n, unit) in [(hours, 'H'), (minutes, 'M'), (seconds, 'S')] {
+            if n > 0 {
+                out.push_str(&format!("{n}{unit}"));
+            }
+        }
+        if out.ends_with('T') {
+            out.push_str("0S");
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,7 +161,34 @@ mod tests {
         let duration = parse_duration("3days").unwrap();
         assert_eq!(duration, Duration::days(3));
 
+        assert_eq!(parse_duration("30min").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::hours(2));
+
         let duration = parse_duration("1week").unwrap();
         assert_eq!(duration, Duration::weeks(1));
+
+        for huge in ["999999999999999d", "99999999999999w", "9999999999999h", "-9999999999999999min"] {
+            assert!(parse_duration(huge).is_err(), "{huge}");
+        }
+    }
+
+    #[test]
+    fn test_iso_duration_round_trip() {
+        assert_eq!(parse_iso_duration("PT2H30M").unwrap(), Duration::minutes(150));
+        assert_eq!(parse_iso_duration("P1W").unwrap(), Duration::days(7));
+        assert_eq!(parse_iso_duration("P1DT1S").unwrap(), Duration::seconds(86_401));
+        assert_eq!(parse_iso_duration("-PT5M").unwrap(), Duration::minutes(-5));
+        for bad in ["", "P", "PT", "2h", "P1H", "PT1D", "P1"] {
+            assert!(parse_iso_duration(bad).is_err(), "{bad}");
+        }
+        for huge in ["P99999999999999D", "P99999999999999999Y", "P99999999999W", "P106000000000DT99999999999H"] {
+            assert!(parse_iso_duration(huge).is_err(), "{huge}");
+        }
+
+        assert_eq!(format_iso_duration(Duration::minutes(150)), "PT2H30M");
+        assert_eq!(format_iso_duration(Duration::days(2)), "P2D");
+        assert_eq!(format_iso_duration(Duration::seconds(90_061)), "P1DT1H1M1S");
+        assert_eq!(format_iso_duration(Duration::zero()), "PT0S");
+        assert_eq!(format_iso_duration(Duration::hours(-3)), "-PT3H");
     }
 }
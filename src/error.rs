@@ -79,6 +79,132 @@ pub enum TaskError {
 
     #[error("Replica reload failed at {path}: {message}")]
     ReplicaReloadFailed { message: String, path: std::path::PathBuf },
+
+    #[error("Task service is not running")]
+    ServiceStopped,
+
+    #[error("Task {id} changed since it was read (expected version {expected}, found {actual})")]
+    Conflict {
+        id: Uuid,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Audit log error: {message}")]
+    AuditLog { message: String },
+
+    /// A storage, hook or sync failure with the operation it interrupted
+    #[error("{context}: {}", error_chain(source.as_ref()))]
+    Context {
+        context: ErrorContext,
+        #[source]
+        source: Box<TaskError>,
+    },
+}
+
+impl TaskError {
+    /// Attach `context` to storage, hook, sync and I/O failures; other
+    /// errors (not found, validation, conflicts, ...) already say what went
+    /// wrong and are returned unchanged, as are errors that have a context
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        let wrap = matches!(
+            self,
+            TaskError::Io(_)
+                | TaskError::Serialization(_)
+                | TaskError::Storage { .. }
+                | TaskError::Sync { .. }
+                | TaskError::Hook { .. }
+                | TaskError::HookFailed { .. }
+                | TaskError::ExternalToolMissing(_)
+                | TaskError::ExternalToolFailed { .. }
+                | TaskError::ReplicaReloadFailed { .. }
+                | TaskError::AuditLog { .. }
+        );
+        if !wrap {
+            return self;
+        }
+        TaskError::Context {
+            context,
+            source: Box::new(self),
+        }
+    }
+
+    /// The operation this error interrupted, if recorded
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            TaskError::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without its [`ErrorContext`], for matching on the cause
+    pub fn root(&self) -> &TaskError {
+        match self {
+            TaskError::Context { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
+
+/// Which step failed: the operation, the task it was applied to and the
+/// storage backend involved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Operation name, e.g. `update` or `sync`
+    pub operation: String,
+    pub task: Option<Uuid>,
+    /// Storage backend, e.g. `file` or `taskchampion`
+    pub backend: Option<String>,
+}
+
+impl ErrorContext {
+    pub fn new<S: Into<String>>(operation: S) -> Self {
+        Self {
+            operation: operation.into(),
+            task: None,
+            backend: None,
+        }
+    }
+
+    pub fn task(mut self, id: Uuid) -> Self {
+        self.task = Some(id);
+        self
+    }
+
+    pub fn backend<S: Into<String>>(mut self, backend: S) -> Self {
+        self.backend = Some(backend.into());
+        self
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    /// e.g. `update of task 5f3c... failed (file storage)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.operation)?;
+        if let Some(id) = self.task {
+            write!(f, " of task {id}")?;
+        }
+        write!(f, " failed")?;
+        if let Some(backend) = &self.backend {
+            write!(f, " ({backend} storage)")?;
+        }
+        Ok(())
+    }
+}
+
+/// `error` followed by its sources, so wrappers such as
+/// [`TaskError::Storage`] do not hide the message underneath
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        if !message.ends_with(&text) {
+            message = format!("{message}: {text}");
+        }
+        source = cause.source();
+    }
+    message
 }
 
 /// Configuration-related errors
@@ -94,8 +220,31 @@ pub enum ConfigError {
     #[error("Environment error: {message}")]
     Environment { message: String },
 
-    #[error("Parse error at line {line}: {content}")]
-    ParseError { line: usize, content: String },
+    #[error(
+        "Parse error at {}:{line}: {content}{}",
+        path.display(),
+        describe_include_chain(include_chain)
+    )]
+    ParseError {
+        path: std::path::PathBuf,
+        line: usize,
+        content: String,
+        /// Files that led to `path` through `include`, outermost first
+        include_chain: Vec<std::path::PathBuf>,
+    },
+
+    #[error("Included file not found: {} (from {}:{line})", path.display(), included_from.display())]
+    MissingInclude {
+        path: std::path::PathBuf,
+        included_from: std::path::PathBuf,
+        line: usize,
+    },
+
+    #[error("Include cycle: {}{}", path.display(), describe_include_chain(include_chain))]
+    IncludeCycle {
+        path: std::path::PathBuf,
+        include_chain: Vec<std::path::PathBuf>,
+    },
 
     #[error("Invalid path {path}: {message}")]
     InvalidPath {
@@ -113,10 +262,22 @@ pub enum ConfigError {
     #[error("Missing required configuration: {key}")]
     MissingRequired { key: String },
 
+    #[error("Invalid config bundle: {message}")]
+    InvalidBundle { message: String },
+
     #[error("XDG directory discovery failed: {message}")]
     XdgError { message: String },
 }
 
+// " (included from a -> b)", or nothing for a top-level file
+fn describe_include_chain(chain: &[std::path::PathBuf]) -> String {
+    if chain.is_empty() {
+        return String::new();
+    }
+    let files: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+    format!(" (included from {})", files.join(" -> "))
+}
+
 /// Query-related errors
 #[derive(thiserror::Error, Debug)]
 pub enum QueryError {
@@ -156,6 +317,25 @@ pub enum StorageError {
 
     #[error("Lock error: {message}")]
     Lock { message: String },
+
+    #[error("Timed out after {after:?} waiting for {operation}")]
+    Timeout {
+        operation: String,
+        after: std::time::Duration,
+    },
+
+    #[error("Storage request queue is full ({capacity} requests pending)")]
+    QueueFull { capacity: usize },
+
+    #[error("Backup error: {message}")]
+    Backup { message: String },
+
+    #[error("{} is locked by {holder} (waited {waited:?})", path.display())]
+    Locked {
+        path: std::path::PathBuf,
+        holder: String,
+        waited: std::time::Duration,
+    },
 }
 
 /// Sync-related errors
@@ -222,4 +402,11 @@ pub enum ValidationError {
 
     #[error("Invalid status transition: from {from} to {to}")]
     InvalidStatusTransition { from: String, to: String },
+
+    #[error("Task {id} has {field} in the future: {value}")]
+    FutureTimestamp {
+        id: uuid::Uuid,
+        field: String,
+        value: chrono::DateTime<chrono::Utc>,
+    },
 }
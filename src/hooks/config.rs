@@ -13,6 +13,10 @@
 //! - `on-modify.py` → [`HookEvent::OnModify`]  
 //! - `post-complete` → [`HookEvent::PostComplete`]
 //!
+//! Scripts inside a `project.<name>` subdirectory, e.g.
+//! `hooks/project.Work/on-add.sh`, only run for tasks in that project or one
+//! of its subprojects.
+//!
 //! ### 2. TOML Configuration Files
 //! Create `.hookrc` files alongside scripts for advanced configuration:
 //!
@@ -61,6 +65,30 @@
 //! - User-configured priority: Any integer value
 //! - Ties broken by alphabetical filename order
 //!
+//! ## Allowlist
+//!
+//! When the data directory syncs through cloud storage, anyone who can write
+//! to it can drop a script into the hooks directory. An optional
+//! [`HookPolicy`] only runs scripts whose SHA-256 is recorded in the
+//! `[allowlist]` table of `hooks.toml`:
+//!
+//! ```toml
+//! [allowlist]
+//! signature = "sha256=9f2c..."
+//!
+//! [allowlist.scripts]
+//! "on-add.sh" = "5d41402abc4b2a76b9719d911017c592..."
+//! ```
+//!
+//! [`HookConfigCollection::trust_scripts`] records the current scripts. The
+//! policy itself, and the key the allowlist is signed with, come from the
+//! caller or from `TASKWARRIOR_HOOKS_POLICY` (`off`, `warn` or `enforce`) and
+//! `TASKWARRIOR_HOOKS_KEY`, never from the synced directory, so editing
+//! `hooks.toml` cannot switch verification off or forge the list. The
+//! signature also covers the interpreters and environment the scripts run
+//! with, and under `enforce` each script is checked again just before it
+//! runs.
+//!
 //! ## Configuration Validation
 //!
 //! All hook configurations are validated:
@@ -70,9 +98,11 @@
 //! - Environment variables must be valid strings
 
 use crate::error::TaskError;
-use crate::hooks::events::HookEvent;
+use crate::hooks::events::{HookContext, HookEvent};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 #[allow(unused_imports)]
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -96,6 +126,8 @@ pub struct Hook {
     pub working_directory: Option<PathBuf>,
     /// Timeout in seconds (None = no timeout)
     pub timeout: Option<u64>,
+    /// Project the hook is limited to
+    pub project: Option<String>,
 }
 
 impl HookConfig {
@@ -104,6 +136,27 @@ impl HookConfig {
         self.enabled && self.events.contains(event)
     }
 
+    /// Whether the hook's project scope covers the task in `context`: the
+    /// task's project is the scoped one or a subproject of it. Without a
+    /// task (or an earlier version of it) a scoped hook does not run.
+    pub fn applies_to(&self, context: &HookContext) -> bool {
+        let Some(scope) = &self.project else {
+            return true;
+        };
+        let project = context
+            .task
+            .as_ref()
+            .or(context.old_task.as_ref())
+            .and_then(|t| t.project.as_deref());
+        project.is_some_and(|p| p == scope || p.starts_with(&format!("{scope}.")))
+    }
+
+    /// Only run for tasks in `project` and its subprojects
+    pub fn with_project<S: Into<String>>(mut self, project: S) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
     /// Set working directory
     pub fn with_working_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
         self.working_directory = Some(dir.into());
@@ -147,11 +200,21 @@ pub struct HookConfig {
     /// Whether this hook is enabled
     pub enabled: bool,
     /// Environment variables to set before execution
+    ///
+    /// Values may contain `{data_dir}`, `{task_uuid}` and `{project}`
+    /// placeholders, expanded per invocation.
     pub environment: HashMap<String, String>,
-    /// Working directory for hook execution
+    /// Working directory for hook execution (placeholders allowed as above)
     pub working_directory: Option<PathBuf>,
     /// Timeout in seconds (None = no timeout)
     pub timeout: Option<u64>,
+    /// Only run for tasks in this project and its subprojects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Checksum the script was allowlisted with under
+    /// [`HookTrust::Enforce`]; it must still match when the hook runs
+    #[serde(skip)]
+    pub checksum: Option<String>,
 }
 
 impl HookConfig {
@@ -165,6 +228,8 @@ impl HookConfig {
             environment: HashMap::new(),
             working_directory: None,
             timeout: None,
+            project: None,
+            checksum: None,
         }
     }
 
@@ -184,6 +249,7 @@ impl HookConfig {
             environment: self.environment.clone(),
             working_directory: self.working_directory.clone(),
             timeout: self.timeout,
+            project: self.project.clone(),
         }
     }
 
@@ -242,6 +308,122 @@ pub struct HookConfigCollection {
     pub global_timeout: Option<u64>,
     /// Whether hooks are enabled globally
     pub enabled: bool,
+    /// Interpreter command lines by script extension, e.g. `py = "python3"`,
+    /// used instead of each script's shebang
+    #[serde(default)]
+    pub interpreters: HashMap<String, String>,
+    /// Checksums of the scripts trusted to run under a [`HookPolicy`]
+    #[serde(default)]
+    pub allowlist: HookAllowlist,
+    /// Scripts that failed allowlist verification when loaded. Under
+    /// [`HookTrust::Enforce`] they are no longer in `hooks`.
+    #[serde(skip)]
+    pub untrusted: Vec<UntrustedHook>,
+}
+
+/// How scripts missing from the allowlist, or changed since, are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookTrust {
+    /// Run every script
+    #[default]
+    Off,
+    /// Run them, but report each one
+    Warn,
+    /// Refuse to load them
+    Enforce,
+}
+
+impl std::str::FromStr for HookTrust {
+    type Err = TaskError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" | "" => Ok(HookTrust::Off),
+            "warn" => Ok(HookTrust::Warn),
+            "enforce" => Ok(HookTrust::Enforce),
+            other => Err(TaskError::Hook {
+                message: format!("Unknown hook policy '{other}', expected off, warn or enforce"),
+            }),
+        }
+    }
+}
+
+/// Allowlist verification settings, supplied from outside the hooks
+/// directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookPolicy {
+    pub trust: HookTrust,
+    /// Key the allowlist signature is checked with; without one only the
+    /// checksums are compared
+    pub key: Option<String>,
+}
+
+impl HookPolicy {
+    /// Policy with the given trust level and no signing key
+    pub fn new(trust: HookTrust) -> Self {
+        Self { trust, key: None }
+    }
+
+    /// Also require the allowlist to be signed with `key`
+    pub fn with_key<K: Into<String>>(mut self, key: K) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Policy from `TASKWARRIOR_HOOKS_POLICY` and `TASKWARRIOR_HOOKS_KEY`.
+    /// An unrecognised policy value is treated as `enforce`.
+    pub fn from_env() -> Self {
+        let trust = std::env::var("TASKWARRIOR_HOOKS_POLICY")
+            .map(|v| v.parse().unwrap_or(HookTrust::Enforce))
+            .unwrap_or_default();
+        let key = std::env::var("TASKWARRIOR_HOOKS_KEY")
+            .ok()
+            .filter(|k| !k.is_empty());
+        Self { trust, key }
+    }
+}
+
+/// The `[allowlist]` table of `hooks.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookAllowlist {
+    /// Hex SHA-256 of each trusted script, keyed by its path relative to
+    /// the hooks directory with `/` separators
+    #[serde(default)]
+    pub scripts: BTreeMap<String, String>,
+    /// `sha256=<hex>` HMAC, made with the policy key, of the entries and
+    /// of every setting that changes how the scripts run (see
+    /// [`HookConfigCollection::sign_allowlist`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Why a script did not pass allowlist verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UntrustedReason {
+    /// Not in the allowlist
+    NotListed,
+    /// Contents differ from the recorded checksum
+    Modified,
+    /// The allowlist signature does not match the policy key
+    BadSignature,
+}
+
+impl std::fmt::Display for UntrustedReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UntrustedReason::NotListed => "is not in the allowlist",
+            UntrustedReason::Modified => "has changed since it was allowlisted",
+            UntrustedReason::BadSignature => "is listed in an allowlist with an invalid signature",
+        })
+    }
+}
+
+/// A hook script that failed allowlist verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UntrustedHook {
+    pub path: PathBuf,
+    pub reason: UntrustedReason,
 }
 
 impl HookConfigCollection {
@@ -252,11 +434,154 @@ impl HookConfigCollection {
             global_env: HashMap::new(),
             global_timeout: None,
             enabled: true,
+            interpreters: HashMap::new(),
+            allowlist: HookAllowlist::default(),
+            untrusted: Vec::new(),
         }
     }
 
-    /// Load hook configuration from a directory
+    /// Load hook configuration from a directory, verifying scripts against
+    /// the allowlist under [`HookPolicy::from_env`]
     pub fn load_from_dir(dir_path: &Path) -> Result<Self, TaskError> {
+        Self::load_from_dir_with_policy(dir_path, &HookPolicy::from_env())
+    }
+
+    /// Load hook configuration from a directory, verifying scripts against
+    /// the allowlist under `policy`. Untrusted scripts are listed in
+    /// [`untrusted`](Self::untrusted); under [`HookTrust::Enforce`] they are
+    /// also left out, and a badly signed allowlist fails the whole load.
+    pub fn load_from_dir_with_policy(
+        dir_path: &Path,
+        policy: &HookPolicy,
+    ) -> Result<Self, TaskError> {
+        let mut collection = Self::load_unverified(dir_path)?;
+        collection.untrusted = collection.verify_allowlist(dir_path, policy)?;
+        Ok(collection)
+    }
+
+    /// Record the checksum of every hook script under `dir_path` in the
+    /// allowlist, replacing the previous entries, and sign it with `key`
+    pub fn trust_scripts(&mut self, dir_path: &Path, key: Option<&str>) -> Result<(), TaskError> {
+        self.allowlist.scripts = self
+            .hooks
+            .iter()
+            .map(|hook| Ok((allowlist_key(dir_path, &hook.path), checksum(&hook.path)?)))
+            .collect::<Result<_, TaskError>>()?;
+        self.allowlist.signature = key.map(|k| self.sign_allowlist(dir_path, k));
+        Ok(())
+    }
+
+    /// Allowlist signature under `key`. Besides the script checksums it
+    /// covers the interpreters, the global environment and each hook's
+    /// environment and working directory, so none of them can be changed
+    /// without the key either.
+    pub fn sign_allowlist(&self, dir_path: &Path, key: &str) -> String {
+        let tag = self.allowlist_mac(dir_path, key).finalize().into_bytes();
+        format!("sha256={}", hex::encode(tag))
+    }
+
+    /// Whether the stored allowlist signature is the one `key` gives
+    pub fn is_allowlist_signed_by(&self, dir_path: &Path, key: &str) -> bool {
+        let tag = self
+            .allowlist
+            .signature
+            .as_deref()
+            .and_then(|signature| signature.strip_prefix("sha256="))
+            .and_then(|hex| hex::decode(hex).ok());
+        tag.is_some_and(|tag| self.allowlist_mac(dir_path, key).verify_slice(&tag).is_ok())
+    }
+
+    // HMAC over everything the allowlist signature covers
+    fn allowlist_mac(&self, dir_path: &Path, key: &str) -> Hmac<Sha256> {
+        let mut lines: Vec<String> = self
+            .allowlist
+            .scripts
+            .iter()
+            .map(|(path, checksum)| format!("script {path:?} {checksum:?}"))
+            .collect();
+        lines.extend(
+            self.interpreters
+                .iter()
+                .map(|(ext, command)| format!("interpreter {ext:?} {command:?}")),
+        );
+        lines.extend(
+            self.global_env
+                .iter()
+                .map(|(name, value)| format!("env {name:?} {value:?}")),
+        );
+        for hook in &self.hooks {
+            let script = allowlist_key(dir_path, &hook.path);
+            lines.extend(
+                hook.environment
+                    .iter()
+                    .map(|(name, value)| format!("hook {script:?} env {name:?} {value:?}")),
+            );
+            if let Some(dir) = &hook.working_directory {
+                lines.push(format!("hook {script:?} working_directory {dir:?}"));
+            }
+        }
+        // Maps have no stable order
+        lines.sort();
+        let payload: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes any key");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// Check every hook against the allowlist and return the ones that fail.
+    /// Under [`HookTrust::Enforce`] they are removed from the collection and
+    /// a signature mismatch is an error.
+    pub fn verify_allowlist(
+        &mut self,
+        dir_path: &Path,
+        policy: &HookPolicy,
+    ) -> Result<Vec<UntrustedHook>, TaskError> {
+        if policy.trust == HookTrust::Off {
+            return Ok(Vec::new());
+        }
+
+        let signed = policy
+            .key
+            .as_deref()
+            .is_none_or(|key| self.is_allowlist_signed_by(dir_path, key));
+        if !signed && policy.trust == HookTrust::Enforce {
+            return Err(TaskError::Hook {
+                message: format!(
+                    "Hook allowlist in {} is not signed with the configured key",
+                    dir_path.display()
+                ),
+            });
+        }
+
+        let mut untrusted = Vec::new();
+        for hook in &mut self.hooks {
+            let reason = match self.allowlist.scripts.get(&allowlist_key(dir_path, &hook.path)) {
+                None => Some(UntrustedReason::NotListed),
+                Some(expected) if checksum(&hook.path).ok().as_ref() != Some(expected) => {
+                    Some(UntrustedReason::Modified)
+                }
+                Some(_) if !signed => Some(UntrustedReason::BadSignature),
+                Some(expected) => {
+                    if policy.trust == HookTrust::Enforce {
+                        hook.checksum = Some(expected.clone());
+                    }
+                    None
+                }
+            };
+            untrusted.extend(reason.map(|reason| UntrustedHook {
+                path: hook.path.clone(),
+                reason,
+            }));
+        }
+
+        if policy.trust == HookTrust::Enforce {
+            self.hooks
+                .retain(|hook| !untrusted.iter().any(|u| u.path == hook.path));
+        }
+        Ok(untrusted)
+    }
+
+    fn load_unverified(dir_path: &Path) -> Result<Self, TaskError> {
         let mut collection = Self::new();
 
         // First try to load existing configuration file
@@ -295,7 +620,8 @@ impl HookConfigCollection {
         for script_path in Self::scan_hook_directory(dir_path)? {
             if HookConfig::is_executable(&script_path) {
                 let events = Self::infer_events_from_path(&script_path);
-                let config = HookConfig::new(&script_path, events);
+                let mut config = HookConfig::new(&script_path, events);
+                config.project = Self::infer_project_from_path(dir_path, &script_path);
                 hooks.push(config);
             }
         }
@@ -346,6 +672,18 @@ impl HookConfigCollection {
         Ok(scripts)
     }
 
+    /// Project scope from the nearest `project.<name>` directory between
+    /// the hooks directory and the script
+    fn infer_project_from_path(dir_path: &Path, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(dir_path).ok()?.parent()?;
+        relative
+            .components()
+            .rev()
+            .filter_map(|c| c.as_os_str().to_str()?.strip_prefix("project."))
+            .find(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+
     /// Infer hook events from script path/name
     fn infer_events_from_path(path: &Path) -> Vec<HookEvent> {
         let mut events = Vec::new();
@@ -494,6 +832,9 @@ impl HookConfigCollection {
             base.global_timeout = override_collection.global_timeout;
         }
 
+        base.interpreters.extend(override_collection.interpreters);
+        base.untrusted.extend(override_collection.untrusted);
+
         // For hooks, replace any existing hooks with same path
         for new_hook in override_collection.hooks {
             // Remove any existing hook with same path
@@ -557,6 +898,23 @@ impl HookConfigCollection {
     }
 }
 
+/// Allowlist entry name for a script: relative to the hooks directory when
+/// inside it, with `/` separators so the list works across platforms
+fn allowlist_key(dir_path: &Path, path: &Path) -> String {
+    path.strip_prefix(dir_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Hex SHA-256 of a script's contents
+pub(crate) fn checksum(path: &Path) -> Result<String, TaskError> {
+    let data = std::fs::read(path).map_err(|e| TaskError::Hook {
+        message: format!("Failed to read hook script {}: {e}", path.display()),
+    })?;
+    Ok(hex::encode(Sha256::digest(&data)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -753,4 +1111,143 @@ mod tests {
             Some(&"global_value".to_string())
         );
     }
+
+    #[cfg(unix)]
+    fn write_hook(dir: &Path, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(name);
+        fs::write(&path, body).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allowlist_verification() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let trusted = write_hook(dir, "on-add.sh", "#!/bin/sh\necho ok\n");
+        let enforce = HookPolicy::new(HookTrust::Enforce).with_key("s3cret");
+
+        let mut collection = HookConfigCollection::load_unverified(dir).unwrap();
+        collection.trust_scripts(dir, Some("s3cret")).unwrap();
+        assert_eq!(collection.allowlist.scripts.keys().collect::<Vec<_>>(), vec!["on-add.sh"]);
+        collection.save_to_file(&dir.join("hooks.toml")).unwrap();
+
+        // A new script and a modified one are refused
+        let dropped = write_hook(dir, "on-modify.sh", "#!/bin/sh\ncurl evil\n");
+        let loaded = HookConfigCollection::load_from_dir_with_policy(dir, &enforce).unwrap();
+        assert_eq!(loaded.hooks.len(), 1);
+        assert_eq!(loaded.hooks[0].path, trusted);
+
+        fs::write(&trusted, "#!/bin/sh\ncurl evil\n").unwrap();
+        let mut loaded = HookConfigCollection::load_unverified(dir).unwrap();
+        let untrusted = loaded.verify_allowlist(dir, &HookPolicy::new(HookTrust::Warn)).unwrap();
+        assert_eq!(untrusted.len(), 2);
+        assert!(untrusted.contains(&UntrustedHook {
+            path: trusted,
+            reason: UntrustedReason::Modified
+        }));
+        assert!(untrusted.contains(&UntrustedHook {
+            path: dropped,
+            reason: UntrustedReason::NotListed
+        }));
+        assert_eq!(loaded.hooks.len(), 2, "warn keeps running them");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allowlist_signature_required() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        write_hook(dir, "on-add.sh", "#!/bin/sh\necho ok\n");
+
+        // Whoever edits hooks.toml without the key cannot produce a valid list
+        let mut collection = HookConfigCollection::load_unverified(dir).unwrap();
+        collection.trust_scripts(dir, Some("forged")).unwrap();
+        collection.save_to_file(&dir.join("hooks.toml")).unwrap();
+
+        let enforce = HookPolicy::new(HookTrust::Enforce).with_key("s3cret");
+        assert!(HookConfigCollection::load_from_dir_with_policy(dir, &enforce).is_err());
+        let untrusted = HookConfigCollection::load_unverified(dir)
+            .unwrap()
+            .verify_allowlist(dir, &HookPolicy::new(HookTrust::Warn).with_key("s3cret"))
+            .unwrap();
+        assert_eq!(untrusted[0].reason, UntrustedReason::BadSignature);
+
+        // Without a key only the checksums are compared
+        let checksums_only = HookPolicy::new(HookTrust::Enforce);
+        let loaded = HookConfigCollection::load_from_dir_with_policy(dir, &checksums_only).unwrap();
+        assert_eq!(loaded.hooks.len(), 1);
+        assert_eq!("Warn".parse::<HookTrust>().unwrap(), HookTrust::Warn);
+        assert!("maybe".parse::<HookTrust>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allowlist_signature_covers_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let script = write_hook(dir, "on-add.sh", "#!/bin/sh\necho ok\n");
+        let enforce = HookPolicy::new(HookTrust::Enforce).with_key("s3cret");
+
+        let mut collection = HookConfigCollection::load_unverified(dir).unwrap();
+        collection.interpreters.insert("sh".to_string(), "/bin/sh".to_string());
+        collection.trust_scripts(dir, Some("s3cret")).unwrap();
+        collection.save_to_file(&dir.join("hooks.toml")).unwrap();
+        let loaded = HookConfigCollection::load_from_dir_with_policy(dir, &enforce).unwrap();
+        assert!(loaded.untrusted.is_empty());
+        assert_eq!(loaded.hooks[0].checksum, loaded.allowlist.scripts.get("on-add.sh").cloned());
+
+        // Settings the scripts run with are signed along with the checksums
+        for tamper in [
+            |c: &mut HookConfigCollection| {
+                c.interpreters.insert("sh".to_string(), "/tmp/evil".to_string());
+            },
+            |c: &mut HookConfigCollection| {
+                c.global_env.insert("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string());
+            },
+            |c: &mut HookConfigCollection| {
+                c.hooks[0].working_directory = Some(PathBuf::from("/tmp"));
+            },
+        ] {
+            let mut edited = collection.clone();
+            tamper(&mut edited);
+            edited.save_to_file(&dir.join("hooks.toml")).unwrap();
+            assert!(HookConfigCollection::load_from_dir_with_policy(dir, &enforce).is_err());
+        }
+
+        // A script swapped after loading is refused when it would run
+        collection.save_to_file(&dir.join("hooks.toml")).unwrap();
+        let loaded = HookConfigCollection::load_from_dir_with_policy(dir, &enforce).unwrap();
+        fs::write(&script, "#!/bin/sh\ncurl evil\n").unwrap();
+        let context = HookContext::new(HookEvent::OnAdd);
+        let result = crate::hooks::HookExecutor::new().run_hook(&loaded.hooks[0], &context);
+        assert!(matches!(result, Err(TaskError::HookFailed { .. })));
+
+        // Untrusted scripts are returned rather than printed
+        let warn = HookPolicy::new(HookTrust::Warn).with_key("s3cret");
+        let loaded = HookConfigCollection::load_from_dir_with_policy(dir, &warn).unwrap();
+        assert_eq!(loaded.untrusted.len(), 1);
+        assert_eq!(loaded.untrusted[0].reason, UntrustedReason::Modified);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_project_directories_scope_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("project.Work")).unwrap();
+        let global = write_hook(dir, "on-add.sh", "#!/bin/sh\n");
+        let scoped = write_hook(&dir.join("project.Work"), "on-add.sh", "#!/bin/sh\n");
+
+        let collection = HookConfigCollection::load_unverified(dir).unwrap();
+        let project_of = |path: &PathBuf| {
+            let hook = collection.hooks.iter().find(|h| &h.path == path).unwrap();
+            assert_eq!(hook.events, vec![HookEvent::OnAdd]);
+            hook.project.clone()
+        };
+        assert_eq!(project_of(&global), None);
+        assert_eq!(project_of(&scoped).as_deref(), Some("Work"));
+    }
 }
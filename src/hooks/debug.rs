@@ -0,0 +1,61 @@
+//! # Hook Debugging
+//!
+//! Helpers for hook authors to exercise a script through the same execution
+//! path the library uses for real task operations, without modifying any
+//! task data.
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::hooks::{debug, HookEvent};
+//!
+//! let run = debug::run_hook("hooks/on-add.sh", HookEvent::OnAdd, None)?;
+//! println!("{run}");
+//! # Ok::<(), taskwarrior3lib::TaskError>(())
+//! ```
+
+use crate::clock;
+use crate::error::TaskError;
+use crate::hooks::executor::{HookExecutor, HookRun};
+use crate::hooks::HookEvent;
+use crate::task::{Priority, Task};
+use chrono::Duration;
+use std::path::Path;
+
+/// Build a representative task with the commonly used fields populated
+pub fn sample_task() -> Task {
+    let mut task = Task::new("Sample task for hook testing".to_string());
+    task.project = Some("Sample".to_string());
+    task.priority = Some(Priority::Medium);
+    task.due = Some(clock::now() + Duration::days(1));
+    task.add_tag("sample".to_string());
+    task
+}
+
+/// Run a hook script once and return its captured stdin/stdout/stderr, exit
+/// code, and timing. Uses [`sample_task`] when no task is supplied.
+pub fn run_hook<P: AsRef<Path>>(
+    path: P,
+    event: HookEvent,
+    task: Option<Task>,
+) -> Result<HookRun, TaskError> {
+    HookExecutor::new().run_single(path, event, task.unwrap_or_else(sample_task))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookResult;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_hook_with_sample_task() {
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = temp_dir.path().join("on-add.sh");
+        fs::write(&script_path, "#!/bin/sh\necho \"$TASKWARRIOR_TASK_PROJECT\"\nexit 0").unwrap();
+
+        let run = run_hook(&script_path, HookEvent::OnAdd, None).unwrap();
+        assert_eq!(run.result, HookResult::Success);
+        assert_eq!(run.stdout.trim(), "Sample");
+        assert!(run.to_string().contains("exit code: 0"));
+    }
+}
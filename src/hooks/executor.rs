@@ -7,12 +7,25 @@
 //! ## Features
 //!
 //! - **Process Management**: Spawns and manages external hook script processes
-//! - **Timeout Handling**: Enforces execution time limits and terminates long-running hooks
+//! - **Timeout Handling**: Enforces execution time limits and terminates long-running hooks,
+//!   along with anything they started in the background (their process group on Unix)
 //! - **Environment Setup**: Configures environment variables and working directory  
 //! - **Input/Output Handling**: Passes task JSON via stdin and captures stdout/stderr
 //! - **Error Recovery**: Gracefully handles script failures and system errors
 //! - **Cross-Platform**: Works on Unix-like systems and Windows
 //!
+//! ## Interpreters
+//!
+//! Scripts are not executed directly. The interpreter is chosen, in order,
+//! from the extension map ([`HookExecutor::with_interpreter`] or the
+//! `interpreters` table of `hooks.toml`), then from the script's shebang
+//! line, and otherwise the script is run by `/bin/sh` on Unix or directly on
+//! Windows. A shebang such as `#!/usr/bin/env python3` or
+//! `#!/usr/local/bin/python3` becomes `python3 script.py`, looked up on
+//! `PATH` when the absolute interpreter path does not exist, so a hook
+//! collection written on one machine runs on another. Map `py` to `python`
+//! where `python3` is not installed under that name.
+//!
 //! ## Usage
 //!
 //! The executor is typically used internally by the hook system:
@@ -56,11 +69,71 @@
 //! - Proper process isolation prevents resource exhaustion
 
 use crate::error::TaskError;
-use crate::hooks::{HookConfig, HookContext, HookResult};
+use crate::hooks::{HookConfig, HookContext, HookEvent, HookResult};
+use crate::task::Task;
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+
+/// Least time to keep reading a hook's pipes once it has exited or been
+/// killed
+const PIPE_GRACE: Duration = Duration::from_millis(200);
+
+/// A child pipe drained on a background thread
+struct PipeReader {
+    handle: std::thread::JoinHandle<()>,
+    output: Arc<Mutex<Vec<u8>>>,
+}
+
+impl PipeReader {
+    /// Output read by `deadline`, whether or not the pipe has closed
+    fn finish(self, deadline: Instant) -> String {
+        HookExecutor::join_until(self.handle, deadline);
+        let output = self.output.lock().unwrap_or_else(|e| e.into_inner());
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+/// Full record of a single hook invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookRun {
+    /// Data written to the hook's stdin
+    pub stdin: String,
+    /// Captured stdout
+    pub stdout: String,
+    /// Captured stderr
+    pub stderr: String,
+    /// Process exit code (None if killed by a signal or timeout)
+    pub exit_code: Option<i32>,
+    /// Wall-clock execution time
+    pub duration: Duration,
+    /// Whether the hook was killed for exceeding its timeout
+    pub timed_out: bool,
+    /// Interpreted result, as used by the hook manager
+    pub result: HookResult,
+}
+
+impl std::fmt::Display for HookRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = self
+            .exit_code
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "none".to_string());
+        writeln!(f, "result:    {:?}", self.result)?;
+        writeln!(f, "exit code: {code}")?;
+        writeln!(f, "duration:  {} ms", self.duration.as_millis())?;
+        if self.timed_out {
+            writeln!(f, "timed out: yes")?;
+        }
+        writeln!(f, "--- stdin ---\n{}", self.stdin)?;
+        writeln!(f, "--- stdout ---\n{}", self.stdout)?;
+        write!(f, "--- stderr ---\n{}", self.stderr)
+    }
+}
 
 /// Hook execution engine for running hook scripts
 #[derive(Debug, Default)]
@@ -69,6 +142,10 @@ pub struct HookExecutor {
     default_timeout: Duration,
     /// Default environment variables
     default_env: HashMap<String, String>,
+    /// Data directory substituted for `{data_dir}` placeholders
+    data_dir: Option<PathBuf>,
+    /// Interpreter command lines by lowercase file extension
+    interpreters: HashMap<String, String>,
 }
 
 impl HookExecutor {
@@ -77,9 +154,53 @@ impl HookExecutor {
         Self {
             default_timeout: Duration::from_secs(30),
             default_env: HashMap::new(),
+            data_dir: None,
+            interpreters: HashMap::new(),
         }
     }
 
+    /// Set the data directory used to expand `{data_dir}` placeholders
+    pub fn with_data_dir<P: Into<PathBuf>>(mut self, data_dir: P) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// The data directory used to expand `{data_dir}` placeholders
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.data_dir.as_deref()
+    }
+
+    /// Expand per-invocation placeholders in a hook setting.
+    ///
+    /// Supported placeholders are `{data_dir}`, `{task_uuid}` and `{project}`;
+    /// they expand to an empty string when the value is unavailable.
+    /// Anything else in braces is left untouched.
+    pub fn expand_placeholders(&self, value: &str, context: &HookContext) -> String {
+        if !value.contains('{') {
+            return value.to_string();
+        }
+        let data_dir = self
+            .data_dir
+            .as_ref()
+            .map(|d| d.display().to_string())
+            .unwrap_or_default();
+        let task_uuid = context
+            .task
+            .as_ref()
+            .map(|t| t.id.to_string())
+            .unwrap_or_default();
+        let project = context
+            .task
+            .as_ref()
+            .and_then(|t| t.project.clone())
+            .unwrap_or_default();
+
+        value
+            .replace("{data_dir}", &data_dir)
+            .replace("{task_uuid}", &task_uuid)
+            .replace("{project}", &project)
+    }
+
     /// Set default timeout for all hooks
     pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
         self.default_timeout = timeout;
@@ -92,6 +213,40 @@ impl HookExecutor {
         self
     }
 
+    /// Run scripts with extension `ext` (with or without the leading dot)
+    /// through `command`, e.g. `("py", "python3")` or
+    /// `("ps1", "powershell -File")`. The script path is appended as the
+    /// last argument. Takes precedence over the script's shebang.
+    pub fn with_interpreter<E: AsRef<str>, C: Into<String>>(mut self, ext: E, command: C) -> Self {
+        let ext = ext.as_ref().trim_start_matches('.').to_lowercase();
+        self.interpreters.insert(ext, command.into());
+        self
+    }
+
+    /// The interpreter command line used to run `path`, without the script
+    /// itself. `None` means the platform default: `/bin/sh` on Unix, direct
+    /// execution elsewhere.
+    pub fn resolve_interpreter<P: AsRef<Path>>(&self, path: P) -> Option<Vec<String>> {
+        let path = path.as_ref();
+        let mapped = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| self.interpreters.get(&e.to_lowercase()));
+        if let Some(command) = mapped {
+            let argv: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+            if !argv.is_empty() {
+                return Some(argv);
+            }
+        }
+
+        let mut first_line = String::new();
+        std::fs::File::open(path)
+            .map(std::io::BufReader::new)
+            .and_then(|mut r| std::io::BufRead::read_line(&mut r, &mut first_line))
+            .ok()?;
+        parse_shebang(&first_line)
+    }
+
     /// Execute a single hook with the given context
     pub fn execute_hook(
         &self,
@@ -106,6 +261,29 @@ impl HookExecutor {
             )));
         }
 
+        Ok(self.run_hook(config, context)?.result)
+    }
+
+    /// Execute a hook and capture its complete I/O and timing
+    pub fn run_hook(&self, config: &HookConfig, context: &HookContext) -> Result<HookRun, TaskError> {
+        if !config.path.exists() {
+            return Err(TaskError::HookFailed {
+                message: format!("Hook script not found: {}", config.path.display()),
+            });
+        }
+        // An allowlisted script must not have changed since it was loaded
+        if let Some(expected) = &config.checksum {
+            let actual = crate::hooks::config::checksum(&config.path)?;
+            if !bool::from(actual.as_bytes().ct_eq(expected.as_bytes())) {
+                return Err(TaskError::HookFailed {
+                    message: format!(
+                        "Hook script {} has changed since it was allowlisted",
+                        config.path.display()
+                    ),
+                });
+            }
+        }
+
         // Prepare the command
         let mut cmd = self.prepare_command(config, context)?;
 
@@ -115,8 +293,34 @@ impl HookExecutor {
             .map(Duration::from_secs)
             .unwrap_or(self.default_timeout);
 
+        let stdin = Self::stdin_payload(context)?;
+
         // Execute the command with timeout
-        self.execute_with_timeout(&mut cmd, timeout)
+        self.execute_with_timeout(&mut cmd, timeout, stdin)
+    }
+
+    /// Run one hook script against a sample task without performing a real
+    /// task operation. Useful for hook authors testing their scripts.
+    pub fn run_single<P: AsRef<Path>>(
+        &self,
+        path: P,
+        event: HookEvent,
+        sample_task: Task,
+    ) -> Result<HookRun, TaskError> {
+        let config = HookConfig::new(path.as_ref(), vec![event.clone()]);
+        let context = HookContext::with_task(event, sample_task);
+        self.run_hook(&config, &context)
+    }
+
+    /// Build the stdin payload: the original task (for modify events) followed
+    /// by the current task, one JSON document per line, as Taskwarrior does.
+    fn stdin_payload(context: &HookContext) -> Result<String, TaskError> {
+        let mut payload = String::new();
+        for task in [&context.old_task, &context.task].into_iter().flatten() {
+            payload.push_str(&serde_json::to_string(task)?);
+            payload.push('\n');
+        }
+        Ok(payload)
     }
 
     /// Prepare the command for execution
@@ -125,24 +329,29 @@ impl HookExecutor {
         config: &HookConfig,
         context: &HookContext,
     ) -> Result<Command, TaskError> {
-        // Some environments may not correctly honor the shebang interpreter path
-        // when executing scripts. To make tests and execution more robust, run
-        // shell scripts via the system shell on Unix.
-        #[cfg(unix)]
-        let mut cmd = {
-            // Use /bin/sh to execute script path as an argument. This is portable
-            // and avoids relying on the shebang pointing to a missing interpreter.
-            let mut c = Command::new("/bin/sh");
-            c.arg(&config.path);
-            c
+        // Run the interpreter ourselves rather than relying on the OS to honor
+        // the shebang: Windows ignores it, and the path it names may not exist
+        // on this machine.
+        let mut cmd = match self.resolve_interpreter(&config.path) {
+            Some(argv) => {
+                let mut c = Command::new(&argv[0]);
+                c.args(&argv[1..]).arg(&config.path);
+                c
+            }
+            #[cfg(unix)]
+            None => {
+                let mut c = Command::new("/bin/sh");
+                c.arg(&config.path);
+                c
+            }
+            #[cfg(not(unix))]
+            None => Command::new(&config.path),
         };
 
-        #[cfg(not(unix))]
-        let mut cmd = Command::new(&config.path);
-
         // Set working directory
         if let Some(ref working_dir) = config.working_directory {
-            cmd.current_dir(working_dir);
+            let expanded = self.expand_placeholders(&working_dir.to_string_lossy(), context);
+            cmd.current_dir(expanded);
         }
 
         // Set up stdio
@@ -153,12 +362,12 @@ impl HookExecutor {
         // Set environment variables
         // Start with default environment
         for (key, value) in &self.default_env {
-            cmd.env(key, value);
+            cmd.env(key, self.expand_placeholders(value, context));
         }
 
         // Add hook-specific environment
         for (key, value) in &config.environment {
-            cmd.env(key, value);
+            cmd.env(key, self.expand_placeholders(value, context));
         }
 
         // Add context-specific environment variables
@@ -207,87 +416,154 @@ impl HookExecutor {
         &self,
         cmd: &mut Command,
         timeout: Duration,
-    ) -> Result<HookResult, TaskError> {
+        stdin_data: String,
+    ) -> Result<HookRun, TaskError> {
         let start_time = Instant::now();
 
+        // In a process group of its own, so a timeout also stops anything
+        // the hook started in the background
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
         // Spawn the process
         let mut child = cmd.spawn().map_err(|e| TaskError::HookFailed {
             message: format!("Failed to spawn hook process: {e}"),
         })?;
 
-        // Send context data as JSON to stdin
-        if let Some(stdin) = child.stdin.take() {
-            // This is optional - hooks can also use environment variables
-            drop(stdin);
-        }
+        // Feed stdin and drain stdout/stderr on helper threads so a chatty
+        // script can't block on a full pipe
+        let writer = child.stdin.take().map(|mut stdin| {
+            let data = stdin_data.clone();
+            std::thread::spawn(move || {
+                // Hooks are free to ignore stdin, so a broken pipe is fine
+                let _ = stdin.write_all(data.as_bytes());
+            })
+        });
+        let stdout_reader = child.stdout.take().map(Self::spawn_reader);
+        let stderr_reader = child.stderr.take().map(Self::spawn_reader);
 
         // Wait for the process to complete or timeout
-        loop {
+        let (status, timed_out) = loop {
             if start_time.elapsed() >= timeout {
-                // Kill the process if it's taking too long
-                if child.kill().is_err() {
-                    // Process might have already finished
-                }
-                return Ok(HookResult::Error("Hook execution timed out".to_string()));
+                // Kill the process if it's taking too long; it may already have exited
+                Self::kill_process_group(&child);
+                let _ = child.kill();
+                let _ = child.wait();
+                break (None, true);
             }
 
             // Check if process has finished
             match child.try_wait() {
-                Ok(Some(status)) => {
-                    // Process finished
-                    return self.process_result(status, &mut child);
-                }
+                Ok(Some(status)) => break (Some(status), false),
                 Ok(None) => {
                     // Process still running, wait a bit
-                    std::thread::sleep(Duration::from_millis(100));
-                    continue;
+                    std::thread::sleep(Duration::from_millis(10));
                 }
                 Err(e) => {
-                    return Ok(HookResult::Error(format!("Error waiting for hook: {e}")));
+                    return Err(TaskError::HookFailed {
+                        message: format!("Error waiting for hook: {e}"),
+                    });
                 }
             }
+        };
+        let duration = start_time.elapsed();
+
+        // Something the hook left running in the background can hold the
+        // pipes open, so stop waiting for them at the timeout (with a grace
+        // period for output written just before the exit)
+        let deadline = (start_time + timeout).max(Instant::now() + PIPE_GRACE);
+        if let Some(writer) = writer {
+            Self::join_until(writer, deadline);
         }
+        let join = |reader: Option<PipeReader>| {
+            reader.map(|r| r.finish(deadline)).unwrap_or_default()
+        };
+        let stdout = join(stdout_reader);
+        let stderr = join(stderr_reader);
+
+        let result = match status {
+            Some(status) => self.process_result(status),
+            None => HookResult::Error("Hook execution timed out".to_string()),
+        };
+
+        Ok(HookRun {
+            stdin: stdin_data,
+            stdout,
+            stderr,
+            exit_code: status.and_then(|s| s.code()),
+            duration,
+            timed_out,
+            result,
+        })
     }
 
-    /// Process the execution result
-    fn process_result(
-        &self,
-        status: std::process::ExitStatus,
-        _child: &mut std::process::Child,
-    ) -> Result<HookResult, TaskError> {
-        // Note: We can't use wait_with_output here because we already waited for the process
-        // The stdout and stderr would need to be captured during execution
+    /// Read a child pipe to completion on a background thread
+    fn spawn_reader<R: Read + Send + 'static>(mut pipe: R) -> PipeReader {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&output);
+        let handle = std::thread::spawn(move || {
+            let mut chunk = [0u8; 8192];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => sink
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .extend_from_slice(&chunk[..n]),
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        PipeReader { handle, output }
+    }
+
+    /// Wait for a helper thread until `deadline`, then leave it behind
+    fn join_until(handle: std::thread::JoinHandle<()>, deadline: Instant) {
+        while !handle.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if handle.is_finished() {
+            let _ = handle.join();
+        }
+    }
 
+    /// Kill every process in the hook's process group
+    #[cfg(unix)]
+    fn kill_process_group(child: &std::process::Child) {
+        if let Ok(pgid) = libc::pid_t::try_from(child.id()) {
+            // SAFETY: kill(2) takes no pointers; the negative pid names the
+            // group created for this hook in `execute_with_timeout`
+            unsafe {
+                libc::kill(-pgid, libc::SIGKILL);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn kill_process_group(child: &std::process::Child) {
+        let _ = child;
+    }
+
+    /// Process the execution result
+    fn process_result(&self, status: std::process::ExitStatus) -> HookResult {
         // Interpret exit code
         match status.code() {
-            Some(0) => {
-                // Success
-                Ok(HookResult::Success)
-            }
-            Some(1) => {
-                // Warning - hook succeeded but wants to warn
-                Ok(HookResult::Warning(
-                    "Hook completed with warnings".to_string(),
-                ))
-            }
-            Some(2) => {
-                // Error - hook failed but operation should continue
-                Ok(HookResult::Error("Hook failed".to_string()))
-            }
-            Some(3) => {
-                // Abort - hook failed and operation should be aborted
-                Ok(HookResult::Abort("Hook aborted operation".to_string()))
-            }
-            Some(code) => {
-                // Other exit codes treated as errors
-                Ok(HookResult::Error(format!("Hook exited with code {code}")))
-            }
-            None => {
-                // Process was terminated by a signal
-                Ok(HookResult::Error(
-                    "Hook was terminated by signal".to_string(),
-                ))
-            }
+            // Success
+            Some(0) => HookResult::Success,
+            // Warning - hook succeeded but wants to warn
+            Some(1) => HookResult::Warning("Hook completed with warnings".to_string()),
+            // Error - hook failed but operation should continue
+            Some(2) => HookResult::Error("Hook failed".to_string()),
+            // Abort - hook failed and operation should be aborted
+            Some(3) => HookResult::Abort("Hook aborted operation".to_string()),
+            // Other exit codes treated as errors
+            Some(code) => HookResult::Error(format!("Hook exited with code {code}")),
+            // Process was terminated by a signal
+            None => HookResult::Error("Hook was terminated by signal".to_string()),
         }
     }
 
@@ -320,6 +596,28 @@ impl HookExecutor {
     }
 }
 
+/// Interpreter command line from a `#!` line. `env` and its `-S` flag are
+/// dropped, and an absolute interpreter path that does not exist here is
+/// reduced to its file name so it is found on `PATH`.
+fn parse_shebang(line: &str) -> Option<Vec<String>> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace().peekable();
+    if words
+        .peek()
+        .is_some_and(|w| Path::new(w).file_name().is_some_and(|n| n == "env"))
+    {
+        words.next();
+        words.next_if_eq(&"-S");
+    }
+
+    let program = words.next()?;
+    let program = if Path::new(program).is_absolute() && !Path::new(program).exists() {
+        Path::new(program).file_name()?.to_str()?
+    } else {
+        program
+    };
+    Some(std::iter::once(program).chain(words).map(str::to_string).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -441,6 +739,45 @@ exit 0
         }
     }
 
+    #[test]
+    fn test_background_process_does_not_block_the_hook() {
+        let temp_dir = TempDir::new().unwrap();
+        let executor = HookExecutor::new();
+        let context = HookContext::new(HookEvent::PreAdd);
+
+        // Exits at once, but the background sleep inherits its pipes
+        let script_path = create_test_script(&temp_dir, "#!/bin/sh\necho started\nsleep 600 &\nexit 0");
+        let config = HookConfig::new(&script_path, vec![HookEvent::PreAdd]).with_timeout(1);
+        let started = Instant::now();
+        let run = executor.run_hook(&config, &context).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(!run.timed_out);
+        assert!(run.result.is_success());
+        assert_eq!(run.stdout.trim(), "started");
+
+        // Times out, and the whole process group goes with it
+        let pid_file = temp_dir.path().join("sleeper.pid");
+        let script = format!(
+            "#!/bin/sh\nsleep 600 &\necho $! > {}\nsleep 600",
+            pid_file.display()
+        );
+        let script_path = create_test_script(&temp_dir, &script);
+        let config = HookConfig::new(&script_path, vec![HookEvent::PreAdd]).with_timeout(1);
+        let started = Instant::now();
+        let run = executor.run_hook(&config, &context).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(run.timed_out);
+        // Killed, if perhaps not yet reaped
+        #[cfg(target_os = "linux")]
+        {
+            let pid = fs::read_to_string(&pid_file).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+            let status = fs::read_to_string(format!("/proc/{}/status", pid.trim())).unwrap_or_default();
+            let running = status.lines().any(|l| l.starts_with("State:") && !l.contains("zombie"));
+            assert!(!running, "background process {} survived", pid.trim());
+        }
+    }
+
     #[test]
     fn test_make_executable() {
         let temp_dir = TempDir::new().unwrap();
@@ -456,4 +793,118 @@ exit 0
         executor.make_executable(&script_path).unwrap();
         assert!(executor.is_executable(&script_path));
     }
+
+    #[test]
+    fn test_run_single_captures_io() {
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = create_test_script(
+            &temp_dir,
+            "#!/bin/sh\ncat\necho \"event=$TASKWARRIOR_HOOK_EVENT\"\necho oops >&2\nexit 1",
+        );
+
+        let task = Task::new("Sample".to_string());
+        let executor = HookExecutor::new();
+        let run = executor
+            .run_single(&script_path, HookEvent::OnAdd, task.clone())
+            .unwrap();
+
+        assert_eq!(run.exit_code, Some(1));
+        assert!(!run.timed_out);
+        assert!(matches!(run.result, HookResult::Warning(_)));
+        assert!(run.stdin.contains("Sample"));
+        assert!(run.stdout.contains(&task.id.to_string()));
+        assert!(run.stdout.contains("event=on-add"));
+        assert_eq!(run.stderr.trim(), "oops");
+    }
+
+    #[test]
+    fn test_run_single_missing_script() {
+        let executor = HookExecutor::new();
+        let result = executor.run_single(
+            "/nonexistent/hook.sh",
+            HookEvent::OnAdd,
+            Task::new("x".to_string()),
+        );
+        assert!(matches!(result, Err(TaskError::HookFailed { .. })));
+    }
+
+    #[test]
+    fn test_placeholders_expanded_in_env_and_working_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_dir = temp_dir.path().join("Home");
+        fs::create_dir_all(&work_dir).unwrap();
+        let script_path = create_test_script(
+            &temp_dir,
+            "#!/bin/sh\necho \"$TARGET\"\npwd",
+        );
+
+        let mut task = Task::new("Templated".to_string());
+        task.project = Some("Home".to_string());
+        let config = HookConfig::new(&script_path, vec![HookEvent::OnAdd])
+            .with_env("TARGET", "{data_dir}/{task_uuid}")
+            .with_working_dir(temp_dir.path().join("{project}"));
+        let context = HookContext::with_task(HookEvent::OnAdd, task.clone());
+        let executor = HookExecutor::new().with_data_dir("/data");
+
+        let run = executor.run_hook(&config, &context).unwrap();
+        let mut lines = run.stdout.lines();
+        assert_eq!(lines.next(), Some(format!("/data/{}", task.id).as_str()));
+        assert!(lines.next().unwrap().ends_with("Home"));
+    }
+
+    #[test]
+    fn test_parse_shebang() {
+        let argv = |line| parse_shebang(line).unwrap();
+        assert_eq!(argv("#!/usr/bin/env python3\n"), vec!["python3"]);
+        assert_eq!(argv("#!/usr/bin/env -S node --no-warnings"), vec!["node", "--no-warnings"]);
+        assert_eq!(argv("#!/nonexistent/bin/python3 -u"), vec!["python3", "-u"]);
+        assert_eq!(argv("#!/bin/sh"), vec!["/bin/sh"]);
+        assert_eq!(parse_shebang("echo hi"), None);
+        assert_eq!(parse_shebang("#!"), None);
+    }
+
+    #[test]
+    fn test_interpreter_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let script = temp_dir.path().join("on-add.py");
+        fs::write(&script, "#!/usr/bin/env python3\nprint('hi')\n").unwrap();
+        let plain = temp_dir.path().join("on-add");
+        fs::write(&plain, "echo hi\n").unwrap();
+
+        let executor = HookExecutor::new();
+        assert_eq!(executor.resolve_interpreter(&script), Some(vec!["python3".to_string()]));
+        assert_eq!(executor.resolve_interpreter(&plain), None);
+
+        // The extension map wins over the shebang
+        let executor = HookExecutor::new().with_interpreter(".PY", "python -X utf8");
+        assert_eq!(
+            executor.resolve_interpreter(&script),
+            Some(vec!["python".to_string(), "-X".to_string(), "utf8".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_mapped_interpreter_runs_script() {
+        let temp_dir = TempDir::new().unwrap();
+        // No shebang and not executable: only the mapping can run it
+        let script = temp_dir.path().join("hook.tw");
+        fs::write(&script, "echo \"via $0\"\n").unwrap();
+
+        let executor = HookExecutor::new().with_interpreter("tw", "sh");
+        let run = executor
+            .run_single(&script, HookEvent::OnAdd, Task::new("x".to_string()))
+            .unwrap();
+        assert!(run.result.is_success(), "{run}");
+        assert!(run.stdout.contains("hook.tw"));
+    }
+
+    #[test]
+    fn test_unknown_placeholders_untouched() {
+        let executor = HookExecutor::new();
+        let context = HookContext::new(HookEvent::OnAdd);
+        assert_eq!(
+            executor.expand_placeholders("{other}/{project}", &context),
+            "{other}/"
+        );
+    }
 }
@@ -5,7 +5,7 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::config::Configuration;
+    use crate::config::{Configuration, ConfigurationProvider};
     use crate::hooks::DefaultHookSystem;
     use crate::storage::FileStorageBackend;
     use crate::task::manager::{DefaultTaskManager, TaskManager, TaskUpdate};
@@ -167,6 +167,53 @@ mod tests {
         assert_eq!(completed_task.status, TaskStatus::Completed);
     }
 
+    #[test]
+    fn test_hook_feedback_and_nag() {
+        use crate::task::manager::DEFAULT_NAG;
+
+        let temp_dir = TempDir::new().unwrap();
+        let hooks_dir = temp_dir.path().join("hooks");
+        fs::create_dir_all(&hooks_dir).unwrap();
+        create_test_hook_script(&hooks_dir, "on-complete.sh", "#!/bin/sh\nexit 1");
+
+        let mut hook_system = DefaultHookSystem::new();
+        hook_system.load_hooks_from_dir(&hooks_dir).unwrap();
+        let storage_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&storage_dir).unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(storage_dir));
+        let mut task_manager =
+            DefaultTaskManager::new(Configuration::default(), storage, Box::new(hook_system))
+                .unwrap();
+
+        let chore = task_manager.add_task("Water plants".to_string()).unwrap();
+        let urgent = task_manager.add_task("File taxes".to_string()).unwrap();
+        task_manager
+            .update_task(urgent.id, TaskUpdate::new().due(chrono::Utc::now()))
+            .unwrap();
+
+        task_manager.start_task(chore.id).unwrap();
+        assert_eq!(task_manager.take_feedback(), [DEFAULT_NAG]);
+        task_manager.complete_task(chore.id).unwrap();
+        assert_eq!(
+            task_manager.take_feedback(),
+            ["Hook completed with warnings", DEFAULT_NAG]
+        );
+        assert!(task_manager.take_feedback().is_empty());
+
+        // Nothing is more urgent than the last pending task
+        task_manager.complete_task(urgent.id).unwrap();
+        assert_eq!(task_manager.take_feedback(), ["Hook completed with warnings"]);
+
+        task_manager.config_mut().set("nag", "");
+        let chore = task_manager.add_task("Dust shelves".to_string()).unwrap();
+        let urgent = task_manager.add_task("Renew passport".to_string()).unwrap();
+        task_manager
+            .update_task(urgent.id, TaskUpdate::new().due(chrono::Utc::now()))
+            .unwrap();
+        task_manager.start_task(chore.id).unwrap();
+        assert!(task_manager.take_feedback().is_empty());
+    }
+
     #[test]
     fn test_delete_task_hooks() {
         let temp_dir = TempDir::new().unwrap();
@@ -208,4 +255,86 @@ mod tests {
         let retrieved = task_manager.get_task(task.id).unwrap();
         assert!(retrieved.is_none());
     }
+
+    /// Native hook that records events and rejects descriptions containing "forbidden"
+    #[derive(Debug, Default)]
+    struct RecordingHook {
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl crate::hooks::NativeHook for RecordingHook {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn handle(&mut self, context: &crate::hooks::HookContext) -> Result<(), crate::error::TaskError> {
+            self.events.lock().unwrap().push(context.event.to_string());
+            match &context.task {
+                Some(task) if task.description.contains("forbidden") => Err(crate::error::TaskError::Hook {
+                    message: "rejected".to_string(),
+                }),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_native_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let hook = RecordingHook::default();
+        let events = std::sync::Arc::clone(&hook.events);
+
+        let mut hook_system = DefaultHookSystem::new();
+        hook_system.register_native(Box::new(hook));
+        assert_eq!(hook_system.hook_count(), 1);
+
+        let storage = Box::new(FileStorageBackend::with_path(temp_dir.path().to_path_buf()));
+        let mut task_manager =
+            DefaultTaskManager::new(Configuration::default(), storage, Box::new(hook_system)).unwrap();
+
+        let task = task_manager.add_task("Allowed".to_string()).unwrap();
+        task_manager.complete_task(task.id).unwrap();
+        let recorded = events.lock().unwrap().clone();
+        assert_eq!(recorded.first().map(String::as_str), Some("pre-add"));
+        assert!(recorded.contains(&"on-complete".to_string()));
+
+        // Errors from pre-events abort the operation
+        let err = task_manager.add_task("forbidden task".to_string()).unwrap_err();
+        assert!(matches!(err.root(), crate::error::TaskError::HookFailed { .. }));
+        assert_eq!(err.context().map(|c| c.operation.as_str()), Some("add"));
+    }
+
+    #[test]
+    fn test_trash_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let hook = RecordingHook::default();
+        let events = std::sync::Arc::clone(&hook.events);
+        let mut hook_system = DefaultHookSystem::new();
+        hook_system.register_native(Box::new(hook));
+        let storage = Box::new(FileStorageBackend::with_path(temp_dir.path().to_path_buf()));
+        let mut task_manager =
+            DefaultTaskManager::new(Configuration::default(), storage, Box::new(hook_system)).unwrap();
+
+        let task = task_manager.add_task("Oops".to_string()).unwrap();
+        let trashed = task_manager
+            .update_task(task.id, TaskUpdate::new().status(TaskStatus::Deleted))
+            .unwrap();
+        assert!(trashed.end.is_some());
+        assert_eq!(task_manager.deleted_tasks().unwrap().len(), 1);
+        assert!(task_manager.pending_tasks().unwrap().is_empty());
+
+        events.lock().unwrap().clear();
+        let restored = task_manager.restore_task(task.id).unwrap();
+        assert_eq!(restored.status, TaskStatus::Pending);
+        assert!(restored.end.is_none());
+        assert_eq!(*events.lock().unwrap(), vec!["pre-restore", "post-modify", "post-restore"]);
+        assert!(task_manager.deleted_tasks().unwrap().is_empty());
+        assert_eq!(task_manager.pending_tasks().unwrap().len(), 1);
+
+        // Only trashed tasks can be restored
+        assert!(matches!(
+            task_manager.restore_task(task.id),
+            Err(crate::error::TaskError::InvalidState { .. })
+        ));
+    }
 }
@@ -62,7 +62,7 @@
 //! providing seamless hook execution during task operations.
 
 use crate::error::TaskError;
-use crate::hooks::config::HookConfig;
+use crate::hooks::config::{HookConfig, UntrustedHook};
 use crate::hooks::events::{HookContext, HookEvent};
 use crate::hooks::executor::HookExecutor;
 use crate::hooks::HookConfigCollection;
@@ -126,6 +126,8 @@ pub struct DefaultHookManager {
     hooks: Vec<HookConfig>,
     /// Hook executor
     executor: HookExecutor,
+    /// Scripts that failed allowlist verification in the last load
+    untrusted: Vec<UntrustedHook>,
 }
 
 impl Default for DefaultHookManager {
@@ -140,6 +142,7 @@ impl DefaultHookManager {
         Self {
             hooks: Vec::new(),
             executor: HookExecutor::new(),
+            untrusted: Vec::new(),
         }
     }
 
@@ -148,19 +151,37 @@ impl DefaultHookManager {
         Self {
             hooks: Vec::new(),
             executor,
+            untrusted: Vec::new(),
         }
     }
 
+    /// Set the data directory that `{data_dir}` placeholders expand to
+    pub fn with_data_dir<P: Into<PathBuf>>(mut self, data_dir: P) -> Self {
+        self.set_data_dir(data_dir);
+        self
+    }
+
+    /// Set the data directory that `{data_dir}` placeholders expand to
+    pub fn set_data_dir<P: Into<PathBuf>>(&mut self, data_dir: P) {
+        self.executor = std::mem::take(&mut self.executor).with_data_dir(data_dir);
+    }
+
     /// Get number of registered hooks
     pub fn hook_count(&self) -> usize {
         self.hooks.len()
     }
 
-    /// Load hooks from configuration directory
+    /// Load hooks from configuration directory.
+    ///
+    /// Like Taskwarrior's `hooks/` under the data location, the directory
+    /// doubles as `{data_dir}` unless a data directory was already set.
     pub fn load_from_config_dir<P: AsRef<std::path::Path>>(
         &mut self,
         config_dir: P,
     ) -> Result<(), TaskError> {
+        if self.executor.data_dir().is_none() {
+            self.set_data_dir(config_dir.as_ref());
+        }
         let hooks_dir = config_dir.as_ref().join("hooks");
         if !hooks_dir.exists() {
             return Ok(());
@@ -177,6 +198,7 @@ impl DefaultHookManager {
     ) -> Result<(), TaskError> {
         // Clear existing hooks
         self.hooks.clear();
+        self.untrusted = collection.untrusted;
 
         // Add hooks from collection
         for hook_config in collection.hooks {
@@ -185,8 +207,8 @@ impl DefaultHookManager {
 
         // Update executor with global settings
         if let Some(timeout) = collection.global_timeout {
-            self.executor =
-                HookExecutor::new().with_default_timeout(std::time::Duration::from_secs(timeout));
+            self.executor = std::mem::take(&mut self.executor)
+                .with_default_timeout(std::time::Duration::from_secs(timeout));
         }
 
         // Add global environment variables
@@ -194,14 +216,27 @@ impl DefaultHookManager {
             self.executor = std::mem::take(&mut self.executor).with_default_env(key, value);
         }
 
+        for (ext, command) in collection.interpreters {
+            self.executor = std::mem::take(&mut self.executor).with_interpreter(ext, command);
+        }
+
         Ok(())
     }
 
+    /// Scripts the last load found missing from the allowlist, changed
+    /// since, or listed under a bad signature
+    pub fn untrusted_hooks(&self) -> &[UntrustedHook] {
+        &self.untrusted
+    }
+
     /// Discover and load hooks from standard locations
     pub fn discover_and_load_hooks(&mut self) -> Result<(), TaskError> {
         let task_data_dir = dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("~/.local/share"))
             .join("taskwarrior");
+        if self.executor.data_dir().is_none() {
+            self.set_data_dir(&task_data_dir);
+        }
 
         let collection = HookConfigCollection::discover_from_standard_locations(&task_data_dir)?;
         self.load_from_collection(collection)
@@ -216,7 +251,7 @@ impl DefaultHookManager {
             .collect();
 
         // Sort by priority (lower numbers first)
-        hooks.sort_by(|a, b| a.priority.cmp(&b.priority));
+        hooks.sort_by_key(|hook| hook.priority);
         hooks
     }
 }
@@ -226,7 +261,7 @@ impl HookManager for DefaultHookManager {
         let hooks = self.get_hooks_for_event(&context.event);
         let mut results = Vec::new();
 
-        for hook in hooks {
+        for hook in hooks.into_iter().filter(|hook| hook.applies_to(context)) {
             let result = self.executor.execute_hook(hook, context)?;
             results.push(result);
         }
@@ -327,4 +362,42 @@ mod tests {
         let results = manager.execute_hooks(&context).unwrap();
         assert_eq!(results.len(), 0); // No hooks registered
     }
+
+    #[test]
+    fn test_project_scoped_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = temp_dir.path().join("on-add.sh");
+        std::fs::write(&script_path, "#!/bin/sh\nexit 0").unwrap();
+
+        let mut manager = DefaultHookManager::new();
+        let config = HookConfig::new(&script_path, vec![HookEvent::OnAdd]).with_project("Work");
+        manager.register_hook(config).unwrap();
+
+        let run_for = |project: Option<&str>| {
+            let mut task = crate::task::Task::new("x".to_string());
+            task.project = project.map(str::to_string);
+            let context = HookContext::with_task(HookEvent::OnAdd, task);
+            manager.execute_hooks(&context).unwrap().len()
+        };
+        assert_eq!(run_for(Some("Work")), 1);
+        assert_eq!(run_for(Some("Work.Reports")), 1);
+        assert_eq!(run_for(Some("Workshop")), 0);
+        assert_eq!(run_for(None), 0);
+    }
+
+    #[test]
+    fn test_data_dir_survives_global_settings() {
+        let collection = HookConfigCollection {
+            global_timeout: Some(5),
+            ..Default::default()
+        };
+        let mut manager = DefaultHookManager::new().with_data_dir("/data");
+        manager.load_from_collection(collection).unwrap();
+        assert_eq!(manager.executor.data_dir(), Some(std::path::Path::new("/data")));
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = DefaultHookManager::new();
+        manager.load_from_config_dir(temp_dir.path()).unwrap();
+        assert_eq!(manager.executor.data_dir(), Some(temp_dir.path()));
+    }
 }
@@ -76,6 +76,8 @@
 //! 1. **Directory structure**: Place executable scripts in hooks directory
 //! 2. **TOML files**: Create `.hookrc` files for advanced configuration
 //! 3. **Programmatic**: Use the API to configure hooks in code
+//! 4. **Native hooks**: Register in-process Rust hooks with
+//!    [`DefaultHookSystem::register_native`]
 //!
 //! See [`HookConfig`] and [`DefaultHookSystem`] for configuration options.
 //!
@@ -89,19 +91,29 @@
 //! For complete documentation and examples, see the [README](README.md).
 
 pub mod config;
+pub mod debug;
 pub mod events;
 pub mod executor;
 pub mod manager;
+pub mod native;
+#[cfg(feature = "notify-desktop")]
+pub mod notify;
+#[cfg(feature = "timewarrior")]
+pub mod timewarrior;
 
 #[cfg(test)]
 pub mod integration_test;
 
 use crate::error::TaskError;
 use crate::task::Task;
-pub use config::{HookConfig, HookConfigCollection};
+pub use config::{
+    HookAllowlist, HookConfig, HookConfigCollection, HookPolicy, HookTrust, UntrustedHook,
+    UntrustedReason,
+};
 pub use events::{HookContext, HookEvent, HookEventData};
-pub use executor::HookExecutor;
+pub use executor::{HookExecutor, HookRun};
 pub use manager::{DefaultHookManager, HookManager, HookResult};
+pub use native::NativeHook;
 
 /// Hook system trait for task operations
 pub trait HookSystem: std::fmt::Debug {
@@ -122,6 +134,12 @@ pub trait HookSystem: std::fmt::Debug {
 
     /// Called after an operation
     fn post_operation(&mut self, operation: &str, task: Option<&Task>) -> Result<(), TaskError>;
+
+    /// Messages for the user from hooks run since the last call, such as
+    /// warnings of hooks that did not abort, oldest first
+    fn take_feedback(&mut self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// Enhanced hook system implementation with script execution
@@ -129,6 +147,10 @@ pub trait HookSystem: std::fmt::Debug {
 pub struct DefaultHookSystem {
     /// Hook manager for executing hooks
     hook_manager: DefaultHookManager,
+    /// In-process hooks, run after script hooks
+    native_hooks: Vec<Box<dyn NativeHook>>,
+    /// Hook messages not yet taken with `take_feedback`
+    feedback: Vec<String>,
 }
 
 impl Default for DefaultHookSystem {
@@ -142,9 +164,18 @@ impl DefaultHookSystem {
     pub fn new() -> Self {
         Self {
             hook_manager: DefaultHookManager::new(),
+            native_hooks: Vec::new(),
+            feedback: Vec::new(),
         }
     }
 
+    /// Set the data directory that `{data_dir}` placeholders in hook
+    /// settings expand to
+    pub fn with_data_dir<P: Into<std::path::PathBuf>>(mut self, data_dir: P) -> Self {
+        self.hook_manager.set_data_dir(data_dir);
+        self
+    }
+
     /// Create new hook system with hooks loaded from directory
     pub fn with_hooks_from_dir<P: AsRef<std::path::Path>>(hooks_dir: P) -> Result<Self, TaskError> {
         let mut hook_system = Self::new();
@@ -158,6 +189,18 @@ impl DefaultHookSystem {
         hooks_dir: P,
     ) -> Result<(), TaskError> {
         let collection = HookConfigCollection::load_from_dir(hooks_dir.as_ref())?;
+        for untrusted in &collection.untrusted {
+            let action = if collection.hooks.iter().any(|hook| hook.path == untrusted.path) {
+                "running anyway"
+            } else {
+                "skipped"
+            };
+            self.feedback.push(format!(
+                "Hook {} {}, {action}",
+                untrusted.path.display(),
+                untrusted.reason
+            ));
+        }
 
         // Register all discovered hooks
         for hook_config in collection.hooks {
@@ -188,9 +231,14 @@ impl DefaultHookSystem {
         &mut self.hook_manager
     }
 
+    /// Register an in-process hook
+    pub fn register_native(&mut self, hook: Box<dyn NativeHook>) {
+        self.native_hooks.push(hook);
+    }
+
     /// Get number of registered hooks
     pub fn hook_count(&self) -> usize {
-        self.hook_manager.hook_count()
+        self.hook_manager.hook_count() + self.native_hooks.len()
     }
 
     /// Execute hooks for a given context
@@ -207,6 +255,19 @@ impl DefaultHookSystem {
                         .to_string(),
                 });
             }
+            if let Some(message) = result.message() {
+                self.feedback.push(message.to_string());
+            }
+        }
+
+        for hook in &mut self.native_hooks {
+            if let Err(e) = hook.handle(context) {
+                if context.event.is_pre_event() {
+                    return Err(TaskError::HookFailed {
+                        message: format!("{}: {e}", hook.name()),
+                    });
+                }
+            }
         }
 
         Ok(())
@@ -268,4 +329,8 @@ impl HookSystem for DefaultHookSystem {
 
         self.execute_hooks_for_context(&context)
     }
+
+    fn take_feedback(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.feedback)
+    }
 }
@@ -0,0 +1,19 @@
+//! In-process hooks
+//!
+//! A [`NativeHook`] is Rust code registered with
+//! [`DefaultHookSystem::register_native`](crate::hooks::DefaultHookSystem::register_native).
+//! It sees the same [`HookContext`] as script hooks, without spawning a
+//! process. Returning an error from a pre-event aborts the operation; errors
+//! from other events are ignored, matching script hook semantics.
+
+use crate::error::TaskError;
+use crate::hooks::HookContext;
+
+/// Hook implemented in Rust and run in-process
+pub trait NativeHook: std::fmt::Debug {
+    /// Name used in error messages
+    fn name(&self) -> &str;
+
+    /// Handle one hook event
+    fn handle(&mut self, context: &HookContext) -> Result<(), TaskError>;
+}
@@ -0,0 +1,302 @@
+//! Desktop notifications
+//!
+//! [`DesktopNotifier`] is a [`NativeHook`] that raises a desktop notification
+//! when a task is added or completed, and [`DesktopNotifier::notify_due_soon`]
+//! announces pending tasks whose due date is near. Notifications go through
+//! `notify-send` (the freedesktop DBus notification service) on Linux and BSD,
+//! and `osascript` on macOS.
+//!
+//! Messages are templates read from config:
+//!
+//! ```text
+//! notify.title=Taskwarrior
+//! notify.add.template=Added: {description}
+//! notify.complete.template=Completed: {description}
+//! notify.due.template=Due {due}: {description}
+//! notify.due.window=1h
+//! notify.add=off                # disable one kind of notification
+//! ```
+//!
+//! Templates can use `{description}`, `{project}`, `{priority}`, `{tags}`,
+//! `{due}` and `{uuid}`.
+
+use chrono::{DateTime, Duration, Local, Utc};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::date::relative::parse_duration;
+use crate::error::{ConfigError, TaskError};
+use crate::hooks::{HookContext, HookEvent, NativeHook};
+use crate::io::process_runner::{default_runner, ProcessRunner};
+use crate::task::{Task, TaskStatus};
+
+/// Notification templates and switches
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifierConfig {
+    pub title: String,
+    /// Template for added tasks; None disables these notifications
+    pub add_template: Option<String>,
+    /// Template for completed tasks; None disables these notifications
+    pub complete_template: Option<String>,
+    /// Template for due-soon tasks; None disables these notifications
+    pub due_template: Option<String>,
+    /// How far ahead a due date counts as "soon"
+    pub due_window: Duration,
+}
+
+impl Default for NotifierConfig {
+    fn default() -> Self {
+        Self {
+            title: "Taskwarrior".to_string(),
+            add_template: Some("Added: {description}".to_string()),
+            complete_template: Some("Completed: {description}".to_string()),
+            due_template: Some("Due {due}: {description}".to_string()),
+            due_window: Duration::hours(1),
+        }
+    }
+}
+
+impl NotifierConfig {
+    /// Read `notify.*` settings, falling back to the defaults
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let defaults = Self::default();
+        let template = |kind: &str, default: Option<String>| {
+            if config.get_bool(&format!("notify.{kind}")) == Some(false) {
+                return None;
+            }
+            config
+                .get(&format!("notify.{kind}.template"))
+                .cloned()
+                .or(default)
+        };
+
+        let due_window = match config.get("notify.due.window") {
+            Some(raw) => parse_duration(raw).map_err(|_| ConfigError::InvalidValue {
+                key: "notify.due.window".to_string(),
+                value: raw.to_string(),
+                expected: "a duration such as '30min' or '1h'".to_string(),
+            })?,
+            None => defaults.due_window,
+        };
+
+        Ok(Self {
+            title: config.get("notify.title").cloned().unwrap_or(defaults.title),
+            add_template: template("add", defaults.add_template),
+            complete_template: template("complete", defaults.complete_template),
+            due_template: template("due", defaults.due_template),
+            due_window,
+        })
+    }
+}
+
+/// Notification hook for desktop environments
+pub struct DesktopNotifier {
+    config: NotifierConfig,
+    runner: Box<dyn ProcessRunner>,
+    /// Tasks already announced as added (post-add can fire more than once)
+    added: HashSet<Uuid>,
+    /// Tasks already announced as due soon, with the due date announced
+    due_notified: HashSet<(Uuid, DateTime<Utc>)>,
+}
+
+impl std::fmt::Debug for DesktopNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DesktopNotifier")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DesktopNotifier {
+    /// Notifier using the system notification command
+    pub fn new(config: NotifierConfig) -> Self {
+        Self::with_runner(config, default_runner())
+    }
+
+    /// Notifier with templates from `notify.*` settings
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        Ok(Self::new(NotifierConfig::from_config(config)?))
+    }
+
+    /// Notifier using a custom process runner
+    pub fn with_runner(config: NotifierConfig, runner: Box<dyn ProcessRunner>) -> Self {
+        Self {
+            config,
+            runner,
+            added: HashSet::new(),
+            due_notified: HashSet::new(),
+        }
+    }
+
+    /// Notify about pending tasks due within the window that have not been
+    /// announced yet. Returns the number of notifications sent.
+    pub fn notify_due_soon(&mut self, tasks: &[Task], now: DateTime<Utc>) -> Result<usize, TaskError> {
+        let Some(template) = self.config.due_template.clone() else {
+            return Ok(0);
+        };
+        let mut sent = 0;
+        for task in tasks {
+            let Some(due) = task.due else {
+                continue;
+            };
+            let window_end = now
+                .checked_add_signed(self.config.due_window)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            if task.status != TaskStatus::Pending || due < now || due > window_end {
+                continue;
+            }
+            if self.due_notified.insert((task.id, due)) {
+                self.send(&render_template(&template, task))?;
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    fn send(&self, body: &str) -> Result<(), TaskError> {
+        let title = self.config.title.as_str();
+        let (program, args): (&str, Vec<String>) = if cfg!(target_os = "macos") {
+            let script = format!(
+                "display notification {} with title {}",
+                applescript_string(body),
+                applescript_string(title)
+            );
+            ("osascript", vec!["-e".to_string(), script])
+        } else {
+            ("notify-send", vec![title.to_string(), body.to_string()])
+        };
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let result = self
+            .runner
+            .run(program, &args, None)
+            .map_err(|_| TaskError::ExternalToolMissing(program.to_string()))?;
+        if result.exit_code != 0 {
+            return Err(TaskError::ExternalToolFailed {
+                name: program.to_string(),
+                exit_code: Some(result.exit_code),
+                stderr: result.stderr,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl NativeHook for DesktopNotifier {
+    fn name(&self) -> &str {
+        "desktop-notifier"
+    }
+
+    fn handle(&mut self, context: &HookContext) -> Result<(), TaskError> {
+        let Some(task) = &context.task else {
+            return Ok(());
+        };
+        let template = match context.event {
+            HookEvent::PostAdd if self.added.insert(task.id) => self.config.add_template.as_ref(),
+            HookEvent::OnComplete => self.config.complete_template.as_ref(),
+            _ => None,
+        };
+        match template {
+            Some(template) => self.send(&render_template(template, task)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Fill `{field}` placeholders from a task
+pub fn render_template(template: &str, task: &Task) -> String {
+    let mut tags: Vec<&str> = task.tags.iter().map(String::as_str).collect();
+    tags.sort_unstable();
+    let due = task
+        .due
+        .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+
+    template
+        .replace("{description}", &task.description)
+        .replace("{project}", task.project.as_deref().unwrap_or(""))
+        .replace("{priority}", task.priority_code().unwrap_or(""))
+        .replace("{tags}", &tags.join(" "))
+        .replace("{due}", &due)
+        .replace("{uuid}", &task.id.to_string())
+}
+
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::process_runner::{MockProcessRunner, ProcessResult};
+    use std::sync::{Arc, Mutex};
+
+    fn recording_notifier(config: NotifierConfig) -> (DesktopNotifier, Arc<Mutex<Vec<Vec<String>>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&calls);
+        let runner = MockProcessRunner {
+            run_fn: move |_cmd: &str, args: &[&str], _timeout| {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(args.iter().map(|a| a.to_string()).collect());
+                Ok(ProcessResult {
+                    exit_code: 0,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                })
+            },
+        };
+        (DesktopNotifier::with_runner(config, Box::new(runner)), calls)
+    }
+
+    #[test]
+    fn test_config_and_templates() {
+        let mut config = Configuration::default();
+        config.set("notify.add", "off");
+        config.set("notify.complete.template", "Done: {description} [{project}]");
+        config.set("notify.due.window", "30min");
+        let notifier_config = NotifierConfig::from_config(&config).unwrap();
+        assert!(notifier_config.add_template.is_none());
+        assert_eq!(notifier_config.due_window, Duration::minutes(30));
+
+        let mut task = Task::new("Ship it".to_string());
+        task.project = Some("Work".to_string());
+        assert_eq!(
+            render_template(notifier_config.complete_template.as_deref().unwrap(), &task),
+            "Done: Ship it [Work]"
+        );
+    }
+
+    #[test]
+    fn test_hook_events_notify_once() {
+        let (mut notifier, calls) = recording_notifier(NotifierConfig::default());
+        let task = Task::new("Call Bob".to_string());
+
+        notifier.handle(&HookContext::with_task(HookEvent::PostAdd, task.clone())).unwrap();
+        notifier.handle(&HookContext::with_task(HookEvent::PostAdd, task.clone())).unwrap();
+        notifier.handle(&HookContext::with_task(HookEvent::PreAdd, task.clone())).unwrap();
+        notifier.handle(&HookContext::with_task(HookEvent::OnComplete, task)).unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].iter().any(|a| a.contains("Added: Call Bob")));
+        assert!(calls[1].iter().any(|a| a.contains("Completed: Call Bob")));
+    }
+
+    #[test]
+    fn test_due_soon() {
+        let (mut notifier, calls) = recording_notifier(NotifierConfig::default());
+        let now = Utc::now();
+        let mut soon = Task::new("soon".to_string());
+        soon.due = Some(now + Duration::minutes(20));
+        let mut later = Task::new("later".to_string());
+        later.due = Some(now + Duration::days(2));
+
+        let tasks = [soon, later];
+        assert_eq!(notifier.notify_due_soon(&tasks, now).unwrap(), 1);
+        assert_eq!(notifier.notify_due_soon(&tasks, now).unwrap(), 0);
+        assert_eq!(calls.lock().unwrap().len(), 1);
+    }
+}
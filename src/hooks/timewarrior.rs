@@ -0,0 +1,354 @@
+//! Timewarrior integration
+//!
+//! [`TimewarriorHook`] is a [`NativeHook`] that mirrors the official
+//! `on-modify.timewarrior` hook: starting a task runs `timew start` with
+//! the task's description, project and tags as Timewarrior tags, stopping
+//! it (or completing or deleting it while active) runs `timew stop`, and
+//! changing those attributes of an active task retags the open interval.
+//!
+//! Going the other way, [`read_intervals`] parses the Timewarrior database
+//! and [`tracked_time`] adds up the intervals tagged with each task, for
+//! comparing against estimates with
+//! [`effort_by_project`](crate::task::effort::effort_by_project).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::{StorageError, TaskError};
+use crate::hooks::{HookContext, HookEvent, NativeHook};
+use crate::io::process_runner::{default_runner, ProcessRunner};
+use crate::task::{Task, TaskStatus};
+
+/// Hook driving `timew` from task start and stop
+pub struct TimewarriorHook {
+    program: String,
+    runner: Box<dyn ProcessRunner>,
+}
+
+impl std::fmt::Debug for TimewarriorHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimewarriorHook")
+            .field("program", &self.program)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for TimewarriorHook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimewarriorHook {
+    /// Hook running `timew` from `PATH`
+    pub fn new() -> Self {
+        Self::with_runner(default_runner())
+    }
+
+    /// Hook using a custom process runner
+    pub fn with_runner(runner: Box<dyn ProcessRunner>) -> Self {
+        Self {
+            program: "timew".to_string(),
+            runner,
+        }
+    }
+
+    /// Run a different `timew` binary
+    pub fn with_program<S: Into<String>>(mut self, program: S) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    fn timew(&self, command: &[&str], tags: &[String]) -> Result<(), TaskError> {
+        let mut args: Vec<&str> = command.to_vec();
+        args.extend(tags.iter().map(String::as_str));
+        args.push(":yes");
+        let result = self
+            .runner
+            .run(&self.program, &args, None)
+            .map_err(|_| TaskError::ExternalToolMissing(self.program.clone()))?;
+        if result.exit_code != 0 {
+            return Err(TaskError::ExternalToolFailed {
+                name: self.program.clone(),
+                exit_code: Some(result.exit_code),
+                stderr: result.stderr,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl NativeHook for TimewarriorHook {
+    fn name(&self) -> &str {
+        "timewarrior"
+    }
+
+    fn handle(&mut self, context: &HookContext) -> Result<(), TaskError> {
+        let Some(task) = &context.task else {
+            return Ok(());
+        };
+        match (&context.event, &context.old_task) {
+            (HookEvent::PostModify, Some(old)) => match (is_tracking(old), is_tracking(task)) {
+                (false, true) => self.timew(&["start"], &interval_tags(task)),
+                (true, false) => self.timew(&["stop"], &interval_tags(old)),
+                (true, true) if interval_tags(old) != interval_tags(task) => {
+                    self.timew(&["untag", "@1"], &interval_tags(old))?;
+                    self.timew(&["tag", "@1"], &interval_tags(task))
+                }
+                _ => Ok(()),
+            },
+            (HookEvent::PostDelete, _) if is_tracking(task) => {
+                self.timew(&["stop"], &interval_tags(task))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether Timewarrior should be tracking `task`: started and not finished
+fn is_tracking(task: &Task) -> bool {
+    task.start.is_some() && !matches!(task.status, TaskStatus::Completed | TaskStatus::Deleted)
+}
+
+/// Timewarrior tags for a task, as the official hook builds them: the
+/// description, the project, then the task's tags
+pub fn interval_tags(task: &Task) -> Vec<String> {
+    let mut tags = vec![task.description.clone()];
+    tags.extend(task.project.clone());
+    let mut task_tags: Vec<String> = task.tags.iter().cloned().collect();
+    task_tags.sort();
+    tags.extend(task_tags);
+    tags
+}
+
+/// One tracked interval
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimewInterval {
+    pub start: DateTime<Utc>,
+    /// None while the interval is still open
+    pub end: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+}
+
+impl TimewInterval {
+    /// Length of the interval, counting an open one up to `now`
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::zero())
+    }
+}
+
+/// The Timewarrior database: `$TIMEWARRIORDB`, `~/.timewarrior` if it
+/// exists, otherwise `timewarrior` in the XDG data directory
+pub fn database_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("TIMEWARRIORDB") {
+        return Some(PathBuf::from(dir));
+    }
+    let legacy = dirs::home_dir().map(|home| home.join(".timewarrior"));
+    match legacy {
+        Some(dir) if dir.is_dir() => Some(dir),
+        _ => dirs::data_dir().map(|dir| dir.join("timewarrior")),
+    }
+}
+
+/// Read every interval from the `data/*.data` files of the database at
+/// `db`, oldest file first. Lines that are not intervals are skipped.
+pub fn read_intervals(db: &Path) -> Result<Vec<TimewInterval>, TaskError> {
+    let data_dir = db.join("data");
+    let entries = match fs::read_dir(&data_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(e)),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .map(|entry| entry.map(|e| e.path()).map_err(io_error))
+        .collect::<Result<_, _>>()?;
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "data"));
+    files.sort();
+
+    let mut intervals = Vec::new();
+    for path in files {
+        let data = fs::read_to_string(&path).map_err(io_error)?;
+        intervals.extend(data.lines().filter_map(parse_interval));
+    }
+    Ok(intervals)
+}
+
+/// Parse a data file line such as
+/// `inc 20250301T090000Z - 20250301T100000Z # "Write report" work`
+pub fn parse_interval(line: &str) -> Option<TimewInterval> {
+    let rest = line.trim().strip_prefix("inc ")?;
+    let (range, tags) = match rest.split_once(" # ") {
+        Some((range, tags)) => (range, tags),
+        None => (rest.trim_end_matches(" #"), ""),
+    };
+    let mut times = range.split(" - ");
+    let start = parse_timestamp(times.next()?)?;
+    let end = match times.next() {
+        Some(end) => Some(parse_timestamp(end)?),
+        None => None,
+    };
+    // Tags may be followed by ` # "annotation"`
+    let tags = tags.split(" # ").next().unwrap_or_default();
+    Some(TimewInterval {
+        start,
+        end,
+        tags: split_tags(tags),
+    })
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s.trim(), "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+/// Split a tag list, honoring double quotes and `\"` escapes
+fn split_tags(s: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => current.extend(chars.next()),
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if !current.is_empty() {
+                    tags.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tags.push(current);
+    }
+    tags
+}
+
+/// Time tracked per task: the intervals tagged with the task's description
+/// (and its project, if it has one), open intervals counted up to `now`
+pub fn tracked_time(
+    tasks: &[Task],
+    intervals: &[TimewInterval],
+    now: DateTime<Utc>,
+) -> HashMap<Uuid, Duration> {
+    let mut tracked = HashMap::new();
+    for task in tasks {
+        let total = intervals
+            .iter()
+            .filter(|i| {
+                i.tags.contains(&task.description)
+                    && task.project.as_ref().is_none_or(|p| i.tags.contains(p))
+            })
+            .fold(Duration::zero(), |sum, i| sum + i.duration(now));
+        if total > Duration::zero() {
+            tracked.insert(task.id, total);
+        }
+    }
+    tracked
+}
+
+fn io_error(e: std::io::Error) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Io(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::process_runner::{MockProcessRunner, ProcessResult};
+    use chrono::TimeZone;
+    use std::sync::{Arc, Mutex};
+
+    fn recording_hook() -> (TimewarriorHook, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&calls);
+        let runner = MockProcessRunner {
+            run_fn: move |_cmd: &str, args: &[&str], _timeout| {
+                recorded.lock().unwrap().push(args.join("|"));
+                Ok(ProcessResult {
+                    exit_code: 0,
+                    stdout: String::new(),
+                    stderr: String::new(),
+                })
+            },
+        };
+        (TimewarriorHook::with_runner(Box::new(runner)), calls)
+    }
+
+    #[test]
+    fn test_start_retag_and_stop() {
+        let (mut hook, calls) = recording_hook();
+        let mut old = Task::new("Write report".to_string());
+        old.project = Some("Work".to_string());
+        let mut started = old.clone();
+        started.start();
+        let mut retagged = started.clone();
+        retagged.tags.insert("urgent".to_string());
+        let mut completed = retagged.clone();
+        completed.status = TaskStatus::Completed;
+
+        for (before, after) in [
+            (&old, &started),
+            (&started, &retagged),
+            (&retagged, &completed),
+        ] {
+            let context =
+                HookContext::with_modify(HookEvent::PostModify, before.clone(), after.clone());
+            hook.handle(&context).unwrap();
+        }
+        // Deleting a task that is not running does nothing
+        hook.handle(&HookContext::with_task(HookEvent::PostDelete, old))
+            .unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "start|Write report|Work|:yes",
+                "untag|@1|Write report|Work|:yes",
+                "tag|@1|Write report|Work|urgent|:yes",
+                "stop|Write report|Work|urgent|:yes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_intervals_and_tracked_time() {
+        let db = tempfile::TempDir::new().unwrap();
+        fs::create_dir(db.path().join("data")).unwrap();
+        fs::write(
+            db.path().join("data/2025-03.data"),
+            concat!(
+                "inc 20250301T090000Z - 20250301T100000Z # \"Write report\" Work\n",
+                "inc 20250302T090000Z - 20250302T093000Z # \"Write report\" Home # \"wrong project\"\n",
+                "inc 20250303T090000Z - 20250303T091500Z # \"Say \\\"hi\\\"\"\n",
+                "inc 20250304T090000Z # \"Write report\" Work\n",
+                "garbage\n",
+            ),
+        )
+        .unwrap();
+        fs::write(db.path().join("data/tags.data"), "{}").unwrap();
+
+        let intervals = read_intervals(db.path()).unwrap();
+        assert_eq!(intervals.len(), 4);
+        assert_eq!(intervals[2].tags, vec!["Say \"hi\""]);
+        assert_eq!(intervals[3].end, None);
+
+        let mut report = Task::new("Write report".to_string());
+        report.project = Some("Work".to_string());
+        let idle = Task::new("Idle".to_string());
+        let now = Utc.with_ymd_and_hms(2025, 3, 4, 9, 45, 0).unwrap();
+        let tracked = tracked_time(&[report.clone(), idle.clone()], &intervals, now);
+        assert_eq!(tracked[&report.id], Duration::minutes(105));
+        assert!(!tracked.contains_key(&idle.id));
+        assert!(read_intervals(&db.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+}
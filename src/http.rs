@@ -0,0 +1,403 @@
+//! Minimal HTTP/1.1 client for the remote backup targets and webhooks
+//!
+//! `https://` URLs go over TLS (rustls, checking the server against the
+//! Mozilla root certificates from `webpki-roots`); `http://` URLs are sent
+//! in the clear. Errors are plain messages; callers wrap them in the
+//! [`TaskError`](crate::error::TaskError) that fits.
+//!
+//! A response body must be framed by `Content-Length` or chunked transfer
+//! coding, or else end with a clean close of the connection (over TLS, a
+//! `close_notify`), so a dropped connection never passes for a complete
+//! response. Bodies over [`MAX_BODY`] are refused.
+
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+/// Largest response body accepted, enough for the backup of a very large
+/// task database
+pub const MAX_BODY: usize = 1 << 30;
+
+/// Largest status line, header block or chunk header accepted
+const MAX_HEAD: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// `Err` unless the status is 2xx, naming `what` was attempted
+    pub fn success(self, what: &str) -> Result<Self, String> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(format!(
+                "{what} failed: server answered {} {}",
+                self.status,
+                String::from_utf8_lossy(&self.body).trim()
+            ))
+        }
+    }
+}
+
+/// An `http://` or `https://` URL split into its parts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Url {
+    /// Whether the URL is `https://`
+    pub tls: bool,
+    /// `host` or `host:port`, as sent in the `Host` header
+    pub authority: String,
+    /// Path and query, starting with `/`
+    pub path: String,
+}
+
+impl Url {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (tls, rest) = match (url.strip_prefix("https://"), url.strip_prefix("http://")) {
+            (Some(rest), _) => (true, rest),
+            (None, Some(rest)) => (false, rest),
+            (None, None) => return Err(format!("URL must start with http:// or https://: {url}")),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if authority.is_empty() {
+            return Err(format!("URL has no host: {url}"));
+        }
+        Ok(Self {
+            tls,
+            authority: authority.to_string(),
+            path: path.to_string(),
+        })
+    }
+
+    /// The host without the port or IPv6 brackets
+    fn host(&self) -> &str {
+        let host = match self.authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => host,
+            _ => &self.authority,
+        };
+        host.trim_start_matches('[').trim_end_matches(']')
+    }
+
+    fn address(&self) -> String {
+        if self.authority.ends_with(']') || !self.authority.contains(':') {
+            format!("{}:{}", self.authority, if self.tls { 443 } else { 80 })
+        } else {
+            self.authority.clone()
+        }
+    }
+}
+
+/// Send one request and read the whole response, giving up on a server
+/// that is silent for `timeout`
+pub fn request(
+    method: &str,
+    url: &Url,
+    headers: &[(&str, String)],
+    body: &[u8],
+    timeout: Duration,
+) -> Result<Response, String> {
+    let network = |e: std::io::Error| format!("{}: {e}", url.authority);
+    let socket = url
+        .address()
+        .to_socket_addrs()
+        .map_err(network)?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", url.authority))?;
+    let stream = TcpStream::connect_timeout(&socket, timeout).map_err(network)?;
+    stream.set_read_timeout(Some(timeout)).map_err(network)?;
+    stream.set_write_timeout(Some(timeout)).map_err(network)?;
+
+    let mut head = format!(
+        "{method} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        url.path,
+        url.authority,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    let response = if url.tls {
+        let name = ServerName::try_from(url.host().to_string())
+            .map_err(|e| format!("{}: {e}", url.authority))?;
+        let connection = ClientConnection::new(tls_config()?, name)
+            .map_err(|e| format!("{}: {e}", url.authority))?;
+        exchange(StreamOwned::new(connection, stream), method, &head, body)
+    } else {
+        exchange(stream, method, &head, body)
+    };
+    response.map_err(network)
+}
+
+fn exchange<S: Read + Write>(
+    stream: S,
+    method: &str,
+    head: &str,
+    body: &[u8],
+) -> io::Result<Response> {
+    let mut stream = BufReader::new(stream);
+    stream.get_mut().write_all(head.as_bytes())?;
+    stream.get_mut().write_all(body)?;
+    stream.get_mut().flush()?;
+    read_response(&mut stream, method)
+}
+
+fn read_response<R: BufRead>(reader: &mut R, method: &str) -> io::Result<Response> {
+    let mut budget = MAX_HEAD;
+    let status_line = read_line(reader, &mut budget)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| malformed("no status code"))?;
+
+    let mut length: Option<usize> = None;
+    let mut chunked = false;
+    loop {
+        let line = read_line(reader, &mut budget)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed("header line without a colon"))?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            if !value.eq_ignore_ascii_case("chunked") {
+                return Err(malformed("unsupported transfer coding"));
+            }
+            chunked = true;
+        } else if name.eq_ignore_ascii_case("content-length") {
+            let n = value
+                .parse()
+                .map_err(|_| malformed("invalid Content-Length"))?;
+            if length.is_some_and(|previous| previous != n) {
+                return Err(malformed("conflicting Content-Length headers"));
+            }
+            length = Some(n);
+        }
+    }
+
+    let body = if method == "HEAD" || matches!(status, 100..=199 | 204 | 304) {
+        Vec::new()
+    } else if chunked {
+        read_chunked(reader)?
+    } else if let Some(length) = length {
+        if length > MAX_BODY {
+            return Err(too_large());
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        body
+    } else {
+        // Delimited by the close; read errors, including a TLS connection
+        // closed without close_notify, mean the body may be cut short
+        let mut body = Vec::new();
+        reader.take(MAX_BODY as u64 + 1).read_to_end(&mut body)?;
+        if body.len() > MAX_BODY {
+            return Err(too_large());
+        }
+        body
+    };
+    Ok(Response { status, body })
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut budget = MAX_HEAD;
+        let size_line = read_line(reader, &mut budget)?;
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size =
+            usize::from_str_radix(size_hex, 16).map_err(|_| malformed("invalid chunk size"))?;
+        if size == 0 {
+            // Skip any trailer fields
+            while !read_line(reader, &mut budget)?.is_empty() {}
+            return Ok(body);
+        }
+        if size > MAX_BODY - body.len() {
+            return Err(too_large());
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        if !read_line(reader, &mut budget)?.is_empty() {
+            return Err(malformed("chunk longer than its size"));
+        }
+    }
+}
+
+// One CRLF-terminated line, charged against `budget`
+fn read_line<R: BufRead>(reader: &mut R, budget: &mut usize) -> io::Result<String> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(*budget as u64)
+        .read_until(b'\n', &mut line)?;
+    *budget -= read;
+    if !line.ends_with(b"\n") {
+        return Err(if *budget == 0 {
+            malformed("header too long")
+        } else {
+            io::Error::from(ErrorKind::UnexpectedEof)
+        });
+    }
+    line.pop();
+    if line.ends_with(b"\r") {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|_| malformed("header is not UTF-8"))
+}
+
+fn malformed(reason: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("malformed HTTP response: {reason}"),
+    )
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("response body is larger than {MAX_BODY} bytes"),
+    )
+}
+
+fn tls_config() -> Result<Arc<ClientConfig>, String> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("TLS setup failed: {e}"))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Percent-encode `s` for a URL path or query, keeping `/` if `keep_slash`
+#[cfg(any(feature = "backup-webdav", feature = "backup-s3"))]
+pub fn encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::new();
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &[u8]) -> io::Result<Response> {
+        read_response(&mut &raw[..], "GET")
+    }
+
+    #[test]
+    fn test_read_response() {
+        let plain = parse(b"HTTP/1.1 404 Not Found\r\nX: y\r\n\r\nmissing").unwrap();
+        assert_eq!(
+            (plain.status, plain.body.as_slice()),
+            (404, b"missing".as_slice())
+        );
+
+        let sized = parse(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nWikipedia").unwrap();
+        assert_eq!(sized.body, b"Wiki");
+
+        let chunked = parse(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5;x=1\r\npedia\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(chunked.body, b"Wikipedia");
+
+        let empty = parse(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+        assert!(empty.body.is_empty());
+        assert!(read_response(
+            &mut &b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n"[..],
+            "HEAD"
+        )
+        .is_ok());
+        assert!(parse(b"garbage").is_err());
+    }
+
+    #[test]
+    fn test_truncated_or_oversized_bodies_are_refused() {
+        let short = parse(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nWiki").unwrap_err();
+        assert_eq!(short.kind(), ErrorKind::UnexpectedEof);
+        let cut = parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n")
+            .unwrap_err();
+        assert_eq!(cut.kind(), ErrorKind::UnexpectedEof);
+        assert!(
+            parse(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nContent-Length: 5\r\n\r\nWiki")
+                .is_err()
+        );
+
+        let huge = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        );
+        assert_eq!(
+            parse(huge.as_bytes()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        let huge_chunk = format!(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n",
+            MAX_BODY + 1
+        );
+        assert_eq!(
+            parse(huge_chunk.as_bytes()).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        let endless_head = vec![b'x'; MAX_HEAD + 10];
+        assert_eq!(
+            parse(&endless_head).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_url() {
+        let url = Url::parse("http://nas.local:8080/dav/tasks/").unwrap();
+        assert!(!url.tls);
+        assert_eq!(url.authority, "nas.local:8080");
+        assert_eq!(url.path, "/dav/tasks/");
+        assert_eq!(
+            (url.host(), url.address().as_str()),
+            ("nas.local", "nas.local:8080")
+        );
+        let secure = Url::parse("https://s3.eu-west-1.amazonaws.com").unwrap();
+        assert!(secure.tls);
+        assert_eq!(secure.address(), "s3.eu-west-1.amazonaws.com:443");
+        assert_eq!(Url::parse("https://[::1]/").unwrap().address(), "[::1]:443");
+        assert_eq!(Url::parse("https://[::1]:8443/").unwrap().host(), "::1");
+        assert!(Url::parse("ftp://example.com/").is_err());
+    }
+
+    #[cfg(any(feature = "backup-webdav", feature = "backup-s3"))]
+    #[test]
+    fn test_encoding() {
+        assert_eq!(encode("a b/c", true), "a%20b/c");
+        assert_eq!(encode("a b/c", false), "a%20b%2Fc");
+    }
+}
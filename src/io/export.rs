@@ -1,4 +1,6 @@
 use crate::error::TaskError;
+use crate::io::profile::SerializationProfile;
+use crate::io::scrub::Scrubber;
 use crate::task::Task;
 use serde::{Deserialize, Serialize};
 use std::io::Write;
@@ -10,6 +12,9 @@ pub enum ExportFormat {
     Json,
     Csv,
     Taskwarrior,
+    /// GeoJSON `FeatureCollection` of the tasks with a location, as point
+    /// features carrying the task JSON as properties, for map views
+    GeoJson,
 }
 
 /// Export configuration
@@ -21,6 +26,10 @@ pub struct ExportConfig {
     pub include_annotations: bool,
     pub custom_fields: Vec<String>,
     pub filter: Option<String>,
+    /// Anonymize tasks before writing them (see [`Scrubber`])
+    pub scrub: Option<Scrubber>,
+    /// JSON dialect of exported tasks
+    pub profile: SerializationProfile,
 }
 
 impl ExportConfig {
@@ -32,8 +41,23 @@ impl ExportConfig {
             include_annotations: true,
             custom_fields: Vec::new(),
             filter: None,
+            scrub: None,
+            profile: SerializationProfile::internal(),
         }
     }
+
+    /// Export anonymized tasks for bug reports
+    pub fn scrubbed(mut self) -> Self {
+        self.scrub = Some(Scrubber::new());
+        self
+    }
+
+    /// Write JSON in `profile`, e.g. [`SerializationProfile::taskwarrior`]
+    /// for output `task import` accepts
+    pub fn with_profile(mut self, profile: SerializationProfile) -> Self {
+        self.profile = profile;
+        self
+    }
 }
 
 /// Task exporter
@@ -65,6 +89,15 @@ impl TaskExporter {
         writer: &mut W,
         config: &ExportConfig,
     ) -> Result<usize, TaskError> {
+        let scrubbed;
+        let tasks = match &config.scrub {
+            Some(scrubber) => {
+                scrubbed = scrubber.scrub_tasks(tasks);
+                scrubbed.as_slice()
+            }
+            None => tasks,
+        };
+
         // Filter tasks based on config (and optional filter expression)
         let filtered_tasks: Vec<_> = tasks
             .iter()
@@ -73,12 +106,17 @@ impl TaskExporter {
 
         match config.format {
             ExportFormat::Json => {
-                // If tags/annotations should be excluded, convert tasks to JSON values and strip keys
-                if !config.include_tags || !config.include_annotations {
+                let stripped = !config.include_tags || !config.include_annotations;
+                // If tags/annotations should be excluded or another dialect
+                // is wanted, convert tasks to JSON values and rewrite them
+                if stripped || !config.profile.is_internal() {
                     let mut values: Vec<serde_json::Value> = Vec::new();
                     for task in &filtered_tasks {
-                        let mut v = serde_json::to_value(task).map_err(TaskError::Serialization)?;
-                        if let serde_json::Value::Object(ref mut map) = v {
+                        let mut v = config
+                            .profile
+                            .to_value(task)
+                            .map_err(TaskError::Serialization)?;
+                        if let (true, serde_json::Value::Object(map)) = (stripped, &mut v) {
                             if !config.include_tags {
                                 map.remove("tags");
                             }
@@ -108,6 +146,9 @@ impl TaskExporter {
             ExportFormat::Taskwarrior => {
                 self.export_taskwarrior(&filtered_tasks, writer, config)?;
             }
+            ExportFormat::GeoJson => {
+                return self.export_geojson(&filtered_tasks, writer, config);
+            }
         }
 
         Ok(filtered_tasks.len())
@@ -203,6 +244,7 @@ impl TaskExporter {
                         if let Some(uda_val) = task.udas.get(other) {
                             match uda_val {
                                 crate::task::model::UdaValue::String(s) => s.clone(),
+                                crate::task::model::UdaValue::Number(n) => n.to_string(),
                                 _ => String::new(),
                             }
                         } else {
@@ -220,6 +262,47 @@ impl TaskExporter {
         Ok(())
     }
 
+    /// Export located tasks as GeoJSON, returning how many were written
+    fn export_geojson<W: Write>(
+        &self,
+        tasks: &[&Task],
+        writer: &mut W,
+        config: &ExportConfig,
+    ) -> Result<usize, TaskError> {
+        let mut features = Vec::new();
+        for task in tasks {
+            let Some((latitude, longitude)) = task.location() else {
+                continue;
+            };
+            let mut properties = config
+                .profile
+                .to_value(task)
+                .map_err(TaskError::Serialization)?;
+            if let serde_json::Value::Object(map) = &mut properties {
+                if !config.include_tags {
+                    map.remove("tags");
+                }
+                if !config.include_annotations {
+                    map.remove("annotations");
+                }
+            }
+            features.push(serde_json::json!({
+                "type": "Feature",
+                "id": task.id.to_string(),
+                // GeoJSON positions are longitude first
+                "geometry": { "type": "Point", "coordinates": [longitude, latitude] },
+                "properties": properties,
+            }));
+        }
+        let count = features.len();
+        let collection = serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        });
+        serde_json::to_writer_pretty(writer, &collection)?;
+        Ok(count)
+    }
+
     /// Export in Taskwarrior format
     fn export_taskwarrior<W: Write>(
         &self,
@@ -314,4 +397,59 @@ mod tests {
         let output = result.unwrap();
         assert!(!output.is_empty());
     }
+
+    #[test]
+    fn test_scrubbed_export() {
+        let mut task = Task::new("Secret plans".to_string());
+        task.project = Some("Private".to_string());
+
+        let exporter = TaskExporter::new();
+        let config = ExportConfig::new(ExportFormat::Json).scrubbed();
+        let json = exporter.export_tasks_to_string(&[task.clone()], &config).unwrap();
+        assert!(!json.contains("Secret") && !json.contains("Private"));
+        assert!(json.contains(&task.id.to_string()));
+    }
+
+    #[test]
+    fn test_taskwarrior_profile_export() {
+        let mut task = Task::new("Pay rent".to_string());
+        task.entry = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2025, 3, 1, 9, 0, 0).unwrap();
+
+        let exporter = TaskExporter::new();
+        let config = ExportConfig::new(ExportFormat::Json)
+            .with_profile(SerializationProfile::taskwarrior());
+        let json = exporter.export_tasks_to_string(&[task], &config).unwrap();
+        let values: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(values[0]["entry"], "20250301T090000Z");
+        assert!(values[0].get("active").is_none());
+    }
+
+    #[test]
+    fn test_geojson_export() {
+        let mut bakery = Task::new("Buy bread".to_string());
+        bakery.set_location(Some((52.521, 13.413)));
+        let anywhere = Task::new("Call mum".to_string());
+
+        let exporter = TaskExporter::new();
+        let config = ExportConfig::new(ExportFormat::GeoJson);
+        let mut output = Vec::new();
+        let count = exporter
+            .export_tasks(&[bakery.clone(), anywhere], &mut output, &config)
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["type"], "FeatureCollection");
+        let feature = &value["features"][0];
+        assert_eq!(feature["id"], bakery.id.to_string());
+        assert_eq!(feature["geometry"]["coordinates"], serde_json::json!([13.413, 52.521]));
+        assert_eq!(feature["properties"]["description"], "Buy bread");
+
+        let config = ExportConfig {
+            custom_fields: vec!["latitude".to_string()],
+            ..ExportConfig::new(ExportFormat::Csv)
+        };
+        let csv = exporter.export_tasks_to_string(&[bakery], &config).unwrap();
+        assert!(csv.lines().nth(1).unwrap().ends_with(",52.521"));
+    }
 }
@@ -3,7 +3,11 @@
 //! This module provides comprehensive task import functionality supporting
 //! multiple formats including JSON, CSV, and Taskwarrior legacy format.
 
+use crate::config::ParseMode;
 use crate::error::TaskError;
+use crate::io::profile::SerializationProfile;
+use crate::io::taskwarrior2;
+use crate::task::uda::UdaTypes;
 use crate::task::{Priority, Task, TaskStatus};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -23,15 +27,43 @@ pub enum ImportFormat {
     Csv,
     /// Legacy Taskwarrior format
     TaskwarriorLegacy,
+    /// Taskwarrior 2.x `pending.data`/`completed.data` lines
+    Taskwarrior2Data,
+}
+
+/// How to handle an imported task whose UUID already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MergeStrategy {
+    /// Replace the existing task with the imported one
+    Overwrite,
+    /// Keep the existing task and drop the imported one
+    Skip,
+    /// Keep whichever was modified most recently (ties keep the existing task)
+    KeepNewest,
+    /// Merge field by field: scalar fields (including the start time and a
+    /// custom priority code) come from the more recently modified task,
+    /// tags/annotations/dependencies/UDAs are unioned
+    MergeFields,
 }
 
 /// Import configuration
 #[derive(Debug, Clone, PartialEq)]
 pub struct ImportConfig {
     pub format: ImportFormat,
+    /// Merge imported tasks into existing ones field by field
+    /// (used when `merge_strategy` is None)
     pub merge_duplicates: bool,
+    /// Overwrite existing tasks with imported ones
+    /// (used when `merge_strategy` is None and `merge_duplicates` is false)
     pub update_existing: bool,
     pub validate_data: bool,
+    /// Explicit merge strategy; overrides the two flags above
+    pub merge_strategy: Option<MergeStrategy>,
+    /// Lenient (the default) skips records that fail to parse and lists
+    /// them in [`ImportResult::errors`]; strict fails the import instead
+    pub mode: ParseMode,
+    /// JSON dialect of imported tasks
+    pub profile: SerializationProfile,
 }
 
 impl Default for ImportConfig {
@@ -41,10 +73,49 @@ impl Default for ImportConfig {
             merge_duplicates: false,
             update_existing: false,
             validate_data: true,
+            merge_strategy: None,
+            mode: ParseMode::Lenient,
+            profile: SerializationProfile::internal(),
         }
     }
 }
 
+impl ImportConfig {
+    /// Strategy applied to UUIDs that already exist
+    pub fn effective_strategy(&self) -> MergeStrategy {
+        match self.merge_strategy {
+            Some(strategy) => strategy,
+            None if self.merge_duplicates => MergeStrategy::MergeFields,
+            None if self.update_existing => MergeStrategy::Overwrite,
+            None => MergeStrategy::Skip,
+        }
+    }
+}
+
+/// Outcome for one imported task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MergeAction {
+    /// New UUID; the task was added
+    Created,
+    /// The existing task was replaced
+    Overwritten,
+    /// The existing task was kept and the imported one dropped
+    Skipped,
+    /// KeepNewest kept the existing task
+    KeptExisting,
+    /// KeepNewest replaced the existing task with the newer import
+    KeptImported,
+    /// Fields were merged; lists the fields that changed
+    Merged { fields: Vec<String> },
+}
+
+/// A merge decision recorded during import
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeDecision {
+    pub id: Uuid,
+    pub action: MergeAction,
+}
+
 /// Import result statistics
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImportResult {
@@ -53,6 +124,98 @@ pub struct ImportResult {
     pub updated_count: usize,
     pub skipped_count: usize,
     pub errors: Vec<String>,
+    /// Merge decision for every imported task, filled by
+    /// [`DefaultTaskImporter::resolve_merges`]
+    #[serde(default)]
+    pub decisions: Vec<MergeDecision>,
+}
+
+/// A top-level task field an import would change, as exported JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    /// Current value; null when unset
+    pub before: serde_json::Value,
+    /// Value after the import; null when removed
+    pub after: serde_json::Value,
+}
+
+/// What importing one record would do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordOutcome {
+    /// New UUID; the task would be added
+    WouldCreate { task: Box<Task> },
+    /// The existing task would change in these fields
+    WouldUpdate {
+        id: Uuid,
+        description: String,
+        changes: Vec<FieldDiff>,
+    },
+    /// Nothing would be written for this record
+    WouldSkip {
+        /// None for records that failed to parse
+        id: Option<Uuid>,
+        reason: String,
+    },
+}
+
+/// Per-record outcomes of an import that was not written, from
+/// [`DefaultTaskImporter::preview`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportPreview {
+    /// Parsed records in input order, then records that failed to parse
+    pub records: Vec<RecordOutcome>,
+}
+
+impl ImportPreview {
+    pub fn creates(&self) -> usize {
+        self.count(|r| matches!(r, RecordOutcome::WouldCreate { .. }))
+    }
+
+    pub fn updates(&self) -> usize {
+        self.count(|r| matches!(r, RecordOutcome::WouldUpdate { .. }))
+    }
+
+    pub fn skips(&self) -> usize {
+        self.count(|r| matches!(r, RecordOutcome::WouldSkip { .. }))
+    }
+
+    fn count(&self, pred: impl Fn(&RecordOutcome) -> bool) -> usize {
+        self.records.iter().filter(|r| pred(r)).count()
+    }
+}
+
+impl std::fmt::Display for ImportPreview {
+    /// One line per record (`+` create, `~` update, `-` skip), with an
+    /// indented `field: before -> after` line per changed field
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for record in &self.records {
+            match record {
+                RecordOutcome::WouldCreate { task } => {
+                    writeln!(f, "+ {} {}", task.id, task.description)?
+                }
+                RecordOutcome::WouldUpdate {
+                    id,
+                    description,
+                    changes,
+                } => {
+                    writeln!(f, "~ {id} {description}")?;
+                    for change in changes {
+                        writeln!(f, "    {}: {} -> {}", change.field, change.before, change.after)?;
+                    }
+                }
+                RecordOutcome::WouldSkip { id: Some(id), reason } => writeln!(f, "- {id} {reason}")?,
+                RecordOutcome::WouldSkip { id: None, reason } => writeln!(f, "- {reason}")?,
+            }
+        }
+        write!(
+            f,
+            "{} to create, {} to update, {} skipped",
+            self.creates(),
+            self.updates(),
+            self.skips()
+        )
+    }
 }
 
 /// Task importer trait
@@ -82,7 +245,7 @@ impl DefaultTaskImporter {
     pub fn import_with_detection<R: Read>(
         &self,
         reader: &mut R,
-        _config: &ImportConfig,
+        config: &ImportConfig,
     ) -> Result<ImportResult, TaskError> {
         let mut content = String::new();
         reader.read_to_string(&mut content)?;
@@ -90,7 +253,7 @@ impl DefaultTaskImporter {
         let format = self.detect_format_from_content(&content)?;
         let config = ImportConfig {
             format,
-            ..Default::default()
+            ..config.clone()
         };
 
         let mut cursor = std::io::Cursor::new(content);
@@ -101,7 +264,9 @@ impl DefaultTaskImporter {
     pub fn detect_format_from_content(&self, content: &str) -> Result<ImportFormat, TaskError> {
         let trimmed = content.trim();
 
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+        if is_taskwarrior2_line(trimmed.lines().next().unwrap_or("")) {
+            Ok(ImportFormat::Taskwarrior2Data)
+        } else if trimmed.starts_with('[') && trimmed.ends_with(']') {
             Ok(ImportFormat::Json)
         } else if content.contains(',')
             && content
@@ -143,6 +308,7 @@ impl DefaultTaskImporter {
                 updated_count: 0,
                 skipped_count: 0,
                 errors: Vec::new(),
+                decisions: Vec::new(),
             });
         }
 
@@ -169,6 +335,7 @@ impl DefaultTaskImporter {
             skipped_count: skipped,
             tasks,
             errors,
+            decisions: Vec::new(),
         })
     }
 
@@ -176,9 +343,19 @@ impl DefaultTaskImporter {
     pub fn import_json<R: Read>(
         &self,
         reader: &mut R,
-        _config: &ImportConfig,
+        config: &ImportConfig,
     ) -> Result<ImportResult, TaskError> {
-        let tasks: Vec<Task> = serde_json::from_reader(reader).map_err(TaskError::Serialization)?;
+        let tasks: Vec<Task> = if config.profile.is_internal() {
+            serde_json::from_reader(reader).map_err(TaskError::Serialization)?
+        } else {
+            let values: Vec<serde_json::Value> =
+                serde_json::from_reader(reader).map_err(TaskError::Serialization)?;
+            values
+                .into_iter()
+                .map(|value| config.profile.from_value(value))
+                .collect::<Result<_, _>>()
+                .map_err(TaskError::Serialization)?
+        };
 
         Ok(ImportResult {
             imported_count: tasks.len(),
@@ -186,6 +363,7 @@ impl DefaultTaskImporter {
             skipped_count: 0,
             tasks,
             errors: Vec::new(),
+            decisions: Vec::new(),
         })
     }
 
@@ -224,11 +402,119 @@ impl DefaultTaskImporter {
             skipped_count: skipped,
             tasks,
             errors,
+            decisions: Vec::new(),
         };
 
         Ok(result)
     }
 
+    /// Import Taskwarrior 2.x data file lines; UDAs are decoded by
+    /// guessing their type
+    pub fn import_taskwarrior2_data<R: Read>(
+        &self,
+        reader: &mut R,
+        _config: &ImportConfig,
+    ) -> Result<ImportResult, TaskError> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        Ok(taskwarrior2::parse_data_file(&content, &UdaTypes::new()))
+    }
+
+    /// Reconcile parsed tasks with `existing` ones (and with duplicates
+    /// inside the import itself) using `strategy`.
+    ///
+    /// Afterwards `result.tasks` holds exactly the tasks that need saving,
+    /// the counts reflect created/updated/skipped tasks, and
+    /// `result.decisions` records what happened to every imported task.
+    pub fn resolve_merges(&self, result: &mut ImportResult, existing: &[Task], strategy: MergeStrategy) {
+        let mut known: HashMap<Uuid, Task> = existing.iter().map(|t| (t.id, t.clone())).collect();
+        let mut to_save: Vec<Task> = Vec::new();
+        let mut created = 0;
+        let mut updated = 0;
+        let mut skipped = 0;
+
+        for imported in std::mem::take(&mut result.tasks) {
+            let id = imported.id;
+            let (merged, action) = match known.get(&id) {
+                None => (Some(imported), MergeAction::Created),
+                Some(current) => merge_task(current, imported, strategy),
+            };
+
+            match &action {
+                MergeAction::Created => created += 1,
+                MergeAction::Skipped | MergeAction::KeptExisting => skipped += 1,
+                MergeAction::Merged { fields } if fields.is_empty() => skipped += 1,
+                _ => updated += 1,
+            }
+
+            if let Some(task) = merged {
+                known.insert(id, task.clone());
+                match to_save.iter_mut().find(|t| t.id == id) {
+                    Some(slot) => *slot = task,
+                    None => to_save.push(task),
+                }
+            }
+            result.decisions.push(MergeDecision { id, action });
+        }
+
+        result.tasks = to_save;
+        result.imported_count = created;
+        result.updated_count = updated;
+        result.skipped_count += skipped;
+    }
+
+    /// What [`resolve_merges`](Self::resolve_merges) followed by saving
+    /// would do to `existing`, record by record, without changing anything.
+    /// Records that failed to parse are listed as skipped with their error.
+    pub fn preview(&self, parsed: &ImportResult, existing: &[Task], strategy: MergeStrategy) -> ImportPreview {
+        let mut known: HashMap<Uuid, Task> = existing.iter().map(|t| (t.id, t.clone())).collect();
+        let mut records = Vec::new();
+
+        for imported in &parsed.tasks {
+            let id = imported.id;
+            let Some(current) = known.get(&id) else {
+                known.insert(id, imported.clone());
+                records.push(RecordOutcome::WouldCreate {
+                    task: Box::new(imported.clone()),
+                });
+                continue;
+            };
+            let (merged, action) = merge_task(current, imported.clone(), strategy);
+            let Some(merged) = merged else {
+                let reason = match action {
+                    MergeAction::KeptExisting => "existing task was modified more recently",
+                    MergeAction::Merged { .. } => "nothing to merge",
+                    _ => "UUID already exists",
+                };
+                records.push(RecordOutcome::WouldSkip {
+                    id: Some(id),
+                    reason: reason.to_string(),
+                });
+                continue;
+            };
+            let changes = field_diff(current, &merged);
+            records.push(if changes.is_empty() {
+                RecordOutcome::WouldSkip {
+                    id: Some(id),
+                    reason: "identical to the existing task".to_string(),
+                }
+            } else {
+                RecordOutcome::WouldUpdate {
+                    id,
+                    description: current.description.clone(),
+                    changes,
+                }
+            });
+            known.insert(id, merged);
+        }
+
+        records.extend(parsed.errors.iter().map(|error| RecordOutcome::WouldSkip {
+            id: None,
+            reason: error.clone(),
+        }));
+        ImportPreview { records }
+    }
+
     /// Parse a single CSV line
     fn parse_csv_line(
         line: &str,
@@ -256,10 +542,8 @@ impl DefaultTaskImporter {
             let field_name = header;
 
             match *field_name {
-                "id" => {
-                    if !value.is_empty() {
-                        task.id = Uuid::parse_str(value).unwrap_or_else(|_| Uuid::new_v4());
-                    }
+                "id" if !value.is_empty() => {
+                    task.id = Uuid::parse_str(value).unwrap_or_else(|_| Uuid::new_v4());
                 }
                 "description" => task.description = value.to_string(),
                 "status" => {
@@ -270,31 +554,23 @@ impl DefaultTaskImporter {
                         _ => TaskStatus::Pending,
                     };
                 }
-                "project" => {
-                    if !value.is_empty() {
-                        task.project = Some(value.to_string());
-                    }
+                "project" if !value.is_empty() => {
+                    task.project = Some(value.to_string());
                 }
-                "priority" => {
-                    if !value.is_empty() {
-                        task.priority = match *value {
-                            "high" | "High" | "H" => Some(Priority::High),
-                            "medium" | "Medium" | "M" => Some(Priority::Medium),
-                            "low" | "Low" | "L" => Some(Priority::Low),
-                            _ => None,
-                        };
-                    }
+                "priority" if !value.is_empty() => {
+                    task.priority = match *value {
+                        "high" | "High" | "H" => Some(Priority::High),
+                        "medium" | "Medium" | "M" => Some(Priority::Medium),
+                        "low" | "Low" | "L" => Some(Priority::Low),
+                        _ => None,
+                    };
                 }
-                "tags" => {
-                    if !value.is_empty() {
-                        task.tags = value.split(',').map(|t| t.trim().to_string()).collect();
-                    }
+                "tags" if !value.is_empty() => {
+                    task.tags = value.split(',').map(|t| t.trim().to_string()).collect();
                 }
-                "due" => {
-                    if !value.is_empty() {
-                        if let Ok(due) = DateTime::parse_from_rfc3339(value) {
-                            task.due = Some(due.with_timezone(&Utc));
-                        }
+                "due" if !value.is_empty() => {
+                    if let Ok(due) = DateTime::parse_from_rfc3339(value) {
+                        task.due = Some(due.with_timezone(&Utc));
                     }
                 }
                 _ => {} // Ignore unknown fields
@@ -368,11 +644,18 @@ impl TaskImporter for DefaultTaskImporter {
         reader: &mut R,
         config: &ImportConfig,
     ) -> Result<ImportResult, TaskError> {
-        match config.format {
+        let result = match config.format {
             ImportFormat::Auto => self.import_with_detection(reader, config),
             ImportFormat::Json => self.import_json(reader, config),
             ImportFormat::Csv => self.import_csv(reader, config),
             ImportFormat::TaskwarriorLegacy => self.import_taskwarrior_legacy(reader, config),
+            ImportFormat::Taskwarrior2Data => self.import_taskwarrior2_data(reader, config),
+        }?;
+        match result.errors.first() {
+            Some(error) if config.mode == ParseMode::Strict => Err(TaskError::InvalidData {
+                message: error.clone(),
+            }),
+            _ => Ok(result),
         }
     }
 
@@ -382,10 +665,162 @@ impl TaskImporter for DefaultTaskImporter {
             ImportFormat::Json,
             ImportFormat::Csv,
             ImportFormat::TaskwarriorLegacy,
+            ImportFormat::Taskwarrior2Data,
         ]
     }
 }
 
+// `[key:"value" ...]`, as opposed to a JSON array
+fn is_taskwarrior2_line(line: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(key, value)| {
+            !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+                && value.starts_with('"')
+        })
+}
+
+/// Merge `imported` into `existing` with `strategy`. Returns the task to
+/// save (None when the existing task is kept untouched) and the decision.
+pub fn merge_task(existing: &Task, imported: Task, strategy: MergeStrategy) -> (Option<Task>, MergeAction) {
+    let last_change = |t: &Task| t.modified.unwrap_or(t.entry);
+
+    match strategy {
+        MergeStrategy::Overwrite => (Some(imported), MergeAction::Overwritten),
+        MergeStrategy::Skip => (None, MergeAction::Skipped),
+        MergeStrategy::KeepNewest => {
+            if last_change(&imported) > last_change(existing) {
+                (Some(imported), MergeAction::KeptImported)
+            } else {
+                (None, MergeAction::KeptExisting)
+            }
+        }
+        MergeStrategy::MergeFields => {
+            let imported_newer = last_change(&imported) > last_change(existing);
+            let mut merged = existing.clone();
+            let mut fields = Vec::new();
+
+            macro_rules! scalar {
+                ($field:ident) => {
+                    if imported.$field.is_some()
+                        && merged.$field != imported.$field
+                        && (imported_newer || merged.$field.is_none())
+                    {
+                        merged.$field = imported.$field.clone();
+                        fields.push(stringify!($field).to_string());
+                    }
+                };
+            }
+            scalar!(project);
+            scalar!(due);
+            scalar!(scheduled);
+            scalar!(wait);
+            scalar!(end);
+            scalar!(recur);
+            scalar!(owner);
+            // Built-in and custom codes alike; custom ones live in the
+            // `priority` UDA, which the UDA union below leaves alone
+            let priority = imported.priority_code();
+            if priority.is_some()
+                && merged.priority_code() != priority
+                && (imported_newer || merged.priority_code().is_none())
+            {
+                merged.set_priority_code(priority);
+                fields.push("priority".to_string());
+            }
+            if imported_newer {
+                if merged.description != imported.description {
+                    merged.description = imported.description.clone();
+                    fields.push("description".to_string());
+                }
+                if merged.status != imported.status {
+                    merged.status = imported.status;
+                    fields.push("status".to_string());
+                }
+                // Started or stopped since, so taken even when cleared
+                if merged.start != imported.start || merged.active != imported.active {
+                    merged.start = imported.start;
+                    merged.active = imported.active;
+                    fields.push("start".to_string());
+                }
+            }
+
+            let tag_count = merged.tags.len();
+            merged.tags.extend(imported.tags.iter().cloned());
+            if merged.tags.len() != tag_count {
+                fields.push("tags".to_string());
+            }
+
+            let dep_count = merged.depends.len();
+            merged.depends.extend(imported.depends.iter().copied());
+            if merged.depends.len() != dep_count {
+                fields.push("depends".to_string());
+            }
+
+            let mut annotations_changed = false;
+            for ann in &imported.annotations {
+                if !merged
+                    .annotations
+                    .iter()
+                    .any(|a| a.entry == ann.entry && a.description == ann.description)
+                {
+                    merged.annotations.push(ann.clone());
+                    annotations_changed = true;
+                }
+            }
+            if annotations_changed {
+                merged.annotations.sort_by_key(|a| a.entry);
+                fields.push("annotations".to_string());
+            }
+
+            let mut udas_changed = false;
+            for (key, value) in imported.udas.iter().filter(|(key, _)| *key != "priority") {
+                let replace = match merged.udas.get(key) {
+                    None => true,
+                    Some(current) => imported_newer && current != value,
+                };
+                if replace {
+                    merged.udas.insert(key.clone(), value.clone());
+                    udas_changed = true;
+                }
+            }
+            if udas_changed {
+                fields.push("udas".to_string());
+            }
+
+            if fields.is_empty() {
+                (None, MergeAction::Merged { fields })
+            } else {
+                merged.modified = Some(last_change(existing).max(last_change(&imported)));
+                (Some(merged), MergeAction::Merged { fields })
+            }
+        }
+    }
+}
+
+// Exported fields that differ, by name; display-only fields are left out
+fn field_diff(before: &Task, after: &Task) -> Vec<FieldDiff> {
+    let as_object = |task: &Task| match serde_json::to_value(task) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (old, new) = (as_object(before), as_object(after));
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .filter(|field| !["id", "urgency"].contains(&field.as_str()))
+        .filter(|field| old.get(*field) != new.get(*field))
+        .map(|field| FieldDiff {
+            field: field.clone(),
+            before: old.get(field).cloned().unwrap_or_default(),
+            after: new.get(field).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
 /// Helper function to import tasks from file
 pub fn import_tasks_from_file(
     file_path: &std::path::Path,
@@ -415,6 +850,20 @@ mod tests {
     use super::*;
     use std::io::Cursor;
 
+    #[test]
+    fn test_strict_mode_rejects_bad_records() {
+        let csv_data = "description,status\nGood,pending\n,pending";
+        let lenient = import_tasks_from_string(csv_data, None).unwrap();
+        assert_eq!(lenient.imported_count, 1);
+        assert_eq!(lenient.errors.len(), 1);
+
+        let strict = ImportConfig {
+            mode: ParseMode::Strict,
+            ..Default::default()
+        };
+        assert!(import_tasks_from_string(csv_data, Some(strict)).is_err());
+    }
+
     #[test]
     fn test_import_csv() {
         let csv_data = "id,description,status\n1,Test task,pending\n";
@@ -440,8 +889,8 @@ mod tests {
         let config = ImportConfig::default();
         let result = importer.import_json(&mut cursor, &config);
 
-        if result.is_err() {
-            eprintln!("Import error: {:?}", result.as_ref().unwrap_err());
+        if let Err(e) = &result {
+            eprintln!("Import error: {e:?}");
         }
         assert!(result.is_ok());
         let import_result = result.unwrap();
@@ -472,5 +921,220 @@ mod tests {
             importer.detect_format(&mut tw_cursor).unwrap(),
             ImportFormat::TaskwarriorLegacy
         );
+
+        let tw2_data = "[description:\"Test\" status:\"pending\" uuid:\"8ef0fb62-c4b6-4bdb-b0e5-0c0a1bc0e0a1\"]";
+        let mut tw2_cursor = Cursor::new(tw2_data);
+        assert_eq!(
+            importer.detect_format(&mut tw2_cursor).unwrap(),
+            ImportFormat::Taskwarrior2Data
+        );
+        let result = import_tasks_from_string(tw2_data, None).unwrap();
+        assert_eq!(result.tasks[0].description, "Test");
+    }
+
+    fn dated(description: &str, id: Uuid, minutes_ago: i64) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.id = id;
+        task.modified = Some(Utc::now() - chrono::Duration::minutes(minutes_ago));
+        task
+    }
+
+    fn parsed(tasks: Vec<Task>) -> ImportResult {
+        ImportResult {
+            imported_count: tasks.len(),
+            tasks,
+            updated_count: 0,
+            skipped_count: 0,
+            errors: Vec::new(),
+            decisions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_effective_strategy() {
+        assert_eq!(ImportConfig::default().effective_strategy(), MergeStrategy::Skip);
+        let config = ImportConfig {
+            update_existing: true,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_strategy(), MergeStrategy::Overwrite);
+        let config = ImportConfig {
+            merge_duplicates: true,
+            update_existing: true,
+            ..Default::default()
+        };
+        assert_eq!(config.effective_strategy(), MergeStrategy::MergeFields);
+        let config = ImportConfig {
+            merge_strategy: Some(MergeStrategy::KeepNewest),
+            ..config
+        };
+        assert_eq!(config.effective_strategy(), MergeStrategy::KeepNewest);
+    }
+
+    #[test]
+    fn test_resolve_overwrite_and_skip() {
+        let importer = DefaultTaskImporter::new();
+        let id = Uuid::new_v4();
+        let existing = vec![dated("old", id, 10)];
+        let fresh = Task::new("new task".to_string());
+
+        let mut result = parsed(vec![dated("imported", id, 20), fresh.clone()]);
+        importer.resolve_merges(&mut result, &existing, MergeStrategy::Overwrite);
+        assert_eq!(result.imported_count, 1);
+        assert_eq!(result.updated_count, 1);
+        assert_eq!(result.tasks.len(), 2);
+        assert_eq!(result.decisions[0].action, MergeAction::Overwritten);
+        assert_eq!(result.decisions[1], MergeDecision { id: fresh.id, action: MergeAction::Created });
+
+        let mut result = parsed(vec![dated("imported", id, 20)]);
+        importer.resolve_merges(&mut result, &existing, MergeStrategy::Skip);
+        assert!(result.tasks.is_empty());
+        assert_eq!(result.skipped_count, 1);
+        assert_eq!(result.decisions[0].action, MergeAction::Skipped);
+    }
+
+    #[test]
+    fn test_preview_lists_every_record() {
+        let importer = DefaultTaskImporter::new();
+        let id = Uuid::new_v4();
+        let mut current = dated("Pay rent", id, 10);
+        current.project = Some("Home".to_string());
+        let same = dated("Same", Uuid::new_v4(), 10);
+        let existing = vec![current.clone(), same.clone()];
+
+        let mut changed = current.clone();
+        changed.project = Some("Bills".to_string());
+        changed.tags.insert("rent".to_string());
+        let fresh = Task::new("New".to_string());
+        let mut result = parsed(vec![changed, same.clone(), fresh.clone()]);
+        result.errors.push("Line 5: Missing description".to_string());
+
+        let preview = importer.preview(&result, &existing, MergeStrategy::Overwrite);
+        assert_eq!((preview.creates(), preview.updates(), preview.skips()), (1, 1, 2));
+        let RecordOutcome::WouldUpdate { changes, .. } = &preview.records[0] else {
+            panic!("expected an update, got {:?}", preview.records[0]);
+        };
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["project", "tags"]);
+        assert_eq!(changes[0].before, "Home");
+        assert_eq!(changes[0].after, "Bills");
+        assert_eq!(
+            preview.records[1],
+            RecordOutcome::WouldSkip {
+                id: Some(same.id),
+                reason: "identical to the existing task".to_string()
+            }
+        );
+        assert!(matches!(&preview.records[2], RecordOutcome::WouldCreate { task } if task.id == fresh.id));
+
+        let text = preview.to_string();
+        assert!(text.contains("    project: \"Home\" -> \"Bills\"\n"));
+        assert!(text.ends_with("- Line 5: Missing description\n1 to create, 1 to update, 2 skipped"));
+
+        let skipped = importer.preview(&result, &existing, MergeStrategy::Skip);
+        assert_eq!((skipped.creates(), skipped.updates(), skipped.skips()), (1, 0, 3));
+    }
+
+    #[test]
+    fn test_resolve_keep_newest() {
+        let importer = DefaultTaskImporter::new();
+        let id = Uuid::new_v4();
+        let existing = vec![dated("current", id, 10)];
+
+        let mut result = parsed(vec![dated("stale", id, 20)]);
+        importer.resolve_merges(&mut result, &existing, MergeStrategy::KeepNewest);
+        assert!(result.tasks.is_empty());
+        assert_eq!(result.decisions[0].action, MergeAction::KeptExisting);
+
+        let mut result = parsed(vec![dated("newer", id, 1)]);
+        importer.resolve_merges(&mut result, &existing, MergeStrategy::KeepNewest);
+        assert_eq!(result.tasks[0].description, "newer");
+        assert_eq!(result.decisions[0].action, MergeAction::KeptImported);
+    }
+
+    #[test]
+    fn test_merge_fields_unions_lists() {
+        let id = Uuid::new_v4();
+        let mut existing = dated("current", id, 10);
+        existing.tags.insert("home".to_string());
+        existing.project = Some("House".to_string());
+        existing
+            .annotations
+            .push(crate::task::Annotation::new("called plumber".to_string()));
+
+        let mut imported = dated("renamed", id, 1);
+        imported.tags.insert("urgent".to_string());
+        imported.due = Some(Utc::now());
+        imported.annotations = existing.annotations.clone();
+
+        let (merged, action) = merge_task(&existing, imported, MergeStrategy::MergeFields);
+        let merged = merged.unwrap();
+        assert_eq!(merged.description, "renamed");
+        assert_eq!(merged.project.as_deref(), Some("House"));
+        assert!(merged.due.is_some());
+        assert_eq!(merged.tags.len(), 2);
+        assert_eq!(merged.annotations.len(), 1);
+        assert_eq!(
+            action,
+            MergeAction::Merged {
+                fields: vec!["due".to_string(), "description".to_string(), "tags".to_string()]
+            }
+        );
+
+        let (unchanged, action) = merge_task(&merged, merged.clone(), MergeStrategy::MergeFields);
+        assert!(unchanged.is_none());
+        assert_eq!(action, MergeAction::Merged { fields: Vec::new() });
+    }
+
+    #[test]
+    fn test_merge_fields_takes_start_and_custom_priority() {
+        let id = Uuid::new_v4();
+        let mut existing = dated("Write report", id, 10);
+        existing.priority = Some(crate::task::Priority::Low);
+
+        let mut imported = dated("Write report", id, 1);
+        imported.start();
+        imported.set_priority_code(Some("C"));
+        imported.modified = Some(Utc::now() - chrono::Duration::minutes(1));
+
+        let (merged, action) = merge_task(&existing, imported.clone(), MergeStrategy::MergeFields);
+        let merged = merged.unwrap();
+        assert_eq!(merged.start, imported.start);
+        assert!(merged.active);
+        assert_eq!(merged.priority, None);
+        assert_eq!(merged.priority_code(), Some("C"));
+        assert_eq!(
+            action,
+            MergeAction::Merged {
+                fields: vec!["priority".to_string(), "start".to_string()]
+            }
+        );
+
+        // An older copy neither restarts a stopped task nor overrides the
+        // priority
+        let mut stale = dated("Write report", id, 20);
+        stale.start();
+        stale.set_priority_code(Some("H"));
+        stale.modified = Some(Utc::now() - chrono::Duration::minutes(20));
+        let (merged, _) = merge_task(&existing, stale, MergeStrategy::MergeFields);
+        assert!(merged.is_none());
+    }
+
+    #[test]
+    fn test_import_taskwarrior_profile() {
+        let json = r#"[{"uuid":"6c3d5e1e-3c7a-4e0e-9d0a-1a5c2b7d9e01","description":"Pay rent",
+            "status":"pending","entry":"20250301T090000Z","due":"20250305T000000Z",
+            "recur":"monthly","urgency":4.2}]"#;
+        // The internal dialect does not read CLI dates
+        assert!(import_tasks_from_string(json, None).is_err());
+
+        let config = ImportConfig {
+            profile: SerializationProfile::taskwarrior(),
+            ..Default::default()
+        };
+        let result = import_tasks_from_string(json, Some(config)).unwrap();
+        let task = &result.tasks[0];
+        assert_eq!(task.entry.to_rfc3339(), "2025-03-01T09:00:00+00:00");
+        assert_eq!(task.recur.as_ref().unwrap().pattern, "monthly");
     }
 }
@@ -5,10 +5,15 @@
 pub mod export;
 pub mod import;
 pub mod process_runner;
+pub mod profile;
+pub mod scrub;
+pub mod taskwarrior2;
 
 // Re-export main functionality
 pub use export::TaskExporter;
 pub use import::TaskImporter;
+pub use profile::SerializationProfile;
+pub use scrub::Scrubber;
 pub use process_runner::{ProcessResult, ProcessRunner, SystemProcessRunner, default_runner};
 
 #[cfg(any(test, feature = "taskchampion"))]
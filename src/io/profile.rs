@@ -0,0 +1,301 @@
+//! Serialization profiles
+//!
+//! A task has two JSON dialects. This library's own serde output uses RFC
+//! 3339 dates, `recur` as an object and an `active` flag. The `task` CLI's
+//! `task export`/`task import` uses `20250601T120000Z` dates and `recur` as
+//! its bare pattern. Taskwarrior 2.5 also wrote `depends` as one
+//! comma-separated string.
+//!
+//! A [`SerializationProfile`] picks the dialect attribute by attribute. Set
+//! it on [`ExportConfig`](crate::io::export::ExportConfig),
+//! [`ImportConfig`](crate::io::import::ImportConfig) or the RPC server so
+//! one program can exchange tasks with both.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::task::model::UdaValue;
+use crate::task::Task;
+
+/// Date format of the `task` CLI's JSON
+pub const COMPACT_DATE: &str = "%Y%m%dT%H%M%SZ";
+
+/// Task attributes holding dates
+const DATE_FIELDS: [&str; 7] = ["entry", "modified", "due", "scheduled", "wait", "end", "start"];
+
+/// How dates are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// `2025-06-01T12:00:00+00:00`
+    #[default]
+    Rfc3339,
+    /// `20250601T120000Z`, as the `task` CLI writes them
+    Compact,
+}
+
+/// How `depends` is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependsStyle {
+    /// A JSON array of UUIDs
+    #[default]
+    Array,
+    /// One comma-separated string, as Taskwarrior 2.5 and older wrote it
+    Joined,
+}
+
+/// How `status` is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusCase {
+    /// `pending`
+    #[default]
+    Lower,
+    /// `Pending`
+    Capitalized,
+}
+
+/// Choice of JSON dialect for tasks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerializationProfile {
+    pub dates: DateStyle,
+    pub depends: DependsStyle,
+    pub status: StatusCase,
+    /// Write the computed `urgency`
+    pub include_urgency: bool,
+    /// Write attributes only this library understands: `active`, and
+    /// `recur` as an object instead of its pattern
+    pub library_fields: bool,
+}
+
+impl Default for SerializationProfile {
+    fn default() -> Self {
+        Self::internal()
+    }
+}
+
+impl SerializationProfile {
+    /// This library's serde format, as `Task` serializes itself
+    pub fn internal() -> Self {
+        Self {
+            dates: DateStyle::Rfc3339,
+            depends: DependsStyle::Array,
+            status: StatusCase::Lower,
+            include_urgency: true,
+            library_fields: true,
+        }
+    }
+
+    /// The format of `task export` and `task import`
+    pub fn taskwarrior() -> Self {
+        Self {
+            dates: DateStyle::Compact,
+            library_fields: false,
+            ..Self::internal()
+        }
+    }
+
+    /// Whether this is the internal format, which `Task`'s serde
+    /// implementation reads and writes without conversion
+    pub fn is_internal(&self) -> bool {
+        *self == Self::internal()
+    }
+
+    /// `task` as JSON in this profile
+    pub fn to_value(&self, task: &Task) -> Result<Value, serde_json::Error> {
+        let mut value = serde_json::to_value(task)?;
+        let Value::Object(map) = &mut value else {
+            return Ok(value);
+        };
+
+        if !self.include_urgency {
+            map.remove("urgency");
+        }
+        if !self.library_fields {
+            map.remove("active");
+            if let Some(recur) = &task.recur {
+                map.insert("recur".to_string(), Value::String(recur.pattern.clone()));
+            }
+        }
+        if self.status == StatusCase::Capitalized {
+            if let Some(Value::String(status)) = map.get_mut("status") {
+                *status = capitalize(status);
+            }
+        }
+        if self.depends == DependsStyle::Joined && !task.depends.is_empty() {
+            let mut ids: Vec<String> = task.depends.iter().map(ToString::to_string).collect();
+            ids.sort();
+            map.insert("depends".to_string(), Value::String(ids.join(",")));
+        }
+        if self.dates == DateStyle::Compact {
+            let uda_dates = task
+                .udas
+                .iter()
+                .filter(|(_, value)| matches!(value, UdaValue::Date(_)))
+                .map(|(key, _)| key.as_str());
+            let fields: Vec<&str> = DATE_FIELDS.into_iter().chain(uda_dates).collect();
+            convert_dates(map, &fields, |d| d.format(COMPACT_DATE).to_string());
+        }
+        Ok(value)
+    }
+
+    /// Parse a task written in this profile. Dates are accepted in either
+    /// style once the profile is not RFC 3339 only.
+    pub fn from_value(&self, mut value: Value) -> Result<Task, serde_json::Error> {
+        if let Value::Object(map) = &mut value {
+            if self.dates == DateStyle::Compact {
+                // UDAs carry no type here; any value that is a compact date
+                // is taken as one
+                let udas: Vec<String> = map
+                    .iter()
+                    .filter(|(key, value)| {
+                        !DATE_FIELDS.contains(&key.as_str())
+                            && value.as_str().is_some_and(|s| parse_compact(s).is_some())
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                let fields: Vec<&str> =
+                    DATE_FIELDS.into_iter().chain(udas.iter().map(String::as_str)).collect();
+                convert_dates(map, &fields, |d| d.to_rfc3339());
+            }
+            if self.status == StatusCase::Capitalized {
+                if let Some(Value::String(status)) = map.get_mut("status") {
+                    *status = status.to_lowercase();
+                }
+            }
+            if !self.library_fields {
+                if let Some(Value::String(pattern)) = map.get("recur") {
+                    let recur = serde_json::json!({ "pattern": pattern, "periodic": false });
+                    map.insert("recur".to_string(), recur);
+                }
+            }
+        }
+        serde_json::from_value(value)
+    }
+
+    /// Rewrite compact dates among `fields` of a JSON object as RFC 3339,
+    /// e.g. for request parameters written in this profile
+    pub fn normalize_dates(&self, value: &mut Value, fields: &[&str]) {
+        if let (DateStyle::Compact, Value::Object(map)) = (self.dates, value) {
+            convert_dates(map, fields, |d| d.to_rfc3339());
+        }
+    }
+}
+
+/// Rewrite the dates of `fields`, and of annotation entries, with `format`.
+/// Values that are not dates in either style are kept.
+fn convert_dates(
+    map: &mut Map<String, Value>,
+    fields: &[&str],
+    format: impl Fn(DateTime<Utc>) -> String,
+) {
+    let convert = |value: &mut Value| {
+        if let Some(date) = value.as_str().and_then(parse_date) {
+            *value = Value::String(format(date));
+        }
+    };
+    for field in fields {
+        if let Some(value) = map.get_mut(*field) {
+            convert(value);
+        }
+    }
+    if let Some(Value::Array(annotations)) = map.get_mut("annotations") {
+        for annotation in annotations {
+            if let Some(value) = annotation.get_mut("entry") {
+                convert(value);
+            }
+        }
+    }
+}
+
+fn parse_compact(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, COMPACT_DATE)
+        .ok()
+        .map(|d| d.and_utc())
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    parse_compact(s).or_else(|| {
+        DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|d| d.with_timezone(&Utc))
+    })
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::recurrence::RecurrencePattern;
+    use crate::task::TaskStatus;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn sample() -> Task {
+        let mut task = Task::new("Pay rent".to_string());
+        task.entry = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+        task.due = Some(Utc.with_ymd_and_hms(2025, 3, 5, 0, 0, 0).unwrap());
+        task.recur = Some(RecurrencePattern::new("monthly".to_string()));
+        task.udas.insert(
+            "reviewed".to_string(),
+            UdaValue::Date(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap()),
+        );
+        task
+    }
+
+    #[test]
+    fn test_internal_profile_matches_serde() {
+        let task = sample();
+        let profile = SerializationProfile::internal();
+        let value = profile.to_value(&task).unwrap();
+        assert_eq!(value, serde_json::to_value(&task).unwrap());
+        assert_eq!(profile.from_value(value).unwrap(), task);
+    }
+
+    #[test]
+    fn test_taskwarrior_profile_round_trip() {
+        let task = sample();
+        let profile = SerializationProfile::taskwarrior();
+        let value = profile.to_value(&task).unwrap();
+        assert_eq!(value["entry"], "20250301T090000Z");
+        assert_eq!(value["due"], "20250305T000000Z");
+        assert_eq!(value["reviewed"], "20250201T000000Z");
+        assert_eq!(value["recur"], "monthly");
+        assert_eq!(value["status"], "pending");
+        assert!(value.get("active").is_none());
+        assert!(value.get("urgency").is_some());
+        assert_eq!(profile.from_value(value).unwrap(), task);
+    }
+
+    #[test]
+    fn test_custom_profile_knobs() {
+        let mut task = sample();
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        task.depends = [a, b].into_iter().collect();
+        task.status = TaskStatus::Completed;
+        let profile = SerializationProfile {
+            depends: DependsStyle::Joined,
+            status: StatusCase::Capitalized,
+            include_urgency: false,
+            ..SerializationProfile::taskwarrior()
+        };
+
+        let value = profile.to_value(&task).unwrap();
+        let mut ids = [a.to_string(), b.to_string()];
+        ids.sort();
+        assert_eq!(value["depends"], ids.join(","));
+        assert_eq!(value["status"], "Completed");
+        assert!(value.get("urgency").is_none());
+        assert_eq!(profile.from_value(value).unwrap(), task);
+    }
+}
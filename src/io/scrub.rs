@@ -0,0 +1,171 @@
+//! Anonymized copies of task data
+//!
+//! A [`Scrubber`] replaces everything a person wrote (descriptions,
+//! annotations, projects, tags, owners and text UDAs) with placeholders
+//! derived from a keyed hash, and keeps everything else: UUIDs, dates,
+//! statuses, priorities, dependencies, recurrence and numeric values. The
+//! result behaves like the original dataset in filters, reports and
+//! urgency, so it can be attached to a bug report instead.
+//!
+//! Equal inputs map to equal placeholders within one scrubber, so a
+//! project shared by ten tasks is still shared afterwards, and project
+//! hierarchies keep their shape (`Home.Garden` becomes
+//! `project-1a2b3c4d.project-5e6f7a8b`). The key is random unless given,
+//! which keeps short values from being recovered by hashing guesses.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::date::relative::parse_iso_duration;
+use crate::task::annotation::Annotation;
+use crate::task::model::UdaValue;
+use crate::task::Task;
+
+/// Tags Taskwarrior gives a meaning, kept as they are
+pub const SPECIAL_TAGS: [&str; 4] = ["next", "nocal", "nocolor", "nonag"];
+
+/// Hex digits of the hash used in placeholders
+const HASH_LEN: usize = 8;
+
+/// Replaces personal text in tasks with hashed placeholders
+#[derive(Clone)]
+pub struct Scrubber {
+    key: Vec<u8>,
+}
+
+// Keep the key out of logs; it would allow checking guesses
+impl std::fmt::Debug for Scrubber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scrubber").finish_non_exhaustive()
+    }
+}
+
+impl Default for Scrubber {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scrubber {
+    /// Scrubber with a random key
+    pub fn new() -> Self {
+        Self::with_key(uuid::Uuid::new_v4().as_bytes())
+    }
+
+    /// Scrubber with a fixed key, for placeholders that stay the same
+    /// across exports
+    pub fn with_key(key: &[u8]) -> Self {
+        Self { key: key.to_vec() }
+    }
+
+    /// Placeholder for `value`, e.g. `task-1a2b3c4d`
+    pub fn placeholder(&self, kind: &str, value: &str) -> String {
+        let data = format!("{kind}:{value}");
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("any key length");
+        mac.update(data.as_bytes());
+        let hash = hex::encode(mac.finalize().into_bytes());
+        format!("{kind}-{}", &hash[..HASH_LEN])
+    }
+
+    /// Scrubbed copy of `task`
+    pub fn scrub_task(&self, task: &Task) -> Task {
+        let mut scrubbed = task.clone();
+        scrubbed.description = self.placeholder("task", &task.description);
+        scrubbed.project = task.project.as_ref().map(|project| {
+            project
+                .split('.')
+                .map(|part| self.placeholder("project", part))
+                .collect::<Vec<_>>()
+                .join(".")
+        });
+        scrubbed.tags = task
+            .tags
+            .iter()
+            .map(|tag| {
+                if SPECIAL_TAGS.contains(&tag.as_str()) {
+                    tag.clone()
+                } else {
+                    self.placeholder("tag", tag)
+                }
+            })
+            .collect();
+        scrubbed.annotations = task
+            .annotations
+            .iter()
+            .map(|a| Annotation::with_timestamp(self.placeholder("note", &a.description), a.entry))
+            .collect();
+        scrubbed.owner = task
+            .owner
+            .as_ref()
+            .map(|owner| self.placeholder("user", owner));
+        for value in scrubbed.udas.values_mut() {
+            if let UdaValue::String(s) = value {
+                // Durations are data, not text
+                if parse_iso_duration(s).is_err() {
+                    *s = self.placeholder("value", s);
+                }
+            }
+        }
+        scrubbed
+    }
+
+    /// Scrubbed copies of `tasks`
+    pub fn scrub_tasks(&self, tasks: &[Task]) -> Vec<Task> {
+        tasks.iter().map(|task| self.scrub_task(task)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::{Priority, TaskStatus};
+
+    #[test]
+    fn test_scrub_keeps_structure() {
+        let mut task = Task::new("Call the dentist about my tooth".to_string());
+        task.project = Some("Home.Health".to_string());
+        task.tags.insert("phone".to_string());
+        task.tags.insert("next".to_string());
+        task.priority = Some(Priority::High);
+        task.status = TaskStatus::Completed;
+        task.annotations
+            .push(Annotation::new("Dr. Smith, 555-0100".to_string()));
+        task.udas
+            .insert("client".to_string(), UdaValue::String("ACME".to_string()));
+        task.udas
+            .insert("estimate".to_string(), UdaValue::String("PT1H".to_string()));
+        task.udas
+            .insert("points".to_string(), UdaValue::Number(3.0));
+        let mut other = Task::new("Buy seeds".to_string());
+        other.project = Some("Home.Garden".to_string());
+        other.depends.insert(task.id);
+
+        let scrubber = Scrubber::with_key(b"test");
+        let scrubbed = scrubber.scrub_tasks(&[task.clone(), other.clone()]);
+        let (a, b) = (&scrubbed[0], &scrubbed[1]);
+
+        assert_eq!(a.id, task.id);
+        assert_eq!(
+            (a.status, a.priority, a.entry),
+            (task.status, task.priority, task.entry)
+        );
+        assert!(a.description.starts_with("task-"));
+        assert_eq!(a.description.len(), "task-".len() + HASH_LEN);
+        let (a_home, a_health) = a.project.as_deref().unwrap().split_once('.').unwrap();
+        let (b_home, b_garden) = b.project.as_deref().unwrap().split_once('.').unwrap();
+        assert_eq!(a_home, b_home);
+        assert_ne!(a_health, b_garden);
+        assert!(a.tags.contains("next"));
+        assert!(!a.tags.contains("phone"));
+        assert_eq!(a.annotations[0].entry, task.annotations[0].entry);
+        assert!(!a.annotations[0].description.contains("Smith"));
+        assert_ne!(a.udas["client"], task.udas["client"]);
+        assert_eq!(a.udas["estimate"], task.udas["estimate"]);
+        assert_eq!(a.udas["points"], task.udas["points"]);
+        assert_eq!(b.depends, other.depends);
+
+        // The same key gives the same placeholders; another key does not
+        assert_eq!(Scrubber::with_key(b"test").scrub_task(&task), *a);
+        assert_ne!(Scrubber::new().scrub_task(&task).description, a.description);
+    }
+}
@@ -0,0 +1,383 @@
+//! Taskwarrior 2.x data files
+//!
+//! Taskwarrior 2 kept tasks in `pending.data` and `completed.data`, one per
+//! line as `[key:"value" key:"value" ...]`, and its undo log in `undo.data`
+//! as `time`/`old`/`new` records separated by `---`. Values are JSON-escaped
+//! with `[` and `]` written as `&open;` and `&close;` (and `"` as `&dquot;`
+//! before 2.4). Dates are Unix timestamps.
+//!
+//! The readers here parse those files directly, so users migrating from
+//! Taskwarrior 2 can bring their tasks and history over without a working
+//! `task` binary. Lines that fail to parse are reported in
+//! [`ImportResult::errors`] instead of aborting the import.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::error::TaskError;
+use crate::io::import::ImportResult;
+use crate::task::annotation::Annotation;
+use crate::task::model::{parse_depends_list, UdaValue};
+use crate::task::recurrence::RecurrencePattern;
+use crate::task::uda::{decode_uda, UdaTypes};
+use crate::task::{Task, TaskStatus};
+
+/// One change from `undo.data`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoEntry {
+    pub time: DateTime<Utc>,
+    /// The task before the change; None when the change created it
+    pub old: Option<Task>,
+    pub new: Task,
+}
+
+/// Everything read from a Taskwarrior 2 data directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Taskwarrior2Data {
+    /// Tasks from `pending.data` and `completed.data`
+    pub tasks: ImportResult,
+    /// History from `undo.data`, oldest first
+    pub undo: Vec<UndoEntry>,
+}
+
+/// Read `pending.data`, `completed.data` and `undo.data` from the data
+/// directory of `config`. Missing files are treated as empty.
+pub fn import_data_dir(config: &Configuration) -> Result<Taskwarrior2Data, TaskError> {
+    let uda_types = UdaTypes::from_config(config);
+    let mut tasks = empty_result();
+    for path in [config.task_data_file(), config.completed_data_file()] {
+        if !path.exists() {
+            continue;
+        }
+        let file = read_data_file(&path, &uda_types)?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        tasks.imported_count += file.imported_count;
+        tasks.skipped_count += file.skipped_count;
+        tasks.tasks.extend(file.tasks);
+        tasks.errors.extend(file.errors.into_iter().map(|e| format!("{name}: {e}")));
+    }
+
+    let undo_path = config.undo_data_file();
+    let undo = if undo_path.exists() {
+        parse_undo_data(&std::fs::read_to_string(&undo_path)?, &uda_types)?
+    } else {
+        Vec::new()
+    };
+    Ok(Taskwarrior2Data { tasks, undo })
+}
+
+/// Read one data file (`pending.data` or `completed.data`)
+pub fn read_data_file(path: &Path, uda_types: &UdaTypes) -> Result<ImportResult, TaskError> {
+    Ok(parse_data_file(&std::fs::read_to_string(path)?, uda_types))
+}
+
+/// Parse the contents of a data file, one task per line
+pub fn parse_data_file(content: &str, uda_types: &UdaTypes) -> ImportResult {
+    let mut result = empty_result();
+    for (line_num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_data_line(line).and_then(|attrs| task_from_attributes(&attrs, uda_types)) {
+            Ok(task) => result.tasks.push(task),
+            Err(e) => {
+                result.errors.push(format!("Line {}: {}", line_num + 1, e));
+                result.skipped_count += 1;
+            }
+        }
+    }
+    result.imported_count = result.tasks.len();
+    result
+}
+
+/// Parse the contents of `undo.data`. Records without a `new` line are
+/// skipped; a malformed task line fails the whole parse, since later
+/// entries depend on earlier ones.
+pub fn parse_undo_data(content: &str, uda_types: &UdaTypes) -> Result<Vec<UndoEntry>, TaskError> {
+    let mut entries = Vec::new();
+    let mut time = None;
+    let mut old = None;
+    let mut new = None;
+
+    for (line_num, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let at_line = |e: TaskError| TaskError::InvalidData {
+            message: format!("undo.data line {}: {}", line_num + 1, e),
+        };
+        if line == "---" {
+            if let (Some(time), Some(new)) = (time.take(), new.take()) {
+                entries.push(UndoEntry { time, old: old.take(), new });
+            }
+            old = None;
+        } else if let Some(seconds) = line.strip_prefix("time ") {
+            time = Some(parse_timestamp(seconds).ok_or_else(|| at_line(invalid(format!("bad time '{seconds}'"))))?);
+        } else if let Some(data) = line.strip_prefix("old ") {
+            old = Some(parse_task_line(data, uda_types).map_err(at_line)?);
+        } else if let Some(data) = line.strip_prefix("new ") {
+            new = Some(parse_task_line(data, uda_types).map_err(at_line)?);
+        } else if !line.is_empty() {
+            return Err(at_line(invalid(format!("unexpected line '{line}'"))));
+        }
+    }
+    // The last record may lack its trailing separator
+    if let (Some(time), Some(new)) = (time, new) {
+        entries.push(UndoEntry { time, old, new });
+    }
+    Ok(entries)
+}
+
+/// Parse one `[key:"value" ...]` line into its attributes, in file order
+pub fn parse_data_line(line: &str) -> Result<Vec<(String, String)>, TaskError> {
+    let inner = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(|| invalid("Data line must be enclosed in [ ]".to_string()))?;
+
+    let mut attrs = Vec::new();
+    let mut chars = inner.char_indices().peekable();
+    loop {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let Some(&(key_start, _)) = chars.peek() else {
+            break;
+        };
+        let key_end = loop {
+            match chars.next() {
+                Some((i, ':')) => break i,
+                Some(_) => {}
+                None => return Err(invalid(format!("Attribute without value: {}", &inner[key_start..]))),
+            }
+        };
+        let key = &inner[key_start..key_end];
+        if key.is_empty() {
+            return Err(invalid("Empty attribute name".to_string()));
+        }
+        if chars.next().map(|(_, c)| c) != Some('"') {
+            return Err(invalid(format!("Value of '{key}' is not quoted")));
+        }
+
+        let value_start = key_end + 2;
+        let value_end = loop {
+            match chars.next() {
+                Some((_, '\\')) => {
+                    chars.next();
+                }
+                Some((i, '"')) => break i,
+                Some(_) => {}
+                None => return Err(invalid(format!("Unterminated value for '{key}'"))),
+            }
+        };
+        attrs.push((key.to_string(), decode_value(&inner[value_start..value_end])));
+    }
+    Ok(attrs)
+}
+
+/// Build a task from data file attributes. `uuid` is required; attributes
+/// without a task field (including `until` and `imask`) become UDAs.
+pub fn task_from_attributes(attrs: &[(String, String)], uda_types: &UdaTypes) -> Result<Task, TaskError> {
+    let attrs: HashMap<&str, &str> = attrs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let uuid = attrs.get("uuid").ok_or_else(|| invalid("Task has no uuid".to_string()))?;
+    let date = |key: &str| -> Result<Option<DateTime<Utc>>, TaskError> {
+        attrs
+            .get(key)
+            .map(|v| parse_timestamp(v).ok_or_else(|| invalid(format!("Invalid {key} date '{v}'"))))
+            .transpose()
+    };
+
+    let mut task = Task::new(attrs.get("description").copied().unwrap_or_default().to_string());
+    task.id = Uuid::parse_str(uuid).map_err(|e| invalid(format!("Invalid uuid '{uuid}': {e}")))?;
+    task.status = match attrs.get("status").copied().unwrap_or("pending") {
+        "pending" => TaskStatus::Pending,
+        "completed" => TaskStatus::Completed,
+        "deleted" => TaskStatus::Deleted,
+        "waiting" => TaskStatus::Waiting,
+        "recurring" => TaskStatus::Recurring,
+        other => return Err(invalid(format!("Unknown status '{other}'"))),
+    };
+    if let Some(entry) = date("entry")? {
+        task.entry = entry;
+    }
+    task.modified = date("modified")?;
+    task.due = date("due")?;
+    task.scheduled = date("scheduled")?;
+    task.wait = date("wait")?;
+    task.end = date("end")?;
+    task.start = date("start")?;
+    task.active = task.start.is_some();
+
+    for (&key, &value) in &attrs {
+        match key {
+            "uuid" | "description" | "status" | "entry" | "modified" | "due" | "scheduled" | "wait"
+            | "end" | "start" => {}
+            "project" => task.project = Some(value.to_string()),
+            "priority" => task.set_priority_code(Some(value)),
+            "tags" => {
+                task.tags = value.split(',').filter(|t| !t.is_empty()).map(str::to_string).collect();
+            }
+            "depends" => task.depends = parse_depends(value)?,
+            "parent" => {
+                task.parent = Some(Uuid::parse_str(value).map_err(|e| invalid(format!("Invalid parent '{value}': {e}")))?);
+            }
+            "mask" => task.mask = Some(value.to_string()),
+            "recur" => match RecurrencePattern::parse(value) {
+                Ok(pattern) => task.recur = Some(pattern),
+                Err(_) => {
+                    task.udas.insert(key.to_string(), UdaValue::String(value.to_string()));
+                }
+            },
+            "until" => {
+                let until = parse_timestamp(value).ok_or_else(|| invalid(format!("Invalid until date '{value}'")))?;
+                task.udas.insert(key.to_string(), UdaValue::Date(until));
+            }
+            _ if key.starts_with("annotation_") => {
+                let entry = parse_timestamp(&key["annotation_".len()..])
+                    .ok_or_else(|| invalid(format!("Invalid annotation key '{key}'")))?;
+                task.annotations.push(Annotation::with_timestamp(value.to_string(), entry));
+            }
+            _ => {
+                task.udas.insert(key.to_string(), decode_uda(value, uda_types.get(key)));
+            }
+        }
+    }
+    task.annotations.sort_by_key(|a| a.entry);
+    Ok(task)
+}
+
+fn parse_task_line(line: &str, uda_types: &UdaTypes) -> Result<Task, TaskError> {
+    task_from_attributes(&parse_data_line(line)?, uda_types)
+}
+
+// Taskwarrior 2.6 writes depends as a JSON array, earlier versions as a
+// comma-separated list
+fn parse_depends(value: &str) -> Result<std::collections::HashSet<Uuid>, TaskError> {
+    if value.starts_with('[') {
+        let ids: Vec<String> = serde_json::from_str(value)?;
+        parse_depends_list(&ids.join(","))
+    } else {
+        parse_depends_list(value)
+    }
+    .map_err(invalid)
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(value.trim().parse().ok()?, 0).single()
+}
+
+// Undo JSON string escapes, then Taskwarrior's bracket and quote entities
+fn decode_value(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let decoded = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                out.push(decoded.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            // \" \\ \/ and anything unexpected stand for themselves
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out.replace("&open;", "[").replace("&close;", "]").replace("&dquot;", "\"")
+}
+
+fn invalid(message: String) -> TaskError {
+    TaskError::InvalidData { message }
+}
+
+fn empty_result() -> ImportResult {
+    ImportResult {
+        tasks: Vec::new(),
+        imported_count: 0,
+        updated_count: 0,
+        skipped_count: 0,
+        errors: Vec::new(),
+        decisions: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PENDING: &str = r#"[description:"Call &open;Bob&close; about \"taxes\"" due:"1700000000" entry:"1690000000" project:"home" status:"pending" tags:"phone,errand" uuid:"8ef0fb62-c4b6-4bdb-b0e5-0c0a1bc0e0a1" annotation_1695000000:"left voicemail" estimate:"3"]
+[description:"Broken line" uuid:"nope"]
+"#;
+
+    const COMPLETED: &str = r#"[description:"File taxes" end:"1710000000" entry:"1690000000" status:"completed" uuid:"0b8ad3c2-1f0e-4d34-9b0e-2f8a0c5d6e7f" depends:"[\"8ef0fb62-c4b6-4bdb-b0e5-0c0a1bc0e0a1\"]"]
+"#;
+
+    const UNDO: &str = r#"time 1690000000
+new [description:"File taxes" entry:"1690000000" status:"pending" uuid:"0b8ad3c2-1f0e-4d34-9b0e-2f8a0c5d6e7f"]
+---
+time 1710000000
+old [description:"File taxes" entry:"1690000000" status:"pending" uuid:"0b8ad3c2-1f0e-4d34-9b0e-2f8a0c5d6e7f"]
+new [description:"File taxes" end:"1710000000" entry:"1690000000" status:"completed" uuid:"0b8ad3c2-1f0e-4d34-9b0e-2f8a0c5d6e7f"]
+---
+"#;
+
+    #[test]
+    fn test_parse_data_line_decodes_values() {
+        let attrs = parse_data_line(r#"[description:"a &open;b&close; \"c\" &dquot;d&dquot;\n" tags:"x"]"#).unwrap();
+        assert_eq!(
+            attrs,
+            vec![
+                ("description".to_string(), "a [b] \"c\" \"d\"\n".to_string()),
+                ("tags".to_string(), "x".to_string()),
+            ]
+        );
+        assert!(parse_data_line("description:\"x\"").is_err());
+        assert!(parse_data_line("[description:\"x]").is_err());
+    }
+
+    #[test]
+    fn test_import_data_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Configuration {
+            data_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        config.set("uda.estimate.type", "numeric");
+        std::fs::write(config.task_data_file(), PENDING).unwrap();
+        std::fs::write(config.completed_data_file(), COMPLETED).unwrap();
+        std::fs::write(config.undo_data_file(), UNDO).unwrap();
+
+        let data = import_data_dir(&config).unwrap();
+        assert_eq!(data.tasks.imported_count, 2);
+        assert_eq!(data.tasks.skipped_count, 1);
+        assert!(data.tasks.errors[0].starts_with("pending.data: Line 2"));
+
+        let call = &data.tasks.tasks[0];
+        assert_eq!(call.description, "Call [Bob] about \"taxes\"");
+        assert_eq!(call.project.as_deref(), Some("home"));
+        assert_eq!(call.due.unwrap().timestamp(), 1_700_000_000);
+        assert!(call.tags.contains("phone") && call.tags.contains("errand"));
+        assert_eq!(call.annotations[0].description, "left voicemail");
+        assert_eq!(call.udas.get("estimate"), Some(&UdaValue::Number(3.0)));
+
+        let taxes = &data.tasks.tasks[1];
+        assert_eq!(taxes.status, TaskStatus::Completed);
+        assert!(taxes.depends.contains(&call.id));
+
+        assert_eq!(data.undo.len(), 2);
+        assert!(data.undo[0].old.is_none());
+        assert_eq!(data.undo[1].old.as_ref().unwrap().status, TaskStatus::Pending);
+        assert_eq!(data.undo[1].new.status, TaskStatus::Completed);
+    }
+}
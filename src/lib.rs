@@ -14,10 +14,19 @@
 //! - **Hook System**: Extensible task lifecycle hooks
 //! - **Reports**: Built-in and custom report generation
 //! - **JSON I/O**: Import and export task data
+//! - **Backups**: Encrypted, checksummed backups to a directory, WebDAV
+//!   (`backup-webdav` feature) or S3 (`backup-s3` feature)
+//! - **Daemon Mode** (`daemon` feature): Long-running task service with
+//!   scheduled maintenance, auto-sync and change events
+//! - **Local Server** (`server` feature): JSON-RPC endpoint over HTTP
+//! - **MCP Tools** (`mcp` feature): Task tools for LLM agents over stdio
+//!
+//! The [`api`] module lists the items covered by semver; other paths may
+//! change between minor releases.
 //!
 //! ## Quick Start
 //!
-//! ```rust
+//! ```rust,no_run
 //! use taskwarrior3lib::{Configuration, TaskManager};
 //! use taskwarrior3lib::storage::TaskChampionStorageBackend;
 //! use taskwarrior3lib::hooks::DefaultHookSystem;
@@ -53,17 +62,36 @@ pub use query::{TaskQuery, TaskQueryBuilder, TaskQueryBuilderImpl};
 pub use task::{Annotation, Priority, Task, TaskStatus};
 
 // Module declarations
+pub mod alerts;
+pub mod api;
+pub mod backup;
+pub mod bootstrap;
+#[cfg(any(test, feature = "proptest"))]
+pub mod arbitrary;
+pub mod board;
+pub mod clock;
 pub mod config;
 pub mod context;
+pub mod core;
+#[cfg(feature = "daemon")]
+pub mod daemon;
 pub mod date;
 pub mod error;
 pub mod hooks;
+#[cfg(any(feature = "webhooks", feature = "backup-webdav", feature = "backup-s3"))]
+mod http;
 pub mod io;
 pub mod query;
 pub mod reports;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod storage;
 pub mod sync;
 pub mod task;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
 
 // Re-export traits
 pub use config::ConfigurationProvider;
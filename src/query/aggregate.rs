@@ -0,0 +1,314 @@
+//! Queries over groups of tasks
+//!
+//! Review questions such as "which projects have more than two overdue
+//! tasks?" ask about groups, not single tasks. A [`GroupQuery`] groups
+//! tasks by project, tag or any other `group_by` field of the reports,
+//! keeps the groups whose aggregates pass every [`Having`] condition, and
+//! returns either the groups or their member tasks:
+//!
+//! ```rust
+//! use taskwarrior3lib::query::aggregate::{Aggregate, GroupQuery};
+//! use taskwarrior3lib::task::Task;
+//!
+//! let mut report = Task::new("Write report".to_string());
+//! report.project = Some("Work".to_string());
+//! let groups = GroupQuery::by("project")
+//!     .more_than(Aggregate::Count, 0.0)
+//!     .groups(&[report]);
+//! assert_eq!(groups[0].key, "Work");
+//! ```
+//!
+//! With tags, a task belongs to a group per tag, so member lists of
+//! different groups can overlap; [`GroupQuery::members`] returns each task
+//! once.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::reports::builtin::group_keys;
+use crate::task::model::UdaValue;
+use crate::task::{Task, TaskStatus};
+
+/// A number computed over the tasks of a group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    /// Tasks in the group
+    Count,
+    /// Pending tasks past their due date
+    Overdue,
+    /// Tasks with this status
+    Status(TaskStatus),
+    /// Tasks carrying this tag
+    Tagged(String),
+    /// Sum of a numeric UDA, e.g. `estimate`; tasks without it count as 0
+    Sum(String),
+    /// Average of a numeric UDA over the tasks that have it; 0 when none do
+    Average(String),
+}
+
+impl Aggregate {
+    /// The aggregate over `tasks`
+    pub fn evaluate(&self, tasks: &[Task]) -> f64 {
+        let count = |pred: &dyn Fn(&Task) -> bool| tasks.iter().filter(|t| pred(t)).count() as f64;
+        match self {
+            Aggregate::Count => tasks.len() as f64,
+            Aggregate::Overdue => count(&|t| t.is_overdue()),
+            Aggregate::Status(status) => count(&|t| t.status == *status),
+            Aggregate::Tagged(tag) => count(&|t| t.tags.contains(tag)),
+            Aggregate::Sum(uda) => numbers(tasks, uda).sum(),
+            Aggregate::Average(uda) => {
+                let values: Vec<f64> = numbers(tasks, uda).collect();
+                if values.is_empty() {
+                    0.0
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Aggregate::Count => write!(f, "count"),
+            Aggregate::Overdue => write!(f, "overdue"),
+            Aggregate::Status(status) => write!(f, "{}", format!("{status:?}").to_lowercase()),
+            Aggregate::Tagged(tag) => write!(f, "+{tag}"),
+            Aggregate::Sum(uda) => write!(f, "sum({uda})"),
+            Aggregate::Average(uda) => write!(f, "avg({uda})"),
+        }
+    }
+}
+
+fn numbers<'a>(tasks: &'a [Task], uda: &'a str) -> impl Iterator<Item = f64> + 'a {
+    tasks.iter().filter_map(move |t| match t.udas.get(uda) {
+        Some(UdaValue::Number(n)) => Some(*n),
+        _ => None,
+    })
+}
+
+/// How an aggregate is compared with a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    pub fn compare(self, actual: f64, value: f64) -> bool {
+        match self {
+            Comparison::Greater => actual > value,
+            Comparison::GreaterOrEqual => actual >= value,
+            Comparison::Less => actual < value,
+            Comparison::LessOrEqual => actual <= value,
+            Comparison::Equal => actual == value,
+            Comparison::NotEqual => actual != value,
+        }
+    }
+}
+
+/// A condition on a group's aggregate, like SQL's `HAVING`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Having {
+    pub aggregate: Aggregate,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+impl Having {
+    pub fn matches(&self, tasks: &[Task]) -> bool {
+        self.comparison
+            .compare(self.aggregate.evaluate(tasks), self.value)
+    }
+}
+
+/// A group that passed a [`GroupQuery`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskGroup {
+    /// Group label, e.g. the project name, or `(none)`
+    pub key: String,
+    pub tasks: Vec<Task>,
+    /// Value of each [`Having`] aggregate, in the query's order
+    pub aggregates: Vec<f64>,
+}
+
+impl TaskGroup {
+    /// Another aggregate over this group
+    pub fn aggregate(&self, aggregate: &Aggregate) -> f64 {
+        aggregate.evaluate(&self.tasks)
+    }
+}
+
+/// Group tasks and keep the groups whose aggregates pass every condition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupQuery {
+    /// A report `group_by` field: `project`, `tag`, `status`, `priority`,
+    /// `due.week` or a UDA name
+    pub group_by: String,
+    pub having: Vec<Having>,
+}
+
+impl GroupQuery {
+    /// Group by `field`, keeping every group until conditions are added
+    pub fn by<S: Into<String>>(field: S) -> Self {
+        Self {
+            group_by: field.into(),
+            having: Vec::new(),
+        }
+    }
+
+    pub fn having(mut self, aggregate: Aggregate, comparison: Comparison, value: f64) -> Self {
+        self.having.push(Having {
+            aggregate,
+            comparison,
+            value,
+        });
+        self
+    }
+
+    /// Keep groups where `aggregate` exceeds `value`
+    pub fn more_than(self, aggregate: Aggregate, value: f64) -> Self {
+        self.having(aggregate, Comparison::Greater, value)
+    }
+
+    /// Keep groups where `aggregate` is below `value`
+    pub fn fewer_than(self, aggregate: Aggregate, value: f64) -> Self {
+        self.having(aggregate, Comparison::Less, value)
+    }
+
+    /// Matching groups in key order, their tasks in input order
+    pub fn groups(&self, tasks: &[Task]) -> Vec<TaskGroup> {
+        let mut grouped: BTreeMap<String, Vec<Task>> = BTreeMap::new();
+        for task in tasks {
+            for key in group_keys(task, &self.group_by) {
+                grouped.entry(key).or_default().push(task.clone());
+            }
+        }
+        grouped
+            .into_iter()
+            .filter(|(_, tasks)| self.having.iter().all(|h| h.matches(tasks)))
+            .map(|(key, tasks)| TaskGroup {
+                aggregates: self
+                    .having
+                    .iter()
+                    .map(|h| h.aggregate.evaluate(&tasks))
+                    .collect(),
+                key,
+                tasks,
+            })
+            .collect()
+    }
+
+    /// Tasks of the matching groups, each once, in input order
+    pub fn members(&self, tasks: &[Task]) -> Vec<Task> {
+        let ids: HashSet<_> = self
+            .groups(tasks)
+            .iter()
+            .flat_map(|g| g.tasks.iter().map(|t| t.id))
+            .collect();
+        tasks
+            .iter()
+            .filter(|t| ids.contains(&t.id))
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Display for GroupQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "group by {}", self.group_by)?;
+        for (i, having) in self.having.iter().enumerate() {
+            let op = match having.comparison {
+                Comparison::Greater => ">",
+                Comparison::GreaterOrEqual => ">=",
+                Comparison::Less => "<",
+                Comparison::LessOrEqual => "<=",
+                Comparison::Equal => "=",
+                Comparison::NotEqual => "!=",
+            };
+            let joiner = if i == 0 { " having" } else { " and" };
+            write!(f, "{joiner} {} {op} {}", having.aggregate, having.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{self, FixedClock, SequentialIds};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn task(description: &str, project: &str, overdue: bool) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.project = Some(project.to_string());
+        let offset = if overdue { -2 } else { 2 };
+        task.due = Some(clock::now() + Duration::days(offset));
+        task
+    }
+
+    #[test]
+    fn test_projects_with_more_than_n_overdue() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let _clock = clock::deterministic(FixedClock::new(now), SequentialIds::new());
+        let mut tasks = vec![
+            task("Invoice", "Work", true),
+            task("Slides", "Work", true),
+            task("Review", "Work", false),
+            task("Taxes", "Home", true),
+            task("Paint", "Home", false),
+        ];
+        tasks[2]
+            .udas
+            .insert("estimate".to_string(), UdaValue::Number(3.0));
+        let mut done = task("Filed", "Home", true);
+        done.status = TaskStatus::Completed;
+        tasks.push(done);
+
+        let query = GroupQuery::by("project").more_than(Aggregate::Overdue, 1.0);
+        assert_eq!(query.to_string(), "group by project having overdue > 1");
+        let groups = query.groups(&tasks);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].key, "Work");
+        assert_eq!(groups[0].aggregates, [2.0]);
+        assert_eq!(groups[0].aggregate(&Aggregate::Sum("estimate".into())), 3.0);
+        assert_eq!(query.members(&tasks).len(), 3);
+
+        let small = GroupQuery::by("project")
+            .fewer_than(Aggregate::Status(TaskStatus::Pending), 3.0)
+            .having(
+                Aggregate::Status(TaskStatus::Completed),
+                Comparison::GreaterOrEqual,
+                1.0,
+            );
+        let keys: Vec<String> = small.groups(&tasks).into_iter().map(|g| g.key).collect();
+        assert_eq!(keys, ["Home"]);
+    }
+
+    #[test]
+    fn test_tag_groups_overlap() {
+        let mut a = Task::new("A".to_string());
+        a.tags.extend(["x".to_string(), "y".to_string()]);
+        let mut b = Task::new("B".to_string());
+        b.tags.insert("y".to_string());
+        let tasks = [a, b];
+
+        let query = GroupQuery::by("tag").more_than(Aggregate::Count, 0.0);
+        let groups = query.groups(&tasks);
+        let sizes: Vec<(&str, usize)> = groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.tasks.len()))
+            .collect();
+        assert_eq!(sizes, [("x", 1), ("y", 2)]);
+        assert_eq!(query.members(&tasks).len(), 2);
+    }
+}
@@ -2,11 +2,16 @@
 //!
 //! This module provides the TaskQueryBuilder implementation.
 
+use crate::clock;
 use crate::error::QueryError;
-use crate::query::{DateFilter, ProjectFilter, SortCriteria, TagFilter, TaskQuery};
+use crate::query::geo::Proximity;
+use crate::query::{
+    DateFilter, OwnerFilter, PriorityFilter, ProjectFilter, SortCriteria, TagFilter,
+    TaskPredicate, TaskQuery,
+};
 #[allow(unused_imports)]
-use crate::task::{Priority, TaskStatus};
-use chrono::{DateTime, Utc};
+use crate::task::{Priority, Task, TaskStatus};
+use chrono::{DateTime, Duration, Utc};
 
 /// TaskQueryBuilder implementation
 #[derive(Debug, Default)]
@@ -19,6 +24,21 @@ pub struct TaskQueryBuilderImpl {
     limit: Option<usize>,
     offset: Option<usize>,
     filter_mode: Option<crate::query::FilterMode>,
+    custom_filters: Vec<TaskPredicate>,
+    owner_filter: Option<OwnerFilter>,
+    priority_filter: Option<PriorityFilter>,
+    near: Option<(f64, f64, f64)>,
+}
+
+impl TaskQueryBuilderImpl {
+    // Chained date conditions must all hold
+    fn and_date(mut self, filter: DateFilter) -> Self {
+        self.date_filter = Some(match self.date_filter.take() {
+            Some(existing) => existing.and(filter),
+            None => filter,
+        });
+        self
+    }
 }
 
 /// TaskQueryBuilder trait definition
@@ -27,10 +47,40 @@ pub trait TaskQueryBuilder {
     fn status(self, status: TaskStatus) -> Self;
     fn project(self, project: String) -> Self;
     fn tag(self, tag: String) -> Self;
+    /// Only tasks with at least one of `tags`
+    fn any_tag(self, tags: Vec<String>) -> Self;
+    /// Only tasks with every one of `tags`
+    fn all_tags(self, tags: Vec<String>) -> Self;
+    /// Only tasks with none of `tags`
+    fn no_tags(self, tags: Vec<String>) -> Self;
+    /// Only tasks with at least `count` tags
+    fn min_tags(self, count: usize) -> Self;
+    /// Only tasks without tags
+    fn untagged(self) -> Self;
+    /// Only tasks owned by the given user
+    fn owner(self, owner: String) -> Self;
+    fn owner_filter(self, filter: OwnerFilter) -> Self;
+    /// Only tasks with the given priority code (e.g. `H`)
+    fn priority(self, code: String) -> Self;
     fn due_before(self, date: DateTime<Utc>) -> Self;
     fn due_after(self, date: DateTime<Utc>) -> Self;
+    /// Only tasks created within `duration` of now
+    fn created_within(self, duration: Duration) -> Self;
+    /// Only completed tasks that ended in `[start, end)`
+    fn completed_between(self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self;
+    /// Only tasks modified after `date`
+    fn modified_since(self, date: DateTime<Utc>) -> Self;
+    /// Only tasks located within `radius_km` of `(latitude, longitude)`;
+    /// see [`Proximity`]
+    fn near(self, latitude: f64, longitude: f64, radius_km: f64) -> Self;
     fn sort_by_priority(self) -> Self;
+    /// Sort by any field, e.g. `SortCriteria::ascending(SortField::Due)`
+    fn sort_by(self, criteria: SortCriteria) -> Self;
     fn filter_mode(self, mode: crate::query::FilterMode) -> Self;
+    /// Add a closure predicate evaluated in memory; see [`TaskPredicate`]
+    fn custom<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&Task) -> bool + Send + Sync + 'static;
     fn limit(self, limit: usize) -> Self;
     fn offset(self, offset: usize) -> Self;
     fn build(self) -> Result<TaskQuery, QueryError>;
@@ -56,13 +106,69 @@ impl TaskQueryBuilder for TaskQueryBuilderImpl {
         self
     }
 
-    fn due_before(mut self, date: DateTime<Utc>) -> Self {
-        self.date_filter = Some(DateFilter::DueBefore(date));
+    fn any_tag(mut self, tags: Vec<String>) -> Self {
+        self.tag_filter.get_or_insert_with(TagFilter::default).any_of.extend(tags);
+        self
+    }
+
+    fn all_tags(mut self, tags: Vec<String>) -> Self {
+        self.tag_filter.get_or_insert_with(TagFilter::default).all_of.extend(tags);
+        self
+    }
+
+    fn no_tags(mut self, tags: Vec<String>) -> Self {
+        self.tag_filter.get_or_insert_with(TagFilter::default).none_of.extend(tags);
+        self
+    }
+
+    fn min_tags(mut self, count: usize) -> Self {
+        self.tag_filter.get_or_insert_with(TagFilter::default).min_count = Some(count);
+        self
+    }
+
+    fn untagged(mut self) -> Self {
+        self.tag_filter.get_or_insert_with(TagFilter::default).untagged = true;
+        self
+    }
+
+    fn owner(mut self, owner: String) -> Self {
+        self.owner_filter = Some(OwnerFilter::Is(owner));
         self
     }
 
-    fn due_after(mut self, date: DateTime<Utc>) -> Self {
-        self.date_filter = Some(DateFilter::DueAfter(date));
+    fn owner_filter(mut self, filter: OwnerFilter) -> Self {
+        self.owner_filter = Some(filter);
+        self
+    }
+
+    fn priority(mut self, code: String) -> Self {
+        self.priority_filter = Some(PriorityFilter::Is(code));
+        self
+    }
+
+    fn due_before(self, date: DateTime<Utc>) -> Self {
+        self.and_date(DateFilter::DueBefore(date))
+    }
+
+    fn due_after(self, date: DateTime<Utc>) -> Self {
+        self.and_date(DateFilter::DueAfter(date))
+    }
+
+    fn created_within(self, duration: Duration) -> Self {
+        self.and_date(DateFilter::EntryAfter(clock::now() - duration))
+    }
+
+    fn completed_between(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.status = Some(TaskStatus::Completed);
+        self.and_date(DateFilter::EndBetween(start, end))
+    }
+
+    fn modified_since(self, date: DateTime<Utc>) -> Self {
+        self.and_date(DateFilter::ModifiedAfter(date))
+    }
+
+    fn near(mut self, latitude: f64, longitude: f64, radius_km: f64) -> Self {
+        self.near = Some((latitude, longitude, radius_km));
         self
     }
 
@@ -71,11 +177,24 @@ impl TaskQueryBuilder for TaskQueryBuilderImpl {
         self
     }
 
+    fn sort_by(mut self, criteria: SortCriteria) -> Self {
+        self.sort = Some(criteria);
+        self
+    }
+
     fn filter_mode(mut self, mode: crate::query::FilterMode) -> Self {
         self.filter_mode = Some(mode);
         self
     }
 
+    fn custom<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Task) -> bool + Send + Sync + 'static,
+    {
+        self.custom_filters.push(TaskPredicate::new(predicate));
+        self
+    }
+
     fn limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
@@ -91,6 +210,17 @@ impl TaskQueryBuilder for TaskQueryBuilderImpl {
         if self.limit == Some(0) {
             return Err(QueryError::InvalidLimit);
         }
+        for part in self.date_filter.iter().flat_map(DateFilter::parts) {
+            if let DateFilter::DueBetween(start, end) | DateFilter::EndBetween(start, end) = *part {
+                if start > end {
+                    return Err(QueryError::InvalidDateRange { start, end });
+                }
+            }
+        }
+        let proximity = self
+            .near
+            .map(|(latitude, longitude, radius_km)| Proximity::new(latitude, longitude, radius_km))
+            .transpose()?;
         // default filter_mode is None (up to caller to interpret), keep optional
         Ok(TaskQuery {
             status: self.status,
@@ -101,6 +231,12 @@ impl TaskQueryBuilder for TaskQueryBuilderImpl {
             limit: self.limit,
             offset: self.offset,
             filter_mode: self.filter_mode,
+            custom_filters: self.custom_filters,
+            owner_filter: self.owner_filter,
+            priority_filter: self.priority_filter,
+            priority_scheme: None,
+            collation: None,
+            proximity,
         })
     }
 }
@@ -140,6 +276,7 @@ impl QueryBuilder for TaskQueryBuilderImpl {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
     fn test_query_builder_basic() {
@@ -154,10 +291,120 @@ mod tests {
         assert!(matches!(query.project_filter, Some(ProjectFilter::Equals(ref p)) if p == "Work"));
     }
 
+    #[test]
+    fn test_tag_builder_methods_combine() {
+        let query = TaskQueryBuilderImpl::new()
+            .any_tag(vec!["work".to_string(), "home".to_string()])
+            .no_tags(vec!["someday".to_string()])
+            .min_tags(2)
+            .build()
+            .unwrap();
+        let filter = query.tag_filter.unwrap();
+        assert_eq!(filter.any_of.len(), 2);
+        assert!(filter.none_of.contains("someday"));
+        assert_eq!(filter.min_count, Some(2));
+        assert_eq!(TaskQueryBuilderImpl::new().untagged().build().unwrap().tag_filter, Some(TagFilter::untagged()));
+    }
+
+    #[test]
+    fn test_reporting_date_filters() {
+        let now = Utc::now();
+        let query = TaskQueryBuilderImpl::new().created_within(Duration::days(7)).build().unwrap();
+        let Some(DateFilter::EntryAfter(since)) = query.date_filter else { panic!() };
+        assert!(since <= now - Duration::days(7) + Duration::seconds(5));
+
+        let mut task = Task::new("Old".to_string());
+        task.entry = now - Duration::days(30);
+        assert!(!query.date_filter.as_ref().unwrap().matches(&task));
+        task.entry = now - Duration::days(1);
+        assert!(query.date_filter.as_ref().unwrap().matches(&task));
+
+        let query = TaskQueryBuilderImpl::new()
+            .completed_between(now - Duration::days(7), now)
+            .build()
+            .unwrap();
+        assert_eq!(query.status, Some(TaskStatus::Completed));
+        task.end = Some(now - Duration::days(2));
+        assert!(query.date_filter.as_ref().unwrap().matches(&task));
+        task.end = None;
+        assert!(!query.date_filter.as_ref().unwrap().matches(&task));
+
+        let query = TaskQueryBuilderImpl::new().modified_since(now - Duration::hours(1)).build().unwrap();
+        assert_eq!(query.date_filter, Some(DateFilter::ModifiedAfter(now - Duration::hours(1))));
+
+        let reversed = TaskQueryBuilderImpl::new().completed_between(now, now - Duration::days(1)).build();
+        assert!(matches!(reversed, Err(QueryError::InvalidDateRange { .. })));
+    }
+
+    #[test]
+    fn test_chained_date_filters_all_apply() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let _clock = clock::deterministic(clock::FixedClock::new(now), clock::SequentialIds::new());
+        let query = TaskQueryBuilderImpl::new()
+            .created_within(Duration::days(30))
+            .completed_between(now - Duration::days(7), now)
+            .due_before(now)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query.date_filter,
+            Some(DateFilter::All(vec![
+                DateFilter::EntryAfter(now - Duration::days(30)),
+                DateFilter::EndBetween(now - Duration::days(7), now),
+                DateFilter::DueBefore(now),
+            ]))
+        );
+
+        let mut task = Task::new("Report".to_string());
+        task.status = TaskStatus::Completed;
+        task.entry = now - Duration::days(10);
+        task.end = Some(now - Duration::days(2));
+        task.due = Some(now - Duration::days(1));
+        assert!(query.matches(&task));
+        task.entry = now - Duration::days(60);
+        assert!(!query.matches(&task));
+
+        let reversed = TaskQueryBuilderImpl::new()
+            .due_before(now)
+            .completed_between(now, now - Duration::days(1))
+            .build();
+        assert!(matches!(reversed, Err(QueryError::InvalidDateRange { .. })));
+    }
+
+    #[test]
+    fn test_near_filter() {
+        let query = TaskQueryBuilderImpl::new().near(52.52, 13.405, 2.0).build().unwrap();
+        let mut errand = Task::new("Buy bread".to_string());
+        assert!(!query.matches(&errand));
+        errand.set_location(Some((52.521, 13.413)));
+        assert!(query.matches(&errand));
+        errand.set_location(Some((48.8566, 2.3522)));
+        assert!(!query.matches(&errand));
+        assert!(query.custom_filters.is_empty());
+        let json = serde_json::to_string(&query).unwrap();
+        assert_eq!(serde_json::from_str::<TaskQuery>(&json).unwrap(), query);
+
+        let invalid = TaskQueryBuilderImpl::new().near(0.0, 200.0, 1.0).build();
+        assert!(matches!(invalid, Err(QueryError::InvalidFilter { .. })));
+    }
+
     #[test]
     fn test_query_builder_validation() {
         let builder = TaskQueryBuilderImpl::new();
         let result = builder.limit(0).build();
         assert!(matches!(result, Err(QueryError::InvalidLimit)));
     }
+
+    #[test]
+    fn test_custom_predicate() {
+        let query = TaskQueryBuilderImpl::new()
+            .custom(|task| task.description.contains('!'))
+            .build()
+            .unwrap();
+
+        assert_eq!(query.custom_filters.len(), 1);
+        assert!(query.matches_custom(&Task::new("Urgent!".to_string())));
+        assert!(!query.matches_custom(&Task::new("Calm".to_string())));
+        assert_eq!(query.clone(), query);
+    }
 }
@@ -0,0 +1,112 @@
+//! String ordering for sorts
+//!
+//! Sorting by description, project, owner or a string UDA compares text.
+//! Byte order, the default, puts `Zebra` before `apple` and `Élan` after
+//! `Zoo`. The `sort.collation` setting picks another [`Collation`]; the task
+//! manager applies it to query sorts and report managers to report sorts.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Configuration;
+
+/// How text values compare when sorting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collation {
+    /// Byte order of the UTF-8 text (`binary`)
+    #[default]
+    Binary,
+    /// Ignore letter case, falling back to byte order for values differing
+    /// only in case (`nocase`)
+    NoCase,
+    /// The Unicode Collation Algorithm with the CLDR root order, which
+    /// places accented letters next to their base letters and suits most
+    /// languages without locale-specific rules (`unicode`)
+    #[cfg(feature = "unicode-collation")]
+    Unicode,
+}
+
+impl Collation {
+    /// Parse a `sort.collation` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "binary" | "bytewise" => Some(Collation::Binary),
+            "nocase" | "case-insensitive" => Some(Collation::NoCase),
+            #[cfg(feature = "unicode-collation")]
+            "unicode" | "locale" => Some(Collation::Unicode),
+            _ => None,
+        }
+    }
+
+    /// Collation from `sort.collation`, byte order when unset or unknown
+    pub fn from_config(config: &Configuration) -> Self {
+        config
+            .get("sort.collation")
+            .and_then(|v| Self::parse(v))
+            .unwrap_or_default()
+    }
+
+    /// Order two strings
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Binary => a.cmp(b),
+            Collation::NoCase => a
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b.chars().flat_map(char::to_lowercase))
+                .then_with(|| a.cmp(b)),
+            #[cfg(feature = "unicode-collation")]
+            Collation::Unicode => {
+                thread_local! {
+                    static COLLATOR: std::cell::RefCell<feruca::Collator> =
+                        std::cell::RefCell::new(feruca::Collator::default());
+                }
+                COLLATOR.with(|collator| collator.borrow_mut().collate(a, b))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: Collation, words: &[&str]) -> Vec<String> {
+        let mut words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        words.sort_by(|a, b| collation.compare(a, b));
+        words
+    }
+
+    #[test]
+    fn test_nocase_ignores_case() {
+        let words = ["Zebra", "apple", "Apple", "mango"];
+        assert_eq!(
+            sorted(Collation::Binary, &words),
+            ["Apple", "Zebra", "apple", "mango"]
+        );
+        assert_eq!(
+            sorted(Collation::NoCase, &words),
+            ["Apple", "apple", "mango", "Zebra"]
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = Configuration::default();
+        assert_eq!(Collation::from_config(&config), Collation::Binary);
+        config.set("sort.collation", "NoCase");
+        assert_eq!(Collation::from_config(&config), Collation::NoCase);
+    }
+
+    #[cfg(feature = "unicode-collation")]
+    #[test]
+    fn test_unicode_places_accents_with_base_letters() {
+        let words = ["Zoo", "Élan", "eagle", "Ezra"];
+        assert_eq!(
+            sorted(Collation::Unicode, &words),
+            ["eagle", "Élan", "Ezra", "Zoo"]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Explaining query matches
+//!
+//! [`TaskQuery::evaluate_explain`] checks a task against each filter clause
+//! of a query and records the outcome, so "why isn't my task in this
+//! report?" is answered by the first failed clause instead of by guessing.
+//! [`DefaultTaskManager::explain_match`](crate::task::manager::DefaultTaskManager::explain_match)
+//! adds the active context, as queries through the manager see it.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::query::{DateFilter, TaskQuery};
+use crate::task::Task;
+
+/// Outcome of one filter clause
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClauseTrace {
+    /// Which clause, e.g. `status`, `project` or `custom[0]`
+    pub clause: String,
+    /// What the clause asks for
+    pub condition: String,
+    /// The task's value for the clause
+    pub actual: String,
+    pub passed: bool,
+}
+
+/// Clause-by-clause result of matching a task against a query
+///
+/// Clauses appear in evaluation order and only for parts of the query that
+/// are set; a query without filters gives an empty trace that matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchTrace {
+    pub clauses: Vec<ClauseTrace>,
+}
+
+impl MatchTrace {
+    /// Whether every clause passed, i.e. the query matches the task
+    pub fn matched(&self) -> bool {
+        self.clauses.iter().all(|c| c.passed)
+    }
+
+    /// Clauses the task failed
+    pub fn failures(&self) -> impl Iterator<Item = &ClauseTrace> {
+        self.clauses.iter().filter(|c| !c.passed)
+    }
+
+    /// Record the outcome of a clause
+    pub fn push<C, E, A>(&mut self, clause: C, condition: E, actual: A, passed: bool)
+    where
+        C: Into<String>,
+        E: Into<String>,
+        A: Into<String>,
+    {
+        self.clauses.push(ClauseTrace {
+            clause: clause.into(),
+            condition: condition.into(),
+            actual: actual.into(),
+            passed,
+        });
+    }
+}
+
+impl fmt::Display for MatchTrace {
+    /// One line per clause, e.g. `FAIL project: Equals("Work") (task: Home)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for clause in &self.clauses {
+            writeln!(
+                f,
+                "{} {}: {} (task: {})",
+                if clause.passed { "pass" } else { "FAIL" },
+                clause.clause,
+                clause.condition,
+                clause.actual
+            )?;
+        }
+        write!(
+            f,
+            "{}",
+            if self.matched() {
+                "matched"
+            } else {
+                "not matched"
+            }
+        )
+    }
+}
+
+impl TaskQuery {
+    /// Check `task` against each filter of this query, recording every
+    /// clause rather than stopping at the first failure. The trace matches
+    /// exactly when [`matches`](TaskQuery::matches) does; sorting, `limit`,
+    /// `offset` and contexts are not considered.
+    pub fn evaluate_explain(&self, task: &Task) -> MatchTrace {
+        let mut trace = MatchTrace::default();
+        if let Some(status) = &self.status {
+            trace.push(
+                "status",
+                format!("{status:?}"),
+                format!("{:?}", task.status),
+                task.status == *status,
+            );
+        }
+        if let Some(filter) = &self.project_filter {
+            trace.push(
+                "project",
+                format!("{filter:?}"),
+                optional(task.project.as_deref()),
+                filter.matches(task.project.as_deref()),
+            );
+        }
+        if let Some(filter) = &self.tag_filter {
+            let mut tags: Vec<&str> = task.tags.iter().map(String::as_str).collect();
+            tags.sort_unstable();
+            let actual = if tags.is_empty() {
+                "no tags".to_string()
+            } else {
+                tags.join(",")
+            };
+            trace.push(
+                "tags",
+                describe_tags(filter),
+                actual,
+                filter.matches(&task.tags),
+            );
+        }
+        for filter in self.date_filter.iter().flat_map(DateFilter::parts) {
+            let (field, value) = date_value(filter, task);
+            trace.push(
+                field,
+                format!("{filter:?}"),
+                value.map_or_else(|| "none".to_string(), |d| d.to_rfc3339()),
+                filter.matches(task),
+            );
+        }
+        if let Some(filter) = &self.priority_filter {
+            trace.push(
+                "priority",
+                format!("{filter:?}"),
+                optional(task.priority_code()),
+                filter.matches(task.priority_code()),
+            );
+        }
+        if let Some(filter) = &self.owner_filter {
+            trace.push(
+                "owner",
+                format!("{filter:?}"),
+                optional(task.owner.as_deref()),
+                filter.matches(task.owner.as_deref()),
+            );
+        }
+        if let Some(proximity) = &self.proximity {
+            trace.push(
+                "location",
+                format!("{proximity:?}"),
+                proximity
+                    .distance_km(task)
+                    .map_or_else(|| "none".to_string(), |d| format!("{d:.3} km away")),
+                proximity.matches(task),
+            );
+        }
+        for (i, predicate) in self.custom_filters.iter().enumerate() {
+            let passed = predicate.matches(task);
+            trace.push(
+                format!("custom[{i}]"),
+                "predicate",
+                if passed { "true" } else { "false" },
+                passed,
+            );
+        }
+        trace
+    }
+}
+
+fn optional(value: Option<&str>) -> String {
+    value.unwrap_or("none").to_string()
+}
+
+fn describe_tags(filter: &crate::query::TagFilter) -> String {
+    let list = |tags: &std::collections::HashSet<String>| {
+        let mut tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        tags.sort_unstable();
+        tags.join(",")
+    };
+    let mut parts = Vec::new();
+    if !filter.any_of.is_empty() {
+        parts.push(format!("any of {}", list(&filter.any_of)));
+    }
+    if !filter.all_of.is_empty() {
+        parts.push(format!("all of {}", list(&filter.all_of)));
+    }
+    if !filter.none_of.is_empty() {
+        parts.push(format!("none of {}", list(&filter.none_of)));
+    }
+    if let Some(count) = filter.min_count {
+        parts.push(format!("at least {count}"));
+    }
+    if filter.untagged {
+        parts.push("untagged".to_string());
+    }
+    if parts.is_empty() {
+        "any".to_string()
+    } else {
+        parts.join("; ")
+    }
+}
+
+/// The date attribute `filter` looks at, and the task's value for it
+fn date_value(filter: &DateFilter, task: &Task) -> (&'static str, Option<DateTime<Utc>>) {
+    match filter {
+        DateFilter::DueBefore(_) | DateFilter::DueAfter(_) | DateFilter::DueBetween(..) => {
+            ("due", task.due)
+        }
+        DateFilter::ScheduledBefore(_) | DateFilter::ScheduledAfter(_) => {
+            ("scheduled", task.scheduled)
+        }
+        DateFilter::ModifiedBefore(_) | DateFilter::ModifiedAfter(_) => {
+            ("modified", Some(task.modified.unwrap_or(task.entry)))
+        }
+        DateFilter::EntryBefore(_) | DateFilter::EntryAfter(_) => ("entry", Some(task.entry)),
+        DateFilter::EndBefore(_) | DateFilter::EndAfter(_) | DateFilter::EndBetween(..) => {
+            ("end", task.end)
+        }
+        // Callers trace each part of a combined filter on its own
+        DateFilter::All(_) => ("date", None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{PriorityFilter, ProjectFilter, TagFilter, TaskPredicate};
+    use crate::task::TaskStatus;
+
+    #[test]
+    fn test_trace_lists_every_clause() {
+        let mut task = Task::new("Write report".to_string());
+        task.project = Some("Home".to_string());
+        task.tags.insert("next".to_string());
+
+        let query = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            project_filter: Some(ProjectFilter::Hierarchy("Work".to_string())),
+            tag_filter: Some(TagFilter::has_tag("next".to_string())),
+            priority_filter: Some(PriorityFilter::Unset),
+            custom_filters: vec![TaskPredicate::new(|t| t.description.len() > 20)],
+            ..Default::default()
+        };
+        let trace = query.evaluate_explain(&task);
+        assert!(!trace.matched());
+        assert_eq!(trace.matched(), query.matches(&task));
+
+        let clauses: Vec<(&str, bool)> = trace
+            .clauses
+            .iter()
+            .map(|c| (c.clause.as_str(), c.passed))
+            .collect();
+        assert_eq!(
+            clauses,
+            [
+                ("status", true),
+                ("project", false),
+                ("tags", true),
+                ("priority", true),
+                ("custom[0]", false)
+            ]
+        );
+        let failed: Vec<&str> = trace.failures().map(|c| c.actual.as_str()).collect();
+        assert_eq!(failed, ["Home", "false"]);
+        assert!(trace
+            .to_string()
+            .contains("FAIL project: Hierarchy(\"Work\") (task: Home)"));
+
+        task.project = Some("Work.Reports".to_string());
+        task.description = "Write the quarterly report".to_string();
+        assert!(query.evaluate_explain(&task).matched());
+        assert!(TaskQuery::default()
+            .evaluate_explain(&task)
+            .clauses
+            .is_empty());
+    }
+}
@@ -0,0 +1,819 @@
+//! Taskwarrior filter strings
+//!
+//! [`TaskQuery::to_filter_string`] produces the filter the `task` CLI would
+//! need to select the same tasks, e.g.
+//! `status:pending project:Home +next due.before:2025-10-01T00:00:00Z`.
+//! Parts with no CLI equivalent are left out: custom predicates, the
+//! proximity (the CLI cannot compare distances), `offset`, sorting (a
+//! report setting in Taskwarrior) and the filter mode.
+//!
+//! [`TaskQuery::parse_filter`] goes the other way, for filters written by
+//! hand such as `report.<name>.filter` settings.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Local, Utc};
+
+use crate::clock;
+use crate::date::relative::{parse_duration, parse_iso_duration};
+use crate::date::{DateParser, DateParsing};
+use crate::error::QueryError;
+use crate::query::{
+    DateFilter, OwnerFilter, PriorityFilter, ProjectFilter, TagFilter, TaskPredicate, TaskQuery,
+};
+use crate::task::uda::encode_uda;
+use crate::task::{Task, TaskStatus};
+
+impl TaskQuery {
+    /// Taskwarrior CLI filter equivalent to this query
+    pub fn to_filter_string(&self) -> String {
+        let mut terms = Vec::new();
+
+        if let Some(status) = &self.status {
+            terms.push(format!("status:{}", status_name(status)));
+        }
+        if let Some(filter) = &self.project_filter {
+            terms.push(project_terms(filter));
+        }
+        if let Some(filter) = &self.tag_filter {
+            terms.extend(tag_terms(filter));
+        }
+        if let Some(filter) = &self.date_filter {
+            terms.extend(date_terms(filter));
+        }
+        if let Some(filter) = &self.priority_filter {
+            terms.push(match filter {
+                PriorityFilter::Is(code) => format!("priority:{}", quote(code)),
+                PriorityFilter::Unset => "priority:".to_string(),
+            });
+        }
+        if let Some(filter) = &self.owner_filter {
+            terms.push(match filter {
+                OwnerFilter::Is(name) => format!("owner:{}", quote(name)),
+                OwnerFilter::IsNot(name) => format!("owner.not:{}", quote(name)),
+                OwnerFilter::Unowned => "owner:".to_string(),
+            });
+        }
+        if let Some(limit) = self.limit {
+            terms.push(format!("limit:{limit}"));
+        }
+
+        terms.join(" ")
+    }
+
+    /// Parse a Taskwarrior CLI filter such as
+    /// `status:pending ( project:Work or +urgent ) due.before:eow -WAITING`.
+    ///
+    /// Terms are joined by `and` (implicit) and `or`, which binds looser,
+    /// and grouped with parentheses. Supported terms:
+    ///
+    /// - `status:`, `project:` (`.is`, `.not`), `priority:` (`.not`),
+    ///   `owner:` (`.not`) and `limit:N`; an empty value means unset
+    /// - `+tag`, `-tag` and the `tags.` terms of [`TagFilter`]
+    /// - virtual tags: `ACTIVE`, `ANNOTATED`, `BLOCKED` (has dependencies),
+    ///   `UNBLOCKED`, `COMPLETED`, `DELETED`, `DUE` (within a week),
+    ///   `TODAY`, `OVERDUE`, `PENDING`, `PRIORITY`, `PROJECT`, `READY`,
+    ///   `SCHEDULED`, `TAGGED` and `WAITING`
+    /// - `due`, `scheduled`, `wait`, `entry`, `start`, `end` and `modified`
+    ///   with `.before`, `.after`, `.by`, `.not` or no modifier (same day);
+    ///   an empty value means unset, `.any:` set. Dates are anything
+    ///   [`DateParser`] understands, e.g. `2025-10-01`, `eom` or `monday`
+    /// - `description:` (`.is`, `.startswith`, `.endswith`, `.hasnt`),
+    ///   matching by substring without a modifier
+    /// - `pushed:N`, the number of times the due date was moved later,
+    ///   with `.gt`, `.gte`, `.lt`, `.lte`, `.not` or no modifier (equal)
+    /// - `name:value` for a UDA
+    /// - any other word, matched against the description
+    ///
+    /// Terms that fit a field of the query are stored there, so the result
+    /// still renders with [`to_filter_string`](Self::to_filter_string) and
+    /// narrows storage lookups; the rest become custom predicates.
+    /// `limit:page` is accepted and ignored.
+    pub fn parse_filter(filter: &str) -> Result<Self, QueryError> {
+        let mut parser = FilterParser {
+            tokens: tokenize(filter)?,
+            pos: 0,
+            source: filter,
+        };
+        if parser.tokens.is_empty() {
+            return Ok(TaskQuery::default());
+        }
+        let query = parser.expression()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.invalid());
+        }
+        Ok(query)
+    }
+}
+
+impl FromStr for TaskQuery {
+    type Err = QueryError;
+
+    /// See [`TaskQuery::parse_filter`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TaskQuery::parse_filter(s)
+    }
+}
+
+// Split a filter into words, keeping quoted values together and making
+// parentheses tokens of their own
+fn tokenize(filter: &str) -> Result<Vec<String>, QueryError> {
+    fn flush(tokens: &mut Vec<String>, current: &mut String, started: &mut bool) {
+        if std::mem::take(started) {
+            tokens.push(std::mem::take(current));
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut started = false;
+    let mut chars = filter.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                started = true;
+                loop {
+                    match chars.next() {
+                        Some('\\') => current.extend(chars.next()),
+                        Some(q) if q == c => break,
+                        Some(other) => current.push(other),
+                        None => {
+                            return Err(QueryError::InvalidFilter {
+                                expression: filter.to_string(),
+                            })
+                        }
+                    }
+                }
+            }
+            '(' | ')' => {
+                flush(&mut tokens, &mut current, &mut started);
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => flush(&mut tokens, &mut current, &mut started),
+            c => {
+                started = true;
+                current.push(c);
+            }
+        }
+    }
+    flush(&mut tokens, &mut current, &mut started);
+    Ok(tokens)
+}
+
+struct FilterParser<'a> {
+    tokens: Vec<String>,
+    pos: usize,
+    source: &'a str,
+}
+
+impl FilterParser<'_> {
+    fn invalid(&self) -> QueryError {
+        QueryError::InvalidFilter {
+            expression: self.source.to_string(),
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.tokens.get(self.pos).is_some_and(|t| t == token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    // Conjunctions separated by `or`
+    fn expression(&mut self) -> Result<TaskQuery, QueryError> {
+        let mut alternatives = vec![self.conjunction()?];
+        while self.eat("or") {
+            alternatives.push(self.conjunction()?);
+        }
+        Ok(either(alternatives))
+    }
+
+    // Terms and groups up to the next `or` or closing parenthesis
+    fn conjunction(&mut self) -> Result<TaskQuery, QueryError> {
+        let mut query = TaskQuery::default();
+        let mut terms = 0;
+        while let Some(token) = self.tokens.get(self.pos).cloned() {
+            self.pos += 1;
+            let term = match token.as_str() {
+                "or" | ")" => {
+                    self.pos -= 1;
+                    break;
+                }
+                "and" => continue,
+                "(" => {
+                    let group = self.expression()?;
+                    if !self.eat(")") {
+                        return Err(self.invalid());
+                    }
+                    group
+                }
+                _ => parse_term(&token)?,
+            };
+            both(&mut query, term);
+            terms += 1;
+        }
+        if terms == 0 {
+            return Err(self.invalid());
+        }
+        Ok(query)
+    }
+}
+
+fn predicate(test: impl Fn(&Task) -> bool + Send + Sync + 'static) -> TaskQuery {
+    TaskQuery {
+        custom_filters: vec![TaskPredicate::new(test)],
+        ..Default::default()
+    }
+}
+
+// Combine two conjoined queries, moving each filter of `term` into `query`
+// when that slot is free and into a predicate otherwise
+fn both(query: &mut TaskQuery, term: TaskQuery) {
+    fn merge<T>(slot: &mut Option<T>, value: Option<T>, extra: &mut Vec<TaskPredicate>, alone: fn(T) -> TaskQuery) {
+        match (slot.is_some(), value) {
+            (_, None) => {}
+            (false, value) => *slot = value,
+            (true, Some(value)) => {
+                let alone = alone(value);
+                extra.push(TaskPredicate::new(move |t| alone.matches(t)));
+            }
+        }
+    }
+
+    let mut extra = term.custom_filters;
+    merge(&mut query.status, term.status, &mut extra, |status| TaskQuery {
+        status: Some(status),
+        ..Default::default()
+    });
+    merge(&mut query.project_filter, term.project_filter, &mut extra, |filter| TaskQuery {
+        project_filter: Some(filter),
+        ..Default::default()
+    });
+    match (&mut query.tag_filter, term.tag_filter) {
+        (Some(tags), Some(more)) if tags.any_of.is_empty() || more.any_of.is_empty() => {
+            tags.any_of.extend(more.any_of);
+            tags.all_of.extend(more.all_of);
+            tags.none_of.extend(more.none_of);
+            tags.untagged |= more.untagged;
+            tags.min_count = tags.min_count.max(more.min_count);
+        }
+        (slot, more) => merge(slot, more, &mut extra, |filter| TaskQuery {
+            tag_filter: Some(filter),
+            ..Default::default()
+        }),
+    }
+    query.date_filter = match (query.date_filter.take(), term.date_filter) {
+        (Some(filter), Some(more)) => Some(filter.and(more)),
+        (filter, more) => filter.or(more),
+    };
+    merge(&mut query.priority_filter, term.priority_filter, &mut extra, |filter| TaskQuery {
+        priority_filter: Some(filter),
+        ..Default::default()
+    });
+    merge(&mut query.owner_filter, term.owner_filter, &mut extra, |filter| TaskQuery {
+        owner_filter: Some(filter),
+        ..Default::default()
+    });
+    query.limit = term.limit.or(query.limit);
+    query.custom_filters.extend(extra);
+}
+
+// Combine alternatives. Lists of exact projects or of single tags keep a
+// structured form; anything else becomes one predicate.
+fn either(mut alternatives: Vec<TaskQuery>) -> TaskQuery {
+    if alternatives.len() == 1 {
+        return alternatives.remove(0);
+    }
+
+    let projects: Option<Vec<String>> = alternatives
+        .iter()
+        .map(|q| match &q.project_filter {
+            Some(ProjectFilter::Exact(p) | ProjectFilter::Equals(p))
+                if *q == TaskQuery { project_filter: q.project_filter.clone(), ..Default::default() } =>
+            {
+                Some(p.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    if let Some(projects) = projects {
+        return TaskQuery {
+            project_filter: Some(ProjectFilter::Multiple(projects)),
+            ..Default::default()
+        };
+    }
+
+    let tags: Option<HashSet<String>> = alternatives
+        .iter()
+        .map(|q| {
+            let tag = q.tag_filter.as_ref()?.all_of.iter().next()?;
+            let alone = TaskQuery {
+                tag_filter: Some(TagFilter::has_tag(tag.clone())),
+                ..Default::default()
+            };
+            (*q == alone).then(|| tag.clone())
+        })
+        .collect();
+    if let Some(tags) = tags {
+        return TaskQuery {
+            tag_filter: Some(TagFilter::include_tags(tags)),
+            ..Default::default()
+        };
+    }
+
+    predicate(move |t| alternatives.iter().any(|q| q.matches(t)))
+}
+
+fn parse_term(token: &str) -> Result<TaskQuery, QueryError> {
+    let invalid = || QueryError::InvalidFilter {
+        expression: token.to_string(),
+    };
+    let mut query = TaskQuery::default();
+
+    if let Some(name) = token
+        .strip_prefix('+')
+        .or_else(|| token.strip_prefix('-'))
+        .filter(|n| !n.is_empty() && !n.contains(':'))
+    {
+        let wanted = token.starts_with('+');
+        return Ok(match virtual_tag(name) {
+            Some(test) => predicate(move |t| test(t) == wanted),
+            None => TaskQuery {
+                tag_filter: Some(token.parse()?),
+                ..Default::default()
+            },
+        });
+    }
+
+    let Some((attribute, value)) = token.split_once(':') else {
+        let word = token.to_string();
+        return Ok(predicate(move |t| t.description.contains(&word)));
+    };
+    let (name, modifier) = attribute.split_once('.').unwrap_or((attribute, ""));
+    let value = value.to_string();
+    match (name, modifier) {
+        ("status", "") => query.status = Some(parse_status(&value).ok_or_else(invalid)?),
+        ("project", "" | "startswith" | "left") if value.is_empty() => {
+            query.project_filter = Some(ProjectFilter::None)
+        }
+        ("project", "" | "startswith" | "left") => {
+            query.project_filter = Some(ProjectFilter::Hierarchy(value))
+        }
+        ("project", "is" | "equals") if value.is_empty() => {
+            query.project_filter = Some(ProjectFilter::None)
+        }
+        ("project", "is" | "equals") => query.project_filter = Some(ProjectFilter::Exact(value)),
+        ("project", "not" | "isnt") => {
+            let filter = ProjectFilter::Hierarchy(value);
+            return Ok(predicate(move |t| !filter.matches(t.project.as_deref())));
+        }
+        ("tags" | "tag", "" | "has" | "contains") if !value.is_empty() => {
+            query.tag_filter = Some(TagFilter::has_tag(value))
+        }
+        ("tags" | "tag", "hasnt" | "nothas") if !value.is_empty() => {
+            query.tag_filter = Some(TagFilter::exclude_tags([value]))
+        }
+        ("tags", _) => query.tag_filter = Some(token.parse()?),
+        ("priority", "") if value.is_empty() => query.priority_filter = Some(PriorityFilter::Unset),
+        ("priority", "") => query.priority_filter = Some(PriorityFilter::Is(value)),
+        ("priority", "not" | "isnt") => {
+            return Ok(predicate(move |t| t.priority_code() != Some(value.as_str())))
+        }
+        ("owner", "") if value.is_empty() => query.owner_filter = Some(OwnerFilter::Unowned),
+        ("owner", "") => query.owner_filter = Some(OwnerFilter::Is(value)),
+        ("owner", "not" | "isnt") => query.owner_filter = Some(OwnerFilter::IsNot(value)),
+        ("limit", "") if value == "page" => {}
+        ("limit", "") => query.limit = Some(value.parse().map_err(|_| invalid())?),
+        ("description", modifier) => {
+            let test: fn(&str, &str) -> bool = match modifier {
+                "" | "contains" | "has" => |d, v| d.contains(v),
+                "is" | "equals" => |d, v| d == v,
+                "isnt" => |d, v| d != v,
+                "startswith" | "left" => |d, v| d.starts_with(v),
+                "endswith" | "right" => |d, v| d.ends_with(v),
+                "hasnt" => |d, v| !d.contains(v),
+                _ => return Err(invalid()),
+            };
+            return Ok(predicate(move |t| test(&t.description, &value)));
+        }
+        ("due" | "scheduled" | "wait" | "entry" | "start" | "end" | "modified", _) => {
+            return date_term(name, modifier, &value).ok_or_else(invalid)?;
+        }
+        ("estimate" | "effort", _) => {
+            return duration_term(name, modifier, &value).ok_or_else(invalid)?;
+        }
+        ("pushed", _) => {
+            let count: u32 = value.trim().parse().map_err(|_| invalid())?;
+            let test: fn(u32, u32) -> bool = match modifier {
+                "" | "is" | "equals" => |v, n| v == n,
+                "not" | "isnt" => |v, n| v != n,
+                "gt" | "above" | "over" => |v, n| v > n,
+                "gte" => |v, n| v >= n,
+                "lt" | "below" | "under" => |v, n| v < n,
+                "lte" => |v, n| v <= n,
+                _ => return Err(invalid()),
+            };
+            return Ok(predicate(move |t| test(t.push_count(), count)));
+        }
+        (uda, "") if uda.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+            let uda = uda.to_string();
+            return Ok(predicate(move |t| {
+                let current = t.udas.get(&uda).map(encode_uda);
+                current.as_deref() == Some(value.as_str()) || (value.is_empty() && current.is_none())
+            }));
+        }
+        _ => return Err(invalid()),
+    }
+    Ok(query)
+}
+
+fn parse_status(value: &str) -> Option<TaskStatus> {
+    match value.to_lowercase().as_str() {
+        "pending" => Some(TaskStatus::Pending),
+        "completed" => Some(TaskStatus::Completed),
+        "deleted" => Some(TaskStatus::Deleted),
+        "waiting" => Some(TaskStatus::Waiting),
+        "recurring" => Some(TaskStatus::Recurring),
+        _ => None,
+    }
+}
+
+// Date comparison on one attribute; None for an unknown modifier
+fn date_term(attribute: &str, modifier: &str, value: &str) -> Option<Result<TaskQuery, QueryError>> {
+    let field: fn(&Task) -> Option<DateTime<Utc>> = match attribute {
+        "due" => |t| t.due,
+        "scheduled" => |t| t.scheduled,
+        "wait" => |t| t.wait,
+        "entry" => |t| Some(t.entry),
+        "start" => |t| t.start,
+        "end" => |t| t.end,
+        _ => |t| Some(t.modified.unwrap_or(t.entry)),
+    };
+
+    if value.is_empty() {
+        return match modifier {
+            "" | "none" | "is" => Some(Ok(predicate(move |t| field(t).is_none()))),
+            "any" => Some(Ok(predicate(move |t| field(t).is_some()))),
+            _ => None,
+        };
+    }
+    let date = match parse_date(value) {
+        Ok(date) => date,
+        Err(e) => return Some(Err(e)),
+    };
+    let day = date.date_naive().and_hms_opt(0, 0, 0)?.and_utc();
+    let next_day = day + Duration::days(1);
+
+    let structured = match (attribute, modifier) {
+        ("due", "before" | "below" | "under") => Some(DateFilter::DueBefore(date)),
+        ("due", "after" | "above" | "over") => Some(DateFilter::DueAfter(date)),
+        ("due", "" | "is" | "equals") => Some(DateFilter::DueBetween(day, next_day)),
+        ("scheduled", "before" | "below" | "under") => Some(DateFilter::ScheduledBefore(date)),
+        ("scheduled", "after" | "above" | "over") => Some(DateFilter::ScheduledAfter(date)),
+        ("modified", "before" | "below" | "under") => Some(DateFilter::ModifiedBefore(date)),
+        ("modified", "after" | "above" | "over") => Some(DateFilter::ModifiedAfter(date)),
+        ("entry", "before" | "below" | "under") => Some(DateFilter::EntryBefore(date)),
+        ("entry", "after" | "above" | "over") => Some(DateFilter::EntryAfter(date)),
+        ("end", "before" | "below" | "under") => Some(DateFilter::EndBefore(date)),
+        ("end", "after" | "above" | "over") => Some(DateFilter::EndAfter(date)),
+        ("end", "" | "is" | "equals") => Some(DateFilter::EndBetween(day, next_day)),
+        _ => None,
+    };
+    if let Some(filter) = structured {
+        return Some(Ok(TaskQuery {
+            date_filter: Some(filter),
+            ..Default::default()
+        }));
+    }
+
+    let test: fn(DateTime<Utc>, DateTime<Utc>) -> bool = match modifier {
+        "before" | "below" | "under" => |v, date| v < date,
+        "after" | "above" | "over" => |v, date| v > date,
+        "by" => |v, date| v <= date,
+        "" | "is" | "equals" => |v, date| v.date_naive() == date.date_naive(),
+        "not" | "isnt" => |v, date| v.date_naive() != date.date_naive(),
+        _ => return None,
+    };
+    Some(Ok(predicate(move |t| field(t).is_some_and(|v| test(v, date)))))
+}
+
+// Duration comparison on the estimate or effort UDA; None for an unknown
+// modifier
+fn duration_term(attribute: &str, modifier: &str, value: &str) -> Option<Result<TaskQuery, QueryError>> {
+    let attribute = attribute.to_string();
+    if value.is_empty() {
+        return match modifier {
+            "" | "none" | "is" => Some(Ok(predicate(move |t| t.duration_uda(&attribute).is_none()))),
+            "any" => Some(Ok(predicate(move |t| t.duration_uda(&attribute).is_some()))),
+            _ => None,
+        };
+    }
+    let duration = match parse_iso_duration(value).or_else(|_| parse_duration(value)) {
+        Ok(duration) => duration,
+        Err(e) => {
+            return Some(Err(QueryError::InvalidFilter {
+                expression: e.to_string(),
+            }))
+        }
+    };
+    let test: fn(Duration, Duration) -> bool = match modifier {
+        "over" | "above" | "after" => |v, d| v > d,
+        "under" | "below" | "before" => |v, d| v < d,
+        "" | "is" | "equals" => |v, d| v == d,
+        "not" | "isnt" => |v, d| v != d,
+        _ => return None,
+    };
+    Some(Ok(predicate(move |t| {
+        t.duration_uda(&attribute).is_some_and(|v| test(v, duration))
+    })))
+}
+
+fn parse_date(value: &str) -> Result<DateTime<Utc>, QueryError> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    DateParser::new()
+        .parse_date(value)
+        .map_err(|e| QueryError::DateParsing { message: e.to_string() })
+}
+
+// Names `virtual_tag` knows, in Taskwarrior's order
+const VIRTUAL_TAGS: [&str; 16] = [
+    "ACTIVE", "ANNOTATED", "BLOCKED", "UNBLOCKED", "COMPLETED", "DELETED", "PENDING", "WAITING",
+    "DUE", "TODAY", "OVERDUE", "PRIORITY", "PROJECT", "READY", "SCHEDULED", "TAGGED",
+];
+
+/// Virtual tags `task` carries, e.g. `PENDING` and `OVERDUE`, as the
+/// `+TAG` filter terms decide them
+pub fn virtual_tags(task: &Task) -> Vec<&'static str> {
+    VIRTUAL_TAGS
+        .into_iter()
+        .filter(|name| virtual_tag(name).is_some_and(|test| test(task)))
+        .collect()
+}
+
+// Taskwarrior virtual tags that can be decided from the task alone
+fn virtual_tag(name: &str) -> Option<fn(&Task) -> bool> {
+    let test: fn(&Task) -> bool = match name {
+        "ACTIVE" => |t| t.is_active(),
+        "ANNOTATED" => |t| !t.annotations.is_empty(),
+        "BLOCKED" => |t| !t.depends.is_empty(),
+        "UNBLOCKED" => |t| t.depends.is_empty(),
+        "COMPLETED" => |t| t.status == TaskStatus::Completed,
+        "DELETED" => |t| t.status == TaskStatus::Deleted,
+        "PENDING" => |t| t.status == TaskStatus::Pending,
+        "WAITING" => |t| t.status == TaskStatus::Waiting || t.is_waiting_at(clock::now()),
+        "DUE" => |t| t.due.is_some_and(|due| due <= clock::now() + Duration::days(7)),
+        "TODAY" => |t| {
+            t.due
+                .is_some_and(|due| due.with_timezone(&Local).date_naive() == Local::now().date_naive())
+        },
+        "OVERDUE" => |t| t.is_overdue(),
+        "PRIORITY" => |t| t.priority.is_some(),
+        "PROJECT" => |t| t.project.is_some(),
+        "READY" => |t| {
+            let now = clock::now();
+            t.status == TaskStatus::Pending
+                && t.depends.is_empty()
+                && t.scheduled.is_none_or(|s| s <= now)
+                && !t.is_waiting_at(now)
+        },
+        "SCHEDULED" => |t| t.scheduled.is_some(),
+        "TAGGED" => |t| !t.tags.is_empty(),
+        _ => return None,
+    };
+    Some(test)
+}
+
+fn status_name(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "pending",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Deleted => "deleted",
+        TaskStatus::Waiting => "waiting",
+        TaskStatus::Recurring => "recurring",
+    }
+}
+
+// `project:` matches by prefix in Taskwarrior, so exact matches use `project.is:`
+fn project_terms(filter: &ProjectFilter) -> String {
+    match filter {
+        ProjectFilter::Equals(project) | ProjectFilter::Exact(project) => {
+            format!("project.is:{}", quote(project))
+        }
+        ProjectFilter::Hierarchy(project) => format!("project:{}", quote(project)),
+        ProjectFilter::Multiple(projects) => {
+            let alternatives: Vec<String> = projects
+                .iter()
+                .map(|p| format!("project.is:{}", quote(p)))
+                .collect();
+            any_of(alternatives)
+        }
+        ProjectFilter::None => "project:".to_string(),
+    }
+}
+
+// A minimum of more than one tag has no CLI equivalent and is left out
+fn tag_terms(filter: &TagFilter) -> Vec<String> {
+    let sorted = |tags: &std::collections::HashSet<String>, prefix: char| {
+        let mut terms: Vec<String> = tags.iter().map(|t| format!("{prefix}{t}")).collect();
+        terms.sort();
+        terms
+    };
+
+    let mut terms = Vec::new();
+    if !filter.any_of.is_empty() {
+        terms.push(any_of(sorted(&filter.any_of, '+')));
+    }
+    terms.extend(sorted(&filter.all_of, '+'));
+    terms.extend(sorted(&filter.none_of, '-'));
+    if filter.untagged {
+        terms.push("tags.none:".to_string());
+    }
+    if filter.min_count == Some(1) {
+        terms.push("tags.any:".to_string());
+    }
+    terms
+}
+
+fn date_terms(filter: &DateFilter) -> Vec<String> {
+    let term = |attribute: &str, modifier: &str, date: &DateTime<Utc>| {
+        format!("{attribute}.{modifier}:{}", date.format("%Y-%m-%dT%H:%M:%SZ"))
+    };
+    match filter {
+        DateFilter::DueBefore(d) => vec![term("due", "before", d)],
+        DateFilter::DueAfter(d) => vec![term("due", "after", d)],
+        DateFilter::DueBetween(start, end) => {
+            vec![term("due", "after", start), term("due", "before", end)]
+        }
+        DateFilter::ScheduledBefore(d) => vec![term("scheduled", "before", d)],
+        DateFilter::ScheduledAfter(d) => vec![term("scheduled", "after", d)],
+        DateFilter::ModifiedBefore(d) => vec![term("modified", "before", d)],
+        DateFilter::ModifiedAfter(d) => vec![term("modified", "after", d)],
+        DateFilter::EntryBefore(d) => vec![term("entry", "before", d)],
+        DateFilter::EntryAfter(d) => vec![term("entry", "after", d)],
+        DateFilter::EndBefore(d) => vec![term("end", "before", d)],
+        DateFilter::EndAfter(d) => vec![term("end", "after", d)],
+        DateFilter::EndBetween(start, end) => {
+            vec![term("end", "after", start), term("end", "before", end)]
+        }
+        DateFilter::All(parts) => parts.iter().flat_map(date_terms).collect(),
+    }
+}
+
+fn any_of(mut alternatives: Vec<String>) -> String {
+    if alternatives.len() == 1 {
+        alternatives.remove(0)
+    } else {
+        format!("( {} )", alternatives.join(" or "))
+    }
+}
+
+// Quote values the CLI would otherwise split or misread
+fn quote(value: &str) -> String {
+    if value.is_empty() || value.chars().any(|c| c.is_whitespace() || "'\"()".contains(c)) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{TaskQueryBuilder, TaskQueryBuilderImpl};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_builder_query_to_filter_string() {
+        let due = Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap();
+        let query = TaskQueryBuilderImpl::new()
+            .status(TaskStatus::Pending)
+            .project("Home".to_string())
+            .tag("next".to_string())
+            .due_before(due)
+            .priority("H".to_string())
+            .limit(5)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            query.to_filter_string(),
+            "status:pending project.is:Home +next due.before:2025-10-01T00:00:00Z priority:H limit:5"
+        );
+        assert_eq!(TaskQuery::default().to_filter_string(), "");
+    }
+
+    #[test]
+    fn test_alternatives_and_quoting() {
+        let mut tags = TagFilter::include_tags(["work", "call"]);
+        tags.none_of.insert("someday".to_string());
+        let query = TaskQuery {
+            project_filter: Some(ProjectFilter::Multiple(vec![
+                "Home".to_string(),
+                "Side Project".to_string(),
+            ])),
+            tag_filter: Some(tags),
+            owner_filter: Some(OwnerFilter::Unowned),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            query.to_filter_string(),
+            "( project.is:Home or project.is:\"Side Project\" ) ( +call or +work ) -someday owner:"
+        );
+    }
+
+    #[test]
+    fn test_tag_filter_round_trip() {
+        let filter: TagFilter = "+next ( +work or +home ) -someday tags.any:".parse().unwrap();
+        let query = TaskQuery {
+            tag_filter: Some(filter.clone()),
+            ..Default::default()
+        };
+        let rendered = query.to_filter_string();
+        assert_eq!(rendered, "( +home or +work ) +next -someday tags.any:");
+        assert_eq!(rendered.parse::<TagFilter>().unwrap(), filter);
+    }
+
+    #[test]
+    fn test_parse_filter_round_trip() {
+        let due = Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap();
+        let query = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            project_filter: Some(ProjectFilter::Multiple(vec![
+                "Home".to_string(),
+                "Side Project".to_string(),
+            ])),
+            tag_filter: Some(TagFilter::include_tags(["work", "call"])),
+            date_filter: Some(DateFilter::DueBefore(due)),
+            priority_filter: Some(PriorityFilter::Is("H".to_string())),
+            owner_filter: Some(OwnerFilter::IsNot("ann".to_string())),
+            limit: Some(5),
+            ..Default::default()
+        };
+        let parsed = TaskQuery::parse_filter(&query.to_filter_string()).unwrap();
+        assert_eq!(parsed, query);
+        assert_eq!(TaskQuery::parse_filter("  ").unwrap(), TaskQuery::default());
+    }
+
+    #[test]
+    fn test_parse_filter_terms() {
+        let now = Utc::now();
+        let mut overdue = Task::new("Renew passport".to_string());
+        overdue.due = Some(now - Duration::days(2));
+        overdue.project = Some("Home".to_string());
+        overdue.set_estimate(Some(Duration::minutes(30)));
+        let mut later = Task::new("Plan trip".to_string());
+        later.due = Some(now + Duration::days(30));
+        later.tags.insert("travel".to_string());
+        later.set_estimate(Some(Duration::hours(3)));
+        later.udas.insert(
+            crate::task::provenance::PUSHED_UDA.to_string(),
+            crate::task::model::UdaValue::Number(3.0),
+        );
+        let mut done = Task::new("Pay rent".to_string());
+        done.status = TaskStatus::Completed;
+        done.end = Some(now);
+
+        let select = |filter: &str| {
+            let query: TaskQuery = filter.parse().unwrap();
+            [&overdue, &later, &done]
+                .into_iter()
+                .filter(|t| query.matches(t))
+                .map(|t| t.description.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(select("+OVERDUE"), vec!["Renew passport"]);
+        assert_eq!(select("-OVERDUE status:pending"), vec!["Plan trip"]);
+        assert_eq!(select("+travel or project:Home"), vec!["Renew passport", "Plan trip"]);
+        assert_eq!(select("( +travel or project:Home ) due.after:now"), vec!["Plan trip"]);
+        assert_eq!(select("due:"), vec!["Pay rent"]);
+        assert_eq!(select("end.after:yesterday"), vec!["Pay rent"]);
+        assert_eq!(select("trip"), vec!["Plan trip"]);
+        assert_eq!(select("description.startswith:\"Pay r\""), vec!["Pay rent"]);
+        assert_eq!(select("project.not:Home status:pending"), vec!["Plan trip"]);
+        assert_eq!(select("estimate.over:2h"), vec!["Plan trip"]);
+        assert_eq!(select("estimate.under:PT1H"), vec!["Renew passport"]);
+        assert_eq!(select("estimate:"), vec!["Pay rent"]);
+        assert_eq!(select("estimate.any: effort:"), vec!["Renew passport", "Plan trip"]);
+        assert_eq!(select("pushed.gte:3"), vec!["Plan trip"]);
+        assert_eq!(select("pushed:0"), vec!["Renew passport", "Pay rent"]);
+
+        // Date terms combine and keep their structured form
+        let query = TaskQuery::parse_filter("due.after:yesterday due.before:tomorrow").unwrap();
+        assert!(matches!(
+            query.date_filter.as_ref().map(DateFilter::parts).as_deref(),
+            Some([DateFilter::DueAfter(_), DateFilter::DueBefore(_)])
+        ));
+        assert!(query.custom_filters.is_empty());
+        assert_eq!(TaskQuery::parse_filter(&query.to_filter_string()).unwrap(), query);
+
+        for bad in ["status:open", "( +a", "+a )", "or +a", "+a:", "due.after:someday", "pushed:lots", "\"open", "estimate.over:P99999999999999D"] {
+            assert!(TaskQuery::parse_filter(bad).is_err(), "{bad}");
+        }
+    }
+}
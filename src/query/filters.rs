@@ -1,9 +1,19 @@
 //! Query filter types and helpers (clean module)
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::config::PriorityScheme;
+use crate::error::QueryError;
+use crate::query::collation::Collation;
+use crate::task::model::UdaValue;
+use crate::task::{Task, TaskStatus};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProjectFilter {
     Exact(String),
     Equals(String),
@@ -12,38 +22,187 @@ pub enum ProjectFilter {
     None,
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+impl ProjectFilter {
+    /// Check a task's project against the filter. `Hierarchy` matches by
+    /// prefix, like Taskwarrior's `project:` term.
+    pub fn matches(&self, project: Option<&str>) -> bool {
+        match self {
+            ProjectFilter::Equals(p) | ProjectFilter::Exact(p) => project == Some(p.as_str()),
+            ProjectFilter::Hierarchy(p) => project.is_some_and(|t| t.starts_with(p.as_str())),
+            ProjectFilter::Multiple(projects) => project.is_some_and(|t| projects.iter().any(|p| p == t)),
+            ProjectFilter::None => project.is_none(),
+        }
+    }
+}
+
+/// Filter on task tags
+///
+/// Every part that is set must match. Parses from Taskwarrior tag terms,
+/// see [`TagFilter::from_str`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TagFilter {
-    pub include: HashSet<String>,
-    pub exclude: HashSet<String>,
+    /// The task has at least one of these tags
+    #[serde(default, alias = "include", serialize_with = "sorted")]
+    pub any_of: HashSet<String>,
+    /// The task has every one of these tags
+    #[serde(default, serialize_with = "sorted")]
+    pub all_of: HashSet<String>,
+    /// The task has none of these tags
+    #[serde(default, alias = "exclude", serialize_with = "sorted")]
+    pub none_of: HashSet<String>,
+    /// The task has at least this many tags
+    #[serde(default)]
+    pub min_count: Option<usize>,
+    /// The task has no tags at all
+    #[serde(default)]
+    pub untagged: bool,
+}
+
+// Tag sets serialize sorted, so equal filters give equal output
+fn sorted<S: serde::Serializer>(tags: &HashSet<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut tags: Vec<&String> = tags.iter().collect();
+    tags.sort();
+    serializer.collect_seq(tags)
 }
 
 impl TagFilter {
     pub fn has_tag(tag: String) -> Self {
         let mut filter = Self::default();
-        filter.include.insert(tag);
+        filter.all_of.insert(tag);
         filter
     }
+    /// Tasks with at least one of `tags`
     pub fn include_tags<T: Into<String>>(tags: impl IntoIterator<Item = T>) -> Self {
         let mut filter = Self::default();
-        for t in tags { filter.include.insert(t.into()); }
+        for t in tags { filter.any_of.insert(t.into()); }
         filter
     }
+    /// Tasks with every one of `tags`
+    pub fn require_tags<T: Into<String>>(tags: impl IntoIterator<Item = T>) -> Self {
+        let mut filter = Self::default();
+        for t in tags { filter.all_of.insert(t.into()); }
+        filter
+    }
+    /// Tasks with none of `tags`
     pub fn exclude_tags<T: Into<String>>(tags: impl IntoIterator<Item = T>) -> Self {
         let mut filter = Self::default();
-        for t in tags { filter.exclude.insert(t.into()); }
+        for t in tags { filter.none_of.insert(t.into()); }
         filter
     }
+    /// Tasks with at least `count` tags
+    pub fn at_least(count: usize) -> Self {
+        Self { min_count: Some(count), ..Self::default() }
+    }
+    /// Tasks without tags
+    pub fn untagged() -> Self {
+        Self { untagged: true, ..Self::default() }
+    }
     pub fn matches(&self, task_tags: &HashSet<String>) -> bool {
-        if !self.include.is_empty() && !self.include.iter().any(|t| task_tags.contains(t)) {
+        if self.untagged && !task_tags.is_empty() { return false; }
+        if !self.any_of.is_empty() && !self.any_of.iter().any(|t| task_tags.contains(t)) {
             return false;
         }
-        if self.exclude.iter().any(|t| task_tags.contains(t)) { return false; }
+        if !self.all_of.iter().all(|t| task_tags.contains(t)) { return false; }
+        if self.none_of.iter().any(|t| task_tags.contains(t)) { return false; }
+        if self.min_count.is_some_and(|n| task_tags.len() < n) { return false; }
         true
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl FromStr for TagFilter {
+    type Err = QueryError;
+
+    /// Parse whitespace-separated tag terms:
+    ///
+    /// - `+a +b`: has both `a` and `b`
+    /// - `+a or +b`: has `a` or `b` (one such group per filter)
+    /// - `-c`: does not have `c`
+    /// - `tags.any:` / `tags.none:`: has some tags / has none
+    /// - `tags.min:N`: has at least `N` tags
+    ///
+    /// Bare parentheses are ignored, so the output of
+    /// [`TaskQuery::to_filter_string`](crate::query::TaskQuery::to_filter_string)
+    /// parses back.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || QueryError::InvalidFilter { expression: s.to_string() };
+        let mut filter = TagFilter::default();
+        let mut tokens = s.split_whitespace().filter(|t| !matches!(*t, "(" | ")")).peekable();
+        while let Some(token) = tokens.next() {
+            if token == "tags.none:" {
+                filter.untagged = true;
+            } else if token == "tags.any:" {
+                filter.min_count = Some(filter.min_count.unwrap_or(0).max(1));
+            } else if let Some(count) = token.strip_prefix("tags.min:") {
+                filter.min_count = Some(count.parse().map_err(|_| invalid())?);
+            } else if let Some(first) = tag(token, '+') {
+                let mut group = vec![first];
+                while tokens.next_if_eq(&"or").is_some() {
+                    group.push(tokens.next().and_then(|t| tag(t, '+')).ok_or_else(invalid)?);
+                }
+                if group.len() == 1 {
+                    filter.all_of.insert(first.to_string());
+                } else if filter.any_of.is_empty() {
+                    filter.any_of.extend(group.into_iter().map(str::to_string));
+                } else {
+                    return Err(invalid());
+                }
+            } else if let Some(excluded) = tag(token, '-') {
+                filter.none_of.insert(excluded.to_string());
+            } else {
+                return Err(invalid());
+            }
+        }
+        Ok(filter)
+    }
+}
+
+// `+tag` or `-tag` term with the sign stripped
+fn tag(token: &str, prefix: char) -> Option<&str> {
+    token.strip_prefix(prefix).filter(|t| !t.is_empty() && !t.contains(':'))
+}
+
+/// Filter on the task `owner` attribute
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OwnerFilter {
+    /// Owned by the given user
+    Is(String),
+    /// Owned by someone other than the given user (unowned tasks excluded)
+    IsNot(String),
+    /// No owner recorded
+    Unowned,
+}
+
+impl OwnerFilter {
+    pub fn matches(&self, owner: Option<&str>) -> bool {
+        match self {
+            OwnerFilter::Is(name) => owner == Some(name.as_str()),
+            OwnerFilter::IsNot(name) => owner.is_some_and(|o| o != name),
+            OwnerFilter::Unowned => owner.is_none(),
+        }
+    }
+}
+
+/// Filter on the task priority code (see [`Task::priority_code`])
+///
+/// [`Task::priority_code`]: crate::task::Task::priority_code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PriorityFilter {
+    /// Priority equals the given code
+    Is(String),
+    /// No priority set
+    Unset,
+}
+
+impl PriorityFilter {
+    pub fn matches(&self, priority: Option<&str>) -> bool {
+        match self {
+            PriorityFilter::Is(code) => priority == Some(code.as_str()),
+            PriorityFilter::Unset => priority.is_none(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DateFilter {
     DueBefore(DateTime<Utc>),
     DueAfter(DateTime<Utc>),
@@ -54,18 +213,296 @@ pub enum DateFilter {
     ModifiedAfter(DateTime<Utc>),
     EntryBefore(DateTime<Utc>),
     EntryAfter(DateTime<Utc>),
+    EndBefore(DateTime<Utc>),
+    EndAfter(DateTime<Utc>),
+    EndBetween(DateTime<Utc>, DateTime<Utc>),
+    /// Every condition must hold, as when several date filters are chained
+    All(Vec<DateFilter>),
+}
+
+impl DateFilter {
+    /// Combine with another filter so that both must match
+    pub fn and(self, other: DateFilter) -> DateFilter {
+        let mut parts = self.into_parts();
+        parts.extend(other.into_parts());
+        DateFilter::All(parts)
+    }
+
+    /// The single conditions this filter is made of
+    pub fn parts(&self) -> Vec<&DateFilter> {
+        match self {
+            DateFilter::All(parts) => parts.iter().flat_map(DateFilter::parts).collect(),
+            single => vec![single],
+        }
+    }
+
+    fn into_parts(self) -> Vec<DateFilter> {
+        match self {
+            DateFilter::All(parts) => parts.into_iter().flat_map(DateFilter::into_parts).collect(),
+            single => vec![single],
+        }
+    }
+
+    /// Check a task against the filter. `Before`/`After` bounds are
+    /// exclusive; `Between` includes its start and excludes its end.
+    /// Tasks never modified count as modified at entry; a missing date
+    /// never matches.
+    pub fn matches(&self, task: &Task) -> bool {
+        let before = |value: Option<DateTime<Utc>>, date: &DateTime<Utc>| value.is_some_and(|v| v < *date);
+        let after = |value: Option<DateTime<Utc>>, date: &DateTime<Utc>| value.is_some_and(|v| v > *date);
+        let between = |value: Option<DateTime<Utc>>, start: &DateTime<Utc>, end: &DateTime<Utc>| {
+            value.is_some_and(|v| *start <= v && v < *end)
+        };
+        let modified = Some(task.modified.unwrap_or(task.entry));
+        match self {
+            DateFilter::DueBefore(d) => before(task.due, d),
+            DateFilter::DueAfter(d) => after(task.due, d),
+            DateFilter::DueBetween(start, end) => between(task.due, start, end),
+            DateFilter::ScheduledBefore(d) => before(task.scheduled, d),
+            DateFilter::ScheduledAfter(d) => after(task.scheduled, d),
+            DateFilter::ModifiedBefore(d) => before(modified, d),
+            DateFilter::ModifiedAfter(d) => after(modified, d),
+            DateFilter::EntryBefore(d) => before(Some(task.entry), d),
+            DateFilter::EntryAfter(d) => after(Some(task.entry), d),
+            DateFilter::EndBefore(d) => before(task.end, d),
+            DateFilter::EndAfter(d) => after(task.end, d),
+            DateFilter::EndBetween(start, end) => between(task.end, start, end),
+            DateFilter::All(parts) => parts.iter().all(|part| part.matches(task)),
+        }
+    }
+}
+
+/// Task attribute a query or report can sort on
+///
+/// Serialized as the Taskwarrior attribute name, so `"due"` or a UDA name
+/// like `"estimate"`. Names that are not built-in attributes parse as
+/// [`SortField::Uda`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SortField {
+    Id,
+    Description,
+    Status,
+    /// Also accepted as `created`
+    Entry,
+    Modified,
+    Due,
+    Scheduled,
+    Wait,
+    Start,
+    End,
+    Priority,
+    Project,
+    Owner,
+    Urgency,
+    Uda(String),
+}
+
+impl SortField {
+    /// The Taskwarrior attribute name
+    pub fn name(&self) -> &str {
+        match self {
+            SortField::Id => "id",
+            SortField::Description => "description",
+            SortField::Status => "status",
+            SortField::Entry => "entry",
+            SortField::Modified => "modified",
+            SortField::Due => "due",
+            SortField::Scheduled => "scheduled",
+            SortField::Wait => "wait",
+            SortField::Start => "start",
+            SortField::End => "end",
+            SortField::Priority => "priority",
+            SortField::Project => "project",
+            SortField::Owner => "owner",
+            SortField::Urgency => "urgency",
+            SortField::Uda(name) => name,
+        }
+    }
+
+    /// Order two tasks by this field, lowest value first. `None` means the
+    /// task has no value for the field. Priorities order from lowest to
+    /// highest under `scheme`; urgency comes from `urgency`; text compares
+    /// under `collation`.
+    fn compare_values(
+        &self,
+        a: &Task,
+        b: &Task,
+        scheme: &PriorityScheme,
+        collation: Collation,
+        urgency: &dyn Fn(&Task) -> f64,
+    ) -> Option<Ordering> {
+        fn both<T: Ord>(a: Option<T>, b: Option<T>) -> Option<Ordering> {
+            Some(a?.cmp(&b?))
+        }
+        match self {
+            SortField::Id => both(a.display_id, b.display_id),
+            SortField::Description => Some(collation.compare(&a.description, &b.description)),
+            SortField::Status => Some(status_rank(a.status).cmp(&status_rank(b.status))),
+            SortField::Entry => Some(a.entry.cmp(&b.entry)),
+            SortField::Modified => {
+                Some(a.modified.unwrap_or(a.entry).cmp(&b.modified.unwrap_or(b.entry)))
+            }
+            SortField::Due => both(a.due, b.due),
+            SortField::Scheduled => both(a.scheduled, b.scheduled),
+            SortField::Wait => both(a.wait, b.wait),
+            SortField::Start => both(a.start, b.start),
+            SortField::End => both(a.end, b.end),
+            SortField::Priority => {
+                let (a, b) = (a.priority_code()?, b.priority_code()?);
+                Some(scheme.compare(Some(b), Some(a)))
+            }
+            SortField::Project => Some(collation.compare(a.project.as_ref()?, b.project.as_ref()?)),
+            SortField::Owner => Some(collation.compare(a.owner.as_ref()?, b.owner.as_ref()?)),
+            SortField::Urgency => Some(urgency(a).total_cmp(&urgency(b))),
+            SortField::Uda(name) => match (a.udas.get(name)?, b.udas.get(name)?) {
+                (UdaValue::String(a), UdaValue::String(b)) => Some(collation.compare(a, b)),
+                (UdaValue::Number(a), UdaValue::Number(b)) => Some(a.total_cmp(b)),
+                (UdaValue::Date(a), UdaValue::Date(b)) => Some(a.cmp(b)),
+                (a, b) => Some(uda_rank(a).cmp(&uda_rank(b))),
+            },
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+fn status_rank(status: TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Pending => 0,
+        TaskStatus::Waiting => 1,
+        TaskStatus::Recurring => 2,
+        TaskStatus::Completed => 3,
+        TaskStatus::Deleted => 4,
+    }
+}
+
+fn uda_rank(value: &UdaValue) -> u8 {
+    match value {
+        UdaValue::Number(_) => 0,
+        UdaValue::Date(_) => 1,
+        UdaValue::String(_) => 2,
+    }
+}
+
+impl FromStr for SortField {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "id" => SortField::Id,
+            "description" => SortField::Description,
+            "status" => SortField::Status,
+            "entry" | "created" => SortField::Entry,
+            "modified" => SortField::Modified,
+            "due" => SortField::Due,
+            "scheduled" => SortField::Scheduled,
+            "wait" => SortField::Wait,
+            "start" => SortField::Start,
+            "end" => SortField::End,
+            "priority" => SortField::Priority,
+            "project" => SortField::Project,
+            "owner" => SortField::Owner,
+            "urgency" => SortField::Urgency,
+            name if is_uda_name(name) => SortField::Uda(name.to_string()),
+            _ => {
+                return Err(QueryError::InvalidSort {
+                    criteria: s.to_string(),
+                })
+            }
+        })
+    }
+}
+
+fn is_uda_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl TryFrom<String> for SortField {
+    type Error = QueryError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SortField> for String {
+    fn from(field: SortField) -> Self {
+        field.name().to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SortCriteria {
-    pub field: String,
+    pub field: SortField,
     pub ascending: bool,
 }
 
 impl SortCriteria {
-    pub fn priority() -> Self { Self { field: "priority".into(), ascending: false } }
-    pub fn ascending(field: &str) -> Self { Self { field: field.into(), ascending: true } }
-    pub fn descending(field: &str) -> Self { Self { field: field.into(), ascending: false } }
+    pub fn priority() -> Self { Self { field: SortField::Priority, ascending: false } }
+    pub fn ascending(field: SortField) -> Self { Self { field, ascending: true } }
+    pub fn descending(field: SortField) -> Self { Self { field, ascending: false } }
+
+    /// Parse a Taskwarrior sort list such as `urgency-,due+,project+/`.
+    /// A missing direction means ascending; the `/` break marker is ignored.
+    pub fn parse_list(spec: &str) -> Result<Vec<Self>, QueryError> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(|term| {
+                let term = term.trim_end_matches('/');
+                let (name, ascending) = match term.strip_suffix('-') {
+                    Some(name) => (name, false),
+                    None => (term.strip_suffix('+').unwrap_or(term), true),
+                };
+                Ok(Self { field: name.parse()?, ascending })
+            })
+            .collect()
+    }
+
+    /// Order two tasks by this criterion. Tasks without a value for the
+    /// field come last in either direction.
+    pub fn compare(
+        &self,
+        a: &Task,
+        b: &Task,
+        scheme: &PriorityScheme,
+        collation: Collation,
+        urgency: &dyn Fn(&Task) -> f64,
+    ) -> Ordering {
+        match self.field.compare_values(a, b, scheme, collation, urgency) {
+            Some(order) if self.ascending => order,
+            Some(order) => order.reverse(),
+            None => {
+                let has_value =
+                    |t: &Task| self.field.compare_values(t, t, scheme, collation, urgency).is_some();
+                has_value(b).cmp(&has_value(a))
+            }
+        }
+    }
+}
+
+/// Sort `tasks` by each criterion in turn, keeping the original order for ties
+pub fn sort_tasks(
+    tasks: &mut [Task],
+    criteria: &[SortCriteria],
+    scheme: &PriorityScheme,
+    collation: Collation,
+    urgency: &dyn Fn(&Task) -> f64,
+) {
+    tasks.sort_by(|a, b| {
+        criteria
+            .iter()
+            .map(|c| c.compare(a, b, scheme, collation, urgency))
+            .find(|order| order.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
 }
 
 /// Extract a simple project token from a Taskwarrior filter expression.
@@ -83,3 +520,117 @@ pub fn parse_project_from_filter(filter: &str) -> Option<String> {
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_filter_sets_and_counts() {
+        let tags = |names: &[&str]| names.iter().map(|t| t.to_string()).collect::<HashSet<_>>();
+
+        let filter: TagFilter = "+work or +home -someday +next".parse().unwrap();
+        assert_eq!(filter.any_of, tags(&["work", "home"]));
+        assert_eq!(filter.all_of, tags(&["next"]));
+        assert_eq!(filter.none_of, tags(&["someday"]));
+        assert!(filter.matches(&tags(&["home", "next"])));
+        assert!(!filter.matches(&tags(&["home"])));
+        assert!(!filter.matches(&tags(&["work", "next", "someday"])));
+
+        let untagged: TagFilter = "tags.none:".parse().unwrap();
+        assert_eq!(untagged, TagFilter::untagged());
+        assert!(untagged.matches(&HashSet::new()));
+        assert!(!untagged.matches(&tags(&["a"])));
+
+        let busy: TagFilter = "tags.min:2".parse().unwrap();
+        assert!(!busy.matches(&tags(&["a"])));
+        assert!(busy.matches(&tags(&["a", "b"])));
+        assert_eq!("tags.any:".parse::<TagFilter>().unwrap(), TagFilter::at_least(1));
+
+        for bad in ["+a or", "+a or -b", "+a or +b +c or +d", "work", "tags.min:x"] {
+            assert!(bad.parse::<TagFilter>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_tag_filter_accepts_old_field_names() {
+        let filter: TagFilter = serde_json::from_str(r#"{"include":["a"],"exclude":["b"]}"#).unwrap();
+        assert_eq!(filter, TagFilter { none_of: ["b".to_string()].into(), ..TagFilter::include_tags(["a"]) });
+    }
+
+    #[test]
+    fn test_sort_field_parse_and_serde() {
+        assert_eq!("due".parse::<SortField>().unwrap(), SortField::Due);
+        assert_eq!("created".parse::<SortField>().unwrap(), SortField::Entry);
+        assert_eq!("estimate".parse::<SortField>().unwrap(), SortField::Uda("estimate".to_string()));
+        assert!(matches!("due date".parse::<SortField>(), Err(QueryError::InvalidSort { .. })));
+        assert!("".parse::<SortField>().is_err());
+
+        let json = serde_json::to_string(&SortCriteria::descending(SortField::Urgency)).unwrap();
+        assert_eq!(json, r#"{"field":"urgency","ascending":false}"#);
+        assert!(serde_json::from_str::<SortCriteria>(r#"{"field":"no such","ascending":true}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_sort_list() {
+        let criteria = SortCriteria::parse_list("urgency-, due+,project+/,size").unwrap();
+        assert_eq!(
+            criteria,
+            vec![
+                SortCriteria::descending(SortField::Urgency),
+                SortCriteria::ascending(SortField::Due),
+                SortCriteria::ascending(SortField::Project),
+                SortCriteria::ascending(SortField::Uda("size".to_string())),
+            ]
+        );
+        assert!(SortCriteria::parse_list("due+,bad field").is_err());
+    }
+
+    #[test]
+    fn test_sort_tasks_missing_values_last() {
+        let mut tasks: Vec<Task> = ["a", "b", "c"].iter().map(|d| Task::new(d.to_string())).collect();
+        tasks[0].udas.insert("size".to_string(), UdaValue::Number(2.0));
+        tasks[2].udas.insert("size".to_string(), UdaValue::Number(1.0));
+        let scheme = PriorityScheme::default();
+
+        let by_size = SortCriteria::parse_list("size-").unwrap();
+        sort_tasks(&mut tasks, &by_size, &scheme, Collation::Binary, &|t| t.urgency);
+        let order: Vec<_> = tasks.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(order, vec!["a", "c", "b"]);
+
+        sort_tasks(
+            &mut tasks,
+            &SortCriteria::parse_list("size+").unwrap(),
+            &scheme,
+            Collation::Binary,
+            &|t| t.urgency,
+        );
+        let order: Vec<_> = tasks.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_sort_projects_case_insensitively() {
+        let projects = [Some("work"), Some("Home"), Some("errands"), None, Some("Work")];
+        let scheme = PriorityScheme::default();
+        let by_project = SortCriteria::parse_list("project+").unwrap();
+        let sorted = |collation: Collation| -> Vec<Option<String>> {
+            let mut tasks: Vec<Task> = projects
+                .iter()
+                .map(|p| {
+                    let mut task = Task::new("Task".to_string());
+                    task.project = p.map(str::to_string);
+                    task
+                })
+                .collect();
+            sort_tasks(&mut tasks, &by_project, &scheme, collation, &|t| t.urgency);
+            tasks.into_iter().map(|t| t.project).collect()
+        };
+        let names = |list: &[&str]| -> Vec<Option<String>> {
+            list.iter().map(|p| Some(p.to_string())).chain([None]).collect()
+        };
+
+        assert_eq!(sorted(Collation::Binary), names(&["Home", "Work", "errands", "work"]));
+        assert_eq!(sorted(Collation::NoCase), names(&["errands", "Home", "Work", "work"]));
+    }
+}
@@ -0,0 +1,132 @@
+//! Proximity filtering
+//!
+//! Tasks tied to a place carry its coordinates in the `latitude` and
+//! `longitude` UDAs (see [`Task::location`]). A [`Proximity`] keeps the
+//! tasks within a radius of a point, measured along the Earth's surface
+//! with the haversine formula, for "errands near me" lists:
+//!
+//! ```rust
+//! use taskwarrior3lib::query::{TaskQueryBuilder, TaskQueryBuilderImpl};
+//!
+//! let query = TaskQueryBuilderImpl::new()
+//!     .near(52.52, 13.405, 2.0)
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! Tasks without a location never match. The proximity is a field of
+//! [`TaskQuery`](crate::query::TaskQuery), so it survives serialization
+//! and storage backends apply it like the other filters.
+
+use crate::error::QueryError;
+use crate::task::Task;
+use serde::{Deserialize, Serialize};
+
+/// Mean Earth radius used for distances
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Great-circle distance in kilometres between two `(latitude, longitude)`
+/// points given in degrees
+pub fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// Tasks located within `radius_km` of a point
+///
+/// Deserializing checks the fields the same way as [`Proximity::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ProximityFields")]
+pub struct Proximity {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_km: f64,
+}
+
+impl Proximity {
+    /// Check the coordinates are in range and the radius is not negative
+    pub fn new(latitude: f64, longitude: f64, radius_km: f64) -> Result<Self, QueryError> {
+        let valid = (-90.0..=90.0).contains(&latitude)
+            && (-180.0..=180.0).contains(&longitude)
+            && radius_km >= 0.0
+            && radius_km.is_finite();
+        if !valid {
+            return Err(QueryError::InvalidFilter {
+                expression: format!("near {latitude},{longitude} within {radius_km} km"),
+            });
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+            radius_km,
+        })
+    }
+
+    /// Distance from the point to the task's location, if it has one
+    pub fn distance_km(&self, task: &Task) -> Option<f64> {
+        task.location()
+            .map(|location| haversine_km((self.latitude, self.longitude), location))
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.distance_km(task)
+            .is_some_and(|distance| distance <= self.radius_km)
+    }
+}
+
+#[derive(Deserialize)]
+struct ProximityFields {
+    latitude: f64,
+    longitude: f64,
+    radius_km: f64,
+}
+
+impl TryFrom<ProximityFields> for Proximity {
+    type Error = QueryError;
+
+    fn try_from(fields: ProximityFields) -> Result<Self, Self::Error> {
+        Proximity::new(fields.latitude, fields.longitude, fields.radius_km)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine_distance() {
+        let berlin = (52.5200, 13.4050);
+        let paris = (48.8566, 2.3522);
+        let distance = haversine_km(berlin, paris);
+        assert!((distance - 878.0).abs() < 2.0, "{distance}");
+        assert_eq!(haversine_km(paris, paris), 0.0);
+    }
+
+    #[test]
+    fn test_proximity_matches_located_tasks() {
+        let near = Proximity::new(52.5200, 13.4050, 2.0).unwrap();
+        let mut bakery = Task::new("Buy bread".to_string());
+        bakery.set_location(Some((52.5210, 13.4130)));
+        let mut airport = Task::new("Pick up Sam".to_string());
+        airport.set_location(Some((52.3667, 13.5033)));
+        let anywhere = Task::new("Call mum".to_string());
+
+        assert!(near.matches(&bakery));
+        assert!(!near.matches(&airport));
+        assert!(!near.matches(&anywhere));
+        assert!(Proximity::new(91.0, 0.0, 1.0).is_err());
+        assert!(Proximity::new(0.0, 0.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_proximity_serde_validates() {
+        let near = Proximity::new(52.52, 13.405, 2.0).unwrap();
+        let json = serde_json::to_string(&near).unwrap();
+        assert_eq!(serde_json::from_str::<Proximity>(&json).unwrap(), near);
+        let bad = r#"{"latitude":95.0,"longitude":0.0,"radius_km":1.0}"#;
+        assert!(serde_json::from_str::<Proximity>(bad).is_err());
+    }
+}
@@ -3,17 +3,36 @@
 //! This module provides the query builder and filtering functionality
 //! for searching and retrieving tasks.
 
-use crate::task::TaskStatus;
+use crate::config::PriorityScheme;
+use crate::query::collation::Collation;
+use crate::task::{Task, TaskStatus};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
+pub mod aggregate;
 pub mod builder;
+pub mod collation;
+pub mod explain;
+mod filter_string;
 pub mod filters;
+pub mod geo;
+pub mod natural;
+pub mod search;
 
 // Re-export commonly used filter types from the filters module
-pub use filters::{DateFilter, ProjectFilter, SortCriteria, TagFilter};
+pub use filter_string::virtual_tags;
+pub use filters::{
+    sort_tasks, DateFilter, OwnerFilter, PriorityFilter, ProjectFilter, SortCriteria, SortField,
+    TagFilter,
+};
 
 /// Task query specification
-#[derive(Debug, Clone, PartialEq, Default)]
+///
+/// Queries serialize to JSON (or any serde format) for saved searches and
+/// RPC; missing fields take their defaults. Custom predicates are closures
+/// and are not serialized.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TaskQuery {
     pub status: Option<TaskStatus>,
     pub project_filter: Option<ProjectFilter>,
@@ -24,6 +43,89 @@ pub struct TaskQuery {
     pub offset: Option<usize>,
     /// How this query interacts with an active Taskwarrior context
     pub filter_mode: Option<crate::query::FilterMode>,
+    /// User-provided predicates; a task must satisfy all of them
+    #[serde(skip)]
+    pub custom_filters: Vec<TaskPredicate>,
+    /// Filter on the task owner
+    pub owner_filter: Option<OwnerFilter>,
+    /// Filter on the task priority
+    pub priority_filter: Option<PriorityFilter>,
+    /// Priority ordering used when sorting by priority; the task manager
+    /// fills this from config when unset
+    pub priority_scheme: Option<PriorityScheme>,
+    /// Text ordering used when sorting by description, project, owner or
+    /// a string UDA; the task manager fills this from config when unset
+    pub collation: Option<Collation>,
+    /// Keep tasks located within a radius of a point
+    pub proximity: Option<geo::Proximity>,
+}
+
+impl TaskQuery {
+    /// Check a task against the custom predicates of this query
+    pub fn matches_custom(&self, task: &Task) -> bool {
+        self.custom_filters.iter().all(|p| p.matches(task))
+    }
+
+    /// Whether `task` passes every filter of this query, custom predicates
+    /// included. Sorting, `limit` and `offset` are not considered.
+    pub fn matches(&self, task: &Task) -> bool {
+        self.status.as_ref().is_none_or(|s| task.status == *s)
+            && self.project_filter.as_ref().is_none_or(|f| f.matches(task.project.as_deref()))
+            && self.tag_filter.as_ref().is_none_or(|f| f.matches(&task.tags))
+            && self.date_filter.as_ref().is_none_or(|f| f.matches(task))
+            && self.priority_filter.as_ref().is_none_or(|f| f.matches(task.priority_code()))
+            && self.owner_filter.as_ref().is_none_or(|f| f.matches(task.owner.as_deref()))
+            && self.proximity.as_ref().is_none_or(|p| p.matches(task))
+            && self.matches_custom(task)
+    }
+
+    /// Sort `tasks` by this query's sort criteria, if any. Priorities use
+    /// the query's scheme (default H/M/L), text the query's collation
+    /// (default byte order) and urgency the stored value.
+    pub fn apply_sort(&self, tasks: &mut [Task]) {
+        if let Some(sort) = &self.sort {
+            let scheme = self.priority_scheme.clone().unwrap_or_default();
+            let collation = self.collation.unwrap_or_default();
+            sort_tasks(tasks, std::slice::from_ref(sort), &scheme, collation, &|t| t.urgency);
+        }
+    }
+}
+
+/// A closure predicate participating in query filtering.
+///
+/// Predicates are evaluated in memory after the backend has loaded candidate
+/// tasks. They cannot be pushed down into storage (e.g. translated to SQL or
+/// a Taskwarrior filter string), so combine them with regular filters to keep
+/// the candidate set small. Two predicates compare equal only if they share
+/// the same underlying closure.
+#[derive(Clone)]
+pub struct TaskPredicate(Arc<dyn Fn(&Task) -> bool + Send + Sync>);
+
+impl TaskPredicate {
+    /// Wrap a closure as a predicate
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&Task) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    /// Evaluate the predicate against a task
+    pub fn matches(&self, task: &Task) -> bool {
+        (self.0)(task)
+    }
+}
+
+impl std::fmt::Debug for TaskPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TaskPredicate(<closure>)")
+    }
+}
+
+impl PartialEq for TaskPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 // Re-export main types
@@ -39,3 +141,41 @@ pub enum FilterMode {
 // Keep default behavior implicit elsewhere; builders may add a field for this.
 
 pub use builder::{TaskQueryBuilder, TaskQueryBuilderImpl};
+pub use explain::{ClauseTrace, MatchTrace};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_query_serde_round_trip() {
+        let query = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            project_filter: Some(ProjectFilter::Hierarchy("Work".to_string())),
+            tag_filter: Some(TagFilter::has_tag("next".to_string())),
+            date_filter: Some(DateFilter::DueBefore(Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap())),
+            sort: Some(SortCriteria::priority()),
+            limit: Some(10),
+            filter_mode: Some(FilterMode::IgnoreContext),
+            priority_scheme: Some(PriorityScheme::default()),
+            collation: Some(Collation::NoCase),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&query).unwrap();
+        assert_eq!(serde_json::from_str::<TaskQuery>(&json).unwrap(), query);
+
+        // Predicates are dropped; missing fields default
+        let with_predicate = TaskQuery {
+            custom_filters: vec![TaskPredicate::new(|_| true)],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&with_predicate).unwrap();
+        assert!(serde_json::from_str::<TaskQuery>(&json).unwrap().custom_filters.is_empty());
+
+        let partial: TaskQuery = serde_json::from_str(r#"{"status":"completed","limit":3}"#).unwrap();
+        assert_eq!(partial.status, Some(TaskStatus::Completed));
+        assert_eq!(partial.limit, Some(3));
+        assert!(partial.tag_filter.is_none());
+    }
+}
@@ -0,0 +1,323 @@
+//! Natural-language quick filters
+//!
+//! An opt-in heuristic parser for search bars. It understands a small,
+//! fixed vocabulary and maps it onto a [`TaskQuery`]:
+//!
+//! | Phrase                              | Meaning                          |
+//! |-------------------------------------|----------------------------------|
+//! | `overdue`                           | pending and due before now       |
+//! | `today`, `tomorrow`, `this week`    | due within that local period     |
+//! | `high/medium/low priority`          | priority `H`/`M`/`L`             |
+//! | `no priority`                       | no priority set                  |
+//! | `in project X`, `project X`         | project `X` and its subprojects  |
+//! | `tagged Y`, `tagged with Y`, `+Y`   | has tag `Y`                      |
+//! | `pending`, `completed`, `done`, ... | task status                      |
+//!
+//! Terms combine with AND: `overdue tasks due this week` means due this
+//! week and already past due, and `+urgent +home tomorrow` means tagged
+//! both `urgent` and `home` and due tomorrow.
+//!
+//! Filler words (`show`, `my`, `tasks`, `due`, ...) are skipped; anything
+//! else is reported in [`NaturalQuery::unrecognized`] rather than guessed at.
+//! The returned [`NaturalQuery::terms`] describe how each phrase was read so
+//! a UI can show the interpretation before running the query.
+//!
+//! ```rust
+//! use taskwarrior3lib::query::natural;
+//!
+//! let parsed = natural::parse("overdue high priority tasks in project Work");
+//! assert!(parsed.unrecognized.is_empty());
+//! println!("{}", parsed.describe());
+//! ```
+
+use crate::clock;
+use crate::query::{DateFilter, PriorityFilter, ProjectFilter, TagFilter, TaskQuery};
+use crate::task::TaskStatus;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+
+/// Words that carry no meaning on their own
+const FILLER: &[&str] = &[
+    "show", "me", "my", "all", "tasks", "task", "that", "are", "is", "and", "with", "due", "the",
+    "list", "find",
+];
+
+/// One recognized phrase and how it was interpreted
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterpretedTerm {
+    /// The words from the input
+    pub phrase: String,
+    /// Human-readable meaning, e.g. `priority is H`
+    pub meaning: String,
+}
+
+/// Result of parsing a natural-language filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaturalQuery {
+    /// The structured query
+    pub query: TaskQuery,
+    /// Phrases recognized, in input order
+    pub terms: Vec<InterpretedTerm>,
+    /// Words that were not understood
+    pub unrecognized: Vec<String>,
+}
+
+impl NaturalQuery {
+    /// Whether every word of the input was understood
+    pub fn is_complete(&self) -> bool {
+        self.unrecognized.is_empty()
+    }
+
+    /// One-line summary of the interpretation, e.g. for a confirmation prompt
+    pub fn describe(&self) -> String {
+        if self.terms.is_empty() {
+            return "all tasks".to_string();
+        }
+        self.terms
+            .iter()
+            .map(|t| t.meaning.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn push(&mut self, words: &[&str], meaning: &str) {
+        self.terms.push(InterpretedTerm {
+            phrase: words.join(" "),
+            meaning: meaning.to_string(),
+        });
+    }
+
+    fn set_project(&mut self, project: &str) {
+        self.query.project_filter = Some(ProjectFilter::Hierarchy(project.to_string()));
+    }
+
+    fn add_tag(&mut self, tag: &str) {
+        self.query
+            .tag_filter
+            .get_or_insert_with(TagFilter::default)
+            .all_of
+            .insert(tag.to_string());
+    }
+}
+
+/// Parse a natural-language filter relative to the current time
+pub fn parse(input: &str) -> NaturalQuery {
+    parse_at(input, clock::now())
+}
+
+/// Parse a natural-language filter relative to `now`
+pub fn parse_at(input: &str, now: DateTime<Utc>) -> NaturalQuery {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let today = now.with_timezone(&Local).date_naive();
+
+    let mut result = NaturalQuery {
+        query: TaskQuery::default(),
+        terms: Vec::new(),
+        unrecognized: Vec::new(),
+    };
+    // Due-date window every date term narrows: start inclusive, end exclusive
+    let mut due: (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = (None, None);
+    let mut narrow_due = |start: Option<DateTime<Utc>>, end: DateTime<Utc>| {
+        due.0 = due.0.max(start);
+        due.1 = Some(due.1.map_or(end, |e| e.min(end)));
+    };
+    let mut i = 0;
+
+    while i < words.len() {
+        let word = lower[i].as_str();
+        let next = lower.get(i + 1).map(String::as_str);
+
+        // Number of words consumed by the matched phrase
+        let consumed = match (word, next) {
+            ("overdue", _) => {
+                result.query.status = Some(TaskStatus::Pending);
+                narrow_due(None, now);
+                result.push(&words[i..=i], "pending and due before now");
+                1
+            }
+            ("today", _) => {
+                narrow_due(Some(local_midnight(today)), local_midnight(today + Duration::days(1)));
+                result.push(&words[i..=i], "due today");
+                1
+            }
+            ("tomorrow", _) => {
+                let tomorrow = today + Duration::days(1);
+                narrow_due(
+                    Some(local_midnight(tomorrow)),
+                    local_midnight(tomorrow + Duration::days(1)),
+                );
+                result.push(&words[i..=i], "due tomorrow");
+                1
+            }
+            ("this", Some("week")) => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                narrow_due(Some(local_midnight(monday)), local_midnight(monday + Duration::days(7)));
+                result.push(&words[i..i + 2], "due this week");
+                2
+            }
+            ("high" | "medium" | "low", Some("priority")) => {
+                let code = match word {
+                    "high" => "H",
+                    "medium" => "M",
+                    _ => "L",
+                };
+                result.query.priority_filter = Some(PriorityFilter::Is(code.to_string()));
+                result.push(&words[i..i + 2], &format!("priority is {code}"));
+                2
+            }
+            ("no", Some("priority")) => {
+                result.query.priority_filter = Some(PriorityFilter::Unset);
+                result.push(&words[i..i + 2], "no priority");
+                2
+            }
+            ("in", Some("project")) if i + 2 < words.len() => {
+                result.set_project(words[i + 2]);
+                result.push(&words[i..i + 3], &format!("project is {}", words[i + 2]));
+                3
+            }
+            ("project", Some(_)) => {
+                result.set_project(words[i + 1]);
+                result.push(&words[i..i + 2], &format!("project is {}", words[i + 1]));
+                2
+            }
+            ("tagged", Some("with")) if i + 2 < words.len() => {
+                result.add_tag(words[i + 2]);
+                result.push(&words[i..i + 3], &format!("tagged {}", words[i + 2]));
+                3
+            }
+            ("tagged", Some(_)) => {
+                result.add_tag(words[i + 1]);
+                result.push(&words[i..i + 2], &format!("tagged {}", words[i + 1]));
+                2
+            }
+            (w, _) if w.len() > 1 && w.starts_with('+') => {
+                let tag = &words[i][1..];
+                result.add_tag(tag);
+                result.push(&words[i..=i], &format!("tagged {tag}"));
+                1
+            }
+            ("pending" | "completed" | "done" | "deleted" | "waiting", _) => {
+                let status = match word {
+                    "pending" => TaskStatus::Pending,
+                    "completed" | "done" => TaskStatus::Completed,
+                    "deleted" => TaskStatus::Deleted,
+                    _ => TaskStatus::Waiting,
+                };
+                result.query.status = Some(status);
+                let name = format!("{status:?}").to_lowercase();
+                result.push(&words[i..=i], &format!("status is {name}"));
+                1
+            }
+            (w, _) if FILLER.contains(&w) => 1,
+            _ => {
+                result.unrecognized.push(words[i].to_string());
+                1
+            }
+        };
+        i += consumed;
+    }
+
+    result.query.date_filter = match due {
+        (Some(start), Some(end)) => Some(DateFilter::DueBetween(start, end)),
+        (None, Some(end)) => Some(DateFilter::DueBefore(end)),
+        _ => None,
+    };
+    result
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    let naive = date.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uses_the_clock() {
+        let now = Utc.with_ymd_and_hms(2025, 2, 3, 10, 0, 0).unwrap();
+        let _clock = clock::deterministic(clock::FixedClock::new(now), clock::SequentialIds::new());
+        assert_eq!(parse("overdue").query.date_filter, Some(DateFilter::DueBefore(now)));
+    }
+
+    #[test]
+    fn test_parse_vocabulary() {
+        let now = Utc::now();
+        let parsed = parse_at("Show my overdue high priority tasks in project Work tagged urgent", now);
+
+        assert!(parsed.is_complete());
+        assert_eq!(parsed.query.status, Some(TaskStatus::Pending));
+        assert_eq!(parsed.query.date_filter, Some(DateFilter::DueBefore(now)));
+        assert_eq!(parsed.query.priority_filter, Some(PriorityFilter::Is("H".to_string())));
+        assert_eq!(
+            parsed.query.project_filter,
+            Some(ProjectFilter::Hierarchy("Work".to_string()))
+        );
+        assert!(parsed.query.tag_filter.as_ref().unwrap().all_of.contains("urgent"));
+        assert_eq!(
+            parsed.describe(),
+            "pending and due before now, priority is H, project is Work, tagged urgent"
+        );
+    }
+
+    #[test]
+    fn test_this_week_spans_seven_days() {
+        let parsed = parse("due this week");
+        match parsed.query.date_filter {
+            Some(DateFilter::DueBetween(start, end)) => {
+                assert!((end - start).num_hours() >= 167 && (end - start).num_hours() <= 169);
+            }
+            other => panic!("unexpected filter: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_date_terms_intersect() {
+        // Thursday; the week started on Monday the 2nd
+        let now = Local
+            .with_ymd_and_hms(2025, 6, 5, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let parsed = parse_at("overdue work tasks due this week", now);
+        let monday = local_midnight(NaiveDate::from_ymd_opt(2025, 6, 2).unwrap());
+        assert_eq!(parsed.query.date_filter, Some(DateFilter::DueBetween(monday, now)));
+        assert_eq!(parsed.query.status, Some(TaskStatus::Pending));
+        assert_eq!(parsed.describe(), "pending and due before now, due this week");
+        assert_eq!(parsed.unrecognized, vec!["work"]);
+    }
+
+    #[test]
+    fn test_tag_and_date_terms_all_apply() {
+        let now = Local
+            .with_ymd_and_hms(2025, 6, 5, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        let parsed = parse_at("+urgent tagged home tomorrow", now);
+        assert!(parsed.is_complete());
+
+        let tomorrow = now + Duration::days(1);
+        let task = |tags: &[&str], due: DateTime<Utc>| {
+            let mut task = crate::task::Task::new("Call".to_string());
+            task.tags = tags.iter().map(|t| t.to_string()).collect();
+            task.due = Some(due);
+            task
+        };
+        assert!(parsed.query.matches(&task(&["urgent", "home"], tomorrow)));
+        assert!(!parsed.query.matches(&task(&["urgent"], tomorrow)));
+        assert!(!parsed.query.matches(&task(&["home"], tomorrow)));
+        assert!(!parsed.query.matches(&task(&["urgent", "home"], now)));
+    }
+
+    #[test]
+    fn test_unrecognized_words_are_reported() {
+        let parsed = parse("+home someday soon");
+        assert_eq!(parsed.unrecognized, vec!["someday", "soon"]);
+        assert!(!parsed.is_complete());
+        assert_eq!(parsed.terms[0].meaning, "tagged home");
+    }
+}
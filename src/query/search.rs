@@ -0,0 +1,188 @@
+//! Relevance-ranked free-text search
+//!
+//! [`TaskManager::search`](crate::task::TaskManager::search) returns the
+//! tasks containing every word of a search string, best match first, the
+//! way users expect a search box to behave. A task's score adds up:
+//!
+//! - each occurrence of a word in the description ([`DESCRIPTION_WEIGHT`])
+//!   or in an annotation ([`ANNOTATION_WEIGHT`]),
+//! - a recency boost of up to [`RECENCY_WEIGHT`] that halves every
+//!   [`RECENCY_HALF_LIFE_DAYS`] days since the task was last modified, and
+//! - [`URGENCY_WEIGHT`] times its (positive) urgency.
+//!
+//! Text matches dominate; recency and urgency mostly break ties between
+//! similar matches. Matching honours `search.case.sensitive`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Configuration;
+use crate::task::urgency::UrgencyCoefficients;
+use crate::task::Task;
+
+/// Score per occurrence of a word in the description
+pub const DESCRIPTION_WEIGHT: f64 = 2.0;
+/// Score per occurrence of a word in an annotation
+pub const ANNOTATION_WEIGHT: f64 = 1.0;
+/// Boost for a task modified just now
+pub const RECENCY_WEIGHT: f64 = 1.0;
+/// Age at which the recency boost has halved
+pub const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+/// Score per point of urgency
+pub const URGENCY_WEIGHT: f64 = 0.1;
+
+/// A task matching a search, with its relevance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub task: Task,
+    /// Relevance; higher is better
+    pub score: f64,
+}
+
+/// A parsed search string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Search {
+    /// Words that must all occur, lowercased unless case-sensitive
+    pub terms: Vec<String>,
+    pub case_sensitive: bool,
+}
+
+impl Search {
+    /// Split `text` into whitespace-separated words
+    pub fn new(text: &str, case_sensitive: bool) -> Self {
+        let terms = text
+            .split_whitespace()
+            .map(|word| {
+                if case_sensitive {
+                    word.to_string()
+                } else {
+                    word.to_lowercase()
+                }
+            })
+            .collect();
+        Self {
+            terms,
+            case_sensitive,
+        }
+    }
+
+    /// Parse `text` with case sensitivity from `search.case.sensitive`
+    pub fn from_config(text: &str, config: &Configuration) -> Self {
+        Self::new(
+            text,
+            config.get_bool("search.case.sensitive").unwrap_or(true),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Text score of `task`, or `None` unless every word occurs in its
+    /// description or annotations
+    pub fn text_score(&self, task: &Task) -> Option<f64> {
+        let description = self.normalize(&task.description);
+        let annotations: Vec<String> = task
+            .annotations
+            .iter()
+            .map(|a| self.normalize(&a.description))
+            .collect();
+        let mut score = 0.0;
+        for term in &self.terms {
+            let in_description = description.matches(term.as_str()).count();
+            let in_annotations: usize = annotations
+                .iter()
+                .map(|a| a.matches(term.as_str()).count())
+                .sum();
+            if in_description + in_annotations == 0 {
+                return None;
+            }
+            score += in_description as f64 * DESCRIPTION_WEIGHT
+                + in_annotations as f64 * ANNOTATION_WEIGHT;
+        }
+        Some(score)
+    }
+
+    /// Full relevance of `task` at `now`, or `None` if it does not match
+    pub fn score(
+        &self,
+        task: &Task,
+        urgency: &UrgencyCoefficients,
+        now: DateTime<Utc>,
+    ) -> Option<f64> {
+        let text = self.text_score(task)?;
+        let touched = task.modified.unwrap_or(task.entry);
+        let age_days = ((now - touched).num_seconds().max(0) as f64) / 86_400.0;
+        let recency = RECENCY_WEIGHT * 0.5_f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+        let urgency = URGENCY_WEIGHT * urgency.score(task, now).max(0.0);
+        Some(text + recency + urgency)
+    }
+
+    /// Matching `tasks`, highest score first; ties keep their input order
+    pub fn rank(
+        &self,
+        tasks: Vec<Task>,
+        urgency: &UrgencyCoefficients,
+        now: DateTime<Utc>,
+    ) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = tasks
+            .into_iter()
+            .filter_map(|task| {
+                self.score(&task, urgency, now)
+                    .map(|score| SearchHit { task, score })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+
+    fn normalize(&self, text: &str) -> String {
+        if self.case_sensitive {
+            text.to_string()
+        } else {
+            text.to_lowercase()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Annotation;
+    use chrono::Duration;
+
+    #[test]
+    fn test_all_terms_must_match() {
+        let search = Search::new("Milk bread", false);
+        let mut task = Task::new("Buy milk".to_string());
+        assert_eq!(search.text_score(&task), None);
+        task.add_annotation(Annotation::new("and bread from the bakery".to_string()));
+        assert_eq!(
+            search.text_score(&task),
+            Some(DESCRIPTION_WEIGHT + ANNOTATION_WEIGHT)
+        );
+        assert_eq!(Search::new("Milk", true).text_score(&task), None);
+    }
+
+    #[test]
+    fn test_rank_orders_by_relevance() {
+        let now = Utc::now();
+        let urgency = UrgencyCoefficients::new();
+        let mut once = Task::new("Fix login bug".to_string());
+        once.entry = now - Duration::days(60);
+        once.modified = Some(once.entry);
+        let twice = Task::new("Bug: login bug on mobile".to_string());
+        let mut recent = Task::new("Fix login bug".to_string());
+        recent.entry = now;
+        let unrelated = Task::new("Write docs".to_string());
+
+        let hits = Search::new("bug", false).rank(
+            vec![once.clone(), unrelated, recent.clone(), twice.clone()],
+            &urgency,
+            now,
+        );
+        let order: Vec<_> = hits.iter().map(|h| h.task.id).collect();
+        assert_eq!(order, [twice.id, recent.id, once.id]);
+        assert!(hits.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+}
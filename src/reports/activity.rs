@@ -0,0 +1,224 @@
+//! Project activity over time
+//!
+//! [`ProjectActivity`] counts, per project and local calendar week (Monday
+//! to Sunday), the tasks added and the tasks completed. A project that
+//! keeps taking in more than gets done is a burnout risk long before its
+//! task count looks alarming, so [`ProjectActivity::warnings`] flags every
+//! project whose backlog grew in each of the last `n` weeks. Warnings
+//! serialize, for review tooling to pick up.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::reports::builtin::NO_GROUP;
+use crate::task::{Task, TaskStatus};
+
+/// Tasks added and completed in one project during one week
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeekActivity {
+    /// Monday the week starts on
+    pub week: NaiveDate,
+    pub added: usize,
+    pub completed: usize,
+}
+
+impl WeekActivity {
+    /// Change of the backlog over the week: added minus completed
+    pub fn delta(&self) -> i64 {
+        self.added as i64 - self.completed as i64
+    }
+}
+
+/// A project whose backlog grew week after week
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogWarning {
+    pub project: String,
+    /// Consecutive weeks of growth, ending with the last week
+    pub weeks: usize,
+    /// Tasks added over those weeks
+    pub added: usize,
+    /// Tasks completed over those weeks
+    pub completed: usize,
+}
+
+impl BacklogWarning {
+    /// Net backlog growth over the flagged weeks
+    pub fn growth(&self) -> i64 {
+        self.added as i64 - self.completed as i64
+    }
+
+    /// One-line description, e.g. `Work: backlog grew 4 weeks in a row (+9)`
+    pub fn message(&self) -> String {
+        format!(
+            "{}: backlog grew {} weeks in a row (+{})",
+            self.project,
+            self.weeks,
+            self.growth()
+        )
+    }
+}
+
+/// Weekly added-vs-completed counts per project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectActivity {
+    /// Monday of the first week
+    pub start: NaiveDate,
+    /// Number of weeks covered
+    pub weeks: usize,
+    /// One entry per week for every project with activity in the range;
+    /// tasks without a project count under `(none)`
+    pub projects: BTreeMap<String, Vec<WeekActivity>>,
+}
+
+impl ProjectActivity {
+    /// Count activity of `tasks` in the `weeks` weeks ending with the week
+    /// containing `last_day`
+    pub fn new(tasks: &[Task], last_day: NaiveDate, weeks: usize) -> Self {
+        let last_week = week_start(last_day);
+        let start = last_week - Duration::weeks(weeks.saturating_sub(1) as i64);
+        let index = |day: NaiveDate| -> Option<usize> {
+            let week = (week_start(day) - start).num_weeks();
+            usize::try_from(week).ok().filter(|w| *w < weeks)
+        };
+
+        let empty: Vec<WeekActivity> = (0..weeks)
+            .map(|i| WeekActivity {
+                week: start + Duration::weeks(i as i64),
+                added: 0,
+                completed: 0,
+            })
+            .collect();
+        let mut projects: BTreeMap<String, Vec<WeekActivity>> = BTreeMap::new();
+        for task in tasks {
+            let added = index(task.entry.with_timezone(&Local).date_naive());
+            let completed = task
+                .end
+                .filter(|_| task.status == TaskStatus::Completed)
+                .and_then(|end| index(end.with_timezone(&Local).date_naive()));
+            if added.is_none() && completed.is_none() {
+                continue;
+            }
+            let project = task.project.clone().unwrap_or_else(|| NO_GROUP.to_string());
+            let counts = projects.entry(project).or_insert_with(|| empty.clone());
+            if let Some(i) = added {
+                counts[i].added += 1;
+            }
+            if let Some(i) = completed {
+                counts[i].completed += 1;
+            }
+        }
+
+        Self {
+            start,
+            weeks,
+            projects,
+        }
+    }
+
+    /// Activity in the `weeks` weeks up to and including the current one
+    pub fn past_weeks(tasks: &[Task], weeks: usize) -> Self {
+        Self::new(
+            tasks,
+            clock::now().with_timezone(&Local).date_naive(),
+            weeks,
+        )
+    }
+
+    /// Projects whose backlog grew in each of the last `min_weeks` weeks,
+    /// longest growth streak first
+    pub fn warnings(&self, min_weeks: usize) -> Vec<BacklogWarning> {
+        let min_weeks = min_weeks.max(1);
+        let mut warnings: Vec<BacklogWarning> = self
+            .projects
+            .iter()
+            .filter_map(|(project, weeks)| {
+                let growing: Vec<&WeekActivity> =
+                    weeks.iter().rev().take_while(|w| w.delta() > 0).collect();
+                (growing.len() >= min_weeks).then(|| BacklogWarning {
+                    project: project.clone(),
+                    weeks: growing.len(),
+                    added: growing.iter().map(|w| w.added).sum(),
+                    completed: growing.iter().map(|w| w.completed).sum(),
+                })
+            })
+            .collect();
+        warnings.sort_by(|a, b| {
+            b.weeks
+                .cmp(&a.weeks)
+                .then_with(|| a.project.cmp(&b.project))
+        });
+        warnings
+    }
+}
+
+/// Monday of the week containing `day`
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn at(day: NaiveDate) -> chrono::DateTime<Utc> {
+        let noon = day.and_hms_opt(12, 0, 0).unwrap();
+        Local
+            .from_local_datetime(&noon)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn task(project: &str, added: NaiveDate, completed: Option<NaiveDate>) -> Task {
+        let mut task = Task::new("Task".to_string());
+        task.project = Some(project.to_string());
+        task.entry = at(added);
+        if let Some(day) = completed {
+            task.complete();
+            task.end = Some(at(day));
+        }
+        task
+    }
+
+    #[test]
+    fn test_weekly_deltas_and_warnings() {
+        // Monday 2025-03-03 starts the first of four weeks
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let week = |n: i64| monday + Duration::weeks(n) + Duration::days(2);
+        let mut tasks = Vec::new();
+        for n in 0..4 {
+            // Work takes in two tasks a week and finishes one
+            tasks.push(task("Work", week(n), None));
+            tasks.push(task("Work", week(n), Some(week(n))));
+            // Home grows only in the last two weeks
+            tasks.push(task("Home", week(n), (n < 2).then(|| week(n))));
+        }
+        tasks.push(task("Old", monday - Duration::weeks(10), None));
+
+        let activity = ProjectActivity::new(&tasks, week(3), 4);
+        assert_eq!(activity.start, monday);
+        assert!(!activity.projects.contains_key("Old"));
+        let work = &activity.projects["Work"];
+        assert_eq!(work.len(), 4);
+        assert_eq!(
+            (work[0].added, work[0].completed, work[0].delta()),
+            (2, 1, 1)
+        );
+        assert_eq!(activity.projects["Home"][0].delta(), 0);
+
+        let warnings = activity.warnings(2);
+        let flagged: Vec<(&str, usize)> = warnings
+            .iter()
+            .map(|w| (w.project.as_str(), w.weeks))
+            .collect();
+        assert_eq!(flagged, [("Work", 4), ("Home", 2)]);
+        assert_eq!(
+            warnings[0].message(),
+            "Work: backlog grew 4 weeks in a row (+4)"
+        );
+        assert_eq!(activity.warnings(3).len(), 1);
+    }
+}
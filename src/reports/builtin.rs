@@ -3,8 +3,16 @@
 //! This module provides comprehensive reporting functionality including
 //! built-in reports, urgency calculations, and formatted output.
 
+use crate::clock;
+use crate::config::{Configuration, PriorityScheme};
 use crate::error::TaskError;
-use crate::task::{Priority, Task, TaskStatus};
+use crate::query::collation::Collation;
+use crate::query::{sort_tasks, SortCriteria, TaskQuery};
+use crate::reports::gantt::GanttChart;
+use crate::task::checklist::Checklist;
+use crate::task::effort::format_short;
+use crate::task::urgency::UrgencyCoefficients;
+use crate::task::{DependencyGraph, Task, TaskStatus};
 #[allow(unused_imports)]
 use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
@@ -22,10 +30,98 @@ pub struct ReportConfig {
     pub limit: Option<usize>,
     /// Sort order (field names with optional +/- prefix)
     pub sort: Option<String>,
-    /// Filter expression
+    /// Taskwarrior filter, see [`TaskQuery::parse_filter`]
     pub filter: Option<String>,
     /// Date format string
     pub date_format: String,
+    /// Group rows into sections by field (`project`, `tags`, `due.week`,
+    /// `status`, `priority` or a UDA name)
+    #[serde(default)]
+    pub group_by: Option<String>,
+    /// Periods covered by the weekly and monthly reports; the current week
+    /// or month by due date when unset
+    #[serde(default)]
+    pub period: Option<ReportPeriod>,
+    /// `limit:page`: show as many rows as fit the consumer's screen, see
+    /// [`ReportManager::generate_for_viewport`](super::ReportManager::generate_for_viewport).
+    /// Without a viewport the report is not limited.
+    #[serde(default)]
+    pub page: bool,
+}
+
+/// Which weeks or months a weekly or monthly report covers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportPeriod {
+    /// A date inside the latest period; today when unset
+    pub anchor: Option<NaiveDate>,
+    /// Number of consecutive periods, ending with the anchor's
+    pub count: u32,
+    /// Date attribute that places a task in a period: `due`, `end`,
+    /// `entry`, `modified`, `scheduled`, `start` or `wait`
+    pub field: String,
+}
+
+impl Default for ReportPeriod {
+    fn default() -> Self {
+        Self {
+            anchor: None,
+            count: 1,
+            field: "due".to_string(),
+        }
+    }
+}
+
+impl ReportPeriod {
+    /// The last `count` periods up to and including the current one
+    pub fn last(count: u32) -> Self {
+        Self {
+            count,
+            ..Self::default()
+        }
+    }
+
+    /// End with the period containing `anchor` instead of the current one
+    pub fn ending_at(mut self, anchor: NaiveDate) -> Self {
+        self.anchor = Some(anchor);
+        self
+    }
+
+    /// Place tasks by `field` instead of their due date, e.g. `end` for
+    /// when they were completed
+    pub fn by<S: Into<String>>(mut self, field: S) -> Self {
+        self.field = field.into();
+        self
+    }
+
+    /// The periods as `(label, first day, day after the last)`, oldest
+    /// first. Weeks start on Monday and are labelled by ISO week
+    /// (`2025-W07`), months as `2025-02`.
+    pub fn ranges(&self, monthly: bool) -> Vec<(String, NaiveDate, NaiveDate)> {
+        let anchor = self.anchor.unwrap_or_else(|| Local::now().date_naive());
+        (0..self.count.max(1))
+            .rev()
+            .map(|back| {
+                if monthly {
+                    let start = anchor.with_day(1).unwrap() - chrono::Months::new(back);
+                    (
+                        start.format("%Y-%m").to_string(),
+                        start,
+                        start + chrono::Months::new(1),
+                    )
+                } else {
+                    let monday = anchor
+                        - Duration::days(anchor.weekday().num_days_from_monday() as i64)
+                        - Duration::weeks(back as i64);
+                    (
+                        monday.format("%G-W%V").to_string(),
+                        monday,
+                        monday + Duration::weeks(1),
+                    )
+                }
+            })
+            .collect()
+    }
 }
 
 impl Default for ReportConfig {
@@ -42,7 +138,58 @@ impl Default for ReportConfig {
             sort: None,
             filter: None,
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
+        }
+    }
+}
+
+impl ReportConfig {
+    /// List report defined by `report.<name>.columns`, `.sort` and `.filter`.
+    /// A `limit:N` term in the filter becomes the row limit and `limit:page`
+    /// sets [`Self::page`]. Returns None when the report has no columns
+    /// configured.
+    pub fn from_config(config: &Configuration, name: &str) -> Option<Self> {
+        let columns: Vec<String> = config
+            .get(&format!("report.{name}.columns"))?
+            .split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect();
+        if columns.is_empty() {
+            return None;
         }
+
+        let mut limit = None;
+        let mut page = false;
+        let filter = config.get(&format!("report.{name}.filter")).map(|filter| {
+            let terms: Vec<&str> = filter
+                .split_whitespace()
+                .filter(|term| match term.strip_prefix("limit:") {
+                    Some("page") => {
+                        page = true;
+                        false
+                    }
+                    Some(n) => {
+                        limit = n.parse().ok();
+                        false
+                    }
+                    None => true,
+                })
+                .collect();
+            terms.join(" ")
+        });
+
+        Some(Self {
+            report_type: ReportType::List,
+            columns,
+            limit,
+            sort: config.get(&format!("report.{name}.sort")).cloned(),
+            filter: filter.filter(|f| !f.is_empty()),
+            page,
+            ..Self::default()
+        })
     }
 }
 
@@ -59,6 +206,12 @@ pub enum ReportType {
     Projects,
     Tags,
     Burndown,
+    /// Unfinished tasks waiting on unfinished dependencies
+    Blocked,
+    /// Unfinished tasks that other unfinished tasks wait on
+    Blocking,
+    /// Scheduled-to-due bars per project, see [`GanttChart`]
+    Gantt,
 }
 
 /// Output format for reports
@@ -68,6 +221,7 @@ pub enum ReportFormat {
     Json,
     Csv,
     Simple,
+    Markdown,
 }
 
 /// Report row data
@@ -84,34 +238,59 @@ pub struct ReportResult {
     pub total_count: usize,
     pub shown_count: usize,
     pub summary: HashMap<String, String>,
+    /// Sections produced when the report is grouped (empty otherwise)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<ReportGroup>,
+}
+
+/// A section of a grouped report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportGroup {
+    /// Group label (e.g. the project name)
+    pub key: String,
+    /// Rows belonging to this group
+    pub rows: Vec<ReportRow>,
+    /// Subtotal: number of tasks in the group
+    pub count: usize,
 }
 
 /// Built-in reports implementation
 #[derive(Debug)]
 pub struct BuiltinReports {
-    urgency_coefficients: HashMap<String, f64>,
+    urgency: UrgencyCoefficients,
+    collation: Collation,
 }
 
 impl BuiltinReports {
     /// Create new built-in reports instance
     pub fn new() -> Self {
-        let mut coefficients = HashMap::new();
-        coefficients.insert("priority.H".to_string(), 6.0);
-        coefficients.insert("priority.M".to_string(), 3.9);
-        coefficients.insert("priority.L".to_string(), 1.8);
-        coefficients.insert("project".to_string(), 1.0);
-        coefficients.insert("tags".to_string(), 1.0);
-        coefficients.insert("due".to_string(), 12.0);
-        coefficients.insert("overdue".to_string(), 6.0);
-        coefficients.insert("blocking".to_string(), 8.0);
-        coefficients.insert("blocked".to_string(), -5.0);
-        coefficients.insert("age".to_string(), 2.0);
+        Self {
+            urgency: UrgencyCoefficients::new(),
+            collation: Collation::default(),
+        }
+    }
 
+    /// Reports using the priority scheme, `urgency.<name>.coefficient` and
+    /// `sort.collation` settings from `config`
+    pub fn from_config(config: &Configuration) -> Self {
         Self {
-            urgency_coefficients: coefficients,
+            urgency: UrgencyCoefficients::from_config(config),
+            collation: Collation::from_config(config),
         }
     }
 
+    /// Use a custom priority scheme for urgency and priority sorting
+    pub fn with_priority_scheme(mut self, scheme: PriorityScheme) -> Self {
+        self.urgency = self.urgency.with_priority_scheme(scheme);
+        self
+    }
+
+    /// Order text columns such as `project` under `collation`
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+
     /// Generate a report based on configuration
     pub fn generate_report(
         &self,
@@ -133,62 +312,20 @@ impl BuiltinReports {
             ReportType::Projects => self.generate_projects_report(&limited_tasks, config),
             ReportType::Tags => self.generate_tags_report(&limited_tasks, config),
             ReportType::Burndown => self.generate_burndown_report(&limited_tasks, config),
+            // Dependencies are looked up among all tasks, not just the listed ones
+            ReportType::Blocked => self.generate_dependency_report(&limited_tasks, tasks, config, true),
+            ReportType::Blocking => self.generate_dependency_report(&limited_tasks, tasks, config, false),
+            ReportType::Gantt => self.generate_gantt_report(&limited_tasks, config),
         }
     }
 
     /// Calculate urgency score for a task
     pub fn calculate_urgency(&self, task: &Task) -> f64 {
-        let mut urgency = 0.0;
-
-        // Priority component
-        match task.priority {
-            Some(Priority::High) => {
-                urgency += self.urgency_coefficients.get("priority.H").unwrap_or(&6.0)
-            }
-            Some(Priority::Medium) => {
-                urgency += self.urgency_coefficients.get("priority.M").unwrap_or(&3.9)
-            }
-            Some(Priority::Low) => {
-                urgency += self.urgency_coefficients.get("priority.L").unwrap_or(&1.8)
-            }
-            None => {}
-        }
-
-        // Project component
-        if task.project.is_some() {
-            urgency += self.urgency_coefficients.get("project").unwrap_or(&1.0);
-        }
-
-        // Tags component
-        if !task.tags.is_empty() {
-            urgency += self.urgency_coefficients.get("tags").unwrap_or(&1.0);
-        }
-
-        // Due date component
-        if let Some(due_date) = &task.due {
-            let now = Utc::now();
-            let days_until_due = due_date.signed_duration_since(now).num_days();
-
-            if days_until_due < 0 {
-                // Overdue
-                urgency += self.urgency_coefficients.get("overdue").unwrap_or(&6.0)
-                    * (-days_until_due as f64);
-            } else if days_until_due <= 7 {
-                // Due soon
-                urgency += self.urgency_coefficients.get("due").unwrap_or(&12.0)
-                    * (8.0 - days_until_due as f64)
-                    / 8.0;
-            }
-        }
-
-        // Age component
-        let age_days = Utc::now().signed_duration_since(task.entry).num_days();
-        urgency += self.urgency_coefficients.get("age").unwrap_or(&2.0) * (age_days as f64) / 365.0;
-
-        urgency.max(0.0)
+        self.urgency.score(task, clock::now())
     }
 
-    /// Apply filter to task list
+    /// Keep the tasks matching a Taskwarrior filter, parsed with
+    /// [`TaskQuery::parse_filter`]
     fn apply_filter(
         &self,
         tasks: &[Task],
@@ -197,51 +334,26 @@ impl BuiltinReports {
         let mut filtered = tasks.to_vec();
 
         if let Some(filter_str) = filter {
-            // Simple filter implementation - can be extended
-            if filter_str.contains("status:pending") {
-                filtered.retain(|task| task.status == TaskStatus::Pending);
-            }
-            if filter_str.contains("status:completed") {
-                filtered.retain(|task| task.status == TaskStatus::Completed);
-            }
+            let query = TaskQuery::parse_filter(filter_str)?;
+            filtered.retain(|task| query.matches(task));
         }
 
         Ok(filtered)
     }
 
-    /// Apply sorting to task list
+    /// Apply a Taskwarrior sort list such as `urgency-,due+` to tasks
     fn apply_sort(&self, tasks: &[Task], sort: &Option<String>) -> Result<Vec<Task>, TaskError> {
         let mut sorted = tasks.to_vec();
 
         if let Some(sort_str) = sort {
-            if sort_str.contains("urgency") {
-                sorted.sort_by(|a, b| {
-                    let urgency_a = self.calculate_urgency(a);
-                    let urgency_b = self.calculate_urgency(b);
-                    if sort_str.contains("urgency-") {
-                        urgency_b
-                            .partial_cmp(&urgency_a)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    } else {
-                        urgency_a
-                            .partial_cmp(&urgency_b)
-                            .unwrap_or(std::cmp::Ordering::Equal)
-                    }
-                });
-            } else if sort_str.contains("due") {
-                sorted.sort_by(|a, b| match (a.due, b.due) {
-                    (Some(due_a), Some(due_b)) => {
-                        if sort_str.contains("due+") {
-                            due_a.cmp(&due_b)
-                        } else {
-                            due_b.cmp(&due_a)
-                        }
-                    }
-                    (Some(_), None) => std::cmp::Ordering::Less,
-                    (None, Some(_)) => std::cmp::Ordering::Greater,
-                    (None, None) => std::cmp::Ordering::Equal,
-                });
-            }
+            let criteria = SortCriteria::parse_list(sort_str)?;
+            sort_tasks(
+                &mut sorted,
+                &criteria,
+                self.urgency.priority_scheme(),
+                self.collation,
+                &|t| self.calculate_urgency(t),
+            );
         }
 
         Ok(sorted)
@@ -256,6 +368,49 @@ impl BuiltinReports {
         }
     }
 
+    /// Build a single report row for a task
+    fn build_row(&self, task: &Task, headers: &[String], config: &ReportConfig) -> ReportRow {
+        let mut values = HashMap::new();
+
+        for column in headers {
+            let value = match column.as_str() {
+                "id" => task.id.to_string(),
+                "description" => task.description.clone(),
+                "project" => task.project.clone().unwrap_or_default(),
+                "due" => task
+                    .due
+                    .map(|d| {
+                        d.with_timezone(&Local)
+                            .format(&config.date_format)
+                            .to_string()
+                    })
+                    .unwrap_or_default(),
+                "priority" => task.priority_code().unwrap_or_default().to_string(),
+                "tags" => task.tags.iter().cloned().collect::<Vec<_>>().join(","),
+                "urgency" => format!("{:.1}", self.calculate_urgency(task)),
+                "estimate" => task.estimate().map(format_short).unwrap_or_default(),
+                "effort" => task.effort().map(format_short).unwrap_or_default(),
+                "pushed" => match task.push_count() {
+                    0 => String::new(),
+                    count => count.to_string(),
+                },
+                "checklist" => {
+                    let checklist = Checklist::from_task(task);
+                    if checklist.is_empty() {
+                        String::new()
+                    } else {
+                        checklist.to_string()
+                    }
+                }
+                "status" => format!("{:?}", task.status),
+                _ => String::new(),
+            };
+            values.insert(column.clone(), value);
+        }
+
+        ReportRow { values }
+    }
+
     /// Generate list report
     fn generate_list_report(
         &self,
@@ -263,38 +418,21 @@ impl BuiltinReports {
         config: &ReportConfig,
     ) -> Result<ReportResult, TaskError> {
         let headers = config.columns.clone();
-        let mut rows = Vec::new();
-
-        for task in tasks {
-            let mut values = HashMap::new();
-
-            for column in &headers {
-                let value = match column.as_str() {
-                    "id" => task.id.to_string(),
-                    "description" => task.description.clone(),
-                    "project" => task.project.clone().unwrap_or_default(),
-                    "due" => task
-                        .due
-                        .map(|d| {
-                            d.with_timezone(&Local)
-                                .format(&config.date_format)
-                                .to_string()
-                        })
-                        .unwrap_or_default(),
-                    "priority" => task.priority.map(|p| format!("{p:?}")).unwrap_or_default(),
-                    "tags" => task.tags.iter().cloned().collect::<Vec<_>>().join(","),
-                    "urgency" => format!("{:.1}", self.calculate_urgency(task)),
-                    "status" => format!("{:?}", task.status),
-                    _ => String::new(),
-                };
-                values.insert(column.clone(), value);
-            }
+        let rows = tasks
+            .iter()
+            .map(|task| self.build_row(task, &headers, config))
+            .collect();
 
-            rows.push(ReportRow { values });
-        }
+        let groups = match &config.group_by {
+            Some(field) => self.group_rows(tasks, &headers, field, config),
+            None => Vec::new(),
+        };
 
         let mut summary = HashMap::new();
         summary.insert("Total tasks".to_string(), tasks.len().to_string());
+        if !groups.is_empty() {
+            summary.insert("Groups".to_string(), groups.len().to_string());
+        }
 
         Ok(ReportResult {
             headers,
@@ -302,9 +440,41 @@ impl BuiltinReports {
             total_count: tasks.len(),
             shown_count: tasks.len(),
             summary,
+            groups,
         })
     }
 
+    /// Split tasks into sections keyed by `field`, preserving task order
+    /// within each section. Tasks with several tags appear under each tag.
+    fn group_rows(
+        &self,
+        tasks: &[Task],
+        headers: &[String],
+        field: &str,
+        config: &ReportConfig,
+    ) -> Vec<ReportGroup> {
+        let mut sections: std::collections::BTreeMap<String, Vec<ReportRow>> =
+            std::collections::BTreeMap::new();
+
+        for task in tasks {
+            for key in group_keys(task, field) {
+                sections
+                    .entry(key)
+                    .or_default()
+                    .push(self.build_row(task, headers, config));
+            }
+        }
+
+        sections
+            .into_iter()
+            .map(|(key, rows)| ReportGroup {
+                key,
+                count: rows.len(),
+                rows,
+            })
+            .collect()
+    }
+
     /// Generate next report (most urgent tasks)
     fn generate_next_report(
         &self,
@@ -354,7 +524,7 @@ impl BuiltinReports {
         tasks: &[Task],
         config: &ReportConfig,
     ) -> Result<ReportResult, TaskError> {
-        let now = Utc::now();
+        let now = clock::now();
         let overdue_tasks: Vec<Task> = tasks
             .iter()
             .filter(|task| {
@@ -372,24 +542,7 @@ impl BuiltinReports {
         tasks: &[Task],
         config: &ReportConfig,
     ) -> Result<ReportResult, TaskError> {
-        let now = Local::now();
-        let week_start = now - Duration::days(now.weekday().num_days_from_monday() as i64);
-        let week_end = week_start + Duration::days(7);
-
-        let weekly_tasks: Vec<Task> = tasks
-            .iter()
-            .filter(|task| {
-                if let Some(due) = task.due {
-                    let due_local = due.with_timezone(&Local);
-                    due_local >= week_start && due_local < week_end
-                } else {
-                    false
-                }
-            })
-            .cloned()
-            .collect();
-
-        self.generate_list_report(&weekly_tasks, config)
+        self.generate_period_report(tasks, config, false)
     }
 
     /// Generate monthly report
@@ -398,37 +551,164 @@ impl BuiltinReports {
         tasks: &[Task],
         config: &ReportConfig,
     ) -> Result<ReportResult, TaskError> {
-        let now = Local::now();
-        let month_start = now
-            .date_naive()
-            .with_day(1)
-            .unwrap()
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
-        let month_end = if now.month() == 12 {
-            month_start
-                .with_year(now.year() + 1)
-                .unwrap()
-                .with_month(1)
-                .unwrap()
-        } else {
-            month_start.with_month(now.month() + 1).unwrap()
+        self.generate_period_report(tasks, config, true)
+    }
+
+    /// List the tasks whose period date falls in one of the configured
+    /// weeks or months. With more than one period, and no other grouping,
+    /// rows are also grouped per period, empty periods included.
+    fn generate_period_report(
+        &self,
+        tasks: &[Task],
+        config: &ReportConfig,
+        monthly: bool,
+    ) -> Result<ReportResult, TaskError> {
+        let period = config.period.clone().unwrap_or_default();
+        let date_of: fn(&Task) -> Option<DateTime<Utc>> = match period.field.as_str() {
+            "due" => |t| t.due,
+            "end" => |t| t.end,
+            "entry" => |t| Some(t.entry),
+            "modified" => |t| Some(t.modified.unwrap_or(t.entry)),
+            "scheduled" => |t| t.scheduled,
+            "start" => |t| t.start,
+            "wait" => |t| t.wait,
+            other => {
+                return Err(TaskError::InvalidData {
+                    message: format!("Cannot place tasks in periods by '{other}'"),
+                })
+            }
         };
 
-        let monthly_tasks: Vec<Task> = tasks
-            .iter()
-            .filter(|task| {
-                if let Some(due) = task.due {
-                    let due_local = due.with_timezone(&Local).naive_local();
-                    due_local >= month_start && due_local < month_end
-                } else {
-                    false
+        let ranges = period.ranges(monthly);
+        let period_of = |task: &Task| {
+            let day = date_of(task)?.with_timezone(&Local).date_naive();
+            ranges.iter().position(|(_, start, end)| *start <= day && day < *end)
+        };
+        let selected: Vec<Task> = tasks.iter().filter(|t| period_of(t).is_some()).cloned().collect();
+
+        let mut result = self.generate_list_report(&selected, config)?;
+        if ranges.len() > 1 && config.group_by.is_none() {
+            let mut groups: Vec<ReportGroup> = ranges
+                .iter()
+                .map(|(label, _, _)| ReportGroup {
+                    key: label.clone(),
+                    rows: Vec::new(),
+                    count: 0,
+                })
+                .collect();
+            for task in &selected {
+                if let Some(i) = period_of(task) {
+                    groups[i].rows.push(self.build_row(task, &result.headers, config));
+                    groups[i].count += 1;
                 }
-            })
+            }
+            result.summary.insert("Periods".to_string(), groups.len().to_string());
+            result.groups = groups;
+        }
+        Ok(result)
+    }
+
+    /// List blocked tasks with the descriptions of their blockers in the
+    /// `blocked_by` column, or blocking tasks with the tasks they hold up in
+    /// the `blocking` column. Both counts go in the summary.
+    fn generate_dependency_report(
+        &self,
+        tasks: &[Task],
+        all_tasks: &[Task],
+        config: &ReportConfig,
+        blocked: bool,
+    ) -> Result<ReportResult, TaskError> {
+        let graph = DependencyGraph::new(all_tasks);
+        let listed: Vec<Task> = tasks
+            .iter()
+            .filter(|t| if blocked { graph.is_blocked(t.id) } else { graph.is_blocking(t.id) })
             .cloned()
             .collect();
 
-        self.generate_list_report(&monthly_tasks, config)
+        let mut result = self.generate_list_report(&listed, config)?;
+        let describe = |related: Vec<&Task>| {
+            related
+                .iter()
+                .map(|t| t.description.as_str())
+                .collect::<Vec<_>>()
+                .join("; ")
+        };
+        let column = if blocked { "blocked_by" } else { "blocking" };
+        if result.headers.iter().any(|h| h == column) {
+            for (row, task) in result.rows.iter_mut().zip(&listed) {
+                let related = if blocked { graph.blockers(task.id) } else { graph.blocked_by(task.id) };
+                row.values.insert(column.to_string(), describe(related));
+            }
+        }
+
+        let blocked_count = all_tasks.iter().filter(|t| graph.is_blocked(t.id)).count();
+        let blocking_count = all_tasks.iter().filter(|t| graph.is_blocking(t.id)).count();
+        result.summary.insert("Blocked tasks".to_string(), blocked_count.to_string());
+        result.summary.insert("Blocking tasks".to_string(), blocking_count.to_string());
+        Ok(result)
+    }
+
+    /// One row per bar of the [`GanttChart`] for `tasks`, grouped by
+    /// project. `start` and `end` hold the bar's dates and `overlaps` the
+    /// descriptions of the bars it overlaps.
+    fn generate_gantt_report(
+        &self,
+        tasks: &[Task],
+        config: &ReportConfig,
+    ) -> Result<ReportResult, TaskError> {
+        let chart = GanttChart::from_tasks(tasks);
+        let by_id: HashMap<_, _> = tasks.iter().map(|t| (t.id, t)).collect();
+        let format_date = |d: DateTime<Utc>| {
+            d.with_timezone(&Local)
+                .format(&config.date_format)
+                .to_string()
+        };
+
+        let headers = config.columns.clone();
+        let mut groups = Vec::new();
+        for lane in &chart.lanes {
+            let rows: Vec<ReportRow> = lane
+                .bars
+                .iter()
+                .map(|bar| {
+                    let mut row = self.build_row(by_id[&bar.id], &headers, config);
+                    let overlaps: Vec<&str> = bar
+                        .overlaps
+                        .iter()
+                        .map(|id| by_id[id].description.as_str())
+                        .collect();
+                    for (column, value) in [
+                        ("start", format_date(bar.start)),
+                        ("end", format_date(bar.end)),
+                        ("overlaps", overlaps.join("; ")),
+                    ] {
+                        if headers.iter().any(|h| h == column) {
+                            row.values.insert(column.to_string(), value);
+                        }
+                    }
+                    row
+                })
+                .collect();
+            groups.push(ReportGroup {
+                key: lane.project.clone().unwrap_or_else(|| NO_GROUP.to_string()),
+                count: rows.len(),
+                rows,
+            });
+        }
+
+        let rows: Vec<ReportRow> = groups.iter().flat_map(|g| g.rows.clone()).collect();
+        let overlapping = chart.bars().filter(|b| !b.overlaps.is_empty()).count();
+        let mut summary = HashMap::new();
+        summary.insert("Scheduled tasks".to_string(), rows.len().to_string());
+        summary.insert("Overlapping tasks".to_string(), overlapping.to_string());
+        Ok(ReportResult {
+            headers,
+            total_count: tasks.len(),
+            shown_count: rows.len(),
+            rows,
+            summary,
+            groups,
+        })
     }
 
     /// Generate summary report
@@ -452,7 +732,7 @@ impl BuiltinReports {
         let overdue_count = tasks
             .iter()
             .filter(|t| {
-                t.status == TaskStatus::Pending && t.due.is_some_and(|due| due < Utc::now())
+                t.status == TaskStatus::Pending && t.due.is_some_and(|due| due < clock::now())
             })
             .count();
 
@@ -483,6 +763,7 @@ impl BuiltinReports {
             total_count: 3,
             shown_count: 3,
             summary,
+            groups: Vec::new(),
         })
     }
 
@@ -522,10 +803,8 @@ impl BuiltinReports {
         }
 
         rows.sort_by(|a, b| {
-            a.values
-                .get("Project")
-                .unwrap_or(&String::new())
-                .cmp(b.values.get("Project").unwrap_or(&String::new()))
+            let project = |row: &ReportRow| row.values.get("Project").cloned().unwrap_or_default();
+            self.collation.compare(&project(a), &project(b))
         });
 
         let total_count = rows.len();
@@ -538,6 +817,7 @@ impl BuiltinReports {
             total_count,
             shown_count: total_count,
             summary,
+            groups: Vec::new(),
         })
     }
 
@@ -592,6 +872,7 @@ impl BuiltinReports {
             total_count,
             shown_count: total_count,
             summary,
+            groups: Vec::new(),
         })
     }
 
@@ -653,6 +934,7 @@ impl BuiltinReports {
             total_count,
             shown_count: total_count,
             summary,
+            groups: Vec::new(),
         })
     }
 }
@@ -663,6 +945,43 @@ impl Default for BuiltinReports {
     }
 }
 
+/// Label used for tasks that have no value for the grouping field
+pub const NO_GROUP: &str = "(none)";
+
+/// Compute the group labels a task belongs to for a `group_by` field
+pub fn group_keys(task: &Task, field: &str) -> Vec<String> {
+    match field {
+        "project" => vec![task.project.clone().unwrap_or_else(|| NO_GROUP.to_string())],
+        "tag" | "tags" => {
+            if task.tags.is_empty() {
+                vec![NO_GROUP.to_string()]
+            } else {
+                let mut tags: Vec<String> = task.tags.iter().cloned().collect();
+                tags.sort();
+                tags
+            }
+        }
+        "due.week" | "due-week" => vec![task
+            .due
+            .map(|d| d.with_timezone(&Local).format("%G-W%V").to_string())
+            .unwrap_or_else(|| NO_GROUP.to_string())],
+        "status" => vec![format!("{:?}", task.status).to_lowercase()],
+        "priority" => vec![task
+            .priority_code()
+            .map(str::to_string)
+            .unwrap_or_else(|| NO_GROUP.to_string())],
+        uda => vec![task
+            .udas
+            .get(uda)
+            .map(|v| match v {
+                crate::task::model::UdaValue::String(s) => s.clone(),
+                crate::task::model::UdaValue::Number(n) => n.to_string(),
+                crate::task::model::UdaValue::Date(d) => d.format("%Y-%m-%d").to_string(),
+            })
+            .unwrap_or_else(|| NO_GROUP.to_string())],
+    }
+}
+
 /// Get default configuration for a report type
 pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
     match report_type {
@@ -678,6 +997,9 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
             sort: Some("due+".to_string()),
             filter: Some("status:pending".to_string()),
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
         },
         ReportType::Next => ReportConfig {
             report_type,
@@ -691,6 +1013,9 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
             sort: Some("urgency-".to_string()),
             filter: Some("status:pending".to_string()),
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
         },
         ReportType::Completed => ReportConfig {
             report_type,
@@ -703,6 +1028,9 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
             sort: None,
             filter: Some("status:completed".to_string()),
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
         },
         ReportType::Overdue => ReportConfig {
             report_type,
@@ -716,6 +1044,9 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
             sort: Some("urgency-".to_string()),
             filter: Some("status:pending".to_string()),
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
         },
         ReportType::Summary => ReportConfig {
             report_type,
@@ -724,6 +1055,42 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
             sort: None,
             filter: None,
             date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
+        },
+        ReportType::Blocked | ReportType::Blocking => ReportConfig {
+            report_type,
+            columns: vec![
+                "id".to_string(),
+                "description".to_string(),
+                "project".to_string(),
+                if report_type == ReportType::Blocked { "blocked_by" } else { "blocking" }.to_string(),
+            ],
+            limit: None,
+            sort: Some("urgency-".to_string()),
+            filter: Some("status:pending".to_string()),
+            date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
+        },
+        ReportType::Gantt => ReportConfig {
+            report_type,
+            columns: vec![
+                "id".to_string(),
+                "description".to_string(),
+                "start".to_string(),
+                "end".to_string(),
+                "overlaps".to_string(),
+            ],
+            limit: None,
+            sort: None,
+            filter: Some("status:pending".to_string()),
+            date_format: "%Y-%m-%d".to_string(),
+            group_by: None,
+            period: None,
+            page: false,
         },
         _ => ReportConfig::default(),
     }
@@ -732,7 +1099,7 @@ pub fn default_config_for_report(report_type: ReportType) -> ReportConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::task::Task;
+    use crate::task::{Priority, Task};
 
     #[test]
     fn test_urgency_calculation() {
@@ -745,6 +1112,17 @@ mod tests {
         assert!(urgency > 0.0);
     }
 
+    #[test]
+    fn test_age_urgency_is_bounded() {
+        let reports = BuiltinReports::new();
+        let mut task = Task::new("Test task".to_string());
+        task.entry = Utc::now() + chrono::Duration::days(30);
+        assert_eq!(reports.calculate_urgency(&task), 0.0);
+
+        task.entry = Utc::now() - chrono::Duration::days(3650);
+        assert_eq!(reports.calculate_urgency(&task), 2.0);
+    }
+
     #[test]
     fn test_list_report() {
         let reports = BuiltinReports::new();
@@ -774,4 +1152,266 @@ mod tests {
         assert!(result.summary.contains_key("Pending"));
         assert!(result.summary.contains_key("Completed"));
     }
+
+    #[test]
+    fn test_group_by_project() {
+        let reports = BuiltinReports::new();
+        let mut a = Task::new("A".to_string());
+        a.project = Some("Home".to_string());
+        let mut b = Task::new("B".to_string());
+        b.project = Some("Work".to_string());
+        let mut c = Task::new("C".to_string());
+        c.project = Some("Home".to_string());
+        let d = Task::new("D".to_string());
+
+        let mut config = default_config_for_report(ReportType::List);
+        config.group_by = Some("project".to_string());
+        let result = reports.generate_report(&[a, b, c, d], &config).unwrap();
+
+        let keys: Vec<_> = result.groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec![NO_GROUP, "Home", "Work"]);
+        assert_eq!(result.groups[1].count, 2);
+        assert_eq!(result.rows.len(), 4);
+        assert_eq!(result.summary.get("Groups").map(String::as_str), Some("3"));
+    }
+
+    #[test]
+    fn test_group_keys_tags_and_due_week() {
+        let mut task = Task::new("T".to_string());
+        task.add_tag("b".to_string());
+        task.add_tag("a".to_string());
+        assert_eq!(group_keys(&task, "tags"), vec!["a", "b"]);
+        assert_eq!(group_keys(&task, "due.week"), vec![NO_GROUP]);
+
+        task.due = Some(Utc::now());
+        assert!(group_keys(&task, "due.week")[0].contains("-W"));
+    }
+
+    #[test]
+    fn test_custom_priority_scheme_urgency_and_sort() {
+        let reports = BuiltinReports::new()
+            .with_priority_scheme(PriorityScheme::new(["C", "H", "M", "L", ""]).with_coefficient("C", 10.0));
+
+        let mut critical = Task::new("Critical".to_string());
+        critical.set_priority_code(Some("C"));
+        let mut high = Task::new("High".to_string());
+        high.priority = Some(Priority::High);
+        assert_eq!(reports.calculate_urgency(&critical), 10.0);
+
+        let sorted = reports
+            .apply_sort(&[high, Task::new("None".to_string()), critical], &Some("priority-".to_string()))
+            .unwrap();
+        let order: Vec<_> = sorted.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(order, vec!["Critical", "High", "None"]);
+    }
+
+    #[test]
+    fn test_apply_sort_uses_every_key() {
+        let reports = BuiltinReports::new();
+        let mut tasks = Vec::new();
+        for (description, project) in [("b", "Home"), ("c", "Work"), ("a", "Home")] {
+            let mut task = Task::new(description.to_string());
+            task.project = Some(project.to_string());
+            tasks.push(task);
+        }
+
+        let sorted = reports.apply_sort(&tasks, &Some("project-,description+".to_string())).unwrap();
+        let order: Vec<_> = sorted.iter().map(|t| t.description.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+        assert!(reports.apply_sort(&tasks, &Some("due date+".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_sort_collation_from_config() {
+        let tasks: Vec<Task> = ["Zebra", "apple", "Mango"].iter().map(|d| Task::new(d.to_string())).collect();
+        let sort = Some("description+".to_string());
+        let order = |reports: &BuiltinReports| -> Vec<String> {
+            let sorted = reports.apply_sort(&tasks, &sort).unwrap();
+            sorted.into_iter().map(|t| t.description).collect()
+        };
+
+        let mut config = Configuration::default();
+        assert_eq!(order(&BuiltinReports::from_config(&config)), ["Mango", "Zebra", "apple"]);
+        config.set("sort.collation", "nocase");
+        assert_eq!(order(&BuiltinReports::from_config(&config)), ["apple", "Mango", "Zebra"]);
+    }
+
+    #[test]
+    fn test_urgency_coefficients_from_config() {
+        let mut config = Configuration::default();
+        let mut task = Task::new("Test task".to_string());
+        task.project = Some("TestProject".to_string());
+        assert_eq!(BuiltinReports::from_config(&config).calculate_urgency(&task), 1.0);
+
+        config.set("urgency.project.coefficient", "5.0");
+        assert_eq!(BuiltinReports::from_config(&config).calculate_urgency(&task), 5.0);
+
+        // UDA terms only count once the UDA is set
+        config.set("urgency.uda.estimate.coefficient", "2.0");
+        assert_eq!(BuiltinReports::from_config(&config).calculate_urgency(&task), 5.0);
+        task.set_estimate(Some(Duration::hours(2)));
+        let reports = BuiltinReports::from_config(&config);
+        assert_eq!(reports.calculate_urgency(&task), 7.0);
+        let columns = ["estimate".to_string(), "effort".to_string()];
+        let row = reports.build_row(&task, &columns, &ReportConfig::default());
+        assert_eq!(row.values["estimate"], "2h");
+        assert_eq!(row.values["effort"], "");
+    }
+
+    #[test]
+    fn test_checklist_column() {
+        let reports = BuiltinReports::new();
+        let mut task = Task::new("Pack for trip".to_string());
+        let columns = ["checklist".to_string()];
+        let row = reports.build_row(&task, &columns, &ReportConfig::default());
+        assert_eq!(row.values["checklist"], "");
+
+        let mut checklist = Checklist::default();
+        for item in ["Passport", "Tickets", "Charger"] {
+            checklist.add(item);
+        }
+        checklist.toggle(2).unwrap();
+        checklist.write_to(&mut task);
+        let row = reports.build_row(&task, &columns, &ReportConfig::default());
+        assert_eq!(row.values["checklist"], "1/3");
+    }
+
+    #[test]
+    fn test_custom_report_filter() {
+        let mut config = Configuration::default();
+        config.set("report.work.columns", "id,description");
+        config.set("report.work.filter", "status:pending project:Work ( +call or +email ) -someday");
+        let report = ReportConfig::from_config(&config, "work").unwrap();
+
+        let task = |description: &str, project: &str, tags: &[&str]| {
+            let mut task = Task::new(description.to_string());
+            task.project = Some(project.to_string());
+            task.tags = tags.iter().map(|t| t.to_string()).collect();
+            task
+        };
+        let mut done = task("Done call", "Work", &["call"]);
+        done.status = TaskStatus::Completed;
+        let tasks = vec![
+            task("Call Bob", "Work.Sales", &["call"]),
+            task("Email Ann", "Work", &["email", "someday"]),
+            task("Call home", "Home", &["call"]),
+            task("Write spec", "Work", &[]),
+            done,
+        ];
+
+        let result = BuiltinReports::new().generate_report(&tasks, &report).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(result.rows[0].values["description"], "Call Bob");
+
+        let mut report = report;
+        report.filter = Some("status:open".to_string());
+        assert!(BuiltinReports::new().generate_report(&tasks, &report).is_err());
+    }
+
+    #[test]
+    fn test_completed_per_week_for_six_weeks() {
+        // Wednesday of ISO week 2025-W10
+        let anchor = NaiveDate::from_ymd_opt(2025, 3, 5).unwrap();
+        let finished = |description: &str, day: NaiveDate| {
+            let mut task = Task::new(description.to_string());
+            task.status = TaskStatus::Completed;
+            task.end = Some(day.and_hms_opt(12, 0, 0).unwrap().and_local_timezone(Local).unwrap().to_utc());
+            task
+        };
+        let tasks = vec![
+            finished("This week", anchor),
+            finished("Monday five weeks back", NaiveDate::from_ymd_opt(2025, 1, 27).unwrap()),
+            finished("Too old", NaiveDate::from_ymd_opt(2025, 1, 26).unwrap()),
+            finished("Next week", NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()),
+            Task::new("Still open".to_string()),
+        ];
+
+        let config = ReportConfig {
+            report_type: ReportType::Weekly,
+            filter: Some("status:completed".to_string()),
+            period: Some(ReportPeriod::last(6).ending_at(anchor).by("end")),
+            ..ReportConfig::default()
+        };
+        let result = BuiltinReports::new().generate_report(&tasks, &config).unwrap();
+        assert_eq!(result.rows.len(), 2);
+        let labels: Vec<&str> = result.groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(labels, ["2025-W05", "2025-W06", "2025-W07", "2025-W08", "2025-W09", "2025-W10"]);
+        let counts: Vec<usize> = result.groups.iter().map(|g| g.count).collect();
+        assert_eq!(counts, [1, 0, 0, 0, 0, 1]);
+
+        let months = ReportPeriod::last(3).ending_at(anchor).ranges(true);
+        assert_eq!(months[0].0, "2025-01");
+        assert_eq!(months[2].1, NaiveDate::from_ymd_opt(2025, 3, 1).unwrap());
+        assert_eq!(months[2].2, NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+
+        let bad = ReportConfig {
+            period: Some(ReportPeriod::default().by("urgency")),
+            ..config
+        };
+        assert!(BuiltinReports::new().generate_report(&tasks, &bad).is_err());
+    }
+
+    #[test]
+    fn test_blocked_and_blocking_reports() {
+        let design = Task::new("Design".to_string());
+        let mut spec = Task::new("Write spec".to_string());
+        spec.entry = design.entry + Duration::seconds(1);
+        let mut build = Task::new("Build".to_string());
+        build.depends.extend([design.id, spec.id]);
+        let mut done = Task::new("Old dependency".to_string());
+        done.complete();
+        let mut polish = Task::new("Polish".to_string());
+        polish.depends.insert(done.id);
+        let tasks = vec![design, spec, build, done, polish];
+        let reports = BuiltinReports::new();
+
+        let blocked = reports
+            .generate_report(&tasks, &default_config_for_report(ReportType::Blocked))
+            .unwrap();
+        assert_eq!(blocked.rows.len(), 1);
+        assert_eq!(blocked.rows[0].values["description"], "Build");
+        assert_eq!(blocked.rows[0].values["blocked_by"], "Design; Write spec");
+        assert_eq!(blocked.summary["Blocked tasks"], "1");
+        assert_eq!(blocked.summary["Blocking tasks"], "2");
+
+        let blocking = reports
+            .generate_report(&tasks, &default_config_for_report(ReportType::Blocking))
+            .unwrap();
+        let mut rows: Vec<(&str, &str)> = blocking
+            .rows
+            .iter()
+            .map(|r| (r.values["description"].as_str(), r.values["blocking"].as_str()))
+            .collect();
+        rows.sort();
+        assert_eq!(rows, vec![("Design", "Build"), ("Write spec", "Build")]);
+    }
+
+    #[test]
+    fn test_gantt_report() {
+        let start = Utc::now();
+        let mut design = Task::new("Design".to_string());
+        design.project = Some("Web".to_string());
+        design.scheduled = Some(start);
+        design.due = Some(start + Duration::days(5));
+        let mut build = Task::new("Build".to_string());
+        build.project = Some("Web".to_string());
+        build.scheduled = Some(start + Duration::days(3));
+        build.due = Some(start + Duration::days(9));
+        let unplanned = Task::new("Unplanned".to_string());
+        let tasks = vec![build, unplanned, design];
+
+        let result = BuiltinReports::new()
+            .generate_report(&tasks, &default_config_for_report(ReportType::Gantt))
+            .unwrap();
+        assert_eq!(result.groups.len(), 1);
+        assert_eq!(result.groups[0].key, "Web");
+        assert_eq!(result.rows.len(), 2);
+        assert_eq!(result.rows[0].values["description"], "Design");
+        assert_eq!(result.rows[0].values["overlaps"], "Build");
+        assert_eq!(
+            result.rows[1].values["end"],
+            (start + Duration::days(9)).with_timezone(&Local).format("%Y-%m-%d").to_string()
+        );
+        assert_eq!(result.summary["Overlapping tasks"], "2");
+    }
 }
@@ -0,0 +1,240 @@
+//! Differences between two runs of a report
+//!
+//! [`ReportDiff::between`] matches the rows of two [`ReportResult`]s by task
+//! UUID and sorts them into added, removed and changed rows. A daily job can
+//! keep yesterday's result (it serializes as JSON) and send the diff against
+//! today's as a "what changed" summary.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::builtin::{ReportResult, ReportRow};
+
+/// A column whose value differs between two runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub column: String,
+    /// Value in the earlier result (empty if the column was not shown)
+    pub before: String,
+    /// Value in the later result (empty if the column was not shown)
+    pub after: String,
+}
+
+/// A row present in both results with different values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowChange {
+    /// Task UUID the rows were matched on
+    pub key: String,
+    pub before: ReportRow,
+    pub after: ReportRow,
+    /// Differing columns, ordered by column name
+    pub fields: Vec<FieldChange>,
+}
+
+/// Rows added, removed and changed between two report results
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    /// Rows only in the later result, in its order
+    pub added: Vec<ReportRow>,
+    /// Rows only in the earlier result, in its order
+    pub removed: Vec<ReportRow>,
+    /// Rows in both whose values differ, in the later result's order
+    pub changed: Vec<RowChange>,
+}
+
+impl ReportDiff {
+    /// Compare `before` with `after`.
+    ///
+    /// Rows are keyed by their `uuid` column, or the `id` column that
+    /// built-in reports fill with the task UUID. Reports without either
+    /// (e.g. `projects`) are keyed by their first column. Rows of grouped
+    /// reports that only appear in a group are included; a row listed
+    /// several times is compared once.
+    pub fn between(before: &ReportResult, after: &ReportResult) -> Self {
+        let old = keyed_rows(before);
+        let new = keyed_rows(after);
+        let old_index: HashMap<&str, &ReportRow> =
+            old.iter().map(|(k, r)| (k.as_str(), *r)).collect();
+        let new_keys: BTreeSet<&str> = new.iter().map(|(k, _)| k.as_str()).collect();
+
+        let mut diff = Self::default();
+        for (key, row) in &new {
+            match old_index.get(key.as_str()) {
+                None => diff.added.push((*row).clone()),
+                Some(old_row) => {
+                    let fields = field_changes(old_row, row);
+                    if !fields.is_empty() {
+                        diff.changed.push(RowChange {
+                            key: key.clone(),
+                            before: (*old_row).clone(),
+                            after: (*row).clone(),
+                            fields,
+                        });
+                    }
+                }
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|(key, _)| !new_keys.contains(key.as_str()))
+            .map(|(_, row)| (*row).clone())
+            .collect();
+        diff
+    }
+
+    /// Whether the two results had the same rows
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Plain-text listing, one line per row and one indented line per changed
+/// column, suitable for an email body
+impl fmt::Display for ReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        for row in &self.added {
+            writeln!(f, "+ {}", label(row))?;
+        }
+        for row in &self.removed {
+            writeln!(f, "- {}", label(row))?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {}", label(&change.after))?;
+            for field in &change.fields {
+                writeln!(
+                    f,
+                    "    {}: {:?} -> {:?}",
+                    field.column, field.before, field.after
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Column rows are matched on
+fn key_column(result: &ReportResult) -> Option<&str> {
+    ["uuid", "id"]
+        .into_iter()
+        .find(|c| result.headers.iter().any(|h| h == c))
+        .or_else(|| result.headers.first().map(String::as_str))
+}
+
+/// Rows with a non-empty key, first occurrence of each key only
+fn keyed_rows(result: &ReportResult) -> Vec<(String, &ReportRow)> {
+    let Some(column) = key_column(result) else {
+        return Vec::new();
+    };
+    let mut seen = BTreeSet::new();
+    result
+        .rows
+        .iter()
+        .chain(result.groups.iter().flat_map(|g| &g.rows))
+        .filter_map(|row| {
+            let key = row.values.get(column).filter(|k| !k.is_empty())?;
+            seen.insert(key.clone()).then(|| (key.clone(), row))
+        })
+        .collect()
+}
+
+fn field_changes(before: &ReportRow, after: &ReportRow) -> Vec<FieldChange> {
+    let columns: BTreeSet<&String> = before.values.keys().chain(after.values.keys()).collect();
+    columns
+        .into_iter()
+        .filter_map(|column| {
+            let old = before
+                .values
+                .get(column)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let new = after
+                .values
+                .get(column)
+                .map(String::as_str)
+                .unwrap_or_default();
+            (old != new).then(|| FieldChange {
+                column: column.clone(),
+                before: old.to_string(),
+                after: new.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Description if the report shows one, otherwise the row's key
+fn label(row: &ReportRow) -> &str {
+    ["description", "uuid", "id"]
+        .iter()
+        .find_map(|c| row.values.get(*c).filter(|v| !v.is_empty()))
+        .map(String::as_str)
+        .unwrap_or("(row)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reports::builtin::{
+        default_config_for_report, BuiltinReports, ReportConfig, ReportType,
+    };
+    use crate::task::Task;
+
+    fn list(tasks: &[Task]) -> ReportResult {
+        let config = ReportConfig {
+            columns: vec![
+                "id".to_string(),
+                "description".to_string(),
+                "project".to_string(),
+            ],
+            ..default_config_for_report(ReportType::List)
+        };
+        BuiltinReports::new()
+            .generate_report(tasks, &config)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_added_removed_and_changed() {
+        let kept = Task::new("Kept".to_string());
+        let mut edited = Task::new("Edited".to_string());
+        let gone = Task::new("Gone".to_string());
+        let yesterday = list(&[kept.clone(), edited.clone(), gone.clone()]);
+
+        edited.project = Some("Home".to_string());
+        let new = Task::new("New".to_string());
+        let today = list(&[new.clone(), edited.clone(), kept]);
+
+        let diff = ReportDiff::between(&yesterday, &today);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].values["id"], new.id.to_string());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].values["description"], "Gone");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, edited.id.to_string());
+        assert_eq!(
+            diff.changed[0].fields,
+            vec![FieldChange {
+                column: "project".to_string(),
+                before: String::new(),
+                after: "Home".to_string(),
+            }]
+        );
+
+        let text = diff.to_string();
+        assert!(text.contains("+ New"));
+        assert!(text.contains("- Gone"));
+        assert!(text.contains("~ Edited\n    project: \"\" -> \"Home\""));
+    }
+
+    #[test]
+    fn test_identical_results_have_no_diff() {
+        let tasks = vec![Task::new("Same".to_string())];
+        let diff = ReportDiff::between(&list(&tasks), &list(&tasks));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No changes\n");
+    }
+}
@@ -0,0 +1,400 @@
+//! "What happened today" digest
+//!
+//! [`DigestBuilder`] sums up one local calendar day: the tasks added,
+//! completed, deleted and otherwise modified, grouped by project, the syncs
+//! that ran and how the number of overdue tasks changed. The [`Digest`]
+//! serializes for tooling and renders as Markdown for end-of-day hook
+//! emails and standup notes:
+//!
+//! ```text
+//! # Tasks on 2025-03-12
+//!
+//! 2 added, 1 completed, 1 modified. Overdue: 3 → 2 (-1).
+//!
+//! ## Work
+//!
+//! - Added: Write report
+//! - Completed: Fix login bug
+//! ```
+//!
+//! Tasks carry no history, so "modified" means last modified that day; a
+//! task changed again since then is not listed. Syncs are not stored at
+//! all: pass the ones you saw, e.g. from the daemon's
+//! [`ServiceEvent`](crate::daemon::ServiceEvent)s.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::clock;
+use crate::reports::builtin::NO_GROUP;
+use crate::task::manager::SyncResult;
+use crate::task::{Task, TaskStatus};
+
+/// A task mentioned in a digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestTask {
+    pub id: Uuid,
+    pub description: String,
+}
+
+impl From<&Task> for DigestTask {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id,
+            description: task.description.clone(),
+        }
+    }
+}
+
+/// The day's changes in one project
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ProjectDigest {
+    /// Project name, or `(none)`
+    pub project: String,
+    pub added: Vec<DigestTask>,
+    pub completed: Vec<DigestTask>,
+    pub deleted: Vec<DigestTask>,
+    /// Changed but neither added, completed nor deleted that day
+    pub modified: Vec<DigestTask>,
+}
+
+/// A sync attempt to include in a digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncEvent {
+    pub time: DateTime<Utc>,
+    pub pulled: usize,
+    pub pushed: usize,
+    /// Error message of a failed sync
+    pub error: Option<String>,
+}
+
+impl SyncEvent {
+    pub fn synced(time: DateTime<Utc>, result: &SyncResult) -> Self {
+        Self {
+            time,
+            pulled: result.tasks_pulled,
+            pushed: result.tasks_pushed,
+            error: None,
+        }
+    }
+
+    pub fn failed<S: Into<String>>(time: DateTime<Utc>, error: S) -> Self {
+        Self {
+            time,
+            pulled: 0,
+            pushed: 0,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Summary of one day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Digest {
+    pub day: NaiveDate,
+    /// Projects with changes, by name
+    pub projects: Vec<ProjectDigest>,
+    /// Syncs during the day, in order
+    pub syncs: Vec<SyncEvent>,
+    /// Overdue tasks when the day began
+    pub overdue_before: usize,
+    /// Overdue tasks when the day ended, or now for today
+    pub overdue_after: usize,
+}
+
+impl Digest {
+    pub fn added(&self) -> usize {
+        self.projects.iter().map(|p| p.added.len()).sum()
+    }
+
+    pub fn completed(&self) -> usize {
+        self.projects.iter().map(|p| p.completed.len()).sum()
+    }
+
+    pub fn deleted(&self) -> usize {
+        self.projects.iter().map(|p| p.deleted.len()).sum()
+    }
+
+    pub fn modified(&self) -> usize {
+        self.projects.iter().map(|p| p.modified.len()).sum()
+    }
+
+    /// Change in the number of overdue tasks over the day
+    pub fn overdue_change(&self) -> i64 {
+        self.overdue_after as i64 - self.overdue_before as i64
+    }
+
+    /// Whether nothing happened
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.syncs.is_empty() && self.overdue_change() == 0
+    }
+
+    /// The digest as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Tasks on {}\n\n", self.day);
+        let mut counts = Vec::new();
+        for (count, label) in [
+            (self.added(), "added"),
+            (self.completed(), "completed"),
+            (self.deleted(), "deleted"),
+            (self.modified(), "modified"),
+        ] {
+            if count > 0 {
+                counts.push(format!("{count} {label}"));
+            }
+        }
+        if counts.is_empty() {
+            out.push_str("No task changes.");
+        } else {
+            out.push_str(&counts.join(", "));
+            out.push('.');
+        }
+        let _ = writeln!(
+            out,
+            " Overdue: {} → {} ({:+}).",
+            self.overdue_before,
+            self.overdue_after,
+            self.overdue_change()
+        );
+
+        for project in &self.projects {
+            let _ = writeln!(out, "\n## {}\n", project.project);
+            for (label, tasks) in [
+                ("Added", &project.added),
+                ("Completed", &project.completed),
+                ("Deleted", &project.deleted),
+                ("Modified", &project.modified),
+            ] {
+                for task in tasks {
+                    let _ = writeln!(out, "- {label}: {}", task.description);
+                }
+            }
+        }
+
+        if !self.syncs.is_empty() {
+            out.push_str("\n## Sync\n\n");
+            for sync in &self.syncs {
+                let time = sync.time.with_timezone(&Local).format("%H:%M");
+                match &sync.error {
+                    Some(error) => {
+                        let _ = writeln!(out, "- {time} failed: {error}");
+                    }
+                    None => {
+                        let _ = writeln!(
+                            out,
+                            "- {time} pulled {}, pushed {}",
+                            sync.pulled, sync.pushed
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Collects what is needed for a [`Digest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestBuilder {
+    day: NaiveDate,
+    syncs: Vec<SyncEvent>,
+}
+
+impl DigestBuilder {
+    /// Digest of the local calendar day `day`
+    pub fn new(day: NaiveDate) -> Self {
+        Self {
+            day,
+            syncs: Vec::new(),
+        }
+    }
+
+    /// Digest of the current local day
+    pub fn today() -> Self {
+        Self::new(clock::now().with_timezone(&Local).date_naive())
+    }
+
+    /// Include `events`; those outside the day are ignored
+    pub fn syncs(mut self, events: impl IntoIterator<Item = SyncEvent>) -> Self {
+        self.syncs.extend(events);
+        self
+    }
+
+    /// Summarize the day from `tasks`, which should include completed and
+    /// deleted tasks
+    pub fn build(&self, tasks: &[Task]) -> Digest {
+        let start = local_midnight(self.day);
+        let end = local_midnight(self.day + Duration::days(1));
+        let during = |time: DateTime<Utc>| start <= time && time < end;
+
+        let mut projects: BTreeMap<String, ProjectDigest> = BTreeMap::new();
+        for task in tasks {
+            let ended = task.end.filter(|end| during(*end));
+            let list = match task.status {
+                TaskStatus::Completed if ended.is_some() => Some(Section::Completed),
+                TaskStatus::Deleted if ended.is_some() => Some(Section::Deleted),
+                _ if during(task.entry) => Some(Section::Added),
+                _ if task.modified.is_some_and(during) => Some(Section::Modified),
+                _ => None,
+            };
+            let Some(list) = list else { continue };
+            let name = task.project.clone().unwrap_or_else(|| NO_GROUP.to_string());
+            let project = projects
+                .entry(name.clone())
+                .or_insert_with(|| ProjectDigest {
+                    project: name,
+                    ..Default::default()
+                });
+            let section = match list {
+                Section::Added => &mut project.added,
+                Section::Completed => &mut project.completed,
+                Section::Deleted => &mut project.deleted,
+                Section::Modified => &mut project.modified,
+            };
+            section.push(DigestTask::from(task));
+            // A task both added and finished today counts as added as well
+            if list != Section::Added && during(task.entry) {
+                project.added.push(DigestTask::from(task));
+            }
+        }
+
+        let mut syncs: Vec<SyncEvent> = self
+            .syncs
+            .iter()
+            .filter(|s| during(s.time))
+            .cloned()
+            .collect();
+        syncs.sort_by_key(|s| s.time);
+
+        Digest {
+            day: self.day,
+            projects: projects.into_values().collect(),
+            syncs,
+            overdue_before: overdue_at(tasks, start),
+            overdue_after: overdue_at(tasks, end.min(clock::now())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Added,
+    Completed,
+    Deleted,
+    Modified,
+}
+
+fn local_midnight(day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight).with_timezone(&Local))
+        .with_timezone(&Utc)
+}
+
+// Tasks that existed, were unfinished and past due at `time`
+fn overdue_at(tasks: &[Task], time: DateTime<Utc>) -> usize {
+    tasks
+        .iter()
+        .filter(|task| task.status != TaskStatus::Recurring)
+        .filter(|task| task.entry <= time && task.due.is_some_and(|due| due < time))
+        .filter(|task| match task.status {
+            TaskStatus::Completed | TaskStatus::Deleted => task.end.is_some_and(|end| end > time),
+            _ => true,
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: NaiveDate, hour: u32) -> DateTime<Utc> {
+        Local
+            .from_local_datetime(&day.and_hms_opt(hour, 0, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_digest_groups_changes_by_project() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let yesterday = day - Duration::days(1);
+
+        let mut report = Task::new("Write report".to_string());
+        report.entry = at(day, 9);
+        report.project = Some("Work".to_string());
+
+        let mut bug = Task::new("Fix login bug".to_string());
+        bug.entry = at(yesterday, 9);
+        bug.project = Some("Work".to_string());
+        bug.due = Some(at(yesterday, 17));
+        bug.status = TaskStatus::Completed;
+        bug.end = Some(at(day, 11));
+
+        let mut plants = Task::new("Water plants".to_string());
+        plants.entry = at(yesterday, 8);
+        plants.due = Some(at(day, 12));
+        plants.modified = Some(at(day, 10));
+
+        let mut old = Task::new("Untouched".to_string());
+        old.entry = at(yesterday, 8);
+        old.modified = Some(at(yesterday, 8));
+
+        let syncs = [
+            SyncEvent::failed(at(day, 13), "network down"),
+            SyncEvent::synced(
+                at(day, 10),
+                &SyncResult {
+                    tasks_pulled: 3,
+                    tasks_pushed: 1,
+                    conflicts_resolved: 0,
+                    tasks_purged: 0,
+                    purge_error: None,
+                },
+            ),
+            SyncEvent::failed(at(yesterday, 10), "too early"),
+        ];
+        let digest = DigestBuilder::new(day)
+            .syncs(syncs)
+            .build(&[report, bug, plants, old]);
+
+        let names: Vec<&str> = digest.projects.iter().map(|p| p.project.as_str()).collect();
+        assert_eq!(names, ["(none)", "Work"]);
+        assert_eq!(digest.projects[0].modified[0].description, "Water plants");
+        assert_eq!(digest.projects[1].added[0].description, "Write report");
+        assert_eq!(digest.projects[1].completed[0].description, "Fix login bug");
+        assert_eq!(
+            (digest.added(), digest.completed(), digest.modified()),
+            (1, 1, 1)
+        );
+        assert_eq!(digest.syncs.len(), 2);
+        assert!(digest.syncs[0].error.is_none());
+        // The bug was overdue at midnight; the plants were by the evening
+        assert_eq!((digest.overdue_before, digest.overdue_after), (1, 1));
+
+        let markdown = digest.to_markdown();
+        assert!(markdown.starts_with(
+            "# Tasks on 2025-03-12\n\n1 added, 1 completed, 1 modified. Overdue: 1 → 1 (+0).\n"
+        ));
+        assert!(
+            markdown.contains("\n## Work\n\n- Added: Write report\n- Completed: Fix login bug\n")
+        );
+        assert!(markdown.contains("- 10:00 pulled 3, pushed 1\n- 13:00 failed: network down\n"));
+    }
+
+    #[test]
+    fn test_quiet_day() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let digest = DigestBuilder::new(day).build(&[]);
+        assert!(digest.is_empty());
+        assert!(digest
+            .to_markdown()
+            .contains("No task changes. Overdue: 0 → 0 (+0)."));
+    }
+}
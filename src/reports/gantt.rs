@@ -0,0 +1,201 @@
+//! Gantt-style schedule data
+//!
+//! [`GanttChart`] turns tasks with a due date into bars running from the
+//! task's `scheduled` date (or its entry date) to its due date, in one lane
+//! per project. Bars in the same lane whose time ranges intersect are
+//! flagged as overlapping, which is where a plan asks for two things at
+//! once. Planning views can draw the bars themselves or use
+//! [`GanttChart::render_text`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::builtin::NO_GROUP;
+use super::layout;
+use crate::task::Task;
+
+/// Longest task label shown by [`GanttChart::render_text`]
+const MAX_LABEL: usize = 30;
+
+/// One task's scheduled time range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GanttBar {
+    pub id: Uuid,
+    pub description: String,
+    /// `scheduled`, or `entry` if unscheduled; never after `end`
+    pub start: DateTime<Utc>,
+    /// `due`
+    pub end: DateTime<Utc>,
+    /// Bars in the same lane whose range intersects this one
+    pub overlaps: Vec<Uuid>,
+}
+
+/// The bars of one project, ordered by start
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GanttLane {
+    /// Project name, `None` for tasks without a project
+    pub project: Option<String>,
+    pub bars: Vec<GanttBar>,
+}
+
+/// Bars for every task with a due date, in lanes ordered by project name
+/// (tasks without a project last)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GanttChart {
+    pub lanes: Vec<GanttLane>,
+}
+
+impl GanttChart {
+    /// Build the chart for `tasks`. Tasks without a due date are left out.
+    pub fn from_tasks(tasks: &[Task]) -> Self {
+        let mut lanes: Vec<GanttLane> = Vec::new();
+        for task in tasks {
+            let Some(end) = task.due else {
+                continue;
+            };
+            let bar = GanttBar {
+                id: task.id,
+                description: task.description.clone(),
+                start: task.scheduled.unwrap_or(task.entry).min(end),
+                end,
+                overlaps: Vec::new(),
+            };
+            match lanes.iter_mut().find(|l| l.project == task.project) {
+                Some(lane) => lane.bars.push(bar),
+                None => lanes.push(GanttLane {
+                    project: task.project.clone(),
+                    bars: vec![bar],
+                }),
+            }
+        }
+
+        lanes.sort_by(|a, b| match (&a.project, &b.project) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        for lane in &mut lanes {
+            lane.bars.sort_by_key(|b| (b.start, b.end, b.id));
+            for i in 0..lane.bars.len() {
+                let (start, end) = (lane.bars[i].start, lane.bars[i].end);
+                lane.bars[i].overlaps = lane
+                    .bars
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, other)| *j != i && other.start < end && start < other.end)
+                    .map(|(_, other)| other.id)
+                    .collect();
+            }
+        }
+        Self { lanes }
+    }
+
+    /// Every bar, lane by lane
+    pub fn bars(&self) -> impl Iterator<Item = &GanttBar> {
+        self.lanes.iter().flat_map(|l| &l.bars)
+    }
+
+    /// Earliest start and latest end of all bars
+    pub fn span(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let start = self.bars().map(|b| b.start).min()?;
+        let end = self.bars().map(|b| b.end).max()?;
+        Some((start, end))
+    }
+
+    /// Plain-text chart with a timeline `width` characters wide. Each lane
+    /// starts with its project name; overlapping bars are drawn with `=`
+    /// instead of `#` and marked with `*`.
+    pub fn render_text(&self, width: usize) -> String {
+        let Some((first, last)) = self.span() else {
+            return "No scheduled tasks\n".to_string();
+        };
+        let width = width.max(1);
+        let total = (last - first).num_seconds().max(1) as f64;
+        let column = |t: DateTime<Utc>| {
+            let offset = (t - first).num_seconds() as f64 / total;
+            ((offset * width as f64) as usize).min(width - 1)
+        };
+        let label_width = self
+            .bars()
+            .map(|b| layout::text_width(&b.description).min(MAX_LABEL))
+            .max()
+            .unwrap_or(0);
+
+        let mut out = format!(
+            "{:w$}   {} .. {}\n",
+            "",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d"),
+            w = label_width + 2
+        );
+        for lane in &self.lanes {
+            out.push_str(lane.project.as_deref().unwrap_or(NO_GROUP));
+            out.push('\n');
+            for bar in &lane.bars {
+                let fill = if bar.overlaps.is_empty() { '#' } else { '=' };
+                let (from, to) = (column(bar.start), column(bar.end));
+                let timeline: String = (0..width)
+                    .map(|c| if (from..=to).contains(&c) { fill } else { ' ' })
+                    .collect();
+                let label = layout::pad(&bar.description, label_width);
+                let marker = if bar.overlaps.is_empty() { ' ' } else { '*' };
+                out.push_str(&format!("  {label} {marker}|{timeline}|\n"));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task(description: &str, project: Option<&str>, start_day: u32, due_day: u32) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.project = project.map(str::to_string);
+        task.scheduled = Some(Utc.with_ymd_and_hms(2025, 3, start_day, 0, 0, 0).unwrap());
+        task.due = Some(Utc.with_ymd_and_hms(2025, 3, due_day, 0, 0, 0).unwrap());
+        task
+    }
+
+    #[test]
+    fn test_lanes_and_overlaps() {
+        let design = task("Design", Some("Web"), 1, 5);
+        let build = task("Build", Some("Web"), 4, 10);
+        let ship = task("Ship", Some("Web"), 10, 11);
+        let errand = task("Errand", None, 2, 3);
+        let mut someday = Task::new("Someday".to_string());
+        someday.project = Some("Web".to_string());
+        let tasks = vec![ship.clone(), errand, build.clone(), someday, design.clone()];
+
+        let chart = GanttChart::from_tasks(&tasks);
+        assert_eq!(chart.lanes.len(), 2);
+        assert_eq!(chart.lanes[0].project.as_deref(), Some("Web"));
+        assert_eq!(chart.lanes[1].project, None);
+        let web: Vec<&str> = chart.lanes[0]
+            .bars
+            .iter()
+            .map(|b| b.description.as_str())
+            .collect();
+        assert_eq!(web, vec!["Design", "Build", "Ship"]);
+        assert_eq!(chart.lanes[0].bars[0].overlaps, vec![build.id]);
+        assert_eq!(chart.lanes[0].bars[1].overlaps, vec![design.id]);
+        // Ship starts when Build ends, which is not an overlap
+        assert!(chart.lanes[0].bars[2].overlaps.is_empty());
+        assert_eq!(
+            chart.span().unwrap().1,
+            Utc.with_ymd_and_hms(2025, 3, 11, 0, 0, 0).unwrap()
+        );
+
+        let text = chart.render_text(10);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "           2025-03-01 .. 2025-03-11");
+        assert_eq!(lines[1], "Web");
+        assert_eq!(lines[2], "  Design *|=====     |");
+        assert_eq!(lines[3], "  Build  *|   =======|");
+        assert_eq!(lines[4], "  Ship    |         #|");
+        assert_eq!(lines[5], "(none)");
+        assert_eq!(lines[6], "  Errand  | ##       |");
+    }
+}
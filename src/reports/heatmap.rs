@@ -0,0 +1,165 @@
+//! Completions-per-day calendar
+//!
+//! [`CompletionCalendar`] buckets completed tasks by the local day they
+//! were finished, over a date range, the way contribution graphs show
+//! activity. Every day in the range has an entry, so frontends can draw
+//! the grid directly, and the current and longest streaks of days with at
+//! least one completion are computed along the way.
+
+use chrono::{Duration, Local, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::task::{Task, TaskStatus};
+
+/// Completions on one day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub completed: usize,
+}
+
+/// Completions per day between two dates, inclusive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionCalendar {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// One entry per day from `start` to `end`
+    pub days: Vec<DayCount>,
+    /// Completions in the range
+    pub total: usize,
+    /// Most completions on a single day
+    pub max_per_day: usize,
+    /// Consecutive days with completions up to `end`. A streak that ran
+    /// until the day before still counts while `end` has none yet.
+    pub current_streak: usize,
+    /// Longest run of consecutive days with completions in the range
+    pub longest_streak: usize,
+}
+
+impl CompletionCalendar {
+    /// Count completions of `tasks` per local day from `start` to `end`.
+    /// Tasks that are not completed or have no end date are ignored.
+    pub fn new(tasks: &[Task], start: NaiveDate, end: NaiveDate) -> Self {
+        let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+        for task in tasks {
+            if task.status != TaskStatus::Completed {
+                continue;
+            }
+            if let Some(finished) = task.end {
+                let day = finished.with_timezone(&Local).date_naive();
+                if (start..=end).contains(&day) {
+                    *counts.entry(day).or_default() += 1;
+                }
+            }
+        }
+
+        let days: Vec<DayCount> = start
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .map(|date| DayCount {
+                date,
+                completed: counts.get(&date).copied().unwrap_or(0),
+            })
+            .collect();
+
+        let mut longest_streak = 0;
+        let mut run = 0;
+        for day in &days {
+            run = if day.completed > 0 { run + 1 } else { 0 };
+            longest_streak = longest_streak.max(run);
+        }
+        let mut recent = days.iter().rev().peekable();
+        if recent.peek().is_some_and(|d| d.completed == 0) {
+            recent.next();
+        }
+        let current_streak = recent.take_while(|d| d.completed > 0).count();
+
+        Self {
+            start,
+            end,
+            total: days.iter().map(|d| d.completed).sum(),
+            max_per_day: days.iter().map(|d| d.completed).max().unwrap_or(0),
+            days,
+            current_streak,
+            longest_streak,
+        }
+    }
+
+    /// Calendar for the `months` months up to and including today
+    pub fn past_months(tasks: &[Task], months: u32) -> Self {
+        let today = Local::now().date_naive();
+        let start = today
+            .checked_sub_months(Months::new(months))
+            .map_or(NaiveDate::MIN, |d| d + Duration::days(1));
+        Self::new(tasks, start, today)
+    }
+
+    /// Completions on `date` (0 outside the range)
+    pub fn count(&self, date: NaiveDate) -> usize {
+        (date - self.start)
+            .num_days()
+            .try_into()
+            .ok()
+            .and_then(|i: usize| self.days.get(i))
+            .map_or(0, |d| d.completed)
+    }
+
+    /// Shade from 0 (no completions) to 4 (the busiest day) for drawing
+    /// the grid
+    pub fn level(&self, date: NaiveDate) -> u8 {
+        let count = self.count(date);
+        if count == 0 || self.max_per_day == 0 {
+            return 0;
+        }
+        (count * 4).div_ceil(self.max_per_day).clamp(1, 4) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn done_on(date: NaiveDate) -> Task {
+        let mut task = Task::new("Done".to_string());
+        task.complete();
+        let noon = date.and_hms_opt(12, 0, 0).unwrap();
+        task.end = Some(
+            Local
+                .from_local_datetime(&noon)
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        task
+    }
+
+    #[test]
+    fn test_counts_and_streaks() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        let mut tasks: Vec<Task> = [1, 2, 2, 3, 3, 3, 3, 5, 8, 9]
+            .into_iter()
+            .map(|d| done_on(day(d)))
+            .collect();
+        tasks.push(Task::new("Still open".to_string()));
+        tasks.push(done_on(day(20)));
+
+        let calendar = CompletionCalendar::new(&tasks, day(1), day(10));
+        assert_eq!(calendar.days.len(), 10);
+        assert_eq!(calendar.total, 10);
+        assert_eq!(calendar.max_per_day, 4);
+        assert_eq!(calendar.count(day(2)), 2);
+        assert_eq!(calendar.count(day(20)), 0);
+        assert_eq!(calendar.longest_streak, 3);
+        // Nothing on the 10th yet, so the streak ending on the 9th holds
+        assert_eq!(calendar.current_streak, 2);
+        assert_eq!([1, 2, 3, 4].map(|d| calendar.level(day(d))), [1, 2, 4, 0]);
+
+        let broken = CompletionCalendar::new(&tasks, day(1), day(11));
+        assert_eq!(broken.current_streak, 0);
+
+        let recent = CompletionCalendar::past_months(&[done_on(Local::now().date_naive())], 3);
+        assert_eq!(recent.current_streak, 1);
+        assert!(recent.days.len() >= 89);
+    }
+}
@@ -0,0 +1,237 @@
+//! Fitting tables to a terminal
+//!
+//! Report consumers know how big their screen is; reports do not. A
+//! [`Viewport`] carries the available rows and columns from the consumer to
+//! [`ReportManager::generate_for_viewport`](super::ReportManager::generate_for_viewport),
+//! which resolves `limit:page`, and to
+//! [`ReportManager::output_table_fitted`](super::ReportManager::output_table_fitted),
+//! which narrows columns and truncates values with an ellipsis so no line
+//! wraps.
+//!
+//! Widths are terminal columns, not bytes or characters: CJK characters
+//! and most emoji take two columns, combining marks none, and truncation
+//! never splits a grapheme cluster.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marker appended to truncated values
+pub const ELLIPSIS: char = '…';
+
+/// Separator between table columns
+pub const COLUMN_SEPARATOR: &str = " | ";
+
+/// Lines of a page taken by other things than task rows: the header, the
+/// separator under it and the prompt after the output
+pub const PAGE_OVERHEAD: usize = 3;
+
+/// Space available to a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Viewport {
+    pub rows: usize,
+    pub columns: usize,
+}
+
+impl Viewport {
+    pub fn new(rows: usize, columns: usize) -> Self {
+        Self { rows, columns }
+    }
+
+    /// Task rows shown by a `limit:page` report, at least one
+    pub fn page_rows(&self) -> usize {
+        self.rows.saturating_sub(PAGE_OVERHEAD).max(1)
+    }
+}
+
+/// Bounds on a column's width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct WidthHint {
+    /// Narrowest the column is squeezed to; its header width when 0
+    pub min: usize,
+    /// Widest the column grows to, even when there is room
+    pub max: Option<usize>,
+}
+
+impl WidthHint {
+    /// A column that always takes `width`
+    pub fn fixed(width: usize) -> Self {
+        Self {
+            min: width,
+            max: Some(width),
+        }
+    }
+
+    /// A column never wider than `max`
+    pub fn at_most(max: usize) -> Self {
+        Self {
+            min: 0,
+            max: Some(max),
+        }
+    }
+
+    /// A column never squeezed below `min`
+    pub fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+}
+
+/// How a table is fitted to its viewport
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableLayout {
+    /// Total line width to fit; unlimited when None
+    pub width: Option<usize>,
+    /// Width hints by column name
+    pub hints: HashMap<String, WidthHint>,
+}
+
+impl TableLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fit lines into the viewport's columns
+    pub fn for_viewport(viewport: Viewport) -> Self {
+        Self {
+            width: Some(viewport.columns),
+            hints: HashMap::new(),
+        }
+    }
+
+    /// Bound the width of `column`
+    pub fn with_hint<S: Into<String>>(mut self, column: S, hint: WidthHint) -> Self {
+        self.hints.insert(column.into(), hint);
+        self
+    }
+
+    /// Widths for `headers` whose content needs `natural` widths.
+    ///
+    /// Hinted maximums apply first. While the line is too wide, the column
+    /// with the most room above its minimum gives up a character, so wide
+    /// free-text columns such as `description` shrink before narrow ones.
+    /// When every column is at its minimum the line stays too wide.
+    pub fn fit(&self, headers: &[String], natural: &HashMap<String, usize>) -> HashMap<String, usize> {
+        let mut widths: Vec<usize> = Vec::with_capacity(headers.len());
+        let mut mins: Vec<usize> = Vec::with_capacity(headers.len());
+        for header in headers {
+            let hint = self.hints.get(header).copied().unwrap_or_default();
+            let mut width = natural.get(header).copied().unwrap_or(0).max(text_width(header));
+            if let Some(max) = hint.max {
+                width = width.min(max);
+            }
+            width = width.max(hint.min);
+            let min = if hint.min == 0 { text_width(header) } else { hint.min };
+            widths.push(width);
+            mins.push(min.min(width));
+        }
+
+        if let Some(limit) = self.width {
+            let separators = COLUMN_SEPARATOR.len() * headers.len().saturating_sub(1);
+            let mut total: usize = widths.iter().sum::<usize>() + separators;
+            while total > limit {
+                let widest = (0..widths.len())
+                    .filter(|&i| widths[i] > mins[i])
+                    .max_by_key(|&i| (widths[i] - mins[i], widths[i]));
+                match widest {
+                    Some(i) => {
+                        widths[i] -= 1;
+                        total -= 1;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        headers.iter().cloned().zip(widths).collect()
+    }
+}
+
+/// Columns `value` takes on screen
+pub fn text_width(value: &str) -> usize {
+    value.width()
+}
+
+/// `value` cut to `width` columns, ending in [`ELLIPSIS`] when shortened
+pub fn truncate(value: &str, width: usize) -> String {
+    if text_width(value) <= width {
+        return value.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for grapheme in value.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if used + grapheme_width > width - 1 {
+            break;
+        }
+        truncated.push_str(grapheme);
+        used += grapheme_width;
+    }
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// `value` truncated to `width` and padded with spaces to exactly `width`
+pub fn pad(value: &str, width: usize) -> String {
+    let truncated = truncate(value, width);
+    let padding = width.saturating_sub(text_width(&truncated));
+    format!("{truncated}{}", " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate("Buy milk", 20), "Buy milk");
+        assert_eq!(truncate("Buy milk", 5), "Buy …");
+        assert_eq!(truncate("Buy milk", 0), "");
+        assert_eq!(pad("ab", 4), "ab  ");
+    }
+
+    #[test]
+    fn test_fit_shrinks_widest_column_first() {
+        let headers = headers(&["id", "description", "project"]);
+        let natural: HashMap<String, usize> =
+            [("id", 2), ("description", 40), ("project", 10)]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect();
+
+        // 2 + 40 + 10 plus two separators is 58 columns
+        let unbounded = TableLayout::new().fit(&headers, &natural);
+        assert_eq!(unbounded["description"], 40);
+
+        let fitted = TableLayout::for_viewport(Viewport::new(24, 40)).fit(&headers, &natural);
+        assert_eq!(fitted["id"], 2);
+        assert_eq!(fitted["project"], 10);
+        assert_eq!(fitted["description"], 22);
+
+        let hinted = TableLayout::for_viewport(Viewport::new(24, 40))
+            .with_hint("description", WidthHint::at_least(30))
+            .fit(&headers, &natural);
+        assert_eq!(hinted["description"], 30);
+        assert_eq!(hinted["project"], 7);
+    }
+
+    #[test]
+    fn test_widths_count_terminal_columns() {
+        assert_eq!(text_width("日本語"), 6);
+        assert_eq!(text_width("e\u{301}te\u{301}"), 3);
+        assert_eq!(text_width("🎉 party"), 8);
+
+        // A wide character that does not fit is dropped, not split
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(pad("日本語", 4), "日… ");
+        assert_eq!(truncate("e\u{301}te\u{301}", 2), "e\u{301}…");
+    }
+}
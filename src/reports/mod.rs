@@ -3,12 +3,23 @@
 //! This module provides comprehensive reporting functionality including
 //! built-in reports, custom report definitions, and various output formats.
 
+pub mod activity;
 pub mod builtin;
+pub mod diff;
+pub mod digest;
+pub mod gantt;
+pub mod heatmap;
+pub mod layout;
+#[cfg(feature = "sqlite-export")]
+pub mod sqlite;
+#[cfg(feature = "xlsx-export")]
+pub mod xlsx;
 
 use crate::error::TaskError;
 use crate::query::TaskQuery;
 use crate::task::Task;
 use builtin::{BuiltinReports, ReportConfig, ReportFormat, ReportResult, ReportType};
+use layout::{TableLayout, Viewport, COLUMN_SEPARATOR};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Write;
@@ -77,6 +88,29 @@ impl ReportManager {
         }
     }
 
+    /// Report manager with urgency coefficients, priority scheme and the
+    /// `report.<name>.*` reports taken from configuration
+    pub fn from_config(config: &crate::config::Configuration) -> Self {
+        let custom_reports = config
+            .settings
+            .keys()
+            .filter_map(|key| key.strip_prefix("report.")?.strip_suffix(".columns"))
+            .filter_map(|name| Some((name.to_string(), ReportConfig::from_config(config, name)?)))
+            .collect();
+        Self {
+            builtin_reports: BuiltinReports::from_config(config),
+            custom_reports,
+        }
+    }
+
+    /// Use a custom priority scheme (see [`Configuration::priority_scheme`])
+    ///
+    /// [`Configuration::priority_scheme`]: crate::config::Configuration::priority_scheme
+    pub fn with_priority_scheme(mut self, scheme: crate::config::PriorityScheme) -> Self {
+        self.builtin_reports = self.builtin_reports.with_priority_scheme(scheme);
+        self
+    }
+
     /// Add custom report configuration
     pub fn add_custom_report<S: Into<String>>(&mut self, name: S, config: ReportConfig) {
         self.custom_reports.insert(name.into(), config);
@@ -105,6 +139,9 @@ impl ReportManager {
             "projects" => Some(ReportType::Projects),
             "tags" => Some(ReportType::Tags),
             "burndown" => Some(ReportType::Burndown),
+            "blocked" => Some(ReportType::Blocked),
+            "blocking" => Some(ReportType::Blocking),
+            "gantt" => Some(ReportType::Gantt),
             _ => None,
         };
 
@@ -120,6 +157,36 @@ impl ReportManager {
         }
     }
 
+    /// Generate a report for a consumer showing `viewport`, so `limit:page`
+    /// reports get as many rows as fit
+    pub fn generate_for_viewport(
+        &self,
+        tasks: &[Task],
+        config: &ReportConfig,
+        viewport: Viewport,
+    ) -> Result<ReportResult, TaskError> {
+        if !config.page {
+            return self.builtin_reports.generate_report(tasks, config);
+        }
+        let paged = ReportConfig {
+            limit: Some(viewport.page_rows()),
+            page: false,
+            ..config.clone()
+        };
+        self.builtin_reports.generate_report(tasks, &paged)
+    }
+
+    /// Write `result` as a table fitted to `layout`: columns are narrowed
+    /// and values truncated with an ellipsis so lines do not wrap
+    pub fn output_table_fitted<W: Write>(
+        &self,
+        result: &ReportResult,
+        layout: &TableLayout,
+        writer: &mut W,
+    ) -> Result<(), TaskError> {
+        self.write_table(result, layout, writer)
+    }
+
     /// Format and output report
     pub fn output_report<W: Write>(
         &self,
@@ -132,6 +199,7 @@ impl ReportManager {
             ReportFormat::Json => self.format_json(result, writer),
             ReportFormat::Csv => self.format_csv(result, writer),
             ReportFormat::Simple => self.format_simple(result, writer),
+            ReportFormat::Markdown => self.format_markdown(result, writer),
         }
     }
 
@@ -140,30 +208,67 @@ impl ReportManager {
         &self,
         result: &ReportResult,
         writer: &mut W,
+    ) -> Result<(), TaskError> {
+        self.write_table(result, &TableLayout::default(), writer)
+    }
+
+    /// Write a table with column widths fitted to `layout`
+    fn write_table<W: Write>(
+        &self,
+        result: &ReportResult,
+        layout: &TableLayout,
+        writer: &mut W,
     ) -> Result<(), TaskError> {
         // Calculate column widths
-        let mut col_widths = HashMap::new();
+        let mut natural: HashMap<String, usize> = HashMap::new();
+        for row in &result.rows {
+            for (key, value) in &row.values {
+                let width = natural.entry(key.clone()).or_default();
+                *width = (*width).max(layout::text_width(value));
+            }
+        }
+        let col_widths = layout.fit(&result.headers, &natural);
 
-        // Check header widths
-        for header in &result.headers {
-            col_widths.insert(header.clone(), header.len());
+        if result.groups.is_empty() {
+            Self::write_table_section(result, &result.rows, &col_widths, writer)?;
+        } else {
+            // One table section per group, separated by a blank line
+            for (i, group) in result.groups.iter().enumerate() {
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                writeln!(writer, "{}", group.key)?;
+                Self::write_table_section(result, &group.rows, &col_widths, writer)?;
+                writeln!(writer, "{} task(s)", group.count)?;
+            }
         }
 
-        // Check data widths
-        for row in &result.rows {
-            for (key, value) in &row.values {
-                let current_width = col_widths.get(key).unwrap_or(&0);
-                col_widths.insert(key.clone(), (*current_width).max(value.len()));
+        // Write summary if present
+        if !result.summary.is_empty() {
+            writeln!(writer)?;
+            writeln!(writer, "Summary:")?;
+            for (key, value) in &result.summary {
+                writeln!(writer, "{key}: {value}")?;
             }
         }
 
+        Ok(())
+    }
+
+    /// Write table header, separator and the given rows
+    fn write_table_section<W: Write>(
+        result: &ReportResult,
+        rows: &[builtin::ReportRow],
+        col_widths: &HashMap<String, usize>,
+        writer: &mut W,
+    ) -> Result<(), TaskError> {
         // Write header
         for (i, header) in result.headers.iter().enumerate() {
             if i > 0 {
-                write!(writer, " | ")?;
+                write!(writer, "{COLUMN_SEPARATOR}")?;
             }
-            let width = col_widths.get(header).copied().unwrap_or(header.len());
-            write!(writer, "{header:<width$}")?;
+            let width = col_widths.get(header).copied().unwrap_or_else(|| layout::text_width(header));
+            write!(writer, "{}", layout::pad(header, width))?;
         }
         writeln!(writer)?;
 
@@ -172,30 +277,73 @@ impl ReportManager {
             if i > 0 {
                 write!(writer, "-+-")?;
             }
-            let width = col_widths.get(header).copied().unwrap_or(header.len());
+            let width = col_widths.get(header).copied().unwrap_or_else(|| layout::text_width(header));
             write!(writer, "{}", "-".repeat(width))?;
         }
         writeln!(writer)?;
 
         // Write data rows
-        for row in &result.rows {
+        for row in rows {
             for (i, header) in result.headers.iter().enumerate() {
                 if i > 0 {
-                    write!(writer, " | ")?;
+                    write!(writer, "{COLUMN_SEPARATOR}")?;
                 }
-                let value = row.values.get(header).cloned().unwrap_or_default();
-                let width = col_widths.get(header).copied().unwrap_or(header.len());
-                write!(writer, "{value:<width$}")?;
+                let value = row.values.get(header).map(String::as_str).unwrap_or_default();
+                let width = col_widths.get(header).copied().unwrap_or_else(|| layout::text_width(header));
+                write!(writer, "{}", layout::pad(value, width))?;
             }
             writeln!(writer)?;
         }
 
-        // Write summary if present
+        Ok(())
+    }
+
+    /// Format report as a Markdown table, with a heading per group
+    fn format_markdown<W: Write>(
+        &self,
+        result: &ReportResult,
+        writer: &mut W,
+    ) -> Result<(), TaskError> {
+        let write_rows = |rows: &[builtin::ReportRow], writer: &mut W| -> std::io::Result<()> {
+            writeln!(writer, "| {} |", result.headers.join(" | "))?;
+            writeln!(writer, "|{}", " --- |".repeat(result.headers.len()))?;
+            for row in rows {
+                let cells: Vec<String> = result
+                    .headers
+                    .iter()
+                    .map(|h| {
+                        row.values
+                            .get(h)
+                            .map(|v| v.replace('|', "\\|"))
+                            .unwrap_or_default()
+                    })
+                    .collect();
+                writeln!(writer, "| {} |", cells.join(" | "))?;
+            }
+            Ok(())
+        };
+
+        if result.groups.is_empty() {
+            write_rows(&result.rows, writer)?;
+        } else {
+            for (i, group) in result.groups.iter().enumerate() {
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                writeln!(writer, "### {}", group.key)?;
+                writeln!(writer)?;
+                write_rows(&group.rows, writer)?;
+                writeln!(writer)?;
+                writeln!(writer, "_{} task(s)_", group.count)?;
+            }
+        }
+
         if !result.summary.is_empty() {
             writeln!(writer)?;
-            writeln!(writer, "Summary:")?;
-            for (key, value) in &result.summary {
-                writeln!(writer, "{key}: {value}")?;
+            let mut summary: Vec<_> = result.summary.iter().collect();
+            summary.sort();
+            for (key, value) in summary {
+                writeln!(writer, "- **{key}**: {value}")?;
             }
         }
 
@@ -275,6 +423,9 @@ impl ReportManager {
             "projects".to_string(),
             "tags".to_string(),
             "burndown".to_string(),
+            "blocked".to_string(),
+            "blocking".to_string(),
+            "gantt".to_string(),
         ];
 
         // Add custom reports
@@ -316,6 +467,9 @@ impl ReportGenerator for ReportManager {
             ReportType::Projects,
             ReportType::Tags,
             ReportType::Burndown,
+            ReportType::Blocked,
+            ReportType::Blocking,
+            ReportType::Gantt,
         ]
     }
 }
@@ -351,6 +505,7 @@ pub fn generate_report_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use layout::WidthHint;
     use crate::task::{Task, TaskStatus};
 
     #[test]
@@ -422,4 +577,89 @@ mod tests {
         let output = generate_report_string(&tasks, "list", ReportFormat::Table).unwrap();
         assert!(output.contains("Test task"));
     }
+
+    #[test]
+    fn test_grouped_table_and_markdown() {
+        let mut home = Task::new("Home task".to_string());
+        home.project = Some("Home".to_string());
+        let mut work = Task::new("Work task".to_string());
+        work.project = Some("Work".to_string());
+
+        let mut config = builtin::default_config_for_report(ReportType::List);
+        config.group_by = Some("project".to_string());
+        let manager = ReportManager::new();
+        let result = manager.generate(&[home, work], &config).unwrap();
+
+        let mut table = Vec::new();
+        manager
+            .output_report(&result, ReportFormat::Table, &mut table)
+            .unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.contains("Home\n"));
+        assert!(table.contains("1 task(s)\n\nWork\n"));
+
+        let mut md = Vec::new();
+        manager
+            .output_report(&result, ReportFormat::Markdown, &mut md)
+            .unwrap();
+        let md = String::from_utf8(md).unwrap();
+        assert!(md.contains("### Home"));
+        assert!(md.contains("### Work"));
+        assert!(md.contains("| id | description | project | due |"));
+    }
+
+    #[test]
+    fn test_page_limit_and_fitted_table() {
+        let mut config = crate::config::Configuration::default();
+        config.set("report.mine.columns", "id,description,project");
+        config.set("report.mine.filter", "status:pending limit:page");
+        let manager = ReportManager::from_config(&config);
+        let report = manager.get_custom_report("mine").unwrap().clone();
+        assert!(report.page);
+        assert_eq!(report.limit, None);
+
+        let tasks: Vec<Task> = (0..30)
+            .map(|i| {
+                let mut task = Task::new(format!("A rather long description for task number {i}"));
+                task.project = Some("Home".to_string());
+                task
+            })
+            .collect();
+        let viewport = Viewport::new(10, 40);
+        let result = manager.generate_for_viewport(&tasks, &report, viewport).unwrap();
+        assert_eq!(result.rows.len(), 7);
+        assert_eq!(manager.generate(&tasks, &report).unwrap().rows.len(), 30);
+
+        // UUIDs stand in for display ids here; keep them short
+        let layout = TableLayout::for_viewport(viewport).with_hint("id", WidthHint::fixed(8));
+        let mut output = Vec::new();
+        manager.output_table_fitted(&result, &layout, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.lines().all(|line| line.chars().count() <= 40));
+        assert!(output.contains("| A rather long desc… | Home"));
+    }
+
+    #[test]
+    fn test_table_aligns_wide_characters() {
+        let tasks = vec![
+            Task::new("日本語のタスク".to_string()),
+            Task::new("🎉 party".to_string()),
+            Task::new("plain".to_string()),
+        ];
+        let config = ReportConfig {
+            columns: vec!["description".to_string(), "status".to_string()],
+            ..ReportConfig::default()
+        };
+        let manager = ReportManager::new();
+        let result = manager.generate(&tasks, &config).unwrap();
+
+        let mut output = Vec::new();
+        manager.output_report(&result, ReportFormat::Table, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        // The description column is as wide as 日本語のタスク: 14 columns
+        for line in output.lines().take(2 + tasks.len()).skip(2) {
+            let (description, _) = line.split_once(" | ").unwrap();
+            assert_eq!(layout::text_width(description), 14, "{line}");
+        }
+    }
 }
@@ -0,0 +1,187 @@
+//! SQLite export of report results
+//!
+//! Writes a [`ReportResult`] into a table with one column per report header,
+//! so the rows can be queried with SQL. Columns are declared without a type:
+//! values that parse as numbers (e.g. `urgency`) are stored as numbers and
+//! sort numerically, everything else as text. Grouped results get an extra
+//! `report_group` column holding the group key.
+
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use super::builtin::{ReportResult, ReportRow};
+use crate::error::{StorageError, TaskError};
+
+/// Column holding the group key of grouped results
+pub const GROUP_COLUMN: &str = "report_group";
+
+fn db_error(context: &str, e: rusqlite::Error) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Database {
+            message: format!("{context}: {e}"),
+        },
+    }
+}
+
+/// Replace `table` in `conn` with the rows of `result`. Returns the number
+/// of rows written.
+pub fn write_table(
+    result: &ReportResult,
+    conn: &Connection,
+    table: &str,
+) -> Result<usize, TaskError> {
+    let grouped = !result.groups.is_empty();
+    let mut columns: Vec<&str> = Vec::new();
+    if grouped {
+        columns.push(GROUP_COLUMN);
+    }
+    columns.extend(result.headers.iter().map(String::as_str));
+    if columns.is_empty() {
+        return Err(TaskError::InvalidData {
+            message: "Report has no columns to export".to_string(),
+        });
+    }
+
+    let column_list = columns
+        .iter()
+        .map(|c| quote(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| db_error("Failed to start export", e))?;
+    tx.execute_batch(&format!(
+        "DROP TABLE IF EXISTS {table}; CREATE TABLE {table} ({column_list});",
+        table = quote(table)
+    ))
+    .map_err(|e| db_error("Failed to create export table", e))?;
+
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let sql = format!(
+        "INSERT INTO {} ({column_list}) VALUES ({placeholders})",
+        quote(table)
+    );
+    let mut written = 0;
+    {
+        let mut insert = tx
+            .prepare(&sql)
+            .map_err(|e| db_error("Failed to prepare export", e))?;
+        let rows: Vec<(Option<&str>, &ReportRow)> = if grouped {
+            result
+                .groups
+                .iter()
+                .flat_map(|g| g.rows.iter().map(move |r| (Some(g.key.as_str()), r)))
+                .collect()
+        } else {
+            result.rows.iter().map(|r| (None, r)).collect()
+        };
+        for (group, row) in rows {
+            let values = group.map(|g| Value::Text(g.to_string())).into_iter().chain(
+                result
+                    .headers
+                    .iter()
+                    .map(|h| value(row.values.get(h).map(String::as_str).unwrap_or_default())),
+            );
+            insert
+                .execute(params_from_iter(values))
+                .map_err(|e| db_error("Failed to export row", e))?;
+            written += 1;
+        }
+    }
+    tx.commit()
+        .map_err(|e| db_error("Failed to commit export", e))?;
+    Ok(written)
+}
+
+/// Open (or create) the database at `path` and replace `table` with the rows
+/// of `result`
+pub fn export_to_file<P: AsRef<Path>>(
+    result: &ReportResult,
+    path: P,
+    table: &str,
+) -> Result<usize, TaskError> {
+    let conn = Connection::open(path).map_err(|e| db_error("Failed to open export database", e))?;
+    write_table(result, &conn, table)
+}
+
+/// Quote an SQL identifier
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Column value for a cell: NULL when empty, a number when the text is
+/// one, text otherwise
+fn value(text: &str) -> Value {
+    if text.is_empty() {
+        return Value::Null;
+    }
+    if text.trim() == text {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::Integer(n);
+        }
+        if let Some(n) = text.parse::<f64>().ok().filter(|n| n.is_finite()) {
+            return Value::Real(n);
+        }
+    }
+    Value::Text(text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reports::builtin::{
+        default_config_for_report, BuiltinReports, ReportConfig, ReportType,
+    };
+    use crate::task::Task;
+
+    #[test]
+    fn test_export_grouped_report() {
+        let mut tasks = Vec::new();
+        for (description, project) in [("Deploy", "Work"), ("Review", "Work"), ("Laundry", "Home")]
+        {
+            let mut task = Task::new(description.to_string());
+            task.project = Some(project.to_string());
+            tasks.push(task);
+        }
+        let config = ReportConfig {
+            columns: vec![
+                "id".to_string(),
+                "description".to_string(),
+                "urgency".to_string(),
+            ],
+            group_by: Some("project".to_string()),
+            ..default_config_for_report(ReportType::List)
+        };
+        let result = BuiltinReports::new()
+            .generate_report(&tasks, &config)
+            .unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(write_table(&result, &conn, "my tasks").unwrap(), 3);
+        let work: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM \"my tasks\" WHERE report_group = 'Work' AND urgency > 0",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(work, 2);
+
+        // Exporting again replaces the table
+        let ungrouped = ReportResult {
+            groups: Vec::new(),
+            ..result
+        };
+        assert_eq!(write_table(&ungrouped, &conn, "my tasks").unwrap(), 3);
+        let columns: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_table_info('my tasks')")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(columns, vec!["id", "description", "urgency"]);
+    }
+}
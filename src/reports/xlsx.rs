@@ -0,0 +1,377 @@
+//! Excel workbook export of report results
+//!
+//! Writes a [`ReportResult`] as an `.xlsx` workbook with one sheet per group
+//! (a single `Report` sheet for ungrouped results), a header row, and
+//! numeric cells where a value parses as a number. The workbook is a plain
+//! uncompressed zip of SpreadsheetML parts, so no compression or spreadsheet
+//! crate is needed.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::builtin::{ReportResult, ReportRow};
+use crate::error::TaskError;
+
+/// Excel's limit on sheet name length
+const MAX_SHEET_NAME: usize = 31;
+
+/// Write `result` as an `.xlsx` workbook to `writer`
+pub fn write_xlsx<W: Write>(result: &ReportResult, writer: &mut W) -> Result<(), TaskError> {
+    let sheets: Vec<(&str, &[ReportRow])> = if result.groups.is_empty() {
+        vec![("Report", &result.rows)]
+    } else {
+        result
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.rows.as_slice()))
+            .collect()
+    };
+    let names = sheet_names(sheets.iter().map(|(name, _)| *name));
+
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", &content_types(sheets.len()));
+    zip.add("_rels/.rels", ROOT_RELS);
+    zip.add("xl/workbook.xml", &workbook(&names));
+    zip.add("xl/_rels/workbook.xml.rels", &workbook_rels(sheets.len()));
+    for (i, (_, rows)) in sheets.iter().enumerate() {
+        let path = format!("xl/worksheets/sheet{}.xml", i + 1);
+        zip.add(&path, &worksheet(&result.headers, rows));
+    }
+    writer.write_all(&zip.finish())?;
+    Ok(())
+}
+
+/// Write `result` as an `.xlsx` workbook at `path`
+pub fn save_xlsx<P: AsRef<Path>>(result: &ReportResult, path: P) -> Result<(), TaskError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_xlsx(result, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Valid, unique sheet names: forbidden characters removed, at most 31
+/// characters, and duplicates (compared case-insensitively, as Excel does)
+/// numbered
+fn sheet_names<'a>(keys: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut used = HashSet::new();
+    keys.enumerate()
+        .map(|(i, key)| {
+            let cleaned: String = key
+                .chars()
+                .filter(|c| {
+                    !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\') && !c.is_control()
+                })
+                .take(MAX_SHEET_NAME)
+                .collect();
+            let base = match cleaned.trim() {
+                "" => format!("Sheet{}", i + 1),
+                name => name.trim_matches('\'').to_string(),
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !used.insert(name.to_lowercase()) {
+                let suffix = format!(" ({n})");
+                let keep = MAX_SHEET_NAME - suffix.chars().count();
+                name = base.chars().take(keep).collect::<String>() + &suffix;
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    "</Relationships>"
+);
+
+fn content_types(sheets: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    ));
+    for i in 1..=sheets {
+        xml.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{i}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#
+        ));
+    }
+    xml.push_str("</Types>");
+    xml
+}
+
+fn workbook(names: &[String]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    ));
+    for (i, name) in names.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{id}" r:id="rId{id}"/>"#,
+            escape(name),
+            id = i + 1
+        ));
+    }
+    xml.push_str("</sheets></workbook>");
+    xml
+}
+
+fn workbook_rels(sheets: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    ));
+    for i in 1..=sheets {
+        xml.push_str(&format!(
+            r#"<Relationship Id="rId{i}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{i}.xml"/>"#
+        ));
+    }
+    xml.push_str("</Relationships>");
+    xml
+}
+
+fn worksheet(headers: &[String], rows: &[ReportRow]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    ));
+    let header_row = headers.iter().map(String::as_str);
+    push_row(&mut xml, 1, header_row, false);
+    for (i, row) in rows.iter().enumerate() {
+        let values = headers
+            .iter()
+            .map(|h| row.values.get(h).map(String::as_str).unwrap_or_default());
+        push_row(&mut xml, i + 2, values, true);
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+fn push_row<'a>(xml: &mut String, r: usize, values: impl Iterator<Item = &'a str>, numbers: bool) {
+    xml.push_str(&format!(r#"<row r="{r}">"#));
+    for (c, value) in values.enumerate() {
+        let cell = format!("{}{r}", column_name(c));
+        match number(value).filter(|_| numbers) {
+            Some(n) => xml.push_str(&format!(r#"<c r="{cell}"><v>{n}</v></c>"#)),
+            None if value.is_empty() => {}
+            None => xml.push_str(&format!(
+                r#"<c r="{cell}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                escape(value)
+            )),
+        }
+    }
+    xml.push_str("</row>");
+}
+
+/// Spreadsheet column letters for a zero-based index: A..Z, AA..
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// The value as a number, if it is written as one
+fn number(value: &str) -> Option<f64> {
+    if value.is_empty() || value.trim() != value {
+        return None;
+    }
+    value.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Escape XML text, dropping characters XML 1.0 cannot represent
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Minimal zip archive writer storing entries uncompressed
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central: Vec<u8>,
+    entries: u16,
+}
+
+/// 1980-01-01, the earliest date a zip entry can carry
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, contents: &str) {
+        let contents = contents.as_bytes();
+        let crc = crc32(contents);
+        let size = contents.len() as u32;
+        let offset = self.data.len() as u32;
+
+        let data = &mut self.data;
+        put32(data, 0x0403_4b50);
+        put16(data, 20); // version needed
+        put16(data, 0); // flags
+        put16(data, 0); // stored
+        put16(data, 0); // time
+        put16(data, DOS_DATE);
+        put32(data, crc);
+        put32(data, size);
+        put32(data, size);
+        put16(data, name.len() as u16);
+        put16(data, 0); // extra field length
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(contents);
+
+        let central = &mut self.central;
+        put32(central, 0x0201_4b50);
+        put16(central, 20); // version made by
+        put16(central, 20); // version needed
+        put16(central, 0); // flags
+        put16(central, 0); // stored
+        put16(central, 0); // time
+        put16(central, DOS_DATE);
+        put32(central, crc);
+        put32(central, size);
+        put32(central, size);
+        put16(central, name.len() as u16);
+        put16(central, 0); // extra field length
+        put16(central, 0); // comment length
+        put16(central, 0); // disk number
+        put16(central, 0); // internal attributes
+        put32(central, 0); // external attributes
+        put32(central, offset);
+        central.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let offset = self.data.len() as u32;
+        let size = self.central.len() as u32;
+        self.data.append(&mut self.central);
+        let data = &mut self.data;
+        put32(data, 0x0605_4b50);
+        put16(data, 0); // this disk
+        put16(data, 0); // disk with the central directory
+        put16(data, self.entries);
+        put16(data, self.entries);
+        put32(data, size);
+        put32(data, offset);
+        put16(data, 0); // comment length
+        self.data
+    }
+}
+
+fn put16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// CRC-32 (IEEE) as used by zip
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reports::builtin::ReportGroup;
+    use std::collections::HashMap;
+
+    fn row(pairs: &[(&str, &str)]) -> ReportRow {
+        ReportRow {
+            values: pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(number("3.5"), Some(3.5));
+        assert_eq!(number(" 3"), None);
+        assert_eq!(number("NaN"), None);
+        assert_eq!(
+            sheet_names(["Home", "home", "a/b:c", "", &"x".repeat(40)].into_iter()),
+            vec!["Home", "home (2)", "abc", "Sheet4", &"x".repeat(31)]
+        );
+    }
+
+    #[test]
+    fn test_one_sheet_per_group() {
+        let result = ReportResult {
+            headers: vec!["description".to_string(), "urgency".to_string()],
+            rows: Vec::new(),
+            total_count: 2,
+            shown_count: 2,
+            summary: HashMap::new(),
+            groups: vec![
+                ReportGroup {
+                    key: "Work".to_string(),
+                    rows: vec![row(&[
+                        ("description", "Fix <bug> & ship"),
+                        ("urgency", "4.2"),
+                    ])],
+                    count: 1,
+                },
+                ReportGroup {
+                    key: "Home".to_string(),
+                    rows: vec![row(&[("description", "Water plants")])],
+                    count: 1,
+                },
+            ],
+        };
+
+        let mut bytes = Vec::new();
+        write_xlsx(&result, &mut bytes).unwrap();
+        assert!(bytes.starts_with(b"PK\x03\x04"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains(r#"<sheet name="Work" sheetId="1" r:id="rId1"/>"#));
+        assert!(text.contains(r#"<sheet name="Home" sheetId="2" r:id="rId2"/>"#));
+        assert!(text.contains("xl/worksheets/sheet2.xml"));
+        assert!(text.contains("Fix &lt;bug&gt; &amp; ship"));
+        assert!(text.contains(r#"<c r="B2"><v>4.2</v></c>"#));
+        assert!(
+            text.contains(r#"<c r="A1" t="inlineStr"><is><t xml:space="preserve">description</t>"#)
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Minimal HTTP/1.1 transport for the JSON-RPC endpoint
+//!
+//! Only what a local client needs: one `POST` with a `Content-Length` body
+//! per connection, answered with a JSON body and `Connection: close`.
+//!
+//! Every request must send `Authorization: Bearer <token>` with the token
+//! from [`TOKEN_FILE`] in the data directory, created readable by the owner
+//! only on first start, and a `Content-Type: application/json` body. A
+//! `Host` other than a loopback name and an `Origin` that was not allowed
+//! with [`RpcServer::allow_origin`] are refused as well, so a web page can
+//! neither drive the endpoint cross-origin nor read it through DNS
+//! rebinding. Browser extensions are allowed by their own origin, e.g.
+//! `moz-extension://<id>`.
+//!
+//! Each connection runs on its own thread with read and write timeouts, so
+//! an idle client cannot hold up the others.
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::daemon::ServiceClient;
+use crate::error::{ConfigError, TaskError};
+use crate::io::profile::SerializationProfile;
+use crate::server::rpc::handle_json_with_profile;
+
+/// Largest request body accepted
+const MAX_BODY: usize = 1024 * 1024;
+
+/// File in the data directory holding the bearer token
+pub const TOKEN_FILE: &str = "rpc.token";
+
+/// Host names accepted in the `Host` header unless more are allowed
+const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// JSON-RPC server bound to a TCP address
+pub struct RpcServer {
+    listener: TcpListener,
+    client: ServiceClient,
+    profile: Option<SerializationProfile>,
+    token: String,
+    allowed_hosts: Vec<String>,
+    allowed_origins: Vec<String>,
+    timeout: Duration,
+}
+
+impl std::fmt::Debug for RpcServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcServer")
+            .field("listener", &self.listener)
+            .field("allowed_hosts", &self.allowed_hosts)
+            .field("allowed_origins", &self.allowed_origins)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RpcServer {
+    /// Bind to `addr` (use port 0 to pick a free port), authenticating
+    /// clients with the token in `data_dir`'s [`TOKEN_FILE`]
+    pub fn bind<A: ToSocketAddrs, P: AsRef<Path>>(
+        addr: A,
+        client: ServiceClient,
+        data_dir: P,
+    ) -> Result<Self, TaskError> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            client,
+            profile: None,
+            token: load_or_create_token(data_dir.as_ref())?,
+            allowed_hosts: LOOPBACK_HOSTS.iter().map(|h| h.to_string()).collect(),
+            allowed_origins: Vec::new(),
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// Exchange tasks in `profile` instead of as
+    /// [`TaskDto`](crate::server::dto::TaskDto)s
+    pub fn with_profile(mut self, profile: SerializationProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Accept requests sent with this `Origin`, e.g. a browser extension's
+    /// `chrome-extension://<id>`
+    pub fn allow_origin<S: Into<String>>(mut self, origin: S) -> Self {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+
+    /// Accept this name in the `Host` header besides the loopback names,
+    /// for a server bound to another address
+    pub fn allow_host<S: Into<String>>(mut self, host: S) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// How long a client may take to send its request or read the answer
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The bearer token clients must send
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr, TaskError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve connections until the service stops. Connection errors are
+    /// answered or dropped; they do not stop the server.
+    pub fn serve(&self) -> Result<(), TaskError> {
+        std::thread::scope(|scope| {
+            for stream in self.listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                scope.spawn(move || {
+                    let _ = stream
+                        .set_read_timeout(Some(self.timeout))
+                        .and_then(|_| stream.set_write_timeout(Some(self.timeout)));
+                    let _ = self.handle_connection(&mut stream);
+                });
+                // A stopped service cannot answer anything; stop accepting
+                if !self.client.is_running() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Serve on a background thread
+    pub fn spawn(self) -> JoinHandle<Result<(), TaskError>> {
+        std::thread::spawn(move || self.serve())
+    }
+
+    fn handle_connection(&self, stream: &mut TcpStream) -> Result<(), TaskError> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let method = request_line.split_whitespace().next().unwrap_or("");
+
+        let mut content_length = 0;
+        let mut host = None;
+        let mut origin = None;
+        let mut content_type = None;
+        let mut authorization = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim().to_string();
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-length" => content_length = value.parse().unwrap_or(0),
+                    "host" => host = Some(value),
+                    "origin" => origin = Some(value),
+                    "content-type" => content_type = Some(value),
+                    "authorization" => authorization = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        if method != "POST" {
+            return write_response(stream, "405 Method Not Allowed", "");
+        }
+        if !host.is_some_and(|h| self.host_allowed(&h)) {
+            return write_response(stream, "403 Forbidden", "");
+        }
+        if origin.is_some_and(|o| !self.allowed_origins.contains(&o)) {
+            return write_response(stream, "403 Forbidden", "");
+        }
+        let token = authorization
+            .as_deref()
+            .and_then(|value| value.split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+            .map_or("", |(_, token)| token);
+        if !bool::from(token.trim().as_bytes().ct_eq(self.token.as_bytes())) {
+            return write_response(stream, "401 Unauthorized", "");
+        }
+        let media_type = content_type
+            .as_deref()
+            .and_then(|value| value.split(';').next())
+            .unwrap_or("");
+        if !media_type.trim().eq_ignore_ascii_case("application/json") {
+            return write_response(stream, "415 Unsupported Media Type", "");
+        }
+        if content_length > MAX_BODY {
+            return write_response(stream, "413 Payload Too Large", "");
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        let response = handle_json_with_profile(
+            &self.client,
+            &String::from_utf8_lossy(&body),
+            self.profile.as_ref(),
+        );
+        write_response(stream, "200 OK", &response)
+    }
+
+    // The `Host` header without its port, e.g. `[::1]` or `localhost`
+    fn host_allowed(&self, host: &str) -> bool {
+        let name = match host.strip_prefix('[') {
+            Some(rest) => rest.split(']').next().map(|ip| format!("[{ip}]")),
+            None => host.split(':').next().map(str::to_string),
+        }
+        .unwrap_or_default();
+        self.allowed_hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&name))
+    }
+}
+
+/// Read the bearer token from `data_dir`'s [`TOKEN_FILE`], creating the
+/// file with a random token, readable by the owner only, if it is missing.
+/// A token file others can read is refused.
+pub fn load_or_create_token(data_dir: &Path) -> Result<String, TaskError> {
+    let path = data_dir.join(TOKEN_FILE);
+    fs::create_dir_all(data_dir)?;
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    match options.open(&path) {
+        Ok(mut file) => {
+            let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+            writeln!(file, "{token}")?;
+            return Ok(token);
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path)?.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(ConfigError::InvalidPath {
+                path,
+                message: format!("token file has mode {mode:o}; make it private with chmod 600"),
+            }
+            .into());
+        }
+    }
+    let token = fs::read_to_string(&path)?.trim().to_string();
+    if token.is_empty() {
+        return Err(ConfigError::InvalidPath {
+            path,
+            message: "token file is empty".to_string(),
+        }
+        .into());
+    }
+    Ok(token)
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> Result<(), TaskError> {
+    let challenge = if status.starts_with("401") {
+        "WWW-Authenticate: Bearer\r\n"
+    } else {
+        ""
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n{challenge}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::daemon::{ServiceConfig, TaskService};
+    use crate::hooks::DefaultHookSystem;
+    use crate::storage::FileStorageBackend;
+    use crate::task::manager::DefaultTaskManager;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+
+    // Send a request with these headers; returns the status line and body
+    fn send(addr: SocketAddr, headers: &str, body: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "POST /rpc HTTP/1.1\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.lines().next().unwrap().to_string();
+        (status, body.to_string())
+    }
+
+    fn post(addr: SocketAddr, token: &str, body: &Value) -> Value {
+        let headers = format!(
+            "Host: localhost\r\nContent-Type: application/json\r\nAuthorization: Bearer {token}\r\n"
+        );
+        let (status, body) = send(addr, &headers, &body.to_string());
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        serde_json::from_str(&body).unwrap()
+    }
+
+    fn start(dir: &TempDir) -> TaskService {
+        let path = dir.path().to_path_buf();
+        TaskService::start(ServiceConfig::default(), move || {
+            let config = ConfigurationBuilder::new().data_dir(path.clone()).build()?;
+            let storage = Box::new(FileStorageBackend::with_path(path));
+            DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new()))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rpc_over_http() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = start(&temp_dir);
+        let server = RpcServer::bind("127.0.0.1:0", service.client(), temp_dir.path()).unwrap();
+        let addr = server.local_addr().unwrap();
+        let token = server.token().to_string();
+        server.spawn();
+
+        let added = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "add",
+                    "params": {"description": "Buy milk", "project": "Home", "tags": ["errand"]}}),
+        );
+        assert_eq!(added["id"], 1);
+        assert_eq!(added["result"]["project"], "Home");
+        let uuid = added["result"]["uuid"].clone();
+
+        let modified = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 2, "method": "modify",
+                    "params": {"uuid": uuid, "priority": "H", "remove_tags": ["errand"]}}),
+        );
+        assert_eq!(modified["result"]["priority"], "H");
+        assert!(modified["result"].get("tags").is_none());
+
+        post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 3, "method": "complete", "params": {"uuid": uuid}}),
+        );
+        let queried = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 4, "method": "query", "params": {"status": "completed"}}),
+        );
+        assert_eq!(queried["result"].as_array().unwrap().len(), 1);
+
+        let missing = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 5, "method": "frobnicate"}),
+        );
+        assert_eq!(
+            missing["error"]["code"],
+            crate::server::rpc::METHOD_NOT_FOUND
+        );
+        let unsynced = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 6, "method": "sync"}),
+        );
+        assert_eq!(unsynced["error"]["code"], crate::server::rpc::TASK_ERROR);
+    }
+
+    #[test]
+    fn test_rpc_with_taskwarrior_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = start(&temp_dir);
+        let server = RpcServer::bind("127.0.0.1:0", service.client(), temp_dir.path())
+            .unwrap()
+            .with_profile(SerializationProfile::taskwarrior());
+        let addr = server.local_addr().unwrap();
+        let token = server.token().to_string();
+        server.spawn();
+
+        let added = post(
+            addr,
+            &token,
+            &json!({"jsonrpc": "2.0", "id": 1, "method": "add",
+                    "params": {"description": "Pay rent", "due": "20250305T000000Z"}}),
+        );
+        assert_eq!(added["result"]["due"], "20250305T000000Z");
+        assert!(added["result"].get("etag").is_none());
+    }
+
+    #[test]
+    fn test_requests_must_be_authorized_local_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = start(&temp_dir);
+        let server = RpcServer::bind("127.0.0.1:0", service.client(), temp_dir.path())
+            .unwrap()
+            .allow_origin("moz-extension://tasks")
+            .with_timeout(Duration::from_millis(200));
+        let addr = server.local_addr().unwrap();
+        let token = server.token().to_string();
+        server.spawn();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(temp_dir.path().join(TOKEN_FILE))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(load_or_create_token(temp_dir.path()).unwrap(), token);
+
+        // An idle connection does not hold up the next client
+        let _idle = TcpStream::connect(addr).unwrap();
+
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": "query", "params": {}}).to_string();
+        let json = "Content-Type: application/json\r\n";
+        let auth = format!("Authorization: Bearer {token}\r\n");
+        let status = |headers: String| send(addr, &headers, &body).0;
+        assert_eq!(
+            status(format!("Host: localhost:1\r\n{json}")),
+            "HTTP/1.1 401 Unauthorized"
+        );
+        assert_eq!(
+            status(format!(
+                "Host: localhost\r\n{json}Authorization: Bearer nope\r\n"
+            )),
+            "HTTP/1.1 401 Unauthorized"
+        );
+        assert_eq!(
+            status(format!(
+                "Host: localhost\r\nContent-Type: text/plain\r\n{auth}"
+            )),
+            "HTTP/1.1 415 Unsupported Media Type"
+        );
+        assert_eq!(
+            status(format!("Host: attacker.example\r\n{json}{auth}")),
+            "HTTP/1.1 403 Forbidden"
+        );
+        assert_eq!(
+            status(format!(
+                "Host: localhost\r\nOrigin: https://attacker.example\r\n{json}{auth}"
+            )),
+            "HTTP/1.1 403 Forbidden"
+        );
+        assert_eq!(
+            status(format!(
+                "Host: [::1]:8080\r\nOrigin: moz-extension://tasks\r\n{json}{auth}"
+            )),
+            "HTTP/1.1 200 OK"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Model Context Protocol tool server
+//!
+//! Publishes a task manager to LLM agents as MCP tools over newline-delimited
+//! JSON-RPC (the MCP stdio transport):
+//!
+//! | Tool                | Arguments                | Result                     |
+//! |---------------------|--------------------------|----------------------------|
+//! | `query_tasks`       | [`QueryParams`] fields   | matching tasks as JSON     |
+//! | `add_task`          | [`AddTaskParams`] fields | the new task as JSON       |
+//! | `complete_task`     | `uuid`                   | the completed task as JSON |
+//! | `summarize_overdue` | none                     | plain-text summary         |
+//!
+//! Argument schemas are generated from the same DTOs the JSON-RPC server
+//! uses, with the status enum taken from [`TaskStatus`].
+//!
+//! ```rust,no_run
+//! use taskwarrior3lib::server::mcp::McpServer;
+//! use taskwarrior3lib::task::manager::TaskManagerBuilder;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let manager = TaskManagerBuilder::new().build()?;
+//! McpServer::new(manager).serve_stdio()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::clock;
+use crate::error::TaskError;
+use crate::query::TaskQuery;
+use crate::server::dto::{AddTaskParams, QueryParams, TaskDto, UuidParams};
+use crate::server::rpc::{RpcError, RpcRequest, RpcResponse, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR};
+use crate::task::manager::TaskManager;
+use crate::task::{Task, TaskStatus};
+
+/// MCP protocol revision implemented
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const STATUSES: [TaskStatus; 5] = [
+    TaskStatus::Pending,
+    TaskStatus::Completed,
+    TaskStatus::Deleted,
+    TaskStatus::Waiting,
+    TaskStatus::Recurring,
+];
+
+/// MCP server backed by a task manager
+#[derive(Debug)]
+pub struct McpServer<M: TaskManager> {
+    manager: M,
+}
+
+impl<M: TaskManager> McpServer<M> {
+    /// Wrap a task manager
+    pub fn new(manager: M) -> Self {
+        Self { manager }
+    }
+
+    /// The wrapped manager
+    pub fn manager(&self) -> &M {
+        &self.manager
+    }
+
+    /// Serve on stdin/stdout until stdin closes
+    pub fn serve_stdio(&mut self) -> Result<(), TaskError> {
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        self.serve(stdin.lock(), stdout.lock())
+    }
+
+    /// Serve newline-delimited JSON-RPC messages from `reader` to `writer`
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> Result<(), TaskError> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one message; notifications produce no response
+    pub fn handle_line(&mut self, line: &str) -> Option<RpcResponse> {
+        match serde_json::from_str::<RpcRequest>(line) {
+            Ok(request) => self.handle_request(&request),
+            Err(e) => Some(RpcResponse::error(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+        }
+    }
+
+    /// Handle one request; notifications (no id) produce no response
+    pub fn handle_request(&mut self, request: &RpcRequest) -> Option<RpcResponse> {
+        if request.id.is_null() {
+            return None;
+        }
+        let outcome = match request.method.as_str() {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "taskwarrior3lib", "version": crate::VERSION },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&request.params),
+            method => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{method}'"))),
+        };
+        Some(RpcResponse::from_outcome(request.id.clone(), outcome))
+    }
+
+    /// Run a tool. Task errors are reported as tool results with `isError`
+    /// so the agent sees them; malformed calls are protocol errors.
+    fn call_tool(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let name = params["name"]
+            .as_str()
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing tool name"))?;
+        let arguments = match &params["arguments"] {
+            Value::Null => json!({}),
+            args => args.clone(),
+        };
+
+        let result = match name {
+            "query_tasks" => {
+                let params: QueryParams = parse_arguments(arguments)?;
+                self.manager
+                    .query_tasks(&TaskQuery::from(&params))
+                    .map(|tasks| tasks_json(&tasks))
+            }
+            "add_task" => {
+                let params: AddTaskParams = parse_arguments(arguments)?;
+                self.add_task(&params).map(|task| task_json(&task))
+            }
+            "complete_task" => {
+                let UuidParams { uuid } = parse_arguments(arguments)?;
+                self.manager.complete_task(uuid).map(|task| task_json(&task))
+            }
+            "summarize_overdue" => self.summarize_overdue(),
+            _ => return Err(RpcError::new(INVALID_PARAMS, format!("unknown tool '{name}'"))),
+        };
+
+        Ok(match result {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+            Err(e) => json!({ "content": [{ "type": "text", "text": e.to_string() }], "isError": true }),
+        })
+    }
+
+    fn add_task(&mut self, params: &AddTaskParams) -> Result<Task, TaskError> {
+        let task = self.manager.add_task(params.description.clone())?;
+        let update = params.to_update();
+        if update.is_empty() {
+            Ok(task)
+        } else {
+            self.manager.update_task(task.id, update)
+        }
+    }
+
+    fn summarize_overdue(&mut self) -> Result<String, TaskError> {
+        let now = clock::now();
+        let mut overdue: Vec<Task> = self
+            .manager
+            .pending_tasks()?
+            .into_iter()
+            .filter(Task::is_overdue)
+            .collect();
+        if overdue.is_empty() {
+            return Ok("No overdue tasks.".to_string());
+        }
+        overdue.sort_by_key(|t| t.due);
+
+        let mut summary = format!("{} overdue task(s):\n", overdue.len());
+        for task in &overdue {
+            let days = task.due.map(|due| (now - due).num_days()).unwrap_or(0);
+            summary.push_str(&format!("- {} ({} day(s) overdue", task.description, days));
+            if let Some(project) = &task.project {
+                summary.push_str(&format!(", project {project}"));
+            }
+            summary.push_str(&format!(", uuid {})\n", task.id));
+        }
+        Ok(summary)
+    }
+}
+
+/// Tool definitions published by `tools/list`
+pub fn tool_definitions() -> Value {
+    let statuses: Vec<String> = STATUSES
+        .iter()
+        .map(|s| serde_json::to_value(s).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default())
+        .collect();
+    let uuid = json!({ "type": "string", "format": "uuid", "description": "Task UUID" });
+
+    json!([
+        {
+            "name": "query_tasks",
+            "description": "List tasks matching optional status, project (including subprojects) and tag filters",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "status": { "type": "string", "enum": statuses },
+                    "project": { "type": "string" },
+                    "tags": { "type": "array", "items": { "type": "string" }, "description": "Tasks must have all of these tags" },
+                    "limit": { "type": "integer", "minimum": 0 },
+                },
+            },
+        },
+        {
+            "name": "add_task",
+            "description": "Create a pending task",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "description": { "type": "string" },
+                    "project": { "type": "string" },
+                    "priority": { "type": "string", "description": "Priority code such as H, M or L" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "due": { "type": "string", "format": "date-time" },
+                },
+                "required": ["description"],
+            },
+        },
+        {
+            "name": "complete_task",
+            "description": "Mark a task completed",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "uuid": uuid },
+                "required": ["uuid"],
+            },
+        },
+        {
+            "name": "summarize_overdue",
+            "description": "Summarize pending tasks whose due date has passed, oldest first",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+fn parse_arguments<T: DeserializeOwned>(arguments: Value) -> Result<T, RpcError> {
+    serde_json::from_value(arguments).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn task_json(task: &Task) -> String {
+    serde_json::to_string_pretty(&TaskDto::from(task)).unwrap_or_default()
+}
+
+fn tasks_json(tasks: &[Task]) -> String {
+    let dtos: Vec<TaskDto> = tasks.iter().map(TaskDto::from).collect();
+    serde_json::to_string_pretty(&dtos).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::storage::FileStorageBackend;
+    use crate::task::manager::{DefaultTaskManager, TaskUpdate};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    fn server(temp_dir: &TempDir) -> McpServer<DefaultTaskManager> {
+        let path = temp_dir.path().to_path_buf();
+        let config = ConfigurationBuilder::new().data_dir(path.clone()).build().unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(path));
+        McpServer::new(DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new())).unwrap())
+    }
+
+    fn call(server: &mut McpServer<DefaultTaskManager>, id: u64, method: &str, params: Value) -> Value {
+        let line = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string();
+        serde_json::to_value(server.handle_line(&line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_handshake_and_tool_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut server = server(&temp_dir);
+
+        let init = call(&mut server, 1, "initialize", json!({}));
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(server
+            .handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .is_none());
+
+        let tools = call(&mut server, 2, "tools/list", Value::Null);
+        let names: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["query_tasks", "add_task", "complete_task", "summarize_overdue"]);
+        let statuses = &tools["result"]["tools"][0]["inputSchema"]["properties"]["status"]["enum"];
+        assert_eq!(statuses[0], "pending");
+    }
+
+    #[test]
+    fn test_tool_calls() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut server = server(&temp_dir);
+
+        let added = call(
+            &mut server,
+            1,
+            "tools/call",
+            json!({ "name": "add_task", "arguments": { "description": "File taxes", "project": "Home" } }),
+        );
+        let text = added["result"]["content"][0]["text"].as_str().unwrap();
+        let task: TaskDto = serde_json::from_str(text).unwrap();
+        assert_eq!(task.project.as_deref(), Some("Home"));
+
+        let yesterday = Utc::now() - chrono::Duration::days(1);
+        server
+            .manager
+            .update_task(task.uuid, TaskUpdate::new().due(yesterday))
+            .unwrap();
+        let summary = call(&mut server, 2, "tools/call", json!({ "name": "summarize_overdue" }));
+        let text = summary["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.starts_with("1 overdue task(s):"));
+        assert!(text.contains("File taxes"));
+
+        call(&mut server, 3, "tools/call", json!({ "name": "complete_task", "arguments": { "uuid": task.uuid } }));
+        let pending = call(
+            &mut server,
+            4,
+            "tools/call",
+            json!({ "name": "query_tasks", "arguments": { "status": "pending" } }),
+        );
+        assert_eq!(pending["result"]["content"][0]["text"], "[]");
+
+        let missing = call(
+            &mut server,
+            5,
+            "tools/call",
+            json!({ "name": "complete_task", "arguments": { "uuid": uuid::Uuid::new_v4() } }),
+        );
+        assert_eq!(missing["result"]["isError"], true);
+    }
+}
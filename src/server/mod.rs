@@ -0,0 +1,44 @@
+//! Local JSON-RPC server
+//!
+//! With the `server` feature a [`TaskService`](crate::daemon::TaskService)
+//! can be exposed over a small JSON-RPC 2.0 endpoint, so a browser
+//! extension, mobile shortcut or script can manage tasks without parsing CLI
+//! output. Requests are `POST`ed as JSON to any path:
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"method":"add","params":{"description":"Buy milk","tags":["errand"]}}
+//! ```
+//!
+//! | Method     | Params                  | Result              |
+//! |------------|-------------------------|---------------------|
+//! | `query`    | [`QueryParams`]         | `[TaskDto]`         |
+//! | `get`      | [`UuidParams`]          | `TaskDto` or `null` |
+//! | `add`      | [`AddTaskParams`]       | [`TaskDto`]         |
+//! | `modify`   | [`ModifyTaskParams`]    | [`TaskDto`]         |
+//! | `complete` | [`UuidParams`]          | [`TaskDto`]         |
+//! | `delete`   | [`UuidParams`]          | [`TaskDto`]         |
+//! | `sync`     | none                    | [`SyncResultDto`]   |
+//!
+//! Clients authenticate with a bearer token kept in the data directory, and
+//! requests from other hosts or web pages are refused; see [`http`].
+//!
+//! With the `mcp` feature, [`mcp::McpServer`] publishes the same operations
+//! as Model Context Protocol tools for LLM agents.
+//!
+//! [`QueryParams`]: dto::QueryParams
+//! [`UuidParams`]: dto::UuidParams
+//! [`AddTaskParams`]: dto::AddTaskParams
+//! [`ModifyTaskParams`]: dto::ModifyTaskParams
+//! [`TaskDto`]: dto::TaskDto
+//! [`SyncResultDto`]: dto::SyncResultDto
+
+pub use crate::api::dto;
+pub mod http;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod rpc;
+
+pub use http::RpcServer;
+pub use rpc::{
+    handle_request, handle_request_with_profile, RpcError, RpcRequest, RpcResponse,
+};
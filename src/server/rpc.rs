@@ -0,0 +1,216 @@
+//! JSON-RPC 2.0 request dispatch
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::daemon::ServiceClient;
+use crate::error::TaskError;
+use crate::io::profile::SerializationProfile;
+use crate::server::dto::{
+    AddTaskParams, ModifyTaskParams, QueryParams, SyncResultDto, TaskDto, UuidParams,
+};
+use crate::task::Task;
+
+/// Error codes defined by JSON-RPC 2.0
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Server-defined code for errors returned by the task manager
+pub const TASK_ERROR: i64 = -32000;
+/// Server-defined code for requests naming a task that does not exist
+pub const NOT_FOUND: i64 = -32001;
+/// Server-defined code for a `modify` whose `if_match` etag is stale
+pub const CONFLICT: i64 = -32002;
+
+/// A JSON-RPC request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default)]
+    pub id: Value,
+}
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    /// Create an error with `code`
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<TaskError> for RpcError {
+    fn from(error: TaskError) -> Self {
+        let code = match error {
+            TaskError::NotFound { .. } => NOT_FOUND,
+            TaskError::Conflict { .. } => CONFLICT,
+            _ => TASK_ERROR,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+/// A JSON-RPC response; exactly one of `result` and `error` is set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    pub id: Value,
+}
+
+impl RpcResponse {
+    /// Build a response from a method outcome
+    pub fn from_outcome(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(value) => (Some(value), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result,
+            error,
+            id,
+        }
+    }
+
+    /// Build an error response
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self::from_outcome(id, Err(error))
+    }
+}
+
+/// Handle one request against a running service
+pub fn handle_request(client: &ServiceClient, request: &RpcRequest) -> RpcResponse {
+    handle_request_with_profile(client, request, None)
+}
+
+/// Handle one request, exchanging tasks in `profile` instead of as
+/// [`TaskDto`]s when one is given. Dates in `add` and `modify` parameters
+/// may then be written in the profile's style too.
+pub fn handle_request_with_profile(
+    client: &ServiceClient,
+    request: &RpcRequest,
+    profile: Option<&SerializationProfile>,
+) -> RpcResponse {
+    let outcome = if request.jsonrpc != "2.0" {
+        Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))
+    } else {
+        dispatch(client, &request.method, request.params.clone(), profile)
+    };
+    RpcResponse::from_outcome(request.id.clone(), outcome)
+}
+
+/// Handle a raw JSON request body, returning the JSON response body
+pub fn handle_json(client: &ServiceClient, body: &str) -> String {
+    handle_json_with_profile(client, body, None)
+}
+
+/// [`handle_json`] with tasks exchanged in `profile`
+pub fn handle_json_with_profile(
+    client: &ServiceClient,
+    body: &str,
+    profile: Option<&SerializationProfile>,
+) -> String {
+    let response = match serde_json::from_str::<Value>(body) {
+        Err(e) => RpcResponse::error(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+        Ok(value) => match serde_json::from_value::<RpcRequest>(value) {
+            Ok(request) => handle_request_with_profile(client, &request, profile),
+            Err(e) => RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, e.to_string())),
+        },
+    };
+    serde_json::to_string(&response).unwrap_or_default()
+}
+
+fn dispatch(
+    client: &ServiceClient,
+    method: &str,
+    mut params: Value,
+    profile: Option<&SerializationProfile>,
+) -> Result<Value, RpcError> {
+    if let Some(profile) = profile {
+        profile.normalize_dates(&mut params, &["due"]);
+    }
+    match method {
+        "query" => {
+            let params: QueryParams = if params.is_null() {
+                QueryParams::default()
+            } else {
+                parse_params(params)?
+            };
+            let tasks = client.query_tasks((&params).into())?;
+            let tasks = tasks
+                .iter()
+                .map(|task| task_value(task, profile))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Array(tasks))
+        }
+        "get" => {
+            let UuidParams { uuid } = parse_params(params)?;
+            match client.get_task(uuid)? {
+                Some(task) => task_value(&task, profile),
+                None => Ok(Value::Null),
+            }
+        }
+        "add" => {
+            let params: AddTaskParams = parse_params(params)?;
+            let mut task = client.add_task(params.description.clone())?;
+            let update = params.to_update();
+            if !update.is_empty() {
+                task = client.update_task(task.id, update)?;
+            }
+            task_value(&task, profile)
+        }
+        "modify" => {
+            let params: ModifyTaskParams = parse_params(params)?;
+            let current = client
+                .get_task(params.uuid)?
+                .ok_or(TaskError::NotFound { id: params.uuid })?;
+            let task = client.update_task(params.uuid, params.to_update(&current))?;
+            task_value(&task, profile)
+        }
+        "complete" => {
+            let UuidParams { uuid } = parse_params(params)?;
+            task_value(&client.complete_task(uuid)?, profile)
+        }
+        "delete" => {
+            let UuidParams { uuid } = parse_params(params)?;
+            task_value(&client.delete_task(uuid)?, profile)
+        }
+        "sync" => to_value(SyncResultDto::from(&client.sync()?)),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{method}'"))),
+    }
+}
+
+/// A task as returned to clients
+fn task_value(task: &Task, profile: Option<&SerializationProfile>) -> Result<Value, RpcError> {
+    match profile {
+        Some(profile) => profile
+            .to_value(task)
+            .map_err(|e| RpcError::new(TASK_ERROR, e.to_string())),
+        None => to_value(TaskDto::from(task)),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(TASK_ERROR, e.to_string()))
+}
@@ -0,0 +1,191 @@
+//! Yearly archive files for finished tasks
+//!
+//! [`FileStorageBackend::archive_completed`](super::FileStorageBackend::archive_completed)
+//! moves completed and deleted tasks out of `tasks.json` into one
+//! `completed-<year>.json` file per year they ended in. Everyday queries for
+//! pending work then never read them; the archive is loaded on first use by
+//! a query that can match finished tasks.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Utc};
+use uuid::Uuid;
+
+use crate::error::{StorageError, TaskError};
+use crate::task::{Task, TaskStatus};
+
+const PREFIX: &str = "completed-";
+
+/// Path of the archive file for `year`
+pub fn file_path(data_dir: &Path, year: i32) -> PathBuf {
+    data_dir.join(format!("{PREFIX}{year}.json"))
+}
+
+/// Whether a task belongs in the archive
+pub fn is_archivable(task: &Task) -> bool {
+    matches!(task.status, TaskStatus::Completed | TaskStatus::Deleted)
+}
+
+/// When the task finished, falling back to its last change
+pub fn finished_at(task: &Task) -> DateTime<Utc> {
+    task.end.or(task.modified).unwrap_or(task.entry)
+}
+
+/// The archive file year for a task
+pub fn year_of(task: &Task) -> i32 {
+    finished_at(task).year()
+}
+
+/// Years that have an archive file in `data_dir`
+pub fn years(data_dir: &Path) -> Result<BTreeSet<i32>, TaskError> {
+    let entries = match fs::read_dir(data_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(e) => return Err(io_error(e)),
+    };
+    let mut years = BTreeSet::new();
+    for entry in entries {
+        let name = entry.map_err(io_error)?.file_name();
+        let year: Option<i32> = name
+            .to_str()
+            .and_then(|n| n.strip_prefix(PREFIX))
+            .and_then(|n| n.strip_suffix(".json"))
+            .and_then(|y| y.parse().ok());
+        years.extend(year);
+    }
+    Ok(years)
+}
+
+/// Read every archive file in `data_dir`
+pub fn load(data_dir: &Path) -> Result<HashMap<Uuid, Task>, TaskError> {
+    let mut tasks = HashMap::new();
+    for year in years(data_dir)? {
+        let path = file_path(data_dir, year);
+        let data = fs::read(&path).map_err(io_error)?;
+        let archived: Vec<Task> = serde_json::from_slice(&data).map_err(|e| TaskError::Storage {
+            source: StorageError::SerializationError {
+                message: format!("Failed to parse archive {}: {e}", path.display()),
+            },
+        })?;
+        tasks.extend(archived.into_iter().map(|t| (t.id, t)));
+    }
+    Ok(tasks)
+}
+
+/// Rewrite the files for `years` from `archive`, removing files left empty
+pub fn write_years(
+    data_dir: &Path,
+    archive: &HashMap<Uuid, Task>,
+    years: &BTreeSet<i32>,
+) -> Result<(), TaskError> {
+    for &year in years {
+        let path = file_path(data_dir, year);
+        let mut tasks: Vec<&Task> = archive.values().filter(|t| year_of(t) == year).collect();
+        if tasks.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).map_err(io_error)?;
+            }
+            continue;
+        }
+        tasks.sort_by_key(|t| (finished_at(t), t.id));
+        let data = serde_json::to_vec_pretty(&tasks).map_err(|e| TaskError::Storage {
+            source: StorageError::SerializationError {
+                message: format!("Failed to serialize archive: {e}"),
+            },
+        })?;
+        let temp_file = path.with_extension("tmp");
+        fs::write(&temp_file, data)
+            .and_then(|_| fs::rename(&temp_file, &path))
+            .map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn io_error(e: std::io::Error) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Io(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::TaskQuery;
+    use crate::storage::{FileStorageBackend, StorageBackend};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn finished(description: &str, year: i32) -> Task {
+        let mut task = Task::new(description.to_string());
+        task.complete();
+        task.end = Some(Utc.with_ymd_and_hms(year, 6, 1, 0, 0, 0).unwrap());
+        task
+    }
+
+    #[test]
+    fn test_archive_and_lazy_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(temp_dir.path());
+        let open = Task::new("Open".to_string());
+        let old = finished("Old", 2023);
+        let older = finished("Older", 2022);
+        let recent = finished("Recent", 2025);
+        for task in [&open, &old, &older, &recent] {
+            storage.save_task(task).unwrap();
+        }
+
+        let cutoff = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(storage.archive_completed(cutoff).unwrap(), 2);
+        assert_eq!(years(temp_dir.path()).unwrap(), BTreeSet::from([2022, 2023]));
+        assert!(storage.archive_file_path(2023).exists());
+
+        // A fresh backend answers pending queries without the archive
+        let reopened = FileStorageBackend::with_path(temp_dir.path());
+        let pending = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            ..Default::default()
+        };
+        assert_eq!(reopened.query_tasks(&pending, None).unwrap().len(), 1);
+        assert!(reopened.archive_cache.lock().unwrap().is_none());
+
+        let completed = TaskQuery {
+            status: Some(TaskStatus::Completed),
+            ..Default::default()
+        };
+        assert_eq!(reopened.query_tasks(&completed, None).unwrap().len(), 3);
+        assert_eq!(reopened.load_all_tasks().unwrap().len(), 4);
+        assert_eq!(reopened.load_task(old.id).unwrap().unwrap().description, "Old");
+    }
+
+    #[test]
+    fn test_archived_task_updates_and_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(temp_dir.path());
+        let mut task = finished("Done", 2023);
+        let other = finished("Also done", 2023);
+        storage.save_task(&task).unwrap();
+        storage.save_task(&other).unwrap();
+        storage.archive_completed(Utc::now()).unwrap();
+
+        // Edits to a finished task stay in the archive
+        task.description = "Done, edited".to_string();
+        storage.save_task(&task).unwrap();
+        let archived = load(temp_dir.path()).unwrap();
+        assert_eq!(archived[&task.id].description, "Done, edited");
+
+        // Reopening moves it back to tasks.json
+        task.status = TaskStatus::Pending;
+        task.end = None;
+        storage.save_task(&task).unwrap();
+        assert!(!load(temp_dir.path()).unwrap().contains_key(&task.id));
+        let reopened = FileStorageBackend::with_path(temp_dir.path());
+        assert_eq!(reopened.load_task(task.id).unwrap().unwrap().status, TaskStatus::Pending);
+
+        // Deleting the last archived task of a year removes its file
+        storage.delete_task(other.id).unwrap();
+        assert!(!storage.archive_file_path(2023).exists());
+        assert!(matches!(storage.delete_task(other.id), Err(TaskError::NotFound { .. })));
+    }
+}
@@ -0,0 +1,300 @@
+//! Data directory introspection
+//!
+//! [`inspect`] looks at a data directory without changing it and reports
+//! which storage formats it holds, every file with its size and
+//! modification time, and how many tasks of each status each format
+//! contains. This is the backend of a `task diagnostics`-style command, and
+//! shows at a glance when an old Taskwarrior 2 data set sits next to a
+//! TaskChampion database.
+//!
+//! | Format                       | Recognized files                                         |
+//! |------------------------------|----------------------------------------------------------|
+//! | [`DataFormat::TaskChampion`] | `taskchampion.sqlite3` (and its `-wal`/`-shm` files)     |
+//! | [`DataFormat::FileBackend`]  | `tasks.json`, its manifest, archives and SQLite index    |
+//! | [`DataFormat::Taskwarrior2`] | `pending.data`, `completed.data`, `undo.data`, `backlog.data` |
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::TaskError;
+use crate::io::taskwarrior2::read_data_file;
+use crate::storage::{FileStorageBackend, StorageBackend, TaskChampionStorageBackend};
+use crate::task::{Task, TaskStatus, UdaTypes};
+
+/// TaskChampion's database file
+pub const TASKCHAMPION_DB: &str = "taskchampion.sqlite3";
+
+/// A way tasks can be stored in a data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    /// Taskwarrior 3's SQLite replica
+    TaskChampion,
+    /// This library's `tasks.json` file backend
+    FileBackend,
+    /// Taskwarrior 2's `.data` files
+    Taskwarrior2,
+}
+
+impl DataFormat {
+    /// The format a file in the data directory belongs to, by name
+    pub fn of_file(name: &str) -> Option<Self> {
+        match name {
+            _ if name.starts_with(TASKCHAMPION_DB) => Some(DataFormat::TaskChampion),
+            "tasks.json" | "tasks.json.manifest" => Some(DataFormat::FileBackend),
+            #[cfg(feature = "sqlite-index")]
+            crate::storage::sqlite_index::INDEX_FILE => Some(DataFormat::FileBackend),
+            _ if name.starts_with("completed-") && name.ends_with(".json") => {
+                Some(DataFormat::FileBackend)
+            }
+            "pending.data" | "completed.data" | "undo.data" | "backlog.data" => {
+                Some(DataFormat::Taskwarrior2)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DataFormat::TaskChampion => "TaskChampion SQLite",
+            DataFormat::FileBackend => "file backend",
+            DataFormat::Taskwarrior2 => "Taskwarrior 2 data files",
+        })
+    }
+}
+
+/// Number of tasks in each status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCounts {
+    pub pending: usize,
+    pub waiting: usize,
+    pub recurring: usize,
+    pub completed: usize,
+    pub deleted: usize,
+}
+
+impl StatusCounts {
+    pub fn from_tasks(tasks: &[Task]) -> Self {
+        let mut counts = Self::default();
+        for task in tasks {
+            let count = match task.status {
+                TaskStatus::Pending => &mut counts.pending,
+                TaskStatus::Waiting => &mut counts.waiting,
+                TaskStatus::Recurring => &mut counts.recurring,
+                TaskStatus::Completed => &mut counts.completed,
+                TaskStatus::Deleted => &mut counts.deleted,
+            };
+            *count += 1;
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.pending + self.waiting + self.recurring + self.completed + self.deleted
+    }
+}
+
+/// One file below the data directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataFile {
+    /// Path relative to the data directory
+    pub path: PathBuf,
+    /// Format the file belongs to; `None` for backups and unknown files
+    pub format: Option<DataFormat>,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// The tasks found in one storage format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatReport {
+    pub format: DataFormat,
+    pub tasks: StatusCounts,
+    /// Why the tasks could not be counted, if they could not
+    pub error: Option<String>,
+}
+
+/// What a data directory contains
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataDirReport {
+    pub data_dir: PathBuf,
+    pub exists: bool,
+    /// Formats present, in [`DataFormat`] order
+    pub formats: Vec<FormatReport>,
+    /// Every file, sorted by path
+    pub files: Vec<DataFile>,
+}
+
+impl DataDirReport {
+    /// Combined size of all files in bytes
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+
+    /// Latest modification time of any file
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.files.iter().filter_map(|f| f.modified).max()
+    }
+
+    /// Whether more than one storage format is present, e.g. after a
+    /// migration that left the old files behind
+    pub fn is_mixed(&self) -> bool {
+        self.formats.len() > 1
+    }
+}
+
+/// Describe the data directory at `data_dir` without modifying it
+pub fn inspect(data_dir: &Path) -> Result<DataDirReport, TaskError> {
+    let mut report = DataDirReport {
+        data_dir: data_dir.to_path_buf(),
+        exists: data_dir.is_dir(),
+        formats: Vec::new(),
+        files: Vec::new(),
+    };
+    if !report.exists {
+        return Ok(report);
+    }
+    collect_files(data_dir, Path::new(""), &mut report.files)?;
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let present = |name: &str| data_dir.join(name).is_file();
+    if present(TASKCHAMPION_DB) {
+        let storage = TaskChampionStorageBackend::new(data_dir.join(TASKCHAMPION_DB));
+        report
+            .formats
+            .push(count(DataFormat::TaskChampion, storage.load_all_tasks()));
+    }
+    if present("tasks.json") {
+        let storage = FileStorageBackend::with_path(data_dir);
+        report
+            .formats
+            .push(count(DataFormat::FileBackend, storage.load_all_tasks()));
+    }
+    if present("pending.data") || present("completed.data") {
+        let uda_types = UdaTypes::new();
+        let mut tasks = Vec::new();
+        let mut error = None;
+        for name in ["pending.data", "completed.data"] {
+            let path = data_dir.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            match read_data_file(&path, &uda_types) {
+                Ok(file) => tasks.extend(file.tasks),
+                Err(e) => error = Some(format!("{name}: {e}")),
+            }
+        }
+        report.formats.push(FormatReport {
+            format: DataFormat::Taskwarrior2,
+            tasks: StatusCounts::from_tasks(&tasks),
+            error,
+        });
+    }
+    Ok(report)
+}
+
+fn count(format: DataFormat, tasks: Result<Vec<Task>, TaskError>) -> FormatReport {
+    match tasks {
+        Ok(tasks) => FormatReport {
+            format,
+            tasks: StatusCounts::from_tasks(&tasks),
+            error: None,
+        },
+        Err(e) => FormatReport {
+            format,
+            tasks: StatusCounts::default(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<DataFile>) -> Result<(), TaskError> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+        // Only top-level files belong to a format; the rest are backups
+        let format = if relative.as_os_str().is_empty() {
+            DataFormat::of_file(&entry.file_name().to_string_lossy())
+        } else {
+            None
+        };
+        files.push(DataFile {
+            path,
+            format,
+            size: metadata.len(),
+            modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_inspect_mixed_directory() {
+        let dir = TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(dir.path());
+        storage.initialize().unwrap();
+        let mut done = Task::new("Done".to_string());
+        done.complete();
+        storage.save_task(&Task::new("Open".to_string())).unwrap();
+        storage.save_task(&done).unwrap();
+
+        let uuid = "a1b2c3d4-0000-4000-8000-000000000001";
+        fs::write(
+            dir.path().join("pending.data"),
+            format!("[description:\"Old task\" entry:\"1700000000\" status:\"pending\" uuid:\"{uuid}\"]\n"),
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+
+        let report = inspect(dir.path()).unwrap();
+        assert!(report.exists);
+        assert!(report.is_mixed());
+        let formats: Vec<(DataFormat, usize)> = report
+            .formats
+            .iter()
+            .map(|f| (f.format, f.tasks.total()))
+            .collect();
+        assert_eq!(
+            formats,
+            [(DataFormat::FileBackend, 2), (DataFormat::Taskwarrior2, 1)]
+        );
+        assert_eq!(report.formats[0].tasks.completed, 1);
+
+        let notes = report
+            .files
+            .iter()
+            .find(|f| f.path == Path::new("notes.txt"))
+            .unwrap();
+        assert_eq!((notes.format, notes.size), (None, 5));
+        assert!(report
+            .files
+            .iter()
+            .any(|f| f.format == Some(DataFormat::FileBackend)));
+        assert!(report.total_size() > 5);
+        assert!(report.last_modified().is_some());
+    }
+
+    #[test]
+    fn test_inspect_missing_directory() {
+        let dir = TempDir::new().unwrap();
+        let report = inspect(&dir.path().join("nowhere")).unwrap();
+        assert!(!report.exists);
+        assert!(report.formats.is_empty() && report.files.is_empty());
+    }
+}
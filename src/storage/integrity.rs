@@ -0,0 +1,144 @@
+//! Storage integrity checks
+//!
+//! [`StorageBackend::verify`](crate::storage::StorageBackend::verify) returns
+//! an [`IntegrityReport`] so backup and sync tooling can detect silent
+//! corruption before copying it elsewhere.
+//!
+//! The file backend writes a [`Manifest`] (`tasks.json.manifest`) next to
+//! `tasks.json` on every save, recording the file's size, task count and an
+//! FNV-1a checksum. The checksum catches accidental damage (truncation, bit
+//! rot, partial copies); it is not a defence against deliberate tampering.
+//! The TaskChampion backend runs SQLite's `PRAGMA integrity_check` and checks
+//! that every task row parses.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Checksum algorithm recorded in manifests
+pub const CHECKSUM_ALGORITHM: &str = "fnv1a-64";
+
+/// Something wrong found while verifying storage
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityIssue {
+    /// Data exists but its manifest does not (e.g. written by an older version)
+    MissingManifest { path: PathBuf },
+    /// The manifest could not be read
+    InvalidManifest { path: PathBuf, message: String },
+    /// The data does not match the manifest's checksum
+    ChecksumMismatch { expected: String, actual: String },
+    /// The data's size differs from the manifest
+    SizeMismatch { expected: u64, actual: u64 },
+    /// The number of tasks differs from the manifest
+    TaskCountMismatch { expected: usize, actual: usize },
+    /// The same UUID appears more than once
+    DuplicateTask { id: Uuid },
+    /// A task record could not be parsed
+    UnreadableTask { id: Option<Uuid>, message: String },
+    /// The database reported a problem
+    Database { message: String },
+}
+
+impl std::fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityIssue::MissingManifest { path } => {
+                write!(f, "manifest missing: {}", path.display())
+            }
+            IntegrityIssue::InvalidManifest { path, message } => {
+                write!(f, "manifest unreadable: {}: {message}", path.display())
+            }
+            IntegrityIssue::ChecksumMismatch { expected, actual } => {
+                write!(f, "checksum mismatch: expected {expected}, found {actual}")
+            }
+            IntegrityIssue::SizeMismatch { expected, actual } => {
+                write!(f, "size mismatch: expected {expected} bytes, found {actual}")
+            }
+            IntegrityIssue::TaskCountMismatch { expected, actual } => {
+                write!(f, "task count mismatch: expected {expected}, found {actual}")
+            }
+            IntegrityIssue::DuplicateTask { id } => write!(f, "duplicate task {id}"),
+            IntegrityIssue::UnreadableTask { id: Some(id), message } => {
+                write!(f, "task {id} unreadable: {message}")
+            }
+            IntegrityIssue::UnreadableTask { id: None, message } => {
+                write!(f, "task data unreadable: {message}")
+            }
+            IntegrityIssue::Database { message } => write!(f, "database: {message}"),
+        }
+    }
+}
+
+/// Result of verifying a storage backend
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// Number of task records examined
+    pub tasks_checked: usize,
+    /// Checksum of the data as it is now, when the backend computes one
+    pub checksum: Option<String>,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Whether no issues were found
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checksum record written alongside a data file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub algorithm: String,
+    pub checksum: String,
+    pub size: u64,
+    pub task_count: usize,
+    pub written: DateTime<Utc>,
+}
+
+impl Manifest {
+    /// Manifest describing `data`
+    pub fn for_data(data: &[u8], task_count: usize) -> Self {
+        Self {
+            algorithm: CHECKSUM_ALGORITHM.to_string(),
+            checksum: checksum(data),
+            size: data.len() as u64,
+            task_count,
+            written: Utc::now(),
+        }
+    }
+
+    /// Path of the manifest for `data_file`
+    pub fn path_for(data_file: &Path) -> PathBuf {
+        let mut name = data_file.file_name().unwrap_or_default().to_os_string();
+        name.push(".manifest");
+        data_file.with_file_name(name)
+    }
+}
+
+/// FNV-1a 64-bit checksum as lowercase hex
+pub fn checksum(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_is_stable() {
+        assert_eq!(checksum(b""), "cbf29ce484222325");
+        assert_eq!(checksum(b"a"), "af63dc4c8601ec8c");
+        assert_ne!(checksum(b"tasks"), checksum(b"taskz"));
+    }
+
+    #[test]
+    fn test_manifest_path() {
+        let path = Manifest::path_for(Path::new("/data/tasks.json"));
+        assert_eq!(path, PathBuf::from("/data/tasks.json.manifest"));
+    }
+}
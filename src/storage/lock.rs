@@ -0,0 +1,277 @@
+//! Cross-process advisory lock on a TaskChampion database
+//!
+//! Writers take an exclusive lock on `<database>.lock` next to the
+//! database for the length of a write, and readers may take a shared one.
+//! This serialises processes using this library only: the `task` CLI never
+//! takes the lock. Against the CLI, saves rely on SQLite instead, reading
+//! and writing each task in one `BEGIN IMMEDIATE` transaction and waiting
+//! out the CLI's transactions with a busy timeout.
+//!
+//! The lock is advisory (`flock` on Unix, `LockFileEx` on Windows), so only
+//! cooperating processes honour it. It is governed by the `locking`
+//! setting, and is released when the [`DataDirLock`] is dropped or the
+//! process exits, so a crash never leaves a stale lock behind. The lock
+//! file records the process holding it for error messages.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::Configuration;
+use crate::error::{ConfigError, StorageError, TaskError};
+
+/// How to take the lock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockOptions {
+    /// Whether to lock at all
+    pub enabled: bool,
+    /// How long to wait for another process to release the lock;
+    /// zero tries once
+    pub timeout: Duration,
+    /// Pause between attempts while waiting
+    pub poll_interval: Duration,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+impl LockOptions {
+    /// Never lock
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Read the CLI's `locking` switch (on by default) and
+    /// `locking.timeout` in milliseconds
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let mut options = Self::default();
+        if let Some(raw) = config.get("locking") {
+            options.enabled =
+                config
+                    .get_bool("locking")
+                    .ok_or_else(|| ConfigError::InvalidValue {
+                        key: "locking".to_string(),
+                        value: raw.to_string(),
+                        expected: "on or off".to_string(),
+                    })?;
+        }
+        if let Some(raw) = config.get("locking.timeout") {
+            let ms = raw.trim().parse().map_err(|_| ConfigError::InvalidValue {
+                key: "locking.timeout".to_string(),
+                value: raw.to_string(),
+                expected: "milliseconds".to_string(),
+            })?;
+            options.timeout = Duration::from_millis(ms);
+        }
+        Ok(options)
+    }
+}
+
+/// The lock file guarding `db_path`
+pub fn lock_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    db_path.with_file_name(name)
+}
+
+/// A held lock, released on drop
+#[derive(Debug)]
+pub struct DataDirLock {
+    file: File,
+    path: PathBuf,
+    exclusive: bool,
+}
+
+impl DataDirLock {
+    /// Take the exclusive lock for writing to `db_path`
+    pub fn exclusive(db_path: &Path, options: &LockOptions) -> Result<Self, TaskError> {
+        Self::acquire(db_path, options, true)
+    }
+
+    /// Take a shared lock for reading `db_path`; waits only for writers
+    pub fn shared(db_path: &Path, options: &LockOptions) -> Result<Self, TaskError> {
+        Self::acquire(db_path, options, false)
+    }
+
+    fn acquire(db_path: &Path, options: &LockOptions, exclusive: bool) -> Result<Self, TaskError> {
+        let path = lock_path(db_path);
+        let io = |e: std::io::Error| TaskError::Storage {
+            source: StorageError::Io(e),
+        };
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io)?;
+
+        let started = Instant::now();
+        loop {
+            let attempt = if exclusive {
+                file.try_lock()
+            } else {
+                file.try_lock_shared()
+            };
+            match attempt {
+                Ok(()) => break,
+                Err(TryLockError::Error(e)) => return Err(io(e)),
+                Err(TryLockError::WouldBlock) if started.elapsed() < options.timeout => {
+                    std::thread::sleep(options.poll_interval.min(options.timeout));
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(TaskError::Storage {
+                        source: StorageError::Locked {
+                            holder: holder(&mut file),
+                            path,
+                            waited: started.elapsed(),
+                        },
+                    });
+                }
+            }
+        }
+
+        if exclusive {
+            // Best effort: the lock is what counts, the note is for humans
+            let _ = file.set_len(0).and_then(|_| {
+                file.rewind()?;
+                writeln!(file, "{}", describe_process())
+            });
+        }
+        Ok(Self {
+            file,
+            path,
+            exclusive,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+}
+
+impl Drop for DataDirLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+fn describe_process() -> String {
+    let program = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "unknown".to_string());
+    format!("pid {} ({program})", std::process::id())
+}
+
+// Who holds the lock, from the note the holder wrote into the lock file
+fn holder(file: &mut File) -> String {
+    let mut note = String::new();
+    let _ = file.rewind().and_then(|_| file.read_to_string(&mut note));
+    match note.trim() {
+        "" => "another process".to_string(),
+        note => note.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick() -> LockOptions {
+        LockOptions::default().timeout(Duration::ZERO)
+    }
+
+    #[test]
+    fn test_exclusive_lock_excludes_others() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("taskchampion.sqlite3");
+        let lock = DataDirLock::exclusive(&db, &quick()).unwrap();
+        assert_eq!(lock.path(), dir.path().join("taskchampion.sqlite3.lock"));
+
+        let err = DataDirLock::shared(&db, &quick()).unwrap_err();
+        match err {
+            TaskError::Storage {
+                source: StorageError::Locked { holder, .. },
+            } => assert_eq!(holder, describe_process()),
+            other => panic!("expected Locked, got {other:?}"),
+        }
+        assert!(DataDirLock::exclusive(&db, &quick()).is_err());
+
+        drop(lock);
+        let first = DataDirLock::shared(&db, &quick()).unwrap();
+        let second = DataDirLock::shared(&db, &quick()).unwrap();
+        assert!(!first.is_exclusive() && !second.is_exclusive());
+        assert!(DataDirLock::exclusive(&db, &quick()).is_err());
+    }
+
+    #[test]
+    fn test_waits_for_release() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("taskchampion.sqlite3");
+        let lock = DataDirLock::exclusive(&db, &quick()).unwrap();
+        let releaser = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(lock);
+        });
+        let options = LockOptions::default().timeout(Duration::from_secs(5));
+        assert!(DataDirLock::exclusive(&db, &options).is_ok());
+        releaser.join().unwrap();
+    }
+
+    #[test]
+    fn test_taskchampion_writes_take_the_lock() {
+        use crate::storage::{StorageBackend, TaskChampionStorageBackend};
+        use crate::task::Task;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = dir.path().join("taskchampion.sqlite3");
+        let mut storage = TaskChampionStorageBackend::new(&db).with_locking(quick());
+        let _cli = DataDirLock::exclusive(&db, &quick()).unwrap();
+        let err = storage
+            .save_task(&Task::new("Blocked".to_string()))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TaskError::Storage {
+                source: StorageError::Locked { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn test_options_from_config() {
+        let mut config = Configuration::default();
+        assert_eq!(
+            LockOptions::from_config(&config).unwrap(),
+            LockOptions::default()
+        );
+        config.set("locking", "off");
+        config.set("locking.timeout", "250");
+        let options = LockOptions::from_config(&config).unwrap();
+        assert!(!options.enabled);
+        assert_eq!(options.timeout, Duration::from_millis(250));
+        config.set("locking", "sometimes");
+        assert!(LockOptions::from_config(&config).is_err());
+    }
+}
@@ -3,24 +3,43 @@
 //! This module provides storage backends for task data, including file-based
 //! and database storage options.
 
+mod archive;
+pub mod inspect;
+pub mod integrity;
+pub mod lock;
+pub mod retry;
 pub mod serialization;
 pub mod taskchampion;
+// Plumbing between the task model and TaskChampion; public for tests and
+// tooling but outside the stable API (see `crate::api`)
+#[doc(hidden)]
 pub mod operation_batch;
+#[doc(hidden)]
 pub mod replica_wrapper;
+#[doc(hidden)]
 pub mod replica_taskchampion;
+#[cfg(feature = "sqlite-index")]
+pub mod sqlite_index;
 
+pub use inspect::{inspect, DataDirReport, DataFormat};
+pub use integrity::{IntegrityIssue, IntegrityReport};
+pub use lock::{DataDirLock, LockOptions};
+pub use retry::{RetryMetrics, RetryPolicy, RetryingStorageBackend};
 pub use taskchampion::TaskChampionStorageBackend;
 
+use crate::clock;
 use crate::error::{StorageError, TaskError};
 use crate::query::TaskQuery;
-use crate::task::Task;
+use crate::task::{Task, TaskStatus};
+use chrono::{DateTime, Utc};
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::{self, File, OpenOptions};
-use std::io::{BufReader, BufWriter};
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::time::SystemTime;
+#[cfg(feature = "sqlite-index")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
 use uuid::Uuid;
 
 /// Storage backend trait for task data
@@ -37,9 +56,22 @@ pub trait StorageBackend: std::fmt::Debug {
     /// Delete a task
     fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError>;
 
+    /// Permanently remove a task. Backends that keep deleted tasks around
+    /// (e.g. TaskChampion) override this to expunge the task entirely.
+    fn purge_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        self.delete_task(id)
+    }
+
     /// Load all tasks
     fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError>;
 
+    /// Move completed and deleted tasks that finished before `before` out
+    /// of the working data into long-term storage; returns how many moved.
+    /// Backends without an archive keep everything in place.
+    fn archive_completed(&mut self, _before: DateTime<Utc>) -> Result<usize, TaskError> {
+        Ok(0)
+    }
+
     /// Query tasks with filters. The optional active_context is the
     /// currently active Taskwarrior context (if any) and may be used by
     /// backends to combine context read filters with the explicit query.
@@ -49,6 +81,29 @@ pub trait StorageBackend: std::fmt::Debug {
         active_context: Option<&crate::config::context::UserContext>,
     ) -> Result<Vec<Task>, TaskError>;
 
+    /// Check stored data for corruption. The default loads every task and
+    /// reports duplicate UUIDs; backends with checksums or database checks
+    /// override it.
+    fn verify(&self) -> Result<IntegrityReport, TaskError> {
+        let tasks = self.load_all_tasks()?;
+        let mut report = IntegrityReport {
+            tasks_checked: tasks.len(),
+            ..Default::default()
+        };
+        let mut seen = std::collections::HashSet::new();
+        for task in &tasks {
+            if !seen.insert(task.id) {
+                report.issues.push(IntegrityIssue::DuplicateTask { id: task.id });
+            }
+        }
+        Ok(report)
+    }
+
+    /// Short name of the backend for error messages, e.g. `file`
+    fn backend_name(&self) -> &str {
+        "custom"
+    }
+
     /// Backup storage
     fn backup(&self) -> Result<String, StorageError>;
 
@@ -72,6 +127,11 @@ pub trait TaskStorage {
 }
 
 /// File-based storage backend
+///
+/// Tasks live in `tasks.json`. Finished tasks can be moved to yearly
+/// `completed-<year>.json` archive files with
+/// [`archive_completed`](StorageBackend::archive_completed); those are only
+/// read when a query can match completed or deleted tasks.
 #[derive(Debug)]
 pub struct FileStorageBackend {
     data_path: PathBuf,
@@ -80,6 +140,17 @@ pub struct FileStorageBackend {
     initialized: bool,
     // In-memory cache for performance
     task_cache: Arc<Mutex<HashMap<Uuid, Task>>>,
+    // Whether `task_cache` holds `tasks.json`; with an index in sync the
+    // file is only read on the first write
+    #[cfg(feature = "sqlite-index")]
+    cache_loaded: AtomicBool,
+    // Archived tasks, loaded on first use
+    archive_cache: Arc<Mutex<Option<HashMap<Uuid, Task>>>>,
+    // Whether to maintain the SQLite field index
+    #[cfg(feature = "sqlite-index")]
+    use_index: bool,
+    #[cfg(feature = "sqlite-index")]
+    index: Option<sqlite_index::SqliteTaskIndex>,
 }
 
 impl FileStorageBackend {
@@ -92,6 +163,13 @@ impl FileStorageBackend {
             data_path,
             initialized: false,
             task_cache: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "sqlite-index")]
+            cache_loaded: AtomicBool::new(false),
+            archive_cache: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "sqlite-index")]
+            use_index: false,
+            #[cfg(feature = "sqlite-index")]
+            index: None,
         }
     }
 
@@ -104,6 +182,13 @@ impl FileStorageBackend {
             data_path,
             initialized: false,
             task_cache: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "sqlite-index")]
+            cache_loaded: AtomicBool::new(false),
+            archive_cache: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "sqlite-index")]
+            use_index: false,
+            #[cfg(feature = "sqlite-index")]
+            index: None,
         }
     }
 
@@ -112,6 +197,157 @@ impl FileStorageBackend {
         &self.tasks_file
     }
 
+    /// Path of the archive file for tasks finished in `year`
+    pub fn archive_file_path(&self, year: i32) -> PathBuf {
+        archive::file_path(&self.data_path, year)
+    }
+
+    /// Read the archive files unless already loaded. Tasks also present
+    /// in `tasks.json` (left by an interrupted move) are skipped.
+    fn load_archive(&self) -> Result<(), TaskError> {
+        let mut archive = self.archive_cache.lock().unwrap();
+        if archive.is_none() {
+            let mut tasks = archive::load(&self.data_path)?;
+            for id in self.hot_ids()? {
+                tasks.remove(&id);
+            }
+            *archive = Some(tasks);
+        }
+        Ok(())
+    }
+
+    /// The tasks of `tasks.json`, read into the cache on first use
+    fn hot_tasks(&self) -> Result<MutexGuard<'_, HashMap<Uuid, Task>>, TaskError> {
+        #[cfg(feature = "sqlite-index")]
+        if !self.cache_loaded.load(Ordering::Acquire) {
+            let mut cache = self.task_cache.lock().unwrap();
+            *cache = match &self.index {
+                Some(index) => self.sync_index(index, true)?.unwrap_or_default(),
+                None => self.load_tasks_from_file()?,
+            };
+            self.cache_loaded.store(true, Ordering::Release);
+            return Ok(cache);
+        }
+        Ok(self.task_cache.lock().unwrap())
+    }
+
+    /// A task of `tasks.json`, read from the index until the cache is loaded
+    fn cached_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        #[cfg(feature = "sqlite-index")]
+        if let (false, Some(index)) = (self.cache_loaded.load(Ordering::Acquire), &self.index) {
+            return index.task(id);
+        }
+        Ok(self.task_cache.lock().unwrap().get(&id).cloned())
+    }
+
+    /// UUIDs of the tasks in `tasks.json`
+    fn hot_ids(&self) -> Result<Vec<Uuid>, TaskError> {
+        if !self.initialized {
+            return Ok(self.load_tasks_from_file()?.into_keys().collect());
+        }
+        #[cfg(feature = "sqlite-index")]
+        if let (false, Some(index)) = (self.cache_loaded.load(Ordering::Acquire), &self.index) {
+            return index.ids();
+        }
+        Ok(self.task_cache.lock().unwrap().keys().copied().collect())
+    }
+
+    /// Replace an archived task in place; false if it is not archived
+    fn update_archived(&self, task: &Task) -> Result<bool, TaskError> {
+        let mut guard = self.archive_cache.lock().unwrap();
+        let Some(archive) = guard.as_mut() else {
+            return Ok(false);
+        };
+        let Some(old) = archive.insert(task.id, task.clone()) else {
+            archive.remove(&task.id);
+            return Ok(false);
+        };
+        let years = BTreeSet::from([archive::year_of(&old), archive::year_of(task)]);
+        archive::write_years(&self.data_path, archive, &years)?;
+        Ok(true)
+    }
+
+    /// Drop a task from the archive; false if it was not archived
+    fn remove_archived(&self, id: Uuid) -> Result<bool, TaskError> {
+        let mut guard = self.archive_cache.lock().unwrap();
+        let Some(archive) = guard.as_mut() else {
+            return Ok(false);
+        };
+        let Some(old) = archive.remove(&id) else {
+            return Ok(false);
+        };
+        archive::write_years(&self.data_path, archive, &BTreeSet::from([archive::year_of(&old)]))?;
+        Ok(true)
+    }
+
+    /// Every task, archived ones included
+    fn all_tasks_with_archive(&self, mut tasks: HashMap<Uuid, Task>) -> Result<HashMap<Uuid, Task>, TaskError> {
+        self.load_archive()?;
+        let archive = self.archive_cache.lock().unwrap();
+        for (id, task) in archive.iter().flatten() {
+            tasks.entry(*id).or_insert_with(|| task.clone());
+        }
+        Ok(tasks)
+    }
+
+    /// Maintain a SQLite index of key fields next to the tasks file and
+    /// answer reads from it. Queries load only the tasks matching their
+    /// status and project, and sort and page in SQL where they can;
+    /// `tasks.json` is parsed on the first write rather than on
+    /// [`initialize`](StorageBackend::initialize) while the index is in
+    /// sync with it.
+    #[cfg(feature = "sqlite-index")]
+    pub fn with_sqlite_index(mut self) -> Self {
+        self.use_index = true;
+        self
+    }
+
+    /// Open the index and bring it in sync with the tasks file. Returns
+    /// the tasks when the file had to be parsed to rebuild the index.
+    #[cfg(feature = "sqlite-index")]
+    fn open_index(&mut self) -> Result<Option<HashMap<Uuid, Task>>, TaskError> {
+        let index =
+            sqlite_index::SqliteTaskIndex::open(self.data_path.join(sqlite_index::INDEX_FILE))?;
+        let tasks = self.sync_index(&index, false)?;
+        self.index = Some(index);
+        Ok(tasks)
+    }
+
+    /// Rebuild `index` if the tasks file changed since the index last saw
+    /// it. The file is only parsed for that, or when `parse` is set, and
+    /// its tasks are returned if it was.
+    #[cfg(feature = "sqlite-index")]
+    fn sync_index(
+        &self,
+        index: &sqlite_index::SqliteTaskIndex,
+        parse: bool,
+    ) -> Result<Option<HashMap<Uuid, Task>>, TaskError> {
+        let data = match fs::read(&self.tasks_file) {
+            Ok(data) => Some(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(TaskError::Storage {
+                    source: StorageError::Io(e),
+                })
+            }
+        };
+        // The JSON file is authoritative
+        let current = integrity::checksum(data.as_deref().unwrap_or_default());
+        let stale = index.source_checksum()?.as_deref() != Some(current.as_str());
+        if !stale && !parse {
+            return Ok(None);
+        }
+        let tasks = match &data {
+            Some(data) => parse_tasks(data)?,
+            None => HashMap::new(),
+        };
+        if stale {
+            index.rebuild(tasks.values())?;
+            index.set_source_checksum(&current)?;
+        }
+        Ok(Some(tasks))
+    }
+
     /// Load all tasks from file into cache
     fn load_tasks_from_file(&self) -> Result<HashMap<Uuid, Task>, TaskError> {
         if !self.tasks_file.exists() {
@@ -123,11 +359,7 @@ impl FileStorageBackend {
         })?;
 
         let reader = BufReader::new(file);
-        let tasks: Vec<Task> = serde_json::from_reader(reader).map_err(|e| TaskError::Storage {
-            source: StorageError::SerializationError {
-                message: format!("Failed to parse tasks file: {e}"),
-            },
-        })?;
+        let tasks: Vec<Task> = serde_json::from_reader(reader).map_err(tasks_file_error)?;
 
         let mut task_map = HashMap::new();
         for task in tasks {
@@ -147,6 +379,13 @@ impl FileStorageBackend {
         // Write to temporary file first
         let temp_file = self.tasks_file.with_extension("tmp");
 
+        let task_vec: Vec<&Task> = tasks.values().collect();
+        let data = serde_json::to_vec_pretty(&task_vec).map_err(|e| TaskError::Storage {
+            source: StorageError::SerializationError {
+                message: format!("Failed to serialize tasks: {e}"),
+            },
+        })?;
+
         {
             let file = OpenOptions::new()
                 .create(true)
@@ -157,13 +396,12 @@ impl FileStorageBackend {
                     source: StorageError::Io(e),
                 })?;
 
-            let writer = BufWriter::new(file);
-            let task_vec: Vec<&Task> = tasks.values().collect();
-
-            serde_json::to_writer_pretty(writer, &task_vec).map_err(|e| TaskError::Storage {
-                source: StorageError::SerializationError {
-                    message: format!("Failed to serialize tasks: {e}"),
-                },
+            let mut writer = BufWriter::new(file);
+            writer.write_all(&data).map_err(|e| TaskError::Storage {
+                source: StorageError::Io(e),
+            })?;
+            writer.flush().map_err(|e| TaskError::Storage {
+                source: StorageError::Io(e),
             })?;
         }
 
@@ -172,7 +410,29 @@ impl FileStorageBackend {
             source: StorageError::Io(e),
         })?;
 
-        Ok(())
+        #[cfg(feature = "sqlite-index")]
+        if let Some(index) = &self.index {
+            index.set_source_checksum(&integrity::checksum(&data))?;
+        }
+
+        self.write_manifest(&data, task_vec.len())
+    }
+
+    /// Record the checksum of the tasks file just written
+    fn write_manifest(&self, data: &[u8], task_count: usize) -> Result<(), TaskError> {
+        let manifest = integrity::Manifest::for_data(data, task_count);
+        let path = integrity::Manifest::path_for(&self.tasks_file);
+        let temp_file = path.with_extension("manifest.tmp");
+        let json = serde_json::to_vec_pretty(&manifest).map_err(|e| TaskError::Storage {
+            source: StorageError::SerializationError {
+                message: format!("Failed to serialize manifest: {e}"),
+            },
+        })?;
+        fs::write(&temp_file, json)
+            .and_then(|_| fs::rename(&temp_file, &path))
+            .map_err(|e| TaskError::Storage {
+                source: StorageError::Io(e),
+            })
     }
 
     /// Create a backup of the current tasks file
@@ -187,10 +447,7 @@ impl FileStorageBackend {
         })?;
 
         // Create timestamped backup filename
-        let timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = clock::now().timestamp();
 
         let backup_file = self.backup_dir.join(format!("tasks_{timestamp}.json"));
 
@@ -260,9 +517,37 @@ impl FileStorageBackend {
                     }
                 }
 
-                // Date filter (simplified implementation)
-                if let Some(_date_filter) = &query.date_filter {
-                    // TODO: Implement date filtering when needed
+                // Date filter
+                if let Some(date_filter) = &query.date_filter {
+                    if !date_filter.matches(task) {
+                        return false;
+                    }
+                }
+
+                // Priority filter
+                if let Some(priority_filter) = &query.priority_filter {
+                    if !priority_filter.matches(task.priority_code()) {
+                        return false;
+                    }
+                }
+
+                // Owner filter
+                if let Some(owner_filter) = &query.owner_filter {
+                    if !owner_filter.matches(task.owner.as_deref()) {
+                        return false;
+                    }
+                }
+
+                // Proximity filter
+                if let Some(proximity) = &query.proximity {
+                    if !proximity.matches(task) {
+                        return false;
+                    }
+                }
+
+                // Custom predicates are evaluated in memory
+                if !query.matches_custom(task) {
+                    return false;
                 }
 
                 // If there's an active context and the query does not explicitly
@@ -286,73 +571,7 @@ impl FileStorageBackend {
             .cloned()
             .collect();
 
-        // Apply sorting
-        if let Some(sort_criteria) = &query.sort {
-            match sort_criteria.field.as_str() {
-                "entry" | "created" => {
-                    filtered.sort_by(|a, b| {
-                        if sort_criteria.ascending {
-                            a.entry.cmp(&b.entry)
-                        } else {
-                            b.entry.cmp(&a.entry)
-                        }
-                    });
-                }
-                "modified" => {
-                    filtered.sort_by(|a, b| {
-                        let a_time = a.modified.unwrap_or(a.entry);
-                        let b_time = b.modified.unwrap_or(b.entry);
-                        if sort_criteria.ascending {
-                            a_time.cmp(&b_time)
-                        } else {
-                            b_time.cmp(&a_time)
-                        }
-                    });
-                }
-                "due" => {
-                    filtered.sort_by(|a, b| match (a.due, b.due) {
-                        (Some(a_due), Some(b_due)) => {
-                            if sort_criteria.ascending {
-                                a_due.cmp(&b_due)
-                            } else {
-                                b_due.cmp(&a_due)
-                            }
-                        }
-                        (Some(_), None) => std::cmp::Ordering::Less,
-                        (None, Some(_)) => std::cmp::Ordering::Greater,
-                        (None, None) => std::cmp::Ordering::Equal,
-                    });
-                }
-                "priority" => {
-                    filtered.sort_by(|a, b| {
-                        match (a.priority, b.priority) {
-                            (Some(a_pri), Some(b_pri)) => {
-                                if sort_criteria.ascending {
-                                    a_pri.cmp(&b_pri)
-                                } else {
-                                    b_pri.cmp(&a_pri) // Higher priority first
-                                }
-                            }
-                            (Some(_), None) => std::cmp::Ordering::Less,
-                            (None, Some(_)) => std::cmp::Ordering::Greater,
-                            (None, None) => std::cmp::Ordering::Equal,
-                        }
-                    });
-                }
-                "project" => {
-                    filtered.sort_by(|a, b| {
-                        let a_project = a.project.as_deref().unwrap_or("");
-                        let b_project = b.project.as_deref().unwrap_or("");
-                        if sort_criteria.ascending {
-                            a_project.cmp(b_project)
-                        } else {
-                            b_project.cmp(a_project)
-                        }
-                    });
-                }
-                _ => {} // Unknown sort field, ignore
-            }
-        }
+        query.apply_sort(&mut filtered);
 
         // Apply pagination
         let start = query.offset.unwrap_or(0);
@@ -365,6 +584,29 @@ impl FileStorageBackend {
     }
 }
 
+fn tasks_file_error(e: serde_json::Error) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::SerializationError {
+            message: format!("Failed to parse tasks file: {e}"),
+        },
+    }
+}
+
+/// Parse the contents of a tasks file
+#[cfg(feature = "sqlite-index")]
+fn parse_tasks(data: &[u8]) -> Result<HashMap<Uuid, Task>, TaskError> {
+    let tasks: Vec<Task> = serde_json::from_slice(data).map_err(tasks_file_error)?;
+    Ok(tasks.into_iter().map(|task| (task.id, task)).collect())
+}
+
+/// Whether a query can match archived (completed or deleted) tasks
+fn needs_archive(query: &TaskQuery) -> bool {
+    !matches!(
+        query.status,
+        Some(TaskStatus::Pending | TaskStatus::Waiting | TaskStatus::Recurring)
+    )
+}
+
 /// Very small parser to extract a project:<name> token from a Taskwarrior
 /// filter expression. Returns Some(name) if found, else None. This is a
 /// pragmatic short-term implementation; full filter parsing will be added
@@ -390,6 +632,10 @@ impl Default for FileStorageBackend {
 }
 
 impl StorageBackend for FileStorageBackend {
+    fn backend_name(&self) -> &str {
+        "file"
+    }
+
     fn initialize(&mut self) -> Result<(), TaskError> {
         if self.initialized {
             return Ok(());
@@ -405,11 +651,21 @@ impl StorageBackend for FileStorageBackend {
             source: StorageError::Io(e),
         })?;
 
-        // Load existing tasks into cache
-        let tasks = self.load_tasks_from_file()?;
-        {
+        // Load existing tasks into cache. An index in sync with the file
+        // answers reads instead, and the cache is filled on the first write.
+        #[cfg(feature = "sqlite-index")]
+        let tasks = if self.use_index {
+            self.open_index()?
+        } else {
+            Some(self.load_tasks_from_file()?)
+        };
+        #[cfg(not(feature = "sqlite-index"))]
+        let tasks = Some(self.load_tasks_from_file()?);
+        if let Some(tasks) = tasks {
             let mut cache = self.task_cache.lock().unwrap();
             *cache = tasks;
+            #[cfg(feature = "sqlite-index")]
+            self.cache_loaded.store(true, Ordering::Release);
         }
 
         self.initialized = true;
@@ -421,28 +677,51 @@ impl StorageBackend for FileStorageBackend {
             self.initialize()?;
         }
 
+        // Archived tasks stay archived while they are finished
+        if archive::is_archivable(task)
+            && !self.hot_tasks()?.contains_key(&task.id)
+            && self.update_archived(task)?
+        {
+            return Ok(());
+        }
+
         // Update cache
         {
-            let mut cache = self.task_cache.lock().unwrap();
+            let mut cache = self.hot_tasks()?;
             cache.insert(task.id, task.clone());
         }
 
+        #[cfg(feature = "sqlite-index")]
+        if let Some(index) = &self.index {
+            index.upsert(task)?;
+        }
+
         // Save to file
-        let cache = self.task_cache.lock().unwrap();
-        self.save_tasks_to_file(&cache)?;
+        {
+            let cache = self.task_cache.lock().unwrap();
+            self.save_tasks_to_file(&cache)?;
+        }
+
+        // A reopened task moves back out of the archive
+        self.remove_archived(task.id)?;
 
         Ok(())
     }
 
     fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
-        if !self.initialized {
+        let task = if !self.initialized {
             // Try to load directly from file if not initialized
-            let tasks = self.load_tasks_from_file()?;
-            return Ok(tasks.get(&id).cloned());
+            self.load_tasks_from_file()?.remove(&id)
+        } else {
+            self.cached_task(id)?
+        };
+        if task.is_some() {
+            return Ok(task);
         }
 
-        let cache = self.task_cache.lock().unwrap();
-        Ok(cache.get(&id).cloned())
+        self.load_archive()?;
+        let archive = self.archive_cache.lock().unwrap();
+        Ok(archive.as_ref().and_then(|a| a.get(&id).cloned()))
     }
 
     fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError> {
@@ -452,29 +731,84 @@ impl StorageBackend for FileStorageBackend {
 
         // Remove from cache
         let removed = {
-            let mut cache = self.task_cache.lock().unwrap();
+            let mut cache = self.hot_tasks()?;
             cache.remove(&id).is_some()
         };
 
         if !removed {
-            return Err(TaskError::NotFound { id });
+            self.load_archive()?;
+            return match self.remove_archived(id)? {
+                true => Ok(()),
+                false => Err(TaskError::NotFound { id }),
+            };
+        }
+
+        #[cfg(feature = "sqlite-index")]
+        if let Some(index) = &self.index {
+            index.remove(id)?;
         }
 
         // Save to file
-        let cache = self.task_cache.lock().unwrap();
-        self.save_tasks_to_file(&cache)?;
+        {
+            let cache = self.task_cache.lock().unwrap();
+            self.save_tasks_to_file(&cache)?;
+        }
+
+        // Drop any stale archived copy so it does not resurface
+        self.load_archive()?;
+        self.remove_archived(id)?;
 
         Ok(())
     }
 
     fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        let tasks = if !self.initialized {
+            self.load_tasks_from_file()?
+        } else {
+            self.hot_tasks()?.clone()
+        };
+        Ok(self.all_tasks_with_archive(tasks)?.into_values().collect())
+    }
+
+    fn archive_completed(&mut self, before: DateTime<Utc>) -> Result<usize, TaskError> {
         if !self.initialized {
-            let tasks = self.load_tasks_from_file()?;
-            return Ok(tasks.into_values().collect());
+            self.initialize()?;
+        }
+        self.load_archive()?;
+
+        let mut cache = self.hot_tasks()?;
+        let moved: Vec<Uuid> = cache
+            .values()
+            .filter(|t| archive::is_archivable(t) && archive::finished_at(t) < before)
+            .map(|t| t.id)
+            .collect();
+        if moved.is_empty() {
+            return Ok(0);
+        }
+
+        // Write the archive first so an interruption leaves a duplicate
+        // (resolved in favor of tasks.json) rather than a lost task
+        {
+            let mut guard = self.archive_cache.lock().unwrap();
+            let archive = guard.get_or_insert_with(HashMap::new);
+            let mut years = BTreeSet::new();
+            for id in &moved {
+                let task = cache[id].clone();
+                years.insert(archive::year_of(&task));
+                archive.insert(*id, task);
+            }
+            archive::write_years(&self.data_path, archive, &years)?;
         }
 
-        let cache = self.task_cache.lock().unwrap();
-        Ok(cache.values().cloned().collect())
+        for id in &moved {
+            cache.remove(id);
+            #[cfg(feature = "sqlite-index")]
+            if let Some(index) = &self.index {
+                index.remove(*id)?;
+            }
+        }
+        self.save_tasks_to_file(&cache)?;
+        Ok(moved.len())
     }
 
     fn query_tasks(
@@ -482,15 +816,135 @@ impl StorageBackend for FileStorageBackend {
         query: &TaskQuery,
         active_context: Option<&crate::config::context::UserContext>,
     ) -> Result<Vec<Task>, TaskError> {
-        let tasks = if !self.initialized {
+        #[cfg(feature = "sqlite-index")]
+        if let (true, Some(index)) = (self.initialized, &self.index) {
+            use crate::query::FilterMode;
+            use sqlite_index::SqliteTaskIndex;
+
+            let with_archive = needs_archive(query) && {
+                self.load_archive()?;
+                let archive = self.archive_cache.lock().unwrap();
+                archive.as_ref().is_some_and(|a| !a.is_empty())
+            };
+            let with_context = active_context.is_some_and(|ctx| {
+                !matches!(query.filter_mode, Some(FilterMode::IgnoreContext))
+                    && parse_project_from_filter(&ctx.read_filter).is_some()
+            });
+            // Let SQL sort and page when nothing else narrows the result
+            if !with_archive && !with_context && SqliteTaskIndex::answers(query) {
+                return index.tasks(query, true);
+            }
+            // Otherwise run the full filter over the matched tasks only
+            let mut tasks: HashMap<Uuid, Task> =
+                index.tasks(query, false)?.into_iter().map(|t| (t.id, t)).collect();
+            if with_archive {
+                tasks = self.all_tasks_with_archive(tasks)?;
+            }
+            return Ok(self.filter_tasks(&tasks, query, active_context));
+        }
+
+        let mut tasks = if !self.initialized {
             self.load_tasks_from_file()?
         } else {
-            self.task_cache.lock().unwrap().clone()
+            self.hot_tasks()?.clone()
         };
+        if needs_archive(query) {
+            tasks = self.all_tasks_with_archive(tasks)?;
+        }
 
         Ok(self.filter_tasks(&tasks, query, active_context))
     }
 
+    fn verify(&self) -> Result<IntegrityReport, TaskError> {
+        use integrity::Manifest;
+
+        let mut report = IntegrityReport::default();
+        let manifest_path = Manifest::path_for(&self.tasks_file);
+        let data = match fs::read(&self.tasks_file) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                // A manifest without data means the tasks file went missing
+                if manifest_path.exists() {
+                    report.issues.push(IntegrityIssue::UnreadableTask {
+                        id: None,
+                        message: format!("{} is missing", self.tasks_file.display()),
+                    });
+                }
+                return Ok(report);
+            }
+            Err(e) => {
+                return Err(TaskError::Storage {
+                    source: StorageError::Io(e),
+                })
+            }
+        };
+        report.checksum = Some(integrity::checksum(&data));
+
+        // Parse record by record so one bad task doesn't hide the rest
+        match serde_json::from_slice::<Vec<serde_json::Value>>(&data) {
+            Ok(records) => {
+                let mut seen = std::collections::HashSet::new();
+                for record in records {
+                    report.tasks_checked += 1;
+                    let id = record["uuid"].as_str().and_then(|u| Uuid::parse_str(u).ok());
+                    match serde_json::from_value::<Task>(record) {
+                        Ok(task) => {
+                            if !seen.insert(task.id) {
+                                report.issues.push(IntegrityIssue::DuplicateTask { id: task.id });
+                            }
+                        }
+                        Err(e) => report.issues.push(IntegrityIssue::UnreadableTask {
+                            id,
+                            message: e.to_string(),
+                        }),
+                    }
+                }
+            }
+            Err(e) => report.issues.push(IntegrityIssue::UnreadableTask {
+                id: None,
+                message: e.to_string(),
+            }),
+        }
+
+        let manifest = match fs::read(&manifest_path) {
+            Ok(raw) => match serde_json::from_slice::<Manifest>(&raw) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    report.issues.push(IntegrityIssue::InvalidManifest {
+                        path: manifest_path,
+                        message: e.to_string(),
+                    });
+                    return Ok(report);
+                }
+            },
+            Err(_) => {
+                report.issues.push(IntegrityIssue::MissingManifest { path: manifest_path });
+                return Ok(report);
+            }
+        };
+
+        let actual = integrity::checksum(&data);
+        if manifest.checksum != actual {
+            report.issues.push(IntegrityIssue::ChecksumMismatch {
+                expected: manifest.checksum,
+                actual,
+            });
+        }
+        if manifest.size != data.len() as u64 {
+            report.issues.push(IntegrityIssue::SizeMismatch {
+                expected: manifest.size,
+                actual: data.len() as u64,
+            });
+        }
+        if manifest.task_count != report.tasks_checked {
+            report.issues.push(IntegrityIssue::TaskCountMismatch {
+                expected: manifest.task_count,
+                actual: report.tasks_checked,
+            });
+        }
+        Ok(report)
+    }
+
     fn backup(&self) -> Result<String, StorageError> {
         if !self.tasks_file.exists() {
             return Ok(String::new());
@@ -524,9 +978,21 @@ impl StorageBackend for FileStorageBackend {
             task_map.insert(task.id, task);
         }
 
+        #[cfg(feature = "sqlite-index")]
+        if let Some(index) = &self.index {
+            index
+                .rebuild(task_map.values())
+                .and_then(|_| index.set_source_checksum(&integrity::checksum(backup_data.as_bytes())))
+                .map_err(|e| StorageError::Database {
+                    message: e.to_string(),
+                })?;
+        }
+
         {
             let mut cache = self.task_cache.lock().unwrap();
             *cache = task_map;
+            #[cfg(feature = "sqlite-index")]
+            self.cache_loaded.store(true, Ordering::Release);
         }
 
         Ok(())
@@ -3,6 +3,7 @@
 //! These are lightweight representations of TaskChampion operations used
 //! by the write-path to construct a unit-of-work that can be committed.
 
+use crate::task::model::UdaValue;
 use crate::task::Task;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -11,9 +12,7 @@ use uuid::Uuid;
 use taskchampion;
 
 /// Operation variant used in OperationBatch
-
-/// Operation variant used in OperationBatch
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Operation {
     /// Create a new task with the provided serialized JSON data
     Create { uuid: Uuid, data: serde_json::Value },
@@ -27,6 +26,12 @@ pub enum Operation {
     /// Unset a single named field (remove it)
     UnsetField { uuid: Uuid, key: String },
 
+    /// Set a UDA, encoded for TaskChampion according to its type
+    SetUda { uuid: Uuid, key: String, value: UdaValue },
+
+    /// Remove a UDA
+    UnsetUda { uuid: Uuid, key: String },
+
     /// Add a tag to the task
     AddTag { uuid: Uuid, tag: String },
 
@@ -36,6 +41,12 @@ pub enum Operation {
     /// Add an annotation (note) to the task
     AddAnnotation { uuid: Uuid, entry: chrono::DateTime<chrono::Utc>, description: String },
 
+    /// Remove the annotation added at `entry`
+    RemoveAnnotation { uuid: Uuid, entry: chrono::DateTime<chrono::Utc> },
+
+    /// Replace the text of the annotation added at `entry`
+    EditAnnotation { uuid: Uuid, entry: chrono::DateTime<chrono::Utc>, description: String },
+
     /// Add a dependency (task uuid) to the task
     AddDependency { uuid: Uuid, depends_on: Uuid },
 
@@ -45,17 +56,35 @@ pub enum Operation {
     /// Delete the task (logical delete)
     Delete { uuid: Uuid },
 
+    /// Remove the task and all its properties from the replica for good
+    Purge { uuid: Uuid },
+
     /// Insert an undo point before the batch
     UndoPoint,
 }
 
 /// Build a Create operation from a Task by serializing its JSON representation.
+/// UDAs are left out; [`build_save_batch`] writes them with typed SetUda ops.
 pub fn create_from_task(task: &Task) -> Operation {
     // Use the existing serialization for Task
-    let data = serde_json::to_value(task).unwrap_or(serde_json::Value::Null);
+    let mut data = serde_json::to_value(task).unwrap_or(serde_json::Value::Null);
+    if let serde_json::Value::Object(map) = &mut data {
+        for key in task.udas.keys() {
+            map.remove(key);
+        }
+    }
     Operation::Create { uuid: task.id, data }
 }
 
+// SetUda ops for every UDA on a new task
+fn set_uda_ops(task: &Task) -> Vec<Operation> {
+    let mut keys: Vec<&String> = task.udas.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| Operation::SetUda { uuid: task.id, key: key.clone(), value: task.udas[key].clone() })
+        .collect()
+}
+
 /// Compute a minimal set of Update operations from `old` to `new` Task.
 /// For now we compare a few commonly changed fields: description, project, tags, status.
 pub fn compute_update_ops(old: &Task, new: &Task) -> Vec<Operation> {
@@ -79,6 +108,47 @@ pub fn compute_update_ops(old: &Task, new: &Task) -> Vec<Operation> {
         });
     }
 
+    if old.owner != new.owner {
+        ops.push(Operation::Update {
+            uuid: old.id,
+            key: "owner".to_string(),
+            old: match &old.owner { Some(o) => serde_json::Value::String(o.clone()), None => serde_json::Value::Null },
+            new: match &new.owner { Some(o) => serde_json::Value::String(o.clone()), None => serde_json::Value::Null },
+        });
+    }
+
+    // Built-in priorities; custom codes are UDAs and handled below
+    if old.priority != new.priority {
+        let code = |task: &Task| match task.priority {
+            Some(p) => serde_json::Value::String(p.code().to_string()),
+            None => serde_json::Value::Null,
+        };
+        ops.push(Operation::Update {
+            uuid: old.id,
+            key: "priority".to_string(),
+            old: code(old),
+            new: code(new),
+        });
+    }
+
+    // Dates as TaskChampion stores them, in epoch seconds
+    let value = |secs: Option<i64>| match secs {
+        Some(secs) => serde_json::Value::String(secs.to_string()),
+        None => serde_json::Value::Null,
+    };
+    for (key, before, after) in [
+        ("due", old.due, new.due),
+        ("wait", old.wait, new.wait),
+        ("scheduled", old.scheduled, new.scheduled),
+        // The etag; whole seconds match what a reload gives back
+        ("modified", old.modified, new.modified),
+    ] {
+        let (before, after) = (before.map(|d| d.timestamp()), after.map(|d| d.timestamp()));
+        if before != after {
+            ops.push(Operation::Update { uuid: old.id, key: key.to_string(), old: value(before), new: value(after) });
+        }
+    }
+
     // Tags: emit AddTag / RemoveTag per delta for fine-grained ops
     if old.tags != new.tags {
         for t in new.tags.difference(&old.tags) {
@@ -93,8 +163,9 @@ pub fn compute_update_ops(old: &Task, new: &Task) -> Vec<Operation> {
         ops.push(Operation::Update {
             uuid: old.id,
             key: "status".to_string(),
-            old: serde_json::Value::String(format!("{:?}", old.status)),
-            new: serde_json::Value::String(format!("{:?}", new.status)),
+            // Serialized names ("completed") are what Taskwarrior stores
+            old: serde_json::to_value(old.status).unwrap_or_default(),
+            new: serde_json::to_value(new.status).unwrap_or_default(),
         });
     }
 
@@ -108,12 +179,37 @@ pub fn compute_update_ops(old: &Task, new: &Task) -> Vec<Operation> {
         }
     }
 
-    // Annotations: treat new annotations appended to the list as additions
+    // UDAs: set changed values, unset removed ones
+    if old.udas != new.udas {
+        let mut keys: Vec<&String> = old.udas.keys().chain(new.udas.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            match (old.udas.get(key), new.udas.get(key)) {
+                (old_value, Some(value)) if old_value != Some(value) => {
+                    ops.push(Operation::SetUda { uuid: old.id, key: key.clone(), value: value.clone() });
+                }
+                (Some(_), None) => ops.push(Operation::UnsetUda { uuid: old.id, key: key.clone() }),
+                _ => {}
+            }
+        }
+    }
+
+    // Annotations are identified by their entry time: new entries are added,
+    // changed text is edited and missing entries are removed
     if old.annotations != new.annotations {
-        // find annotations in new that are not present in old by (entry, description)
         for ann in &new.annotations {
-            if !old.annotations.iter().any(|a| a.entry == ann.entry && a.description == ann.description) {
-                ops.push(Operation::AddAnnotation { uuid: old.id, entry: ann.entry, description: ann.description.clone() });
+            match old.annotations.iter().find(|a| a.entry == ann.entry) {
+                None => ops.push(Operation::AddAnnotation { uuid: old.id, entry: ann.entry, description: ann.description.clone() }),
+                Some(prev) if prev.description != ann.description => {
+                    ops.push(Operation::EditAnnotation { uuid: old.id, entry: ann.entry, description: ann.description.clone() });
+                }
+                Some(_) => {}
+            }
+        }
+        for ann in &old.annotations {
+            if !new.annotations.iter().any(|a| a.entry == ann.entry) {
+                ops.push(Operation::RemoveAnnotation { uuid: old.id, entry: ann.entry });
             }
         }
     }
@@ -127,76 +223,27 @@ pub fn build_save_batch(existing: Option<&Task>, new_task: &Task) -> Vec<Operati
     let mut batch = Vec::new();
     batch.push(Operation::UndoPoint);
     match existing {
-        None => batch.push(create_from_task(new_task)),
+        None => {
+            batch.push(create_from_task(new_task));
+            batch.extend(set_uda_ops(new_task));
+        }
         Some(old) => batch.extend(compute_update_ops(old, new_task)),
     }
     batch
 }
 
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::task::model::Task;
-    use crate::task::annotation::Annotation;
-    use chrono::Utc;
-    use uuid::Uuid;
-
-    #[test]
-    fn test_compute_tags_add_remove() {
-        let mut old = Task::new("old".to_string());
-        old.id = Uuid::new_v4();
-        old.tags.insert("a".to_string());
-        old.tags.insert("b".to_string());
-
-        let mut new = old.clone();
-        new.tags.remove("a");
-        new.tags.insert("c".to_string());
-
-        let ops = compute_update_ops(&old, &new);
-        assert!(ops.contains(&Operation::AddTag { uuid: old.id, tag: "c".to_string() }));
-        assert!(ops.contains(&Operation::RemoveTag { uuid: old.id, tag: "a".to_string() }));
-    }
-
-    #[test]
-    fn test_compute_annotations_add() {
-        let mut old = Task::new("old".to_string());
-        old.id = Uuid::new_v4();
-
-        let mut new = old.clone();
-        let ann = Annotation::with_timestamp("note1".to_string(), Utc::now());
-        new.annotations.push(ann.clone());
-
-        let ops = compute_update_ops(&old, &new);
-        assert!(ops.iter().any(|op| match op {
-            Operation::AddAnnotation { uuid, description, .. } => *uuid == old.id && description == &ann.description,
-            _ => false,
-        }));
-    }
-
-    #[test]
-    fn test_compute_dependencies_add_remove() {
-        let mut old = Task::new("old".to_string());
-        old.id = Uuid::new_v4();
-        let dep1 = Uuid::new_v4();
-        let dep2 = Uuid::new_v4();
-        old.depends.insert(dep1);
-
-        let mut new = old.clone();
-        new.depends.remove(&dep1);
-        new.depends.insert(dep2);
-
-        let ops = compute_update_ops(&old, &new);
-        assert!(ops.contains(&Operation::AddDependency { uuid: old.id, depends_on: dep2 }));
-        assert!(ops.contains(&Operation::RemoveDependency { uuid: old.id, depends_on: dep1 }));
-    }
-}
-
 /// Convenience: build a delete batch for a given task uuid.
 pub fn build_delete_batch(id: Uuid) -> Vec<Operation> {
     vec![Operation::UndoPoint, Operation::Delete { uuid: id }]
 }
 
+/// Convenience: build a purge batch for a given task uuid. Purges are not
+/// undoable, so no undo point is added.
+pub fn build_purge_batch(id: Uuid) -> Vec<Operation> {
+    vec![Operation::Purge { uuid: id }]
+}
+
 #[cfg(feature = "taskchampion")]
 /// Convert our Operation enum to TaskChampion operations.
 /// 
@@ -254,6 +301,12 @@ pub fn to_taskchampion_operations(
                 let mut task_data = TaskData::create(*uuid, &mut tc_ops);
                 task_data.update("status", Some("deleted".to_string()), &mut tc_ops);
             }
+            Operation::Purge { uuid } => {
+                // Removing needs the current properties so the delete can be synced
+                if let Ok(Some(mut task_data)) = replica.get_task_data(*uuid) {
+                    task_data.delete(&mut tc_ops);
+                }
+            }
             Operation::Update { uuid, key, old: _, new } => {
                 // Use TaskData update for simple key/value changes
                 let value_str = match new {
@@ -275,6 +328,14 @@ pub fn to_taskchampion_operations(
                 let mut task_data = TaskData::create(*uuid, &mut tc_ops);
                 task_data.update(key, None, &mut tc_ops);
             }
+            Operation::SetUda { uuid, key, value } => {
+                let mut task_data = TaskData::create(*uuid, &mut tc_ops);
+                task_data.update(key, Some(crate::task::uda::encode_uda(value)), &mut tc_ops);
+            }
+            Operation::UnsetUda { uuid, key } => {
+                let mut task_data = TaskData::create(*uuid, &mut tc_ops);
+                task_data.update(key, None, &mut tc_ops);
+            }
             Operation::AddTag { uuid, tag } => {
                 // Prefer Task helper if we can get a snapshot
                 if let Ok(Some(mut current_task)) = replica.get_task(*uuid) {
@@ -313,6 +374,22 @@ pub fn to_taskchampion_operations(
                     task_data.update(&annotation_key, Some(description.clone()), &mut tc_ops);
                 }
             }
+            Operation::RemoveAnnotation { uuid, entry } => {
+                // Prefer Task helper if we can get a snapshot
+                if let Ok(Some(mut current_task)) = replica.get_task(*uuid) {
+                    let _ = current_task.remove_annotation(*entry, &mut tc_ops);
+                } else {
+                    let mut task_data = TaskData::create(*uuid, &mut tc_ops);
+                    let annotation_key = format!("annotation_{}", entry.timestamp());
+                    task_data.update(&annotation_key, None, &mut tc_ops);
+                }
+            }
+            Operation::EditAnnotation { uuid, entry, description } => {
+                // TaskChampion has no edit helper; rewrite the annotation key
+                let mut task_data = TaskData::create(*uuid, &mut tc_ops);
+                let annotation_key = format!("annotation_{}", entry.timestamp());
+                task_data.update(&annotation_key, Some(description.clone()), &mut tc_ops);
+            }
             Operation::AddDependency { uuid, depends_on } => {
                 // Prefer Task helper if we can get a snapshot
                 if let Ok(Some(mut current_task)) = replica.get_task(*uuid) {
@@ -399,3 +476,126 @@ mod taskchampion_tests {
         // Should contain operations for creating and adding tag
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::model::Task;
+    use crate::task::annotation::Annotation;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_compute_tags_add_remove() {
+        let mut old = Task::new("old".to_string());
+        old.id = Uuid::new_v4();
+        old.tags.insert("a".to_string());
+        old.tags.insert("b".to_string());
+
+        let mut new = old.clone();
+        new.tags.remove("a");
+        new.tags.insert("c".to_string());
+
+        let ops = compute_update_ops(&old, &new);
+        assert!(ops.contains(&Operation::AddTag { uuid: old.id, tag: "c".to_string() }));
+        assert!(ops.contains(&Operation::RemoveTag { uuid: old.id, tag: "a".to_string() }));
+    }
+
+    #[test]
+    fn test_compute_date_updates() {
+        let old = Task::new("old".to_string());
+        let mut new = old.clone();
+        let wait = Utc.with_ymd_and_hms(2025, 3, 14, 9, 0, 0).unwrap();
+        new.wait = Some(wait);
+
+        let ops = compute_update_ops(&old, &new);
+        assert_eq!(
+            ops,
+            [Operation::Update {
+                uuid: old.id,
+                key: "wait".to_string(),
+                old: serde_json::Value::Null,
+                new: serde_json::Value::String(wait.timestamp().to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compute_annotations_add() {
+        let mut old = Task::new("old".to_string());
+        old.id = Uuid::new_v4();
+
+        let mut new = old.clone();
+        let ann = Annotation::with_timestamp("note1".to_string(), Utc::now());
+        new.annotations.push(ann.clone());
+
+        let ops = compute_update_ops(&old, &new);
+        assert!(ops.iter().any(|op| match op {
+            Operation::AddAnnotation { uuid, description, .. } => *uuid == old.id && description == &ann.description,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn test_compute_dependencies_add_remove() {
+        let mut old = Task::new("old".to_string());
+        old.id = Uuid::new_v4();
+        let dep1 = Uuid::new_v4();
+        let dep2 = Uuid::new_v4();
+        old.depends.insert(dep1);
+
+        let mut new = old.clone();
+        new.depends.remove(&dep1);
+        new.depends.insert(dep2);
+
+        let ops = compute_update_ops(&old, &new);
+        assert!(ops.contains(&Operation::AddDependency { uuid: old.id, depends_on: dep2 }));
+        assert!(ops.contains(&Operation::RemoveDependency { uuid: old.id, depends_on: dep1 }));
+    }
+
+    #[test]
+    fn test_uda_ops() {
+        let mut old = Task::new("old".to_string());
+        old.udas.insert("estimate".to_string(), UdaValue::Number(3.0));
+        old.udas.insert("client".to_string(), UdaValue::String("ACME".to_string()));
+
+        let create = build_save_batch(None, &old);
+        match &create[1] {
+            Operation::Create { data, .. } => assert!(data.get("estimate").is_none()),
+            other => panic!("expected Create, got {other:?}"),
+        }
+        assert!(create.contains(&Operation::SetUda { uuid: old.id, key: "estimate".to_string(), value: UdaValue::Number(3.0) }));
+
+        let mut new = old.clone();
+        new.udas.insert("estimate".to_string(), UdaValue::Number(5.0));
+        new.udas.remove("client");
+        let ops = compute_update_ops(&old, &new);
+        assert_eq!(
+            ops,
+            vec![
+                Operation::UnsetUda { uuid: old.id, key: "client".to_string() },
+                Operation::SetUda { uuid: old.id, key: "estimate".to_string(), value: UdaValue::Number(5.0) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_annotations_edit_remove() {
+        let mut old = Task::new("old".to_string());
+        let first = Annotation::with_timestamp("first".to_string(), Utc::now());
+        let second = Annotation::with_timestamp("second".to_string(), first.entry + chrono::Duration::seconds(1));
+        old.annotations = vec![first.clone(), second.clone()];
+
+        let mut new = old.clone();
+        new.annotations = vec![Annotation::with_timestamp("first, edited".to_string(), first.entry)];
+
+        let ops = compute_update_ops(&old, &new);
+        assert_eq!(
+            ops,
+            vec![
+                Operation::EditAnnotation { uuid: old.id, entry: first.entry, description: "first, edited".to_string() },
+                Operation::RemoveAnnotation { uuid: old.id, entry: second.entry },
+            ]
+        );
+    }
+}
@@ -4,14 +4,55 @@ use crate::storage::replica_wrapper::ReplicaWrapper;
 use std::path::Path;
 use uuid::Uuid;
 #[cfg(feature = "taskchampion")]
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+#[cfg(feature = "taskchampion")]
 use std::sync::{Arc, Mutex};
 
 // Commands sent to the replica actor thread
 #[cfg(feature = "taskchampion")]
 enum ReplicaCommand {
     Commit { ops: Vec<Op>, resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
+    Save { task: Box<crate::task::Task>, resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
     Open { path: std::path::PathBuf, resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
     ReadTask { id: Uuid, resp: std::sync::mpsc::Sender<Result<Option<crate::task::Task>, TaskError>> },
+    AllTasks { resp: std::sync::mpsc::Sender<Result<Vec<crate::task::Task>, TaskError>> },
+    PendingTasks { resp: std::sync::mpsc::Sender<Result<Vec<crate::task::Task>, TaskError>> },
+    WorkingSet { resp: std::sync::mpsc::Sender<Result<Vec<(usize, Uuid)>, TaskError>> },
+    Undo { resp: std::sync::mpsc::Sender<Result<bool, TaskError>> },
+    NumLocalOperations { resp: std::sync::mpsc::Sender<Result<usize, TaskError>> },
+    NumUndoPoints { resp: std::sync::mpsc::Sender<Result<usize, TaskError>> },
+    Ping { resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
+    Sync { server: crate::sync::SyncServerConfig, avoid_snapshots: bool, resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
+    RebuildWorkingSet { renumber: bool, resp: std::sync::mpsc::Sender<Result<(), TaskError>> },
+    SetUdaTypes { types: crate::task::UdaTypes },
+}
+
+// Sending side of the actor's command queue
+#[cfg(feature = "taskchampion")]
+type CommandSender = std::sync::mpsc::SyncSender<(Ticket, ReplicaCommand)>;
+
+// Shared by a queued command and its caller. Whichever of the actor
+// starting the command and the caller giving up on it comes first wins, so
+// a caller that gives up knows the command will never run.
+#[cfg(feature = "taskchampion")]
+#[derive(Clone, Default)]
+struct Ticket(Arc<AtomicU8>);
+
+#[cfg(feature = "taskchampion")]
+impl Ticket {
+    const QUEUED: u8 = 0;
+    const STARTED: u8 = 1;
+    const WITHDRAWN: u8 = 2;
+
+    // Called by the actor; false if the caller withdrew the command
+    fn start(&self) -> bool {
+        self.0.compare_exchange(Self::QUEUED, Self::STARTED, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+    }
+
+    // Called by the caller; false if the actor already started the command
+    fn withdraw(&self) -> bool {
+        self.0.compare_exchange(Self::QUEUED, Self::WITHDRAWN, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+    }
 }
 
 // Legacy helper removed: prefer the replica-aware mapping helper
@@ -56,6 +97,14 @@ pub fn map_ops_to_tc_operations_with_replica(replica: &mut taskchampion::Replica
                 let mut td = TaskData::create(*uuid, &mut tc_ops);
                 td.update(key, None, &mut tc_ops);
             }
+            Op::SetUda { uuid, key, value } => {
+                let mut td = TaskData::create(*uuid, &mut tc_ops);
+                td.update(key, Some(crate::task::uda::encode_uda(value)), &mut tc_ops);
+            }
+            Op::UnsetUda { uuid, key } => {
+                let mut td = TaskData::create(*uuid, &mut tc_ops);
+                td.update(key, None, &mut tc_ops);
+            }
             Op::AddTag { uuid, tag } => {
                 if let Ok(mut t) = replica.create_task(*uuid, &mut tc_ops) {
                     if let Ok(tc_tag) = tag.parse::<TcTag>() {
@@ -76,6 +125,15 @@ pub fn map_ops_to_tc_operations_with_replica(replica: &mut taskchampion::Replica
                     let _ = t.add_annotation(ann, &mut tc_ops);
                 }
             }
+            Op::RemoveAnnotation { uuid, entry } => {
+                if let Ok(mut t) = replica.create_task(*uuid, &mut tc_ops) {
+                    let _ = t.remove_annotation(*entry, &mut tc_ops);
+                }
+            }
+            Op::EditAnnotation { uuid, entry, description } => {
+                let mut td = TaskData::create(*uuid, &mut tc_ops);
+                td.update(format!("annotation_{}", entry.timestamp()), Some(description.clone()), &mut tc_ops);
+            }
             Op::AddDependency { uuid, depends_on } => {
                 if let Ok(mut t) = replica.create_task(*uuid, &mut tc_ops) {
                     let _ = t.add_dependency(*depends_on, &mut tc_ops);
@@ -102,6 +160,11 @@ pub fn map_ops_to_tc_operations_with_replica(replica: &mut taskchampion::Replica
                 let mut td = TaskData::create(*uuid, &mut tc_ops);
                 td.update("status", Some("deleted".to_string()), &mut tc_ops);
             }
+            Op::Purge { uuid } => {
+                if let Ok(Some(mut td)) = replica.get_task_data(*uuid) {
+                    td.delete(&mut tc_ops);
+                }
+            }
         }
     }
 
@@ -115,278 +178,472 @@ pub fn map_ops_to_tc_operations_with_replica(replica: &mut taskchampion::Replica
 // uses the `taskchampion` crate can be implemented behind the feature flag
 // later.
 
-/// Factory to open a TaskChampion-backed replica wrapper.
-pub fn open_taskchampion_replica(path: &Path) -> Result<Box<dyn ReplicaWrapper>, TaskError> {
-    #[cfg(feature = "taskchampion")]
-    {
-        // Run the non-Send taskchampion::Replica on a dedicated thread and
-        // communicate with it via channels. This proxy is Send+Sync and
-        // implements ReplicaWrapper without forcing Replica itself to be Send.
-        use std::sync::mpsc;
-        use std::thread;
+// TaskChampion stores dates as epoch seconds; older writes used RFC 3339
+#[cfg(feature = "taskchampion")]
+fn tc_date(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    match value.parse::<i64>() {
+        Ok(secs) => chrono::DateTime::from_timestamp(secs, 0),
+        Err(_) => chrono::DateTime::parse_from_rfc3339(value).ok().map(|dt| dt.with_timezone(&chrono::Utc)),
+    }
+}
+
+// Build a Task from the string properties TaskChampion stores for it
+#[cfg(feature = "taskchampion")]
+fn task_from_task_data(td: &taskchampion::TaskData, uda_types: &crate::task::UdaTypes) -> crate::task::Task {
+    let id = td.get_uuid();
+    // Build a Task from available fields.
+    // Minimal fields: description, status, entry
+    let description = td.get("description").map(|s| s.to_string()).unwrap_or_default();
+    let status_str = td.get("status").map(|s| s.to_string()).unwrap_or_else(|| "pending".to_string());
+    let status = match status_str.as_str() {
+        "pending" => crate::task::model::TaskStatus::Pending,
+        "completed" => crate::task::model::TaskStatus::Completed,
+        "deleted" => crate::task::model::TaskStatus::Deleted,
+        "waiting" => crate::task::model::TaskStatus::Waiting,
+        "recurring" => crate::task::model::TaskStatus::Recurring,
+        _ => crate::task::model::TaskStatus::Pending,
+    };
+    let entry = td.get("entry").and_then(tc_date).unwrap_or_else(chrono::Utc::now);
+
+    // Start with a new Task and overwrite fields
+    let mut task = crate::task::model::Task::new(description.clone());
+    task.id = id;
+    task.description = description;
+    task.status = status;
+    task.entry = entry;
+
+    // project
+    if let Some(proj) = td.get("project") {
+        task.project = Some(proj.to_string());
+    }
+
+    // tags
+    if let Some(tags_str) = td.get("tags") {
+        let set: std::collections::HashSet<String> = tags_str.split_whitespace().map(|s| s.to_string()).collect();
+        task.tags = set;
+    }
+
+    // priority
+    if let Some(prio) = td.get("priority") {
+        task.set_priority_code(Some(prio));
+    }
+
+    // timestamps: modified, due, scheduled, wait, end, start
+    if let Some(mod_s) = td.get("modified") {
+        if let Some(dt) = tc_date(mod_s) {
+            task.modified = Some(dt);
+        }
+    }
+    if let Some(due_s) = td.get("due") {
+        if let Some(dt) = tc_date(due_s) {
+            task.due = Some(dt);
+        }
+    }
+    if let Some(sched_s) = td.get("scheduled") {
+        if let Some(dt) = tc_date(sched_s) {
+            task.scheduled = Some(dt);
+        }
+    }
+    if let Some(wait_s) = td.get("wait") {
+        if let Some(dt) = tc_date(wait_s) {
+            task.wait = Some(dt);
+        }
+    }
+    if let Some(end_s) = td.get("end") {
+        if let Some(dt) = tc_date(end_s) {
+            task.end = Some(dt);
+        }
+    }
+    if let Some(start_s) = td.get("start") {
+        if let Some(dt) = tc_date(start_s) {
+            task.start = Some(dt);
+        }
+    }
+
+    // annotations: try keys 'annotations' or lines in a single string
+    if let Some(anns_str) = td.get("annotations") {
+        for line in anns_str.lines() {
+            // Expect "<rfc3339> <description>"
+            if let Some((ts, desc)) = line.split_once(' ') {
+                if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
+                    let ann = crate::task::annotation::Annotation::with_timestamp(desc.replace("\\n", "\n"), dt.with_timezone(&chrono::Utc));
+                    task.annotations.push(ann);
+                } else {
+                    // fallback: store whole line as description with current time
+                    let ann = crate::task::annotation::Annotation::new(line.to_string());
+                    task.annotations.push(ann);
+                }
+            } else {
+                let ann = crate::task::annotation::Annotation::new(line.to_string());
+                task.annotations.push(ann);
+            }
+        }
+    }
+
+    // annotations written by TaskChampion as `annotation_<timestamp>` keys
+    for (k, v) in td.iter() {
+        let ts = k.strip_prefix("annotation_").and_then(|ts| ts.parse::<i64>().ok());
+        if let Some(entry) = ts.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)) {
+            task.annotations.push(crate::task::annotation::Annotation::with_timestamp(v.clone(), entry));
+        }
+    }
+    task.annotations.sort_by_key(|a| a.entry);
+
+    // dependencies
+    if let Some(dep_str) = td.get("depends") {
+        if let Ok(deps) = crate::task::model::parse_depends_list(dep_str) {
+            task.depends = deps;
+        }
+    }
+    for (k, _) in td.iter() {
+        if let Some(dep) = crate::task::model::dependency_key_uuid(k) {
+            task.depends.insert(dep);
+        }
+    }
+
+    // recurrence
+    if let Some(recur_s) = td.get("recur") {
+        if let Ok(rp) = crate::task::recurrence::RecurrencePattern::parse(recur_s) {
+            task.recur = Some(rp);
+        }
+    }
+
+    // parent, mask
+    if let Some(parent_s) = td.get("parent") {
+        if let Ok(u) = Uuid::parse_str(parent_s) {
+            task.parent = Some(u);
+        }
+    }
+    if let Some(mask_s) = td.get("mask") {
+        task.mask = Some(mask_s.to_string());
+    }
+    if let Some(owner_s) = td.get("owner") {
+        task.owner = Some(owner_s.to_string());
+    }
+
+    // active flag
+    if let Some(active_s) = td.get("active") {
+        let s = &active_s[..];
+        task.active = matches!(s, "1" | "true" | "True");
+    }
+
+    // UDAs: any key that is not a task attribute, decoded by declared type
+    for (k, v) in td.iter() {
+        if crate::task::uda::is_task_attribute(k) { continue; }
+        task.udas.insert(k.clone(), crate::task::uda::decode_uda(v, uda_types.get(k)));
+    }
+
+    task
+}
+
+#[cfg(feature = "taskchampion")]
+fn with_display_id(mut task: crate::task::Task, ws: &taskchampion::WorkingSet) -> crate::task::Task {
+    task.display_id = ws.by_uuid(task.id).map(|i| i as u32);
+    task
+}
+
+// Read the stored task, diff `task` against it and write the changes in a
+// single `BEGIN IMMEDIATE` transaction on a storage handle of our own, so
+// no other writer (the `task` CLI included) can change the task between
+// the read and the write. The replica itself opens a transaction per step.
+// The diff is mapped to TaskChampion operations on a scratch in-memory
+// replica holding only the stored task, and applied the way
+// `Replica::commit_operations` does.
+#[cfg(feature = "taskchampion")]
+fn save_in_transaction(path: &Path, task: &crate::task::Task, uda_types: &crate::task::UdaTypes) -> Result<(), TaskError> {
+    use std::collections::HashSet;
+    use taskchampion::storage::{AccessMode, StorageConfig};
+    use taskchampion::{Operation, Operations, TaskData};
+
+    let failed = |e: taskchampion::Error| TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion save failed: {e}") } };
+    let mut storage = StorageConfig::OnDisk {
+        taskdb_dir: path.to_path_buf(),
+        create_if_missing: true,
+        access_mode: AccessMode::ReadWrite,
+    }.into_storage().map_err(failed)?;
+    let mut txn = storage.txn().map_err(failed)?;
+
+    let mut scratch = taskchampion::Replica::new(StorageConfig::InMemory.into_storage().map_err(failed)?);
+    let existing = match txn.get_task(task.id).map_err(failed)? {
+        Some(stored) => {
+            let mut seed = Operations::new();
+            let mut td = TaskData::create(task.id, &mut seed);
+            for (key, value) in stored {
+                td.update(key, Some(value), &mut seed);
+            }
+            scratch.commit_operations(seed).map_err(failed)?;
+            scratch.get_task_data(task.id).map_err(failed)?.map(|td| task_from_task_data(&td, uda_types))
+        }
+        None => None,
+    };
+    let ops = crate::storage::operation_batch::build_save_batch(existing.as_ref(), task);
+    let tc_ops = crate::storage::operation_batch::to_taskchampion_operations(&mut scratch, &ops)?;
+
+    let pending = |v: &Option<String>| matches!(v.as_deref(), Some("pending" | "recurring"));
+    let mut working_set: HashSet<Uuid> = txn.get_working_set().map_err(failed)?.into_iter().flatten().collect();
+    for mut op in tc_ops {
+        match &mut op {
+            Operation::Create { uuid } => {
+                // The mapping opens every update with a create; recording
+                // one for a task that exists would make undo delete it
+                if !txn.create_task(*uuid).map_err(failed)? {
+                    continue;
+                }
+            }
+            Operation::Delete { uuid, .. } => {
+                txn.delete_task(*uuid).map_err(failed)?;
+            }
+            Operation::Update { uuid, property, old_value, value, .. } => {
+                if let Some(mut stored) = txn.get_task(*uuid).map_err(failed)? {
+                    // The mapping starts from an empty task, so take the
+                    // old value undo restores from the stored one
+                    *old_value = stored.get(property.as_str()).cloned();
+                    match value {
+                        Some(value) => stored.insert(property.clone(), value.clone()),
+                        None => stored.remove(property),
+                    };
+                    txn.set_task(*uuid, stored).map_err(failed)?;
+                }
+                if property == "status" && !pending(old_value) && pending(value) && working_set.insert(*uuid) {
+                    txn.add_to_working_set(*uuid).map_err(failed)?;
+                }
+            }
+            Operation::UndoPoint => {}
+        }
+        txn.add_operation(op).map_err(failed)?;
+    }
+    txn.commit().map_err(failed)
+}
+
+#[cfg(feature = "taskchampion")]
+fn sync_replica(replica: &mut taskchampion::Replica, server: crate::sync::SyncServerConfig, avoid_snapshots: bool) -> Result<(), TaskError> {
+    use crate::sync::SyncServerConfig;
+    use taskchampion::ServerConfig;
+
+    let config = match server {
+        SyncServerConfig::Local { server_dir } => ServerConfig::Local { server_dir },
+        SyncServerConfig::Remote { url, client_id, encryption_secret } => ServerConfig::Remote { url, client_id, encryption_secret },
+    };
+    let mut server = config.into_server().map_err(|e| TaskError::Sync { message: format!("Failed to open sync server: {e}") })?;
+    replica.sync(&mut server, avoid_snapshots).map_err(|e| TaskError::Sync { message: format!("TaskChampion sync failed: {e}") })
+}
+
+#[cfg(feature = "taskchampion")]
+fn read_error(e: &taskchampion::Error) -> TaskError {
+    TaskError::Storage { source: StorageError::Database { message: format!("Failed to read replica task data: {e}") } }
+}
+
+// Spawn the actor thread that owns the replica at `path`, returning its
+// command channel once the replica has opened
+#[cfg(feature = "taskchampion")]
+fn spawn_replica_actor(
+    path: &Path,
+    options: &ReplicaOptions,
+    depth: Arc<AtomicUsize>,
+) -> Result<(CommandSender, std::thread::JoinHandle<()>), TaskError> {
+    // Run the non-Send taskchampion::Replica on a dedicated thread and
+    // communicate with it via channels. This proxy is Send+Sync and
+    // implements ReplicaWrapper without forcing Replica itself to be Send.
+    use std::sync::mpsc;
+    use std::thread;
     // PathBuf is available via std::path when needed; avoid unused-import warning
-        use taskchampion::{Operations, TaskData};
-        use taskchampion::storage::{StorageConfig, AccessMode};
+    use taskchampion::{Operations, TaskData};
+    use taskchampion::storage::{StorageConfig, AccessMode};
 
-        // Command enum for actor requests is declared at module scope below
+    // Command enum for actor requests is declared at module scope below
 
-        // Create channels and spawn the actor thread. The actor will create the
-        // Replica from the provided path inside the thread (so we don't need
-        // Replica to be Send) and reply to requests over response channels.
-    let (cmd_tx, cmd_rx) = mpsc::channel::<ReplicaCommand>();
-        let path_buf = path.to_path_buf();
+    // Create channels and spawn the actor thread. The actor will create the
+    // Replica from the provided path inside the thread (so we don't need
+    // Replica to be Send) and reply to requests over response channels.
+    let (cmd_tx, cmd_rx) = mpsc::sync_channel::<(Ticket, ReplicaCommand)>(options.queue_capacity);
+    let path_buf = path.to_path_buf();
 
     // The actor will use the replica-aware mapping helper
     // map_ops_to_tc_operations_with_replica to build Operations.
 
-        // startup handshake channel
-        let (startup_tx, startup_rx) = mpsc::channel();
-
-    thread::Builder::new()
-            .name("replica-taskchampion-actor".to_string())
-            .spawn(move || {
-                // Try to construct storage and replica inside the thread.
-                let storage_res = StorageConfig::OnDisk {
-                    taskdb_dir: path_buf.clone(),
-                    create_if_missing: true,
-                    access_mode: AccessMode::ReadWrite,
-                }.into_storage();
-
-                let mut replica = match storage_res {
-                    Ok(storage) => match taskchampion::Replica::new(storage) {
-                        rep => {
-                            // warn: Replica::new returns value directly in this API
-                            rep
-                        }
-                    },
-                    Err(e) => {
-                        let _ = startup_tx.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("Failed to open TaskChampion storage: {e}") } }));
-                        return;
+    // startup handshake channel
+    let (startup_tx, startup_rx) = mpsc::channel();
+
+    let handle = thread::Builder::new()
+        .name("replica-taskchampion-actor".to_string())
+        .spawn(move || {
+            // Try to construct storage and replica inside the thread.
+            let storage_res = StorageConfig::OnDisk {
+                taskdb_dir: path_buf.clone(),
+                create_if_missing: true,
+                access_mode: AccessMode::ReadWrite,
+            }.into_storage();
+
+            let mut replica = match storage_res {
+                Ok(storage) => match taskchampion::Replica::new(storage) {
+                    rep => {
+                        // warn: Replica::new returns value directly in this API
+                        rep
                     }
-                };
-                        use std::sync::Arc;
-                // signal successful startup
-                let _ = startup_tx.send(Ok(()));
-
-                // actor loop
-                while let Ok(cmd) = cmd_rx.recv() {
-                    match cmd {
-                        ReplicaCommand::Commit { ops, resp } => {
-                            // Map our internal ops into taskchampion::Operations using the
-                            // helper that prefers Task helper methods when possible.
-                            match crate::storage::operation_batch::to_taskchampion_operations(&mut replica, &ops) {
-                                Ok(tc_ops) => {
-                                    let res = replica.commit_operations(tc_ops);
-                                    let _ = match res {
-                                        Ok(_) => resp.send(Ok(())),
-                                        Err(e) => resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion commit failed: {e}") } })),
-                                    };
-                                }
-                                Err(e) => {
-                                    let _ = resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion mapping failed: {e}") } }));
-                                }
+                },
+                Err(e) => {
+                    let _ = startup_tx.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("Failed to open TaskChampion storage: {e}") } }));
+                    return;
+                }
+            };
+                    use std::sync::Arc;
+            // signal successful startup
+            let _ = startup_tx.send(Ok(()));
+            let mut uda_types = crate::task::UdaTypes::new();
+            let mut db_path = path_buf;
+
+            // actor loop
+            while let Ok((ticket, cmd)) = cmd_rx.recv() {
+                depth.fetch_sub(1, Ordering::SeqCst);
+                if !ticket.start() {
+                    continue;
+                }
+                match cmd {
+                    ReplicaCommand::Commit { ops, resp } => {
+                        // Map our internal ops into taskchampion::Operations using the
+                        // helper that prefers Task helper methods when possible.
+                        match crate::storage::operation_batch::to_taskchampion_operations(&mut replica, &ops) {
+                            Ok(tc_ops) => {
+                                let res = replica.commit_operations(tc_ops);
+                                let _ = match res {
+                                    Ok(_) => resp.send(Ok(())),
+                                    Err(e) => resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion commit failed: {e}") } })),
+                                };
                             }
-                        }
-                        ReplicaCommand::Open { path, resp } => {
-                            // Attempt to replace replica by constructing a new one.
-                            let storage_res = StorageConfig::OnDisk {
-                                taskdb_dir: path.clone(),
-                                create_if_missing: true,
-                                access_mode: AccessMode::ReadWrite,
-                            }.into_storage();
-                            match storage_res {
-                                Ok(storage) => {
-                                    // create a new replica in-place
-                                    replica = taskchampion::Replica::new(storage);
-                                    let _ = resp.send(Ok(()));
-                                }
-                                Err(e) => {
-                                    let _ = resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("Failed to open TaskChampion storage: {e}") } }));
-                                }
+                            Err(e) => {
+                                let _ = resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion mapping failed: {e}") } }));
                             }
                         }
-                        ReplicaCommand::ReadTask { id, resp } => {
-                            // Query the replica's task data map and convert to our Task type.
-                            match replica.all_task_data() {
-                                Ok(map) => {
-                                    if let Some(td) = map.get(&id) {
-                                        // td is a map-like structure: &HashMap<String, String>
-                                        // Build a Task from available fields.
-                                        // Minimal fields: description, status, entry
-                                        let description = td.get("description").map(|s| s.to_string()).unwrap_or_default();
-                                        let status_str = td.get("status").map(|s| s.to_string()).unwrap_or_else(|| "pending".to_string());
-                                        let status = match status_str.as_str() {
-                                            "pending" => crate::task::model::TaskStatus::Pending,
-                                            "completed" => crate::task::model::TaskStatus::Completed,
-                                            "deleted" => crate::task::model::TaskStatus::Deleted,
-                                            "waiting" => crate::task::model::TaskStatus::Waiting,
-                                            _ => crate::task::model::TaskStatus::Pending,
-                                        };
-                                        let entry = td.get("entry").and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok()).map(|dt| dt.with_timezone(&chrono::Utc)).unwrap_or_else(chrono::Utc::now);
-
-                                        // Start with a new Task and overwrite fields
-                                        let mut task = crate::task::model::Task::new(description.clone());
-                                        task.id = id;
-                                        task.description = description;
-                                        task.status = status;
-                                        task.entry = entry;
-
-                                        // project
-                                        if let Some(proj) = td.get("project") {
-                                            task.project = Some(proj.to_string());
-                                        }
-
-                                        // tags
-                                        if let Some(tags_str) = td.get("tags") {
-                                            let set: std::collections::HashSet<String> = tags_str.split_whitespace().map(|s| s.to_string()).collect();
-                                            task.tags = set;
-                                        }
-
-                                        // timestamps: modified, due, scheduled, wait, end, start
-                                        if let Some(mod_s) = td.get("modified") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(mod_s) {
-                                                task.modified = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-                                        if let Some(due_s) = td.get("due") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(due_s) {
-                                                task.due = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-                                        if let Some(sched_s) = td.get("scheduled") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(sched_s) {
-                                                task.scheduled = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-                                        if let Some(wait_s) = td.get("wait") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(wait_s) {
-                                                task.wait = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-                                        if let Some(end_s) = td.get("end") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(end_s) {
-                                                task.end = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-                                        if let Some(start_s) = td.get("start") {
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(start_s) {
-                                                task.start = Some(dt.with_timezone(&chrono::Utc));
-                                            }
-                                        }
-
-                                        // priority
-                                        if let Some(prio) = td.get("priority") {
-                                            match &prio[..] {
-                                                "H" => task.priority = Some(crate::task::model::Priority::High),
-                                                "M" => task.priority = Some(crate::task::model::Priority::Medium),
-                                                "L" => task.priority = Some(crate::task::model::Priority::Low),
-                                                _ => {}
-                                            }
-                                        }
-
-                                        // annotations: try keys 'annotations' or lines in a single string
-                                        if let Some(anns_str) = td.get("annotations") {
-                                            for line in anns_str.lines() {
-                                                // Expect "<rfc3339> <description>"
-                                                if let Some((ts, desc)) = line.split_once(' ') {
-                                                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                                                        let ann = crate::task::annotation::Annotation::with_timestamp(desc.replace("\\n", "\n"), dt.with_timezone(&chrono::Utc));
-                                                        task.annotations.push(ann);
-                                                    } else {
-                                                        // fallback: store whole line as description with current time
-                                                        let ann = crate::task::annotation::Annotation::new(line.to_string());
-                                                        task.annotations.push(ann);
-                                                    }
-                                                } else {
-                                                    let ann = crate::task::annotation::Annotation::new(line.to_string());
-                                                    task.annotations.push(ann);
-                                                }
-                                            }
-                                        }
-
-                                        // dependencies
-                                        if let Some(dep_str) = td.get("depends") {
-                                            let mut deps = std::collections::HashSet::new();
-                                            for token in dep_str.split_whitespace() {
-                                                if let Ok(u) = Uuid::parse_str(token) {
-                                                    deps.insert(u);
-                                                }
-                                            }
-                                            task.depends = deps;
-                                        }
-
-                                        // recurrence
-                                        if let Some(recur_s) = td.get("recur") {
-                                            if let Ok(rp) = crate::task::recurrence::RecurrencePattern::parse(recur_s) {
-                                                task.recur = Some(rp);
-                                            }
-                                        }
-
-                                        // parent, mask
-                                        if let Some(parent_s) = td.get("parent") {
-                                            if let Ok(u) = Uuid::parse_str(parent_s) {
-                                                task.parent = Some(u);
-                                            }
-                                        }
-                                        if let Some(mask_s) = td.get("mask") {
-                                            task.mask = Some(mask_s.to_string());
-                                        }
-
-                                        // active flag
-                                        if let Some(active_s) = td.get("active") {
-                                            let s = &active_s[..];
-                                            task.active = matches!(s, "1" | "true" | "True");
-                                        }
-
-                                        // UDAs: any key not recognized above and not in a list of standard fields
-                                        let standard = ["description","status","entry","project","tags","modified","due","scheduled","wait","end","start","priority","annotations","depends","recur","parent","mask","active","id","uuid"];
-                                        for (k, v) in td.iter() {
-                                            if standard.contains(&k.as_str()) { continue; }
-                                            // Try to parse number
-                                            if let Ok(n) = v.parse::<f64>() {
-                                                task.udas.insert(k.clone(), crate::task::model::UdaValue::Number(n));
-                                                continue;
-                                            }
-                                            // Try date
-                                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(v) {
-                                                task.udas.insert(k.clone(), crate::task::model::UdaValue::Date(dt.with_timezone(&chrono::Utc)));
-                                                continue;
-                                            }
-                                            // Fallback to string
-                                            task.udas.insert(k.clone(), crate::task::model::UdaValue::String(v.clone()));
-                                        }
-
-                                        let _ = resp.send(Ok(Some(task)));
-                                    } else {
-                                        let _ = resp.send(Ok(None));
-                                    }
-                                }
-                                Err(e) => {
-                                    let _ = resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("Failed to read replica task data: {e}") } }));
-                                }
+                    }
+                    ReplicaCommand::Save { task, resp } => {
+                        let _ = resp.send(save_in_transaction(&db_path, &task, &uda_types));
+                    }
+                    ReplicaCommand::Open { path, resp } => {
+                        // Attempt to replace replica by constructing a new one.
+                        let storage_res = StorageConfig::OnDisk {
+                            taskdb_dir: path.clone(),
+                            create_if_missing: true,
+                            access_mode: AccessMode::ReadWrite,
+                        }.into_storage();
+                        match storage_res {
+                            Ok(storage) => {
+                                // create a new replica in-place
+                                replica = taskchampion::Replica::new(storage);
+                                db_path = path;
+                                let _ = resp.send(Ok(()));
+                            }
+                            Err(e) => {
+                                let _ = resp.send(Err(TaskError::Storage { source: StorageError::Database { message: format!("Failed to open TaskChampion storage: {e}") } }));
                             }
                         }
                     }
+                    ReplicaCommand::SetUdaTypes { types } => {
+                        uda_types = types;
+                    }
+                    ReplicaCommand::ReadTask { id, resp } => {
+                        let res = replica.get_task_data(id).map_err(|e| read_error(&e)).and_then(|td| {
+                            let ws = replica.working_set().map_err(|e| read_error(&e))?;
+                            Ok(td.map(|td| with_display_id(task_from_task_data(&td, &uda_types), &ws)))
+                        });
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::AllTasks { resp } => {
+                        let res = replica.all_task_data().map_err(|e| read_error(&e)).and_then(|map| {
+                            let ws = replica.working_set().map_err(|e| read_error(&e))?;
+                            Ok(map.values().map(|td| with_display_id(task_from_task_data(td, &uda_types), &ws)).collect())
+                        });
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::PendingTasks { resp } => {
+                        let res = replica.pending_task_data().map_err(|e| read_error(&e)).and_then(|data| {
+                            let ws = replica.working_set().map_err(|e| read_error(&e))?;
+                            Ok(data.iter().map(|td| with_display_id(task_from_task_data(td, &uda_types), &ws)).collect())
+                        });
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::WorkingSet { resp } => {
+                        let res = replica.working_set().map(|ws| ws.iter().collect()).map_err(|e| read_error(&e));
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::Undo { resp } => {
+                        let res = replica
+                            .get_undo_operations()
+                            .and_then(|ops| replica.commit_reversed_operations(ops))
+                            .map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("TaskChampion undo failed: {e}") } });
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::NumLocalOperations { resp } => {
+                        let _ = resp.send(replica.num_local_operations().map_err(|e| read_error(&e)));
+                    }
+                    ReplicaCommand::NumUndoPoints { resp } => {
+                        let _ = resp.send(replica.num_undo_points().map_err(|e| read_error(&e)));
+                    }
+                    ReplicaCommand::RebuildWorkingSet { renumber, resp } => {
+                        let res = replica.rebuild_working_set(renumber).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to rebuild working set: {e}") } });
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::Sync { server, avoid_snapshots, resp } => {
+                        let res = sync_replica(&mut replica, server, avoid_snapshots);
+                        let _ = resp.send(res);
+                    }
+                    ReplicaCommand::Ping { resp } => {
+                        // Touch the database so a broken handle shows up here
+                        let _ = resp.send(replica.num_local_operations().map(|_| ()).map_err(|e| read_error(&e)));
+                    }
                 }
-            }).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to spawn replica actor thread: {e}") } })?;
-
-        // Wait for startup handshake
-        use std::time::Duration;
-        match startup_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(Ok(())) => {
-                let proxy = ReplicaTaskChampionActor { sender: Arc::new(Mutex::new(cmd_tx)) };
-                return Ok(Box::new(proxy));
             }
-            Ok(Err(e)) => return Err(e),
-            Err(_) => return Err(TaskError::Storage { source: StorageError::Database { message: "Timed out waiting for replica actor startup".to_string() } }),
+        }).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to spawn replica actor thread: {e}") } })?;
+
+    // Wait for startup handshake
+    use std::time::Duration;
+    match startup_rx.recv_timeout(Duration::from_secs(5)) {
+        Ok(Ok(())) => Ok((cmd_tx, handle)),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(TaskError::Storage { source: StorageError::Database { message: "Timed out waiting for replica actor startup".to_string() } }),
+    }
+}
+
+/// Limits applied to requests sent to the replica actor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaOptions {
+    /// Requests that may wait in the queue before new ones are rejected
+    /// with [`StorageError::QueueFull`]
+    pub queue_capacity: usize,
+    /// How long a caller waits for a reply before getting
+    /// [`StorageError::Timeout`]; `None` waits forever. A request still
+    /// queued by then is withdrawn, so a timed-out write is never applied
+    /// later; one the replica has already started is waited for.
+    pub request_timeout: Option<std::time::Duration>,
+}
+
+impl Default for ReplicaOptions {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 64,
+            request_timeout: Some(std::time::Duration::from_secs(30)),
         }
     }
+}
+
+/// Factory to open a TaskChampion-backed replica wrapper.
+pub fn open_taskchampion_replica(path: &Path) -> Result<Box<dyn ReplicaWrapper>, TaskError> {
+    open_taskchampion_replica_with(path, ReplicaOptions::default())
+}
+
+/// Open a TaskChampion-backed replica wrapper with custom queue and timeout limits
+pub fn open_taskchampion_replica_with(path: &Path, options: ReplicaOptions) -> Result<Box<dyn ReplicaWrapper>, TaskError> {
+    #[cfg(feature = "taskchampion")]
+    {
+        let depth = Arc::new(AtomicUsize::new(0));
+        let (sender, handle) = spawn_replica_actor(path, &options, depth.clone())?;
+        Ok(Box::new(ReplicaTaskChampionActor::new(path, options, sender, handle, depth)))
+    }
 
     // Fallback stub when feature is not enabled
     #[cfg(not(feature = "taskchampion"))]
     {
-        // consume path to avoid unused variable warning when feature is disabled
-        let _ = path;
+        // consume arguments to avoid unused variable warnings when feature is disabled
+        let _ = (path, options);
         Ok(Box::new(ReplicaTaskChampionStub))
     }
 }
@@ -419,38 +676,207 @@ impl ReplicaWrapper for ReplicaTaskChampionStub {
 
 #[cfg(feature = "taskchampion")]
 struct ReplicaTaskChampionActor {
-    // Sender is protected by Mutex only to satisfy Send+Sync; mpsc::Sender is
-    // already Send, but wrapping keeps the field Sync for the boxed trait object.
-    sender: Arc<Mutex<std::sync::mpsc::Sender<ReplicaCommand>>>,
+    // Mutex keeps the proxy Sync and lets a request restart a dead actor
+    state: Mutex<ActorState>,
+}
+
+#[cfg(feature = "taskchampion")]
+struct ActorState {
+    // None once closed
+    sender: Option<CommandSender>,
+    handle: Option<std::thread::JoinHandle<()>>,
+    // Commands queued but not yet picked up by the actor
+    depth: Arc<AtomicUsize>,
+    // Needed to restart the actor
+    path: std::path::PathBuf,
+    options: ReplicaOptions,
+    uda_types: crate::task::UdaTypes,
+}
+
+#[cfg(feature = "taskchampion")]
+impl ActorState {
+    // Stop accepting commands, let the actor drain its queue and wait for it
+    fn shutdown(&mut self) -> Result<(), TaskError> {
+        self.sender = None;
+        match self.handle.take().map(|handle| handle.join()) {
+            Some(Err(_)) => Err(TaskError::Storage { source: StorageError::Database { message: "Replica actor thread panicked".to_string() } }),
+            _ => Ok(()),
+        }
+    }
+
+    // Replace a dead actor with a fresh one on the same replica
+    fn restart(&mut self) -> Result<(), TaskError> {
+        let _ = self.shutdown();
+        self.depth.store(0, Ordering::SeqCst);
+        let (sender, handle) = spawn_replica_actor(&self.path, &self.options, self.depth.clone())?;
+        self.sender = Some(sender);
+        self.handle = Some(handle);
+        let types = self.uda_types.clone();
+        self.enqueue(Ticket::default(), ReplicaCommand::SetUdaTypes { types })
+    }
+
+    // Queue a command without blocking; a full queue is reported rather than waited on
+    fn enqueue(&mut self, ticket: Ticket, command: ReplicaCommand) -> Result<(), TaskError> {
+        use std::sync::mpsc::TrySendError;
+        let sender = self.sender.as_ref().ok_or(TaskError::ServiceStopped)?;
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        match sender.try_send((ticket, command)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.depth.fetch_sub(1, Ordering::SeqCst);
+                Err(TaskError::Storage { source: StorageError::QueueFull { capacity: self.options.queue_capacity } })
+            }
+            Err(TrySendError::Disconnected(command)) => {
+                // The actor died; restart it and try once more
+                self.restart()?;
+                let sender = self.sender.as_ref().ok_or(TaskError::ServiceStopped)?;
+                self.depth.fetch_add(1, Ordering::SeqCst);
+                sender.try_send(command).map_err(|e| {
+                    self.depth.fetch_sub(1, Ordering::SeqCst);
+                    TaskError::Storage { source: StorageError::Database { message: format!("Failed to send command to replica actor: {e}") } }
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "taskchampion")]
+impl ReplicaTaskChampionActor {
+    fn new(
+        path: &Path,
+        options: ReplicaOptions,
+        sender: CommandSender,
+        handle: std::thread::JoinHandle<()>,
+        depth: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            state: Mutex::new(ActorState {
+                sender: Some(sender),
+                handle: Some(handle),
+                depth,
+                path: path.to_path_buf(),
+                options,
+                uda_types: crate::task::UdaTypes::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, ActorState>, TaskError> {
+        self.state.lock().map_err(|_| TaskError::Storage { source: StorageError::Database { message: "Replica actor state mutex poisoned".to_string() } })
+    }
+
+    // Send a command built around a fresh response channel and wait for the
+    // reply. A command that cannot be delivered because the actor died is
+    // retried once on a restarted actor; one that was delivered is never
+    // resent, since it may already have been applied. On timeout the
+    // command is withdrawn if the actor has not started it, and otherwise
+    // waited for, so a caller holding a write lock never releases it while
+    // its write is still to come.
+    fn request<T>(
+        &self,
+        what: &str,
+        command: impl FnOnce(std::sync::mpsc::Sender<Result<T, TaskError>>) -> ReplicaCommand,
+    ) -> Result<T, TaskError> {
+        use std::sync::mpsc::RecvTimeoutError;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let ticket = Ticket::default();
+        let timeout = {
+            let mut state = self.lock()?;
+            state.enqueue(ticket.clone(), command(tx))?;
+            state.options.request_timeout
+        };
+        let wait = |rx: std::sync::mpsc::Receiver<Result<T, TaskError>>| {
+            rx.recv().map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("No response from replica actor: {e}") } })?
+        };
+        let Some(after) = timeout else {
+            return wait(rx);
+        };
+        match rx.recv_timeout(after) {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) if ticket.withdraw() => Err(TaskError::Storage { source: StorageError::Timeout { operation: format!("replica {what}"), after } }),
+            Err(RecvTimeoutError::Timeout) => wait(rx),
+            Err(RecvTimeoutError::Disconnected) => Err(TaskError::Storage { source: StorageError::Database { message: "No response from replica actor: channel closed".to_string() } }),
+        }
+    }
+}
+
+#[cfg(feature = "taskchampion")]
+impl Drop for ReplicaTaskChampionActor {
+    fn drop(&mut self) {
+        if let Ok(state) = self.state.get_mut() {
+            let _ = state.shutdown();
+        }
+    }
 }
 
 #[cfg(feature = "taskchampion")]
 impl ReplicaWrapper for ReplicaTaskChampionActor {
     fn commit_operations(&mut self, ops: &[Op]) -> Result<(), TaskError> {
-        let (tx, rx) = std::sync::mpsc::channel();
-    let cmd = ReplicaCommand::Commit { ops: ops.to_vec(), resp: tx };
-        // Acquire lock briefly to send
-        let guard = self.sender.lock().map_err(|_| TaskError::Storage { source: StorageError::Database { message: "Replica actor sender mutex poisoned".to_string() } })?;
-        guard.send(cmd).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to send commit command to replica actor: {e}") } })?;
-        rx.recv().map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("No response from replica actor: {e}") } })??;
-        Ok(())
+        self.request("commit", |resp| ReplicaCommand::Commit { ops: ops.to_vec(), resp })
+    }
+
+    fn save_task(&mut self, task: &crate::task::Task) -> Result<(), TaskError> {
+        self.request("save", |resp| ReplicaCommand::Save { task: Box::new(task.clone()), resp })
     }
 
     fn open(&mut self, path: &Path) -> Result<(), TaskError> {
-        let (tx, rx) = std::sync::mpsc::channel();
-    let cmd = ReplicaCommand::Open { path: path.to_path_buf(), resp: tx };
-        let guard = self.sender.lock().map_err(|_| TaskError::Storage { source: StorageError::Database { message: "Replica actor sender mutex poisoned".to_string() } })?;
-        guard.send(cmd).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to send open command to replica actor: {e}") } })?;
-        rx.recv().map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("No response from replica actor: {e}") } })??;
+        self.request("open", |resp| ReplicaCommand::Open { path: path.to_path_buf(), resp })?;
+        self.lock()?.path = path.to_path_buf();
         Ok(())
     }
 
-    fn read_task(&self, _id: Uuid) -> Result<Option<crate::task::Task>, TaskError> {
-        let (tx, rx) = std::sync::mpsc::channel();
-        let cmd = ReplicaCommand::ReadTask { id: _id, resp: tx };
-        let guard = self.sender.lock().map_err(|_| TaskError::Storage { source: StorageError::Database { message: "Replica actor sender mutex poisoned".to_string() } })?;
-        guard.send(cmd).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to send read command to replica actor: {e}") } })?;
-        let res = rx.recv().map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("No response from replica actor: {e}") } })?;
-        res
+    fn set_uda_types(&mut self, types: crate::task::UdaTypes) {
+        if let Ok(state) = self.state.get_mut() {
+            state.uda_types = types.clone();
+            let _ = state.enqueue(Ticket::default(), ReplicaCommand::SetUdaTypes { types });
+        }
+    }
+
+    fn queue_depth(&self) -> usize {
+        self.lock().map(|state| state.depth.load(Ordering::SeqCst)).unwrap_or(0)
+    }
+
+    fn all_tasks(&self) -> Result<Vec<crate::task::Task>, TaskError> {
+        self.request("all tasks", |resp| ReplicaCommand::AllTasks { resp })
+    }
+
+    fn pending_tasks(&self) -> Result<Vec<crate::task::Task>, TaskError> {
+        self.request("pending tasks", |resp| ReplicaCommand::PendingTasks { resp })
+    }
+
+    fn working_set(&self) -> Result<Vec<(usize, Uuid)>, TaskError> {
+        self.request("working set", |resp| ReplicaCommand::WorkingSet { resp })
+    }
+
+    fn undo(&mut self) -> Result<bool, TaskError> {
+        self.request("undo", |resp| ReplicaCommand::Undo { resp })
+    }
+
+    fn rebuild_working_set(&self, renumber: bool) -> Result<(), TaskError> {
+        self.request("working set rebuild", |resp| ReplicaCommand::RebuildWorkingSet { renumber, resp })
+    }
+
+    fn sync(&mut self, server: &crate::sync::SyncServerConfig, avoid_snapshots: bool) -> Result<(), TaskError> {
+        self.request("sync", |resp| ReplicaCommand::Sync { server: server.clone(), avoid_snapshots, resp })
+    }
+
+    fn num_local_operations(&self) -> Result<usize, TaskError> {
+        self.request("operation count", |resp| ReplicaCommand::NumLocalOperations { resp })
+    }
+
+    fn num_undo_points(&self) -> Result<usize, TaskError> {
+        self.request("undo point count", |resp| ReplicaCommand::NumUndoPoints { resp })
+    }
+
+    fn health_check(&self) -> Result<(), TaskError> {
+        self.request("health check", |resp| ReplicaCommand::Ping { resp })
+    }
+
+    fn close(&mut self) -> Result<(), TaskError> {
+        self.lock()?.shutdown()
+    }
+
+    fn read_task(&self, id: Uuid) -> Result<Option<crate::task::Task>, TaskError> {
+        self.request("read", |resp| ReplicaCommand::ReadTask { id, resp })
     }
 }
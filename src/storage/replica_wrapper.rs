@@ -1,7 +1,7 @@
 //! Replica wrapper abstraction
 //!
 //! Provides a trait to abstract over the TaskChampion Replica for unit testing.
-use crate::error::TaskError;
+use crate::error::{StorageError, TaskError};
 use crate::storage::operation_batch::Operation as Op;
 use uuid::Uuid;
 use std::path::Path;
@@ -16,9 +16,92 @@ pub trait ReplicaWrapper: Send + Sync {
 
     /// Read a task by uuid
     fn read_task(&self, id: Uuid) -> Result<Option<crate::task::Task>, TaskError>;
+
+    /// Store `task`, writing only what differs from the stored version.
+    /// The TaskChampion replica reads and writes in one SQLite transaction;
+    /// this default reads and commits separately.
+    fn save_task(&mut self, task: &crate::task::Task) -> Result<(), TaskError> {
+        let existing = self.read_task(task.id).unwrap_or_default();
+        self.commit_operations(&crate::storage::operation_batch::build_save_batch(existing.as_ref(), task))
+    }
     
+    /// Declare UDA types used to decode UDAs read back from the replica;
+    /// without them UDA values are guessed
+    fn set_uda_types(&mut self, _types: crate::task::UdaTypes) {}
+
+    /// Read every task in the replica, including completed and deleted ones
+    fn all_tasks(&self) -> Result<Vec<crate::task::Task>, TaskError> {
+        Err(unsupported("all_tasks"))
+    }
+
+    /// Read the tasks in the working set (pending and recurring tasks)
+    fn pending_tasks(&self) -> Result<Vec<crate::task::Task>, TaskError> {
+        Err(unsupported("pending_tasks"))
+    }
+
+    /// The working set as `(index, uuid)` pairs; the index is the task's
+    /// display ID
+    fn working_set(&self) -> Result<Vec<(usize, Uuid)>, TaskError> {
+        Err(unsupported("working_set"))
+    }
+
+    /// Revert the operations back to the last undo point. Returns false when
+    /// there is nothing local to undo.
+    fn undo(&mut self) -> Result<bool, TaskError> {
+        Err(unsupported("undo"))
+    }
+
+    /// Drop tasks that are no longer pending from the working set. With
+    /// `renumber` the remaining tasks get dense display IDs from 1, as the
+    /// Taskwarrior CLI does before each report when `gc` is on.
+    fn rebuild_working_set(&self, _renumber: bool) -> Result<(), TaskError> {
+        Err(unsupported("rebuild_working_set"))
+    }
+
+    /// Sync with a server: pull remote changes, push local ones. An empty
+    /// replica starts from the server's latest snapshot. Unless
+    /// `avoid_snapshots` is set, a snapshot is uploaded whenever the server
+    /// asks for one.
+    fn sync(&mut self, _server: &crate::sync::SyncServerConfig, _avoid_snapshots: bool) -> Result<(), TaskError> {
+        Err(unsupported("sync"))
+    }
+
+    /// Number of local operations not yet synchronized
+    fn num_local_operations(&self) -> Result<usize, TaskError> {
+        Err(unsupported("num_local_operations"))
+    }
+
+    /// Number of undo points available
+    fn num_undo_points(&self) -> Result<usize, TaskError> {
+        Err(unsupported("num_undo_points"))
+    }
+
+    /// Check that the replica still answers and its database is readable
+    fn health_check(&self) -> Result<(), TaskError> {
+        Ok(())
+    }
+
+    /// Release the replica: finish queued work and free its database
+    /// handle. Later calls fail.
+    fn close(&mut self) -> Result<(), TaskError> {
+        Ok(())
+    }
+
+    /// Requests queued and not yet started; 0 for replicas without a queue
+    fn queue_depth(&self) -> usize {
+        0
+    }
+
     /// Get the last operations committed (for testing)
     fn get_last_operations(&self) -> Option<Vec<Op>> {
         None
     }
 }
+
+fn unsupported(operation: &str) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Database {
+            message: format!("Replica does not support {operation}"),
+        },
+    }
+}
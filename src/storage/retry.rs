@@ -0,0 +1,575 @@
+//! Retrying storage backend for data kept on network shares
+//!
+//! NFS and SMB mounts fail now and then with errors that go away on their
+//! own: a stale file handle after a server failover, a timed-out read, a
+//! lock held a moment too long. [`RetryingStorageBackend`] retries those
+//! with exponential backoff instead of failing the whole operation, and
+//! leaves permanent errors such as a full disk or a permission problem
+//! alone.
+//!
+//! Retried writes are safe to repeat: saving writes the whole task again,
+//! and a delete or purge whose retry finds the task already gone counts as
+//! done, since the attempt that reported an error may have applied.
+//! Archiving is not retried because a repeated run would misreport how
+//! many tasks moved.
+//!
+//! ```rust
+//! use taskwarrior3lib::storage::{FileStorageBackend, RetryPolicy, RetryingStorageBackend};
+//!
+//! let dir = tempfile::TempDir::new().unwrap();
+//! let storage = RetryingStorageBackend::new(
+//!     FileStorageBackend::with_path(dir.path()),
+//!     RetryPolicy::default().max_attempts(5),
+//! );
+//! let metrics = storage.metrics();
+//! assert_eq!(metrics.snapshot().retries, 0);
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::clock;
+use crate::config::context::UserContext;
+use crate::config::Configuration;
+use crate::error::{ConfigError, StorageError, TaskError};
+use crate::query::TaskQuery;
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::StorageBackend;
+use crate::task::Task;
+
+/// When and how often to retry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per operation, the first included
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubled after each failure
+    pub backoff: Duration,
+    /// Longest wait between two attempts
+    pub max_backoff: Duration,
+    /// Retries allowed per minute across all operations; once used up,
+    /// errors are returned at once instead of piling up retries against a
+    /// share that is down. `None` allows any number.
+    pub max_retries_per_minute: Option<u32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            max_retries_per_minute: Some(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn max_retries_per_minute(mut self, limit: Option<u32>) -> Self {
+        self.max_retries_per_minute = limit;
+        self
+    }
+
+    /// Read `storage.retry.attempts`, `storage.retry.backoff` and
+    /// `storage.retry.max_backoff` (milliseconds) and
+    /// `storage.retry.per_minute` (0 for no limit)
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let number = |key: &str| -> Result<Option<u64>, ConfigError> {
+            let Some(raw) = config.get(key) else {
+                return Ok(None);
+            };
+            raw.trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    value: raw.to_string(),
+                    expected: "a whole number".to_string(),
+                })
+        };
+
+        let mut policy = Self::default();
+        if let Some(attempts) = number("storage.retry.attempts")? {
+            policy = policy.max_attempts(u32::try_from(attempts).unwrap_or(u32::MAX));
+        }
+        if let Some(ms) = number("storage.retry.backoff")? {
+            policy.backoff = Duration::from_millis(ms);
+        }
+        if let Some(ms) = number("storage.retry.max_backoff")? {
+            policy.max_backoff = Duration::from_millis(ms);
+        }
+        if let Some(limit) = number("storage.retry.per_minute")? {
+            policy.max_retries_per_minute =
+                (limit > 0).then(|| u32::try_from(limit).unwrap_or(u32::MAX));
+        }
+        Ok(policy)
+    }
+
+    /// Wait before retry number `retry` (from 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+/// Whether `error` is worth retrying: interrupted or timed-out I/O, lost
+/// network connections, stale NFS handles, busy locks and SQLite databases
+/// locked by another connection
+pub fn is_transient(error: &TaskError) -> bool {
+    match error.root() {
+        TaskError::Storage { source } => is_transient_storage(source),
+        _ => false,
+    }
+}
+
+fn is_transient_storage(error: &StorageError) -> bool {
+    use std::io::ErrorKind;
+
+    match error {
+        // The kinds cover the OS codes on every platform: EAGAIN is
+        // `WouldBlock`, EBUSY `ResourceBusy` and ESTALE
+        // `StaleNetworkFileHandle`
+        StorageError::Io(e) => matches!(
+            e.kind(),
+            ErrorKind::Interrupted
+                | ErrorKind::WouldBlock
+                | ErrorKind::TimedOut
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::BrokenPipe
+                | ErrorKind::ResourceBusy
+                | ErrorKind::StaleNetworkFileHandle
+                | ErrorKind::NetworkDown
+                | ErrorKind::NetworkUnreachable
+                | ErrorKind::HostUnreachable
+        ),
+        StorageError::Lock { .. } | StorageError::Timeout { .. } => true,
+        // Database errors only carry SQLite's message: "database is
+        // locked" for SQLITE_BUSY, "database table is locked" for
+        // SQLITE_LOCKED
+        StorageError::Database { message } => {
+            let message = message.to_lowercase();
+            ["database is locked", "database table is locked", "database is busy"]
+                .iter()
+                .any(|busy| message.contains(busy))
+        }
+        _ => false,
+    }
+}
+
+/// Retry counts of a [`RetryingStorageBackend`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RetryStats {
+    /// Operations run
+    pub operations: u64,
+    /// Attempts after the first
+    pub retries: u64,
+    /// Operations that succeeded after at least one retry
+    pub recovered: u64,
+    /// Operations that still failed after the last attempt
+    pub exhausted: u64,
+    /// Retries skipped because the per-minute limit was reached
+    pub throttled: u64,
+    /// Retries by operation, e.g. `save`
+    pub by_operation: BTreeMap<String, u64>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    stats: RetryStats,
+    /// When recent retries happened, for the per-minute limit
+    recent: VecDeque<DateTime<Utc>>,
+}
+
+/// Handle for reading the retry counts, usable after the backend has been
+/// handed to a task manager
+#[derive(Clone, Default)]
+pub struct RetryMetrics {
+    inner: Arc<Mutex<MetricsState>>,
+}
+
+impl std::fmt::Debug for RetryMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RetryMetrics")
+            .field(&self.lock().stats)
+            .finish()
+    }
+}
+
+impl RetryMetrics {
+    /// The counts so far
+    pub fn snapshot(&self) -> RetryStats {
+        self.lock().stats.clone()
+    }
+
+    /// Start counting from zero
+    pub fn reset(&self) {
+        *self.lock() = MetricsState::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MetricsState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a wanted retry of `op`; false if the limit forbids it
+    fn allow_retry(&self, op: &str, limit: Option<u32>) -> bool {
+        let mut state = self.lock();
+        let now = clock::now();
+        let window_start = now - chrono::Duration::minutes(1);
+        while state.recent.front().is_some_and(|t| *t <= window_start) {
+            state.recent.pop_front();
+        }
+        if limit.is_some_and(|limit| state.recent.len() >= limit as usize) {
+            state.stats.throttled += 1;
+            return false;
+        }
+        state.recent.push_back(now);
+        state.stats.retries += 1;
+        *state.stats.by_operation.entry(op.to_string()).or_default() += 1;
+        true
+    }
+
+    fn finish(&self, retried: bool, ok: bool) {
+        let mut state = self.lock();
+        state.stats.operations += 1;
+        if retried && ok {
+            state.stats.recovered += 1;
+        } else if retried {
+            state.stats.exhausted += 1;
+        }
+    }
+}
+
+/// Wraps a storage backend and retries operations failing with transient
+/// errors (see [`is_transient`])
+#[derive(Debug)]
+pub struct RetryingStorageBackend<S: StorageBackend> {
+    inner: S,
+    policy: RetryPolicy,
+    metrics: RetryMetrics,
+}
+
+impl<S: StorageBackend> RetryingStorageBackend<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            metrics: RetryMetrics::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Handle for reading retry counts, shared with this backend
+    pub fn metrics(&self) -> RetryMetrics {
+        self.metrics.clone()
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+// Run `call` until it succeeds, fails permanently or runs out of attempts
+fn with_retries<T, E>(
+    policy: &RetryPolicy,
+    metrics: &RetryMetrics,
+    op: &str,
+    transient: fn(&E) -> bool,
+    mut call: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut retries = 0;
+    loop {
+        let result = call();
+        let retry = match &result {
+            Err(e) if transient(e) && retries + 1 < policy.max_attempts => {
+                metrics.allow_retry(op, policy.max_retries_per_minute)
+            }
+            _ => false,
+        };
+        if !retry {
+            metrics.finish(retries > 0, result.is_ok());
+            return result;
+        }
+        retries += 1;
+        std::thread::sleep(policy.delay(retries));
+    }
+}
+
+impl<S: StorageBackend> RetryingStorageBackend<S> {
+    fn retry<T>(
+        &self,
+        op: &str,
+        call: impl FnMut() -> Result<T, TaskError>,
+    ) -> Result<T, TaskError> {
+        with_retries(&self.policy, &self.metrics, op, is_transient, call)
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for RetryingStorageBackend<S> {
+    fn backend_name(&self) -> &str {
+        self.inner.backend_name()
+    }
+
+    fn initialize(&mut self) -> Result<(), TaskError> {
+        let inner = &mut self.inner;
+        with_retries(
+            &self.policy,
+            &self.metrics,
+            "initialize",
+            is_transient,
+            || inner.initialize(),
+        )
+    }
+
+    fn save_task(&mut self, task: &Task) -> Result<(), TaskError> {
+        let inner = &mut self.inner;
+        with_retries(&self.policy, &self.metrics, "save", is_transient, || {
+            inner.save_task(task)
+        })
+    }
+
+    fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        self.retry("load", || self.inner.load_task(id))
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        let inner = &mut self.inner;
+        let mut attempts = 0;
+        with_retries(&self.policy, &self.metrics, "delete", is_transient, || {
+            attempts += 1;
+            match inner.delete_task(id) {
+                Err(TaskError::NotFound { .. }) if attempts > 1 => Ok(()),
+                result => result,
+            }
+        })
+    }
+
+    fn purge_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        let inner = &mut self.inner;
+        let mut attempts = 0;
+        with_retries(&self.policy, &self.metrics, "purge", is_transient, || {
+            attempts += 1;
+            match inner.purge_task(id) {
+                Err(TaskError::NotFound { .. }) if attempts > 1 => Ok(()),
+                result => result,
+            }
+        })
+    }
+
+    fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        self.retry("load_all", || self.inner.load_all_tasks())
+    }
+
+    fn archive_completed(&mut self, before: DateTime<Utc>) -> Result<usize, TaskError> {
+        self.inner.archive_completed(before)
+    }
+
+    fn query_tasks(
+        &self,
+        query: &TaskQuery,
+        active_context: Option<&UserContext>,
+    ) -> Result<Vec<Task>, TaskError> {
+        self.retry("query", || self.inner.query_tasks(query, active_context))
+    }
+
+    fn verify(&self) -> Result<IntegrityReport, TaskError> {
+        self.retry("verify", || self.inner.verify())
+    }
+
+    fn backup(&self) -> Result<String, StorageError> {
+        with_retries(
+            &self.policy,
+            &self.metrics,
+            "backup",
+            is_transient_storage,
+            || self.inner.backup(),
+        )
+    }
+
+    fn restore(&mut self, backup_data: &str) -> Result<(), StorageError> {
+        let inner = &mut self.inner;
+        with_retries(
+            &self.policy,
+            &self.metrics,
+            "restore",
+            is_transient_storage,
+            || inner.restore(backup_data),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorageBackend;
+    use crate::testing::{storage_full, FlakyStorageBackend, StorageOp};
+
+    fn stale_handle() -> TaskError {
+        TaskError::Storage {
+            source: StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::StaleNetworkFileHandle,
+                "Stale file handle",
+            )),
+        }
+    }
+
+    fn retrying(
+        dir: &tempfile::TempDir,
+        policy: RetryPolicy,
+    ) -> RetryingStorageBackend<FlakyStorageBackend<FileStorageBackend>> {
+        let policy = policy.backoff(Duration::ZERO);
+        let mut storage = RetryingStorageBackend::new(
+            FlakyStorageBackend::new(FileStorageBackend::with_path(dir.path())),
+            policy,
+        );
+        storage.initialize().unwrap();
+        storage
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_os_errors_are_classified_by_kind() {
+        let os = |code| TaskError::Storage {
+            source: StorageError::Io(std::io::Error::from_raw_os_error(code)),
+        };
+        // ESTALE, EAGAIN, EBUSY, then ENOENT and ENOSPC
+        for code in [116, 11, 16] {
+            assert!(is_transient(&os(code)), "{code}");
+        }
+        for code in [2, 28] {
+            assert!(!is_transient(&os(code)), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_busy_sqlite_databases_are_transient() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("busy.sqlite3");
+        let holder = rusqlite::Connection::open(&path).unwrap();
+        holder.execute_batch("CREATE TABLE t (x); BEGIN EXCLUSIVE;").unwrap();
+        let other = rusqlite::Connection::open(&path).unwrap();
+        other.busy_timeout(Duration::ZERO).unwrap();
+        let busy = other.execute("INSERT INTO t VALUES (1)", []).unwrap_err();
+
+        let database = |message: String| TaskError::Storage {
+            source: StorageError::Database { message },
+        };
+        assert!(is_transient(&database(format!("Failed to commit operations: {busy}"))));
+        assert!(!is_transient(&database("no such table: tasks".to_string())));
+    }
+
+    #[test]
+    fn test_transient_errors_are_retried() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut storage = retrying(&dir, RetryPolicy::default());
+        let faults = storage.inner().faults();
+        let metrics = storage.metrics();
+        let task = Task::new("On the share".to_string());
+
+        faults.fail_once(StorageOp::Save, stale_handle);
+        storage.save_task(&task).unwrap();
+        assert_eq!(faults.calls(StorageOp::Save), 2);
+
+        faults.fail_always(StorageOp::Load, stale_handle);
+        assert!(storage.load_task(task.id).is_err());
+        assert_eq!(faults.calls(StorageOp::Load), 3);
+        faults.clear();
+
+        // A full disk will not fix itself
+        faults.fail_once(StorageOp::Save, storage_full);
+        assert!(storage.save_task(&task).is_err());
+        assert_eq!(faults.calls(StorageOp::Save), 3);
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.operations, 4);
+        assert_eq!((stats.retries, stats.recovered, stats.exhausted), (3, 1, 1));
+        assert_eq!(stats.by_operation["load"], 2);
+    }
+
+    #[test]
+    fn test_retried_delete_that_already_applied_succeeds() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut storage = retrying(&dir, RetryPolicy::default());
+        let task = Task::new("Gone".to_string());
+        storage.save_task(&task).unwrap();
+
+        // The inner backend deleted the task but the error came back
+        // anyway, as when a reply is lost; the retry then finds nothing
+        storage.inner.inner_mut().delete_task(task.id).unwrap();
+        storage
+            .inner()
+            .faults()
+            .fail_once(StorageOp::Delete, stale_handle);
+        storage.delete_task(task.id).unwrap();
+        assert!(storage.load_task(task.id).unwrap().is_none());
+        assert!(matches!(
+            storage.delete_task(task.id),
+            Err(TaskError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_retry_limit_per_minute() {
+        let _clock = clock::deterministic(
+            clock::FixedClock::new(Utc::now()),
+            clock::SequentialIds::new(),
+        );
+        let dir = tempfile::TempDir::new().unwrap();
+        let storage = retrying(
+            &dir,
+            RetryPolicy::default()
+                .max_attempts(10)
+                .max_retries_per_minute(Some(2)),
+        );
+        let faults = storage.inner().faults();
+        faults.fail_always(StorageOp::LoadAll, stale_handle);
+        assert!(storage.load_all_tasks().is_err());
+        assert_eq!(faults.calls(StorageOp::LoadAll), 3);
+        assert_eq!(storage.metrics().snapshot().throttled, 1);
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut config = Configuration::default();
+        config.set("storage.retry.attempts", "5");
+        config.set("storage.retry.backoff", "250");
+        config.set("storage.retry.per_minute", "0");
+        let policy = RetryPolicy::from_config(&config).unwrap();
+        assert_eq!(policy.max_attempts, 5);
+        assert_eq!(policy.delay(1), Duration::from_millis(250));
+        assert_eq!(policy.delay(3), Duration::from_millis(1000));
+        assert_eq!(policy.delay(10), policy.max_backoff);
+        assert_eq!(policy.max_retries_per_minute, None);
+
+        config.set("storage.retry.backoff", "soon");
+        assert!(RetryPolicy::from_config(&config).is_err());
+    }
+}
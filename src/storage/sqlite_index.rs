@@ -0,0 +1,503 @@
+//! SQLite index for the file storage backend
+//!
+//! Maintains a table of key task fields (status, project, dates, priority)
+//! next to `tasks.json`, each row carrying the task's JSON. The index is
+//! updated on every save and delete. Queries filter on status and project
+//! in SQL and deserialise only the rows that match; when nothing else
+//! filters the result, the sort (by entry, modified, due or end), `offset`
+//! and `limit` run in SQL as well.
+//!
+//! The JSON file stays the source of truth; the index can always be
+//! rebuilt from it, and records the checksum of the `tasks.json` it was built
+//! from so an edit made behind its back (another client, a sync, a restore)
+//! triggers a rebuild. While the checksums agree the file backend answers
+//! reads from the index and leaves `tasks.json` unparsed until the first
+//! write, which rewrites the whole file.
+
+use crate::error::{StorageError, TaskError};
+use crate::query::{ProjectFilter, SortField, TaskQuery};
+use crate::task::Task;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Index file name inside the data directory
+pub const INDEX_FILE: &str = "tasks.index.sqlite3";
+
+/// Layout of the index tables; an index with another version is dropped
+/// and rebuilt
+const SCHEMA_VERSION: &str = "2";
+
+const UPSERT: &str = "INSERT OR REPLACE INTO task_index
+    (uuid, status, project, priority, entry, modified, due, end_time, data)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)";
+
+/// SQLite index of key task fields
+pub struct SqliteTaskIndex {
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl std::fmt::Debug for SqliteTaskIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteTaskIndex")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+fn db_error(context: &str, e: rusqlite::Error) -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Database {
+            message: format!("{context}: {e}"),
+        },
+    }
+}
+
+impl SqliteTaskIndex {
+    /// Open (or create) the index database at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, TaskError> {
+        let path = path.as_ref().to_path_buf();
+        let conn = Connection::open(&path).map_err(|e| db_error("Failed to open task index", e))?;
+        let schema_error = |e| db_error("Failed to create task index schema", e);
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS index_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        )
+        .map_err(schema_error)?;
+        let version: Option<String> = conn
+            .query_row("SELECT value FROM index_meta WHERE key = 'schema'", [], |row| row.get(0))
+            .optional()
+            .map_err(schema_error)?;
+        if version.as_deref() != Some(SCHEMA_VERSION) {
+            // Dropping the checksum too makes the backend rebuild the index
+            conn.execute_batch("DROP TABLE IF EXISTS task_index; DELETE FROM index_meta;")
+                .map_err(schema_error)?;
+        }
+        // Dates are microseconds since the epoch, so SQL sorts them as
+        // finely as the in-memory sort does
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS task_index (
+                uuid TEXT PRIMARY KEY,
+                status TEXT NOT NULL,
+                project TEXT,
+                priority TEXT,
+                entry INTEGER NOT NULL,
+                modified INTEGER,
+                due INTEGER,
+                end_time INTEGER,
+                data TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS task_index_status ON task_index(status);
+            CREATE INDEX IF NOT EXISTS task_index_project ON task_index(project);",
+        )
+        .map_err(schema_error)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO index_meta (key, value) VALUES ('schema', ?1)",
+            params![SCHEMA_VERSION],
+        )
+        .map_err(schema_error)?;
+        Ok(Self { path, conn })
+    }
+
+    /// Path of the index database
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Insert or update the index row for a task
+    pub fn upsert(&self, task: &Task) -> Result<(), TaskError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached(UPSERT)
+            .map_err(|e| db_error("Failed to update task index", e))?;
+        insert(&mut stmt, task).map_err(|e| db_error("Failed to update task index", e))
+    }
+
+    /// Remove a task from the index
+    pub fn remove(&self, id: Uuid) -> Result<(), TaskError> {
+        self.conn
+            .execute("DELETE FROM task_index WHERE uuid = ?1", params![id.to_string()])
+            .map_err(|e| db_error("Failed to update task index", e))?;
+        Ok(())
+    }
+
+    /// Number of indexed tasks
+    pub fn len(&self) -> Result<usize, TaskError> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM task_index", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(|e| db_error("Failed to count task index", e))
+    }
+
+    /// Checksum of the tasks file the index was last synced with
+    pub fn source_checksum(&self) -> Result<Option<String>, TaskError> {
+        self.conn
+            .query_row(
+                "SELECT value FROM index_meta WHERE key = 'source_checksum'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| db_error("Failed to read task index metadata", e))
+    }
+
+    /// Record the checksum of the tasks file the index now matches
+    pub fn set_source_checksum(&self, checksum: &str) -> Result<(), TaskError> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO index_meta (key, value) VALUES ('source_checksum', ?1)",
+                params![checksum],
+            )
+            .map_err(|e| db_error("Failed to update task index metadata", e))?;
+        Ok(())
+    }
+
+    /// Whether the index is empty
+    pub fn is_empty(&self) -> Result<bool, TaskError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Replace the whole index with the given tasks
+    pub fn rebuild<'a, I>(&self, tasks: I) -> Result<(), TaskError>
+    where
+        I: IntoIterator<Item = &'a Task>,
+    {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| db_error("Failed to rebuild task index", e))?;
+        tx.execute("DELETE FROM task_index", [])
+            .map_err(|e| db_error("Failed to rebuild task index", e))?;
+        {
+            let mut stmt = tx
+                .prepare(UPSERT)
+                .map_err(|e| db_error("Failed to rebuild task index", e))?;
+            for task in tasks {
+                insert(&mut stmt, task).map_err(|e| db_error("Failed to rebuild task index", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| db_error("Failed to rebuild task index", e))?;
+        Ok(())
+    }
+
+    /// Load the task with `id` from the index
+    pub fn task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        let data: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT data FROM task_index WHERE uuid = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| db_error("Failed to query task index", e))?;
+        data.as_deref().map(parse_task).transpose()
+    }
+
+    /// UUIDs of every indexed task
+    pub fn ids(&self) -> Result<Vec<Uuid>, TaskError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT uuid FROM task_index")
+            .map_err(|e| db_error("Failed to query task index", e))?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| db_error("Failed to query task index", e))?;
+        let mut ids = Vec::new();
+        for row in rows {
+            let raw = row.map_err(|e| db_error("Failed to read task index", e))?;
+            if let Ok(id) = Uuid::parse_str(&raw) {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Whether [`Self::tasks`] with `paged` set gives the exact answer to
+    /// `query`: it filters on nothing but status and project, and sorts by
+    /// an indexed date, if at all.
+    pub fn answers(query: &TaskQuery) -> bool {
+        query.tag_filter.is_none()
+            && query.date_filter.is_none()
+            && query.priority_filter.is_none()
+            && query.owner_filter.is_none()
+            && query.proximity.is_none()
+            && query.custom_filters.is_empty()
+            && query.sort.as_ref().is_none_or(|s| sort_column(&s.field).is_some())
+    }
+
+    /// Load the tasks matching the status and project filters of `query`.
+    /// With `paged` set the sort, `offset` and `limit` are applied as well;
+    /// see [`Self::answers`] for when that completes the query.
+    pub fn tasks(&self, query: &TaskQuery, paged: bool) -> Result<Vec<Task>, TaskError> {
+        let mut clauses: Vec<String> = Vec::new();
+        let mut args: Vec<String> = Vec::new();
+
+        if let Some(status) = &query.status {
+            args.push(format!("{status:?}").to_lowercase());
+            clauses.push(format!("status = ?{}", args.len()));
+        }
+
+        match &query.project_filter {
+            Some(ProjectFilter::Equals(p)) | Some(ProjectFilter::Exact(p)) => {
+                args.push(p.clone());
+                clauses.push(format!("project = ?{}", args.len()));
+            }
+            Some(ProjectFilter::Hierarchy(p)) => {
+                args.push(p.clone());
+                clauses.push(format!("substr(project, 1, length(?{n})) = ?{n}", n = args.len()));
+            }
+            Some(ProjectFilter::Multiple(projects)) => {
+                let mut placeholders = Vec::new();
+                for p in projects {
+                    args.push(p.clone());
+                    placeholders.push(format!("?{}", args.len()));
+                }
+                if placeholders.is_empty() {
+                    clauses.push("0".to_string());
+                } else {
+                    clauses.push(format!("project IN ({})", placeholders.join(", ")));
+                }
+            }
+            Some(ProjectFilter::None) => clauses.push("project IS NULL".to_string()),
+            None => {}
+        }
+
+        let mut sql = "SELECT data FROM task_index".to_string();
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        if paged {
+            if let Some(sort) = &query.sort {
+                let column = sort_column(&sort.field).ok_or_else(|| TaskError::Storage {
+                    source: StorageError::Database {
+                        message: format!("Task index cannot sort by {:?}", sort.field),
+                    },
+                })?;
+                // Tasks without a value come last in either direction, as
+                // in the in-memory sort
+                let direction = if sort.ascending { "ASC" } else { "DESC" };
+                sql.push_str(&format!(
+                    " ORDER BY {column} IS NULL, {column} {direction}, uuid"
+                ));
+            }
+            if query.limit.is_some() || query.offset.is_some() {
+                let limit = query.limit.map_or(-1, |n| i64::try_from(n).unwrap_or(i64::MAX));
+                let offset = query.offset.map_or(0, |n| i64::try_from(n).unwrap_or(i64::MAX));
+                sql.push_str(&format!(" LIMIT {limit} OFFSET {offset}"));
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| db_error("Failed to query task index", e))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(|e| db_error("Failed to query task index", e))?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let data = row.map_err(|e| db_error("Failed to read task index", e))?;
+            tasks.push(parse_task(&data)?);
+        }
+        Ok(tasks)
+    }
+}
+
+/// Index column holding the value `field` sorts by
+fn sort_column(field: &SortField) -> Option<&'static str> {
+    match field {
+        SortField::Entry => Some("entry"),
+        SortField::Modified => Some("COALESCE(modified, entry)"),
+        SortField::Due => Some("due"),
+        SortField::End => Some("end_time"),
+        _ => None,
+    }
+}
+
+fn insert(stmt: &mut rusqlite::Statement<'_>, task: &Task) -> rusqlite::Result<()> {
+    let data = serde_json::to_string(task)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    stmt.execute(params![
+        task.id.to_string(),
+        status_key(task),
+        task.project,
+        task.priority.map(|p| format!("{p:?}")),
+        task.entry.timestamp_micros(),
+        task.modified.map(|d| d.timestamp_micros()),
+        task.due.map(|d| d.timestamp_micros()),
+        task.end.map(|d| d.timestamp_micros()),
+        data,
+    ])?;
+    Ok(())
+}
+
+fn parse_task(data: &str) -> Result<Task, TaskError> {
+    serde_json::from_str(data).map_err(|e| TaskError::Storage {
+        source: StorageError::SerializationError {
+            message: format!("Failed to parse indexed task: {e}"),
+        },
+    })
+}
+
+fn status_key(task: &Task) -> String {
+    format!("{:?}", task.status).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{TaskQueryBuilder, TaskQueryBuilderImpl};
+    use crate::task::TaskStatus;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tasks_push_down_status_and_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let index = SqliteTaskIndex::open(temp_dir.path().join(INDEX_FILE)).unwrap();
+
+        let mut work = Task::new("Work".to_string());
+        work.project = Some("Work.Reports".to_string());
+        let mut home = Task::new("Home".to_string());
+        home.project = Some("Home".to_string());
+        let mut done = Task::new("Done".to_string());
+        done.status = TaskStatus::Completed;
+        index.rebuild([&work, &home, &done]).unwrap();
+        assert_eq!(index.len().unwrap(), 3);
+
+        let pending = TaskQueryBuilderImpl::new()
+            .status(TaskStatus::Pending)
+            .build()
+            .unwrap();
+        assert_eq!(index.tasks(&pending, false).unwrap().len(), 2);
+
+        let hierarchy = TaskQuery {
+            project_filter: Some(ProjectFilter::Hierarchy("Work".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(index.tasks(&hierarchy, false).unwrap(), vec![work.clone()]);
+        assert_eq!(index.task(work.id).unwrap(), Some(work.clone()));
+
+        index.remove(work.id).unwrap();
+        assert!(index.tasks(&hierarchy, false).unwrap().is_empty());
+        assert_eq!(index.task(work.id).unwrap(), None);
+        assert_eq!(index.ids().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_tasks_sort_and_page_in_sql() {
+        use crate::query::{SortCriteria, SortField};
+        use chrono::{Duration, Utc};
+
+        let temp_dir = TempDir::new().unwrap();
+        let index = SqliteTaskIndex::open(temp_dir.path().join(INDEX_FILE)).unwrap();
+        let now = Utc::now();
+        let tasks: Vec<Task> = [Some(2), None, Some(0), Some(1)]
+            .iter()
+            .enumerate()
+            .map(|(i, due)| {
+                let mut task = Task::new(format!("Task {i}"));
+                task.due = due.map(|days| now + Duration::days(days));
+                task
+            })
+            .collect();
+        index.rebuild(&tasks).unwrap();
+
+        let descriptions = |query: &TaskQuery| -> Vec<String> {
+            index.tasks(query, true).unwrap().into_iter().map(|t| t.description).collect()
+        };
+        // Tasks without a due date come last in either direction
+        let mut query = TaskQuery {
+            sort: Some(SortCriteria::ascending(SortField::Due)),
+            ..Default::default()
+        };
+        assert!(SqliteTaskIndex::answers(&query));
+        assert_eq!(descriptions(&query), ["Task 2", "Task 3", "Task 0", "Task 1"]);
+        query.sort = Some(SortCriteria::descending(SortField::Due));
+        assert_eq!(descriptions(&query), ["Task 0", "Task 3", "Task 2", "Task 1"]);
+        query.offset = Some(1);
+        query.limit = Some(2);
+        assert_eq!(descriptions(&query), ["Task 3", "Task 2"]);
+        query.limit = None;
+        assert_eq!(descriptions(&query).len(), 3);
+
+        query.sort = Some(SortCriteria::priority());
+        assert!(!SqliteTaskIndex::answers(&query));
+        assert!(index.tasks(&query, true).is_err());
+        query.sort = None;
+        query.tag_filter = Some(crate::query::TagFilter::untagged());
+        assert!(!SqliteTaskIndex::answers(&query));
+    }
+
+    #[test]
+    fn test_file_backend_uses_index() {
+        use crate::storage::{FileStorageBackend, StorageBackend};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut backend = FileStorageBackend::with_path(temp_dir.path()).with_sqlite_index();
+        backend.initialize().unwrap();
+
+        let mut work = Task::new("Work".to_string());
+        work.project = Some("Work".to_string());
+        backend.save_task(&work).unwrap();
+        backend.save_task(&Task::new("Other".to_string())).unwrap();
+        assert!(temp_dir.path().join(INDEX_FILE).exists());
+
+        let query = TaskQueryBuilderImpl::new()
+            .project("Work".to_string())
+            .build()
+            .unwrap();
+        let found = backend.query_tasks(&query, None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, work.id);
+
+        // A fresh backend reuses the persisted index and leaves tasks.json
+        // unparsed until it writes
+        let mut reopened = FileStorageBackend::with_path(temp_dir.path()).with_sqlite_index();
+        reopened.initialize().unwrap();
+        assert!(!reopened.cache_loaded.load(std::sync::atomic::Ordering::Acquire));
+        assert_eq!(reopened.query_tasks(&query, None).unwrap().len(), 1);
+        assert_eq!(reopened.load_task(work.id).unwrap().unwrap().id, work.id);
+        assert_eq!(reopened.load_all_tasks().unwrap().len(), 2);
+
+        reopened.delete_task(work.id).unwrap();
+        assert!(reopened.query_tasks(&query, None).unwrap().is_empty());
+        assert_eq!(reopened.load_all_tasks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_external_edit_rebuilds_index() {
+        use crate::storage::{FileStorageBackend, StorageBackend};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut backend = FileStorageBackend::with_path(temp_dir.path()).with_sqlite_index();
+        backend.initialize().unwrap();
+        let mut task = Task::new("Moves".to_string());
+        task.project = Some("Work".to_string());
+        backend.save_task(&task).unwrap();
+        drop(backend);
+
+        // Another client moves the task without changing the task count
+        let tasks_file = temp_dir.path().join("tasks.json");
+        let edited = std::fs::read_to_string(&tasks_file)
+            .unwrap()
+            .replace("\"Work\"", "\"Home\"");
+        std::fs::write(&tasks_file, edited).unwrap();
+
+        let mut reopened = FileStorageBackend::with_path(temp_dir.path()).with_sqlite_index();
+        reopened.initialize().unwrap();
+        let home = TaskQueryBuilderImpl::new()
+            .project("Home".to_string())
+            .build()
+            .unwrap();
+        assert_eq!(reopened.query_tasks(&home, None).unwrap().len(), 1);
+    }
+}
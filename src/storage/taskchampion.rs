@@ -5,6 +5,7 @@
 
 use crate::error::{StorageError, TaskError};
 use crate::query::TaskQuery;
+use crate::storage::lock::{DataDirLock, LockOptions};
 use crate::storage::StorageBackend;
 use crate::task::{Task, TaskStatus, Priority};
 use chrono::{DateTime, Utc};
@@ -18,6 +19,12 @@ pub struct TaskChampionStorageBackend {
     db_path: PathBuf,
     // Optional injected replica wrapper for commit operations (testable)
     replica: Option<Box<dyn crate::storage::replica_wrapper::ReplicaWrapper>>,
+    // Declared UDA types for decoding UDA values
+    uda_types: crate::task::UdaTypes,
+    // Renumber the working set before each query (Taskwarrior's `gc`)
+    gc: bool,
+    // Cross-process lock held around writes
+    locking: LockOptions,
 }
 
 impl std::fmt::Debug for TaskChampionStorageBackend {
@@ -34,9 +41,42 @@ impl TaskChampionStorageBackend {
         Self {
             db_path: db_path.into(),
             replica: None,
+            uda_types: crate::task::UdaTypes::new(),
+            gc: true,
+            locking: LockOptions::disabled(),
         }
     }
 
+    /// Hold an exclusive lock on `<database>.lock` around each write so
+    /// other instances of this library do not interleave with it, and wait
+    /// up to the same timeout for SQLite's own locks. The `task` CLI does
+    /// not take this lock; saves are kept apart from its writes by running
+    /// in one SQLite transaction. Writes fail with [`StorageError::Locked`]
+    /// if the lock stays taken.
+    pub fn with_locking(mut self, options: LockOptions) -> Self {
+        self.locking = options;
+        self
+    }
+
+    /// Renumber the working set before each query so display IDs stay dense,
+    /// as the Taskwarrior CLI does when `gc` is on (the default). Turn it off
+    /// to keep IDs stable across queries, like `rc.gc=0`.
+    pub fn with_gc(mut self, gc: bool) -> Self {
+        self.gc = gc;
+        self
+    }
+
+    /// Decode UDAs by their declared types (see [`UdaTypes::from_config`])
+    ///
+    /// [`UdaTypes::from_config`]: crate::task::UdaTypes::from_config
+    pub fn with_uda_types(mut self, types: crate::task::UdaTypes) -> Self {
+        if let Some(replica) = &mut self.replica {
+            replica.set_uda_types(types.clone());
+        }
+        self.uda_types = types;
+        self
+    }
+
     /// Create TaskChampion storage with standard path
     pub fn with_standard_path() -> Self {
         // Standard TaskChampion database location
@@ -53,18 +93,95 @@ impl TaskChampionStorageBackend {
 
     /// Open database connection
     fn open_connection(&self) -> Result<Connection, TaskError> {
-        Connection::open(&self.db_path).map_err(|e| TaskError::Storage {
+        let conn = Connection::open(&self.db_path).map_err(|e| TaskError::Storage {
             source: StorageError::Database {
                 message: format!("Failed to open TaskChampion database: {e}"),
             },
-        })
+        })?;
+        if self.locking.enabled {
+            conn.busy_timeout(self.locking.timeout).map_err(|e| TaskError::Storage {
+                source: StorageError::Database {
+                    message: format!("Failed to set busy timeout: {e}"),
+                },
+            })?;
+        }
+        Ok(conn)
+    }
+
+    /// The write lock, when locking is enabled
+    fn write_lock(&self) -> Result<Option<DataDirLock>, TaskError> {
+        if !self.locking.enabled {
+            return Ok(None);
+        }
+        DataDirLock::exclusive(&self.db_path, &self.locking).map(Some)
     }
 
     /// Inject a replica wrapper (used by tests to mock commits).
-    pub fn set_replica(&mut self, replica: Box<dyn crate::storage::replica_wrapper::ReplicaWrapper>) {
+    pub fn set_replica(&mut self, mut replica: Box<dyn crate::storage::replica_wrapper::ReplicaWrapper>) {
+        replica.set_uda_types(self.uda_types.clone());
         self.replica = Some(replica);
     }
     
+    /// Pending and recurring tasks, read from the replica's working set
+    pub fn pending_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        self.require_replica()?.pending_tasks()
+    }
+
+    /// The replica's working set as `(display ID, uuid)` pairs
+    pub fn working_set(&self) -> Result<Vec<(usize, Uuid)>, TaskError> {
+        self.require_replica()?.working_set()
+    }
+
+    /// Revert the replica to its last undo point; false when there is nothing to undo
+    pub fn undo(&mut self) -> Result<bool, TaskError> {
+        let _lock = self.write_lock()?;
+        match &mut self.replica {
+            Some(replica) => replica.undo(),
+            None => Err(Self::no_replica()),
+        }
+    }
+
+    /// Drop finished tasks from the working set, optionally renumbering the rest
+    pub fn rebuild_working_set(&self, renumber: bool) -> Result<(), TaskError> {
+        let _lock = self.write_lock()?;
+        self.require_replica()?.rebuild_working_set(renumber)
+    }
+
+    /// Number of local operations not yet synchronized
+    pub fn num_local_operations(&self) -> Result<usize, TaskError> {
+        self.require_replica()?.num_local_operations()
+    }
+
+    /// Check that the replica answers and its database is readable
+    pub fn health_check(&self) -> Result<(), TaskError> {
+        self.require_replica()?.health_check()
+    }
+
+    /// Close the replica, finishing queued work and releasing its database handle
+    pub fn close(&mut self) -> Result<(), TaskError> {
+        match self.replica.take() {
+            Some(mut replica) => replica.close(),
+            None => Ok(()),
+        }
+    }
+
+    /// Replica requests queued and not yet started
+    pub fn queue_depth(&self) -> usize {
+        self.replica.as_ref().map_or(0, |replica| replica.queue_depth())
+    }
+
+    fn require_replica(&self) -> Result<&dyn crate::storage::replica_wrapper::ReplicaWrapper, TaskError> {
+        self.replica.as_deref().ok_or_else(Self::no_replica)
+    }
+
+    fn no_replica() -> TaskError {
+        TaskError::Storage {
+            source: StorageError::Database {
+                message: "TaskChampion replica not configured: no ReplicaWrapper injected".to_string(),
+            },
+        }
+    }
+
     /// Get the last operations committed (for testing).
     pub fn get_last_operations(&self) -> Option<Vec<crate::storage::operation_batch::Operation>> {
         self.replica.as_ref()?.get_last_operations()
@@ -100,12 +217,15 @@ impl TaskChampionStorageBackend {
         };
 
         let priority_str = task_data["priority"].as_str();
-        let priority = priority_str.and_then(|p| match p {
-            "H" => Some(Priority::High),
-            "M" => Some(Priority::Medium),
-            "L" => Some(Priority::Low),
-            _ => None,
-        });
+        let priority = priority_str.and_then(Priority::from_code);
+        // Custom priority codes (from `uda.priority.values`) are kept as a UDA
+        let mut udas = HashMap::new();
+        if let Some(code) = priority_str.filter(|c| priority.is_none() && !c.is_empty()) {
+            udas.insert(
+                "priority".to_string(),
+                crate::task::model::UdaValue::String(code.to_string()),
+            );
+        }
 
         // Parse timestamps - TaskChampion uses Unix timestamps
         let entry = if let Some(entry_ts) = task_data["entry"].as_str() {
@@ -138,6 +258,41 @@ impl TaskChampionStorageBackend {
             HashSet::new()
         };
 
+        // Dependencies may be a JSON array, a comma-separated string, or
+        // individual `dep_<uuid>` keys depending on which client wrote them
+        let mut depends: HashSet<Uuid> = match &task_data["depends"] {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .filter_map(|d| d.as_str().and_then(|s| Uuid::parse_str(s).ok()))
+                .collect(),
+            serde_json::Value::String(joined) => {
+                crate::task::model::parse_depends_list(joined).unwrap_or_default()
+            }
+            _ => HashSet::new(),
+        };
+        if let Some(obj) = task_data.as_object() {
+            depends.extend(obj.keys().filter_map(|k| crate::task::model::dependency_key_uuid(k)));
+        }
+
+        if let Some(obj) = task_data.as_object() {
+            for (key, value) in obj {
+                if crate::task::uda::is_task_attribute(key) || udas.contains_key(key) {
+                    continue;
+                }
+                let decoded = match value {
+                    serde_json::Value::String(raw) => {
+                        crate::task::uda::decode_uda(raw, self.uda_types.get(key))
+                    }
+                    serde_json::Value::Number(n) => match n.as_f64() {
+                        Some(n) => crate::task::model::UdaValue::Number(n),
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                udas.insert(key.clone(), decoded);
+            }
+        }
+
         let project = task_data["project"].as_str().map(|s| s.to_string());
         let urgency = task_data["urgency"].as_f64().unwrap_or(0.0);
 
@@ -156,20 +311,30 @@ impl TaskChampionStorageBackend {
             project,
             tags,
             annotations: Vec::new(), // TODO: Parse from JSON
-            depends: HashSet::new(), // TODO: Parse from JSON
+            depends,
             urgency,
-            udas: HashMap::new(),    // TODO: Parse UDAs from JSON
+            udas,
             recur: None,             // TODO: Add recurrence support
             parent: None,
             mask: None,
             active: false, // TODO: Check if task is started
             start: None,   // TODO: Add start time
+            owner: task_data["owner"].as_str().map(|s| s.to_string()),
         })
     }
 }
 
 impl StorageBackend for TaskChampionStorageBackend {
+    fn backend_name(&self) -> &str {
+        "taskchampion"
+    }
+
     fn initialize(&mut self) -> Result<(), TaskError> {
+        // An injected replica opens (or creates) the database itself
+        if self.replica.is_some() {
+            return Ok(());
+        }
+
         // Check if database file exists
         if !self.db_path.exists() {
             return Err(TaskError::Storage {
@@ -188,23 +353,13 @@ impl StorageBackend for TaskChampionStorageBackend {
     }
 
     fn save_task(&mut self, _task: &Task) -> Result<(), TaskError> {
-        // Build operation batch for the task
-        use crate::storage::operation_batch::build_save_batch;
-
-        // If a replica wrapper is injected (tests), avoid touching the DB schema
-        // and assume no existing task unless we can read it via the replica.
-        let existing = if let Some(replica) = &self.replica {
-            // Use replica's read_task if available
-            replica.read_task(_task.id).unwrap_or_default()
-        } else {
-            self.load_task(_task.id)?
-        };
-
-        let ops = build_save_batch(existing.as_ref(), _task);
+        // The replica reads the stored task, diffs it and writes the changes
+        // in one SQLite transaction; the lock keeps other instances of this
+        // library out for the whole save
+        let _lock = self.write_lock()?;
 
         if let Some(replica) = &mut self.replica {
-            // The replica wrapper now handles translation to TaskChampion operations internally
-            replica.commit_operations(&ops).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to commit operations: {e}") } })?;
+            replica.save_task(_task).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to commit operations: {e}") } })?;
             Ok(())
         } else {
             Err(TaskError::Storage {
@@ -216,6 +371,10 @@ impl StorageBackend for TaskChampionStorageBackend {
     }
 
     fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        if let Some(replica) = &self.replica {
+            return replica.read_task(id);
+        }
+
         let conn = self.open_connection()?;
         
         let mut stmt = conn.prepare(
@@ -242,6 +401,25 @@ impl StorageBackend for TaskChampionStorageBackend {
 
         let ops = build_delete_batch(_id);
 
+        let _lock = self.write_lock()?;
+        if let Some(replica) = &mut self.replica {
+            replica.commit_operations(&ops).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to commit operations: {e}") } })?;
+            Ok(())
+        } else {
+            Err(TaskError::Storage {
+                source: StorageError::Database {
+                    message: "TaskChampion write path not configured: no ReplicaWrapper injected".to_string(),
+                },
+            })
+        }
+    }
+
+    fn purge_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        use crate::storage::operation_batch::build_purge_batch;
+
+        let ops = build_purge_batch(id);
+
+        let _lock = self.write_lock()?;
         if let Some(replica) = &mut self.replica {
             replica.commit_operations(&ops).map_err(|e| TaskError::Storage { source: StorageError::Database { message: format!("Failed to commit operations: {e}") } })?;
             Ok(())
@@ -255,6 +433,10 @@ impl StorageBackend for TaskChampionStorageBackend {
     }
 
     fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        if let Some(replica) = &self.replica {
+            return replica.all_tasks();
+        }
+
         let conn = self.open_connection()?;
         
         let mut stmt = conn.prepare(
@@ -292,7 +474,18 @@ impl StorageBackend for TaskChampionStorageBackend {
         query: &TaskQuery,
         active_context: Option<&crate::config::context::UserContext>,
     ) -> Result<Vec<Task>, TaskError> {
-        let mut tasks = self.load_all_tasks()?;
+        if self.gc {
+            if let Some(replica) = &self.replica {
+                let _lock = self.write_lock()?;
+                replica.rebuild_working_set(true)?;
+            }
+        }
+
+        // Pending tasks are exactly the working set, so skip the full scan
+        let mut tasks = match (&self.replica, &query.status) {
+            (Some(replica), Some(TaskStatus::Pending)) => replica.pending_tasks()?,
+            _ => self.load_all_tasks()?,
+        };
 
         // Apply filters (simplified implementation)
         tasks.retain(|task| {
@@ -307,15 +500,55 @@ impl StorageBackend for TaskChampionStorageBackend {
             if let Some(project_filter) = &query.project_filter {
                 use crate::query::ProjectFilter;
                 match project_filter {
-                    ProjectFilter::Equals(project) | ProjectFilter::Exact(project) => {
-                        if task.project.as_ref() != Some(project) {
-                            return false;
-                        }
+                    ProjectFilter::Equals(project) | ProjectFilter::Exact(project)
+                        if task.project.as_ref() != Some(project) =>
+                    {
+                        return false;
                     }
                     _ => {} // TODO: Implement other project filters
                 }
             }
 
+            // Tag filter
+            if let Some(tag_filter) = &query.tag_filter {
+                if !tag_filter.matches(&task.tags) {
+                    return false;
+                }
+            }
+
+            // Date filter
+            if let Some(date_filter) = &query.date_filter {
+                if !date_filter.matches(task) {
+                    return false;
+                }
+            }
+
+            // Priority filter
+            if let Some(priority_filter) = &query.priority_filter {
+                if !priority_filter.matches(task.priority_code()) {
+                    return false;
+                }
+            }
+
+            // Owner filter
+            if let Some(owner_filter) = &query.owner_filter {
+                if !owner_filter.matches(task.owner.as_deref()) {
+                    return false;
+                }
+            }
+
+            // Proximity filter
+            if let Some(proximity) = &query.proximity {
+                if !proximity.matches(task) {
+                    return false;
+                }
+            }
+
+            // Custom predicates are evaluated in memory
+            if !query.matches_custom(task) {
+                return false;
+            }
+
             // Active context (AND) unless explicitly ignored
             if let Some(ctx) = active_context {
                 use crate::query::FilterMode;
@@ -331,6 +564,7 @@ impl StorageBackend for TaskChampionStorageBackend {
 
             true
         });
+        query.apply_sort(&mut tasks);
 
         // Apply pagination
         let start = query.offset.unwrap_or(0);
@@ -339,6 +573,49 @@ impl StorageBackend for TaskChampionStorageBackend {
         Ok(tasks.into_iter().skip(start).take(end - start).collect())
     }
 
+    fn verify(&self) -> Result<crate::storage::IntegrityReport, TaskError> {
+        use crate::storage::{IntegrityIssue, IntegrityReport};
+
+        let conn = self.open_connection()?;
+        let mut report = IntegrityReport::default();
+        let db_error = |e: rusqlite::Error| TaskError::Storage {
+            source: StorageError::Database {
+                message: format!("Failed to verify database: {e}"),
+            },
+        };
+
+        let mut stmt = conn.prepare("PRAGMA integrity_check").map_err(db_error)?;
+        let results = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+        report.issues.extend(
+            results
+                .into_iter()
+                .filter(|message| message != "ok")
+                .map(|message| IntegrityIssue::Database { message }),
+        );
+
+        let mut stmt = conn.prepare("SELECT uuid, data FROM tasks").map_err(db_error)?;
+        let mut rows = stmt.query([]).map_err(db_error)?;
+        while let Some(row) = rows.next().map_err(db_error)? {
+            report.tasks_checked += 1;
+            if let Err(e) = self.row_to_task(row) {
+                let id = row
+                    .get::<_, String>("uuid")
+                    .ok()
+                    .and_then(|u| Uuid::parse_str(&u).ok());
+                report.issues.push(IntegrityIssue::UnreadableTask {
+                    id,
+                    message: e.to_string(),
+                });
+            }
+        }
+
+        Ok(report)
+    }
+
     fn backup(&self) -> Result<String, StorageError> {
         Err(StorageError::Database {
             message: "Backup not supported for TaskChampion backend".to_string(),
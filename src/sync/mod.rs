@@ -5,6 +5,11 @@
 
 pub mod replica;
 pub mod helpers;
+mod taskchampion;
+#[cfg(feature = "taskd")]
+pub mod taskd;
+
+pub use taskchampion::TaskChampionSyncManager;
 
 use crate::error::{SyncError, TaskError};
 use crate::task::Task;
@@ -29,6 +34,44 @@ pub trait SyncManager: std::fmt::Debug {
 
     /// Get sync status
     fn status(&self) -> SyncStatus;
+
+    /// Sync and upload a snapshot of the full task database, so new
+    /// replicas can start from it instead of replaying every change
+    fn create_snapshot(&mut self) -> Result<(), SyncError> {
+        Err(SyncError::Protocol {
+            message: "Snapshots are not supported by this sync manager".to_string(),
+        })
+    }
+}
+
+/// Where a TaskChampion replica syncs to
+#[derive(Clone, PartialEq, Eq)]
+pub enum SyncServerConfig {
+    /// A server database in a local directory, for a single machine
+    Local { server_dir: std::path::PathBuf },
+    /// A taskchampion-sync-server instance
+    Remote {
+        url: String,
+        client_id: uuid::Uuid,
+        encryption_secret: Vec<u8>,
+    },
+}
+
+// Keep the encryption secret out of logs
+impl std::fmt::Debug for SyncServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncServerConfig::Local { server_dir } => {
+                f.debug_struct("Local").field("server_dir", server_dir).finish()
+            }
+            SyncServerConfig::Remote { url, client_id, .. } => f
+                .debug_struct("Remote")
+                .field("url", url)
+                .field("client_id", client_id)
+                .field("encryption_secret", &"<redacted>")
+                .finish(),
+        }
+    }
 }
 
 /// Sync status information
@@ -0,0 +1,135 @@
+//! Sync through a TaskChampion replica
+//!
+//! TaskChampion syncs operations, not whole tasks: local changes are pushed
+//! as versions and remote versions are replayed on top of them, so
+//! conflicting edits are merged property by property. A new replica starts
+//! from the server's latest snapshot and only replays the versions after it.
+
+use std::path::Path;
+
+use crate::error::{SyncError, TaskError};
+use crate::storage::operation_batch::build_save_batch;
+use crate::storage::replica_taskchampion::open_taskchampion_replica;
+use crate::storage::replica_wrapper::ReplicaWrapper;
+use crate::sync::{SyncManager, SyncServerConfig, SyncStatus};
+use crate::task::Task;
+
+/// [`SyncManager`] that syncs a TaskChampion replica with a sync server
+pub struct TaskChampionSyncManager {
+    replica: Box<dyn ReplicaWrapper>,
+    server: SyncServerConfig,
+    last_sync: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl std::fmt::Debug for TaskChampionSyncManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskChampionSyncManager")
+            .field("server", &self.server)
+            .field("last_sync", &self.last_sync)
+            .finish()
+    }
+}
+
+impl TaskChampionSyncManager {
+    /// Sync `replica` with `server`
+    pub fn new(replica: Box<dyn ReplicaWrapper>, server: SyncServerConfig) -> Self {
+        Self {
+            replica,
+            server,
+            last_sync: None,
+        }
+    }
+
+    /// Set up a new replica in `data_dir` from the server's latest snapshot
+    /// plus the changes made since. Much faster than replaying the whole
+    /// history on a first sync. Fails if the replica already holds tasks.
+    pub fn bootstrap(data_dir: &Path, server: SyncServerConfig) -> Result<Self, SyncError> {
+        let replica = open_taskchampion_replica(data_dir).map_err(sync_error)?;
+        let is_empty = replica.all_tasks().map_err(sync_error)?.is_empty()
+            && replica.num_local_operations().map_err(sync_error)? == 0;
+        if !is_empty {
+            return Err(SyncError::Conflict {
+                message: format!("Replica at {} is not empty", data_dir.display()),
+            });
+        }
+
+        let mut manager = Self::new(replica, server);
+        manager.sync(true)?;
+        Ok(manager)
+    }
+
+    /// The replica being synced
+    pub fn replica(&self) -> &dyn ReplicaWrapper {
+        self.replica.as_ref()
+    }
+
+    fn sync(&mut self, avoid_snapshots: bool) -> Result<(), SyncError> {
+        self.replica
+            .sync(&self.server, avoid_snapshots)
+            .map_err(sync_error)?;
+        self.last_sync = Some(crate::clock::now());
+        Ok(())
+    }
+}
+
+fn sync_error(e: TaskError) -> SyncError {
+    SyncError::Network {
+        message: e.to_string(),
+    }
+}
+
+impl SyncManager for TaskChampionSyncManager {
+    /// Sync the replica. Tasks are read from the replica, so `tasks` is
+    /// ignored; the pushed count is the number of local operations sent.
+    fn synchronize(&mut self, _tasks: &[Task]) -> Result<(usize, usize, usize), TaskError> {
+        let pushed = self.replica.num_local_operations()?;
+        self.sync(true)
+            .map_err(|e| TaskError::Sync { message: e.to_string() })?;
+        Ok((0, pushed, 0))
+    }
+
+    fn pull(&mut self) -> Result<Vec<Task>, SyncError> {
+        self.sync(true)?;
+        self.replica.all_tasks().map_err(sync_error)
+    }
+
+    fn push(&mut self, tasks: &[Task]) -> Result<usize, SyncError> {
+        for task in tasks {
+            let existing = self.replica.read_task(task.id).map_err(sync_error)?;
+            let ops = build_save_batch(existing.as_ref(), task);
+            self.replica.commit_operations(&ops).map_err(sync_error)?;
+        }
+        self.sync(true)?;
+        Ok(tasks.len())
+    }
+
+    /// TaskChampion merges concurrent edits during sync; what is left to
+    /// resolve here goes to the remote side
+    fn resolve_conflicts(&mut self, conflicts: &[(Task, Task)]) -> Result<Vec<Task>, SyncError> {
+        Ok(conflicts.iter().map(|(_, remote)| remote.clone()).collect())
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    fn status(&self) -> SyncStatus {
+        SyncStatus {
+            last_sync: self.last_sync,
+            server_url: match &self.server {
+                SyncServerConfig::Local { server_dir } => {
+                    Some(format!("file://{}", server_dir.display()))
+                }
+                SyncServerConfig::Remote { url, .. } => Some(url.clone()),
+            },
+            is_connected: self.last_sync.is_some(),
+            pending_changes: self.replica.num_local_operations().unwrap_or(0),
+        }
+    }
+
+    /// Sync, offering the server a snapshot. TaskChampion only uploads one
+    /// when the server asks for it; a local server never keeps snapshots.
+    fn create_snapshot(&mut self) -> Result<(), SyncError> {
+        self.sync(false)
+    }
+}
@@ -0,0 +1,874 @@
+//! Sync with a Taskwarrior 2 taskserver (taskd)
+//!
+//! The taskserver protocol sends one message per TLS connection: a 4-byte
+//! big-endian length (counting itself), `name: value` header lines, a blank
+//! line and a payload. A sync request carries the credentials in its
+//! headers and, as payload, the sync key from the previous sync followed by
+//! every task changed since then as one JSON object per line. The server
+//! merges them and answers with the tasks other clients changed plus a new
+//! sync key.
+//!
+//! Settings come from the taskrc as Taskwarrior 2 reads them: `taskd.server`
+//! (`host:port`), `taskd.credentials` (`org/user/key`), `taskd.certificate`,
+//! `taskd.key`, `taskd.ca` and `taskd.trust`. The sync key and the time of
+//! the last sync are kept in [`STATE_FILE`] in the data directory.
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore,
+    SignatureScheme, StreamOwned,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::clock;
+use crate::config::discovery::expand_home;
+use crate::config::Configuration;
+use crate::error::{ConfigError, SyncError, TaskError};
+use crate::io::profile::SerializationProfile;
+use crate::storage::StorageBackend;
+use crate::sync::{SyncManager, SyncStatus};
+use crate::task::Task;
+
+/// Sync state kept in the data directory
+pub const STATE_FILE: &str = "taskd.sync.json";
+
+/// Largest response accepted from the server
+const MAX_MESSAGE: usize = 100 * 1024 * 1024;
+
+/// Network timeout for connecting, reading and writing
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Account on the taskserver, from `taskd.credentials`
+#[derive(Clone, PartialEq, Eq)]
+pub struct TaskdCredentials {
+    pub org: String,
+    pub user: String,
+    pub key: String,
+}
+
+// Keep the user key out of logs
+impl std::fmt::Debug for TaskdCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskdCredentials")
+            .field("org", &self.org)
+            .field("user", &self.user)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl TaskdCredentials {
+    /// Parse `org/user/key`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.splitn(3, '/');
+        let (org, user, key) = (parts.next()?, parts.next()?, parts.next()?);
+        if org.is_empty() || user.is_empty() || key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            org: org.to_string(),
+            user: user.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+/// How the server certificate is checked, from `taskd.trust`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskdTrust {
+    /// Verify the certificate chain and the host name
+    #[default]
+    Strict,
+    /// Verify the certificate chain only
+    IgnoreHostname,
+    /// Accept any certificate
+    AllowAll,
+}
+
+impl TaskdTrust {
+    /// Parse `strict`, `ignore hostname` or `allow all`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "strict" => Some(TaskdTrust::Strict),
+            "ignore hostname" => Some(TaskdTrust::IgnoreHostname),
+            "allow all" => Some(TaskdTrust::AllowAll),
+            _ => None,
+        }
+    }
+}
+
+/// Connection settings for a taskserver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskdConfig {
+    /// `host:port`
+    pub server: String,
+    pub credentials: TaskdCredentials,
+    /// Client certificate (PEM)
+    pub certificate: PathBuf,
+    /// Client private key (PEM)
+    pub key: PathBuf,
+    /// CA certificate the server's certificate is checked against (PEM)
+    pub ca: Option<PathBuf>,
+    pub trust: TaskdTrust,
+}
+
+impl TaskdConfig {
+    /// Read the `taskd.*` settings
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let required = |key: &str| {
+            config
+                .get(key)
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| v.trim().to_string())
+                .ok_or_else(|| ConfigError::MissingRequired {
+                    key: key.to_string(),
+                })
+        };
+
+        let server = required("taskd.server")?;
+        if server
+            .rsplit_once(':')
+            .and_then(|(_, p)| p.parse::<u16>().ok())
+            .is_none()
+        {
+            return Err(ConfigError::InvalidValue {
+                key: "taskd.server".to_string(),
+                value: server,
+                expected: "host:port".to_string(),
+            });
+        }
+        let credentials_value = required("taskd.credentials")?;
+        let credentials = TaskdCredentials::parse(&credentials_value).ok_or_else(|| {
+            ConfigError::InvalidValue {
+                key: "taskd.credentials".to_string(),
+                value: credentials_value.clone(),
+                expected: "org/user/key".to_string(),
+            }
+        })?;
+        let trust = match config.get("taskd.trust") {
+            None => TaskdTrust::default(),
+            Some(value) => TaskdTrust::parse(value).ok_or_else(|| ConfigError::InvalidValue {
+                key: "taskd.trust".to_string(),
+                value: value.clone(),
+                expected: "strict, ignore hostname or allow all".to_string(),
+            })?,
+        };
+
+        Ok(Self {
+            server,
+            credentials,
+            certificate: expand_home(&required("taskd.certificate")?),
+            key: expand_home(&required("taskd.key")?),
+            ca: config
+                .get("taskd.ca")
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| expand_home(v.trim())),
+            trust,
+        })
+    }
+}
+
+/// One protocol message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskdMessage {
+    pub headers: Vec<(String, String)>,
+    pub payload: String,
+}
+
+impl TaskdMessage {
+    /// Value of header `name`
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Encode with the length prefix
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = String::new();
+        for (name, value) in &self.headers {
+            body.push_str(&format!("{name}: {value}\n"));
+        }
+        body.push('\n');
+        body.push_str(&self.payload);
+
+        let mut bytes = ((body.len() + 4) as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+
+    /// Read one length-prefixed message
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, SyncError> {
+        let mut prefix = [0u8; 4];
+        reader.read_exact(&mut prefix).map_err(network_error)?;
+        let len = u32::from_be_bytes(prefix) as usize;
+        if !(4..=MAX_MESSAGE).contains(&len) {
+            return Err(protocol_error(format!("Invalid message length {len}")));
+        }
+        let mut body = vec![0u8; len - 4];
+        reader.read_exact(&mut body).map_err(network_error)?;
+        let body = String::from_utf8(body)
+            .map_err(|_| protocol_error("Message is not UTF-8".to_string()))?;
+
+        let (head, payload) = body.split_once("\n\n").unwrap_or((body.as_str(), ""));
+        let headers = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Ok(Self {
+            headers,
+            payload: payload.to_string(),
+        })
+    }
+}
+
+/// Sends a request to the taskserver and returns its response
+pub trait TaskdTransport: std::fmt::Debug {
+    fn exchange(&mut self, request: &TaskdMessage) -> Result<TaskdMessage, SyncError>;
+}
+
+/// [`TaskdTransport`] opening a TLS connection for every request
+pub struct TlsTransport {
+    server: String,
+    tls: Arc<ClientConfig>,
+}
+
+impl std::fmt::Debug for TlsTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsTransport")
+            .field("server", &self.server)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TlsTransport {
+    /// Load the certificates and key named in `config`
+    pub fn new(config: &TaskdConfig) -> Result<Self, SyncError> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let certs = CertificateDer::pem_file_iter(&config.certificate)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| pem_error(&config.certificate, e))?;
+        let key =
+            PrivateKeyDer::from_pem_file(&config.key).map_err(|e| pem_error(&config.key, e))?;
+
+        let verifier: Arc<dyn ServerCertVerifier> = match (&config.ca, config.trust) {
+            (_, TaskdTrust::AllowAll) => Arc::new(LenientVerifier {
+                inner: None,
+                provider: Arc::clone(&provider),
+            }),
+            (Some(ca), trust) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_file_iter(ca).map_err(|e| pem_error(ca, e))? {
+                    roots
+                        .add(cert.map_err(|e| pem_error(ca, e))?)
+                        .map_err(tls_error)?;
+                }
+                let webpki = WebPkiServerVerifier::builder_with_provider(
+                    Arc::new(roots),
+                    Arc::clone(&provider),
+                )
+                .build()
+                .map_err(|e| protocol_error(e.to_string()))?;
+                if trust == TaskdTrust::Strict {
+                    webpki
+                } else {
+                    Arc::new(LenientVerifier {
+                        inner: Some(webpki),
+                        provider: Arc::clone(&provider),
+                    })
+                }
+            }
+            (None, _) => {
+                return Err(SyncError::Authentication {
+                    message: "taskd.ca is required unless taskd.trust is 'allow all'".to_string(),
+                })
+            }
+        };
+
+        let tls = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier)
+            .with_client_auth_cert(certs, key)
+            .map_err(tls_error)?;
+        Ok(Self {
+            server: config.server.clone(),
+            tls: Arc::new(tls),
+        })
+    }
+}
+
+impl TaskdTransport for TlsTransport {
+    fn exchange(&mut self, request: &TaskdMessage) -> Result<TaskdMessage, SyncError> {
+        let (host, _) = self
+            .server
+            .rsplit_once(':')
+            .ok_or_else(|| protocol_error(format!("Invalid server '{}'", self.server)))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let name =
+            ServerName::try_from(host.to_string()).map_err(|e| protocol_error(e.to_string()))?;
+
+        let tcp = TcpStream::connect(&self.server).map_err(network_error)?;
+        tcp.set_read_timeout(Some(TIMEOUT)).map_err(network_error)?;
+        tcp.set_write_timeout(Some(TIMEOUT))
+            .map_err(network_error)?;
+        let connection = ClientConnection::new(Arc::clone(&self.tls), name).map_err(tls_error)?;
+        let mut stream = StreamOwned::new(connection, tcp);
+
+        stream.write_all(&request.encode()).map_err(network_error)?;
+        stream.flush().map_err(network_error)?;
+        TaskdMessage::read_from(&mut stream)
+    }
+}
+
+/// Certificate check for `taskd.trust` other than strict: with `inner`,
+/// the chain is verified but a host name mismatch is accepted; without it
+/// any certificate is. Handshake signatures are always verified.
+#[derive(Debug)]
+struct LenientVerifier {
+    inner: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for LenientVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let Some(inner) = &self.inner else {
+            return Ok(ServerCertVerified::assertion());
+        };
+        match inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+/// Persisted between syncs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SyncState {
+    sync_key: Option<String>,
+    last_sync: Option<DateTime<Utc>>,
+}
+
+/// [`SyncManager`] for a Taskwarrior 2 taskserver
+///
+/// Tasks received from the server are saved to `storage`, which should be
+/// the backend the task manager uses, since
+/// [`SyncManager::synchronize`] has no way to hand them back.
+pub struct TaskdSyncManager {
+    transport: Box<dyn TaskdTransport>,
+    server: String,
+    credentials: TaskdCredentials,
+    storage: Box<dyn StorageBackend>,
+    state_file: Option<PathBuf>,
+    state: SyncState,
+}
+
+impl std::fmt::Debug for TaskdSyncManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskdSyncManager")
+            .field("server", &self.server)
+            .field("credentials", &self.credentials)
+            .field("last_sync", &self.state.last_sync)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TaskdSyncManager {
+    /// Sync through `transport`, saving received tasks to `storage`. The
+    /// sync state is only kept in memory; see [`Self::with_state_file`].
+    pub fn new(
+        config: &TaskdConfig,
+        transport: Box<dyn TaskdTransport>,
+        storage: Box<dyn StorageBackend>,
+    ) -> Self {
+        Self {
+            transport,
+            server: config.server.clone(),
+            credentials: config.credentials.clone(),
+            storage,
+            state_file: None,
+            state: SyncState::default(),
+        }
+    }
+
+    /// Sync over TLS with the server from the taskrc, keeping the sync
+    /// state in the data directory
+    pub fn from_config(
+        config: &Configuration,
+        storage: Box<dyn StorageBackend>,
+    ) -> Result<Self, TaskError> {
+        let taskd = TaskdConfig::from_config(config)
+            .map_err(|source| TaskError::Configuration { source })?;
+        let transport = TlsTransport::new(&taskd).map_err(|e| TaskError::Sync {
+            message: e.to_string(),
+        })?;
+        Self::new(&taskd, Box::new(transport), storage)
+            .with_state_file(config.data_dir.join(STATE_FILE))
+    }
+
+    /// Load and save the sync key and last sync time at `path`
+    pub fn with_state_file<P: Into<PathBuf>>(mut self, path: P) -> Result<Self, TaskError> {
+        let path = path.into();
+        self.state = match std::fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SyncState::default(),
+            Err(e) => return Err(TaskError::Io(e)),
+        };
+        self.state_file = Some(path);
+        Ok(self)
+    }
+
+    /// Key the server handed out on the last sync
+    pub fn sync_key(&self) -> Option<&str> {
+        self.state.sync_key.as_deref()
+    }
+
+    /// Upload `tasks` and save what the server sends back. Returns the
+    /// received tasks.
+    fn sync(&mut self, tasks: &[&Task]) -> Result<Vec<Task>, SyncError> {
+        let mut payload = String::new();
+        if let Some(key) = &self.state.sync_key {
+            payload.push_str(key);
+            payload.push('\n');
+        }
+        for task in tasks {
+            payload.push_str(&to_wire(task)?.to_string());
+            payload.push('\n');
+        }
+        let request = TaskdMessage {
+            headers: vec![
+                (
+                    "client".to_string(),
+                    format!("taskwarrior3lib {}", env!("CARGO_PKG_VERSION")),
+                ),
+                ("protocol".to_string(), "v1".to_string()),
+                ("type".to_string(), "sync".to_string()),
+                ("org".to_string(), self.credentials.org.clone()),
+                ("user".to_string(), self.credentials.user.clone()),
+                ("key".to_string(), self.credentials.key.clone()),
+            ],
+            payload,
+        };
+
+        // Changes made while the exchange is in flight are not in this
+        // upload, so they must still count as pending afterwards
+        let started = clock::now();
+        let response = self.transport.exchange(&request)?;
+        check_status(&response)?;
+
+        let mut received = Vec::new();
+        for line in response
+            .payload
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+        {
+            if line.starts_with('{') {
+                let value: Value =
+                    serde_json::from_str(line).map_err(|e| protocol_error(e.to_string()))?;
+                received.push(from_wire(value)?);
+            } else if Uuid::parse_str(line).is_ok() {
+                self.state.sync_key = Some(line.to_string());
+            }
+        }
+        for task in &received {
+            self.storage.save_task(task).map_err(storage_error)?;
+        }
+        self.state.last_sync = Some(started);
+        self.save_state()?;
+        Ok(received)
+    }
+
+    // Write the state atomically so a crash never loses the sync key
+    fn save_state(&self) -> Result<(), SyncError> {
+        let Some(path) = &self.state_file else {
+            return Ok(());
+        };
+        let data =
+            serde_json::to_string_pretty(&self.state).map_err(|e| protocol_error(e.to_string()))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(data.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| storage_error(TaskError::Io(e)))
+    }
+
+    /// Whether `task` changed since the last sync
+    fn is_pending(&self, task: &Task) -> bool {
+        match self.state.last_sync {
+            None => true,
+            Some(last) => task.modified.unwrap_or(task.entry) > last,
+        }
+    }
+}
+
+impl SyncManager for TaskdSyncManager {
+    /// Upload the tasks changed since the last sync and save the server's
+    /// changes. Tasks sent and received in the same sync were merged by the
+    /// server and count as resolved conflicts.
+    fn synchronize(&mut self, tasks: &[Task]) -> Result<(usize, usize, usize), TaskError> {
+        let pending: Vec<&Task> = tasks.iter().filter(|t| self.is_pending(t)).collect();
+        let received = self.sync(&pending).map_err(|e| TaskError::Sync {
+            message: e.to_string(),
+        })?;
+        let conflicts = received
+            .iter()
+            .filter(|r| pending.iter().any(|p| p.id == r.id))
+            .count();
+        Ok((received.len(), pending.len(), conflicts))
+    }
+
+    fn pull(&mut self) -> Result<Vec<Task>, SyncError> {
+        self.sync(&[])
+    }
+
+    fn push(&mut self, tasks: &[Task]) -> Result<usize, SyncError> {
+        let tasks: Vec<&Task> = tasks.iter().collect();
+        self.sync(&tasks)?;
+        Ok(tasks.len())
+    }
+
+    /// The server merges concurrent edits; its version wins
+    fn resolve_conflicts(&mut self, conflicts: &[(Task, Task)]) -> Result<Vec<Task>, SyncError> {
+        Ok(conflicts.iter().map(|(_, remote)| remote.clone()).collect())
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    fn status(&self) -> SyncStatus {
+        let pending_changes = self
+            .storage
+            .load_all_tasks()
+            .map(|tasks| tasks.iter().filter(|t| self.is_pending(t)).count())
+            .unwrap_or(0);
+        SyncStatus {
+            last_sync: self.state.last_sync,
+            server_url: Some(format!("taskd://{}", self.server)),
+            is_connected: self.state.last_sync.is_some(),
+            pending_changes,
+        }
+    }
+}
+
+/// Turn a response with an error code into the matching error
+fn check_status(response: &TaskdMessage) -> Result<(), SyncError> {
+    let code: u32 = response
+        .header("code")
+        .and_then(|c| c.parse().ok())
+        .ok_or_else(|| protocol_error("Response has no status code".to_string()))?;
+    let message = format!("{code} {}", response.header("status").unwrap_or_default())
+        .trim_end()
+        .to_string();
+    match code {
+        200..=299 => Ok(()),
+        430..=432 => Err(SyncError::Authentication { message }),
+        420 | 421 | 302 => Err(SyncError::Network { message }),
+        _ => Err(SyncError::Protocol { message }),
+    }
+}
+
+/// The taskserver's task JSON: the `task` CLI dialect without computed
+/// attributes
+fn wire_profile() -> SerializationProfile {
+    SerializationProfile {
+        include_urgency: false,
+        ..SerializationProfile::taskwarrior()
+    }
+}
+
+/// Task JSON as the taskserver stores it: compact dates, `recur` as its
+/// pattern and no computed attributes
+pub fn to_wire(task: &Task) -> Result<Value, SyncError> {
+    let mut value = wire_profile()
+        .to_value(task)
+        .map_err(|e| protocol_error(e.to_string()))?;
+    if let Value::Object(map) = &mut value {
+        map.remove("id");
+    }
+    Ok(value)
+}
+
+/// Parse a task as sent by the taskserver
+pub fn from_wire(value: Value) -> Result<Task, SyncError> {
+    wire_profile()
+        .from_value(value)
+        .map_err(|e| protocol_error(format!("Invalid task from server: {e}")))
+}
+
+fn network_error(e: std::io::Error) -> SyncError {
+    SyncError::Network {
+        message: e.to_string(),
+    }
+}
+
+fn protocol_error(message: String) -> SyncError {
+    SyncError::Protocol { message }
+}
+
+fn tls_error(e: rustls::Error) -> SyncError {
+    SyncError::Network {
+        message: format!("TLS error: {e}"),
+    }
+}
+
+fn pem_error(path: &Path, e: rustls::pki_types::pem::Error) -> SyncError {
+    SyncError::Authentication {
+        message: format!("Cannot read {}: {e}", path.display()),
+    }
+}
+
+fn storage_error(e: TaskError) -> SyncError {
+    SyncError::Protocol {
+        message: format!("Failed to save synced task: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigSource;
+    use crate::storage::FileStorageBackend;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    /// Answers with scripted responses and records the requests
+    #[derive(Debug, Clone, Default)]
+    struct ScriptedTransport {
+        requests: Arc<Mutex<Vec<TaskdMessage>>>,
+        responses: Arc<Mutex<Vec<TaskdMessage>>>,
+    }
+
+    impl TaskdTransport for ScriptedTransport {
+        fn exchange(&mut self, request: &TaskdMessage) -> Result<TaskdMessage, SyncError> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(self.responses.lock().unwrap().remove(0))
+        }
+    }
+
+    fn response(code: &str, payload: &str) -> TaskdMessage {
+        TaskdMessage {
+            headers: vec![
+                ("code".to_string(), code.to_string()),
+                ("status".to_string(), "Ok".to_string()),
+            ],
+            payload: payload.to_string(),
+        }
+    }
+
+    fn config() -> TaskdConfig {
+        let mut config = Configuration::default();
+        for (key, value) in [
+            ("taskd.server", "taskd.example.com:53589"),
+            ("taskd.credentials", "Public/alice/1a2b3c"),
+            ("taskd.certificate", "/keys/alice.cert.pem"),
+            ("taskd.key", "/keys/alice.key.pem"),
+            ("taskd.trust", "ignore hostname"),
+        ] {
+            config.insert(key, value, ConfigSource::Default);
+        }
+        TaskdConfig::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_config_and_message_framing() {
+        let config = config();
+        assert_eq!(config.credentials.user, "alice");
+        assert_eq!(config.trust, TaskdTrust::IgnoreHostname);
+        assert_eq!(config.ca, None);
+        assert!(!format!("{:?}", config.credentials).contains("1a2b3c"));
+
+        let message = response("200", "{\"a\":1}\n");
+        let bytes = message.encode();
+        assert_eq!(&bytes[..4], &(bytes.len() as u32).to_be_bytes());
+        assert!(bytes[4..].starts_with(b"code: 200\nstatus: Ok\n\n"));
+        assert_eq!(
+            TaskdMessage::read_from(&mut bytes.as_slice()).unwrap(),
+            message
+        );
+        assert!(TaskdMessage::read_from(&mut [0u8, 0, 0, 2].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_wire_format_round_trip() {
+        let mut task = Task::new("Pay rent".to_string());
+        task.entry = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+        task.recur = Some(crate::task::recurrence::RecurrencePattern::new(
+            "monthly".to_string(),
+        ));
+        task.annotations
+            .push(crate::task::annotation::Annotation::with_timestamp(
+                "landlord".to_string(),
+                task.entry,
+            ));
+
+        let wire = to_wire(&task).unwrap();
+        assert_eq!(wire["entry"], "20250301T090000Z");
+        assert_eq!(wire["annotations"][0]["entry"], "20250301T090000Z");
+        assert_eq!(wire["recur"], "monthly");
+        assert!(wire.get("urgency").is_none());
+        assert_eq!(from_wire(wire).unwrap(), task);
+    }
+
+    #[test]
+    fn test_sync_exchanges_changes_and_keeps_sync_key() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(temp.path());
+        storage.initialize().unwrap();
+        let transport = ScriptedTransport::default();
+
+        let remote = Task::new("From another client".to_string());
+        let key = Uuid::new_v4().to_string();
+        let remote_line = to_wire(&remote).unwrap().to_string();
+        transport
+            .responses
+            .lock()
+            .unwrap()
+            .push(response("200", &format!("{remote_line}\n{key}\n")));
+        transport
+            .responses
+            .lock()
+            .unwrap()
+            .push(response("201", &format!("{key}\n")));
+        transport
+            .responses
+            .lock()
+            .unwrap()
+            .push(response("430", ""));
+
+        let state_file = temp.path().join(STATE_FILE);
+        let mut manager =
+            TaskdSyncManager::new(&config(), Box::new(transport.clone()), Box::new(storage))
+                .with_state_file(&state_file)
+                .unwrap();
+        let local = Task::new("Local".to_string());
+        assert_eq!(manager.synchronize(std::slice::from_ref(&local)).unwrap(), (1, 1, 0));
+        assert_eq!(manager.sync_key(), Some(key.as_str()));
+        assert_eq!(manager.status().pending_changes, 0);
+
+        // Nothing changed since, so nothing is uploaded
+        assert_eq!(manager.synchronize(&[local]).unwrap(), (0, 0, 0));
+        let requests = transport.requests.lock().unwrap().clone();
+        assert_eq!(requests[0].header("type"), Some("sync"));
+        assert_eq!(requests[0].header("org"), Some("Public"));
+        assert_eq!(requests[0].payload.lines().count(), 1);
+        assert_eq!(requests[1].payload, format!("{key}\n"));
+
+        let reloaded = FileStorageBackend::with_path(temp.path());
+        assert!(reloaded.load_task(remote.id).unwrap().is_some());
+        let restored = TaskdSyncManager::new(&config(), Box::new(transport), Box::new(reloaded))
+            .with_state_file(&state_file)
+            .unwrap();
+        assert_eq!(restored.sync_key(), Some(key.as_str()));
+
+        assert!(matches!(
+            manager.pull(),
+            Err(SyncError::Authentication { .. })
+        ));
+    }
+
+    /// Takes five seconds of clock time per exchange
+    #[derive(Debug)]
+    struct SlowTransport {
+        inner: ScriptedTransport,
+        clock: clock::FixedClock,
+    }
+
+    impl TaskdTransport for SlowTransport {
+        fn exchange(&mut self, request: &TaskdMessage) -> Result<TaskdMessage, SyncError> {
+            self.clock.advance(chrono::Duration::seconds(5));
+            self.inner.exchange(request)
+        }
+    }
+
+    #[test]
+    fn test_changes_during_sync_stay_pending() {
+        let start = Utc.with_ymd_and_hms(2025, 4, 1, 8, 0, 0).unwrap();
+        let fixed = clock::FixedClock::new(start);
+        let _clock = clock::deterministic(fixed.clone(), clock::SequentialIds::new());
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut storage = FileStorageBackend::with_path(temp.path());
+        storage.initialize().unwrap();
+        let inner = ScriptedTransport::default();
+        inner.responses.lock().unwrap().push(response("201", ""));
+        inner.responses.lock().unwrap().push(response("201", ""));
+
+        let state_file = temp.path().join(STATE_FILE);
+        let transport = SlowTransport { inner: inner.clone(), clock: fixed };
+        let mut manager = TaskdSyncManager::new(&config(), Box::new(transport), Box::new(storage))
+            .with_state_file(&state_file)
+            .unwrap();
+        let mut task = Task::new("Edited mid-sync".to_string());
+        task.modified = Some(start - chrono::Duration::minutes(1));
+        manager.synchronize(std::slice::from_ref(&task)).unwrap();
+        assert_eq!(manager.status().last_sync, Some(start));
+        assert!(!state_file.with_extension("json.tmp").exists());
+
+        // Edited while the first exchange was in flight
+        task.modified = Some(start + chrono::Duration::seconds(2));
+        assert_eq!(manager.synchronize(std::slice::from_ref(&task)).unwrap().1, 1);
+    }
+}
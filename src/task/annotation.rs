@@ -2,6 +2,7 @@
 //!
 //! This module contains annotation and priority related types.
 
+use crate::clock;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -18,7 +19,7 @@ impl Annotation {
     /// Create a new annotation with current timestamp
     pub fn new(description: String) -> Self {
         Self {
-            entry: Utc::now(),
+            entry: clock::now(),
             description,
         }
     }
@@ -29,10 +30,31 @@ impl Annotation {
     }
 }
 
+/// Index of the annotation `text` refers to, as Taskwarrior's `denotate`
+/// resolves it: an exact match on the description first, otherwise the
+/// first description containing `text`
+pub fn find_annotation(annotations: &[Annotation], text: &str) -> Option<usize> {
+    annotations
+        .iter()
+        .position(|a| a.description == text)
+        .or_else(|| annotations.iter().position(|a| a.description.contains(text)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_annotation_prefers_exact_match() {
+        let annotations = vec![
+            Annotation::new("call back later".to_string()),
+            Annotation::new("call".to_string()),
+        ];
+        assert_eq!(find_annotation(&annotations, "call"), Some(1));
+        assert_eq!(find_annotation(&annotations, "back"), Some(0));
+        assert_eq!(find_annotation(&annotations, "email"), None);
+    }
+
     #[test]
     fn test_new_annotation() {
         let annotation = Annotation::new("Test note".to_string());
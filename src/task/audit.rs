@@ -0,0 +1,554 @@
+//! Append-only audit log of task changes
+//!
+//! With `audit.log` set, [`DefaultTaskManager`](crate::task::manager::DefaultTaskManager)
+//! appends one JSON line per add, update, complete, delete, restore and
+//! purge: who made the change, when, the operation batch it amounts to and
+//! whether it succeeded.
+//!
+//! Every entry carries the SHA-256 hash of its own content chained to the
+//! hash of the entry before it, so editing, removing or reordering entries
+//! breaks the chain from that point on and [`verify`] reports where. The
+//! chain continues across rotated files; once the oldest file is dropped,
+//! the first remaining entry's `prev` hash has to be trusted as is.
+//!
+//! A plain hash chain only shows accidental damage: whoever can write the
+//! file can also recompute every hash after an edit. With `audit.key` set
+//! the hashes are HMACs under that key, which should live outside the data
+//! directory, and only holders of the key can extend or verify the chain.
+//! Without a key, copy the head hash ([`AuditLog::head`]) somewhere the log's
+//! writers cannot reach and compare it with [`AuditVerification::last_hash`].
+//!
+//! Writers take an exclusive lock on `<file>.lock` and read the head of the
+//! chain again before each append, so several processes can share a log.
+//!
+//! Settings:
+//! - `audit.log`: file to write, e.g. `~/.task/audit.log`; unset disables the log
+//! - `audit.user`: name recorded as the actor (default: `$USER`)
+//! - `audit.key`: secret the entry hashes are keyed with (default: none)
+//! - `audit.rotate.size`: rotate once the file reaches this many bytes
+//!   (default 10 MiB, `0` never rotates)
+//! - `audit.rotate.keep`: rotated files to keep as `<file>.1` (newest) to
+//!   `<file>.N` (default 5)
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::clock;
+use crate::config::discovery::expand_home;
+use crate::config::Configuration;
+use crate::error::{ConfigError, TaskError};
+use crate::storage::lock::{DataDirLock, LockOptions};
+use crate::storage::operation_batch::Operation;
+
+/// `prev` of the very first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Audit log settings
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuditConfig {
+    pub path: PathBuf,
+    pub user: String,
+    /// Size in bytes that triggers rotation; 0 disables rotation
+    pub max_size: u64,
+    /// Number of rotated files to keep
+    pub keep: usize,
+    /// Secret the entry hashes are keyed with
+    pub key: Option<String>,
+}
+
+impl std::fmt::Debug for AuditConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditConfig")
+            .field("path", &self.path)
+            .field("user", &self.user)
+            .field("max_size", &self.max_size)
+            .field("keep", &self.keep)
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl AuditConfig {
+    /// Log to `path` as the current user, with the default rotation
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            user: current_user(),
+            max_size: 10 * 1024 * 1024,
+            keep: 5,
+            key: None,
+        }
+    }
+
+    /// Read the `audit.*` settings; `None` when `audit.log` is not set
+    pub fn from_config(config: &Configuration) -> Result<Option<Self>, ConfigError> {
+        let Some(path) = config.get("audit.log").filter(|p| !p.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let mut audit = Self::new(expand_home(path.trim()));
+        if let Some(user) = config.get("audit.user").filter(|u| !u.trim().is_empty()) {
+            audit.user = user.trim().to_string();
+        }
+        if let Some(size) = config.get("audit.rotate.size") {
+            audit.max_size = parse_setting("audit.rotate.size", size, "a size in bytes")?;
+        }
+        if let Some(keep) = config.get("audit.rotate.keep") {
+            audit.keep = parse_setting("audit.rotate.keep", keep, "a number of files")?;
+        }
+        audit.key = config
+            .get("audit.key")
+            .filter(|k| !k.is_empty())
+            .map(|k| k.to_string());
+        Ok(Some(audit))
+    }
+}
+
+fn parse_setting<T: std::str::FromStr>(
+    key: &str,
+    value: &str,
+    expected: &str,
+) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        expected: expected.to_string(),
+    })
+}
+
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// One recorded change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, counting from 1 across rotations
+    pub seq: u64,
+    pub time: DateTime<Utc>,
+    pub user: String,
+    /// `add`, `update`, `complete`, `delete`, `restore` or `purge`
+    pub operation: String,
+    pub task: Option<Uuid>,
+    /// Operations written to storage; empty when the change failed
+    pub batch: Vec<Operation>,
+    /// Error message of a failed change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Hash of the previous entry
+    pub prev: String,
+}
+
+impl AuditEntry {
+    /// Hash chaining this entry to the one after it, keyed like the log
+    pub fn hash(&self, key: Option<&str>) -> String {
+        hash_body(key, &serde_json::to_string(self).unwrap_or_default())
+    }
+}
+
+fn hash_body(key: Option<&str>, body: &str) -> String {
+    match key {
+        Some(key) => hex::encode(keyed(key, body).finalize().into_bytes()),
+        None => hex::encode(Sha256::digest(body.as_bytes())),
+    }
+}
+
+fn keyed(key: &str, body: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC takes any key");
+    mac.update(body.as_bytes());
+    mac
+}
+
+// Whether `hash` is the one `body` gives; keyed hashes are compared in
+// constant time
+fn hash_matches(key: Option<&str>, body: &str, hash: &str) -> bool {
+    match key {
+        Some(key) => hex::decode(hash).is_ok_and(|tag| keyed(key, body).verify_slice(&tag).is_ok()),
+        None => hash_body(None, body) == hash,
+    }
+}
+
+/// Prefix of every line; the entry's JSON object follows without its `{`.
+/// The hash covers the entry exactly as written, so verifying does not
+/// depend on the JSON coming out the same when serialized again.
+const HASH_PREFIX: &str = "{\"hash\":\"";
+
+fn format_line(key: Option<&str>, entry: &AuditEntry) -> Result<(String, String), TaskError> {
+    let body = serde_json::to_string(entry)?;
+    let hash = hash_body(key, &body);
+    Ok((format!("{HASH_PREFIX}{hash}\",{}\n", &body[1..]), hash))
+}
+
+/// Split a line into its entry and the hash it claims, checking the claim
+fn parse_line(
+    key: Option<&str>,
+    path: &Path,
+    number: usize,
+    line: &str,
+) -> Result<(AuditEntry, String), TaskError> {
+    let unreadable = |reason: String| TaskError::AuditLog {
+        message: format!("{}:{number}: unreadable entry: {reason}", path.display()),
+    };
+    let rest = line
+        .strip_prefix(HASH_PREFIX)
+        .ok_or_else(|| unreadable("no hash".to_string()))?;
+    let (hash, body) = rest
+        .split_once("\",")
+        .ok_or_else(|| unreadable("no hash".to_string()))?;
+    let body = format!("{{{body}");
+    let entry: AuditEntry = serde_json::from_str(&body).map_err(|e| unreadable(e.to_string()))?;
+    if !hash_matches(key, &body, hash) {
+        return Err(TaskError::AuditLog {
+            message: format!(
+                "{}:{number}: content does not match its hash at entry {}",
+                path.display(),
+                entry.seq
+            ),
+        });
+    }
+    Ok((entry, hash.to_string()))
+}
+
+/// Writer appending to the audit log
+#[derive(Debug)]
+pub struct AuditLog {
+    config: AuditConfig,
+}
+
+impl AuditLog {
+    /// Open the log, checking that the newest entry in it can be continued
+    pub fn open(config: AuditConfig) -> Result<Self, TaskError> {
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let log = Self { config };
+        log.head()?;
+        Ok(log)
+    }
+
+    /// Sequence number and hash of the newest entry, `(0, GENESIS_HASH)`
+    /// for an empty log
+    pub fn head(&self) -> Result<(u64, String), TaskError> {
+        // After a rotation the current file may still be empty
+        for path in self.files() {
+            if let Some((number, line)) = last_line(&path)? {
+                let (entry, hash) = parse_line(self.key(), &path, number, &line)?;
+                return Ok((entry.seq, hash));
+            }
+        }
+        Ok((0, GENESIS_HASH.to_string()))
+    }
+
+    fn key(&self) -> Option<&str> {
+        self.config.key.as_deref()
+    }
+
+    pub fn config(&self) -> &AuditConfig {
+        &self.config
+    }
+
+    /// Existing log files, current first, then `.1`, `.2`, ...
+    pub fn files(&self) -> Vec<PathBuf> {
+        std::iter::once(self.config.path.clone())
+            .chain((1..=self.config.keep).map(|n| rotated(&self.config.path, n)))
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    /// Append an entry for `operation` on `task`; `batch` is ignored if
+    /// `result` is an error. Other writers are locked out from reading the
+    /// head of the chain until the entry is written.
+    pub fn record(
+        &self,
+        operation: &str,
+        task: Option<Uuid>,
+        batch: Vec<Operation>,
+        result: Result<(), &TaskError>,
+    ) -> Result<AuditEntry, TaskError> {
+        let (batch, error) = match result {
+            Ok(()) => (batch, None),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let _lock = DataDirLock::exclusive(&self.config.path, &LockOptions::default())?;
+        let (seq, prev) = self.head()?;
+        let entry = AuditEntry {
+            seq: seq + 1,
+            time: clock::now(),
+            user: self.config.user.clone(),
+            operation: operation.to_string(),
+            task,
+            batch,
+            error,
+            prev,
+        };
+        let (line, _) = format_line(self.key(), &entry)?;
+
+        self.rotate_if_full(line.len() as u64)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(entry)
+    }
+
+    fn rotate_if_full(&self, incoming: u64) -> Result<(), TaskError> {
+        let size = match fs::metadata(&self.config.path) {
+            Ok(meta) => meta.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if self.config.max_size == 0 || size == 0 || size + incoming <= self.config.max_size {
+            return Ok(());
+        }
+        let path = &self.config.path;
+        if self.config.keep == 0 {
+            fs::remove_file(path)?;
+            return Ok(());
+        }
+        let oldest = rotated(path, self.config.keep);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for n in (1..self.config.keep).rev() {
+            let from = rotated(path, n);
+            if from.exists() {
+                fs::rename(&from, rotated(path, n + 1))?;
+            }
+        }
+        fs::rename(path, rotated(path, 1))?;
+        Ok(())
+    }
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Last non-empty line of a file and its line number
+fn last_line(path: &Path) -> Result<Option<(usize, String)>, TaskError> {
+    let mut last = None;
+    for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            last = Some((index + 1, line));
+        }
+    }
+    Ok(last)
+}
+
+/// Result of a successful [`verify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditVerification {
+    /// Number of entries checked
+    pub entries: u64,
+    /// `prev` of the oldest entry, taken on trust
+    pub first_prev: String,
+    /// Hash of the newest entry
+    pub last_hash: String,
+}
+
+/// Check the hash chain of log files given oldest first, e.g. the reverse
+/// of [`AuditLog::files`]. Fails at the first entry whose hash, `prev` or
+/// sequence number does not follow from the one before it.
+pub fn verify<P: AsRef<Path>>(files: &[P]) -> Result<AuditVerification, TaskError> {
+    verify_with_key(files, None)
+}
+
+/// [`verify`] a log whose hashes are keyed with `key` (`audit.key`)
+pub fn verify_with_key<P: AsRef<Path>>(
+    files: &[P],
+    key: Option<&str>,
+) -> Result<AuditVerification, TaskError> {
+    let mut verification = AuditVerification {
+        entries: 0,
+        first_prev: String::new(),
+        last_hash: String::new(),
+    };
+    let mut expected: Option<(u64, String)> = None;
+    for path in files {
+        let path = path.as_ref();
+        for (index, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let number = index + 1;
+            let (entry, hash) = parse_line(key, path, number, &line)?;
+            let broken = |reason: &str| TaskError::AuditLog {
+                message: format!(
+                    "{}:{number}: {reason} at entry {}",
+                    path.display(),
+                    entry.seq
+                ),
+            };
+            match &expected {
+                Some((_, prev)) if entry.prev != *prev => return Err(broken("chain broken")),
+                Some((seq, _)) if entry.seq != seq + 1 => return Err(broken("sequence gap")),
+                Some(_) => {}
+                None => verification.first_prev = entry.prev.clone(),
+            }
+            verification.entries += 1;
+            expected = Some((entry.seq, hash.clone()));
+            verification.last_hash = hash;
+        }
+    }
+    Ok(verification)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SequentialIds};
+    use crate::storage::operation_batch::build_delete_batch;
+    use chrono::TimeZone;
+
+    fn config(dir: &Path) -> AuditConfig {
+        AuditConfig {
+            user: "alice".to_string(),
+            ..AuditConfig::new(dir.join("audit.log"))
+        }
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut settings = Configuration::default();
+        assert_eq!(AuditConfig::from_config(&settings).unwrap(), None);
+        settings.set("audit.log", "/var/log/task/audit.log");
+        settings.set("audit.user", "bob");
+        settings.set("audit.rotate.keep", "2");
+        settings.set("audit.key", "s3cret");
+        let audit = AuditConfig::from_config(&settings).unwrap().unwrap();
+        assert_eq!(audit.path, PathBuf::from("/var/log/task/audit.log"));
+        assert_eq!((audit.user.as_str(), audit.keep), ("bob", 2));
+        assert_eq!(audit.key.as_deref(), Some("s3cret"));
+        assert!(!format!("{audit:?}").contains("s3cret"));
+
+        settings.set("audit.rotate.size", "big");
+        assert!(matches!(
+            AuditConfig::from_config(&settings),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chain_survives_reopen_and_detects_tampering() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 5, 1, 8, 0, 0).unwrap();
+        let _guard = clock::deterministic(FixedClock::new(now), SequentialIds::new());
+        let id = clock::new_id();
+
+        let log = AuditLog::open(config(dir.path())).unwrap();
+        let first = log
+            .record("delete", Some(id), build_delete_batch(id), Ok(()))
+            .unwrap();
+        assert_eq!((first.seq, first.prev.as_str()), (1, GENESIS_HASH));
+        assert_eq!(first.time, now);
+
+        let log = AuditLog::open(config(dir.path())).unwrap();
+        let failed = TaskError::NotFound { id };
+        let second = log
+            .record("update", Some(id), build_delete_batch(id), Err(&failed))
+            .unwrap();
+        assert_eq!(second.seq, 2);
+        assert_eq!(second.prev, first.hash(None));
+        assert!(second.batch.is_empty());
+        assert!(second.error.unwrap().contains("not found"));
+
+        let path = dir.path().join("audit.log");
+        let verified = verify(&[&path]).unwrap();
+        assert_eq!(verified.entries, 2);
+
+        log.record("purge", Some(id), Vec::new(), Ok(())).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replacen("alice", "mallory", 1)).unwrap();
+        let err = verify(&[&path]).unwrap_err().to_string();
+        assert!(err.contains(":1: content does not match"), "{err}");
+
+        // A dropped first entry looks like rotation, a dropped middle one
+        // breaks the chain
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(&path, lines[1..].join("\n")).unwrap();
+        assert_eq!(verify(&[&path]).unwrap().first_prev, first.hash(None));
+        fs::write(&path, [lines[0], lines[2]].join("\n")).unwrap();
+        let err = verify(&[&path]).unwrap_err().to_string();
+        assert!(err.contains(":2: chain broken at entry 3"), "{err}");
+    }
+
+    #[test]
+    fn test_rotation_keeps_the_chain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let log = AuditLog::open(AuditConfig {
+            max_size: 600,
+            keep: 2,
+            ..config(dir.path())
+        })
+        .unwrap();
+        for _ in 0..12 {
+            let id = Uuid::new_v4();
+            log.record("delete", Some(id), build_delete_batch(id), Ok(()))
+                .unwrap();
+        }
+
+        let files = log.files();
+        assert_eq!(files.len(), 3);
+        assert!(!dir.path().join("audit.log.3").exists());
+        let oldest_first: Vec<PathBuf> = files.into_iter().rev().collect();
+        let verified = verify(&oldest_first).unwrap();
+        assert_eq!(verified.last_hash, log.head().unwrap().1);
+        assert_ne!(verified.first_prev, GENESIS_HASH);
+
+        // Files out of order do not chain
+        assert!(verify(&log.files()).is_err());
+    }
+
+    #[test]
+    fn test_writers_share_the_chain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = AuditLog::open(config(dir.path())).unwrap();
+        let second = AuditLog::open(config(dir.path())).unwrap();
+        for log in [&first, &second, &first, &second] {
+            let id = Uuid::new_v4();
+            log.record("delete", Some(id), build_delete_batch(id), Ok(()))
+                .unwrap();
+        }
+        let verified = verify(&[dir.path().join("audit.log")]).unwrap();
+        assert_eq!(verified.entries, 4);
+        assert_eq!(first.head().unwrap(), (4, verified.last_hash));
+    }
+
+    #[test]
+    fn test_keyed_chain_needs_the_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let keyed = AuditConfig {
+            key: Some("s3cret".to_string()),
+            ..config(dir.path())
+        };
+        let log = AuditLog::open(keyed.clone()).unwrap();
+        let id = Uuid::new_v4();
+        let entry = log.record("purge", Some(id), Vec::new(), Ok(())).unwrap();
+        log.record("purge", Some(id), Vec::new(), Ok(())).unwrap();
+
+        let path = dir.path().join("audit.log");
+        let verified = verify_with_key(&[&path], Some("s3cret")).unwrap();
+        assert_eq!(verified.entries, 2);
+        assert_eq!(AuditLog::open(keyed).unwrap().head().unwrap().1, verified.last_hash);
+        assert_ne!(entry.hash(Some("s3cret")), entry.hash(None));
+
+        // Recomputing the hashes without the key does not pass
+        assert!(verify(&[&path]).is_err());
+        assert!(verify_with_key(&[&path], Some("guess")).is_err());
+        assert!(AuditLog::open(config(dir.path())).is_err());
+    }
+}
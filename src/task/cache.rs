@@ -0,0 +1,311 @@
+//! Query result caching
+//!
+//! [`CachedTaskManager`] wraps any [`TaskManager`] and memoizes query results
+//! keyed by the normalized query. Every mutation made through the wrapper
+//! invalidates the cache; entries also expire after a TTL so changes made by
+//! other processes (the `task` CLI, sync) eventually become visible.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::config::{Configuration, ConfigurationProvider};
+use crate::error::TaskError;
+use crate::query::TaskQuery;
+use crate::task::manager::{AddOptions, SyncResult, TaskManager, TaskUpdate, ValidationReport};
+use crate::task::Task;
+
+/// Cache hit/miss counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    tasks: Vec<Task>,
+    stored_at: Instant,
+}
+
+/// TaskManager wrapper that caches query results
+#[derive(Debug)]
+pub struct CachedTaskManager<M: TaskManager> {
+    inner: M,
+    entries: HashMap<String, CacheEntry>,
+    /// Insertion order, oldest first, used to evict when full
+    order: VecDeque<String>,
+    ttl: Duration,
+    max_entries: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<M: TaskManager> CachedTaskManager<M> {
+    /// Wrap a task manager with a 5 second TTL and room for 64 queries
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            ttl: Duration::from_secs(5),
+            max_entries: 64,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Set how long a cached result stays valid
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the maximum number of cached queries
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Drop all cached results
+    pub fn invalidate(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Current cache statistics
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+
+    /// Access the wrapped manager
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Unwrap into the inner manager
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+
+    /// Normalized cache key for a query: its JSON form, which lists tag
+    /// sets sorted
+    fn query_key(query: &TaskQuery) -> Option<String> {
+        serde_json::to_string(query).ok().map(|json| format!("query:{json}"))
+    }
+
+    /// Serve `key` from the cache or compute it with `load`
+    fn cached<F>(&mut self, key: String, load: F) -> Result<Vec<Task>, TaskError>
+    where
+        F: FnOnce(&mut M) -> Result<Vec<Task>, TaskError>,
+    {
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.stored_at.elapsed() < self.ttl {
+                self.hits += 1;
+                return Ok(entry.tasks.clone());
+            }
+            self.entries.remove(&key);
+            self.order.retain(|k| k != &key);
+        }
+
+        self.misses += 1;
+        let tasks = load(&mut self.inner)?;
+
+        if self.max_entries > 0 {
+            while self.entries.len() >= self.max_entries {
+                match self.order.pop_front() {
+                    Some(oldest) => {
+                        self.entries.remove(&oldest);
+                    }
+                    None => break,
+                }
+            }
+            self.order.push_back(key.clone());
+            self.entries.insert(
+                key,
+                CacheEntry {
+                    tasks: tasks.clone(),
+                    stored_at: Instant::now(),
+                },
+            );
+        }
+
+        Ok(tasks)
+    }
+}
+
+impl<M: TaskManager> ConfigurationProvider for CachedTaskManager<M> {
+    fn config(&self) -> &Configuration {
+        self.inner.config()
+    }
+
+    fn config_mut(&mut self) -> &mut Configuration {
+        // Config changes (e.g. switching context) can change query results
+        self.invalidate();
+        self.inner.config_mut()
+    }
+
+    fn reload_config(&mut self) -> Result<(), TaskError> {
+        self.invalidate();
+        self.inner.reload_config()
+    }
+}
+
+impl<M: TaskManager> TaskManager for CachedTaskManager<M> {
+    fn add_task(&mut self, description: String) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.add_task(description)
+    }
+
+    fn add_task_with_options(
+        &mut self,
+        description: String,
+        options: AddOptions,
+    ) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.add_task_with_options(description, options)
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        self.inner.get_task(id)
+    }
+
+    fn update_task(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.update_task(id, updates)
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.delete_task(id)
+    }
+
+    fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.complete_task(id)
+    }
+
+    fn restore_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.invalidate();
+        self.inner.restore_task(id)
+    }
+
+    fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
+        // Closure predicates have no stable identity to key on
+        let key = Self::query_key(query).filter(|_| query.custom_filters.is_empty());
+        match key {
+            Some(key) => self.cached(key, |inner| inner.query_tasks(query)),
+            None => self.inner.query_tasks(query),
+        }
+    }
+
+    fn pending_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.cached("pending".to_string(), |inner| inner.pending_tasks())
+    }
+
+    fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.cached("completed".to_string(), |inner| inner.completed_tasks())
+    }
+
+    fn deleted_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.cached("deleted".to_string(), |inner| inner.deleted_tasks())
+    }
+
+    fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError> {
+        Ok(self.query_tasks(query)?.len())
+    }
+
+    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+        self.invalidate();
+        self.inner.sync()
+    }
+
+    fn validate_all(&self) -> Result<ValidationReport, TaskError> {
+        self.inner.validate_all()
+    }
+
+    fn take_feedback(&mut self) -> Vec<String> {
+        self.inner.take_feedback()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::query::{TaskQueryBuilder, TaskQueryBuilderImpl};
+    use crate::storage::FileStorageBackend;
+    use crate::task::manager::DefaultTaskManager;
+    use tempfile::TempDir;
+
+    fn cached_manager(temp_dir: &TempDir) -> CachedTaskManager<DefaultTaskManager> {
+        let config = ConfigurationBuilder::new()
+            .data_dir(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(temp_dir.path().to_path_buf()));
+        let hooks = Box::new(DefaultHookSystem::new());
+        CachedTaskManager::new(DefaultTaskManager::new(config, storage, hooks).unwrap())
+    }
+
+    #[test]
+    fn test_query_hits_cache_until_mutation() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = cached_manager(&temp_dir);
+        manager.add_task("First".to_string()).unwrap();
+
+        let query = TaskQueryBuilderImpl::new().build().unwrap();
+        assert_eq!(manager.query_tasks(&query).unwrap().len(), 1);
+        assert_eq!(manager.query_tasks(&query).unwrap().len(), 1);
+        assert_eq!(manager.stats().hits, 1);
+        assert_eq!(manager.stats().misses, 1);
+
+        manager.add_task("Second".to_string()).unwrap();
+        assert_eq!(manager.stats().entries, 0);
+        assert_eq!(manager.query_tasks(&query).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_query_key_ignores_tag_order() {
+        let tags: Vec<String> = (0..32).map(|i| format!("tag{i}")).collect();
+        let query = |tags: Vec<String>| TaskQuery {
+            tag_filter: Some(crate::query::TagFilter::include_tags(tags)),
+            ..Default::default()
+        };
+        let forward = query(tags.clone());
+        let backward = query(tags.into_iter().rev().collect());
+        assert_eq!(
+            CachedTaskManager::<DefaultTaskManager>::query_key(&forward),
+            CachedTaskManager::<DefaultTaskManager>::query_key(&backward)
+        );
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = cached_manager(&temp_dir);
+        manager.query_tasks(&forward).unwrap();
+        manager.query_tasks(&backward).unwrap();
+        assert_eq!(manager.stats().hits, 1);
+    }
+
+    #[test]
+    fn test_ttl_and_size_bounds() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = cached_manager(&temp_dir)
+            .with_ttl(Duration::ZERO)
+            .with_max_entries(1);
+
+        manager.pending_tasks().unwrap();
+        manager.completed_tasks().unwrap();
+        assert_eq!(manager.stats().entries, 1);
+
+        // Zero TTL means every lookup misses
+        manager.completed_tasks().unwrap();
+        assert_eq!(manager.stats().hits, 0);
+        assert_eq!(manager.stats().misses, 3);
+    }
+}
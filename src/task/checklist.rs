@@ -0,0 +1,154 @@
+//! Checklists inside a task
+//!
+//! Small steps that don't deserve tasks of their own live in the task as a
+//! checklist: an ordered list of items, each with a done flag. The list is
+//! stored as a JSON array in the [`CHECKLIST_UDA`] string UDA, e.g.
+//!
+//! ```text
+//! checklist:[{"text":"Passport","done":true},{"text":"Tickets","done":false}]
+//! ```
+//!
+//! so it survives export, import and sync, and Taskwarrior shows it like any
+//! other UDA. The `checklist` report column shows progress as `1/2`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TaskError;
+use crate::task::model::UdaValue;
+use crate::task::Task;
+
+/// UDA holding a task's checklist
+pub const CHECKLIST_UDA: &str = "checklist";
+
+/// One checklist entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// Ordered checklist of a task
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Checklist {
+    pub items: Vec<ChecklistItem>,
+}
+
+impl Checklist {
+    /// The checklist stored on `task`, empty when there is none or the UDA
+    /// does not hold a checklist
+    pub fn from_task(task: &Task) -> Self {
+        match task.udas.get(CHECKLIST_UDA) {
+            Some(UdaValue::String(raw)) => serde_json::from_str(raw).unwrap_or_default(),
+            _ => Self::default(),
+        }
+    }
+
+    /// The UDA value for this checklist, `None` once it is empty
+    pub fn to_uda(&self) -> Option<UdaValue> {
+        if self.items.is_empty() {
+            return None;
+        }
+        serde_json::to_string(self).ok().map(UdaValue::String)
+    }
+
+    /// Store the checklist on `task`, removing the UDA when it is empty
+    pub fn write_to(&self, task: &mut Task) {
+        match self.to_uda() {
+            Some(value) => {
+                task.udas.insert(CHECKLIST_UDA.to_string(), value);
+            }
+            None => {
+                task.udas.remove(CHECKLIST_UDA);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Append an open item
+    pub fn add<S: Into<String>>(&mut self, text: S) {
+        self.items.push(ChecklistItem {
+            text: text.into(),
+            done: false,
+        });
+    }
+
+    /// Flip the done flag of the item at `index` (0-based), returning the
+    /// new flag
+    pub fn toggle(&mut self, index: usize) -> Result<bool, TaskError> {
+        let item = self
+            .items
+            .get_mut(index)
+            .ok_or_else(|| out_of_range(index))?;
+        item.done = !item.done;
+        Ok(item.done)
+    }
+
+    /// Remove the item at `index` (0-based)
+    pub fn remove(&mut self, index: usize) -> Result<ChecklistItem, TaskError> {
+        if index >= self.items.len() {
+            return Err(out_of_range(index));
+        }
+        Ok(self.items.remove(index))
+    }
+
+    /// Done and total item counts
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.items.iter().filter(|i| i.done).count();
+        (done, self.items.len())
+    }
+}
+
+impl fmt::Display for Checklist {
+    /// Progress as `done/total`, e.g. `3/7`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (done, total) = self.progress();
+        write!(f, "{done}/{total}")
+    }
+}
+
+fn out_of_range(index: usize) -> TaskError {
+    TaskError::InvalidData {
+        message: format!("No checklist item {index}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checklist_round_trip() {
+        let mut task = Task::new("Pack for trip".to_string());
+        assert!(Checklist::from_task(&task).is_empty());
+
+        let mut checklist = Checklist::default();
+        checklist.add("Passport");
+        checklist.add("Tickets");
+        checklist.add("Charger");
+        assert!(checklist.toggle(0).unwrap());
+        assert!(checklist.toggle(3).is_err());
+        checklist.write_to(&mut task);
+        assert!(matches!(
+            task.udas.get(CHECKLIST_UDA),
+            Some(UdaValue::String(raw)) if raw.starts_with(r#"[{"text":"Passport","done":true}"#)
+        ));
+
+        let mut checklist = Checklist::from_task(&task);
+        assert_eq!(checklist.progress(), (1, 3));
+        assert_eq!(checklist.to_string(), "1/3");
+        assert_eq!(checklist.remove(1).unwrap().text, "Tickets");
+        assert!(checklist.remove(2).is_err());
+        assert_eq!(checklist.to_string(), "1/2");
+
+        checklist.items.clear();
+        checklist.write_to(&mut task);
+        assert!(!task.udas.contains_key(CHECKLIST_UDA));
+    }
+}
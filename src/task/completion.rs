@@ -0,0 +1,147 @@
+//! Following dependency chains on completion
+//!
+//! Completing a task can unblock the tasks that depend on it.
+//! [`DefaultTaskManager::complete_task_chained`](crate::task::manager::DefaultTaskManager::complete_task_chained)
+//! reports those tasks and, depending on `dependency.chain.start`, starts
+//! the next one:
+//!
+//! ```text
+//! dependency.chain.start=off      # only report unblocked dependents (default)
+//! dependency.chain.start=prompt   # ask the confirmation handler first
+//! dependency.chain.start=auto     # start it without asking
+//! ```
+//!
+//! The next task is the earliest-entered unblocked dependent. With
+//! `prompt`, a manager without a confirmation handler starts nothing, and
+//! `confirmation=off` starts it without asking, as in Taskwarrior.
+
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::error::ConfigError;
+use crate::task::dependencies::DependencyGraph;
+use crate::task::{Task, TaskStatus};
+
+/// Answers yes/no questions on behalf of the user, e.g. with a terminal
+/// prompt or a dialog
+pub struct ConfirmationHandler(Box<dyn FnMut(&str) -> bool + Send>);
+
+impl ConfirmationHandler {
+    /// Wrap a closure receiving the question and returning the answer
+    pub fn new<F>(handler: F) -> Self
+    where
+        F: FnMut(&str) -> bool + Send + 'static,
+    {
+        Self(Box::new(handler))
+    }
+
+    /// Ask `prompt`
+    pub fn confirm(&mut self, prompt: &str) -> bool {
+        (self.0)(prompt)
+    }
+}
+
+impl fmt::Debug for ConfirmationHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConfirmationHandler(<closure>)")
+    }
+}
+
+/// Whether completing a task starts the next task in its chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChainStart {
+    #[default]
+    Off,
+    /// Start it if the confirmation handler agrees
+    Prompt,
+    Auto,
+}
+
+impl ChainStart {
+    /// Read `dependency.chain.start`
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let Some(raw) = config.get("dependency.chain.start") else {
+            return Ok(Self::default());
+        };
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" | "no" | "0" => Ok(ChainStart::Off),
+            "prompt" | "ask" => Ok(ChainStart::Prompt),
+            "auto" | "yes" | "1" => Ok(ChainStart::Auto),
+            _ => Err(ConfigError::InvalidValue {
+                key: "dependency.chain.start".to_string(),
+                value: raw.to_string(),
+                expected: "'off', 'prompt' or 'auto'".to_string(),
+            }),
+        }
+    }
+}
+
+/// Outcome of completing a task and following its dependency chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainedCompletion {
+    /// The completed task
+    pub task: Task,
+    /// Pending dependents no longer blocked by anything, earliest entered
+    /// first
+    pub unblocked: Vec<Task>,
+    /// The dependent that was started, if any
+    pub started: Option<Task>,
+}
+
+/// Pending tasks depending on `completed` that have no unfinished
+/// dependency left, ordered by entry time
+pub fn unblocked_dependents(tasks: &[Task], completed: Uuid) -> Vec<Task> {
+    let graph = DependencyGraph::new(tasks);
+    let mut unblocked: Vec<Task> = tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Pending && t.depends.contains(&completed))
+        .filter(|t| graph.blockers(t.id).is_empty())
+        .cloned()
+        .collect();
+    unblocked.sort_by_key(|t| (t.entry, t.id));
+    unblocked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_unblocked_dependents() {
+        let mut design = Task::new("Design".to_string());
+        let review = Task::new("Review".to_string());
+        let mut build = Task::new("Build".to_string());
+        build.depends.insert(design.id);
+        let mut ship = Task::new("Ship".to_string());
+        ship.entry = build.entry + Duration::seconds(1);
+        ship.depends.extend([design.id, review.id]);
+        let mut docs = Task::new("Docs".to_string());
+        docs.entry = build.entry + Duration::seconds(2);
+        docs.depends.insert(design.id);
+
+        design.complete();
+        let tasks = [design.clone(), review, build.clone(), ship, docs.clone()];
+        let unblocked: Vec<Uuid> = unblocked_dependents(&tasks, design.id)
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        // Ship still waits on the review
+        assert_eq!(unblocked, [build.id, docs.id]);
+    }
+
+    #[test]
+    fn test_chain_start_from_config() {
+        let mut config = Configuration::default();
+        assert_eq!(ChainStart::from_config(&config).unwrap(), ChainStart::Off);
+        config.set("dependency.chain.start", "Prompt");
+        assert_eq!(
+            ChainStart::from_config(&config).unwrap(),
+            ChainStart::Prompt
+        );
+        config.set("dependency.chain.start", "sometimes");
+        assert!(ChainStart::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Tasks with their computed fields
+//!
+//! Rendering a task row takes more than the stored task: its urgency, its
+//! virtual tags, what blocks it and how far off it is due. Computing those
+//! per row means another lookup per task, so
+//! [`TaskManager::query_decorated`](crate::task::TaskManager::query_decorated)
+//! returns [`DecoratedTask`]s with everything computed in one pass.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::query::virtual_tags;
+use crate::task::urgency::UrgencyCoefficients;
+use crate::task::{DependencyGraph, Task};
+
+/// A task plus the values frontends compute for display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecoratedTask {
+    pub task: Task,
+    /// Urgency under the configured coefficients
+    pub urgency: f64,
+    /// Virtual tags such as `OVERDUE` or `READY`
+    pub virtual_tags: Vec<String>,
+    /// Unfinished tasks this one waits on, earliest entered first
+    pub blocked_by: Vec<Uuid>,
+    /// Short ID from the working set, where the storage backend has one
+    pub display_id: Option<u32>,
+    /// Time until the due date in its largest unit, e.g. `3d`, or `-2h`
+    /// when overdue
+    pub relative_due: Option<String>,
+    /// How often the due date has been moved later
+    pub pushed: u32,
+}
+
+/// Decorate `tasks`, looking up blockers among `all` tasks
+pub fn decorate(
+    tasks: Vec<Task>,
+    all: &[Task],
+    urgency: &UrgencyCoefficients,
+    now: DateTime<Utc>,
+) -> Vec<DecoratedTask> {
+    let graph = DependencyGraph::new(all);
+    tasks
+        .into_iter()
+        .map(|task| DecoratedTask {
+            urgency: urgency.score(&task, now),
+            virtual_tags: virtual_tags(&task).into_iter().map(String::from).collect(),
+            blocked_by: graph.blockers(task.id).iter().map(|t| t.id).collect(),
+            display_id: task.display_id,
+            relative_due: task.due.map(|due| format_relative(due - now)),
+            pushed: task.push_count(),
+            task,
+        })
+        .collect()
+}
+
+/// `duration` in its largest whole unit, as Taskwarrior's `relative` date
+/// format shows it: `1y`, `3mo`, `2w`, `5d`, `4h`, `10min` or `30s`
+pub fn format_relative(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let seconds = duration.num_seconds().unsigned_abs();
+    let units = [
+        (365 * 86_400, "y"),
+        (30 * 86_400, "mo"),
+        (7 * 86_400, "w"),
+        (86_400, "d"),
+        (3_600, "h"),
+        (60, "min"),
+    ];
+    let (count, unit) = units
+        .into_iter()
+        .find(|(size, _)| seconds >= *size)
+        .map_or((seconds, "s"), |(size, unit)| (seconds / size, unit));
+    format!("{sign}{count}{unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_relative() {
+        assert_eq!(
+            format_relative(Duration::days(3) + Duration::hours(5)),
+            "3d"
+        );
+        assert_eq!(format_relative(Duration::days(15)), "2w");
+        assert_eq!(format_relative(Duration::days(400)), "1y");
+        assert_eq!(format_relative(-Duration::hours(2)), "-2h");
+        assert_eq!(format_relative(Duration::seconds(42)), "42s");
+    }
+
+    #[test]
+    fn test_decorate() {
+        let now = Utc::now();
+        let design = Task::new("Design".to_string());
+        let mut build = Task::new("Build".to_string());
+        build.depends.insert(design.id);
+        build.due = Some(now - Duration::days(2));
+        build.display_id = Some(2);
+        let all = vec![design.clone(), build.clone()];
+
+        let decorated = decorate(vec![build.clone()], &all, &UrgencyCoefficients::new(), now);
+        let row = &decorated[0];
+        assert_eq!(row.task, build);
+        assert_eq!(row.blocked_by, [design.id]);
+        assert_eq!(row.display_id, Some(2));
+        assert_eq!(row.relative_due.as_deref(), Some("-2d"));
+        assert_eq!(row.pushed, 0);
+        assert!(row.virtual_tags.iter().any(|t| t == "OVERDUE"));
+        assert!(row.virtual_tags.iter().any(|t| t == "BLOCKED"));
+        assert_eq!(row.urgency, UrgencyCoefficients::new().score(&build, now));
+    }
+}
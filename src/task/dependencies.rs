@@ -0,0 +1,259 @@
+//! Dependency graph over a set of tasks
+//!
+//! A task's `depends` only points one way, at the tasks it waits for.
+//! [`DependencyGraph`] indexes a task list in both directions so reports can
+//! ask which tasks are blocked and which are holding others up. Only
+//! unfinished tasks (neither completed nor deleted) block; dependencies on
+//! tasks outside the list are ignored.
+//!
+//! The graph can be exported for visualization with
+//! [`DependencyGraph::to_dot`] (GraphViz) or [`DependencyGraph::to_json`]
+//! (nodes and edges for web front ends).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::query::TaskQuery;
+use crate::task::{Task, TaskStatus};
+
+/// Both directions of the `depends` relation between tasks
+#[derive(Debug, Clone)]
+pub struct DependencyGraph<'a> {
+    tasks: HashMap<Uuid, &'a Task>,
+    dependents: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    /// Index `tasks` by id and by the tasks depending on them
+    pub fn new(tasks: &'a [Task]) -> Self {
+        let mut dependents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for task in tasks {
+            for dependency in &task.depends {
+                dependents.entry(*dependency).or_default().push(task.id);
+            }
+        }
+        for ids in dependents.values_mut() {
+            ids.sort();
+        }
+        Self {
+            tasks: tasks.iter().map(|t| (t.id, t)).collect(),
+            dependents,
+        }
+    }
+
+    /// The task with `id`, if it is in the graph
+    pub fn get(&self, id: Uuid) -> Option<&'a Task> {
+        self.tasks.get(&id).copied()
+    }
+
+    /// Unfinished tasks that `id` is waiting on
+    pub fn blockers(&self, id: Uuid) -> Vec<&'a Task> {
+        let Some(task) = self.get(id) else {
+            return Vec::new();
+        };
+        let mut blockers: Vec<&'a Task> = task
+            .depends
+            .iter()
+            .filter_map(|dep| self.get(*dep))
+            .filter(|t| is_open(t))
+            .collect();
+        blockers.sort_by_key(|t| (t.entry, t.id));
+        blockers
+    }
+
+    /// Unfinished tasks waiting on `id`
+    pub fn blocked_by(&self, id: Uuid) -> Vec<&'a Task> {
+        let mut blocked: Vec<&'a Task> = self
+            .dependents
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|dependent| self.get(*dependent))
+            .filter(|t| is_open(t))
+            .collect();
+        blocked.sort_by_key(|t| (t.entry, t.id));
+        blocked
+    }
+
+    /// Whether `id` is unfinished and waits on an unfinished task
+    pub fn is_blocked(&self, id: Uuid) -> bool {
+        self.get(id).is_some_and(is_open) && !self.blockers(id).is_empty()
+    }
+
+    /// Whether `id` is unfinished and an unfinished task waits on it
+    pub fn is_blocking(&self, id: Uuid) -> bool {
+        self.get(id).is_some_and(is_open) && !self.blocked_by(id).is_empty()
+    }
+
+    /// Tasks matching `filter` ordered by (entry, id), with the edges
+    /// between them as (prerequisite, dependent) pairs
+    fn subgraph(&self, filter: &TaskQuery) -> (Vec<&'a Task>, Vec<(Uuid, Uuid)>) {
+        let mut nodes: Vec<&'a Task> = self
+            .tasks
+            .values()
+            .copied()
+            .filter(|t| filter.matches(t))
+            .collect();
+        nodes.sort_by_key(|t| (t.entry, t.id));
+        let included: HashSet<Uuid> = nodes.iter().map(|t| t.id).collect();
+        let mut edges: Vec<(Uuid, Uuid)> = nodes
+            .iter()
+            .flat_map(|t| t.depends.iter().map(move |dep| (*dep, t.id)))
+            .filter(|(dep, _)| included.contains(dep))
+            .collect();
+        edges.sort();
+        (nodes, edges)
+    }
+
+    /// GraphViz DOT source for the tasks matching `filter` and the
+    /// dependencies between them. Edges point from a prerequisite to the
+    /// task waiting on it, dashed once the prerequisite is finished. Nodes
+    /// are colored by status, and open tasks by urgency.
+    pub fn to_dot(&self, filter: &TaskQuery) -> String {
+        let (nodes, edges) = self.subgraph(filter);
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=\"rounded,filled\"];\n");
+        for task in &nodes {
+            let mut label = task.description.clone();
+            if let Some(project) = &task.project {
+                label.push_str(&format!("\n{project}"));
+            }
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\", fillcolor=\"{}\"];",
+                task.id,
+                escape_dot(&label),
+                node_color(task)
+            );
+        }
+        for (from, to) in &edges {
+            let style = if self.get(*from).is_some_and(is_open) {
+                ""
+            } else {
+                " [style=dashed]"
+            };
+            let _ = writeln!(dot, "    \"{from}\" -> \"{to}\"{style};");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// JSON form of [`to_dot`](Self::to_dot): `nodes` carry the id,
+    /// description, project, status, urgency and whether the task is
+    /// blocked; `edges` run `from` a prerequisite `to` its dependent
+    pub fn to_json(&self, filter: &TaskQuery) -> Value {
+        let (nodes, edges) = self.subgraph(filter);
+        let nodes: Vec<Value> = nodes
+            .iter()
+            .map(|t| {
+                json!({
+                    "id": t.id,
+                    "description": t.description,
+                    "project": t.project,
+                    "status": t.status,
+                    "urgency": t.urgency,
+                    "blocked": self.is_blocked(t.id),
+                })
+            })
+            .collect();
+        let edges: Vec<Value> = edges
+            .iter()
+            .map(|(from, to)| json!({ "from": from, "to": to }))
+            .collect();
+        json!({ "nodes": nodes, "edges": edges })
+    }
+}
+
+fn is_open(task: &Task) -> bool {
+    !matches!(task.status, TaskStatus::Completed | TaskStatus::Deleted)
+}
+
+/// Fill color for a node: green when completed, grey when deleted, blue
+/// when waiting; open tasks go from yellow to red as urgency rises
+fn node_color(task: &Task) -> &'static str {
+    match task.status {
+        TaskStatus::Completed => "#c8e6c9",
+        TaskStatus::Deleted => "#e0e0e0",
+        TaskStatus::Waiting => "#cfe3f7",
+        TaskStatus::Pending | TaskStatus::Recurring => match task.urgency {
+            u if u >= 10.0 => "#f4a6a6",
+            u if u >= 5.0 => "#f9d48b",
+            _ => "#fff3b0",
+        },
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocked_and_blocking() {
+        let design = Task::new("Design".to_string());
+        let mut review = Task::new("Review".to_string());
+        review.complete();
+        let mut build = Task::new("Build".to_string());
+        build.depends.extend([design.id, review.id, Uuid::new_v4()]);
+        let mut ship = Task::new("Ship".to_string());
+        ship.depends.insert(build.id);
+        let tasks = vec![design.clone(), review.clone(), build.clone(), ship.clone()];
+
+        let graph = DependencyGraph::new(&tasks);
+        // Finished and unknown dependencies don't block
+        assert_eq!(graph.blockers(build.id).iter().map(|t| t.id).collect::<Vec<_>>(), vec![design.id]);
+        assert_eq!(graph.blocked_by(build.id).iter().map(|t| t.id).collect::<Vec<_>>(), vec![ship.id]);
+        assert!(graph.is_blocked(build.id) && graph.is_blocking(build.id));
+        assert!(!graph.is_blocked(design.id) && graph.is_blocking(design.id));
+        assert!(!graph.is_blocking(review.id));
+    }
+
+    #[test]
+    fn test_dot_and_json_export() {
+        let mut design = Task::new("Design \"v2\"".to_string());
+        design.project = Some("Web".to_string());
+        design.urgency = 12.0;
+        let mut review = Task::new("Review".to_string());
+        review.project = Some("Web".to_string());
+        review.complete();
+        let mut build = Task::new("Build".to_string());
+        build.project = Some("Web".to_string());
+        build.depends.extend([design.id, review.id]);
+        let mut chores = Task::new("Chores".to_string());
+        chores.depends.insert(build.id);
+        let tasks = vec![design.clone(), review.clone(), build.clone(), chores];
+        let graph = DependencyGraph::new(&tasks);
+        let web = TaskQuery::parse_filter("project:Web").unwrap();
+
+        let dot = graph.to_dot(&web);
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains(&format!(
+            "\"{}\" [label=\"Design \\\"v2\\\"\\nWeb\", fillcolor=\"#f4a6a6\"];",
+            design.id
+        )));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", design.id, build.id)));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\" [style=dashed];", review.id, build.id)));
+        // Chores is filtered out, and so is its edge
+        assert!(!dot.contains("Chores"));
+        assert_eq!(dot.matches("->").count(), 2);
+
+        let json = graph.to_json(&web);
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 3);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 2);
+        let build_node = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|n| n["description"] == "Build")
+            .unwrap();
+        assert_eq!(build_node["blocked"], true);
+    }
+}
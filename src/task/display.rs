@@ -0,0 +1,360 @@
+//! Detail view of a single task
+//!
+//! [`Task::to_display`] renders what `task <id> information` shows: every
+//! set field, annotations with their timestamps under the description,
+//! UDAs under the labels their schema gives them, and the urgency broken
+//! down into its terms, so frontends can show a detail pane that matches
+//! the CLI without assembling one themselves.
+//!
+//! ```text
+//! Name         Value
+//! ID           3
+//! Description  Pay rent
+//!                2025-05-31 12:00:00 Called landlord
+//! Status       Pending
+//! Project      Home
+//! Entered      2025-05-24 12:00:00 (1w)
+//! Due          2025-06-05 12:00:00
+//! Virtual tags ANNOTATED UNBLOCKED PENDING DUE PROJECT READY
+//! UUID         1b0c2a59-…
+//! Urgency      8.015
+//! Estimate     3
+//!
+//!     project      1 *      1 =      1
+//!     due      0.581 *     12 =  6.971
+//!     age      0.022 *      2 =  0.044
+//!                               ------
+//!                                8.015
+//! ```
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, Local, Utc};
+use unicode_width::UnicodeWidthStr;
+
+use crate::clock;
+use crate::config::Configuration;
+use crate::query::virtual_tags;
+use crate::task::decorated::format_relative;
+use crate::task::model::UdaValue;
+use crate::task::urgency::{UrgencyCoefficients, UrgencyTerm};
+use crate::task::{Task, TaskStatus, UdaSchema};
+
+/// Taskwarrior's `dateformat.info` default, in chrono's syntax
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How [`Task::to_display`] renders a task
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayOptions {
+    /// UDA labels
+    pub schema: UdaSchema,
+    pub urgency: UrgencyCoefficients,
+    /// chrono format for dates, shown in local time
+    pub date_format: String,
+    /// Whether to list the urgency terms after the fields
+    pub urgency_breakdown: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            schema: UdaSchema::default(),
+            urgency: UrgencyCoefficients::new(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            urgency_breakdown: true,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// UDA labels, urgency coefficients and `dateformat.info` from config
+    pub fn from_config(config: &Configuration) -> Self {
+        Self {
+            schema: UdaSchema::from_config(config),
+            urgency: UrgencyCoefficients::from_config(config),
+            date_format: config
+                .get("dateformat.info")
+                .map_or_else(|| DEFAULT_DATE_FORMAT.to_string(), |f| strftime(f)),
+            urgency_breakdown: true,
+        }
+    }
+
+    pub fn date_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.date_format = format.into();
+        self
+    }
+
+    pub fn urgency_breakdown(mut self, show: bool) -> Self {
+        self.urgency_breakdown = show;
+        self
+    }
+
+    fn date(&self, date: DateTime<Utc>) -> String {
+        date.with_timezone(&Local)
+            .format(&self.date_format)
+            .to_string()
+    }
+}
+
+/// Translate a Taskwarrior date format such as `Y-M-D H:N:S` into chrono's
+/// syntax; letters without a meaning are kept as written
+pub fn strftime(format: &str) -> String {
+    let mut out = String::new();
+    for c in format.chars() {
+        let spec = match c {
+            'Y' => "%Y",
+            'y' => "%y",
+            'M' => "%m",
+            'm' => "%-m",
+            'D' => "%d",
+            'd' => "%-d",
+            'H' => "%H",
+            'h' => "%-H",
+            'N' => "%M",
+            'n' => "%-M",
+            'S' => "%S",
+            's' => "%-S",
+            'A' => "%A",
+            'a' => "%a",
+            'B' => "%B",
+            'b' => "%b",
+            'V' => "%V",
+            'j' => "%j",
+            'J' => "%-j",
+            '%' => "%%",
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        out.push_str(spec);
+    }
+    out
+}
+
+impl Task {
+    /// The task as the CLI's `information` report shows it
+    pub fn to_display(&self, options: &DisplayOptions) -> String {
+        let now = clock::now();
+        let mut rows: Vec<(String, String)> = vec![("Name".into(), "Value".into())];
+        let mut row = |name: &str, value: String| rows.push((name.to_string(), value));
+
+        if let Some(id) = self.display_id {
+            row("ID", id.to_string());
+        }
+        let mut description = self.description.clone();
+        for annotation in &self.annotations {
+            let _ = write!(
+                description,
+                "\n  {} {}",
+                options.date(annotation.entry),
+                annotation.description
+            );
+        }
+        row("Description", description);
+        row("Status", status_name(self.status).to_string());
+        if let Some(project) = &self.project {
+            row("Project", project.clone());
+        }
+        if let Some(recur) = &self.recur {
+            row("Recurrence", recur.to_string());
+        }
+        if let Some(parent) = self.parent {
+            row("Parent task", parent.to_string());
+        }
+        if let Some(mask) = &self.mask {
+            row("Mask", mask.clone());
+        }
+        row(
+            "Entered",
+            format!(
+                "{} ({})",
+                options.date(self.entry),
+                format_relative(now - self.entry)
+            ),
+        );
+        for (name, date) in [
+            ("Waiting until", self.wait),
+            ("Scheduled", self.scheduled),
+            ("Start", self.start),
+            ("Due", self.due),
+            ("End", self.end),
+            ("Last modified", self.modified),
+        ] {
+            if let Some(date) = date {
+                row(name, options.date(date));
+            }
+        }
+        if !self.tags.is_empty() {
+            let mut tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+            tags.sort_unstable();
+            row("Tags", tags.join(" "));
+        }
+        row("Virtual tags", virtual_tags(self).join(" "));
+        if !self.depends.is_empty() {
+            let mut depends: Vec<String> = self.depends.iter().map(|id| id.to_string()).collect();
+            depends.sort();
+            row("This task depends on", depends.join("\n"));
+        }
+        row("UUID", self.id.to_string());
+        let terms = options.urgency.breakdown(self, now);
+        row("Urgency", number(options.urgency.score(self, now)));
+        if let Some(priority) = &self.priority {
+            row("Priority", priority.code().to_string());
+        }
+        if let Some(owner) = &self.owner {
+            row("Owner", owner.clone());
+        }
+
+        let mut udas: Vec<(&String, &UdaValue)> = self.udas.iter().collect();
+        udas.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in udas {
+            let label = match options.schema.get(name).and_then(|d| d.label.as_deref()) {
+                Some(label) => label.to_string(),
+                None if options.schema.get(name).is_some() => name.clone(),
+                // Taskwarrior marks values of undeclared UDAs
+                None => format!("[{name}]"),
+            };
+            let value = match value {
+                UdaValue::String(s) => s.clone(),
+                UdaValue::Number(n) => number(*n),
+                UdaValue::Date(d) => options.date(*d),
+            };
+            row(&label, value);
+        }
+
+        let mut out = render_rows(&rows);
+        if options.urgency_breakdown && !terms.is_empty() {
+            out.push('\n');
+            out.push_str(&render_urgency(&terms, options.urgency.score(self, now)));
+        }
+        out
+    }
+}
+
+fn status_name(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "Pending",
+        TaskStatus::Completed => "Completed",
+        TaskStatus::Deleted => "Deleted",
+        TaskStatus::Waiting => "Waiting",
+        TaskStatus::Recurring => "Recurring",
+    }
+}
+
+// Up to three decimals, without trailing zeros
+fn number(n: f64) -> String {
+    let s = format!("{n:.3}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+// Two columns; continuation lines of a value stay in the value column
+fn render_rows(rows: &[(String, String)]) -> String {
+    let width = rows.iter().map(|(name, _)| name.width()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (name, value) in rows {
+        let mut lines = value.lines();
+        let first = lines.next().unwrap_or("");
+        let pad = width - name.width();
+        let _ = writeln!(out, "{name}{:pad$} {first}", "");
+        for line in lines {
+            let _ = writeln!(out, "{:width$} {line}", "");
+        }
+    }
+    out
+}
+
+fn render_urgency(terms: &[UrgencyTerm], total: f64) -> String {
+    let width = terms.iter().map(|t| t.name.width()).max().unwrap_or(0);
+    let mut out = String::new();
+    for term in terms {
+        let pad = width - term.name.width();
+        let _ = writeln!(
+            out,
+            "    {}{:pad$} {:>6} * {:>6} = {:>6}",
+            term.name,
+            "",
+            number(term.factor),
+            number(term.coefficient),
+            number(term.value())
+        );
+    }
+    let indent = 4 + width + 1 + 6 + 3 + 6 + 3;
+    let _ = writeln!(out, "{:indent$}------", "");
+    let _ = writeln!(out, "{:indent$}{:>6}", "", number(total));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SequentialIds};
+    use crate::task::Annotation;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_information_view() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let _clock = clock::deterministic(FixedClock::new(now), SequentialIds::new());
+        let mut config = Configuration::default();
+        config.set("uda.estimate.type", "numeric");
+        config.set("uda.estimate.label", "Estimate");
+        let options = DisplayOptions::from_config(&config);
+        let local = |date: DateTime<Utc>| options.date(date);
+
+        let mut task = Task::new("Pay rent".to_string());
+        task.display_id = Some(3);
+        task.entry = now - Duration::days(8);
+        task.due = Some(now + Duration::days(4));
+        task.project = Some("Home".to_string());
+        task.annotations.push(Annotation {
+            entry: now - Duration::days(1),
+            description: "Called landlord".to_string(),
+        });
+        task.udas
+            .insert("estimate".to_string(), UdaValue::Number(3.0));
+        task.udas
+            .insert("legacy".to_string(), UdaValue::String("x".to_string()));
+
+        let expected = format!(
+            "Name         Value
+ID           3
+Description  Pay rent
+               {annotated} Called landlord
+Status       Pending
+Project      Home
+Entered      {entered} (1w)
+Due          {due}
+Virtual tags ANNOTATED UNBLOCKED PENDING DUE PROJECT READY
+UUID         00000000-0000-0000-0000-000000000001
+Urgency      8.015
+Estimate     3
+[legacy]     x
+
+    project      1 *      1 =      1
+    due      0.581 *     12 =  6.971
+    age      0.022 *      2 =  0.044
+                              ------
+                               8.015
+",
+            annotated = local(now - Duration::days(1)),
+            entered = local(task.entry),
+            due = local(now + Duration::days(4)),
+        );
+        assert_eq!(task.to_display(&options), expected);
+
+        let brief = task.to_display(&options.clone().urgency_breakdown(false));
+        assert!(brief.ends_with("[legacy]     x\n"));
+    }
+
+    #[test]
+    fn test_strftime() {
+        assert_eq!(strftime("Y-M-D H:N:S"), "%Y-%m-%d %H:%M:%S");
+        assert_eq!(strftime("d/m/y"), "%-d/%-m/%y");
+    }
+}
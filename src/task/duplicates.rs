@@ -0,0 +1,161 @@
+//! Duplicate detection
+//!
+//! Before creating a task, frontends can ask whether a pending task already
+//! says the same thing. [`similarity`] compares two descriptions after
+//! lowercasing them and dropping punctuation, taking the higher of
+//!
+//! - the normalized Levenshtein similarity of the two strings, which
+//!   catches typos (`Buy milk` / `Buy mlik`), and
+//! - the token overlap (Jaccard index of the word sets), which catches
+//!   reordering (`call mum re: flights` / `flights: call mum`).
+//!
+//! [`TaskManager::add_unless_similar`](crate::task::TaskManager::add_unless_similar)
+//! returns the matches instead of adding when any reach the threshold:
+//!
+//! ```text
+//! duplicates.threshold=0.8   # similarity from 0 to 1 (default 0.8)
+//! ```
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Configuration;
+use crate::error::ConfigError;
+use crate::task::Task;
+
+/// Similarity at or above which tasks count as duplicates
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// An existing task resembling a new description
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarTask {
+    pub task: Task,
+    /// From 0 (unrelated) to 1 (same words)
+    pub similarity: f64,
+}
+
+/// What [`TaskManager::add_unless_similar`](crate::task::TaskManager::add_unless_similar) did
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddOutcome {
+    /// No similar task; the new task was added
+    Added(Box<Task>),
+    /// Nothing was added; these pending tasks look the same, most similar
+    /// first
+    Similar(Vec<SimilarTask>),
+}
+
+/// Read `duplicates.threshold`
+pub fn threshold(config: &Configuration) -> Result<f64, ConfigError> {
+    let Some(raw) = config.get("duplicates.threshold") else {
+        return Ok(DEFAULT_THRESHOLD);
+    };
+    raw.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|t| (0.0..=1.0).contains(t))
+        .ok_or_else(|| ConfigError::InvalidValue {
+            key: "duplicates.threshold".to_string(),
+            value: raw.to_string(),
+            expected: "a number from 0 to 1".to_string(),
+        })
+}
+
+/// Similarity of two descriptions from 0 to 1
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize(a), normalize(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    levenshtein_similarity(&a, &b).max(token_overlap(&a, &b))
+}
+
+/// `candidates` at least `threshold` similar to `description`, most
+/// similar first
+pub fn find_similar(description: &str, candidates: Vec<Task>, threshold: f64) -> Vec<SimilarTask> {
+    let mut similar: Vec<SimilarTask> = candidates
+        .into_iter()
+        .filter_map(|task| {
+            let similarity = similarity(description, &task.description);
+            (similarity >= threshold).then_some(SimilarTask { task, similarity })
+        })
+        .collect();
+    similar.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    similar
+}
+
+/// Lowercase words separated by single spaces
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn levenshtein_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // Single-row dynamic programme over edit distances
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
+
+fn token_overlap(a: &str, b: &str) -> f64 {
+    let a: BTreeSet<&str> = a.split(' ').filter(|w| !w.is_empty()).collect();
+    let b: BTreeSet<&str> = b.split(' ').filter(|w| !w.is_empty()).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Buy milk", "buy milk!"), 1.0);
+        assert_eq!(similarity("flights: call mum", "Call mum re flights"), 0.75);
+        assert!(similarity("Buy milk", "Buy mlik") >= 0.75);
+        assert!(similarity("Buy milk", "File taxes") < 0.3);
+        assert_eq!(levenshtein_similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+    }
+
+    #[test]
+    fn test_find_similar_and_threshold() {
+        let tasks = vec![
+            Task::new("Renew passport".to_string()),
+            Task::new("Renew pasport".to_string()),
+            Task::new("Water plants".to_string()),
+        ];
+        let similar = find_similar("renew passport", tasks, DEFAULT_THRESHOLD);
+        let found: Vec<&str> = similar
+            .iter()
+            .map(|s| s.task.description.as_str())
+            .collect();
+        assert_eq!(found, ["Renew passport", "Renew pasport"]);
+
+        let mut config = Configuration::default();
+        assert_eq!(threshold(&config).unwrap(), DEFAULT_THRESHOLD);
+        config.set("duplicates.threshold", "0.6");
+        assert_eq!(threshold(&config).unwrap(), 0.6);
+        config.set("duplicates.threshold", "2");
+        assert!(threshold(&config).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Estimates and effort per project
+//!
+//! Tasks carry their estimated and spent work in the `estimate` and
+//! `effort` duration UDAs (see [`Task::estimate`] and [`Task::effort`]).
+//! [`effort_by_project`] sums both per project and sets them against time
+//! recorded by a time tracker, such as Timewarrior intervals tagged with
+//! the task, to show where plans and reality drift apart.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Duration;
+use uuid::Uuid;
+
+use crate::reports::builtin::NO_GROUP;
+use crate::task::Task;
+
+/// Estimated, recorded and tracked work for one project
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectEffort {
+    /// Tasks in the project
+    pub tasks: usize,
+    /// Tasks with an estimate
+    pub estimated_tasks: usize,
+    /// Sum of `estimate`
+    pub estimate: Duration,
+    /// Sum of `effort`
+    pub effort: Duration,
+    /// Sum of the tracked time supplied for the project's tasks
+    pub tracked: Duration,
+}
+
+impl Default for ProjectEffort {
+    fn default() -> Self {
+        Self {
+            tasks: 0,
+            estimated_tasks: 0,
+            estimate: Duration::zero(),
+            effort: Duration::zero(),
+            tracked: Duration::zero(),
+        }
+    }
+}
+
+impl ProjectEffort {
+    /// Tracked time beyond the estimate; negative while under it
+    pub fn overrun(&self) -> Duration {
+        self.tracked - self.estimate
+    }
+
+    /// Estimate not yet covered by recorded effort, never negative
+    pub fn remaining(&self) -> Duration {
+        (self.estimate - self.effort).max(Duration::zero())
+    }
+}
+
+/// Sum estimates and effort of `tasks` per project (tasks without a project
+/// under `(none)`), together with the `tracked` time recorded per task
+pub fn effort_by_project(
+    tasks: &[Task],
+    tracked: &HashMap<Uuid, Duration>,
+) -> BTreeMap<String, ProjectEffort> {
+    let mut projects: BTreeMap<String, ProjectEffort> = BTreeMap::new();
+    for task in tasks {
+        let project = task.project.clone().unwrap_or_else(|| NO_GROUP.to_string());
+        let entry = projects.entry(project).or_default();
+        entry.tasks += 1;
+        if let Some(estimate) = task.estimate() {
+            entry.estimated_tasks += 1;
+            entry.estimate += estimate;
+        }
+        if let Some(effort) = task.effort() {
+            entry.effort += effort;
+        }
+        if let Some(time) = tracked.get(&task.id) {
+            entry.tracked += *time;
+        }
+    }
+    projects
+}
+
+/// Compact form for report columns, e.g. "1d4h" or "45min"
+pub fn format_short(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes == 0 {
+        return "0min".to_string();
+    }
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.unsigned_abs();
+    let (days, hours, minutes) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    let mut out = sign.to_string();
+    for (n, unit) in [(days, "d"), (hours, "h"), (minutes, "min")] {
+        if n > 0 {
+            out.push_str(&format!("{n}{unit}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effort_by_project() {
+        let mut api = Task::new("API".to_string());
+        api.project = Some("Web".to_string());
+        api.set_estimate(Some(Duration::hours(4)));
+        api.set_effort(Some(Duration::hours(1)));
+        let mut ui = Task::new("UI".to_string());
+        ui.project = Some("Web".to_string());
+        // Older Taskwarrior data stores durations as seconds
+        ui.udas
+            .insert("estimate".to_string(), crate::task::model::UdaValue::Number(7200.0));
+        let chores = Task::new("Chores".to_string());
+        let tracked = HashMap::from([(api.id, Duration::hours(5)), (chores.id, Duration::minutes(30))]);
+
+        let projects = effort_by_project(&[api.clone(), ui, chores], &tracked);
+        let web = &projects["Web"];
+        assert_eq!(web.tasks, 2);
+        assert_eq!(web.estimated_tasks, 2);
+        assert_eq!(web.estimate, Duration::hours(6));
+        assert_eq!(web.effort, Duration::hours(1));
+        assert_eq!(web.remaining(), Duration::hours(5));
+        assert_eq!(web.overrun(), Duration::hours(-1));
+        assert_eq!(projects[NO_GROUP].tracked, Duration::minutes(30));
+
+        assert_eq!(api.udas["estimate"], crate::task::model::UdaValue::String("PT4H".to_string()));
+        assert_eq!(format_short(Duration::minutes(1710)), "1d4h30min");
+        assert_eq!(format_short(Duration::minutes(-45)), "-45min");
+    }
+}
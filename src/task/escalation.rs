@@ -0,0 +1,422 @@
+//! Deadline escalation
+//!
+//! An [`EscalationPolicy`] is a set of named rules that change tasks as
+//! their deadline nears or passes, so automation that used to need an
+//! external hook script lives in config:
+//!
+//! ```text
+//! escalation.client.filter=+client        # which tasks (Taskwarrior filter; default all)
+//! escalation.client.overdue=2d            # fire 2 days after due...
+//! escalation.client.due.within=1d         # ...or instead 1 day before due
+//! escalation.client.priority=H            # set the priority
+//! escalation.client.tags=+escalated -someday
+//! escalation.client.annotation=Escalated: client deadline missed
+//! ```
+//!
+//! Only pending tasks with a due date are considered. A rule applies when
+//! it would change the task, so a task escalated once is left alone on
+//! later runs: it already has the priority and tags, and the annotation is
+//! not added twice. Rules apply in name order, each on top of the previous.
+//!
+//! The task manager evaluates the policy during maintenance; see
+//! [`DefaultTaskManager::escalate`](crate::task::manager::DefaultTaskManager::escalate).
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::date::relative::parse_duration;
+use crate::error::ConfigError;
+use crate::query::TaskQuery;
+use crate::storage::operation_batch::{compute_update_ops, Operation};
+use crate::task::{Annotation, Task, TaskStatus};
+
+/// When a rule fires, relative to the due date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscalationTrigger {
+    /// At least this long past due
+    Overdue(Duration),
+    /// Due within this long, or already overdue
+    DueWithin(Duration),
+}
+
+impl EscalationTrigger {
+    /// Whether a task due at `due` has reached the trigger at `now`
+    pub fn is_reached(&self, due: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        match *self {
+            // Offsets past the representable dates never, or always, fire
+            EscalationTrigger::Overdue(after) => {
+                due.checked_add_signed(after).is_some_and(|at| now >= at)
+            }
+            EscalationTrigger::DueWithin(before) => {
+                due.checked_sub_signed(before).is_none_or(|at| now >= at)
+            }
+        }
+    }
+}
+
+/// One named escalation rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscalationRule {
+    pub name: String,
+    /// Tasks the rule applies to; None means every pending task with a due date
+    pub filter: Option<TaskQuery>,
+    pub trigger: EscalationTrigger,
+    /// Priority code to set
+    pub priority: Option<String>,
+    pub add_tags: BTreeSet<String>,
+    pub remove_tags: BTreeSet<String>,
+    /// Annotation to add, once
+    pub annotation: Option<String>,
+}
+
+impl EscalationRule {
+    /// A rule without actions; add them with the `with_*` methods
+    pub fn new<S: Into<String>>(name: S, trigger: EscalationTrigger) -> Self {
+        Self {
+            name: name.into(),
+            filter: None,
+            trigger,
+            priority: None,
+            add_tags: BTreeSet::new(),
+            remove_tags: BTreeSet::new(),
+            annotation: None,
+        }
+    }
+
+    /// Only escalate tasks matching `query`
+    pub fn with_filter(mut self, query: TaskQuery) -> Self {
+        self.filter = Some(query);
+        self
+    }
+
+    pub fn with_priority<S: Into<String>>(mut self, code: S) -> Self {
+        self.priority = Some(code.into());
+        self
+    }
+
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.add_tags.insert(tag.into());
+        self
+    }
+
+    pub fn without_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.remove_tags.insert(tag.into());
+        self
+    }
+
+    pub fn with_annotation<S: Into<String>>(mut self, text: S) -> Self {
+        self.annotation = Some(text.into());
+        self
+    }
+
+    /// Whether the rule's filter and trigger select `task` at `now`
+    pub fn selects(&self, task: &Task, now: DateTime<Utc>) -> bool {
+        task.status == TaskStatus::Pending
+            && task
+                .due
+                .is_some_and(|due| self.trigger.is_reached(due, now))
+            && self.filter.as_ref().is_none_or(|query| query.matches(task))
+    }
+
+    /// Apply the rule's actions to `task`, returning whether anything changed
+    fn apply(&self, task: &mut Task, now: DateTime<Utc>) -> bool {
+        let mut changed = false;
+        if let Some(code) = &self.priority {
+            if task.priority_code() != Some(code.as_str()) {
+                task.set_priority_code(Some(code));
+                changed = true;
+            }
+        }
+        for tag in &self.add_tags {
+            changed |= task.tags.insert(tag.clone());
+        }
+        for tag in &self.remove_tags {
+            changed |= task.tags.remove(tag);
+        }
+        if let Some(text) = &self.annotation {
+            if !task.annotations.iter().any(|a| a.description == *text) {
+                task.annotations
+                    .push(Annotation::with_timestamp(text.clone(), now));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn from_config(config: &Configuration, name: &str) -> Result<Self, ConfigError> {
+        let key = |suffix: &str| format!("escalation.{name}.{suffix}");
+        let duration = |suffix: &str| -> Result<Option<Duration>, ConfigError> {
+            let key = key(suffix);
+            match config.get(&key) {
+                Some(raw) => parse_duration(raw)
+                    .map(Some)
+                    .map_err(|_| ConfigError::InvalidValue {
+                        key,
+                        value: raw.to_string(),
+                        expected: "a duration such as '2d' or '12h'".to_string(),
+                    }),
+                None => Ok(None),
+            }
+        };
+
+        let trigger = match (duration("overdue")?, duration("due.within")?) {
+            (Some(after), None) => EscalationTrigger::Overdue(after),
+            (None, Some(before)) => EscalationTrigger::DueWithin(before),
+            _ => {
+                return Err(ConfigError::InvalidValue {
+                    key: key("overdue"),
+                    value: config.get(&key("overdue")).cloned().unwrap_or_default(),
+                    expected: format!("exactly one of it and '{}'", key("due.within")),
+                })
+            }
+        };
+
+        let mut rule = Self::new(name, trigger);
+        if let Some(filter) = config.get(&key("filter")) {
+            let query = TaskQuery::parse_filter(filter).map_err(|e| ConfigError::InvalidValue {
+                key: key("filter"),
+                value: filter.to_string(),
+                expected: format!("a Taskwarrior filter ({e})"),
+            })?;
+            rule.filter = Some(query);
+        }
+        if let Some(code) = config.get(&key("priority")) {
+            let code = code.trim();
+            if !config.priority_scheme().is_valid(code) {
+                return Err(ConfigError::InvalidValue {
+                    key: key("priority"),
+                    value: code.to_string(),
+                    expected: "a priority code of the configured scheme".to_string(),
+                });
+            }
+            rule.priority = Some(code.to_string());
+        }
+        if let Some(tags) = config.get(&key("tags")) {
+            for term in tags.split_whitespace() {
+                match (term.strip_prefix('+'), term.strip_prefix('-')) {
+                    (Some(tag), _) if !tag.is_empty() => rule.add_tags.insert(tag.to_string()),
+                    (_, Some(tag)) if !tag.is_empty() => rule.remove_tags.insert(tag.to_string()),
+                    _ => {
+                        return Err(ConfigError::InvalidValue {
+                            key: key("tags"),
+                            value: tags.to_string(),
+                            expected: "tags prefixed with '+' or '-', e.g. '+escalated -someday'"
+                                .to_string(),
+                        })
+                    }
+                };
+            }
+        }
+        rule.annotation = config
+            .get(&key("annotation"))
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty());
+        Ok(rule)
+    }
+}
+
+/// A task changed (or, in a dry run, selected) by escalation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Escalation {
+    pub id: Uuid,
+    pub description: String,
+    /// Rules that changed the task, in the order they applied
+    pub rules: Vec<String>,
+    /// The task after escalation
+    pub task: Task,
+    /// Operations taking the stored task to the escalated one
+    pub operations: Vec<Operation>,
+}
+
+/// What an escalation run changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EscalationReport {
+    pub escalations: Vec<Escalation>,
+    /// Tasks left alone because they changed after the run loaded them
+    pub skipped: Vec<Uuid>,
+    /// True when nothing was actually saved
+    pub dry_run: bool,
+}
+
+impl EscalationReport {
+    /// Number of escalated tasks
+    pub fn len(&self) -> usize {
+        self.escalations.len()
+    }
+
+    /// Whether no task was escalated
+    pub fn is_empty(&self) -> bool {
+        self.escalations.is_empty()
+    }
+
+    /// All operations as one batch behind a single undo point
+    pub fn batch(&self) -> Vec<Operation> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        std::iter::once(Operation::UndoPoint)
+            .chain(
+                self.escalations
+                    .iter()
+                    .flat_map(|e| e.operations.iter().cloned()),
+            )
+            .collect()
+    }
+}
+
+/// Escalation rules read from `escalation.<name>.*`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EscalationPolicy {
+    pub rules: Vec<EscalationRule>,
+}
+
+impl EscalationPolicy {
+    /// Read every `escalation.<name>.*` rule, ordered by name
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let names: BTreeSet<&str> = config
+            .settings
+            .keys()
+            .filter_map(|key| key.strip_prefix("escalation."))
+            .filter_map(|rest| rest.split_once('.').map(|(name, _)| name))
+            .collect();
+        let rules = names
+            .into_iter()
+            .map(|name| EscalationRule::from_config(config, name))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Add a rule after the existing ones
+    pub fn with_rule(mut self, rule: EscalationRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Whether the policy has any rules
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Escalations due for `tasks` at `now`, without saving anything
+    pub fn evaluate(&self, tasks: &[Task], now: DateTime<Utc>) -> Vec<Escalation> {
+        let mut escalations = Vec::new();
+        for task in tasks {
+            let mut escalated = task.clone();
+            let mut rules = Vec::new();
+            for rule in &self.rules {
+                if rule.selects(&escalated, now) && rule.apply(&mut escalated, now) {
+                    rules.push(rule.name.clone());
+                }
+            }
+            if !rules.is_empty() {
+                escalations.push(Escalation {
+                    id: task.id,
+                    description: task.description.clone(),
+                    rules,
+                    operations: compute_update_ops(task, &escalated),
+                    task: escalated,
+                });
+            }
+        }
+        escalations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Priority;
+    use chrono::TimeZone;
+
+    fn client_config() -> Configuration {
+        let mut config = Configuration::default();
+        config.set("escalation.client.filter", "+client");
+        config.set("escalation.client.overdue", "2d");
+        config.set("escalation.client.priority", "H");
+        config.set("escalation.client.tags", "+escalated -someday");
+        config.set("escalation.client.annotation", "Escalated: overdue");
+        config
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut config = client_config();
+        config.set("escalation.soon.due.within", "1d");
+        config.set("escalation.soon.tags", "+soon");
+
+        let policy = EscalationPolicy::from_config(&config).unwrap();
+        let names: Vec<&str> = policy.rules.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["client", "soon"]);
+        let client = &policy.rules[0];
+        assert_eq!(
+            client.trigger,
+            EscalationTrigger::Overdue(Duration::days(2))
+        );
+        assert_eq!(client.priority.as_deref(), Some("H"));
+        assert!(client.add_tags.contains("escalated") && client.remove_tags.contains("someday"));
+        assert_eq!(
+            policy.rules[1].trigger,
+            EscalationTrigger::DueWithin(Duration::days(1))
+        );
+
+        config.set("escalation.soon.overdue", "1d");
+        assert!(EscalationPolicy::from_config(&config).is_err());
+        config.unset("escalation.soon.overdue");
+        config.set("escalation.client.priority", "X");
+        assert!(EscalationPolicy::from_config(&config).is_err());
+        config.set("escalation.client.priority", "H");
+        config.set("escalation.client.tags", "escalated");
+        assert!(EscalationPolicy::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_triggers_beyond_representable_dates() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 10, 12, 0, 0).unwrap();
+        let far = Duration::days(99_999_999_999);
+        assert!(!EscalationTrigger::Overdue(far).is_reached(now, now));
+        assert!(EscalationTrigger::DueWithin(far).is_reached(now, now));
+    }
+
+    #[test]
+    fn test_evaluate_escalates_once() {
+        let policy = EscalationPolicy::from_config(&client_config()).unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 6, 10, 12, 0, 0).unwrap();
+        let mut task = Task::new("Send invoice".to_string());
+        task.tags.insert("client".to_string());
+        task.tags.insert("someday".to_string());
+        task.due = Some(now - Duration::days(1));
+        let mut other = Task::new("Water plants".to_string());
+        other.due = Some(now - Duration::days(5));
+
+        // One day overdue is not enough
+        assert!(policy
+            .evaluate(&[task.clone(), other.clone()], now)
+            .is_empty());
+
+        let later = now + Duration::days(1);
+        let escalations = policy.evaluate(&[task.clone(), other], later);
+        assert_eq!(escalations.len(), 1);
+        let escalation = &escalations[0];
+        assert_eq!(escalation.rules, ["client"]);
+        assert_eq!(escalation.task.priority, Some(Priority::High));
+        assert!(escalation.task.tags.contains("escalated"));
+        assert!(!escalation.task.tags.contains("someday"));
+        assert_eq!(escalation.task.annotations.len(), 1);
+        assert!(escalation.operations.contains(&Operation::AddTag {
+            uuid: task.id,
+            tag: "escalated".to_string()
+        }));
+        assert!(escalation
+            .operations
+            .iter()
+            .any(|op| matches!(op, Operation::Update { key, .. } if key == "priority")));
+
+        // Already escalated: nothing left to change
+        assert!(policy
+            .evaluate(std::slice::from_ref(&escalation.task), later)
+            .is_empty());
+    }
+}
@@ -5,15 +5,39 @@
 
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
 use uuid::Uuid;
 
+use crate::clock;
 use crate::config::{Configuration, ConfigurationProvider};
-use crate::error::{TaskError, ValidationError};
+use crate::error::{ErrorContext, TaskError, ValidationError};
 use crate::hooks::HookSystem;
-use crate::query::TaskQuery;
+use crate::io::import::{DefaultTaskImporter, ImportConfig, ImportPreview, ImportResult};
+use crate::query::aggregate::{GroupQuery, TaskGroup};
+use crate::query::collation::Collation;
+use crate::query::search::{Search, SearchHit};
+use crate::query::{FilterMode, MatchTrace, TaskPredicate, TaskQuery};
 use crate::storage::StorageBackend;
+use crate::storage::operation_batch::{build_delete_batch, build_purge_batch, build_save_batch};
 use crate::sync::SyncManager;
+use crate::task::audit::{AuditConfig, AuditLog};
+use crate::task::checklist::{Checklist, CHECKLIST_UDA};
+use crate::task::completion::{
+    unblocked_dependents, ChainStart, ChainedCompletion, ConfirmationHandler,
+};
+use crate::task::decorated::{decorate, DecoratedTask};
+use crate::task::duplicates::{find_similar, AddOutcome, SimilarTask};
+use crate::task::escalation::{EscalationPolicy, EscalationReport};
 use crate::task::model::UdaValue;
+use crate::task::metrics::Metrics;
+use crate::task::reschedule::{business_days, next_business_day, Shift};
+use crate::task::provenance::{count_push, stamp};
+use crate::task::retention::{PurgeReport, PurgedTask, RetentionPolicy};
+use crate::task::snooze::{Snooze, SnoozeTimes};
+use crate::task::subtasks::{all_done, parent_of, SubtaskRollup, PARENT_UDA};
+use crate::task::tags::{TagRegistry, TagUsage};
+use crate::task::urgency::UrgencyCoefficients;
 use crate::task::{Task, TaskStatus};
 
 /// Minimal ProjectFilter definition to avoid corrupted filter.rs
@@ -70,6 +94,26 @@ pub trait TaskManager: ConfigurationProvider {
     /// Complete a task
     fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError>;
 
+    /// Remove the annotation matching `text` (exact match first, then the
+    /// first annotation containing it), like Taskwarrior's `denotate`
+    fn denotate(&mut self, id: Uuid, text: &str) -> Result<Task, TaskError> {
+        let task = self.get_task(id)?.ok_or(TaskError::NotFound { id })?;
+        let index = crate::task::annotation::find_annotation(&task.annotations, text).ok_or_else(|| {
+            TaskError::InvalidData {
+                message: format!("No annotation matching '{text}'"),
+            }
+        })?;
+        let mut annotations = task.annotations;
+        annotations.remove(index);
+        self.update_task(
+            id,
+            TaskUpdate {
+                annotations: Some(annotations),
+                ..Default::default()
+            },
+        )
+    }
+
     /// Query tasks with filters
     fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError>;
 
@@ -79,6 +123,14 @@ pub trait TaskManager: ConfigurationProvider {
     /// Get all completed tasks  
     fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError>;
 
+    /// Get all tasks marked deleted but still kept (the trash). Tasks
+    /// removed with [`delete_task`](Self::delete_task) are gone for good.
+    fn deleted_tasks(&mut self) -> Result<Vec<Task>, TaskError>;
+
+    /// Take a task out of the trash: back to pending with `end` cleared.
+    /// Runs the `restore` operation hooks and the modify hook.
+    fn restore_task(&mut self, id: Uuid) -> Result<Task, TaskError>;
+
     /// Count tasks matching query
     fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError>;
 
@@ -87,6 +139,354 @@ pub trait TaskManager: ConfigurationProvider {
 
     /// Validate all tasks in storage
     fn validate_all(&self) -> Result<ValidationReport, TaskError>;
+
+    /// Messages for the user from operations since the last call, oldest
+    /// first: hook feedback, then notes such as the `nag` message
+    fn take_feedback(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Add `task` as a subtask of `parent_id`: the child records its parent
+    /// in the [`PARENT_UDA`] UDA and the parent depends on the child
+    fn add_subtask(&mut self, parent_id: Uuid, task: NewTask) -> Result<Task, TaskError> {
+        let parent = self
+            .get_task(parent_id)?
+            .ok_or(TaskError::NotFound { id: parent_id })?;
+        let child = self.add_task(task.description)?;
+        let attributes = task
+            .attributes
+            .set_uda_value(PARENT_UDA, UdaValue::String(parent_id.to_string()));
+        let child = self.update_task(child.id, attributes)?;
+
+        let mut depends = parent.depends;
+        depends.insert(child.id);
+        self.update_task(
+            parent_id,
+            TaskUpdate {
+                depends: Some(depends),
+                ..Default::default()
+            },
+        )?;
+        Ok(child)
+    }
+
+    /// Pending tasks, in any context, whose description is at least
+    /// `duplicates.threshold` similar to `description`, most similar first
+    fn similar_tasks(&mut self, description: &str) -> Result<Vec<SimilarTask>, TaskError> {
+        let threshold = crate::task::duplicates::threshold(self.config())?;
+        let pending = self.query_tasks(&TaskQuery {
+            status: Some(TaskStatus::Pending),
+            filter_mode: Some(FilterMode::IgnoreContext),
+            ..Default::default()
+        })?;
+        Ok(find_similar(description, pending, threshold))
+    }
+
+    /// Add `task` unless [`similar_tasks`](Self::similar_tasks) finds a
+    /// likely duplicate, in which case nothing is added and the matches are
+    /// returned for the user to choose from
+    fn add_unless_similar(&mut self, task: NewTask) -> Result<AddOutcome, TaskError> {
+        let similar = self.similar_tasks(&task.description)?;
+        if !similar.is_empty() {
+            return Ok(AddOutcome::Similar(similar));
+        }
+        let added = self.add_task(task.description)?;
+        if task.attributes.is_empty() {
+            return Ok(AddOutcome::Added(Box::new(added)));
+        }
+        let added = self.update_task(added.id, task.attributes)?;
+        Ok(AddOutcome::Added(Box::new(added)))
+    }
+
+    /// Subtasks of `parent_id` in any status, oldest first, regardless of
+    /// the active context
+    fn subtasks(&mut self, parent_id: Uuid) -> Result<Vec<Task>, TaskError> {
+        let query = TaskQuery {
+            filter_mode: Some(FilterMode::IgnoreContext),
+            custom_filters: vec![TaskPredicate::new(move |t| parent_of(t) == Some(parent_id))],
+            ..Default::default()
+        };
+        let mut children = self.query_tasks(&query)?;
+        children.sort_by_key(|t| (t.entry, t.id));
+        Ok(children)
+    }
+
+    /// Move the due date of every task matching `query` by `shift`, keeping
+    /// new dates on business days if `reschedule.businessdays` is set.
+    /// Returns the updated tasks.
+    fn reschedule_where(&mut self, query: &TaskQuery, shift: Shift) -> Result<Vec<Task>, TaskError> {
+        let business_days = business_days(self.config());
+        let mut updated = Vec::new();
+        for task in self.query_tasks(query)? {
+            let mut due = shift.apply(task.due).map_err(|e| TaskError::DateParsing {
+                message: e.to_string(),
+            })?;
+            if business_days {
+                due = next_business_day(due);
+            }
+            updated.push(self.update_task(task.id, TaskUpdate::new().due(due))?);
+        }
+        Ok(updated)
+    }
+
+    /// Query tasks together with their urgency, virtual tags, blockers,
+    /// display ID and relative due date, for rendering rows in one pass
+    fn query_decorated(&mut self, query: &TaskQuery) -> Result<Vec<DecoratedTask>, TaskError> {
+        let tasks = self.query_tasks(query)?;
+        // Blockers may lie outside the query, so look them up among all tasks
+        let all = if tasks.iter().any(|t| !t.depends.is_empty()) {
+            self.query_tasks(&TaskQuery {
+                filter_mode: Some(FilterMode::IgnoreContext),
+                ..Default::default()
+            })?
+        } else {
+            Vec::new()
+        };
+        let urgency = UrgencyCoefficients::from_config(self.config());
+        Ok(decorate(tasks, &all, &urgency, clock::now()))
+    }
+
+    /// Tasks matching `query`, grouped and filtered by `groups`'
+    /// aggregate conditions (see [`crate::query::aggregate`])
+    fn query_groups(
+        &mut self,
+        query: &TaskQuery,
+        groups: &GroupQuery,
+    ) -> Result<Vec<TaskGroup>, TaskError> {
+        Ok(groups.groups(&self.query_tasks(query)?))
+    }
+
+    /// Tasks matching `query` that contain every word of `text`, most
+    /// relevant first (see [`crate::query::search`])
+    fn search(&mut self, text: &str, query: &TaskQuery) -> Result<Vec<SearchHit>, TaskError> {
+        let search = Search::from_config(text, self.config());
+        if search.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tasks = self.query_tasks(query)?;
+        let urgency = UrgencyCoefficients::from_config(self.config());
+        Ok(search.rank(tasks, &urgency, clock::now()))
+    }
+
+    /// Hide a task until `until` by setting its `wait` date, and its
+    /// `scheduled` date too with `snooze.scheduled`; presets are resolved
+    /// with [`SnoozeTimes::from_config`]
+    fn snooze(&mut self, id: Uuid, until: Snooze) -> Result<Task, TaskError> {
+        let times = SnoozeTimes::from_config(self.config())?;
+        let until = times.resolve(until, clock::now())?;
+        let mut update = TaskUpdate::new().wait(until);
+        if times.scheduled {
+            update = update.scheduled(until);
+        }
+        self.update_task(id, update)
+    }
+
+    /// Append an open item to the task's checklist
+    fn add_checklist_item(&mut self, id: Uuid, text: &str) -> Result<Task, TaskError> {
+        edit_checklist(self, id, |checklist| {
+            checklist.add(text);
+            Ok(())
+        })
+    }
+
+    /// Flip the done flag of checklist item `index` (0-based)
+    fn toggle_checklist_item(&mut self, id: Uuid, index: usize) -> Result<Task, TaskError> {
+        edit_checklist(self, id, |checklist| checklist.toggle(index).map(|_| ()))
+    }
+
+    /// Remove checklist item `index` (0-based); removing the last item
+    /// removes the checklist
+    fn remove_checklist_item(&mut self, id: Uuid, index: usize) -> Result<Task, TaskError> {
+        edit_checklist(self, id, |checklist| checklist.remove(index).map(|_| ()))
+    }
+}
+
+/// Apply `edit` to the checklist of task `id` and save it
+fn edit_checklist<M, F>(manager: &mut M, id: Uuid, edit: F) -> Result<Task, TaskError>
+where
+    M: TaskManager + ?Sized,
+    F: FnOnce(&mut Checklist) -> Result<(), TaskError>,
+{
+    let task = manager.get_task(id)?.ok_or(TaskError::NotFound { id })?;
+    let mut checklist = Checklist::from_task(&task);
+    edit(&mut checklist)?;
+    let update = match checklist.to_uda() {
+        Some(value) => TaskUpdate::new().set_uda_value(CHECKLIST_UDA, value),
+        None => TaskUpdate::new().unset_uda(CHECKLIST_UDA),
+    };
+    manager.update_task(id, update)
+}
+
+/// A task manager chosen at runtime, e.g. file storage, TaskChampion or a
+/// test double behind one pointer. Boxed managers are task managers
+/// themselves, so they also fit generic wrappers such as
+/// [`CachedTaskManager`](crate::task::CachedTaskManager).
+pub type DynTaskManager = Box<dyn TaskManager>;
+
+impl<M: ConfigurationProvider + ?Sized> ConfigurationProvider for Box<M> {
+    fn config(&self) -> &Configuration {
+        (**self).config()
+    }
+
+    fn config_mut(&mut self) -> &mut Configuration {
+        (**self).config_mut()
+    }
+
+    fn reload_config(&mut self) -> Result<(), TaskError> {
+        (**self).reload_config()
+    }
+}
+
+// Every method forwards, including the provided ones, so overrides in the
+// boxed manager are kept
+impl<M: TaskManager + ?Sized> TaskManager for Box<M> {
+    fn add_task(&mut self, description: String) -> Result<Task, TaskError> {
+        (**self).add_task(description)
+    }
+
+    fn add_task_with_options(
+        &mut self,
+        description: String,
+        options: AddOptions,
+    ) -> Result<Task, TaskError> {
+        (**self).add_task_with_options(description, options)
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        (**self).get_task(id)
+    }
+
+    fn update_task(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+        (**self).update_task(id, updates)
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        (**self).delete_task(id)
+    }
+
+    fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        (**self).complete_task(id)
+    }
+
+    fn denotate(&mut self, id: Uuid, text: &str) -> Result<Task, TaskError> {
+        (**self).denotate(id, text)
+    }
+
+    fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
+        (**self).query_tasks(query)
+    }
+
+    fn pending_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        (**self).pending_tasks()
+    }
+
+    fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        (**self).completed_tasks()
+    }
+
+    fn deleted_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        (**self).deleted_tasks()
+    }
+
+    fn restore_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        (**self).restore_task(id)
+    }
+
+    fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError> {
+        (**self).count_tasks(query)
+    }
+
+    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+        (**self).sync()
+    }
+
+    fn validate_all(&self) -> Result<ValidationReport, TaskError> {
+        (**self).validate_all()
+    }
+
+    fn take_feedback(&mut self) -> Vec<String> {
+        (**self).take_feedback()
+    }
+
+    fn add_subtask(&mut self, parent_id: Uuid, task: NewTask) -> Result<Task, TaskError> {
+        (**self).add_subtask(parent_id, task)
+    }
+
+    fn similar_tasks(&mut self, description: &str) -> Result<Vec<SimilarTask>, TaskError> {
+        (**self).similar_tasks(description)
+    }
+
+    fn add_unless_similar(&mut self, task: NewTask) -> Result<AddOutcome, TaskError> {
+        (**self).add_unless_similar(task)
+    }
+
+    fn subtasks(&mut self, parent_id: Uuid) -> Result<Vec<Task>, TaskError> {
+        (**self).subtasks(parent_id)
+    }
+
+    fn reschedule_where(&mut self, query: &TaskQuery, shift: Shift) -> Result<Vec<Task>, TaskError> {
+        (**self).reschedule_where(query, shift)
+    }
+
+    fn query_decorated(&mut self, query: &TaskQuery) -> Result<Vec<DecoratedTask>, TaskError> {
+        (**self).query_decorated(query)
+    }
+
+    fn query_groups(
+        &mut self,
+        query: &TaskQuery,
+        groups: &GroupQuery,
+    ) -> Result<Vec<TaskGroup>, TaskError> {
+        (**self).query_groups(query, groups)
+    }
+
+    fn search(&mut self, text: &str, query: &TaskQuery) -> Result<Vec<SearchHit>, TaskError> {
+        (**self).search(text, query)
+    }
+
+    fn snooze(&mut self, id: Uuid, until: Snooze) -> Result<Task, TaskError> {
+        (**self).snooze(id, until)
+    }
+
+    fn add_checklist_item(&mut self, id: Uuid, text: &str) -> Result<Task, TaskError> {
+        (**self).add_checklist_item(id, text)
+    }
+
+    fn toggle_checklist_item(&mut self, id: Uuid, index: usize) -> Result<Task, TaskError> {
+        (**self).toggle_checklist_item(id, index)
+    }
+
+    fn remove_checklist_item(&mut self, id: Uuid, index: usize) -> Result<Task, TaskError> {
+        (**self).remove_checklist_item(id, index)
+    }
+}
+
+/// A task to add: its description plus attributes applied after creation
+#[derive(Debug, Default, Clone)]
+pub struct NewTask {
+    pub description: String,
+    pub attributes: TaskUpdate,
+}
+
+impl NewTask {
+    pub fn new<S: Into<String>>(description: S) -> Self {
+        Self {
+            description: description.into(),
+            attributes: TaskUpdate::new(),
+        }
+    }
+
+    /// Set attributes beyond the description
+    pub fn with(mut self, attributes: TaskUpdate) -> Self {
+        self.attributes = attributes;
+        self
+    }
+}
+
+impl From<&str> for NewTask {
+    fn from(description: &str) -> Self {
+        Self::new(description)
+    }
 }
 
 /// Task update structure for partial updates
@@ -97,9 +497,21 @@ pub struct TaskUpdate {
     pub project: Option<String>,
     pub priority: Option<crate::task::Priority>,
     pub due: Option<DateTime<Utc>>,
+    /// Hide the task until then; a future date makes a pending task waiting
+    pub wait: Option<DateTime<Utc>>,
+    pub scheduled: Option<DateTime<Utc>>,
     pub tags: Option<std::collections::HashSet<String>>,
     pub annotations: Option<Vec<crate::task::Annotation>>,
     pub uda: Option<HashMap<String, String>>,
+    /// Typed UDA values, kept as numbers or dates through storage
+    pub uda_values: Option<HashMap<String, UdaValue>>,
+    /// UDAs to remove
+    pub remove_udas: Option<std::collections::HashSet<String>>,
+    /// Replace the tasks this one depends on
+    pub depends: Option<std::collections::HashSet<Uuid>>,
+    /// Only apply the update if the stored task still has this
+    /// [`Task::etag`]; not a change by itself
+    pub expected_etag: Option<String>,
 }
 
 impl TaskUpdate {
@@ -114,6 +526,13 @@ impl TaskUpdate {
         self
     }
 
+    /// Fail with [`TaskError::Conflict`] unless the stored task's
+    /// [`Task::etag`] is still `etag`
+    pub fn if_match<S: Into<String>>(mut self, etag: S) -> Self {
+        self.expected_etag = Some(etag.into());
+        self
+    }
+
     /// Set status
     pub fn status(mut self, status: TaskStatus) -> Self {
         self.status = Some(status);
@@ -132,12 +551,29 @@ impl TaskUpdate {
         self
     }
 
+    /// Set priority by code, allowing custom values from `uda.priority.values`
+    pub fn priority_code<S: Into<String>>(self, code: S) -> Self {
+        self.set_uda("priority", code)
+    }
+
     /// Set due date
     pub fn due(mut self, due: DateTime<Utc>) -> Self {
         self.due = Some(due);
         self
     }
 
+    /// Set wait date
+    pub fn wait(mut self, wait: DateTime<Utc>) -> Self {
+        self.wait = Some(wait);
+        self
+    }
+
+    /// Set scheduled date
+    pub fn scheduled(mut self, scheduled: DateTime<Utc>) -> Self {
+        self.scheduled = Some(scheduled);
+        self
+    }
+
     /// Add tag
     pub fn add_tag<S: Into<String>>(mut self, tag: S) -> Self {
         self.tags
@@ -162,6 +598,22 @@ impl TaskUpdate {
         self
     }
 
+    /// Set a UDA to a typed value
+    pub fn set_uda_value<K: Into<String>>(mut self, key: K, value: UdaValue) -> Self {
+        self.uda_values
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value);
+        self
+    }
+
+    /// Remove a UDA
+    pub fn unset_uda<K: Into<String>>(mut self, key: K) -> Self {
+        self.remove_udas
+            .get_or_insert_with(std::collections::HashSet::new)
+            .insert(key.into());
+        self
+    }
+
     /// Check if update is empty
     pub fn is_empty(&self) -> bool {
         self.description.is_none()
@@ -169,28 +621,49 @@ impl TaskUpdate {
             && self.project.is_none()
             && self.priority.is_none()
             && self.due.is_none()
+            && self.wait.is_none()
+            && self.scheduled.is_none()
             && self.tags.as_ref().is_none_or(|t| t.is_empty())
-            && self.annotations.as_ref().is_none_or(|a| a.is_empty())
+            // An empty list clears annotations, so it counts as a change
+            && self.annotations.is_none()
             && self.uda.as_ref().is_none_or(|u| u.is_empty())
+            && self.uda_values.as_ref().is_none_or(|u| u.is_empty())
+            && self.remove_udas.as_ref().is_none_or(|u| u.is_empty())
+            // Like annotations, an empty set clears dependencies
+            && self.depends.is_none()
     }
 
     /// Apply update to a task
     pub fn apply_to(&self, task: &mut Task) {
+        let before = task.clone();
         if let Some(ref desc) = self.description {
             task.description = desc.clone();
         }
         if let Some(status) = self.status {
+            // Completing or deleting records when, as `Task::complete` does
+            if matches!(status, TaskStatus::Completed | TaskStatus::Deleted) && task.end.is_none() {
+                task.end = Some(clock::now());
+            }
             task.status = status;
         }
         if let Some(ref project) = self.project {
             task.project = Some(project.clone());
         }
         if let Some(priority) = self.priority {
-            task.priority = Some(priority);
+            task.set_priority_code(Some(priority.code()));
         }
         if let Some(due) = self.due {
             task.due = Some(due);
         }
+        if let Some(wait) = self.wait {
+            task.wait = Some(wait);
+            if task.status == TaskStatus::Pending && wait > clock::now() {
+                task.status = TaskStatus::Waiting;
+            }
+        }
+        if let Some(scheduled) = self.scheduled {
+            task.scheduled = Some(scheduled);
+        }
         if let Some(ref tags) = self.tags {
             task.tags = tags.clone();
         }
@@ -199,13 +672,40 @@ impl TaskUpdate {
         }
         if let Some(ref uda) = self.uda {
             for (key, value) in uda {
+                if key == "priority" {
+                    // Custom priority codes share the `priority` attribute
+                    task.set_priority_code(Some(value));
+                    continue;
+                }
                 task.udas
                     .insert(key.clone(), UdaValue::String(value.clone()));
             }
         }
+        if let Some(ref values) = self.uda_values {
+            for (key, value) in values {
+                task.udas.insert(key.clone(), value.clone());
+            }
+        }
+        if let Some(ref depends) = self.depends {
+            task.depends = depends.clone();
+        }
+        if let Some(ref keys) = self.remove_udas {
+            for key in keys {
+                if key == "priority" {
+                    task.set_priority_code(None);
+                } else {
+                    task.udas.remove(key);
+                }
+            }
+        }
 
-        // Update modification time
-        task.modified = Some(Utc::now());
+        // One stamp for the whole update, however many setters ran
+        task.modified = before.modified;
+        task.touch();
+        count_push(&before, task);
+        if let Some(modified) = task.modified {
+            stamp(&before, task, modified);
+        }
     }
 }
 
@@ -215,6 +715,10 @@ pub struct SyncResult {
     pub tasks_pulled: usize,
     pub tasks_pushed: usize,
     pub conflicts_resolved: usize,
+    /// Tasks removed by the retention policy after sync (`purge.on-sync`)
+    pub tasks_purged: usize,
+    /// Why the purge after sync failed; the sync itself still succeeded
+    pub purge_error: Option<String>,
 }
 
 /// Validation report for all tasks
@@ -226,6 +730,13 @@ pub struct ValidationReport {
     pub errors: Vec<ValidationError>,
 }
 
+/// How far ahead of the local clock a task timestamp may be before
+/// [`TaskManager::validate_all`] reports it as clock skew
+pub const CLOCK_SKEW_TOLERANCE: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Taskwarrior's default `nag` message
+pub const DEFAULT_NAG: &str = "You have more urgent tasks.";
+
 /// Default task manager implementation
 #[derive(Debug)]
 pub struct DefaultTaskManager {
@@ -235,6 +746,14 @@ pub struct DefaultTaskManager {
     sync_manager: Option<Box<dyn SyncManager>>,
     // Cached mtime of the configuration file to avoid reloading on every query
     last_config_mtime: Option<std::time::SystemTime>,
+    // Operation metrics; behind a lock so read-only operations can record
+    metrics: Mutex<Metrics>,
+    // Audit log of changes, when `audit.log` is set
+    audit: Option<AuditLog>,
+    // Asked before starting the next task of a dependency chain
+    confirmation: Option<ConfirmationHandler>,
+    // Messages not yet taken with `take_feedback`
+    feedback: Vec<String>,
 }
 
 impl DefaultTaskManager {
@@ -250,12 +769,20 @@ impl DefaultTaskManager {
             Err(_) => None,
         };
 
+        let audit = AuditConfig::from_config(&config)?
+            .map(AuditLog::open)
+            .transpose()?;
+
         let mut manager = Self {
             config,
             storage,
             hooks,
             sync_manager: None,
             last_config_mtime,
+            metrics: Mutex::new(Metrics::default()),
+            audit,
+            confirmation: None,
+            feedback: Vec::new(),
         };
 
         // Initialize storage
@@ -270,6 +797,321 @@ impl DefaultTaskManager {
         self
     }
 
+    /// Record changes in `audit`, replacing the log from `audit.log`
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Ask `handler` before starting the next task of a dependency chain
+    /// (`dependency.chain.start=prompt`)
+    pub fn with_confirmation_handler(mut self, handler: ConfirmationHandler) -> Self {
+        self.confirmation = Some(handler);
+        self
+    }
+
+    /// The audit log changes are recorded in, if any
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit.as_ref()
+    }
+
+    /// Snapshot of operation metrics gathered so far
+    pub fn metrics(&self) -> Metrics {
+        self.metrics.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+
+    /// Clear all gathered metrics
+    pub fn reset_metrics(&self) {
+        if let Ok(mut metrics) = self.metrics.lock() {
+            *metrics = Metrics::default();
+        }
+    }
+
+    fn record_metric<T>(&self, operation: &str, started: Instant, result: &Result<T, TaskError>) {
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.record(operation, started.elapsed(), result);
+        }
+    }
+
+    /// Pull `entry`, `modified`, `start` and `end` timestamps that are
+    /// beyond [`CLOCK_SKEW_TOLERANCE`] in the future back to now, as
+    /// [`validate_all`](TaskManager::validate_all) reports them. Returns
+    /// the IDs of the tasks that changed.
+    pub fn normalize_future_timestamps(&mut self) -> Result<Vec<Uuid>, TaskError> {
+        let now = clock::now();
+        let mut changed = Vec::new();
+        for mut task in self.storage.load_all_tasks()? {
+            if task.future_timestamps(now + CLOCK_SKEW_TOLERANCE).is_empty() {
+                continue;
+            }
+            task.clamp_future_timestamps(now);
+            self.storage.save_task(&task)?;
+            changed.push(task.id);
+        }
+        Ok(changed)
+    }
+
+    /// Turn waiting tasks whose wait date has passed into pending tasks,
+    /// as the `task` CLI does before each command. Returns the promoted
+    /// tasks. Runs before every query and during daemon maintenance.
+    pub fn promote_waiting(&mut self) -> Result<Vec<Task>, TaskError> {
+        let now = clock::now();
+        let waiting = TaskQuery {
+            status: Some(TaskStatus::Waiting),
+            ..Default::default()
+        };
+        let mut promoted = Vec::new();
+        for mut task in self.storage.query_tasks(&waiting, None)? {
+            if task.promote_if_unwaited(now) {
+                self.storage.save_task(&task)?;
+                promoted.push(task);
+            }
+        }
+        Ok(promoted)
+    }
+
+    /// Permanently remove a deleted or completed task from storage
+    pub fn purge_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.audited("purge", Some(id), |mgr| mgr.purge_task_inner(id))
+    }
+
+    fn purge_task_inner(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        let task = self
+            .storage
+            .load_task(id)?
+            .ok_or(TaskError::NotFound { id })?;
+        if !matches!(task.status, TaskStatus::Deleted | TaskStatus::Completed) {
+            return Err(TaskError::InvalidState {
+                message: format!("only deleted or completed tasks can be purged, {id} is {:?}", task.status),
+            });
+        }
+
+        let started = Instant::now();
+        let result = self.storage.purge_task(id).map(|_| task);
+        self.record_metric("purge", started, &result);
+        result
+    }
+
+    /// Move tasks completed or deleted before `before` to the storage
+    /// backend's archive, if it has one; returns how many moved
+    pub fn archive_completed(&mut self, before: DateTime<Utc>) -> Result<usize, TaskError> {
+        self.storage.archive_completed(before)
+    }
+
+    /// Purge deleted/completed tasks older than the configured retention
+    /// (`purge.deleted`, `purge.completed`). With `dry_run` nothing is removed
+    /// and the report lists what would be.
+    pub fn purge_expired(&mut self, dry_run: bool) -> Result<PurgeReport, TaskError> {
+        let policy = RetentionPolicy::from_config(&self.config)?;
+        self.purge_with_policy(&policy, dry_run)
+    }
+
+    /// Purge tasks expired under an explicit policy. A failed purge stops
+    /// the run and is recorded in the report's `error` alongside what was
+    /// already removed.
+    pub fn purge_with_policy(
+        &mut self,
+        policy: &RetentionPolicy,
+        dry_run: bool,
+    ) -> Result<PurgeReport, TaskError> {
+        let mut report = PurgeReport {
+            purged: Vec::new(),
+            dry_run,
+            error: None,
+        };
+        if !policy.is_enabled() {
+            return Ok(report);
+        }
+
+        let tasks = self.storage.load_all_tasks()?;
+        for task in policy.expired(&tasks, clock::now()) {
+            if !dry_run {
+                if let Err(e) = self.purge_task(task.id) {
+                    report.error = Some(format!("purging task {}: {e}", task.id));
+                    break;
+                }
+            }
+            report.purged.push(PurgedTask::from_task(task));
+        }
+        Ok(report)
+    }
+
+    /// Start working on a task, as `task start` does. Runs the modify
+    /// hooks, so time trackers such as the Timewarrior hook see it.
+    pub fn start_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.audited("start", Some(id), |mgr| {
+            let old_task = mgr.storage.load_task(id)?.ok_or(TaskError::NotFound { id })?;
+            if old_task.status != TaskStatus::Pending {
+                return Err(TaskError::InvalidState {
+                    message: format!("Only pending tasks can be started, task {id} is not"),
+                });
+            }
+            let mut task = old_task.clone();
+            task.start();
+            mgr.execute_hooks_with_action("modify", &task, |mgr| {
+                mgr.storage.save_task(&task)?;
+                mgr.hooks.on_modify(&old_task, &task)?;
+                Ok(())
+            })?;
+            Ok(task)
+        })
+        .inspect(|task| self.nag(task))
+    }
+
+    /// Complete a task and report the dependents it unblocked. Following
+    /// `dependency.chain.start`, the earliest-entered of them is started,
+    /// after asking the confirmation handler when set to `prompt`.
+    pub fn complete_task_chained(&mut self, id: Uuid) -> Result<ChainedCompletion, TaskError> {
+        let mode = ChainStart::from_config(&self.config)?;
+        let task = self.complete_task(id)?;
+        let unblocked = unblocked_dependents(&self.storage.load_all_tasks()?, id);
+
+        let next = unblocked.first().filter(|next| !next.is_active());
+        let start = next.is_some_and(|next| match mode {
+            ChainStart::Off => false,
+            ChainStart::Auto => true,
+            ChainStart::Prompt if self.config.get_bool("confirmation") == Some(false) => true,
+            ChainStart::Prompt => self.confirmation.as_mut().is_some_and(|handler| {
+                handler.confirm(&format!("Start next task '{}'?", next.description))
+            }),
+        });
+        let started = match next {
+            Some(next) if start => Some(self.start_task(next.id)?),
+            _ => None,
+        };
+
+        Ok(ChainedCompletion {
+            task,
+            unblocked,
+            started,
+        })
+    }
+
+    /// Apply the deadline escalation rules from config
+    /// (`escalation.<name>.*`). With `dry_run` nothing is saved and the
+    /// report lists what would change.
+    pub fn escalate(&mut self, dry_run: bool) -> Result<EscalationReport, TaskError> {
+        let policy = EscalationPolicy::from_config(&self.config)?;
+        self.escalate_with_policy(&policy, dry_run)
+    }
+
+    /// Apply an explicit escalation policy. Each escalated task is saved as
+    /// an update, so hooks and the audit log see it like any other edit.
+    pub fn escalate_with_policy(
+        &mut self,
+        policy: &EscalationPolicy,
+        dry_run: bool,
+    ) -> Result<EscalationReport, TaskError> {
+        let mut report = EscalationReport {
+            escalations: Vec::new(),
+            skipped: Vec::new(),
+            dry_run,
+        };
+        if !policy.is_enabled() {
+            return Ok(report);
+        }
+
+        let pending = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            ..Default::default()
+        };
+        let tasks = self.storage.query_tasks(&pending, None)?;
+        for mut escalation in policy.evaluate(&tasks, clock::now()) {
+            if !dry_run {
+                // Leave tasks alone that changed since they were loaded
+                let mut update = TaskUpdate::new();
+                if let Some(before) = tasks.iter().find(|t| t.id == escalation.id) {
+                    update = update.if_match(before.etag());
+                }
+                if let Some(code) = escalation.task.priority_code() {
+                    update = update.priority_code(code);
+                }
+                update.tags = Some(escalation.task.tags.clone());
+                update.annotations = Some(escalation.task.annotations.clone());
+                escalation.task = match self.update_task(escalation.id, update) {
+                    Ok(task) => task,
+                    Err(TaskError::Conflict { id, .. }) => {
+                        report.skipped.push(id);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+            }
+            report.escalations.push(escalation);
+        }
+        Ok(report)
+    }
+
+    /// Import tasks from `reader` and save them, resolving UUIDs that
+    /// already exist with the config's merge strategy. The result lists
+    /// the saved tasks and a merge decision for every imported task.
+    pub fn import_tasks<R: std::io::Read>(
+        &mut self,
+        reader: &mut R,
+        config: &ImportConfig,
+    ) -> Result<ImportResult, TaskError> {
+        let importer = DefaultTaskImporter::new();
+        let mut result = importer.import_with_detection(reader, config)?;
+        let existing = self.storage.load_all_tasks()?;
+        importer.resolve_merges(&mut result, &existing, config.effective_strategy());
+
+        for task in &result.tasks {
+            self.storage.save_task(task)?;
+        }
+        Ok(result)
+    }
+
+    /// What [`import_tasks`](Self::import_tasks) would do with `reader`,
+    /// record by record, without saving anything: tasks it would create,
+    /// field changes it would make and records it would skip, with why
+    pub fn preview_import<R: std::io::Read>(
+        &mut self,
+        reader: &mut R,
+        config: &ImportConfig,
+    ) -> Result<ImportPreview, TaskError> {
+        let importer = DefaultTaskImporter::new();
+        let parsed = importer.import_with_detection(reader, config)?;
+        let existing = self.storage.load_all_tasks()?;
+        Ok(importer.preview(&parsed, &existing, config.effective_strategy()))
+    }
+
+    /// Tag registry built from `tag.<name>.*` settings
+    pub fn tag_registry(&self) -> TagRegistry {
+        TagRegistry::from_config(&self.config)
+    }
+
+    /// All known tags (used by tasks or configured) with usage counts
+    pub fn tag_usage(&self) -> Result<Vec<TagUsage>, TaskError> {
+        let tasks = self.storage.load_all_tasks()?;
+        Ok(self.tag_registry().usage(&tasks))
+    }
+
+    /// Why `query` does or does not return task `id`: each filter clause
+    /// with its outcome, plus the active context's project when the query
+    /// honors contexts. Waiting tasks whose wait date has passed are
+    /// promoted first, as before any query.
+    pub fn explain_match(&mut self, id: Uuid, query: &TaskQuery) -> Result<MatchTrace, TaskError> {
+        self.promote_waiting()?;
+        let task = self.storage.load_task(id)?.ok_or(TaskError::NotFound { id })?;
+        let mut trace = query.evaluate_explain(&task);
+
+        let ignore = matches!(query.filter_mode, Some(FilterMode::IgnoreContext));
+        let active = self.config.discover_contexts()?.into_iter().find(|c| c.active);
+        if let (false, Some(context)) = (ignore, active) {
+            if let Some(SimpleProjectFilter::Equals(project)) =
+                parse_project_from_context_filter(&context.read_filter)
+            {
+                trace.push(
+                    format!("context {}", context.name),
+                    format!("project:{project}"),
+                    task.project.clone().unwrap_or_else(|| "none".to_string()),
+                    task.project.as_deref() == Some(project.as_str()),
+                );
+            }
+        }
+        Ok(trace)
+    }
+
     /// Validate a task before operations
     fn validate_task(&self, task: &Task) -> Result<(), ValidationError> {
         // Check required fields
@@ -303,9 +1145,18 @@ impl DefaultTaskManager {
             }
         }
 
+        // Validate priority against the configured scheme
+        if let Some(code) = task.priority_code() {
+            if !self.config.priority_scheme().is_valid(code) {
+                return Err(ValidationError::InvalidPriority {
+                    priority: code.to_string(),
+                });
+            }
+        }
+
         // Validate due date is not in far future
         if let Some(due) = task.due {
-            let max_future = Utc::now() + chrono::Duration::days(365 * 10); // 10 years
+            let max_future = clock::now() + chrono::Duration::days(365 * 10); // 10 years
             if due > max_future {
                 return Err(ValidationError::DueDateTooFar { due });
             }
@@ -315,6 +1166,54 @@ impl DefaultTaskManager {
     }
 
     /// Execute pre/post operation hooks around an action closure.
+    /// Run `change` and append it to the audit log, if there is one. The
+    /// batch is rebuilt from the task before and after; an error writing
+    /// the log is returned even though the change itself was saved.
+    fn audited<F>(&mut self, operation: &str, id: Option<Uuid>, change: F) -> Result<Task, TaskError>
+    where
+        F: FnOnce(&mut Self) -> Result<Task, TaskError>,
+    {
+        if self.audit.is_none() {
+            let result = change(self);
+            return self.in_context(operation, id, result);
+        }
+        let before = id.and_then(|id| self.storage.load_task(id).ok().flatten());
+        let result = change(self);
+        let result = self.in_context(operation, id, result);
+        let batch = match &result {
+            Ok(task) => match operation {
+                "delete" => build_delete_batch(task.id),
+                "purge" => build_purge_batch(task.id),
+                _ => build_save_batch(before.as_ref(), task),
+            },
+            Err(_) => Vec::new(),
+        };
+        let task_id = result.as_ref().map(|task| task.id).ok().or(id);
+        let recorded = match &self.audit {
+            Some(audit) => audit.record(operation, task_id, batch, result.as_ref().map(|_| ())),
+            None => return result,
+        };
+        match (result, recorded) {
+            (Ok(_), Err(e)) => Err(e),
+            (result, _) => result,
+        }
+    }
+
+    /// Name the operation, task and backend in a storage, hook or sync
+    /// error (see [`TaskError::with_context`])
+    fn in_context<T>(
+        &self,
+        operation: &str,
+        id: Option<Uuid>,
+        result: Result<T, TaskError>,
+    ) -> Result<T, TaskError> {
+        result.map_err(|e| {
+            let mut context = ErrorContext::new(operation).backend(self.storage.backend_name());
+            context.task = id;
+            e.with_context(context)
+        })
+    }
+
     fn execute_hooks_with_action<F>(
         &mut self,
         operation: &str,
@@ -340,26 +1239,207 @@ impl ConfigurationProvider for DefaultTaskManager {
         &mut self.config
     }
 
-    fn reload_config(&mut self) -> Result<(), TaskError> {
-        self.config =
-            Configuration::from_xdg().map_err(|e| TaskError::Configuration { source: e })?;
-        Ok(())
+    fn reload_config(&mut self) -> Result<(), TaskError> {
+        self.config =
+            Configuration::from_xdg().map_err(|e| TaskError::Configuration { source: e })?;
+        Ok(())
+    }
+}
+
+impl TaskManager for DefaultTaskManager {
+    fn add_task(&mut self, description: String) -> Result<Task, TaskError> {
+        // Default behavior: use CombineWithContext semantics
+        let options = AddOptions::default();
+        self.add_task_with_options(description, options)
+    }
+
+    fn add_task_with_options(
+        &mut self,
+        description: String,
+        options: AddOptions,
+    ) -> Result<Task, TaskError> {
+        let started = Instant::now();
+        let result = self.audited("add", None, |mgr| {
+            mgr.add_task_with_options_inner(description, options)
+        });
+        self.record_metric("add", started, &result);
+        result
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        let started = Instant::now();
+        let result = self.in_context("get", Some(id), self.get_task_inner(id));
+        self.record_metric("get", started, &result);
+        result
+    }
+
+    fn update_task(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+        let started = Instant::now();
+        let result = self.audited("update", Some(id), |mgr| mgr.update_task_inner(id, updates));
+        self.record_metric("update", started, &result);
+        result
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        let started = Instant::now();
+        let result = self.audited("delete", Some(id), |mgr| mgr.delete_task_inner(id));
+        self.record_metric("delete", started, &result);
+        result
+    }
+
+    fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        let started = Instant::now();
+        let result = self.audited("complete", Some(id), |mgr| mgr.complete_task_inner(id));
+        self.record_metric("complete", started, &result);
+        let task = result?;
+        self.nag(&task);
+        // The completion is saved; a failed roll-up must not report it failed
+        if let Err(e) = self.roll_up_subtasks(&task) {
+            self.feedback
+                .push(format!("Could not roll up the parent of '{}': {e}", task.description));
+        }
+        Ok(task)
+    }
+
+    fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
+        let started = Instant::now();
+        let result = self.query_tasks_inner(query);
+        let result = self.in_context("query", None, result);
+        self.record_metric("query", started, &result);
+        result
+    }
+
+    /// Pending tasks, after promoting waiting tasks whose wait date has
+    /// passed. Pending tasks with a future `wait` date are left out.
+    fn pending_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        let query = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            project_filter: None,
+            tag_filter: None,
+            date_filter: None,
+            sort: None,
+            limit: None,
+            offset: None,
+            filter_mode: None,
+            custom_filters: Vec::new(),
+            owner_filter: None,
+            priority_filter: None,
+            priority_scheme: None,
+            collation: None,
+            proximity: None,
+        };
+        let now = clock::now();
+        let mut tasks = self.query_tasks(&query)?;
+        tasks.retain(|task| !task.is_waiting_at(now));
+        Ok(tasks)
+    }
+
+    fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        let query = TaskQuery {
+            status: Some(TaskStatus::Completed),
+            project_filter: None,
+            tag_filter: None,
+            date_filter: None,
+            sort: None,
+            limit: None,
+            offset: None,
+            filter_mode: None,
+            custom_filters: Vec::new(),
+            owner_filter: None,
+            priority_filter: None,
+            priority_scheme: None,
+            collation: None,
+            proximity: None,
+        };
+        self.query_tasks(&query)
+    }
+
+    fn deleted_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        let query = TaskQuery {
+            status: Some(TaskStatus::Deleted),
+            ..Default::default()
+        };
+        self.query_tasks(&query)
+    }
+
+    fn restore_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        let started = Instant::now();
+        let result = self.audited("restore", Some(id), |mgr| mgr.restore_task_inner(id));
+        self.record_metric("restore", started, &result);
+        result
+    }
+
+    fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError> {
+        let tasks = self.query_tasks(query)?;
+        Ok(tasks.len())
+    }
+
+    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+        let started = Instant::now();
+        let result = self.sync_inner();
+        let result = self.in_context("sync", None, result);
+        self.record_metric("sync", started, &result);
+        if let Ok(mut metrics) = self.metrics.lock() {
+            let sync = &mut metrics.sync;
+            sync.runs += 1;
+            match &result {
+                Ok(r) => {
+                    sync.tasks_pulled += r.tasks_pulled as u64;
+                    sync.tasks_pushed += r.tasks_pushed as u64;
+                    sync.conflicts_resolved += r.conflicts_resolved as u64;
+                    sync.last_success = Some(clock::now());
+                }
+                Err(_) => sync.failures += 1,
+            }
+        }
+        result
+    }
+
+    fn validate_all(&self) -> Result<ValidationReport, TaskError> {
+        let all_tasks = self.storage.load_all_tasks()?;
+        let total_tasks = all_tasks.len();
+        let mut errors = Vec::new();
+        let mut valid_count = 0;
+
+        let limit = clock::now() + CLOCK_SKEW_TOLERANCE;
+        for task in &all_tasks {
+            let skewed = task.future_timestamps(limit);
+            match self.validate_task(task) {
+                Ok(_) if skewed.is_empty() => valid_count += 1,
+                Ok(_) => {}
+                Err(e) => errors.push(e),
+            }
+            errors.extend(skewed.into_iter().map(|(field, value)| ValidationError::FutureTimestamp {
+                id: task.id,
+                field: field.to_string(),
+                value,
+            }));
+        }
+
+        Ok(ValidationReport {
+            total_tasks,
+            valid_tasks: valid_count,
+            invalid_tasks: total_tasks - valid_count,
+            errors,
+        })
+    }
+
+    fn take_feedback(&mut self) -> Vec<String> {
+        let mut feedback = self.hooks.take_feedback();
+        feedback.append(&mut self.feedback);
+        feedback
     }
 }
 
-impl TaskManager for DefaultTaskManager {
-    fn add_task(&mut self, description: String) -> Result<Task, TaskError> {
-        // Default behavior: use CombineWithContext semantics
-        let options = AddOptions::default();
-        self.add_task_with_options(description, options)
-    }
-
-    fn add_task_with_options(
+// Operation bodies; the TaskManager impl above wraps them to record metrics
+impl DefaultTaskManager {
+    fn add_task_with_options_inner(
         &mut self,
         description: String,
         options: AddOptions,
     ) -> Result<Task, TaskError> {
         let mut task = Task::new(description);
+        task.owner = self.config.task_owner();
 
         // Apply active context write defaults if present and not ignored.
         // For now we only support a simple project:<name> write default.
@@ -377,6 +1457,8 @@ impl TaskManager for DefaultTaskManager {
             }
         }
 
+        self.tag_registry().apply_implications(&mut task.tags);
+
         // Validate task
         self.validate_task(&task)
             .map_err(|e| TaskError::Validation { source: e })?;
@@ -394,11 +1476,11 @@ impl TaskManager for DefaultTaskManager {
         Ok(saved_task)
     }
 
-    fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+    fn get_task_inner(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
         self.storage.load_task(id)
     }
 
-    fn update_task(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+    fn update_task_inner(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
         if updates.is_empty() {
             return Err(TaskError::EmptyUpdate);
         }
@@ -409,10 +1491,22 @@ impl TaskManager for DefaultTaskManager {
             .load_task(id)?
             .ok_or(TaskError::NotFound { id })?;
 
+        if let Some(expected) = &updates.expected_etag {
+            let actual = task.etag();
+            if *expected != actual {
+                return Err(TaskError::Conflict {
+                    id,
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+
         let old_task = task.clone();
 
-        // Apply updates
+        // Apply updates, then any tags implied by the resulting set
         updates.apply_to(&mut task);
+        self.tag_registry().apply_implications(&mut task.tags);
 
         // Validate updated task
         self.validate_task(&task)
@@ -429,7 +1523,7 @@ impl TaskManager for DefaultTaskManager {
         Ok(new_task)
     }
 
-    fn delete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+    fn delete_task_inner(&mut self, id: Uuid) -> Result<Task, TaskError> {
         let task = self
             .storage
             .load_task(id)?
@@ -446,10 +1540,90 @@ impl TaskManager for DefaultTaskManager {
         Ok(deleted_task)
     }
 
-    fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+    fn restore_task_inner(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        let old_task = self
+            .storage
+            .load_task(id)?
+            .ok_or(TaskError::NotFound { id })?;
+        if old_task.status != TaskStatus::Deleted {
+            return Err(TaskError::InvalidState {
+                message: format!("only deleted tasks can be restored, {id} is {:?}", old_task.status),
+            });
+        }
+
+        let mut task = old_task.clone();
+        TaskUpdate::new().status(TaskStatus::Pending).apply_to(&mut task);
+        task.end = None;
+
+        let new_task = task.clone();
+        self.execute_hooks_with_action("restore", &new_task, |mgr| {
+            mgr.storage.save_task(&new_task)?;
+            mgr.hooks.on_modify(&old_task, &new_task)?;
+            Ok(())
+        })?;
+
+        Ok(new_task)
+    }
+
+    /// After completing or starting `task`, add the `nag` message to the
+    /// feedback if a pending task is more urgent, as Taskwarrior does. An
+    /// empty `nag` turns this off. The change is already saved, so a failed
+    /// lookup only means no nag.
+    fn nag(&mut self, task: &Task) {
+        let message = self.config.get("nag").map_or(DEFAULT_NAG, |m| m.as_str()).trim();
+        if message.is_empty() {
+            return;
+        }
+        let coefficients = UrgencyCoefficients::from_config(&self.config);
+        let now = clock::now();
+        let urgency = coefficients.score(task, now);
+        let pending = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            ..Default::default()
+        };
+        let Ok(tasks) = self.storage.query_tasks(&pending, None) else {
+            return;
+        };
+        let skipped = tasks
+            .iter()
+            .any(|t| t.id != task.id && coefficients.score(t, now) > urgency);
+        if skipped {
+            self.feedback.push(message.to_string());
+        }
+    }
+
+    /// After `task` was completed, handle its parent once no subtask is
+    /// left open, as `subtask.rollup` says
+    fn roll_up_subtasks(&mut self, task: &Task) -> Result<(), TaskError> {
+        let Some(parent_id) = parent_of(task) else {
+            return Ok(());
+        };
+        let rollup = SubtaskRollup::from_config(&self.config)?;
+        let parent = match self.storage.load_task(parent_id)? {
+            Some(parent) if parent.status == TaskStatus::Pending => parent,
+            _ => return Ok(()),
+        };
+        if rollup == SubtaskRollup::Off || !all_done(&self.subtasks(parent_id)?) {
+            return Ok(());
+        }
+        match rollup {
+            SubtaskRollup::Complete => self.complete_task(parent_id).map(|_| ()),
+            _ => {
+                self.feedback.push(format!(
+                    "All subtasks of '{}' are done; it can be completed",
+                    parent.description
+                ));
+                Ok(())
+            }
+        }
+    }
+
+    fn complete_task_inner(&mut self, id: Uuid) -> Result<Task, TaskError> {
         let updates = TaskUpdate::new().status(TaskStatus::Completed);
 
-        let task = self.update_task(id, updates)?;
+        // Not through `update_task`, which would audit and measure the
+        // change a second time as an update
+        let task = self.update_task_inner(id, updates)?;
 
         // Execute completion hooks
         self.hooks.on_complete(&task)?;
@@ -457,7 +1631,7 @@ impl TaskManager for DefaultTaskManager {
         Ok(task)
     }
 
-    fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
+    fn query_tasks_inner(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
         // Check whether the config file has changed since last time by
         // comparing the file mtime. Only reload when it changed.
     // Clone the PathBuf to avoid holding an immutable borrow on self
@@ -469,6 +1643,26 @@ impl TaskManager for DefaultTaskManager {
             // Update cached mtime to the new value (may be None)
             self.last_config_mtime = std::fs::metadata(&cfg_path).and_then(|m| m.modified()).ok();
         }
+        self.promote_waiting()?;
+
+        // Sort by the configured priority scheme and collation unless the
+        // query brings its own
+        let scheme_query;
+        let query = if (query.priority_scheme.is_none() || query.collation.is_none())
+            && query.sort.is_some()
+        {
+            scheme_query = TaskQuery {
+                priority_scheme: query
+                    .priority_scheme
+                    .clone()
+                    .or_else(|| Some(self.config.priority_scheme())),
+                collation: query.collation.or_else(|| Some(Collation::from_config(&self.config))),
+                ..query.clone()
+            };
+            &scheme_query
+        } else {
+            query
+        };
 
         // Discover active context and pass it to storage backends. If
         // no context is active, pass None. Default behavior is to honor
@@ -480,7 +1674,6 @@ impl TaskManager for DefaultTaskManager {
         // If there's an active context and the query does not explicitly
         // ignore it, compose the context read_filter into the query.
         let effective_query = if let Some(ctx) = active.as_ref() {
-            use crate::query::FilterMode;
             let ignore = matches!(query.filter_mode, Some(FilterMode::IgnoreContext));
             if !ignore {
                 // Attempt to parse a simple project token from the context read filter
@@ -515,74 +1708,32 @@ impl TaskManager for DefaultTaskManager {
         }
     }
 
-    fn pending_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
-        let query = TaskQuery {
-            status: Some(TaskStatus::Pending),
-            project_filter: None,
-            tag_filter: None,
-            date_filter: None,
-            sort: None,
-            limit: None,
-            offset: None,
-            filter_mode: None,
-        };
-        self.query_tasks(&query)
-    }
-
-    fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
-        let query = TaskQuery {
-            status: Some(TaskStatus::Completed),
-            project_filter: None,
-            tag_filter: None,
-            date_filter: None,
-            sort: None,
-            limit: None,
-            offset: None,
-            filter_mode: None,
-        };
-        self.query_tasks(&query)
-    }
-
-    fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError> {
-        let tasks = self.query_tasks(query)?;
-        Ok(tasks.len())
-    }
-
-    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+    fn sync_inner(&mut self) -> Result<SyncResult, TaskError> {
         if let Some(ref mut sync_manager) = self.sync_manager {
             let all_tasks = self.storage.load_all_tasks()?;
             let (pulled, pushed, conflicts) = sync_manager.synchronize(&all_tasks)?;
 
+            // The sync has happened; a failing purge is reported, not raised
+            let (tasks_purged, purge_error) = match RetentionPolicy::from_config(&self.config) {
+                Ok(policy) if policy.on_sync => match self.purge_with_policy(&policy, false) {
+                    Ok(report) => (report.len(), report.error),
+                    Err(e) => (0, Some(e.to_string())),
+                },
+                Ok(_) => (0, None),
+                Err(e) => (0, Some(e.to_string())),
+            };
+
             Ok(SyncResult {
                 tasks_pulled: pulled,
                 tasks_pushed: pushed,
                 conflicts_resolved: conflicts,
+                tasks_purged,
+                purge_error,
             })
         } else {
             Err(TaskError::SyncNotConfigured)
         }
     }
-
-    fn validate_all(&self) -> Result<ValidationReport, TaskError> {
-        let all_tasks = self.storage.load_all_tasks()?;
-        let total_tasks = all_tasks.len();
-        let mut errors = Vec::new();
-        let mut valid_count = 0;
-
-        for task in &all_tasks {
-            match self.validate_task(task) {
-                Ok(_) => valid_count += 1,
-                Err(e) => errors.push(e),
-            }
-        }
-
-        Ok(ValidationReport {
-            total_tasks,
-            valid_tasks: valid_count,
-            invalid_tasks: total_tasks - valid_count,
-            errors,
-        })
-    }
 }
 
 /// Options to control behavior when adding/creating a task
@@ -643,30 +1794,47 @@ impl TaskManagerBuilder {
         self
     }
 
+    /// [`build`](Self::build), boxed as a [`DynTaskManager`]
+    pub fn build_dyn(self) -> Result<DynTaskManager, TaskError> {
+        Ok(Box::new(self.build()?))
+    }
+
+    // TaskChampion if a replica exists in the data directory, else the file
+    // backend
+    fn default_storage(config: &Configuration) -> Result<Box<dyn StorageBackend>, TaskError> {
+        #[cfg(feature = "taskchampion")]
+        if let Ok(replica_path) = crate::config::discovery::discover_data_dir() {
+            let taskchampion_db = replica_path.join("taskchampion.sqlite3");
+            if taskchampion_db.exists() {
+                return Ok(Box::new(
+                    crate::storage::TaskChampionStorageBackend::new(taskchampion_db)
+                        .with_uda_types(crate::task::UdaTypes::from_config(config))
+                        .with_gc(config.get_bool("gc").unwrap_or(true))
+                        .with_locking(crate::storage::LockOptions::from_config(config)?),
+                ));
+            }
+        }
+        #[cfg(not(feature = "taskchampion"))]
+        let _ = config;
+        Ok(Box::new(crate::storage::FileStorageBackend::new()))
+    }
+
     /// Build TaskManager with defaults for missing components
     pub fn build(self) -> Result<DefaultTaskManager, TaskError> {
         let config = self
             .config
             .unwrap_or_else(|| Configuration::from_xdg().unwrap_or_default());
 
-        let storage = self.storage.unwrap_or_else(|| {
-            // Try TaskChampion first if replica exists
-            if let Ok(replica_path) = crate::config::discovery::discover_data_dir() {
-                let taskchampion_db = replica_path.join("taskchampion.sqlite3");
-                if taskchampion_db.exists() {
-                    #[cfg(feature = "taskchampion")]
-                    {
-                        return Box::new(crate::storage::TaskChampionStorageBackend::new(taskchampion_db));
-                    }
-                }
-            }
-            // Fall back to file storage
-            Box::new(crate::storage::FileStorageBackend::new())
-        });
+        let storage = match self.storage {
+            Some(storage) => storage,
+            None => Self::default_storage(&config)?,
+        };
 
         let hooks = self
             .hooks
-            .unwrap_or_else(|| Box::new(crate::hooks::DefaultHookSystem::new()));
+            .unwrap_or_else(|| {
+                Box::new(crate::hooks::DefaultHookSystem::new().with_data_dir(&config.data_dir))
+            });
 
         let mut manager = DefaultTaskManager::new(config, storage, hooks)?;
 
@@ -681,10 +1849,43 @@ impl TaskManagerBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::query::TagFilter;
+    use crate::storage::operation_batch::Operation;
+    use crate::storage::FileStorageBackend;
+    use crate::sync::DefaultSyncManager;
+    use crate::task::audit::{verify, AuditEntry};
+    use crate::task::CachedTaskManager;
     use crate::task::Priority;
-    #[allow(unused_imports)]
+    use chrono::{Datelike, Duration, Local, Weekday};
+    use std::sync::Arc;
     use tempfile::TempDir;
 
+    /// A manager over file storage in a fresh directory, with `settings` applied
+    fn test_manager(settings: &[(&str, &str)]) -> (TempDir, DefaultTaskManager) {
+        let dir = TempDir::new().unwrap();
+        let storage = Box::new(FileStorageBackend::with_path(dir.path()));
+        let manager = test_manager_with(&dir, settings, storage);
+        (dir, manager)
+    }
+
+    /// A manager over `storage`, configured to use `dir` for its data
+    fn test_manager_with(
+        dir: &TempDir,
+        settings: &[(&str, &str)],
+        storage: Box<dyn StorageBackend>,
+    ) -> DefaultTaskManager {
+        let mut config = ConfigurationBuilder::new()
+            .data_dir(dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        for &(key, value) in settings {
+            config.set(key, value);
+        }
+        DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new())).unwrap()
+    }
+
     #[test]
     fn test_task_update_builder() {
         let update = TaskUpdate::new()
@@ -722,6 +1923,35 @@ mod tests {
         assert!(task.modified > original_modified);
     }
 
+    #[test]
+    fn test_apply_custom_priority_code() {
+        let mut task = Task::new("Task".to_string());
+        task.priority = Some(Priority::High);
+
+        TaskUpdate::new().priority_code("C").apply_to(&mut task);
+        assert_eq!(task.priority, None);
+        assert_eq!(task.priority_code(), Some("C"));
+
+        TaskUpdate::new().priority(Priority::Low).apply_to(&mut task);
+        assert_eq!(task.priority_code(), Some("L"));
+        assert!(!task.udas.contains_key("priority"));
+    }
+
+    #[test]
+    fn test_apply_typed_udas() {
+        let mut task = Task::new("Task".to_string());
+        task.udas.insert("client".to_string(), UdaValue::String("ACME".to_string()));
+
+        let update = TaskUpdate::new()
+            .set_uda_value("estimate", UdaValue::Number(2.5))
+            .unset_uda("client");
+        assert!(!update.is_empty());
+        update.apply_to(&mut task);
+
+        assert_eq!(task.udas.get("estimate"), Some(&UdaValue::Number(2.5)));
+        assert!(!task.udas.contains_key("client"));
+    }
+
     #[test]
     fn test_task_manager_builder() {
         let builder = TaskManagerBuilder::new();
@@ -755,4 +1985,553 @@ mod tests {
         // Should fall back to FileStorageBackend when no TaskChampion replica exists
         assert!(format!("{:?}", storage).contains("FileStorageBackend"));
     }
+
+    #[test]
+    fn test_audit_log_records_changes() {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("audit.log");
+        let mut manager = test_manager_with(
+            &dir,
+            &[("audit.log", log.to_str().unwrap()), ("audit.user", "alice")],
+            Box::new(FileStorageBackend::with_path(dir.path())),
+        );
+
+        let task = manager.add_task("Audited".to_string()).unwrap();
+        manager.complete_task(task.id).unwrap();
+        let missing = Uuid::new_v4();
+        assert!(manager.delete_task(missing).is_err());
+        manager.purge_task(task.id).unwrap();
+
+        assert_eq!(verify(&[&log]).unwrap().entries, 4);
+        let entries: Vec<AuditEntry> = std::fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let ops: Vec<&str> = entries.iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(ops, ["add", "complete", "delete", "purge"]);
+        assert!(entries.iter().all(|e| e.user == "alice"));
+        assert!(matches!(entries[0].batch[1], Operation::Create { uuid, .. } if uuid == task.id));
+        assert!(entries[1].batch.iter().any(|op| matches!(
+            op,
+            Operation::Update { key, new, .. } if key == "status" && new == "completed"
+        )));
+        assert_eq!(entries[2].task, Some(missing));
+        assert!(entries[2].batch.is_empty() && entries[2].error.is_some());
+        assert_eq!(entries[3].batch, vec![Operation::Purge { uuid: task.id }]);
+    }
+
+    #[test]
+    fn test_escalate_overdue_client_tasks() {
+        let (_dir, mut manager) = test_manager(&[
+            ("escalation.client.filter", "+client"),
+            ("escalation.client.overdue", "2d"),
+            ("escalation.client.priority", "H"),
+            ("escalation.client.tags", "+escalated"),
+        ]);
+
+        let task = manager.add_task("Send invoice".to_string()).unwrap();
+        let update = TaskUpdate::new()
+            .add_tag("client")
+            .due(clock::now() - chrono::Duration::days(3));
+        manager.update_task(task.id, update).unwrap();
+        manager.add_task("Water plants".to_string()).unwrap();
+
+        let preview = manager.escalate(true).unwrap();
+        assert_eq!(preview.len(), 1);
+        assert!(manager.get_task(task.id).unwrap().unwrap().priority.is_none());
+
+        let report = manager.escalate(false).unwrap();
+        assert_eq!(report.escalations[0].rules, ["client"]);
+        assert_eq!(report.batch().first(), Some(&Operation::UndoPoint));
+        let stored = manager.get_task(task.id).unwrap().unwrap();
+        assert_eq!(stored.priority, Some(Priority::High));
+        assert!(stored.tags.contains("escalated"));
+        assert!(manager.escalate(false).unwrap().is_empty());
+    }
+
+    /// File storage whose query results pass through `answer`
+    #[derive(Debug)]
+    struct ScriptedQueries {
+        inner: crate::storage::FileStorageBackend,
+        answer: fn(Vec<Task>) -> Result<Vec<Task>, TaskError>,
+    }
+
+    impl StorageBackend for ScriptedQueries {
+        fn initialize(&mut self) -> Result<(), TaskError> {
+            self.inner.initialize()
+        }
+        fn save_task(&mut self, task: &Task) -> Result<(), TaskError> {
+            self.inner.save_task(task)
+        }
+        fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+            self.inner.load_task(id)
+        }
+        fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+            self.inner.delete_task(id)
+        }
+        fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+            self.inner.load_all_tasks()
+        }
+        fn query_tasks(
+            &self,
+            query: &TaskQuery,
+            context: Option<&crate::config::context::UserContext>,
+        ) -> Result<Vec<Task>, TaskError> {
+            (self.answer)(self.inner.query_tasks(query, context)?)
+        }
+        fn backup(&self) -> Result<String, crate::error::StorageError> {
+            self.inner.backup()
+        }
+        fn restore(&mut self, backup_data: &str) -> Result<(), crate::error::StorageError> {
+            self.inner.restore(backup_data)
+        }
+    }
+
+    #[test]
+    fn test_escalation_skips_tasks_changed_meanwhile() {
+        let dir = TempDir::new().unwrap();
+        // Copies older than what is stored, as if another process saved
+        // every task right after the query
+        let storage = Box::new(ScriptedQueries {
+            inner: FileStorageBackend::with_path(dir.path()),
+            answer: |mut tasks| {
+                for task in &mut tasks {
+                    task.modified = Some(task.entry - chrono::Duration::hours(1));
+                }
+                Ok(tasks)
+            },
+        });
+        let mut manager = test_manager_with(
+            &dir,
+            &[
+                ("escalation.client.filter", "+client"),
+                ("escalation.client.overdue", "2d"),
+                ("escalation.client.priority", "H"),
+            ],
+            storage,
+        );
+        let task = manager.add_task("Send invoice".to_string()).unwrap();
+        let update = TaskUpdate::new()
+            .add_tag("client")
+            .due(clock::now() - chrono::Duration::days(3));
+        manager.update_task(task.id, update).unwrap();
+
+        let report = manager.escalate(false).unwrap();
+        assert!(report.is_empty());
+        assert_eq!(report.skipped, vec![task.id]);
+        assert!(manager.get_task(task.id).unwrap().unwrap().priority.is_none());
+    }
+
+    #[test]
+    fn test_failed_nag_lookup_keeps_the_change() {
+        let dir = TempDir::new().unwrap();
+        let storage = Box::new(ScriptedQueries {
+            inner: FileStorageBackend::with_path(dir.path()),
+            answer: |_| Err(TaskError::ServiceStopped),
+        });
+        let mut manager = test_manager_with(&dir, &[], storage);
+        let task = manager.add_task("Write report".to_string()).unwrap();
+
+        assert!(manager.start_task(task.id).unwrap().is_active());
+        let done = manager.complete_task(task.id).unwrap();
+        assert_eq!(done.status, TaskStatus::Completed);
+        assert!(manager.take_feedback().is_empty());
+    }
+
+    /// File storage that refuses to purge once `purges_left` runs out
+    #[derive(Debug)]
+    struct FailingPurges {
+        inner: FileStorageBackend,
+        purges_left: usize,
+    }
+
+    impl StorageBackend for FailingPurges {
+        fn initialize(&mut self) -> Result<(), TaskError> {
+            self.inner.initialize()
+        }
+        fn save_task(&mut self, task: &Task) -> Result<(), TaskError> {
+            self.inner.save_task(task)
+        }
+        fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+            self.inner.load_task(id)
+        }
+        fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+            self.inner.delete_task(id)
+        }
+        fn purge_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+            if self.purges_left == 0 {
+                return Err(TaskError::ServiceStopped);
+            }
+            self.purges_left -= 1;
+            self.inner.purge_task(id)
+        }
+        fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+            self.inner.load_all_tasks()
+        }
+        fn query_tasks(
+            &self,
+            query: &TaskQuery,
+            context: Option<&crate::config::context::UserContext>,
+        ) -> Result<Vec<Task>, TaskError> {
+            self.inner.query_tasks(query, context)
+        }
+        fn backup(&self) -> Result<String, crate::error::StorageError> {
+            self.inner.backup()
+        }
+        fn restore(&mut self, backup_data: &str) -> Result<(), crate::error::StorageError> {
+            self.inner.restore(backup_data)
+        }
+    }
+
+    #[test]
+    fn test_purge_failure_keeps_partial_report() {
+        let dir = TempDir::new().unwrap();
+        let storage = Box::new(FailingPurges {
+            inner: FileStorageBackend::with_path(dir.path()),
+            purges_left: 1,
+        });
+        let mut manager = test_manager_with(&dir, &[], storage);
+        for description in ["Old one", "Old two"] {
+            let mut task = manager.add_task(description.to_string()).unwrap();
+            task.status = TaskStatus::Deleted;
+            task.end = Some(clock::now() - Duration::days(10));
+            manager.storage.save_task(&task).unwrap();
+        }
+
+        let policy = RetentionPolicy {
+            deleted_after: Some(Duration::days(1)),
+            ..Default::default()
+        };
+        let report = manager.purge_with_policy(&policy, false).unwrap();
+        assert_eq!(report.len(), 1);
+        assert!(report.error.unwrap().contains(&TaskError::ServiceStopped.to_string()));
+        assert_eq!(manager.storage.load_all_tasks().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_purge_failure_does_not_fail_sync() {
+        let (_dir, manager) = test_manager(&[("purge.on-sync", "eventually")]);
+        let mut manager = manager.with_sync(Box::new(DefaultSyncManager::new()));
+
+        let result = manager.sync().unwrap();
+        assert_eq!(result.tasks_purged, 0);
+        assert!(result.purge_error.unwrap().contains("purge.on-sync"));
+    }
+
+    #[test]
+    fn test_complete_task_chained_starts_next() {
+        let (_dir, manager) = test_manager(&[("dependency.chain.start", "prompt")]);
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let asked = Arc::clone(&prompts);
+        let mut manager =
+            manager.with_confirmation_handler(ConfirmationHandler::new(move |prompt| {
+                asked.lock().unwrap().push(prompt.to_string());
+                true
+            }));
+
+        let design = manager.add_task("Design".to_string()).unwrap();
+        let mut build = manager.add_task("Build".to_string()).unwrap();
+        build.depends.insert(design.id);
+        manager.storage.save_task(&build).unwrap();
+
+        let done = manager.complete_task_chained(design.id).unwrap();
+        assert_eq!(done.task.status, TaskStatus::Completed);
+        assert_eq!(done.unblocked.len(), 1);
+        assert_eq!(done.started.as_ref().map(|t| t.id), Some(build.id));
+        assert!(manager.get_task(build.id).unwrap().unwrap().is_active());
+        assert_eq!(*prompts.lock().unwrap(), ["Start next task 'Build'?"]);
+
+        // Nothing depends on the build, so nothing else starts
+        let done = manager.complete_task_chained(build.id).unwrap();
+        assert!(done.unblocked.is_empty() && done.started.is_none());
+    }
+
+    #[test]
+    fn test_subtasks_roll_up_to_parent() {
+        let (_dir, mut manager) = test_manager(&[("subtask.rollup", "complete")]);
+
+        let trip = manager.add_task("Plan trip".to_string()).unwrap();
+        let flights = manager
+            .add_subtask(trip.id, NewTask::new("Book flights").with(TaskUpdate::new().add_tag("travel")))
+            .unwrap();
+        let hotel = manager.add_subtask(trip.id, "Book hotel".into()).unwrap();
+        assert!(flights.tags.contains("travel"));
+
+        let children: Vec<Uuid> = manager.subtasks(trip.id).unwrap().iter().map(|t| t.id).collect();
+        assert_eq!(children, [flights.id, hotel.id]);
+        let parent = manager.get_task(trip.id).unwrap().unwrap();
+        assert_eq!(parent.depends, [flights.id, hotel.id].into_iter().collect());
+
+        manager.complete_task(flights.id).unwrap();
+        let parent = manager.get_task(trip.id).unwrap().unwrap();
+        assert_eq!(parent.status, TaskStatus::Pending);
+        manager.complete_task(hotel.id).unwrap();
+        let parent = manager.get_task(trip.id).unwrap().unwrap();
+        assert_eq!(parent.status, TaskStatus::Completed);
+
+        assert!(matches!(
+            manager.add_subtask(Uuid::new_v4(), "Orphan".into()),
+            Err(TaskError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_subtask_rollup_reports_through_feedback() {
+        let (_dir, mut manager) = test_manager(&[("subtask.rollup", "warn"), ("nag", "")]);
+
+        let trip = manager.add_task("Plan trip".to_string()).unwrap();
+        let flights = manager.add_subtask(trip.id, "Book flights".into()).unwrap();
+        let hotel = manager.add_subtask(trip.id, "Book hotel".into()).unwrap();
+        manager.complete_task(flights.id).unwrap();
+        assert!(manager.take_feedback().is_empty());
+        manager.complete_task(hotel.id).unwrap();
+        assert_eq!(
+            manager.take_feedback(),
+            ["All subtasks of 'Plan trip' are done; it can be completed"]
+        );
+
+        // A broken setting does not undo the completion
+        let visa = manager.add_subtask(trip.id, "Get visa".into()).unwrap();
+        manager.config.set("subtask.rollup", "sometimes");
+        let done = manager.complete_task(visa.id).unwrap();
+        assert_eq!(done.status, TaskStatus::Completed);
+        let feedback = manager.take_feedback();
+        assert_eq!(feedback.len(), 1);
+        assert!(feedback[0].starts_with("Could not roll up the parent of 'Get visa'"));
+    }
+
+    #[test]
+    fn test_checklist_items() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let task = manager.add_task("Pack for trip".to_string()).unwrap();
+        manager.add_checklist_item(task.id, "Passport").unwrap();
+        manager.add_checklist_item(task.id, "Tickets").unwrap();
+        let task = manager.toggle_checklist_item(task.id, 1).unwrap();
+        assert_eq!(Checklist::from_task(&task).to_string(), "1/2");
+        assert!(matches!(
+            manager.toggle_checklist_item(task.id, 2),
+            Err(TaskError::InvalidData { .. })
+        ));
+
+        let stored = manager.get_task(task.id).unwrap().unwrap();
+        let checklist = Checklist::from_task(&stored);
+        assert_eq!(checklist.items[0].text, "Passport");
+        assert!(checklist.items[1].done);
+
+        manager.remove_checklist_item(task.id, 0).unwrap();
+        let task = manager.remove_checklist_item(task.id, 0).unwrap();
+        assert!(!task.udas.contains_key(CHECKLIST_UDA));
+    }
+
+    #[test]
+    fn test_reschedule_where() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let due = Utc::now() + Duration::days(1);
+        let report = manager.add_task("Write report".to_string()).unwrap();
+        manager.update_task(report.id, TaskUpdate::new().due(due).project("Work".to_string())).unwrap();
+        let slides = manager.add_task("Make slides".to_string()).unwrap();
+        manager.update_task(slides.id, TaskUpdate::new().project("Work".to_string())).unwrap();
+        let chore = manager.add_task("Mow lawn".to_string()).unwrap();
+
+        let work = TaskQuery::parse_filter("project:Work").unwrap();
+        let moved = manager.reschedule_where(&work, Shift::parse("+1w").unwrap()).unwrap();
+        assert_eq!(moved.len(), 2);
+        let report = manager.get_task(report.id).unwrap().unwrap();
+        assert_eq!(report.due, Some(due + Duration::weeks(1)));
+        // Tasks without a due date move from now
+        assert!(manager.get_task(slides.id).unwrap().unwrap().due.is_some());
+        assert!(manager.get_task(chore.id).unwrap().unwrap().due.is_none());
+
+        manager.config_mut().set("reschedule.businessdays", "yes");
+        let today = Local::now();
+        let saturday = today + Duration::days(12 - today.weekday().num_days_from_monday() as i64);
+        let moved = manager
+            .reschedule_where(&work, Shift::To(saturday.with_timezone(&Utc)))
+            .unwrap();
+        assert!(moved
+            .iter()
+            .all(|t| t.due.unwrap().with_timezone(&Local).weekday() == Weekday::Mon));
+    }
+
+    #[test]
+    fn test_query_decorated() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let design = manager.add_task("Design".to_string()).unwrap();
+        let build = manager.add_task("Build".to_string()).unwrap();
+        manager
+            .update_task(
+                build.id,
+                TaskUpdate {
+                    depends: Some([design.id].into_iter().collect()),
+                    project: Some("Web".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // The blocker is outside the queried project
+        let web = TaskQuery::parse_filter("project:Web").unwrap();
+        let rows = manager.query_decorated(&web).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].blocked_by, [design.id]);
+        assert!(rows[0].virtual_tags.iter().any(|t| t == "PROJECT"));
+        assert!(rows[0].urgency > 0.0);
+        assert_eq!(rows[0].relative_due, None);
+    }
+
+    #[test]
+    fn test_update_records_field_modified() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let task = manager.add_task("File taxes".to_string()).unwrap();
+        assert_eq!(task.field_modified("due"), None);
+        let dated = manager
+            .update_task(task.id, TaskUpdate::new().due(Utc::now()))
+            .unwrap();
+        let tagged = manager
+            .update_task(task.id, TaskUpdate::new().add_tag("home"))
+            .unwrap();
+
+        let stored = manager.get_task(task.id).unwrap().unwrap();
+        assert_eq!(stored.field_modified("due"), dated.modified);
+        assert_eq!(stored.field_modified("tags"), tagged.modified);
+        assert_eq!(stored.field_modified("project"), None);
+    }
+
+    #[test]
+    fn test_update_counts_pushed_due_dates() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let task = manager.add_task("Clean garage".to_string()).unwrap();
+        let due = Utc::now() + chrono::Duration::days(1);
+        for days in [0, 7, 3, 14] {
+            manager
+                .update_task(task.id, TaskUpdate::new().due(due + chrono::Duration::days(days)))
+                .unwrap();
+        }
+        let stored = manager.get_task(task.id).unwrap().unwrap();
+        assert_eq!(stored.push_count(), 2);
+
+        let pushed = TaskQuery::parse_filter("pushed.gte:2").unwrap();
+        assert_eq!(manager.query_tasks(&pushed).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_add_unless_similar() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let milk = manager.add_task("Buy milk".to_string()).unwrap();
+        let done = manager.add_task("Pay rent".to_string()).unwrap();
+        manager.complete_task(done.id).unwrap();
+
+        match manager.add_unless_similar(NewTask::new("buy milk!")).unwrap() {
+            AddOutcome::Similar(similar) => {
+                assert_eq!(similar.len(), 1);
+                assert_eq!(similar[0].task.id, milk.id);
+                assert_eq!(similar[0].similarity, 1.0);
+            }
+            other => panic!("expected a duplicate warning, got {other:?}"),
+        }
+        assert_eq!(manager.pending_tasks().unwrap().len(), 1);
+
+        // Completed tasks are not duplicates
+        let rent = NewTask::new("Pay rent").with(TaskUpdate::new().project("Home"));
+        match manager.add_unless_similar(rent).unwrap() {
+            AddOutcome::Added(task) => assert_eq!(task.project.as_deref(), Some("Home")),
+            other => panic!("expected the task to be added, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_hits() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        manager.add_task("Call the bank".to_string()).unwrap();
+        let once = manager.add_task("Review invoice".to_string()).unwrap();
+        let twice = manager
+            .add_task("Invoice: send invoice to client".to_string())
+            .unwrap();
+
+        // Case-sensitive by default
+        let hits = manager.search("Invoice", &TaskQuery::default()).unwrap();
+        let order: Vec<Uuid> = hits.iter().map(|h| h.task.id).collect();
+        assert_eq!(order, [twice.id]);
+
+        manager.config_mut().set("search.case.sensitive", "no");
+        let hits = manager.search("INVOICE", &TaskQuery::default()).unwrap();
+        let order: Vec<Uuid> = hits.iter().map(|h| h.task.id).collect();
+        assert_eq!(order, [twice.id, once.id]);
+        assert!(hits[0].score > hits[1].score);
+        assert!(manager.search("  ", &TaskQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snooze() {
+        let (_dir, mut manager) = test_manager(&[]);
+
+        let task = manager.add_task("Reply to Alex".to_string()).unwrap();
+        let until = Utc::now() + Duration::hours(2);
+        let snoozed = manager.snooze(task.id, Snooze::Until(until)).unwrap();
+        assert_eq!(snoozed.wait, Some(until));
+        assert_eq!(snoozed.status, TaskStatus::Waiting);
+        assert_eq!(snoozed.scheduled, None);
+        assert!(manager.pending_tasks().unwrap().is_empty());
+
+        manager.config_mut().set("snooze.scheduled", "yes");
+        let snoozed = manager.snooze(task.id, Snooze::TomorrowMorning).unwrap();
+        assert!(snoozed.wait.unwrap() > until);
+        assert_eq!(snoozed.scheduled, snoozed.wait);
+    }
+
+    #[test]
+    fn test_explain_match_includes_context() {
+        let (_dir, mut manager) = test_manager(&[
+            ("context", "work"),
+            ("context.work", "project:Work"),
+        ]);
+
+        let mut task = manager.add_task("Call plumber".to_string()).unwrap();
+        task.project = Some("Home".to_string());
+        task.tags.insert("phone".to_string());
+        manager.storage.save_task(&task).unwrap();
+
+        let query = TaskQuery {
+            tag_filter: Some(TagFilter::has_tag("phone".to_string())),
+            ..Default::default()
+        };
+        assert!(manager.query_tasks(&query).unwrap().is_empty());
+        let trace = manager.explain_match(task.id, &query).unwrap();
+        assert!(!trace.matched());
+        let failed: Vec<&str> = trace.failures().map(|c| c.clause.as_str()).collect();
+        assert_eq!(failed, ["context work"]);
+
+        let ignoring = TaskQuery {
+            filter_mode: Some(FilterMode::IgnoreContext),
+            ..query
+        };
+        assert!(manager.explain_match(task.id, &ignoring).unwrap().matched());
+        assert_eq!(manager.query_tasks(&ignoring).unwrap().len(), 1);
+        assert!(matches!(
+            manager.explain_match(Uuid::new_v4(), &ignoring),
+            Err(TaskError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_boxed_manager_in_generic_wrapper() {
+        let (dir, manager) = test_manager(&[]);
+        let managers: Vec<DynTaskManager> = vec![Box::new(manager)];
+        for manager in managers {
+            let mut cached = CachedTaskManager::new(manager);
+            let task = cached.add_task("Behind a pointer".to_string()).unwrap();
+            assert_eq!(cached.pending_tasks().unwrap().len(), 1);
+            cached.complete_task(task.id).unwrap();
+            assert!(cached.pending_tasks().unwrap().is_empty());
+            assert_eq!(cached.config().data_dir, dir.path());
+        }
+    }
 }
@@ -0,0 +1,211 @@
+//! Operation metrics
+//!
+//! [`DefaultTaskManager`](crate::task::manager::DefaultTaskManager) records
+//! a [`Metrics`] snapshot as it runs: per-operation counts, error counts and
+//! durations, hook failures, and sync statistics. With the `prometheus`
+//! feature the snapshot can be rendered in the Prometheus text exposition
+//! format for scraping by a self-hosted frontend.
+
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::error::TaskError;
+
+/// Counters and timings for one kind of operation
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OperationStats {
+    /// Number of calls
+    pub count: u64,
+    /// Number of calls that returned an error
+    pub errors: u64,
+    /// Total time spent across all calls
+    pub total_duration: Duration,
+    /// Slowest single call
+    pub max_duration: Duration,
+}
+
+impl OperationStats {
+    /// Mean duration per call
+    pub fn average_duration(&self) -> Duration {
+        // Divided in u128 nanoseconds: the count may not fit in a u32
+        self.total_duration
+            .as_nanos()
+            .checked_div(u128::from(self.count))
+            .map_or(Duration::ZERO, |nanos| {
+                Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+            })
+    }
+}
+
+/// Synchronization statistics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncStats {
+    pub runs: u64,
+    pub failures: u64,
+    pub tasks_pulled: u64,
+    pub tasks_pushed: u64,
+    pub conflicts_resolved: u64,
+    /// Completion time of the last successful sync
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+/// Snapshot of task manager metrics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    /// Stats keyed by operation name (`add`, `update`, `query`, ...)
+    pub operations: BTreeMap<String, OperationStats>,
+    /// Operations that failed because a hook rejected or errored
+    pub hook_failures: u64,
+    pub sync: SyncStats,
+}
+
+impl Metrics {
+    /// Record one call of `operation`
+    pub fn record<T>(&mut self, operation: &str, duration: Duration, result: &Result<T, TaskError>) {
+        let stats = self.operations.entry(operation.to_string()).or_default();
+        stats.count += 1;
+        stats.total_duration += duration;
+        stats.max_duration = stats.max_duration.max(duration);
+        if let Err(e) = result {
+            stats.errors += 1;
+            if matches!(e.root(), TaskError::Hook { .. } | TaskError::HookFailed { .. }) {
+                self.hook_failures += 1;
+            }
+        }
+    }
+
+    /// Stats for one operation, if it has run
+    pub fn operation(&self, operation: &str) -> Option<&OperationStats> {
+        self.operations.get(operation)
+    }
+
+    /// Render the metrics in Prometheus text exposition format
+    #[cfg(feature = "prometheus")]
+    pub fn to_prometheus(&self) -> String {
+        use std::fmt::Write;
+
+        let mut out = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{labels} {value}");
+            }
+        };
+        let per_op = |f: &dyn Fn(&OperationStats) -> String| {
+            self.operations
+                .iter()
+                .map(|(op, s)| (format!("{{operation=\"{op}\"}}"), f(s)))
+                .collect::<Vec<_>>()
+        };
+
+        family(
+            "taskwarrior_operations_total",
+            "counter",
+            "Task manager operations",
+            per_op(&|s| s.count.to_string()),
+        );
+        family(
+            "taskwarrior_operation_errors_total",
+            "counter",
+            "Task manager operations that returned an error",
+            per_op(&|s| s.errors.to_string()),
+        );
+        family(
+            "taskwarrior_operation_duration_seconds_sum",
+            "counter",
+            "Total time spent in task manager operations",
+            per_op(&|s| s.total_duration.as_secs_f64().to_string()),
+        );
+        family(
+            "taskwarrior_operation_duration_seconds_max",
+            "gauge",
+            "Slowest single task manager operation",
+            per_op(&|s| s.max_duration.as_secs_f64().to_string()),
+        );
+        family(
+            "taskwarrior_hook_failures_total",
+            "counter",
+            "Operations that failed in a hook",
+            vec![(String::new(), self.hook_failures.to_string())],
+        );
+        family(
+            "taskwarrior_sync_runs_total",
+            "counter",
+            "Sync attempts",
+            vec![(String::new(), self.sync.runs.to_string())],
+        );
+        family(
+            "taskwarrior_sync_failures_total",
+            "counter",
+            "Failed sync attempts",
+            vec![(String::new(), self.sync.failures.to_string())],
+        );
+        family(
+            "taskwarrior_sync_tasks_total",
+            "counter",
+            "Tasks transferred by sync",
+            vec![
+                ("{direction=\"pulled\"}".to_string(), self.sync.tasks_pulled.to_string()),
+                ("{direction=\"pushed\"}".to_string(), self.sync.tasks_pushed.to_string()),
+            ],
+        );
+        if let Some(last) = self.sync.last_success {
+            family(
+                "taskwarrior_sync_last_success_timestamp_seconds",
+                "gauge",
+                "Unix time of the last successful sync",
+                vec![(String::new(), last.timestamp().to_string())],
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_counts_errors_and_hook_failures() {
+        let mut metrics = Metrics::default();
+        metrics.record("add", Duration::from_millis(4), &Ok::<(), TaskError>(()));
+        metrics.record(
+            "add",
+            Duration::from_millis(2),
+            &Err::<(), _>(TaskError::Hook {
+                message: "rejected".to_string(),
+            }),
+        );
+
+        let add = metrics.operation("add").unwrap();
+        assert_eq!(add.count, 2);
+        assert_eq!(add.errors, 1);
+        assert_eq!(add.max_duration, Duration::from_millis(4));
+        assert_eq!(add.average_duration(), Duration::from_millis(3));
+        assert_eq!(metrics.hook_failures, 1);
+    }
+
+    #[test]
+    fn test_average_duration_with_large_count() {
+        let stats = OperationStats {
+            count: u64::from(u32::MAX) + 2,
+            total_duration: Duration::from_secs(u64::from(u32::MAX) + 2),
+            ..Default::default()
+        };
+        assert_eq!(stats.average_duration(), Duration::from_secs(1));
+        assert_eq!(OperationStats::default().average_duration(), Duration::ZERO);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[test]
+    fn test_prometheus_text() {
+        let mut metrics = Metrics::default();
+        metrics.record("query", Duration::from_millis(1), &Ok::<(), TaskError>(()));
+        let text = metrics.to_prometheus();
+        assert!(text.contains("# TYPE taskwarrior_operations_total counter"));
+        assert!(text.contains("taskwarrior_operations_total{operation=\"query\"} 1"));
+        assert!(text.contains("taskwarrior_hook_failures_total 0"));
+    }
+}
@@ -4,13 +4,47 @@
 //! task models, operations, and the main TaskManager trait.
 
 pub mod annotation;
+pub mod audit;
+pub mod cache;
+pub mod checklist;
+pub mod completion;
+pub mod decorated;
+pub mod dependencies;
+pub mod display;
+pub mod duplicates;
+pub mod effort;
+pub mod escalation;
 pub mod manager;
+pub mod metrics;
 pub mod model;
 pub mod operations;
+pub mod provenance;
 pub mod recurrence;
+pub mod reschedule;
+pub mod retention;
+pub mod snooze;
+pub mod subtasks;
+pub mod tags;
+pub mod uda;
+pub mod urgency;
 
 // Re-export main types
 pub use annotation::Annotation;
-pub use manager::{TaskManager, TaskManagerBuilder};
+pub use cache::CachedTaskManager;
+pub use checklist::{Checklist, ChecklistItem};
+pub use decorated::DecoratedTask;
+pub use dependencies::DependencyGraph;
+pub use display::DisplayOptions;
+pub use duplicates::{AddOutcome, SimilarTask};
+pub use effort::ProjectEffort;
+pub use escalation::{EscalationPolicy, EscalationReport, EscalationRule};
+pub use manager::{DynTaskManager, NewTask, TaskManager, TaskManagerBuilder};
+pub use metrics::Metrics;
 pub use model::{Priority, Task, TaskStatus};
 pub use recurrence::RecurrencePattern;
+pub use reschedule::Shift;
+pub use retention::{PurgeReport, RetentionPolicy};
+pub use snooze::Snooze;
+pub use tags::{TagInfo, TagRegistry, TagUsage};
+pub use uda::{UdaDefinition, UdaSchema, UdaType, UdaTypes};
+pub use urgency::{due_factor, AgingCurve, PriorityAging, UrgencyCoefficients, UrgencyTerm};
@@ -2,11 +2,13 @@
 //!
 //! This module contains the core Task struct and related types.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::clock;
+use crate::date::relative::{format_iso_duration, parse_duration, parse_iso_duration};
 use crate::task::{Annotation, RecurrencePattern};
 
 /// Task status enumeration
@@ -35,6 +37,27 @@ pub enum Priority {
     High,
 }
 
+impl Priority {
+    /// Taskwarrior code for this priority (`H`, `M` or `L`)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Priority::High => "H",
+            Priority::Medium => "M",
+            Priority::Low => "L",
+        }
+    }
+
+    /// Parse a built-in priority code; custom codes return None
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "H" => Some(Priority::High),
+            "M" => Some(Priority::Medium),
+            "L" => Some(Priority::Low),
+            _ => None,
+        }
+    }
+}
+
 /// User-defined attribute value types
 #[derive(Debug, Clone, PartialEq)]
 pub enum UdaValue {
@@ -192,6 +215,10 @@ pub struct Task {
 
     /// Start time for time tracking
     pub start: Option<DateTime<Utc>>,
+
+    /// Owning user on shared databases (stamped from `user.name` when
+    /// `owner.stamp` is enabled)
+    pub owner: Option<String>,
 }
 
 impl Serialize for Task {
@@ -261,6 +288,9 @@ impl Serialize for Task {
         if let Some(start) = &self.start {
             map.serialize_entry("start", start)?;
         }
+        if let Some(owner) = &self.owner {
+            map.serialize_entry("owner", owner)?;
+        }
 
         // Serialize UDAs as flattened fields
         for (key, value) in &self.udas {
@@ -271,6 +301,46 @@ impl Serialize for Task {
     }
 }
 
+/// Accepted encodings of the `depends` attribute.
+///
+/// Taskwarrior 2.x emitted a comma-separated string of UUIDs while newer
+/// exports use a JSON array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependsRepr {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl DependsRepr {
+    fn into_set(self) -> Result<HashSet<Uuid>, String> {
+        match self {
+            DependsRepr::List(items) => items
+                .iter()
+                .map(|s| Uuid::parse_str(s.trim()).map_err(|e| format!("invalid dependency '{s}': {e}")))
+                .collect(),
+            DependsRepr::Joined(joined) => parse_depends_list(&joined),
+        }
+    }
+}
+
+/// Parse a comma- or whitespace-separated list of dependency UUIDs
+pub fn parse_depends_list(value: &str) -> Result<HashSet<Uuid>, String> {
+    value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            Uuid::parse_str(token).map_err(|e| format!("invalid dependency '{token}': {e}"))
+        })
+        .collect()
+}
+
+/// Extract the dependency UUID from a TaskChampion `dep_<uuid>` key
+pub fn dependency_key_uuid(key: &str) -> Option<Uuid> {
+    key.strip_prefix("dep_")
+        .and_then(|rest| Uuid::parse_str(rest).ok())
+}
+
 impl<'de> Deserialize<'de> for Task {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -314,6 +384,7 @@ impl<'de> Deserialize<'de> for Task {
                 let mut mask = None;
                 let mut active = false;
                 let mut start = None;
+                let mut owner = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -348,7 +419,21 @@ impl<'de> Deserialize<'de> for Task {
                             end = Some(map.next_value()?);
                         }
                         "priority" => {
-                            priority = Some(map.next_value()?);
+                            // Codes outside H/M/L come from a customized
+                            // `uda.priority.values` and are kept as a UDA
+                            let raw: Option<String> = map.next_value()?;
+                            match raw.as_deref() {
+                                None | Some("") => {}
+                                Some(code) => match Priority::from_code(code) {
+                                    Some(p) => priority = Some(p),
+                                    None => {
+                                        udas.insert(
+                                            "priority".to_string(),
+                                            UdaValue::String(code.to_string()),
+                                        );
+                                    }
+                                },
+                            }
                         }
                         "project" => {
                             project = Some(map.next_value()?);
@@ -360,7 +445,8 @@ impl<'de> Deserialize<'de> for Task {
                             annotations = map.next_value()?;
                         }
                         "depends" => {
-                            depends = map.next_value()?;
+                            let raw: DependsRepr = map.next_value()?;
+                            depends.extend(raw.into_set().map_err(de::Error::custom)?);
                         }
                         "urgency" => {
                             urgency = map.next_value()?;
@@ -380,6 +466,14 @@ impl<'de> Deserialize<'de> for Task {
                         "start" => {
                             start = Some(map.next_value()?);
                         }
+                        "owner" => {
+                            owner = Some(map.next_value()?);
+                        }
+                        // TaskChampion stores each dependency as its own `dep_<uuid>` key
+                        _ if dependency_key_uuid(&key).is_some() => {
+                            let _: de::IgnoredAny = map.next_value()?;
+                            depends.extend(dependency_key_uuid(&key));
+                        }
                         // Unknown fields are treated as UDAs
                         _ => {
                             // Try to deserialize as UdaValue using its untagged deserializer
@@ -417,6 +511,7 @@ impl<'de> Deserialize<'de> for Task {
                     mask,
                     active,
                     start,
+                    owner,
                 })
             }
         }
@@ -429,11 +524,11 @@ impl Task {
     /// Create a new task with minimal required fields
     pub fn new(description: String) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: clock::new_id(),
             display_id: None,
             description,
             status: TaskStatus::Pending,
-            entry: Utc::now(),
+            entry: clock::now(),
             modified: None,
             due: None,
             scheduled: None,
@@ -451,14 +546,33 @@ impl Task {
             mask: None,
             active: false,
             start: None,
+            owner: None,
         }
     }
 
+    /// Stamp `modified` with the current time. It doubles as the etag, so
+    /// it always moves forward, even where storage keeps whole seconds only.
+    pub fn touch(&mut self) {
+        self.touch_at(clock::now());
+    }
+
+    /// [`touch`](Self::touch) with an explicit current time
+    pub fn touch_at(&mut self, now: DateTime<Utc>) {
+        let previous = self.modified.unwrap_or(self.entry);
+        self.modified = Some(if now.timestamp() > previous.timestamp() {
+            now
+        } else {
+            previous
+                .checked_add_signed(Duration::seconds(1))
+                .unwrap_or(previous)
+        });
+    }
+
     /// Mark task as completed
     pub fn complete(&mut self) {
         self.status = TaskStatus::Completed;
-        self.end = Some(Utc::now());
-        self.modified = Some(Utc::now());
+        self.end = Some(clock::now());
+        self.touch();
         self.active = false;
         self.start = None;
     }
@@ -466,8 +580,8 @@ impl Task {
     /// Mark task as deleted
     pub fn delete(&mut self) {
         self.status = TaskStatus::Deleted;
-        self.end = Some(Utc::now());
-        self.modified = Some(Utc::now());
+        self.end = Some(clock::now());
+        self.touch();
         self.active = false;
         self.start = None;
     }
@@ -475,28 +589,157 @@ impl Task {
     /// Start working on task (time tracking)
     pub fn start(&mut self) {
         self.active = true;
-        self.start = Some(Utc::now());
-        self.modified = Some(Utc::now());
+        self.start = Some(clock::now());
+        self.touch();
     }
 
     /// Stop working on task (time tracking)
     pub fn stop(&mut self) {
         self.active = false;
         self.start = None;
-        self.modified = Some(Utc::now());
+        self.touch();
+    }
+
+    /// Priority code, including custom codes from a configured priority
+    /// scheme that don't map onto [`Priority`]
+    pub fn priority_code(&self) -> Option<&str> {
+        match (&self.priority, self.udas.get("priority")) {
+            (Some(p), _) => Some(p.code()),
+            (None, Some(UdaValue::String(code))) => Some(code.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Set the priority from a code; built-in codes use the typed
+    /// [`Priority`], anything else is stored as the `priority` UDA
+    pub fn set_priority_code(&mut self, code: Option<&str>) {
+        self.udas.remove("priority");
+        self.priority = None;
+        match code {
+            None | Some("") => {}
+            Some(code) => match Priority::from_code(code) {
+                Some(p) => self.priority = Some(p),
+                None => {
+                    self.udas
+                        .insert("priority".to_string(), UdaValue::String(code.to_string()));
+                }
+            },
+        }
+        self.touch();
+    }
+
+    /// Estimated work, from the `estimate` duration UDA
+    pub fn estimate(&self) -> Option<Duration> {
+        self.duration_uda("estimate")
+    }
+
+    /// Set or clear the `estimate` duration UDA
+    pub fn set_estimate(&mut self, estimate: Option<Duration>) {
+        self.set_duration_uda("estimate", estimate);
+    }
+
+    /// Work done so far, from the `effort` duration UDA
+    pub fn effort(&self) -> Option<Duration> {
+        self.duration_uda("effort")
+    }
+
+    /// Set or clear the `effort` duration UDA
+    pub fn set_effort(&mut self, effort: Option<Duration>) {
+        self.set_duration_uda("effort", effort);
+    }
+
+    /// Latitude in degrees, from the `latitude` numeric UDA
+    pub fn latitude(&self) -> Option<f64> {
+        self.number_uda("latitude").filter(|v| (-90.0..=90.0).contains(v))
+    }
+
+    /// Longitude in degrees, from the `longitude` numeric UDA
+    pub fn longitude(&self) -> Option<f64> {
+        self.number_uda("longitude").filter(|v| (-180.0..=180.0).contains(v))
+    }
+
+    /// When `field` (an attribute such as `due`, or a UDA name) last
+    /// changed, as recorded in the [`FIELD_MODIFIED_UDA`](crate::task::provenance::FIELD_MODIFIED_UDA)
+    /// map; `None` if no change has been recorded
+    pub fn field_modified(&self, field: &str) -> Option<DateTime<Utc>> {
+        crate::task::provenance::FieldTimes::from_task(self).get(field)
+    }
+
+    /// How often the due date has been moved later, from the
+    /// [`PUSHED_UDA`](crate::task::provenance::PUSHED_UDA) UDA
+    pub fn push_count(&self) -> u32 {
+        self.number_uda(crate::task::provenance::PUSHED_UDA)
+            .filter(|n| *n >= 0.0)
+            .map_or(0, |n| n as u32)
+    }
+
+    /// Where the task can be done, as `(latitude, longitude)` in degrees;
+    /// `None` unless both coordinates are set and in range
+    pub fn location(&self) -> Option<(f64, f64)> {
+        Some((self.latitude()?, self.longitude()?))
+    }
+
+    /// Set or clear the `latitude` and `longitude` UDAs
+    pub fn set_location(&mut self, location: Option<(f64, f64)>) {
+        match location {
+            Some((latitude, longitude)) => {
+                self.udas
+                    .insert("latitude".to_string(), UdaValue::Number(latitude));
+                self.udas
+                    .insert("longitude".to_string(), UdaValue::Number(longitude));
+            }
+            None => {
+                self.udas.remove("latitude");
+                self.udas.remove("longitude");
+            }
+        }
+        self.touch();
+    }
+
+    /// Value of a numeric UDA, also accepting numbers stored as text
+    pub fn number_uda(&self, name: &str) -> Option<f64> {
+        match self.udas.get(name)? {
+            UdaValue::Number(n) => Some(*n).filter(|n| n.is_finite()),
+            UdaValue::String(s) => s.trim().parse().ok().filter(|n: &f64| n.is_finite()),
+            UdaValue::Date(_) => None,
+        }
+    }
+
+    /// Value of a duration UDA: ISO 8601 as Taskwarrior writes it
+    /// ("PT2H"), a shorthand such as "90min", or a number of seconds
+    pub fn duration_uda(&self, name: &str) -> Option<Duration> {
+        match self.udas.get(name)? {
+            UdaValue::String(s) => parse_iso_duration(s).or_else(|_| parse_duration(s)).ok(),
+            UdaValue::Number(n) => Duration::try_seconds(*n as i64),
+            UdaValue::Date(_) => None,
+        }
+    }
+
+    /// Store a duration UDA in ISO 8601 form, or remove it
+    pub fn set_duration_uda(&mut self, name: &str, value: Option<Duration>) {
+        match value {
+            Some(d) => {
+                self.udas
+                    .insert(name.to_string(), UdaValue::String(format_iso_duration(d)));
+            }
+            None => {
+                self.udas.remove(name);
+            }
+        }
+        self.touch();
     }
 
     /// Add a tag to the task
     pub fn add_tag(&mut self, tag: String) {
         self.tags.insert(tag);
-        self.modified = Some(Utc::now());
+        self.touch();
     }
 
     /// Remove a tag from the task
     pub fn remove_tag(&mut self, tag: &str) -> bool {
         let removed = self.tags.remove(tag);
         if removed {
-            self.modified = Some(Utc::now());
+            self.touch();
         }
         removed
     }
@@ -509,7 +752,7 @@ impl Task {
     /// Add an annotation to the task
     pub fn add_annotation(&mut self, annotation: Annotation) {
         self.annotations.push(annotation);
-        self.modified = Some(Utc::now());
+        self.touch();
     }
 
     /// Remove an annotation by description
@@ -518,26 +761,107 @@ impl Task {
         self.annotations.retain(|a| a.description != description);
         let removed = self.annotations.len() < initial_len;
         if removed {
-            self.modified = Some(Utc::now());
+            self.touch();
         }
         removed
     }
 
     /// Check if task is overdue
     pub fn is_overdue(&self) -> bool {
-        self.due.is_some_and(|due| due < Utc::now()) && self.status == TaskStatus::Pending
+        self.due.is_some_and(|due| due < clock::now()) && self.status == TaskStatus::Pending
     }
 
     /// Check if task is active (being worked on)
     pub fn is_active(&self) -> bool {
         self.active && self.start.is_some()
     }
+
+    /// Version tag for optimistic concurrency: the last modification time
+    /// (creation time if never modified) in whole seconds, the precision
+    /// TaskChampion stores. Pass it to
+    /// [`TaskUpdate::if_match`](crate::task::manager::TaskUpdate::if_match)
+    /// to reject the update if someone else changed the task meanwhile.
+    pub fn etag(&self) -> String {
+        self.modified
+            .unwrap_or(self.entry)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    }
+
+    /// Whether the task is hidden at `now`: waiting, or pending with a
+    /// `wait` date still in the future
+    pub fn is_waiting_at(&self, now: DateTime<Utc>) -> bool {
+        match self.status {
+            TaskStatus::Waiting | TaskStatus::Pending => self.wait.is_some_and(|wait| wait > now),
+            _ => false,
+        }
+    }
+
+    /// Turn a waiting task whose wait date has passed (or was never set)
+    /// back into a pending one; returns whether the status changed
+    pub fn promote_if_unwaited(&mut self, now: DateTime<Utc>) -> bool {
+        if self.status != TaskStatus::Waiting || self.wait.is_some_and(|wait| wait > now) {
+            return false;
+        }
+        self.status = TaskStatus::Pending;
+        self.touch_at(now);
+        true
+    }
+
+    /// `entry`, `modified`, `start` and `end` values later than `limit`.
+    /// These record when something happened, so a future value means the
+    /// task came from a device whose clock was ahead.
+    pub fn future_timestamps(&self, limit: DateTime<Utc>) -> Vec<(&'static str, DateTime<Utc>)> {
+        [
+            ("entry", Some(self.entry)),
+            ("modified", self.modified),
+            ("start", self.start),
+            ("end", self.end),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| Some((field, value.filter(|v| *v > limit)?)))
+        .collect()
+    }
+
+    /// Pull future `entry`, `modified`, `start` and `end` values back to
+    /// `now`; returns whether anything changed
+    pub fn clamp_future_timestamps(&mut self, now: DateTime<Utc>) -> bool {
+        let mut changed = false;
+        let mut clamp = |value: &mut DateTime<Utc>| {
+            if *value > now {
+                *value = now;
+                changed = true;
+            }
+        };
+        clamp(&mut self.entry);
+        for value in [&mut self.modified, &mut self.start, &mut self.end].into_iter().flatten() {
+            clamp(value);
+        }
+        changed
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_promote_if_unwaited() {
+        let now = Utc::now();
+        let mut task = Task::new("Later".to_string());
+        task.status = TaskStatus::Waiting;
+        task.wait = Some(now + chrono::Duration::days(1));
+        assert!(task.is_waiting_at(now));
+        assert!(!task.promote_if_unwaited(now));
+        assert_eq!(task.status, TaskStatus::Waiting);
+
+        let later = now + chrono::Duration::days(2);
+        assert!(!task.is_waiting_at(later));
+        assert!(task.promote_if_unwaited(later));
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(task.modified, Some(later));
+        assert!(!task.promote_if_unwaited(later));
+    }
+
     #[test]
     fn test_new_task() {
         let task = Task::new("Test task".to_string());
@@ -546,6 +870,68 @@ mod tests {
         assert!(!task.active);
     }
 
+    #[test]
+    fn test_new_task_under_frozen_clock() {
+        use crate::clock::{FixedClock, SequentialIds};
+        use chrono::TimeZone;
+
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let time = FixedClock::new(start);
+        let _guard = clock::deterministic(time.clone(), SequentialIds::new());
+
+        let mut task = Task::new("Frozen".to_string());
+        assert_eq!(task.id, Uuid::from_u128(1));
+        assert_eq!(task.entry, start);
+        time.advance(chrono::Duration::minutes(30));
+        task.complete();
+        assert_eq!(task.end, Some(start + chrono::Duration::minutes(30)));
+        assert_eq!(task.modified, task.end);
+        assert_eq!(Task::new("Next".to_string()).id, Uuid::from_u128(2));
+    }
+
+    #[test]
+    fn test_mutations_move_modified_forward() {
+        use crate::clock::{FixedClock, SequentialIds};
+        use chrono::TimeZone;
+
+        let start = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let _guard = clock::deterministic(FixedClock::new(start), SequentialIds::new());
+
+        // Every change within the same second still gets a newer etag
+        let mut task = Task::new("Busy".to_string());
+        let mut seen = vec![task.modified.unwrap_or(task.entry)];
+        task.start();
+        seen.extend(task.modified);
+        task.add_annotation(Annotation::new("note".to_string()));
+        seen.extend(task.modified);
+        task.remove_annotation("note");
+        seen.extend(task.modified);
+        task.stop();
+        seen.extend(task.modified);
+        task.complete();
+        seen.extend(task.modified);
+        assert_eq!(seen.len(), 6);
+        assert!(seen.windows(2).all(|w| w[0].timestamp() < w[1].timestamp()), "{seen:?}");
+    }
+
+    #[test]
+    fn test_future_timestamps() {
+        let now = Utc::now();
+        let mut task = Task::new("Skewed".to_string());
+        task.entry = now + chrono::Duration::hours(2);
+        task.modified = Some(now + chrono::Duration::hours(3));
+        task.end = Some(now - chrono::Duration::hours(1));
+
+        let fields: Vec<&str> = task.future_timestamps(now).iter().map(|(f, _)| *f).collect();
+        assert_eq!(fields, vec!["entry", "modified"]);
+
+        assert!(task.clamp_future_timestamps(now));
+        assert_eq!(task.entry, now);
+        assert_eq!(task.modified, Some(now));
+        assert!(task.future_timestamps(now).is_empty());
+        assert!(!task.clamp_future_timestamps(now));
+    }
+
     #[test]
     fn test_complete_task() {
         let mut task = Task::new("Test task".to_string());
@@ -664,4 +1050,89 @@ mod tests {
         // display_id should be serialized as "id" when present
         assert_eq!(json_value.get("id").unwrap().as_u64().unwrap(), 42);
     }
+
+    #[test]
+    fn test_depends_accepts_string_array_and_dep_keys() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let base = format!(
+            r#""uuid":"{}","description":"d","entry":"2024-01-01T00:00:00Z""#,
+            Uuid::new_v4()
+        );
+
+        let from_array: Task =
+            serde_json::from_str(&format!(r#"{{{base},"depends":["{a}","{b}"]}}"#)).unwrap();
+        let from_string: Task =
+            serde_json::from_str(&format!(r#"{{{base},"depends":"{a},{b}"}}"#)).unwrap();
+        let from_keys: Task = serde_json::from_str(&format!(
+            r#"{{{base},"dep_{a}":"x","dep_{b}":"x"}}"#
+        ))
+        .unwrap();
+        let keys_then_field: Task = serde_json::from_str(&format!(
+            r#"{{{base},"dep_{a}":"x","depends":["{b}"]}}"#
+        ))
+        .unwrap();
+
+        let expected: HashSet<Uuid> = [a, b].into_iter().collect();
+        assert_eq!(from_array.depends, expected);
+        assert_eq!(from_string.depends, expected);
+        assert_eq!(from_keys.depends, expected);
+        assert_eq!(keys_then_field.depends, expected);
+        assert!(from_keys.udas.is_empty());
+
+        let round_trip: Task =
+            serde_json::from_str(&serde_json::to_string(&from_string).unwrap()).unwrap();
+        assert_eq!(round_trip.depends, expected);
+    }
+
+    #[test]
+    fn test_depends_invalid_uuid_is_error() {
+        let json = format!(
+            r#"{{"uuid":"{}","description":"d","entry":"2024-01-01T00:00:00Z","depends":"nope"}}"#,
+            Uuid::new_v4()
+        );
+        assert!(serde_json::from_str::<Task>(&json).is_err());
+    }
+
+    #[test]
+    fn test_owner_round_trip() {
+        let mut task = Task::new("Owned".to_string());
+        task.owner = Some("alice".to_string());
+
+        let json = serde_json::to_string(&task).unwrap();
+        assert!(json.contains(r#""owner":"alice""#));
+        let back: Task = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.owner.as_deref(), Some("alice"));
+        assert!(!back.udas.contains_key("owner"));
+    }
+
+    #[test]
+    fn test_custom_priority_code_round_trip() {
+        let mut task = Task::new("Critical".to_string());
+        task.set_priority_code(Some("C"));
+        assert_eq!(task.priority, None);
+        assert_eq!(task.priority_code(), Some("C"));
+
+        let json = serde_json::to_string(&task).unwrap();
+        let back: Task = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.priority_code(), Some("C"));
+
+        task.set_priority_code(Some("H"));
+        assert_eq!(task.priority, Some(Priority::High));
+        assert!(!task.udas.contains_key("priority"));
+    }
+
+    #[test]
+    fn test_out_of_range_estimate_is_none() {
+        let mut task = Task::new("Forever".to_string());
+        task.set_estimate(Some(Duration::hours(2)));
+        assert_eq!(task.estimate(), Some(Duration::hours(2)));
+
+        for huge in ["P99999999999999D", "999999999999999d"] {
+            task.udas.insert("estimate".to_string(), UdaValue::String(huge.to_string()));
+            assert_eq!(task.estimate(), None, "{huge}");
+        }
+        task.udas.insert("estimate".to_string(), UdaValue::Number(1e300));
+        assert_eq!(task.estimate(), None);
+    }
 }
@@ -0,0 +1,176 @@
+//! When each field of a task last changed
+//!
+//! `modified` says when a task last changed, not what changed. Conflict
+//! resolution and reports such as "due dates moved three times" need to
+//! know per field, so every update made through the task manager records
+//! the time of each field it changed in the [`FIELD_MODIFIED_UDA`] string
+//! UDA, a JSON object keyed by attribute name:
+//!
+//! ```text
+//! fieldmodified:{"due":"2025-03-12T09:30:00Z","tags":"2025-03-10T17:02:11Z"}
+//! ```
+//!
+//! The map travels with the task through export, import and sync. Fields
+//! never changed since the map was introduced have no entry; read them with
+//! [`Task::field_modified`].
+//!
+//! Updates also count how often a due date was moved later in the numeric
+//! [`PUSHED_UDA`] UDA ([`Task::push_count`]), so chronically deferred tasks
+//! can be found with `pushed.gte:3` or shown in a `pushed` report column.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::task::model::UdaValue;
+use crate::task::Task;
+
+/// UDA holding the per-field modification times
+pub const FIELD_MODIFIED_UDA: &str = "fieldmodified";
+
+/// Numeric UDA counting how often the due date was moved later
+pub const PUSHED_UDA: &str = "pushed";
+
+/// Last change of each field that has changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldTimes {
+    pub fields: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl FieldTimes {
+    /// The times stored on `task`, empty when there are none
+    pub fn from_task(task: &Task) -> Self {
+        match task.udas.get(FIELD_MODIFIED_UDA) {
+            Some(UdaValue::String(raw)) => serde_json::from_str(raw).unwrap_or_default(),
+            _ => Self::default(),
+        }
+    }
+
+    /// Store the times on `task`
+    pub fn write_to(&self, task: &mut Task) {
+        if self.fields.is_empty() {
+            task.udas.remove(FIELD_MODIFIED_UDA);
+        } else if let Ok(raw) = serde_json::to_string(self) {
+            task.udas
+                .insert(FIELD_MODIFIED_UDA.to_string(), UdaValue::String(raw));
+        }
+    }
+
+    pub fn get(&self, field: &str) -> Option<DateTime<Utc>> {
+        self.fields.get(field).copied()
+    }
+}
+
+/// Attributes and UDAs that differ between `old` and `new`, sorted
+pub fn changed_fields(old: &Task, new: &Task) -> Vec<String> {
+    let mut changed: Vec<String> = [
+        ("description", old.description != new.description),
+        ("status", old.status != new.status),
+        ("project", old.project != new.project),
+        ("priority", old.priority_code() != new.priority_code()),
+        ("due", old.due != new.due),
+        ("wait", old.wait != new.wait),
+        ("scheduled", old.scheduled != new.scheduled),
+        ("start", old.start != new.start),
+        ("end", old.end != new.end),
+        ("tags", old.tags != new.tags),
+        ("annotations", old.annotations != new.annotations),
+        ("depends", old.depends != new.depends),
+    ]
+    .into_iter()
+    .filter(|(_, differs)| *differs)
+    .map(|(field, _)| field.to_string())
+    .collect();
+
+    let udas = old.udas.keys().chain(new.udas.keys());
+    for name in udas {
+        let tracked = ![FIELD_MODIFIED_UDA, PUSHED_UDA, "priority"].contains(&name.as_str());
+        if tracked && old.udas.get(name) != new.udas.get(name) && !changed.contains(name) {
+            changed.push(name.clone());
+        }
+    }
+    changed.sort();
+    changed
+}
+
+/// Record `at` as the modification time of every field changed from `old`
+/// to `new`
+pub fn stamp(old: &Task, new: &mut Task, at: DateTime<Utc>) {
+    let changed = changed_fields(old, new);
+    if changed.is_empty() {
+        return;
+    }
+    let mut times = FieldTimes::from_task(old);
+    for field in changed {
+        times.fields.insert(field, at);
+    }
+    times.write_to(new);
+}
+
+/// Increase the push count of `new` if its due date is later than that of
+/// `old`; setting a first due date or clearing it is not a push
+pub fn count_push(old: &Task, new: &mut Task) {
+    let pushed = matches!((old.due, new.due), (Some(before), Some(after)) if after > before);
+    if pushed {
+        let count = old.push_count() + 1;
+        new.udas
+            .insert(PUSHED_UDA.to_string(), UdaValue::Number(f64::from(count)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_changed_fields() {
+        let old = Task::new("Write report".to_string());
+        let mut new = old.clone();
+        new.due = Some(Utc::now());
+        new.tags.insert("work".to_string());
+        new.udas
+            .insert("estimate".to_string(), UdaValue::Number(2.0));
+        assert_eq!(changed_fields(&old, &new), ["due", "estimate", "tags"]);
+        assert!(changed_fields(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_stamp_keeps_earlier_fields() {
+        let first = Utc::now() - Duration::days(2);
+        let second = Utc::now();
+        let old = Task::new("Write report".to_string());
+
+        let mut dated = old.clone();
+        dated.due = Some(second);
+        stamp(&old, &mut dated, first);
+
+        let mut moved = dated.clone();
+        moved.project = Some("Work".to_string());
+        stamp(&dated, &mut moved, second);
+
+        let times = FieldTimes::from_task(&moved);
+        assert_eq!(times.get("due"), Some(first));
+        assert_eq!(times.get("project"), Some(second));
+        assert_eq!(times.get("description"), None);
+    }
+
+    #[test]
+    fn test_count_push() {
+        let now = Utc::now();
+        let mut task = Task::new("Write report".to_string());
+        let mut moves = |due: Option<DateTime<Utc>>| {
+            let old = task.clone();
+            task.due = due;
+            count_push(&old, &mut task);
+            task.push_count()
+        };
+        assert_eq!(moves(Some(now)), 0);
+        assert_eq!(moves(Some(now + Duration::days(1))), 1);
+        assert_eq!(moves(Some(now)), 1);
+        assert_eq!(moves(Some(now + Duration::days(7))), 2);
+        assert_eq!(moves(None), 2);
+    }
+}
@@ -0,0 +1,119 @@
+//! Bulk rescheduling of due dates
+//!
+//! [`TaskManager::reschedule_where`](crate::task::TaskManager::reschedule_where)
+//! moves the due date of every task matching a query by one [`Shift`]:
+//!
+//! - an absolute date (`2025-04-01`, `friday`, `next monday`) gives all of
+//!   them the same due date, while
+//! - a relative offset (`+1w`, `-2d`) moves each task from its own due
+//!   date, or from now if it has none.
+//!
+//! With `reschedule.businessdays=yes`, dates landing on a Saturday or
+//! Sunday move on to the following Monday.
+
+use chrono::{DateTime, Datelike, Duration, Local, Utc, Weekday};
+
+use crate::clock;
+use crate::config::Configuration;
+use crate::date::{DateParser, DateParsing};
+use crate::error::DateError;
+
+/// How to move due dates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shift {
+    /// Set every due date to this date
+    To(DateTime<Utc>),
+    /// Offset each due date by a relative expression such as `+1w`
+    By(String),
+}
+
+impl Shift {
+    /// Parse a shift: `+`/`-` offsets are relative, anything else is a
+    /// date understood by [`DateParser`], optionally after `next`
+    pub fn parse(input: &str) -> Result<Self, DateError> {
+        let input = input.trim();
+        if input.starts_with(['+', '-']) {
+            let parser = DateParser::new();
+            // Reject bad offsets now rather than for each task
+            parser.calculate_relative_date(clock::now(), input)?;
+            return Ok(Shift::By(input.to_string()));
+        }
+        let date = input
+            .strip_prefix("next ")
+            .map_or(input, str::trim_start);
+        DateParser::new().parse_date(date).map(Shift::To)
+    }
+
+    /// New due date for a task currently due at `due`
+    pub fn apply(&self, due: Option<DateTime<Utc>>) -> Result<DateTime<Utc>, DateError> {
+        match self {
+            Shift::To(date) => Ok(*date),
+            Shift::By(offset) => {
+                DateParser::new().calculate_relative_date(due.unwrap_or_else(clock::now), offset)
+            }
+        }
+    }
+}
+
+/// Whether rescheduling keeps due dates on business days
+/// (`reschedule.businessdays`, off by default)
+pub fn business_days(config: &Configuration) -> bool {
+    config.get_bool("reschedule.businessdays").unwrap_or(false)
+}
+
+/// `date`, or the following Monday if it falls on a weekend in local time
+pub fn next_business_day(date: DateTime<Utc>) -> DateTime<Utc> {
+    let local = date.with_timezone(&Local);
+    let days = match local.weekday() {
+        Weekday::Sat => 2,
+        Weekday::Sun => 1,
+        _ => return date,
+    };
+    // Keep the local time of day across DST changes
+    (local.naive_local() + Duration::days(days))
+        .and_local_timezone(Local)
+        .earliest()
+        .map_or(date + Duration::days(days), |d| d.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn local(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        let noon = NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        Local
+            .from_local_datetime(&noon)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_and_apply() {
+        let due = local(2025, 3, 5);
+        let week = Shift::parse("+1w").unwrap();
+        assert_eq!(week, Shift::By("+1w".to_string()));
+        assert_eq!(week.apply(Some(due)).unwrap(), due + Duration::weeks(1));
+
+        let fixed = Shift::parse("2025-04-01").unwrap();
+        assert_eq!(
+            fixed.apply(Some(due)).unwrap(),
+            Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap()
+        );
+        assert!(matches!(Shift::parse("next monday"), Ok(Shift::To(_))));
+        assert!(Shift::parse("+1fortnight").is_err());
+        assert!(Shift::parse("someday").is_err());
+    }
+
+    #[test]
+    fn test_next_business_day() {
+        // 2025-03-15 is a Saturday
+        assert_eq!(next_business_day(local(2025, 3, 15)), local(2025, 3, 17));
+        assert_eq!(next_business_day(local(2025, 3, 16)), local(2025, 3, 17));
+        assert_eq!(next_business_day(local(2025, 3, 14)), local(2025, 3, 14));
+    }
+}
@@ -0,0 +1,220 @@
+//! Retention of deleted and completed tasks
+//!
+//! Deleted and completed tasks otherwise accumulate forever. A
+//! [`RetentionPolicy`] says how long they are kept, and the task manager's
+//! maintenance job purges anything older:
+//!
+//! ```text
+//! purge.on-sync=after 180d     # purge deleted tasks older than 180 days after each sync
+//! purge.deleted=after 90d      # age threshold for deleted tasks
+//! purge.completed=after 365d   # age threshold for completed tasks (default: keep)
+//! ```
+//!
+//! `purge.on-sync` also accepts a boolean to run the job after sync using the
+//! `purge.deleted`/`purge.completed` thresholds. Its duration form is a
+//! shorthand for `purge.deleted`; an explicit `purge.deleted` wins. A task's
+//! age is measured from its `end` time, falling back to `modified` and then
+//! `entry`.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::date::relative::parse_duration;
+use crate::error::ConfigError;
+use crate::task::{Task, TaskStatus};
+
+/// How long deleted and completed tasks are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// Purge deleted tasks older than this; None keeps them
+    pub deleted_after: Option<Duration>,
+    /// Purge completed tasks older than this; None keeps them
+    pub completed_after: Option<Duration>,
+    /// Run the purge job after every successful sync
+    pub on_sync: bool,
+}
+
+/// A task removed (or, in a dry run, selected) by the purge job
+#[derive(Debug, Clone, PartialEq)]
+pub struct PurgedTask {
+    pub id: Uuid,
+    pub description: String,
+    pub status: TaskStatus,
+    /// Time the task's age was measured from
+    pub since: DateTime<Utc>,
+}
+
+/// What a purge run removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PurgeReport {
+    pub purged: Vec<PurgedTask>,
+    /// True when nothing was actually removed
+    pub dry_run: bool,
+    /// Why the run stopped early; `purged` lists what went before it
+    pub error: Option<String>,
+}
+
+impl PurgeReport {
+    /// Number of purged tasks
+    pub fn len(&self) -> usize {
+        self.purged.len()
+    }
+
+    /// Whether nothing was purged
+    pub fn is_empty(&self) -> bool {
+        self.purged.is_empty()
+    }
+}
+
+impl RetentionPolicy {
+    /// Read the policy from `purge.on-sync`, `purge.deleted` and `purge.completed`
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let mut policy = Self {
+            deleted_after: parse_threshold(config, "purge.deleted")?,
+            completed_after: parse_threshold(config, "purge.completed")?,
+            on_sync: false,
+        };
+
+        if let Some(raw) = config.get("purge.on-sync") {
+            match config.get_bool("purge.on-sync") {
+                Some(enabled) => policy.on_sync = enabled,
+                None => {
+                    let after = parse_after("purge.on-sync", raw)?;
+                    if config.get("purge.deleted").is_none() {
+                        policy.deleted_after = after;
+                    }
+                    policy.on_sync = policy.is_enabled();
+                }
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Whether the policy would ever purge anything
+    pub fn is_enabled(&self) -> bool {
+        self.deleted_after.is_some() || self.completed_after.is_some()
+    }
+
+    /// Whether `task` is old enough to purge at `now`
+    pub fn is_expired(&self, task: &Task, now: DateTime<Utc>) -> bool {
+        let threshold = match task.status {
+            TaskStatus::Deleted => self.deleted_after,
+            TaskStatus::Completed => self.completed_after,
+            _ => None,
+        };
+        // A threshold reaching past the representable dates never expires
+        threshold.is_some_and(|age| {
+            age_reference(task)
+                .checked_add_signed(age)
+                .is_some_and(|at| at <= now)
+        })
+    }
+
+    /// Tasks from `tasks` that should be purged at `now`
+    pub fn expired<'a>(&self, tasks: &'a [Task], now: DateTime<Utc>) -> Vec<&'a Task> {
+        tasks.iter().filter(|t| self.is_expired(t, now)).collect()
+    }
+}
+
+impl PurgedTask {
+    pub(crate) fn from_task(task: &Task) -> Self {
+        Self {
+            id: task.id,
+            description: task.description.clone(),
+            status: task.status,
+            since: age_reference(task),
+        }
+    }
+}
+
+/// Time a task's age is measured from
+fn age_reference(task: &Task) -> DateTime<Utc> {
+    task.end.or(task.modified).unwrap_or(task.entry)
+}
+
+fn parse_threshold(config: &Configuration, key: &str) -> Result<Option<Duration>, ConfigError> {
+    match config.get(key) {
+        Some(raw) => parse_after(key, raw),
+        None => Ok(None),
+    }
+}
+
+/// Parse `after <duration>` or `never`
+fn parse_after(key: &str, raw: &str) -> Result<Option<Duration>, ConfigError> {
+    let value = raw.trim();
+    if value.eq_ignore_ascii_case("never") {
+        return Ok(None);
+    }
+    let duration = value.strip_prefix("after").unwrap_or(value).trim();
+    parse_duration(duration)
+        .map(Some)
+        .map_err(|_| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: raw.to_string(),
+            expected: "'after <duration>' (e.g. 'after 180d') or 'never'".to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_from_config() {
+        let mut config = Configuration::default();
+        config.set("purge.on-sync", "after 180d");
+        config.set("purge.completed", "after 52w");
+
+        let policy = RetentionPolicy::from_config(&config).unwrap();
+        assert!(policy.on_sync);
+        assert_eq!(policy.deleted_after, Some(Duration::days(180)));
+        assert_eq!(policy.completed_after, Some(Duration::weeks(52)));
+
+        config.set("purge.on-sync", "no");
+        config.set("purge.deleted", "never");
+        let policy = RetentionPolicy::from_config(&config).unwrap();
+        assert!(!policy.on_sync);
+        assert_eq!(policy.deleted_after, None);
+
+        config.set("purge.deleted", "eventually");
+        assert!(RetentionPolicy::from_config(&config).is_err());
+
+        // An explicit purge.deleted beats the on-sync shorthand
+        config.set("purge.on-sync", "after 180d");
+        config.set("purge.deleted", "after 30d");
+        let policy = RetentionPolicy::from_config(&config).unwrap();
+        assert!(policy.on_sync);
+        assert_eq!(policy.deleted_after, Some(Duration::days(30)));
+    }
+
+    #[test]
+    fn test_expired_uses_end_time() {
+        let policy = RetentionPolicy {
+            deleted_after: Some(Duration::days(30)),
+            ..Default::default()
+        };
+        let now = Utc::now();
+
+        let mut old = Task::new("old".to_string());
+        old.status = TaskStatus::Deleted;
+        old.end = Some(now - Duration::days(31));
+        let mut recent = old.clone();
+        recent.end = Some(now - Duration::days(1));
+        let mut completed = old.clone();
+        completed.status = TaskStatus::Completed;
+
+        let tasks = [old.clone(), recent, completed];
+        let expired = policy.expired(&tasks, now);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, old.id);
+
+        // Too far out to add to a date: never expires
+        let forever = RetentionPolicy {
+            deleted_after: Some(Duration::days(99_999_999_999)),
+            ..Default::default()
+        };
+        assert!(!forever.is_expired(&old, now));
+    }
+}
@@ -0,0 +1,163 @@
+//! Snoozing tasks
+//!
+//! [`TaskManager::snooze`](crate::task::TaskManager::snooze) hides a task
+//! until a given time by setting its `wait` date, the one-call "remind me
+//! later" of inbox-style frontends. Besides explicit times it knows the
+//! usual presets, computed in local time from config:
+//!
+//! ```text
+//! snooze.later=3h           # "later today": this long from now, at most
+//!                           # a day (default 3h)
+//! snooze.morning=09:00      # time of day for "tomorrow morning" and
+//!                           # "next week" (Monday) (default 09:00)
+//! snooze.scheduled=no       # also set `scheduled` to the same time
+//! ```
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Utc};
+
+use crate::config::Configuration;
+use crate::date::relative::parse_duration;
+use crate::error::{ConfigError, TaskError};
+
+/// When a snoozed task comes back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Snooze {
+    /// A fixed time
+    Until(DateTime<Utc>),
+    /// `snooze.later` from now
+    LaterToday,
+    /// Tomorrow at `snooze.morning`
+    TomorrowMorning,
+    /// Next Monday at `snooze.morning`
+    NextWeek,
+}
+
+impl From<DateTime<Utc>> for Snooze {
+    fn from(until: DateTime<Utc>) -> Self {
+        Snooze::Until(until)
+    }
+}
+
+/// Times of day and offsets the presets resolve with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnoozeTimes {
+    pub later: Duration,
+    pub morning: NaiveTime,
+    /// Set `scheduled` along with `wait`
+    pub scheduled: bool,
+}
+
+impl Default for SnoozeTimes {
+    fn default() -> Self {
+        Self {
+            later: Duration::hours(3),
+            morning: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or(NaiveTime::MIN),
+            scheduled: false,
+        }
+    }
+}
+
+impl SnoozeTimes {
+    /// Read `snooze.later`, `snooze.morning` and `snooze.scheduled`
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let mut times = Self::default();
+        if let Some(raw) = config.get("snooze.later") {
+            times.later = parse_duration(raw)
+                .ok()
+                .filter(|d| *d > Duration::zero() && *d <= Duration::days(1))
+                .ok_or_else(|| ConfigError::InvalidValue {
+                    key: "snooze.later".to_string(),
+                    value: raw.to_string(),
+                    expected: "a positive duration of at most a day, such as '3h'".to_string(),
+                })?;
+        }
+        if let Some(raw) = config.get("snooze.morning") {
+            times.morning = NaiveTime::parse_from_str(raw.trim(), "%H:%M").map_err(|_| {
+                ConfigError::InvalidValue {
+                    key: "snooze.morning".to_string(),
+                    value: raw.to_string(),
+                    expected: "a time of day such as '09:00'".to_string(),
+                }
+            })?;
+        }
+        times.scheduled = config.get_bool("snooze.scheduled").unwrap_or(false);
+        Ok(times)
+    }
+
+    /// The time `snooze` ends, counted from `now`; an error when that is
+    /// past the latest representable date
+    pub fn resolve(&self, snooze: Snooze, now: DateTime<Utc>) -> Result<DateTime<Utc>, TaskError> {
+        let out_of_range = || TaskError::InvalidData {
+            message: format!("snoozing {snooze:?} from {now} goes past the latest representable date"),
+        };
+        let today = now.with_timezone(&Local).date_naive();
+        let days_ahead = match snooze {
+            Snooze::Until(until) => return Ok(until),
+            Snooze::LaterToday => return now.checked_add_signed(self.later).ok_or_else(out_of_range),
+            Snooze::TomorrowMorning => 1,
+            Snooze::NextWeek => 7 - today.weekday().num_days_from_monday() as i64,
+        };
+        let ahead = Duration::days(days_ahead);
+        let day = today.checked_add_signed(ahead).ok_or_else(out_of_range)?;
+        match day.and_time(self.morning).and_local_timezone(Local).earliest() {
+            Some(at) => Ok(at.with_timezone(&Utc)),
+            None => now.checked_add_signed(ahead).ok_or_else(out_of_range),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone, Weekday};
+
+    #[test]
+    fn test_presets() {
+        // Wednesday afternoon
+        let wednesday = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let now = Local
+            .from_local_datetime(&wednesday.and_hms_opt(14, 30, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc);
+        let times = SnoozeTimes::default();
+
+        assert_eq!(
+            times.resolve(Snooze::LaterToday, now).unwrap(),
+            now + Duration::hours(3)
+        );
+        let tomorrow = times
+            .resolve(Snooze::TomorrowMorning, now)
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(tomorrow.date_naive(), wednesday + Duration::days(1));
+        assert_eq!(tomorrow.time(), times.morning);
+        let monday = times.resolve(Snooze::NextWeek, now).unwrap().with_timezone(&Local);
+        assert_eq!(monday.weekday(), Weekday::Mon);
+        assert_eq!(monday.date_naive(), wednesday + Duration::days(5));
+        assert_eq!(times.resolve(now.into(), now).unwrap(), now);
+
+        let end_of_time = DateTime::<Utc>::MAX_UTC;
+        assert!(times.resolve(Snooze::LaterToday, end_of_time).is_err());
+        assert!(times.resolve(Snooze::NextWeek, end_of_time).is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = Configuration::default();
+        config.set("snooze.later", "90min");
+        config.set("snooze.morning", "07:30");
+        config.set("snooze.scheduled", "yes");
+        let times = SnoozeTimes::from_config(&config).unwrap();
+        assert_eq!(times.later, Duration::minutes(90));
+        assert_eq!(times.morning, NaiveTime::from_hms_opt(7, 30, 0).unwrap());
+        assert!(times.scheduled);
+
+        config.set("snooze.morning", "breakfast");
+        assert!(SnoozeTimes::from_config(&config).is_err());
+        config.set("snooze.morning", "07:30");
+        for bad in ["0min", "2d", "99999999999d"] {
+            config.set("snooze.later", bad);
+            assert!(SnoozeTimes::from_config(&config).is_err(), "{bad}");
+        }
+    }
+}
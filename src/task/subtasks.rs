@@ -0,0 +1,100 @@
+//! Subtasks
+//!
+//! Taskwarrior has no subtasks, so frontends fake them in different ways.
+//! [`TaskManager::add_subtask`](crate::task::TaskManager::add_subtask)
+//! settles on one representation built from standard attributes:
+//!
+//! - the child carries the parent's UUID in the [`PARENT_UDA`] UDA
+//!   (`parent` itself is taken by recurrence), and
+//! - the parent depends on the child, so it shows as blocked until every
+//!   subtask is done.
+//!
+//! When the last open subtask is completed, the task manager rolls the
+//! completion up to the parent as `subtask.rollup` says: `warn` (the
+//! default) prints a warning, `complete` completes the parent and `off`
+//! does nothing.
+
+use uuid::Uuid;
+
+use crate::config::Configuration;
+use crate::error::ConfigError;
+use crate::task::model::UdaValue;
+use crate::task::{Task, TaskStatus};
+
+/// UDA holding the UUID of a subtask's parent
+pub const PARENT_UDA: &str = "parenttask";
+
+/// What happens to a parent once all its subtasks are done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubtaskRollup {
+    Off,
+    /// Print a warning that the parent can be completed
+    #[default]
+    Warn,
+    /// Complete the parent
+    Complete,
+}
+
+impl SubtaskRollup {
+    /// Read `subtask.rollup`
+    pub fn from_config(config: &Configuration) -> Result<Self, ConfigError> {
+        let Some(raw) = config.get("subtask.rollup") else {
+            return Ok(Self::default());
+        };
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" | "no" | "0" => Ok(SubtaskRollup::Off),
+            "warn" => Ok(SubtaskRollup::Warn),
+            "complete" | "auto" => Ok(SubtaskRollup::Complete),
+            _ => Err(ConfigError::InvalidValue {
+                key: "subtask.rollup".to_string(),
+                value: raw.to_string(),
+                expected: "'off', 'warn' or 'complete'".to_string(),
+            }),
+        }
+    }
+}
+
+/// The parent recorded on a subtask, if any
+pub fn parent_of(task: &Task) -> Option<Uuid> {
+    match task.udas.get(PARENT_UDA)? {
+        UdaValue::String(id) => Uuid::parse_str(id).ok(),
+        _ => None,
+    }
+}
+
+/// Whether every subtask in `children` is completed or deleted
+pub fn all_done(children: &[Task]) -> bool {
+    children
+        .iter()
+        .all(|t| matches!(t.status, TaskStatus::Completed | TaskStatus::Deleted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_of_and_rollup_config() {
+        let parent = Task::new("Plan trip".to_string());
+        let mut child = Task::new("Book flights".to_string());
+        assert_eq!(parent_of(&child), None);
+        child.udas.insert(
+            PARENT_UDA.to_string(),
+            UdaValue::String(parent.id.to_string()),
+        );
+        assert_eq!(parent_of(&child), Some(parent.id));
+
+        let mut config = Configuration::default();
+        assert_eq!(
+            SubtaskRollup::from_config(&config).unwrap(),
+            SubtaskRollup::Warn
+        );
+        config.set("subtask.rollup", "complete");
+        assert_eq!(
+            SubtaskRollup::from_config(&config).unwrap(),
+            SubtaskRollup::Complete
+        );
+        config.set("subtask.rollup", "maybe");
+        assert!(SubtaskRollup::from_config(&config).is_err());
+    }
+}
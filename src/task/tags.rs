@@ -0,0 +1,197 @@
+//! Tag registry
+//!
+//! Tags can be described in config with `tag.<name>.*` settings:
+//!
+//! ```text
+//! tag.work.description=Day job
+//! tag.work.color=blue
+//! tag.work.implies=office,+billable
+//! ```
+//!
+//! The task manager applies `implies` relationships whenever tags are added
+//! or modified, so adding `+work` also adds `+office` and `+billable`.
+//! Frontends can use [`TagRegistry::usage`] to populate tag pickers.
+
+use crate::config::Configuration;
+use crate::task::Task;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Metadata for a single tag
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TagInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    /// Tags that are added alongside this one
+    pub implies: Vec<String>,
+}
+
+/// A tag with the number of tasks using it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagUsage {
+    pub name: String,
+    pub count: usize,
+    /// Registry entry, if the tag is configured
+    pub info: Option<TagInfo>,
+}
+
+/// Registry of configured tags
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TagRegistry {
+    tags: BTreeMap<String, TagInfo>,
+}
+
+impl TagRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all `tag.<name>.description|color|implies` settings
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut registry = Self::new();
+        for (key, value) in &config.settings {
+            let Some(rest) = key.strip_prefix("tag.") else {
+                continue;
+            };
+            let Some((name, attribute)) = rest.rsplit_once('.') else {
+                continue;
+            };
+            if name.is_empty() {
+                continue;
+            }
+            let info = registry.entry(name);
+            match attribute {
+                "description" => info.description = Some(value.clone()),
+                "color" => info.color = Some(value.clone()),
+                "implies" => info.implies = parse_tag_list(value),
+                _ => {}
+            }
+        }
+        registry
+    }
+
+    /// Register or replace a tag
+    pub fn insert(&mut self, info: TagInfo) {
+        self.tags.insert(info.name.clone(), info);
+    }
+
+    fn entry(&mut self, name: &str) -> &mut TagInfo {
+        self.tags.entry(name.to_string()).or_insert_with(|| TagInfo {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Look up a configured tag
+    pub fn get(&self, name: &str) -> Option<&TagInfo> {
+        self.tags.get(name)
+    }
+
+    /// All configured tags, sorted by name
+    pub fn tags(&self) -> impl Iterator<Item = &TagInfo> {
+        self.tags.values()
+    }
+
+    /// Add every tag implied by the given set, following chains of
+    /// implications. Returns true if any tag was added.
+    pub fn apply_implications(&self, tags: &mut HashSet<String>) -> bool {
+        let mut pending: Vec<String> = tags.iter().cloned().collect();
+        let mut added = false;
+        while let Some(tag) = pending.pop() {
+            let Some(info) = self.tags.get(&tag) else {
+                continue;
+            };
+            for implied in &info.implies {
+                if tags.insert(implied.clone()) {
+                    added = true;
+                    pending.push(implied.clone());
+                }
+            }
+        }
+        added
+    }
+
+    /// Tag usage counts across `tasks`, including configured tags that no
+    /// task uses yet. Sorted by name.
+    pub fn usage(&self, tasks: &[Task]) -> Vec<TagUsage> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for task in tasks {
+            for tag in &task.tags {
+                *counts.entry(tag.as_str()).or_insert(0) += 1;
+            }
+        }
+        for name in self.tags.keys() {
+            counts.entry(name.as_str()).or_insert(0);
+        }
+
+        let mut usage: Vec<TagUsage> = counts
+            .into_iter()
+            .map(|(name, count)| TagUsage {
+                name: name.to_string(),
+                count,
+                info: self.tags.get(name).cloned(),
+            })
+            .collect();
+        usage.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+}
+
+/// Parse a comma/space separated tag list, accepting an optional leading `+`
+fn parse_tag_list(raw: &str) -> Vec<String> {
+    raw.split(|c: char| c == ',' || c.is_whitespace())
+        .map(|t| t.trim().trim_start_matches('+'))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> TagRegistry {
+        let mut config = Configuration::default();
+        config.set("tag.work.description", "Day job");
+        config.set("tag.work.color", "blue");
+        config.set("tag.work.implies", "office, +billable");
+        config.set("tag.office.implies", "work");
+        TagRegistry::from_config(&config)
+    }
+
+    #[test]
+    fn test_from_config() {
+        let registry = registry();
+        let work = registry.get("work").unwrap();
+        assert_eq!(work.description.as_deref(), Some("Day job"));
+        assert_eq!(work.color.as_deref(), Some("blue"));
+        assert_eq!(work.implies, vec!["office", "billable"]);
+        assert_eq!(registry.tags().count(), 2);
+    }
+
+    #[test]
+    fn test_implications_are_transitive_and_cycle_safe() {
+        let registry = registry();
+        let mut tags: HashSet<String> = ["office".to_string()].into();
+        assert!(registry.apply_implications(&mut tags));
+        assert_eq!(tags.len(), 3);
+        assert!(tags.contains("billable"));
+        assert!(!registry.apply_implications(&mut tags));
+    }
+
+    #[test]
+    fn test_usage_counts() {
+        let registry = registry();
+        let mut a = Task::new("a".to_string());
+        a.add_tag("work".to_string());
+        a.add_tag("misc".to_string());
+        let mut b = Task::new("b".to_string());
+        b.add_tag("work".to_string());
+
+        let usage = registry.usage(&[a, b]);
+        let names: Vec<_> = usage.iter().map(|u| (u.name.as_str(), u.count)).collect();
+        assert_eq!(names, vec![("misc", 1), ("office", 0), ("work", 2)]);
+        assert!(usage[2].info.is_some());
+    }
+}
@@ -0,0 +1,231 @@
+//! UDA types and their TaskChampion encoding
+//!
+//! TaskChampion stores every task property as a string. Taskwarrior keeps
+//! the type of each UDA in config (`uda.<name>.type`) and encodes values by
+//! type: numbers in decimal, dates as Unix timestamps, durations and strings
+//! as written. [`encode_uda`] and [`decode_uda`] follow the same conventions
+//! so a numeric `estimate` written through this library reads back as a
+//! number. Values of undeclared UDAs are guessed on read.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::Configuration;
+use crate::task::model::UdaValue;
+
+/// Declared type of a UDA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UdaType {
+    String,
+    Numeric,
+    Date,
+    Duration,
+}
+
+impl UdaType {
+    /// Parse a `uda.<name>.type` value
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "string" => Some(UdaType::String),
+            "numeric" => Some(UdaType::Numeric),
+            "date" => Some(UdaType::Date),
+            "duration" => Some(UdaType::Duration),
+            _ => None,
+        }
+    }
+}
+
+/// Declared UDA types by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UdaTypes(HashMap<String, UdaType>);
+
+impl UdaTypes {
+    /// No declared types; every value is guessed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Types declared by `uda.<name>.type` settings; unknown types are ignored
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut types = Self::new();
+        for (key, value) in &config.settings {
+            let Some(name) = key.strip_prefix("uda.").and_then(|k| k.strip_suffix(".type")) else {
+                continue;
+            };
+            if let Some(uda_type) = UdaType::parse(value) {
+                types.insert(name, uda_type);
+            }
+        }
+        types
+    }
+
+    /// Declare the type of a UDA
+    pub fn insert<S: Into<String>>(&mut self, name: S, uda_type: UdaType) {
+        self.0.insert(name.into(), uda_type);
+    }
+
+    /// Declared type of a UDA
+    pub fn get(&self, name: &str) -> Option<UdaType> {
+        self.0.get(name).copied()
+    }
+}
+
+/// A UDA as declared by its `uda.<name>.*` settings
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UdaDefinition {
+    pub name: String,
+    /// `uda.<name>.type`; None when missing or not a known type
+    pub uda_type: Option<UdaType>,
+    /// `uda.<name>.label`
+    pub label: Option<String>,
+    /// Allowed values from `uda.<name>.values`, where an empty entry
+    /// allows leaving the UDA unset; an empty list allows anything
+    pub values: Vec<String>,
+    /// `uda.<name>.default`
+    pub default: Option<String>,
+}
+
+/// Every UDA declared in config, by name
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UdaSchema {
+    udas: BTreeMap<String, UdaDefinition>,
+}
+
+impl UdaSchema {
+    /// Load all `uda.<name>.type|label|values|default` settings
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut schema = Self::default();
+        for (key, value) in &config.settings {
+            let Some((name, attribute)) = key.strip_prefix("uda.").and_then(|k| k.rsplit_once('.')) else {
+                continue;
+            };
+            let definition = schema.udas.entry(name.to_string()).or_insert_with(|| UdaDefinition {
+                name: name.to_string(),
+                ..Default::default()
+            });
+            match attribute {
+                "type" => definition.uda_type = UdaType::parse(value),
+                "label" => definition.label = Some(value.clone()),
+                "values" => definition.values = value.split(',').map(|v| v.trim().to_string()).collect(),
+                "default" => definition.default = Some(value.clone()),
+                _ => {}
+            }
+        }
+        schema
+    }
+
+    /// Definition of a UDA
+    pub fn get(&self, name: &str) -> Option<&UdaDefinition> {
+        self.udas.get(name)
+    }
+
+    /// Definitions in name order
+    pub fn iter(&self) -> impl Iterator<Item = &UdaDefinition> {
+        self.udas.values()
+    }
+
+    /// Declared types, for decoding values
+    pub fn types(&self) -> UdaTypes {
+        let mut types = UdaTypes::new();
+        for definition in self.iter() {
+            if let Some(uda_type) = definition.uda_type {
+                types.insert(definition.name.clone(), uda_type);
+            }
+        }
+        types
+    }
+}
+
+/// Task properties with their own fields, or TaskChampion's per-item keys
+/// (`tag_<name>`, `dep_<uuid>`, `annotation_<timestamp>`); anything else on
+/// a task is a UDA
+pub fn is_task_attribute(key: &str) -> bool {
+    const ATTRIBUTES: &[&str] = &[
+        "description", "status", "entry", "project", "tags", "modified", "due", "scheduled",
+        "wait", "end", "start", "priority", "annotations", "depends", "recur", "parent", "mask",
+        "active", "owner", "id", "uuid", "urgency", "imask", "until",
+    ];
+    ATTRIBUTES.contains(&key)
+        || key.starts_with("tag_")
+        || key.starts_with("dep_")
+        || key.starts_with("annotation_")
+}
+
+/// Encode a UDA value as a TaskChampion property string
+pub fn encode_uda(value: &UdaValue) -> String {
+    match value {
+        UdaValue::String(s) => s.clone(),
+        // Whole numbers are written without a fraction, as Taskwarrior does
+        UdaValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+        UdaValue::Number(n) => n.to_string(),
+        UdaValue::Date(d) => d.timestamp().to_string(),
+    }
+}
+
+/// Decode a TaskChampion property string, using the declared type when known.
+/// A value that does not parse as its declared type is kept as a string.
+pub fn decode_uda(raw: &str, uda_type: Option<UdaType>) -> UdaValue {
+    let string = || UdaValue::String(raw.to_string());
+    match uda_type {
+        Some(UdaType::String) | Some(UdaType::Duration) => string(),
+        Some(UdaType::Numeric) => raw.trim().parse().map(UdaValue::Number).unwrap_or_else(|_| string()),
+        Some(UdaType::Date) => parse_date(raw).map(UdaValue::Date).unwrap_or_else(string),
+        None => {
+            if let Ok(n) = raw.trim().parse::<f64>() {
+                UdaValue::Number(n)
+            } else if let Ok(d) = DateTime::parse_from_rfc3339(raw) {
+                UdaValue::Date(d.with_timezone(&Utc))
+            } else {
+                string()
+            }
+        }
+    }
+}
+
+// Unix timestamp, or RFC 3339 as written by older versions of this library
+fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    match raw.trim().parse::<i64>() {
+        Ok(seconds) => Utc.timestamp_opt(seconds, 0).single(),
+        Err(_) => DateTime::parse_from_rfc3339(raw).ok().map(|d| d.with_timezone(&Utc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_by_type() {
+        let mut config = Configuration::default();
+        config.set("uda.estimate.type", "numeric");
+        config.set("uda.reviewed.type", "date");
+        config.set("uda.code.type", "string");
+        let types = UdaTypes::from_config(&config);
+        assert_eq!(types.get("estimate"), Some(UdaType::Numeric));
+
+        let date = Utc.with_ymd_and_hms(2025, 10, 1, 12, 0, 0).unwrap();
+        let values = [
+            ("estimate", UdaValue::Number(3.0), "3"),
+            ("estimate", UdaValue::Number(2.5), "2.5"),
+            ("reviewed", UdaValue::Date(date), "1759320000"),
+            ("code", UdaValue::String("007".to_string()), "007"),
+        ];
+        for (name, value, encoded) in values {
+            assert_eq!(encode_uda(&value), encoded);
+            assert_eq!(decode_uda(encoded, types.get(name)), value);
+        }
+
+        // Undeclared values are guessed; declared strings are never guessed
+        assert_eq!(decode_uda("007", None), UdaValue::Number(7.0));
+        assert_eq!(decode_uda("soon", types.get("estimate")), UdaValue::String("soon".to_string()));
+    }
+
+    #[test]
+    fn test_task_attributes() {
+        assert!(is_task_attribute("description"));
+        assert!(is_task_attribute("tag_next"));
+        assert!(!is_task_attribute("estimate"));
+    }
+}
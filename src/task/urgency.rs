@@ -0,0 +1,380 @@
+//! Urgency scoring
+//!
+//! The urgency formula on its own, with the time passed in, so it can be
+//! evaluated without a report manager or a clock. [`BuiltinReports`]
+//! (crate::reports::BuiltinReports) scores tasks with it for sorting and
+//! the `urgency` column.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::{Configuration, PriorityScheme};
+use crate::task::Task;
+
+/// Taskwarrior's due-date factor: 0.2 for tasks due more than 14 days
+/// out, rising linearly over 21 days to 1.0 once a week overdue
+pub fn due_factor(due: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let days_overdue = now.signed_duration_since(due).num_seconds() as f64 / 86_400.0;
+    if days_overdue >= 7.0 {
+        1.0
+    } else if days_overdue >= -14.0 {
+        (days_overdue + 14.0) * 0.8 / 21.0 + 0.2
+    } else {
+        0.2
+    }
+}
+
+/// Shape of the priority term's change as a task ages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgingCurve {
+    /// Priority counts the same at any age, as in Taskwarrior
+    #[default]
+    Flat,
+    /// Moves in a straight line to the final factor
+    Linear,
+    /// Moves geometrically to the final factor, changing fastest early
+    /// when decaying and late when growing
+    Exponential,
+}
+
+impl AgingCurve {
+    /// Parse a `urgency.priority.aging` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "none" | "flat" => Some(AgingCurve::Flat),
+            "linear" => Some(AgingCurve::Linear),
+            "exponential" => Some(AgingCurve::Exponential),
+            _ => None,
+        }
+    }
+}
+
+/// Aging of the priority term: its coefficient is multiplied by a factor
+/// running from 1 for a new task to `factor` at `urgency.age.max` days.
+/// A factor below 1 lets stale priorities decay; above 1 escalates them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriorityAging {
+    pub curve: AgingCurve,
+    /// Multiplier reached at `urgency.age.max`
+    pub factor: f64,
+}
+
+impl Default for PriorityAging {
+    fn default() -> Self {
+        Self {
+            curve: AgingCurve::Flat,
+            factor: 1.0,
+        }
+    }
+}
+
+impl PriorityAging {
+    /// Multiplier for a task that has reached `progress` (0 to 1) of
+    /// `urgency.age.max`
+    pub fn multiplier(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        let factor = self.factor.max(0.0);
+        match self.curve {
+            AgingCurve::Flat => 1.0,
+            AgingCurve::Linear => 1.0 + (factor - 1.0) * progress,
+            AgingCurve::Exponential => factor.powf(progress),
+        }
+    }
+}
+
+/// One term of a task's urgency, as `task info` lists them
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencyTerm {
+    /// `priority`, `project`, `tags`, `uda.<name>`, `due` or `age`
+    pub name: String,
+    /// How much the term applies, e.g. the due factor
+    pub factor: f64,
+    pub coefficient: f64,
+}
+
+impl UrgencyTerm {
+    /// Contribution to the urgency
+    pub fn value(&self) -> f64 {
+        self.coefficient * self.factor
+    }
+}
+
+/// Urgency coefficients by term, plus the priority scheme
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencyCoefficients {
+    coefficients: HashMap<String, f64>,
+    priority_scheme: PriorityScheme,
+    /// Age in days at which the age term reaches its full coefficient
+    age_max: f64,
+    priority_aging: PriorityAging,
+}
+
+impl Default for UrgencyCoefficients {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UrgencyCoefficients {
+    /// Taskwarrior's default coefficients
+    pub fn new() -> Self {
+        let mut coefficients = HashMap::new();
+        coefficients.insert("project".to_string(), 1.0);
+        coefficients.insert("tags".to_string(), 1.0);
+        coefficients.insert("due".to_string(), 12.0);
+        coefficients.insert("blocking".to_string(), 8.0);
+        coefficients.insert("blocked".to_string(), -5.0);
+        coefficients.insert("age".to_string(), 2.0);
+        // `urgency.uda.<name>.coefficient`: added when the UDA is set
+        coefficients.insert("uda.estimate".to_string(), 0.0);
+        coefficients.insert("uda.effort".to_string(), 0.0);
+
+        Self {
+            coefficients,
+            priority_scheme: PriorityScheme::default(),
+            age_max: 365.0,
+            priority_aging: PriorityAging::default(),
+        }
+    }
+
+    /// Coefficients from the priority scheme, `urgency.<name>.coefficient`,
+    /// `urgency.age.max` and `urgency.priority.aging[.factor]` settings of
+    /// `config`. Values that do not parse keep their defaults.
+    pub fn from_config(config: &Configuration) -> Self {
+        let mut urgency = Self::new().with_priority_scheme(config.priority_scheme());
+        for (name, coefficient) in urgency.coefficients.iter_mut() {
+            if let Some(value) = config
+                .get(&format!("urgency.{name}.coefficient"))
+                .and_then(|v| v.trim().parse().ok())
+            {
+                *coefficient = value;
+            }
+        }
+        if let Some(age_max) = config
+            .get("urgency.age.max")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|max| *max > 0.0)
+        {
+            urgency.age_max = age_max;
+        }
+        if let Some(curve) = config
+            .get("urgency.priority.aging")
+            .and_then(|v| AgingCurve::parse(v))
+        {
+            urgency.priority_aging.curve = curve;
+        }
+        if let Some(factor) = config
+            .get("urgency.priority.aging.factor")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|factor| *factor >= 0.0)
+        {
+            urgency.priority_aging.factor = factor;
+        }
+        urgency
+    }
+
+    /// Use a custom priority scheme
+    pub fn with_priority_scheme(mut self, scheme: PriorityScheme) -> Self {
+        self.priority_scheme = scheme;
+        self
+    }
+
+    /// Let the priority term decay or grow with age
+    pub fn with_priority_aging(mut self, aging: PriorityAging) -> Self {
+        self.priority_aging = aging;
+        self
+    }
+
+    pub fn priority_aging(&self) -> PriorityAging {
+        self.priority_aging
+    }
+
+    /// Age in days at which the age term, and priority aging, are complete
+    pub fn age_max(&self) -> f64 {
+        self.age_max
+    }
+
+    pub fn priority_scheme(&self) -> &PriorityScheme {
+        &self.priority_scheme
+    }
+
+    /// Coefficient of `term`, e.g. `due` or `uda.estimate`
+    pub fn coefficient(&self, term: &str) -> Option<f64> {
+        self.coefficients.get(term).copied()
+    }
+
+    /// Urgency of `task` at `now`
+    pub fn score(&self, task: &Task, now: DateTime<Utc>) -> f64 {
+        let urgency: f64 = self
+            .breakdown(task, now)
+            .iter()
+            .map(UrgencyTerm::value)
+            .sum();
+        urgency.max(0.0)
+    }
+
+    /// The terms adding up to the urgency of `task` at `now`, leaving out
+    /// those contributing nothing
+    pub fn breakdown(&self, task: &Task, now: DateTime<Utc>) -> Vec<UrgencyTerm> {
+        let mut terms = Vec::new();
+        let mut add = |name: &str, factor: f64, coefficient: f64| {
+            if factor * coefficient != 0.0 {
+                terms.push(UrgencyTerm {
+                    name: name.to_string(),
+                    factor,
+                    coefficient,
+                });
+            }
+        };
+
+        // An entry date in the future (clock skew) counts as brand new
+        let age_days = now.signed_duration_since(task.entry).num_days().max(0) as f64;
+        let age_progress = age_days.min(self.age_max) / self.age_max;
+
+        // Priority component
+        if let Some(code) = task.priority_code() {
+            add(
+                "priority",
+                self.priority_aging.multiplier(age_progress),
+                self.priority_scheme.coefficient(code),
+            );
+        }
+
+        // Project component
+        if task.project.is_some() {
+            add(
+                "project",
+                1.0,
+                *self.coefficients.get("project").unwrap_or(&1.0),
+            );
+        }
+
+        // Tags component
+        if !task.tags.is_empty() {
+            add("tags", 1.0, *self.coefficients.get("tags").unwrap_or(&1.0));
+        }
+
+        // UDA components, by name
+        let mut udas: Vec<(&String, &f64)> = self
+            .coefficients
+            .iter()
+            .filter(|(term, _)| {
+                term.strip_prefix("uda.")
+                    .is_some_and(|uda| task.udas.contains_key(uda))
+            })
+            .collect();
+        udas.sort_by(|a, b| a.0.cmp(b.0));
+        for (term, coefficient) in udas {
+            add(term, 1.0, *coefficient);
+        }
+
+        // Due date component
+        if let Some(due) = task.due {
+            add(
+                "due",
+                due_factor(due, now),
+                *self.coefficients.get("due").unwrap_or(&12.0),
+            );
+        }
+
+        // Age component, from 0 for new tasks to the full coefficient at
+        // `urgency.age.max` days
+        add(
+            "age",
+            age_progress,
+            *self.coefficients.get("age").unwrap_or(&2.0),
+        );
+
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_score_is_a_function_of_now() {
+        let urgency = UrgencyCoefficients::new();
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let mut task = Task::new("Pay rent".to_string());
+        task.entry = now;
+        task.due = Some(now + Duration::days(4));
+
+        // Due in four days: 12 * ((-4 + 14) * 0.8 / 21 + 0.2)
+        assert!((urgency.score(&task, now) - 6.971_428).abs() < 1e-6);
+        // Two days overdue six days later, plus six days of age
+        let later = now + Duration::days(6);
+        let expected = 12.0 * (16.0 * 0.8 / 21.0 + 0.2) + 2.0 * 6.0 / 365.0;
+        assert!((urgency.score(&task, later) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_priority_aging_from_config() {
+        let mut config = Configuration::default();
+        config.set("urgency.age.coefficient", "0");
+        config.set("urgency.age.max", "10");
+        config.set("urgency.priority.aging", "linear");
+        config.set("urgency.priority.aging.factor", "0.5");
+        let urgency = UrgencyCoefficients::from_config(&config);
+        assert_eq!(urgency.age_max(), 10.0);
+
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let mut task = Task::new("Refactor".to_string());
+        task.priority = Some(crate::task::Priority::High);
+        task.entry = now;
+        assert_eq!(urgency.score(&task, now), 6.0);
+        assert_eq!(urgency.score(&task, now + Duration::days(5)), 4.5);
+        // Capped at urgency.age.max
+        assert_eq!(urgency.score(&task, now + Duration::days(40)), 3.0);
+
+        let growing = urgency.with_priority_aging(PriorityAging {
+            curve: AgingCurve::Exponential,
+            factor: 4.0,
+        });
+        assert_eq!(growing.score(&task, now + Duration::days(5)), 12.0);
+    }
+
+    #[test]
+    fn test_breakdown_sums_to_score() {
+        let urgency = UrgencyCoefficients::new();
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        let mut task = Task::new("Pay rent".to_string());
+        task.entry = now - Duration::days(73);
+        task.due = Some(now + Duration::days(4));
+        task.project = Some("Home".to_string());
+
+        let terms = urgency.breakdown(&task, now);
+        let names: Vec<&str> = terms.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["project", "due", "age"]);
+        assert_eq!(terms[2].factor, 0.2);
+        let sum: f64 = terms.iter().map(UrgencyTerm::value).sum();
+        assert_eq!(sum, urgency.score(&task, now));
+    }
+
+    #[test]
+    fn test_due_term_matches_taskwarrior() {
+        // The due term as `task info` reports it (factor * 12.0, three
+        // decimals), from Taskwarrior's Task::urgency_due
+        let golden = [
+            (-30.0, 2.4),
+            (-14.0, 2.4),
+            (-7.0, 5.6),
+            (-1.0, 8.343),
+            (0.0, 8.8),
+            (0.5, 9.029),
+            (3.0, 10.171),
+            (7.0, 12.0),
+            (100.0, 12.0),
+        ];
+        let urgency = UrgencyCoefficients::new();
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        for (days_overdue, expected) in golden {
+            let due = now - Duration::seconds((days_overdue * 86_400.0) as i64);
+            let term = urgency.coefficient("due").unwrap() * due_factor(due, now);
+            assert_eq!((term * 1000.0).round() / 1000.0, expected, "{days_overdue} days");
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! In-memory task manager recording its calls
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use uuid::Uuid;
+
+use crate::clock;
+use crate::config::{Configuration, ConfigurationProvider};
+use crate::error::TaskError;
+use crate::query::TaskQuery;
+use crate::task::manager::{AddOptions, SyncResult, TaskUpdate, ValidationReport};
+use crate::task::{Task, TaskManager, TaskStatus};
+
+/// A [`TaskManager`] method, for scheduling failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MockMethod {
+    AddTask,
+    GetTask,
+    UpdateTask,
+    DeleteTask,
+    CompleteTask,
+    Denotate,
+    QueryTasks,
+    PendingTasks,
+    CompletedTasks,
+    DeletedTasks,
+    RestoreTask,
+    CountTasks,
+    Sync,
+    ValidateAll,
+}
+
+/// One recorded call with its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockCall {
+    AddTask {
+        description: String,
+        filter_mode: Option<crate::query::FilterMode>,
+    },
+    GetTask {
+        id: Uuid,
+    },
+    UpdateTask {
+        id: Uuid,
+        description: Option<String>,
+        status: Option<TaskStatus>,
+    },
+    DeleteTask {
+        id: Uuid,
+    },
+    CompleteTask {
+        id: Uuid,
+    },
+    Denotate {
+        id: Uuid,
+        text: String,
+    },
+    QueryTasks {
+        query: TaskQuery,
+    },
+    PendingTasks,
+    CompletedTasks,
+    DeletedTasks,
+    RestoreTask {
+        id: Uuid,
+    },
+    CountTasks {
+        query: TaskQuery,
+    },
+    Sync,
+    ValidateAll,
+}
+
+impl MockCall {
+    pub fn method(&self) -> MockMethod {
+        match self {
+            Self::AddTask { .. } => MockMethod::AddTask,
+            Self::GetTask { .. } => MockMethod::GetTask,
+            Self::UpdateTask { .. } => MockMethod::UpdateTask,
+            Self::DeleteTask { .. } => MockMethod::DeleteTask,
+            Self::CompleteTask { .. } => MockMethod::CompleteTask,
+            Self::Denotate { .. } => MockMethod::Denotate,
+            Self::QueryTasks { .. } => MockMethod::QueryTasks,
+            Self::PendingTasks => MockMethod::PendingTasks,
+            Self::CompletedTasks => MockMethod::CompletedTasks,
+            Self::DeletedTasks => MockMethod::DeletedTasks,
+            Self::RestoreTask { .. } => MockMethod::RestoreTask,
+            Self::CountTasks { .. } => MockMethod::CountTasks,
+            Self::Sync => MockMethod::Sync,
+            Self::ValidateAll => MockMethod::ValidateAll,
+        }
+    }
+}
+
+type ErrorFn = Box<dyn Fn() -> TaskError + Send>;
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<MockCall>,
+    queued: HashMap<MockMethod, VecDeque<TaskError>>,
+    always: HashMap<MockMethod, ErrorFn>,
+}
+
+/// In-memory [`TaskManager`] for tests of code that takes one.
+///
+/// Tasks live in a map and behave as with the real manager: updates stamp
+/// `modified`, completing sets `end`, deleting removes the task and tasks
+/// updated to `deleted` can be restored. Every call is recorded before it
+/// runs, including calls that fail, and any method can be made to fail
+/// once ([`Self::fail_next`]) or every time ([`Self::fail_always`]).
+pub struct MockTaskManager {
+    config: Configuration,
+    tasks: HashMap<Uuid, Task>,
+    sync_result: SyncResult,
+    state: Mutex<MockState>,
+}
+
+impl std::fmt::Debug for MockTaskManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockTaskManager")
+            .field("tasks", &self.tasks.len())
+            .field("calls", &self.calls().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for MockTaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTaskManager {
+    pub fn new() -> Self {
+        Self {
+            config: Configuration::default(),
+            tasks: HashMap::new(),
+            sync_result: SyncResult {
+                tasks_pulled: 0,
+                tasks_pushed: 0,
+                conflicts_resolved: 0,
+                tasks_purged: 0,
+                purge_error: None,
+            },
+            state: Mutex::new(MockState::default()),
+        }
+    }
+
+    /// Start with these tasks stored
+    pub fn with_tasks<I: IntoIterator<Item = Task>>(mut self, tasks: I) -> Self {
+        self.tasks
+            .extend(tasks.into_iter().map(|task| (task.id, task)));
+        self
+    }
+
+    /// Use this configuration
+    pub fn with_config(mut self, config: Configuration) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Return `result` from [`TaskManager::sync`]
+    pub fn with_sync_result(mut self, result: SyncResult) -> Self {
+        self.sync_result = result;
+        self
+    }
+
+    /// Fail the next call of `method` with `error`. Queued errors are
+    /// returned in order, one per call.
+    pub fn fail_next(&self, method: MockMethod, error: TaskError) {
+        self.state()
+            .queued
+            .entry(method)
+            .or_default()
+            .push_back(error);
+    }
+
+    /// Fail every call of `method` with an error from `error`, after any
+    /// queued by [`Self::fail_next`]
+    pub fn fail_always<F>(&self, method: MockMethod, error: F)
+    where
+        F: Fn() -> TaskError + Send + 'static,
+    {
+        self.state().always.insert(method, Box::new(error));
+    }
+
+    /// Stop failing calls of `method`
+    pub fn succeed(&self, method: MockMethod) {
+        let mut state = self.state();
+        state.queued.remove(&method);
+        state.always.remove(&method);
+    }
+
+    /// Calls made so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state().calls.clone()
+    }
+
+    /// Number of calls of `method` made so far
+    pub fn call_count(&self, method: MockMethod) -> usize {
+        self.state()
+            .calls
+            .iter()
+            .filter(|call| call.method() == method)
+            .count()
+    }
+
+    /// Forget the recorded calls
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    /// Stored tasks, in no particular order
+    pub fn tasks(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.values()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        // A test that panicked while holding the lock has failed already
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record `call` and return the error scheduled for it, if any
+    fn enter(&self, call: MockCall) -> Result<(), TaskError> {
+        let method = call.method();
+        let mut state = self.state();
+        state.calls.push(call);
+        if let Some(error) = state.queued.get_mut(&method).and_then(VecDeque::pop_front) {
+            return Err(error);
+        }
+        match state.always.get(&method) {
+            Some(error) => Err(error()),
+            None => Ok(()),
+        }
+    }
+
+    fn stored(&mut self, id: Uuid) -> Result<&mut Task, TaskError> {
+        self.tasks.get_mut(&id).ok_or(TaskError::NotFound { id })
+    }
+
+    fn apply(&mut self, id: Uuid, update: &TaskUpdate) -> Result<Task, TaskError> {
+        if update.is_empty() {
+            return Err(TaskError::EmptyUpdate);
+        }
+        let task = self.stored(id)?;
+        if let Some(expected) = &update.expected_etag {
+            let actual = task.etag();
+            if *expected != actual {
+                return Err(TaskError::Conflict {
+                    id,
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        update.apply_to(task);
+        Ok(task.clone())
+    }
+
+    fn select(&self, query: &TaskQuery) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self
+            .tasks
+            .values()
+            .filter(|task| query.matches(task))
+            .cloned()
+            .collect();
+        tasks.sort_by_key(|task| task.entry);
+        query.apply_sort(&mut tasks);
+        tasks
+            .into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    fn with_status(&self, status: TaskStatus) -> Vec<Task> {
+        self.select(&TaskQuery {
+            status: Some(status),
+            ..Default::default()
+        })
+    }
+}
+
+impl ConfigurationProvider for MockTaskManager {
+    fn config(&self) -> &Configuration {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut Configuration {
+        &mut self.config
+    }
+
+    fn reload_config(&mut self) -> Result<(), TaskError> {
+        Ok(())
+    }
+}
+
+impl TaskManager for MockTaskManager {
+    fn add_task(&mut self, description: String) -> Result<Task, TaskError> {
+        self.add_task_with_options(description, AddOptions::default())
+    }
+
+    fn add_task_with_options(
+        &mut self,
+        description: String,
+        options: AddOptions,
+    ) -> Result<Task, TaskError> {
+        self.enter(MockCall::AddTask {
+            description: description.clone(),
+            filter_mode: options.filter_mode,
+        })?;
+        let task = Task::new(description);
+        self.tasks.insert(task.id, task.clone());
+        Ok(task)
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        self.enter(MockCall::GetTask { id })?;
+        Ok(self.tasks.get(&id).cloned())
+    }
+
+    fn update_task(&mut self, id: Uuid, updates: TaskUpdate) -> Result<Task, TaskError> {
+        self.enter(MockCall::UpdateTask {
+            id,
+            description: updates.description.clone(),
+            status: updates.status,
+        })?;
+        self.apply(id, &updates)
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.enter(MockCall::DeleteTask { id })?;
+        self.tasks.remove(&id).ok_or(TaskError::NotFound { id })
+    }
+
+    fn complete_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.enter(MockCall::CompleteTask { id })?;
+        self.apply(id, &TaskUpdate::new().status(TaskStatus::Completed))
+    }
+
+    fn denotate(&mut self, id: Uuid, text: &str) -> Result<Task, TaskError> {
+        self.enter(MockCall::Denotate {
+            id,
+            text: text.to_string(),
+        })?;
+        let task = self.stored(id)?;
+        let index =
+            crate::task::annotation::find_annotation(&task.annotations, text).ok_or_else(|| {
+                TaskError::InvalidData {
+                    message: format!("No annotation matching '{text}'"),
+                }
+            })?;
+        let mut annotations = task.annotations.clone();
+        annotations.remove(index);
+        self.apply(
+            id,
+            &TaskUpdate {
+                annotations: Some(annotations),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn query_tasks(&mut self, query: &TaskQuery) -> Result<Vec<Task>, TaskError> {
+        self.enter(MockCall::QueryTasks {
+            query: query.clone(),
+        })?;
+        Ok(self.select(query))
+    }
+
+    fn pending_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.enter(MockCall::PendingTasks)?;
+        let now = clock::now();
+        let mut tasks = self.with_status(TaskStatus::Pending);
+        tasks.retain(|task| !task.is_waiting_at(now));
+        Ok(tasks)
+    }
+
+    fn completed_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.enter(MockCall::CompletedTasks)?;
+        Ok(self.with_status(TaskStatus::Completed))
+    }
+
+    fn deleted_tasks(&mut self) -> Result<Vec<Task>, TaskError> {
+        self.enter(MockCall::DeletedTasks)?;
+        Ok(self.with_status(TaskStatus::Deleted))
+    }
+
+    fn restore_task(&mut self, id: Uuid) -> Result<Task, TaskError> {
+        self.enter(MockCall::RestoreTask { id })?;
+        let task = self.stored(id)?;
+        if task.status != TaskStatus::Deleted {
+            return Err(TaskError::InvalidState {
+                message: format!(
+                    "only deleted tasks can be restored, {id} is {:?}",
+                    task.status
+                ),
+            });
+        }
+        TaskUpdate::new().status(TaskStatus::Pending).apply_to(task);
+        task.end = None;
+        Ok(task.clone())
+    }
+
+    fn count_tasks(&mut self, query: &TaskQuery) -> Result<usize, TaskError> {
+        self.enter(MockCall::CountTasks {
+            query: query.clone(),
+        })?;
+        Ok(self.select(query).len())
+    }
+
+    fn sync(&mut self) -> Result<SyncResult, TaskError> {
+        self.enter(MockCall::Sync)?;
+        Ok(self.sync_result.clone())
+    }
+
+    fn validate_all(&self) -> Result<ValidationReport, TaskError> {
+        self.enter(MockCall::ValidateAll)?;
+        Ok(ValidationReport {
+            total_tasks: self.tasks.len(),
+            valid_tasks: self.tasks.len(),
+            invalid_tasks: 0,
+            errors: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{hook_abort, sync_conflict};
+
+    #[test]
+    fn test_records_calls_and_fails_on_demand() {
+        let mut manager = MockTaskManager::new();
+        let task = manager.add_task("Mocked".to_string()).unwrap();
+        manager.fail_next(MockMethod::CompleteTask, hook_abort());
+
+        assert!(matches!(
+            manager.complete_task(task.id),
+            Err(TaskError::HookFailed { .. })
+        ));
+        let done = manager.complete_task(task.id).unwrap();
+        assert_eq!(done.status, TaskStatus::Completed);
+        assert!(done.end.is_some());
+        assert!(manager.pending_tasks().unwrap().is_empty());
+
+        manager.fail_always(MockMethod::Sync, sync_conflict);
+        assert!(manager.sync().is_err());
+        assert!(manager.sync().is_err());
+        manager.succeed(MockMethod::Sync);
+        assert_eq!(manager.sync().unwrap().tasks_pulled, 0);
+
+        assert_eq!(manager.call_count(MockMethod::CompleteTask), 2);
+        assert_eq!(manager.call_count(MockMethod::Sync), 3);
+        assert_eq!(
+            manager.calls()[0],
+            MockCall::AddTask {
+                description: "Mocked".to_string(),
+                filter_mode: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_trash_and_queries() {
+        let mut manager =
+            MockTaskManager::new().with_tasks(["a", "b", "c"].map(|d| Task::new(d.to_string())));
+        let id_of =
+            |m: &MockTaskManager, d: &str| m.tasks().find(|t| t.description == d).unwrap().id;
+        let (a, b) = (id_of(&manager, "a"), id_of(&manager, "b"));
+
+        manager.delete_task(a).unwrap();
+        manager
+            .update_task(b, TaskUpdate::new().status(TaskStatus::Deleted))
+            .unwrap();
+        assert_eq!(manager.deleted_tasks().unwrap().len(), 1);
+        let query = TaskQuery {
+            status: Some(TaskStatus::Pending),
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(manager.query_tasks(&query).unwrap().len(), 1);
+        assert_eq!(manager.count_tasks(&TaskQuery::default()).unwrap(), 2);
+        let id = b;
+
+        assert_eq!(
+            manager.restore_task(id).unwrap().status,
+            TaskStatus::Pending
+        );
+        assert!(matches!(
+            manager.restore_task(id),
+            Err(TaskError::InvalidState { .. })
+        ));
+        assert!(matches!(
+            manager.update_task(Uuid::nil(), TaskUpdate::new().description("x")),
+            Err(TaskError::NotFound { .. })
+        ));
+    }
+}
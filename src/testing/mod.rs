@@ -0,0 +1,44 @@
+//! Test doubles for code built on this library
+//!
+//! - [`MockTaskManager`]: an in-memory [`TaskManager`](crate::task::TaskManager)
+//!   that records every call and fails on demand
+//! - [`FlakyStorageBackend`]: wraps any storage backend and injects errors
+//!   into chosen operations, to exercise the real manager's error paths
+//!
+//! The functions below build the errors those paths usually see, so tests
+//! do not depend on how a particular backend words them.
+
+mod manager;
+mod storage;
+
+pub use manager::{MockCall, MockMethod, MockTaskManager};
+pub use storage::{FaultInjector, FlakyStorageBackend, StorageOp};
+
+use crate::error::{StorageError, SyncError, TaskError};
+
+/// The error a backend returns when the disk is full
+pub fn storage_full() -> TaskError {
+    TaskError::Storage {
+        source: StorageError::Io(std::io::Error::new(
+            std::io::ErrorKind::StorageFull,
+            "No space left on device",
+        )),
+    }
+}
+
+/// The error of a pre-operation hook that rejected the change
+pub fn hook_abort() -> TaskError {
+    TaskError::HookFailed {
+        message: "Hook aborted operation".to_string(),
+    }
+}
+
+/// The error of a sync that ran into a conflicting change on the server
+pub fn sync_conflict() -> TaskError {
+    TaskError::Sync {
+        message: SyncError::Conflict {
+            message: "server has a newer version of the task".to_string(),
+        }
+        .to_string(),
+    }
+}
@@ -0,0 +1,318 @@
+//! Storage backend wrapper injecting failures
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::config::context::UserContext;
+use crate::error::{StorageError, TaskError};
+use crate::query::TaskQuery;
+use crate::storage::integrity::IntegrityReport;
+use crate::storage::StorageBackend;
+use crate::task::Task;
+
+/// A [`StorageBackend`] method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageOp {
+    Initialize,
+    Save,
+    Load,
+    Delete,
+    Purge,
+    LoadAll,
+    Archive,
+    Query,
+    Verify,
+    Backup,
+    Restore,
+}
+
+type ErrorFn = Box<dyn Fn() -> TaskError + Send>;
+
+struct Fault {
+    op: StorageOp,
+    /// Calls to let through before failing
+    skip: usize,
+    /// Failures left; None fails forever
+    remaining: Option<usize>,
+    error: ErrorFn,
+}
+
+#[derive(Default)]
+struct Faults {
+    faults: Vec<Fault>,
+    calls: HashMap<StorageOp, usize>,
+}
+
+/// Handle for scheduling failures of a [`FlakyStorageBackend`], usable
+/// after the backend has been handed to a task manager
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    inner: Arc<Mutex<Faults>>,
+}
+
+impl std::fmt::Debug for FaultInjector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let faults = self.lock();
+        f.debug_struct("FaultInjector")
+            .field("faults", &faults.faults.len())
+            .field("calls", &faults.calls)
+            .finish()
+    }
+}
+
+impl FaultInjector {
+    /// Fail the next call of `op`
+    pub fn fail_once<F>(&self, op: StorageOp, error: F)
+    where
+        F: Fn() -> TaskError + Send + 'static,
+    {
+        self.add(op, 0, Some(1), error);
+    }
+
+    /// Fail every call of `op`
+    pub fn fail_always<F>(&self, op: StorageOp, error: F)
+    where
+        F: Fn() -> TaskError + Send + 'static,
+    {
+        self.add(op, 0, None, error);
+    }
+
+    /// Let `successes` calls of `op` through, then fail every one after
+    pub fn fail_after<F>(&self, op: StorageOp, successes: usize, error: F)
+    where
+        F: Fn() -> TaskError + Send + 'static,
+    {
+        self.add(op, successes, None, error);
+    }
+
+    /// Remove every scheduled failure
+    pub fn clear(&self) {
+        self.lock().faults.clear();
+    }
+
+    /// Calls of `op` so far, failed ones included
+    pub fn calls(&self, op: StorageOp) -> usize {
+        self.lock().calls.get(&op).copied().unwrap_or(0)
+    }
+
+    fn add<F>(&self, op: StorageOp, skip: usize, remaining: Option<usize>, error: F)
+    where
+        F: Fn() -> TaskError + Send + 'static,
+    {
+        self.lock().faults.push(Fault {
+            op,
+            skip,
+            remaining,
+            error: Box::new(error),
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Faults> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a call of `op` and return the error of the first fault due
+    fn check(&self, op: StorageOp) -> Result<(), TaskError> {
+        let mut faults = self.lock();
+        *faults.calls.entry(op).or_default() += 1;
+        let mut error = None;
+        for fault in faults.faults.iter_mut().filter(|f| f.op == op) {
+            if fault.skip > 0 {
+                fault.skip -= 1;
+                continue;
+            }
+            if error.is_none() && fault.remaining != Some(0) {
+                error = Some((fault.error)());
+                if let Some(remaining) = &mut fault.remaining {
+                    *remaining -= 1;
+                }
+            }
+        }
+        faults.faults.retain(|f| f.remaining != Some(0));
+        error.map_or(Ok(()), Err)
+    }
+
+    /// [`Self::check`] for the methods returning a [`StorageError`]
+    fn check_storage(&self, op: StorageOp) -> Result<(), StorageError> {
+        self.check(op).map_err(|e| match e {
+            TaskError::Storage { source } => source,
+            other => StorageError::Database {
+                message: other.to_string(),
+            },
+        })
+    }
+}
+
+/// Wraps a storage backend and fails chosen operations on demand, so the
+/// task manager's handling of a full disk or a locked database can be
+/// tested deterministically:
+///
+/// ```rust
+/// use taskwarrior3lib::storage::FileStorageBackend;
+/// use taskwarrior3lib::testing::{storage_full, FlakyStorageBackend, StorageOp};
+///
+/// let dir = tempfile::TempDir::new().unwrap();
+/// let storage = FlakyStorageBackend::new(FileStorageBackend::with_path(dir.path()));
+/// let faults = storage.faults();
+/// faults.fail_once(StorageOp::Save, storage_full);
+/// ```
+#[derive(Debug)]
+pub struct FlakyStorageBackend<S: StorageBackend> {
+    inner: S,
+    faults: FaultInjector,
+}
+
+impl<S: StorageBackend> FlakyStorageBackend<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            faults: FaultInjector::default(),
+        }
+    }
+
+    /// Handle for scheduling failures, shared with this backend
+    pub fn faults(&self) -> FaultInjector {
+        self.faults.clone()
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: StorageBackend> StorageBackend for FlakyStorageBackend<S> {
+    fn backend_name(&self) -> &str {
+        self.inner.backend_name()
+    }
+
+    fn initialize(&mut self) -> Result<(), TaskError> {
+        self.faults.check(StorageOp::Initialize)?;
+        self.inner.initialize()
+    }
+
+    fn save_task(&mut self, task: &Task) -> Result<(), TaskError> {
+        self.faults.check(StorageOp::Save)?;
+        self.inner.save_task(task)
+    }
+
+    fn load_task(&self, id: Uuid) -> Result<Option<Task>, TaskError> {
+        self.faults.check(StorageOp::Load)?;
+        self.inner.load_task(id)
+    }
+
+    fn delete_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        self.faults.check(StorageOp::Delete)?;
+        self.inner.delete_task(id)
+    }
+
+    fn purge_task(&mut self, id: Uuid) -> Result<(), TaskError> {
+        self.faults.check(StorageOp::Purge)?;
+        self.inner.purge_task(id)
+    }
+
+    fn load_all_tasks(&self) -> Result<Vec<Task>, TaskError> {
+        self.faults.check(StorageOp::LoadAll)?;
+        self.inner.load_all_tasks()
+    }
+
+    fn archive_completed(&mut self, before: DateTime<Utc>) -> Result<usize, TaskError> {
+        self.faults.check(StorageOp::Archive)?;
+        self.inner.archive_completed(before)
+    }
+
+    fn query_tasks(
+        &self,
+        query: &TaskQuery,
+        active_context: Option<&UserContext>,
+    ) -> Result<Vec<Task>, TaskError> {
+        self.faults.check(StorageOp::Query)?;
+        self.inner.query_tasks(query, active_context)
+    }
+
+    fn verify(&self) -> Result<IntegrityReport, TaskError> {
+        self.faults.check(StorageOp::Verify)?;
+        self.inner.verify()
+    }
+
+    fn backup(&self) -> Result<String, StorageError> {
+        self.faults.check_storage(StorageOp::Backup)?;
+        self.inner.backup()
+    }
+
+    fn restore(&mut self, backup_data: &str) -> Result<(), StorageError> {
+        self.faults.check_storage(StorageOp::Restore)?;
+        self.inner.restore(backup_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigurationBuilder;
+    use crate::hooks::DefaultHookSystem;
+    use crate::storage::FileStorageBackend;
+    use crate::task::manager::DefaultTaskManager;
+    use crate::task::TaskManager;
+    use crate::testing::storage_full;
+
+    #[test]
+    fn test_manager_sees_injected_failures() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = ConfigurationBuilder::new()
+            .data_dir(dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        let storage = FlakyStorageBackend::new(FileStorageBackend::with_path(dir.path()));
+        let faults = storage.faults();
+        let mut manager = DefaultTaskManager::new(
+            config,
+            Box::new(storage),
+            Box::new(DefaultHookSystem::new()),
+        )
+        .unwrap();
+
+        faults.fail_once(StorageOp::Save, storage_full);
+        let err = manager.add_task("Lost".to_string()).unwrap_err();
+        assert!(matches!(
+            err.root(),
+            TaskError::Storage { source: StorageError::Io(e) }
+                if e.kind() == std::io::ErrorKind::StorageFull
+        ));
+        let context = err.context().unwrap();
+        assert_eq!(context.operation, "add");
+        assert_eq!(context.backend.as_deref(), Some("file"));
+        let task = manager.add_task("Kept".to_string()).unwrap();
+
+        faults.fail_after(StorageOp::Load, 1, || TaskError::Storage {
+            source: StorageError::Lock {
+                message: "database is locked".to_string(),
+            },
+        });
+        assert!(manager.get_task(task.id).unwrap().is_some());
+        let err = manager.get_task(task.id).unwrap_err();
+        assert_eq!(err.context().unwrap().task, Some(task.id));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "get of task {} failed (file storage): Storage error: Lock error: database is locked",
+                task.id
+            )
+        );
+        assert!(manager.get_task(task.id).is_err());
+        faults.clear();
+        assert!(manager.get_task(task.id).unwrap().is_some());
+        assert_eq!(faults.calls(StorageOp::Save), 2);
+        assert_eq!(faults.calls(StorageOp::Load), 4);
+    }
+}
@@ -34,3 +34,260 @@ fn test_create_and_read_task_replica_actor() {
     assert_eq!(t.project.as_deref().unwrap_or_default(), "TestProj");
     assert!(t.has_tag("one"));
 }
+
+#[test]
+fn test_typed_udas_round_trip_replica_actor() {
+    use taskwarrior3lib::storage::operation_batch::{build_save_batch, compute_update_ops};
+    use taskwarrior3lib::task::model::UdaValue;
+    use taskwarrior3lib::task::{Task, UdaType, UdaTypes};
+
+    let tmp = TempDir::new().expect("tempdir");
+    let mut replica = open_taskchampion_replica(tmp.path()).expect("open replica");
+    let mut types = UdaTypes::new();
+    types.insert("estimate", UdaType::Numeric);
+    types.insert("code", UdaType::String);
+    replica.set_uda_types(types);
+
+    let mut task = Task::new("Typed UDAs".to_string());
+    task.udas.insert("estimate".to_string(), UdaValue::Number(3.0));
+    task.udas.insert("code".to_string(), UdaValue::String("007".to_string()));
+    replica.commit_operations(&build_save_batch(None, &task)).expect("commit");
+
+    let read = replica.read_task(task.id).expect("read").expect("task");
+    assert_eq!(read.udas.get("estimate"), Some(&UdaValue::Number(3.0)));
+    assert_eq!(read.udas.get("code"), Some(&UdaValue::String("007".to_string())));
+
+    let mut updated = read.clone();
+    updated.udas.remove("code");
+    updated.udas.insert("estimate".to_string(), UdaValue::Number(4.5));
+    replica.commit_operations(&compute_update_ops(&read, &updated)).expect("commit update");
+
+    let read = replica.read_task(task.id).expect("read").expect("task");
+    assert_eq!(read.udas.get("estimate"), Some(&UdaValue::Number(4.5)));
+    assert!(!read.udas.contains_key("code"));
+}
+
+#[test]
+fn test_bulk_reads_working_set_and_undo_replica_actor() {
+    use taskwarrior3lib::storage::operation_batch::build_save_batch;
+    use taskwarrior3lib::task::{Task, TaskStatus};
+
+    let tmp = TempDir::new().expect("tempdir");
+    let mut replica = open_taskchampion_replica(tmp.path()).expect("open replica");
+
+    let first = Task::new("First".to_string());
+    let mut done = Task::new("Done".to_string());
+    done.status = TaskStatus::Completed;
+    // Each save batch starts with an undo point
+    for task in [&first, &done] {
+        replica.commit_operations(&build_save_batch(None, task)).expect("commit");
+    }
+
+    assert_eq!(replica.all_tasks().expect("all").len(), 2);
+    let pending = replica.pending_tasks().expect("pending");
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, first.id);
+    assert_eq!(pending[0].display_id, Some(1));
+    assert_eq!(replica.working_set().expect("working set"), vec![(1, first.id)]);
+    assert!(replica.num_local_operations().expect("count") > 0);
+    assert_eq!(replica.num_undo_points().expect("undo points"), 2);
+
+    // Undo reverts the second save only
+    assert!(replica.undo().expect("undo"));
+    assert!(replica.read_task(done.id).expect("read").is_none());
+    assert!(replica.read_task(first.id).expect("read").is_some());
+}
+
+#[test]
+fn test_health_check_and_close_replica_actor() {
+    use taskwarrior3lib::error::TaskError;
+    use taskwarrior3lib::task::Task;
+
+    let tmp = TempDir::new().expect("tempdir");
+    let mut replica = open_taskchampion_replica(tmp.path()).expect("open replica");
+    replica.health_check().expect("healthy");
+
+    let task = Task::new("Written before close".to_string());
+    replica
+        .commit_operations(&taskwarrior3lib::storage::operation_batch::build_save_batch(None, &task))
+        .expect("commit");
+    replica.close().expect("close");
+    assert!(matches!(replica.health_check(), Err(TaskError::ServiceStopped)));
+    assert!(matches!(replica.read_task(task.id), Err(TaskError::ServiceStopped)));
+
+    // The closed actor released the database, so a new one sees the write
+    let reopened = open_taskchampion_replica(tmp.path()).expect("reopen replica");
+    assert!(reopened.read_task(task.id).expect("read").is_some());
+}
+
+#[test]
+fn test_queue_full_and_timeout_replica_actor() {
+    use std::time::Duration;
+    use taskwarrior3lib::error::{StorageError, TaskError};
+    use taskwarrior3lib::storage::replica_taskchampion::{open_taskchampion_replica_with, ReplicaOptions};
+
+    let tmp = TempDir::new().expect("tempdir");
+    let options = ReplicaOptions {
+        queue_capacity: 1,
+        request_timeout: Some(Duration::from_millis(200)),
+    };
+    let replica = open_taskchampion_replica_with(tmp.path(), options).expect("open replica");
+    replica.health_check().expect("health check");
+
+    // Another connection holds the write lock for a while, so the actor
+    // stays busy with the first write it starts
+    let hold = Duration::from_millis(800);
+    let db = tmp.path().join("taskchampion.sqlite3");
+    let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let holder = rusqlite::Connection::open(db).expect("open database");
+            holder.execute_batch("BEGIN IMMEDIATE").expect("lock database");
+            locked_tx.send(()).unwrap();
+            std::thread::sleep(hold);
+            holder.execute_batch("ROLLBACK").expect("unlock database");
+        });
+        locked_rx.recv().unwrap();
+        let replica = &*replica;
+
+        // Started before its caller gave up, so it is waited for
+        let started = scope.spawn(move || replica.rebuild_working_set(false));
+        std::thread::sleep(Duration::from_millis(50));
+
+        // Still queued when its caller gives up, so it is withdrawn, and
+        // while it holds the only slot the next request is turned away
+        assert!(matches!(
+            replica.health_check(),
+            Err(TaskError::Storage { source: StorageError::Timeout { .. } })
+        ));
+        assert!(matches!(
+            replica.health_check(),
+            Err(TaskError::Storage { source: StorageError::QueueFull { capacity: 1 } })
+        ));
+        started.join().unwrap().expect("write waited for past the timeout");
+    });
+}
+
+#[test]
+fn test_gc_renumbers_working_set_on_query() {
+    use taskwarrior3lib::query::TaskQuery;
+    use taskwarrior3lib::storage::{StorageBackend, TaskChampionStorageBackend};
+    use taskwarrior3lib::task::{Task, TaskStatus};
+
+    for gc in [true, false] {
+        let tmp = TempDir::new().expect("tempdir");
+        let mut storage = TaskChampionStorageBackend::new(tmp.path().join("taskchampion.sqlite3")).with_gc(gc);
+        storage.set_replica(open_taskchampion_replica(tmp.path()).expect("open replica"));
+
+        let mut tasks: Vec<Task> = ["one", "two", "three"].iter().map(|d| Task::new(d.to_string())).collect();
+        for task in &tasks {
+            storage.save_task(task).expect("save");
+        }
+        tasks[0].status = TaskStatus::Completed;
+        storage.save_task(&tasks[0]).expect("complete");
+
+        let query = TaskQuery { status: Some(TaskStatus::Pending), ..Default::default() };
+        let mut ids: Vec<_> = storage
+            .query_tasks(&query, None)
+            .expect("query")
+            .iter()
+            .map(|t| t.display_id)
+            .collect();
+        ids.sort();
+        let expected = if gc { vec![Some(1), Some(2)] } else { vec![Some(2), Some(3)] };
+        assert_eq!(ids, expected, "gc = {gc}");
+    }
+}
+
+#[test]
+fn test_sync_and_bootstrap_through_local_server() {
+    use taskwarrior3lib::sync::{SyncManager, SyncServerConfig, TaskChampionSyncManager};
+    use taskwarrior3lib::task::Task;
+
+    let server_dir = TempDir::new().expect("tempdir");
+    let server = SyncServerConfig::Local { server_dir: server_dir.path().to_path_buf() };
+
+    let first = TempDir::new().expect("tempdir");
+    let mut manager = TaskChampionSyncManager::new(open_taskchampion_replica(first.path()).expect("open"), server.clone());
+    let task = Task::new("Shared task".to_string());
+    assert_eq!(manager.push(std::slice::from_ref(&task)).expect("push"), 1);
+    manager.create_snapshot().expect("snapshot");
+    assert_eq!(manager.status().pending_changes, 0);
+
+    let second = TempDir::new().expect("tempdir");
+    let bootstrapped = TaskChampionSyncManager::bootstrap(second.path(), server.clone()).expect("bootstrap");
+    let read = bootstrapped.replica().read_task(task.id).expect("read").expect("task");
+    assert_eq!(read.description, "Shared task");
+
+    // A replica that already holds tasks cannot be bootstrapped
+    assert!(TaskChampionSyncManager::bootstrap(first.path(), server).is_err());
+}
+
+#[test]
+fn test_etag_survives_round_trip() {
+    use chrono::{Duration, TimeZone, Utc};
+    use taskwarrior3lib::storage::{StorageBackend, TaskChampionStorageBackend};
+    use taskwarrior3lib::task::manager::TaskUpdate;
+    use taskwarrior3lib::task::Task;
+
+    let tmp = TempDir::new().expect("tempdir");
+    let mut storage = TaskChampionStorageBackend::new(tmp.path().join("taskchampion.sqlite3"));
+    storage.set_replica(open_taskchampion_replica(tmp.path()).expect("open replica"));
+
+    // Sub-second times are cut to whole seconds on the way in
+    let mut task = Task::new("Versioned".to_string());
+    task.entry = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap() + Duration::milliseconds(250);
+    task.modified = Some(task.entry + Duration::milliseconds(500));
+    storage.save_task(&task).expect("save");
+    let stored = storage.load_task(task.id).expect("load").expect("task");
+    assert_eq!(stored.etag(), task.etag());
+
+    let mut updated = stored.clone();
+    TaskUpdate::new().description("Versioned again").apply_to(&mut updated);
+    assert_ne!(updated.etag(), stored.etag());
+    storage.save_task(&updated).expect("save update");
+    let reloaded = storage.load_task(task.id).expect("load").expect("task");
+    assert_eq!(reloaded.etag(), updated.etag());
+}
+
+#[test]
+fn test_save_task_runs_in_one_transaction() {
+    use std::time::{Duration, Instant};
+    use taskwarrior3lib::storage::{StorageBackend, TaskChampionStorageBackend};
+    use taskwarrior3lib::task::manager::TaskUpdate;
+    use taskwarrior3lib::task::Task;
+
+    let tmp = TempDir::new().expect("tempdir");
+    let db = tmp.path().join("taskchampion.sqlite3");
+    let mut storage = TaskChampionStorageBackend::new(&db);
+    storage.set_replica(open_taskchampion_replica(tmp.path()).expect("open replica"));
+
+    let task = Task::new("Water plants".to_string());
+    storage.save_task(&task).expect("save");
+    let stored = storage.load_task(task.id).expect("load").expect("task");
+    assert_eq!(stored.display_id, Some(1));
+
+    // Another writer, like the `task` CLI, holds the database; the save
+    // waits for it instead of interleaving
+    let cli = rusqlite::Connection::open(&db).expect("open");
+    cli.execute_batch("BEGIN IMMEDIATE").expect("begin");
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        cli.execute_batch("COMMIT").expect("commit");
+    });
+    let mut updated = stored.clone();
+    TaskUpdate::new().description("Water the plants").apply_to(&mut updated);
+    let started = Instant::now();
+    storage.save_task(&updated).expect("save update");
+    assert!(started.elapsed() >= Duration::from_millis(250));
+    release.join().unwrap();
+
+    let reloaded = storage.load_task(task.id).expect("load").expect("task");
+    assert_eq!(reloaded.description, "Water the plants");
+    assert_eq!(reloaded.etag(), updated.etag());
+
+    // Each save is one undo step
+    assert!(storage.undo().expect("undo"));
+    let undone = storage.load_task(task.id).expect("load").expect("task");
+    assert_eq!(undone.description, "Water plants");
+}
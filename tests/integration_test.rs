@@ -3,9 +3,9 @@
 //! These tests validate that all components work together correctly.
 
 use taskwarrior3lib::{
-    config::ConfigurationBuilder,
+    config::{ConfigurationBuilder, ConfigurationProvider},
     hooks::DefaultHookSystem,
-    query::{TaskQueryBuilder, TaskQueryBuilderImpl},
+    query::{OwnerFilter, TagFilter, TaskQuery, TaskQueryBuilder, TaskQueryBuilderImpl},
     reports::ReportManager,
     storage::FileStorageBackend,
     task::{
@@ -133,6 +133,15 @@ fn test_query_functionality() -> Result<(), Box<dyn std::error::Error>> {
     )?;
     assert_eq!(completed_count, 1);
 
+    // Custom predicates are applied in memory alongside regular filters
+    let custom_count = manager.count_tasks(
+        &TaskQueryBuilderImpl::new()
+            .status(TaskStatus::Pending)
+            .custom(|task| task.description.ends_with('4'))
+            .build()?,
+    )?;
+    assert_eq!(custom_count, 1);
+
     Ok(())
 }
 
@@ -220,3 +229,466 @@ fn test_comprehensive_workflow() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Test owner stamping and owner queries
+#[test]
+fn test_owner_stamping_and_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let config = ConfigurationBuilder::new()
+        .data_dir(temp_dir.path().to_path_buf())
+        .set("owner.stamp", "yes")
+        .set("user.name", "alice")
+        .build()?;
+    let storage = Box::new(FileStorageBackend::with_path(temp_dir.path().to_path_buf()));
+    let mut manager = DefaultTaskManager::new(config, storage, Box::new(DefaultHookSystem::new()))?;
+
+    let mine = manager.add_task("Mine".to_string())?;
+    assert_eq!(mine.owner.as_deref(), Some("alice"));
+
+    manager.config_mut().set("user.name", "bob");
+    let theirs = manager.add_task("Theirs".to_string())?;
+    manager.config_mut().set("owner.stamp", "no");
+    let nobody = manager.add_task("Nobody".to_string())?;
+    assert!(nobody.owner.is_none());
+
+    let query = TaskQueryBuilderImpl::new().owner("alice".to_string()).build()?;
+    let found = manager.query_tasks(&query)?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, mine.id);
+
+    let query = TaskQueryBuilderImpl::new()
+        .owner_filter(OwnerFilter::IsNot("alice".to_string()))
+        .build()?;
+    let found = manager.query_tasks(&query)?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, theirs.id);
+
+    let query = TaskQueryBuilderImpl::new()
+        .owner_filter(OwnerFilter::Unowned)
+        .build()?;
+    assert_eq!(manager.query_tasks(&query)?[0].id, nobody.id);
+
+    Ok(())
+}
+
+/// Test tag set and count filters through the manager
+#[test]
+fn test_tag_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+
+    let work = manager.add_task("Work".to_string())?;
+    manager.update_task(work.id, TaskUpdate::new().add_tag("work").add_tag("next"))?;
+    let home = manager.add_task("Home".to_string())?;
+    manager.update_task(home.id, TaskUpdate::new().add_tag("home"))?;
+    let bare = manager.add_task("Bare".to_string())?;
+
+    let mut descriptions = |query| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let mut found: Vec<String> = manager.query_tasks(&query)?.into_iter().map(|t| t.description).collect();
+        found.sort();
+        Ok(found)
+    };
+
+    let query = TaskQueryBuilderImpl::new()
+        .any_tag(vec!["work".to_string(), "home".to_string()])
+        .build()?;
+    assert_eq!(descriptions(query)?, vec!["Home", "Work"]);
+    assert_eq!(descriptions(TaskQueryBuilderImpl::new().untagged().build()?)?, vec![bare.description]);
+    assert_eq!(descriptions(TaskQueryBuilderImpl::new().min_tags(2).build()?)?, vec!["Work"]);
+
+    let query = TaskQuery {
+        tag_filter: Some("+work or +home -next".parse::<TagFilter>()?),
+        ..Default::default()
+    };
+    assert_eq!(descriptions(query)?, vec!["Home"]);
+
+    Ok(())
+}
+
+/// Test a customized priority scheme across validation and sorting
+#[test]
+fn test_custom_priority_scheme() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    manager.config_mut().set("uda.priority.values", "C,H,M,L,");
+
+    let high = manager.add_task("High".to_string())?;
+    manager.update_task(high.id, TaskUpdate::new().priority(Priority::High))?;
+    let critical = manager.add_task("Critical".to_string())?;
+    manager.update_task(critical.id, TaskUpdate::new().priority_code("C"))?;
+    manager.add_task("Unprioritized".to_string())?;
+
+    let bogus = manager.add_task("Bogus".to_string())?;
+    assert!(manager
+        .update_task(bogus.id, TaskUpdate::new().priority_code("X"))
+        .is_err());
+
+    let query = TaskQueryBuilderImpl::new().sort_by_priority().build()?;
+    let sorted = manager.query_tasks(&query)?;
+    assert_eq!(sorted[0].id, critical.id);
+    assert_eq!(sorted[1].id, high.id);
+
+    Ok(())
+}
+
+/// Test tag implications and usage counts from configured tags
+#[test]
+fn test_tag_registry_implications() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    manager.config_mut().set("tag.work.implies", "+office");
+    manager.config_mut().set("tag.urgent.color", "red");
+
+    let task = manager.add_task("Write report".to_string())?;
+    let updated = manager.update_task(task.id, TaskUpdate::new().add_tag("work"))?;
+    assert!(updated.has_tag("office"));
+
+    let usage = manager.tag_usage()?;
+    let counts: Vec<_> = usage.iter().map(|u| (u.name.as_str(), u.count)).collect();
+    assert_eq!(counts, vec![("office", 1), ("urgent", 0), ("work", 1)]);
+
+    Ok(())
+}
+
+/// Test running a natural-language quick filter
+#[test]
+fn test_natural_language_filter() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::query::natural;
+
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    let task = manager.add_task("Ship release".to_string())?;
+    manager.update_task(
+        task.id,
+        TaskUpdate::new().project("Work.Release").priority(Priority::High),
+    )?;
+    let other = manager.add_task("Groceries".to_string())?;
+    manager.update_task(other.id, TaskUpdate::new().priority(Priority::Low))?;
+
+    let parsed = natural::parse("high priority tasks in project Work");
+    assert!(parsed.is_complete());
+    let found = manager.query_tasks(&parsed.query)?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, task.id);
+
+    Ok(())
+}
+
+/// Test that the task manager gathers operation metrics
+#[test]
+fn test_manager_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+
+    let task = manager.add_task("Measured".to_string())?;
+    manager.complete_task(task.id)?;
+    manager.pending_tasks()?;
+    assert!(manager.sync().is_err());
+
+    let metrics = manager.metrics();
+    assert_eq!(metrics.operation("add").map(|s| s.count), Some(1));
+    assert_eq!(metrics.operation("complete").map(|s| s.count), Some(1));
+    // The completion is not counted again as an update
+    assert!(metrics.operation("update").is_none());
+    assert_eq!(metrics.operation("query").map(|s| s.count), Some(1));
+    assert_eq!(metrics.operation("sync").map(|s| s.errors), Some(1));
+    assert_eq!(metrics.sync.failures, 1);
+    assert_eq!(metrics.hook_failures, 0);
+
+    manager.reset_metrics();
+    assert!(manager.metrics().operations.is_empty());
+
+    Ok(())
+}
+
+/// Test retention purging on demand and after sync
+#[test]
+fn test_retention_purge() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::storage::StorageBackend;
+    use taskwarrior3lib::sync::DefaultSyncManager;
+    use taskwarrior3lib::Task;
+
+    let temp_dir = TempDir::new()?;
+    let long_ago = chrono::Utc::now() - chrono::Duration::days(200);
+    {
+        let mut storage = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+        storage.initialize()?;
+        for (description, status) in [
+            ("Old deleted", TaskStatus::Deleted),
+            ("Old completed", TaskStatus::Completed),
+            ("Still pending", TaskStatus::Pending),
+        ] {
+            let mut task = Task::new(description.to_string());
+            task.status = status;
+            task.entry = long_ago;
+            if status != TaskStatus::Pending {
+                task.end = Some(long_ago);
+            }
+            storage.save_task(&task)?;
+        }
+    }
+
+    let mut manager = create_test_manager(&temp_dir)?
+        .with_sync(Box::new(DefaultSyncManager::with_server("http://localhost")));
+    manager.config_mut().set("purge.completed", "after 365d");
+
+    // Nothing is old enough for the completed threshold
+    assert!(manager.purge_expired(false)?.is_empty());
+
+    manager.config_mut().set("purge.on-sync", "after 180d");
+    let dry = manager.purge_expired(true)?;
+    assert!(dry.dry_run);
+    assert_eq!(dry.purged.len(), 1);
+    assert_eq!(dry.purged[0].description, "Old deleted");
+
+    let result = manager.sync()?;
+    assert_eq!(result.tasks_purged, 1);
+    let remaining = manager.query_tasks(&TaskQueryBuilderImpl::new().build()?)?;
+    assert_eq!(remaining.len(), 2);
+
+    let pending = manager.pending_tasks()?;
+    assert!(manager.purge_task(pending[0].id).is_err());
+
+    Ok(())
+}
+
+
+#[test]
+fn test_import_merge_strategies() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::io::import::{ImportConfig, MergeAction, MergeStrategy, RecordOutcome};
+
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    let task = manager.add_task("Existing task".to_string())?;
+
+    let json = format!(
+        r#"[{{"uuid":"{}","description":"Imported copy","status":"pending","entry":"2020-01-01T00:00:00Z","modified":"2020-01-01T00:00:00Z","tags":["imported"]}}]"#,
+        task.id
+    );
+
+    // A preview of an overwrite lists the changes and saves nothing
+    let config = ImportConfig {
+        merge_strategy: Some(MergeStrategy::Overwrite),
+        ..Default::default()
+    };
+    let preview = manager.preview_import(&mut json.as_bytes(), &config)?;
+    assert_eq!(preview.updates(), 1);
+    assert!(matches!(&preview.records[0], RecordOutcome::WouldUpdate { changes, .. }
+        if changes.iter().any(|c| c.field == "description" && c.after == "Imported copy")));
+    assert_eq!(manager.get_task(task.id)?.unwrap().description, "Existing task");
+
+    // The stored task is newer, so KeepNewest leaves it alone
+    let config = ImportConfig {
+        merge_strategy: Some(MergeStrategy::KeepNewest),
+        ..Default::default()
+    };
+    let result = manager.import_tasks(&mut json.as_bytes(), &config)?;
+    assert_eq!(result.decisions[0].action, MergeAction::KeptExisting);
+    assert_eq!(manager.get_task(task.id)?.unwrap().description, "Existing task");
+
+    // Field merge keeps the newer description but picks up the tag
+    let config = ImportConfig {
+        merge_duplicates: true,
+        ..Default::default()
+    };
+    let result = manager.import_tasks(&mut json.as_bytes(), &config)?;
+    assert_eq!(result.updated_count, 1);
+    let merged = manager.get_task(task.id)?.unwrap();
+    assert_eq!(merged.description, "Existing task");
+    assert!(merged.has_tag("imported"));
+
+    Ok(())
+}
+
+#[test]
+fn test_storage_verify_detects_corruption() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::storage::{IntegrityIssue, StorageBackend, TaskChampionStorageBackend};
+
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    manager.add_task("Checked task".to_string())?;
+
+    let storage = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+    let report = storage.verify()?;
+    assert!(report.is_ok(), "unexpected issues: {:?}", report.issues);
+    assert_eq!(report.tasks_checked, 1);
+
+    // Flip a character in the description without touching the manifest
+    let tasks_file = temp_dir.path().join("tasks.json");
+    let content = std::fs::read_to_string(&tasks_file)?;
+    std::fs::write(&tasks_file, content.replace("Checked task", "Checked tasc"))?;
+    let report = storage.verify()?;
+    assert!(matches!(report.issues[..], [IntegrityIssue::ChecksumMismatch { .. }]));
+
+    // TaskChampion databases are checked with PRAGMA integrity_check and by
+    // parsing every row
+    let db_path = temp_dir.path().join("taskchampion.sqlite3");
+    let conn = rusqlite::Connection::open(&db_path)?;
+    conn.execute_batch(
+        "CREATE TABLE tasks (uuid TEXT PRIMARY KEY, data TEXT);
+         INSERT INTO tasks VALUES ('6f1b0c3e-3f5c-4b8e-9a57-0d7c2f1e8a11', '{\"description\":\"ok\",\"status\":\"pending\"}');
+         INSERT INTO tasks VALUES ('8c2d4e6f-1a3b-4c5d-8e7f-9a0b1c2d3e4f', 'not json');",
+    )?;
+    let report = TaskChampionStorageBackend::new(&db_path).verify()?;
+    assert_eq!(report.tasks_checked, 2);
+    assert!(matches!(
+        report.issues[..],
+        [IntegrityIssue::UnreadableTask { id: Some(_), .. }]
+    ));
+
+    Ok(())
+}
+
+/// Removing annotations by text, as `task denotate` does
+#[test]
+fn test_denotate() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+
+    let task = manager.add_task("Call the plumber".to_string())?;
+    let mut annotations = Vec::new();
+    for text in ["left a voicemail", "left"] {
+        annotations.push(taskwarrior3lib::task::Annotation::new(text.to_string()));
+    }
+    manager.update_task(
+        task.id,
+        TaskUpdate {
+            annotations: Some(annotations),
+            ..Default::default()
+        },
+    )?;
+
+    // Exact match wins over a substring match
+    let task = manager.denotate(task.id, "left")?;
+    let texts: Vec<_> = task.annotations.iter().map(|a| a.description.as_str()).collect();
+    assert_eq!(texts, vec!["left a voicemail"]);
+
+    assert!(manager.denotate(task.id, "plumber").is_err());
+
+    // Removing the last annotation is a real change, not an empty update
+    let task = manager.denotate(task.id, "voicemail")?;
+    assert!(task.annotations.is_empty());
+    let stored = manager.get_task(task.id)?.unwrap();
+    assert!(stored.annotations.is_empty());
+
+    Ok(())
+}
+
+/// Tasks synced from a device whose clock ran ahead
+#[test]
+fn test_future_timestamps_flagged_and_normalized() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::error::ValidationError;
+    use taskwarrior3lib::storage::StorageBackend;
+
+    let temp_dir = TempDir::new()?;
+    let mut storage = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+    storage.initialize()?;
+    let mut task = taskwarrior3lib::Task::new("From the future".to_string());
+    task.entry = chrono::Utc::now() + chrono::Duration::days(2);
+    storage.save_task(&task)?;
+
+    let mut manager = create_test_manager(&temp_dir)?;
+    manager.add_task("On time".to_string())?;
+
+    let report = manager.validate_all()?;
+    assert_eq!(report.valid_tasks, 1);
+    assert_eq!(report.invalid_tasks, 1);
+    assert!(matches!(
+        &report.errors[..],
+        [ValidationError::FutureTimestamp { id, field, .. }] if *id == task.id && field == "entry"
+    ));
+
+    assert_eq!(manager.normalize_future_timestamps()?, vec![task.id]);
+    assert!(manager.validate_all()?.errors.is_empty());
+    assert!(manager.get_task(task.id)?.unwrap().entry <= chrono::Utc::now());
+
+    Ok(())
+}
+
+/// Waiting tasks show up as pending once their wait date passes
+#[test]
+fn test_waiting_tasks_promoted() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::storage::StorageBackend;
+
+    let temp_dir = TempDir::new()?;
+    let mut storage = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+    storage.initialize()?;
+    let now = chrono::Utc::now();
+    let mut seed = |description: &str, status, wait| -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
+        let mut task = taskwarrior3lib::Task::new(description.to_string());
+        task.status = status;
+        task.wait = Some(wait);
+        storage.save_task(&task)?;
+        Ok(task.id)
+    };
+    let elapsed = seed("Elapsed", TaskStatus::Waiting, now - chrono::Duration::hours(1))?;
+    let hidden = seed("Hidden", TaskStatus::Waiting, now + chrono::Duration::days(1))?;
+    seed("Deferred", TaskStatus::Pending, now + chrono::Duration::days(1))?;
+
+    let mut manager = create_test_manager(&temp_dir)?;
+    let pending: Vec<_> = manager.pending_tasks()?.into_iter().map(|t| t.id).collect();
+    assert_eq!(pending, vec![elapsed]);
+    assert_eq!(manager.get_task(elapsed)?.unwrap().status, TaskStatus::Pending);
+    assert_eq!(manager.get_task(hidden)?.unwrap().status, TaskStatus::Waiting);
+
+    let reopened = FileStorageBackend::with_path(temp_dir.path().to_path_buf());
+    assert_eq!(reopened.load_task(elapsed)?.unwrap().status, TaskStatus::Pending);
+    assert!(manager.promote_waiting()?.is_empty());
+
+    Ok(())
+}
+
+/// Reporting filters on creation, completion and modification dates
+#[test]
+fn test_reporting_date_filters() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    let done = manager.add_task("Done".to_string())?;
+    manager.add_task("Open".to_string())?;
+    manager.complete_task(done.id)?;
+
+    let now = chrono::Utc::now();
+    let query = TaskQueryBuilderImpl::new()
+        .completed_between(now - chrono::Duration::hours(1), now + chrono::Duration::hours(1))
+        .build()?;
+    let found = manager.query_tasks(&query)?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, done.id);
+
+    let query = TaskQueryBuilderImpl::new()
+        .completed_between(now - chrono::Duration::days(2), now - chrono::Duration::days(1))
+        .build()?;
+    assert!(manager.query_tasks(&query)?.is_empty());
+
+    let query = TaskQueryBuilderImpl::new().created_within(chrono::Duration::days(1)).build()?;
+    assert_eq!(manager.query_tasks(&query)?.len(), 2);
+    let query = TaskQueryBuilderImpl::new().modified_since(now + chrono::Duration::hours(1)).build()?;
+    assert!(manager.query_tasks(&query)?.is_empty());
+
+    Ok(())
+}
+
+/// Updates made against a stale read are rejected
+#[test]
+fn test_update_if_match() -> Result<(), Box<dyn std::error::Error>> {
+    use taskwarrior3lib::error::TaskError;
+
+    let temp_dir = TempDir::new()?;
+    let mut manager = create_test_manager(&temp_dir)?;
+    let task = manager.add_task("Shared".to_string())?;
+    let read = manager.get_task(task.id)?.unwrap().etag();
+
+    let first = manager.update_task(task.id, TaskUpdate::new().project("Home").if_match(read.clone()))?;
+    assert_ne!(first.etag(), read);
+
+    let stale = manager.update_task(task.id, TaskUpdate::new().project("Work").if_match(read));
+    assert!(matches!(stale, Err(TaskError::Conflict { id, .. }) if id == task.id));
+    assert_eq!(manager.get_task(task.id)?.unwrap().project.as_deref(), Some("Home"));
+
+    // Back-to-back updates still get distinct versions
+    let second = manager.update_task(task.id, TaskUpdate::new().project("Work").if_match(first.etag()))?;
+    let third = manager.update_task(task.id, TaskUpdate::new().project("Home"))?;
+    assert_ne!(second.etag(), third.etag());
+
+    Ok(())
+}